@@ -0,0 +1,399 @@
+//! Instruction builders and a small devnet client shared by the examples.
+//! Account orders follow the docs on `ProgramInstruction`.
+
+use solana_client::rpc_client::RpcClient;
+use solana_program::hash::Hash;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{system_instruction, system_program, sysvar};
+use solana_sdk::account::from_account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer as _};
+use solana_sdk::transaction::Transaction;
+use std::error::Error;
+use std::time::Duration;
+use strike_wallet::client::create_op_account;
+use strike_wallet::instruction::{
+    AddressBookUpdate, BalanceAccountCreation, InitialWalletConfig, ProgramInstruction,
+};
+use strike_wallet::model::balance_account::BalanceAccountGuidHash;
+use strike_wallet::model::multisig_op::{
+    ApprovalDisposition, DenialReason, MultisigOp, OperationDisposition,
+};
+use strike_wallet::model::wallet::Wallet;
+
+pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+pub const DEVNET_URL: &str = "https://api.devnet.solana.com";
+
+pub fn init_wallet(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    assistant_account: &Pubkey,
+    initial_config: InitialWalletConfig,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(*assistant_account, true),
+        ],
+        data: ProgramInstruction::InitWallet { initial_config }.pack(),
+    }
+}
+
+/// Starts a config op (balance account creation, address book update and the
+/// like), all of which take the same accounts.
+pub fn init_config_op(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    instruction: ProgramInstruction,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new_readonly(*wallet_account, false),
+            AccountMeta::new_readonly(*initiator_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: instruction.pack(),
+    }
+}
+
+/// Finalizes a config op started with `init_config_op`.
+pub fn finalize_config_op(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    instruction: ProgramInstruction,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(*rent_collector_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: instruction.pack(),
+    }
+}
+
+pub fn init_balance_account_creation(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    creation_params: BalanceAccountCreation,
+) -> Instruction {
+    init_config_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitBalanceAccountCreation {
+            account_guid_hash,
+            creation_params,
+        },
+    )
+}
+
+pub fn finalize_balance_account_creation(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    creation_params: BalanceAccountCreation,
+) -> Instruction {
+    finalize_config_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        rent_collector_account,
+        ProgramInstruction::FinalizeBalanceAccountCreation {
+            account_guid_hash,
+            creation_params,
+        },
+    )
+}
+
+pub fn init_address_book_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    update: AddressBookUpdate,
+) -> Instruction {
+    init_config_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitAddressBookUpdate { update },
+    )
+}
+
+pub fn finalize_address_book_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    update: AddressBookUpdate,
+) -> Instruction {
+    finalize_config_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        rent_collector_account,
+        ProgramInstruction::FinalizeAddressBookUpdate { update },
+    )
+}
+
+/// Starts a SOL transfer out of a balance account.
+pub fn init_sol_transfer(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    fee_payer: &Pubkey,
+    instruction: ProgramInstruction,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*source_account, false),
+            AccountMeta::new_readonly(*destination_account, false),
+            AccountMeta::new_readonly(*initiator_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            // the system program stands in for the token mint of SOL transfers
+            AccountMeta::new_readonly(system_program::id(), false),
+            // SOL transfers have no destination token account, so the destination stands in
+            AccountMeta::new(*destination_account, false),
+            AccountMeta::new(*fee_payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        ],
+        data: instruction.pack(),
+    }
+}
+
+pub fn finalize_sol_transfer(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*source_account, false),
+            AccountMeta::new(*destination_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(*rent_collector_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeTransfer {
+            account_guid_hash,
+            amount,
+            token_mint: system_program::id(),
+            memo_hash: None,
+            fee_reimbursement: 0,
+            ata_rent_payer: None,
+            token_program: None,
+        }
+        .pack(),
+    }
+}
+
+pub fn set_approval_disposition(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    approver: &Pubkey,
+    disposition: ApprovalDisposition,
+    params_hash: Hash,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new_readonly(*approver, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::SetApprovalDisposition {
+            disposition,
+            params_hash,
+            denial_reason: DenialReason::None,
+            reason_hash: None,
+        }
+        .pack(),
+    }
+}
+
+/// Sends the examples' transactions to a cluster, paid for by the keypair
+/// the Solana CLI is configured with.
+pub struct DevnetClient {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+    pub program_id: Pubkey,
+    pub rent: Rent,
+}
+
+impl DevnetClient {
+    /// Takes the program id of a deployed wallet program and, optionally, the
+    /// cluster URL from the command line. The payer keypair is read from
+    /// `STRIKE_WALLET_PAYER` or else from the Solana CLI's default path.
+    pub fn from_args() -> Result<DevnetClient> {
+        let mut args = std::env::args().skip(1);
+        let program_id = args
+            .next()
+            .ok_or("usage: <wallet program id> [cluster url]")?
+            .parse::<Pubkey>()?;
+        let url = args.next().unwrap_or_else(|| DEVNET_URL.to_string());
+        let payer_path = std::env::var("STRIKE_WALLET_PAYER").unwrap_or_else(|_| {
+            format!(
+                "{}/.config/solana/id.json",
+                std::env::var("HOME").unwrap_or_default()
+            )
+        });
+        let payer = read_keypair_file(&payer_path)
+            .map_err(|err| format!("failed to read payer keypair {}: {}", payer_path, err))?;
+        let rpc = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+        let rent = from_account::<Rent, _>(&rpc.get_account(&sysvar::rent::id())?)
+            .ok_or("failed to read the rent sysvar")?;
+        Ok(DevnetClient {
+            rpc,
+            payer,
+            program_id,
+            rent,
+        })
+    }
+
+    /// Airdrops SOL to the payer if it has less than `min_sol`.
+    pub fn fund_payer(&self, min_sol: u64) -> Result<()> {
+        if self.rpc.get_balance(&self.payer.pubkey())? >= min_sol * LAMPORTS_PER_SOL {
+            return Ok(());
+        }
+        let signature = self
+            .rpc
+            .request_airdrop(&self.payer.pubkey(), min_sol * LAMPORTS_PER_SOL)?;
+        while !self.rpc.confirm_transaction(&signature)? {
+            std::thread::sleep(Duration::from_millis(500));
+        }
+        Ok(())
+    }
+
+    /// Sends the instructions in one transaction paid for by the payer, which
+    /// signs in addition to the given signers.
+    pub fn send(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Signature> {
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let (recent_blockhash, _) = self.rpc.get_recent_blockhash()?;
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            recent_blockhash,
+        );
+        Ok(self.rpc.send_and_confirm_transaction(&transaction)?)
+    }
+
+    /// Creates and initializes a wallet account.
+    pub fn create_wallet(
+        &self,
+        wallet_account: &Keypair,
+        assistant_account: &Keypair,
+        initial_config: InitialWalletConfig,
+    ) -> Result<Signature> {
+        self.send(
+            &[
+                system_instruction::create_account(
+                    &self.payer.pubkey(),
+                    &wallet_account.pubkey(),
+                    self.rent.minimum_balance(Wallet::LEN),
+                    Wallet::LEN as u64,
+                    &self.program_id,
+                ),
+                init_wallet(
+                    &self.program_id,
+                    &wallet_account.pubkey(),
+                    &assistant_account.pubkey(),
+                    initial_config,
+                ),
+            ],
+            &[wallet_account, assistant_account],
+        )
+    }
+
+    /// Creates an op account and starts an op in it with the instruction
+    /// `build` returns for the op account's address. Returns that address.
+    pub fn start_op(
+        &self,
+        initiator: &Keypair,
+        build: impl FnOnce(&Pubkey) -> Instruction,
+    ) -> Result<Pubkey> {
+        let multisig_op_account = Keypair::new();
+        let instruction = build(&multisig_op_account.pubkey());
+        let create_account = create_op_account(
+            &self.program_id,
+            &self.payer.pubkey(),
+            &multisig_op_account.pubkey(),
+            &self.rent,
+            &ProgramInstruction::unpack(&instruction.data)?,
+        )
+        .ok_or("instruction does not start an op")?;
+        self.send(
+            &[create_account, instruction],
+            &[&multisig_op_account, initiator],
+        )?;
+        Ok(multisig_op_account.pubkey())
+    }
+
+    pub fn get_op(&self, multisig_op_account: &Pubkey) -> Result<MultisigOp> {
+        Ok(MultisigOp::unpack_from_slice(
+            &self.rpc.get_account_data(multisig_op_account)?,
+        )?)
+    }
+
+    /// Approves an op with the params hash it was started with. Real clients
+    /// should compute the hash from the params they expect instead, so that
+    /// approvers only ever approve what they were shown.
+    pub fn approve(&self, multisig_op_account: &Pubkey, approver: &Keypair) -> Result<()> {
+        let op = self.get_op(multisig_op_account)?;
+        self.send(
+            &[set_approval_disposition(
+                &self.program_id,
+                multisig_op_account,
+                &approver.pubkey(),
+                ApprovalDisposition::APPROVE,
+                op.params_hash,
+            )],
+            &[approver],
+        )?;
+        if self.get_op(multisig_op_account)?.operation_disposition != OperationDisposition::APPROVED
+        {
+            return Err("op is not approved yet".into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+//! Onboards a new wallet on devnet: creates the wallet, adds a balance
+//! account with a whitelist, whitelists a destination, funds the balance
+//! account and transfers SOL out of it. Every op is approved by the wallet's
+//! single approver.
+//!
+//! Run against a deployed wallet program with
+//!
+//! ```bash
+//! $ cargo run --example devnet_onboarding -- <wallet program id> [cluster url]
+//! ```
+
+mod common;
+
+use common::{DevnetClient, Result};
+use solana_program::hash::hash;
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_sdk::signature::{Keypair, Signer as _};
+use std::time::Duration;
+use strike_wallet::instruction::{
+    AddressBookUpdate, BalanceAccountCreation, BalanceAccountWhitelistUpdate, InitialWalletConfig,
+    ProgramInstruction,
+};
+use strike_wallet::model::address_book::{AddressBookEntry, AddressBookEntryNameHash};
+use strike_wallet::model::balance_account::{BalanceAccountGuidHash, BalanceAccountNameHash};
+use strike_wallet::model::multisig_op::BooleanSetting;
+use strike_wallet::model::signer::Signer;
+use strike_wallet::utils::SlotId;
+
+fn main() -> Result<()> {
+    let client = DevnetClient::from_args()?;
+    client.fund_payer(2)?;
+    let program_id = client.program_id;
+    let payer = client.payer.pubkey();
+
+    let wallet_account = Keypair::new();
+    let wallet = wallet_account.pubkey();
+    let assistant = Keypair::new();
+    let approver = Keypair::new();
+    let approvers = vec![(SlotId::new(0), Signer::new(approver.pubkey()))];
+
+    client.create_wallet(
+        &wallet_account,
+        &assistant,
+        InitialWalletConfig {
+            approvals_required_for_config: 1,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: approvers.clone(),
+            config_approvers: approvers.clone(),
+        },
+    )?;
+    println!("created wallet {}", wallet);
+
+    // a balance account's address is derived from its guid hash
+    let account_guid_hash = BalanceAccountGuidHash::new(&hash(wallet.as_ref()).to_bytes());
+    let (balance_account, _) =
+        Pubkey::find_program_address(&[&account_guid_hash.to_bytes()], &program_id);
+    let creation_params = BalanceAccountCreation {
+        slot_id: SlotId::new(0),
+        name_hash: BalanceAccountNameHash::new(&hash(b"Operating").to_bytes()),
+        approvals_required_for_transfer: 1,
+        approval_timeout_for_transfer: Duration::from_secs(3600),
+        transfer_approvers: approvers.clone(),
+        whitelist_enabled: BooleanSetting::On,
+        dapps_enabled: BooleanSetting::Off,
+        // the balance account is added to the address book, so other balance
+        // accounts can whitelist it
+        address_book_slot_id: SlotId::new(0),
+    };
+    let op = client.start_op(&assistant, |op| {
+        common::init_balance_account_creation(
+            &program_id,
+            &wallet,
+            op,
+            &assistant.pubkey(),
+            account_guid_hash,
+            creation_params.clone(),
+        )
+    })?;
+    client.approve(&op, &approver)?;
+    client.send(
+        &[common::finalize_balance_account_creation(
+            &program_id,
+            &wallet,
+            &op,
+            &payer,
+            account_guid_hash,
+            creation_params,
+        )],
+        &[],
+    )?;
+    println!("created balance account {}", balance_account);
+
+    let destination = AddressBookEntry {
+        address: Keypair::new().pubkey(),
+        name_hash: AddressBookEntryNameHash::new(&hash(b"Treasury").to_bytes()),
+    };
+    let update = AddressBookUpdate {
+        add_address_book_entries: vec![(SlotId::new(1), destination)],
+        remove_address_book_entries: vec![],
+        balance_account_whitelist_updates: vec![BalanceAccountWhitelistUpdate {
+            guid_hash: account_guid_hash,
+            add_allowed_destinations: vec![(SlotId::new(1), destination)],
+            remove_allowed_destinations: vec![],
+        }],
+    };
+    let op = client.start_op(&assistant, |op| {
+        common::init_address_book_update(
+            &program_id,
+            &wallet,
+            op,
+            &assistant.pubkey(),
+            update.clone(),
+        )
+    })?;
+    client.approve(&op, &approver)?;
+    client.send(
+        &[common::finalize_address_book_update(
+            &program_id,
+            &wallet,
+            &op,
+            &payer,
+            update,
+        )],
+        &[],
+    )?;
+    println!("whitelisted destination {}", destination.address);
+
+    client.send(
+        &[system_instruction::transfer(
+            &payer,
+            &balance_account,
+            LAMPORTS_PER_SOL,
+        )],
+        &[],
+    )?;
+    println!("funded balance account with 1 SOL");
+
+    let amount = LAMPORTS_PER_SOL / 10;
+    let op = client.start_op(&assistant, |op| {
+        common::init_sol_transfer(
+            &program_id,
+            &wallet,
+            op,
+            &assistant.pubkey(),
+            &balance_account,
+            &destination.address,
+            &payer,
+            ProgramInstruction::InitTransfer {
+                account_guid_hash,
+                amount,
+                destination_name_hash: destination.name_hash,
+                approval_timeout: None,
+                memo_hash: None,
+                ata_rent_payer: None,
+            },
+        )
+    })?;
+    client.approve(&op, &approver)?;
+    client.send(
+        &[common::finalize_sol_transfer(
+            &program_id,
+            &wallet,
+            &op,
+            &payer,
+            &balance_account,
+            &destination.address,
+            account_guid_hash,
+            amount,
+        )],
+        &[],
+    )?;
+    println!(
+        "transferred {} lamports to {}, which now holds {} lamports",
+        amount,
+        destination.address,
+        client.rpc.get_balance(&destination.address)?
+    );
+
+    Ok(())
+}
@@ -0,0 +1,207 @@
+//! `proptest` strategies for the program's account state and instructions,
+//! for property tests here and in crates building on this one.
+//!
+//! Values are drawn by decoding mostly-zero byte strings rather than by
+//! building them field by field, so that every arbitrary value is one the
+//! program could actually read out of an account or instruction.
+
+use crate::instruction::ProgramInstruction;
+use crate::model::account_type::AccountType;
+use crate::model::balance_account::BalanceAccount;
+use crate::model::multisig_op::MultisigOp;
+use crate::model::wallet::Wallet;
+use proptest::arbitrary::{any, Arbitrary};
+use proptest::collection::vec;
+use proptest::strategy::{BoxedStrategy, Strategy};
+use solana_program::program_pack::Pack;
+
+/// Upper bound of the instruction payloads generated, beyond any tag's needs.
+const MAX_INSTRUCTION_PAYLOAD_LEN: usize = 2048;
+
+/// Zeroed buffers of `len` bytes with a few arbitrary bytes set. These decode
+/// far more often than uniformly random bytes, since zero is a valid count,
+/// flag or setting almost everywhere. `len` must be nonzero.
+pub fn sparse_bytes(len: usize) -> impl Strategy<Value = Vec<u8>> {
+    vec((0..len, any::<u8>()), 0..16).prop_map(move |patches| {
+        let mut bytes = vec![0; len];
+        for (index, value) in patches {
+            bytes[index] = value;
+        }
+        bytes
+    })
+}
+
+/// Sparse account data of `len` bytes marked as an initialized account of the
+/// given type.
+fn account_data(account_type: AccountType, len: usize) -> impl Strategy<Value = Vec<u8>> {
+    sparse_bytes(len).prop_map(move |mut bytes| {
+        bytes[0] = account_type as u8;
+        bytes
+    })
+}
+
+impl Arbitrary for Wallet {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        account_data(AccountType::Wallet, Wallet::LEN)
+            .prop_filter_map("not a valid wallet", |bytes| {
+                Wallet::unpack_from_slice(&bytes).ok()
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for BalanceAccount {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        sparse_bytes(BalanceAccount::LEN)
+            .prop_filter_map("not a valid balance account", |bytes| {
+                BalanceAccount::unpack_from_slice(&bytes).ok()
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for MultisigOp {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        account_data(AccountType::MultisigOp, MultisigOp::LEN)
+            .prop_filter_map("not a valid multisig op", |bytes| {
+                MultisigOp::unpack_from_slice(&bytes).ok()
+            })
+            .boxed()
+    }
+}
+
+/// Instructions of every kind: the tag is drawn from the range in use and
+/// followed by a payload long enough for any of them.
+impl Arbitrary for ProgramInstruction {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (0..95u8, 1..MAX_INSTRUCTION_PAYLOAD_LEN)
+            .prop_flat_map(|(tag, payload_len)| {
+                sparse_bytes(payload_len).prop_map(move |payload| {
+                    let mut bytes = vec![tag];
+                    bytes.extend_from_slice(&payload);
+                    bytes
+                })
+            })
+            .prop_filter_map("not a valid instruction", |bytes| {
+                ProgramInstruction::unpack(&bytes).ok()
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use solana_program::program_error::ProgramError;
+
+    fn pack<T: Pack>(value: T) -> Vec<u8> {
+        let mut bytes = vec![0; T::LEN];
+        T::pack(value, &mut bytes).unwrap();
+        bytes
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn test_wallet_roundtrip(wallet in any::<Wallet>()) {
+            let bytes = pack(wallet.clone());
+            prop_assert_eq!(Wallet::unpack_unchecked(&bytes).unwrap(), wallet);
+        }
+
+        #[test]
+        fn test_balance_account_roundtrip(balance_account in any::<BalanceAccount>()) {
+            let mut bytes = vec![0; BalanceAccount::LEN];
+            balance_account.pack_into_slice(&mut bytes);
+            prop_assert_eq!(
+                BalanceAccount::unpack_from_slice(&bytes).unwrap(),
+                balance_account
+            );
+        }
+
+        #[test]
+        fn test_multisig_op_roundtrip(multisig_op in any::<MultisigOp>()) {
+            let bytes = pack(multisig_op);
+            let unpacked = MultisigOp::unpack_unchecked(&bytes).unwrap();
+            prop_assert_eq!(pack(unpacked), bytes);
+        }
+
+        #[test]
+        fn test_instruction_roundtrip(instruction in any::<ProgramInstruction>()) {
+            let bytes = instruction.pack();
+            let unpacked = ProgramInstruction::unpack(&bytes).unwrap();
+            prop_assert_eq!(unpacked.pack(), bytes);
+        }
+
+        #[test]
+        fn test_truncated_accounts_are_rejected(
+            wallet in any::<Wallet>(),
+            multisig_op in any::<MultisigOp>(),
+            keep in any::<prop::sample::Index>(),
+        ) {
+            let bytes = pack(wallet);
+            prop_assert_eq!(
+                Wallet::unpack_unchecked(&bytes[..keep.index(Wallet::LEN)]),
+                Err(ProgramError::InvalidAccountData)
+            );
+            let bytes = pack(multisig_op);
+            prop_assert!(
+                MultisigOp::unpack_unchecked(&bytes[..keep.index(MultisigOp::LEN)]).is_err()
+            );
+        }
+
+        #[test]
+        fn test_corrupted_accounts_do_not_panic(
+            wallet_bytes in sparse_bytes(Wallet::LEN),
+            multisig_op_bytes in sparse_bytes(MultisigOp::LEN),
+            balance_account_bytes in sparse_bytes(BalanceAccount::LEN),
+        ) {
+            let _ = Wallet::unpack_unchecked(&wallet_bytes);
+            let _ = MultisigOp::unpack_unchecked(&multisig_op_bytes);
+            let _ = BalanceAccount::unpack_from_slice(&balance_account_bytes);
+        }
+
+        #[test]
+        fn test_truncated_instructions_do_not_panic(
+            instruction in any::<ProgramInstruction>(),
+            keep in any::<prop::sample::Index>(),
+        ) {
+            // older clients leave trailing fields out, so this may still unpack
+            let bytes = instruction.pack();
+            let _ = ProgramInstruction::unpack(&bytes[..keep.index(bytes.len())]);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_arbitrary_instruction_bytes_do_not_panic(bytes in vec(any::<u8>(), 0..1024)) {
+            let _ = ProgramInstruction::unpack(&bytes);
+        }
+
+        #[test]
+        fn test_corrupted_instructions_do_not_panic(
+            instruction in any::<ProgramInstruction>(),
+            corruptions in vec((any::<prop::sample::Index>(), any::<u8>()), 1..8),
+        ) {
+            let mut bytes = instruction.pack();
+            for (index, value) in corruptions {
+                let index = index.index(bytes.len());
+                bytes[index] = value;
+            }
+            let _ = ProgramInstruction::unpack(&bytes);
+        }
+    }
+}
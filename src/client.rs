@@ -0,0 +1,679 @@
+//! Helpers for clients building the transactions that start ops, so that op
+//! account creation parameters are derived from the instruction itself, for
+//! working out what accounts and ops cost, for submitting dispositions in
+//! parallel, for executing dapp templates, and for the program's read-only
+//! instructions.
+
+use crate::instruction::builders::with_program_config;
+use crate::instruction::{NamedEntry, ProgramInstruction};
+use crate::model::activity::ActivityCheckpoint;
+use crate::model::address_book_page::AddressBookPage;
+use crate::model::address_book_update_buffer::AddressBookUpdateBuffer;
+use crate::model::dapp_template::DAppTemplate;
+use crate::model::multisig_op::{
+    ApprovalDisposition, DenialReason, MultisigOp, MultisigOpParams, ParamsHashVersion,
+};
+use crate::model::pending_disposition::PendingDisposition;
+use crate::model::pending_ops::PendingOps;
+use crate::model::program_config::ProgramConfig;
+use crate::model::signer_directory::SignerDirectory;
+use crate::model::wallet::Wallet;
+use crate::model::wallet_export::WalletExport;
+use crate::utils::SlotId;
+use solana_program::hash::Hash;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{system_instruction, system_program};
+
+/// The rent-exempt balance of the op account the given instruction starts an
+/// op in, or `None` if the instruction doesn't start an op.
+pub fn rent_for_op(rent: &Rent, instruction: &ProgramInstruction) -> Option<u64> {
+    MultisigOp::space_required(instruction).map(|space| rent.minimum_balance(space))
+}
+
+/// The fee the cluster charges per transaction signature, unless it has been
+/// configured otherwise.
+pub const DEFAULT_LAMPORTS_PER_SIGNATURE: u64 = 5000;
+
+/// The accounts the program owns, for working out how much space and rent
+/// they take before creating them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramAccount {
+    Wallet,
+    MultisigOp,
+    PendingDisposition,
+    PendingOps,
+    SignerDirectory,
+    AddressBookPage,
+    ActivityCheckpoint,
+    ProgramConfig,
+    WalletExport,
+    AddressBookUpdateBuffer { update_len: u32 },
+}
+
+impl ProgramAccount {
+    pub fn space(&self) -> usize {
+        match self {
+            ProgramAccount::Wallet => Wallet::LEN,
+            ProgramAccount::MultisigOp => MultisigOp::LEN,
+            ProgramAccount::PendingDisposition => PendingDisposition::LEN,
+            ProgramAccount::PendingOps => PendingOps::LEN,
+            ProgramAccount::SignerDirectory => SignerDirectory::LEN,
+            ProgramAccount::AddressBookPage => AddressBookPage::LEN,
+            ProgramAccount::ActivityCheckpoint => ActivityCheckpoint::LEN,
+            ProgramAccount::ProgramConfig => ProgramConfig::LEN,
+            ProgramAccount::WalletExport => WalletExport::LEN,
+            ProgramAccount::AddressBookUpdateBuffer { update_len } => {
+                AddressBookUpdateBuffer::space_required(*update_len as usize)
+            }
+        }
+    }
+
+    /// The balance that makes the account rent-exempt.
+    pub fn rent(&self, rent: &Rent) -> u64 {
+        rent.minimum_balance(self.space())
+    }
+}
+
+/// What an op costs in lamports from start to finish, so that payers can be
+/// funded up front. The op account's rent is paid when the op is started
+/// and returned to the rent collector when it is finalized; the fees are
+/// spent for good.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpCostEstimate {
+    pub op_account_rent: u64,
+    /// Signed by the payer and by the op account created alongside the op.
+    pub init_fee: u64,
+    /// One signature for each approval the op needs.
+    pub approval_fees: u64,
+    /// Signed by the rent collector alone.
+    pub finalize_fee: u64,
+}
+
+impl OpCostEstimate {
+    pub fn fees(&self) -> u64 {
+        self.init_fee
+            .saturating_add(self.approval_fees)
+            .saturating_add(self.finalize_fee)
+    }
+
+    /// The most lamports held at any point in the op's life.
+    pub fn total(&self) -> u64 {
+        self.op_account_rent.saturating_add(self.fees())
+    }
+}
+
+/// Estimates what an op started by the given instruction costs once it has
+/// gathered `approvals_required` approvals, each given in a transaction of
+/// its own, and been finalized. Returns `None` if the instruction doesn't
+/// start an op.
+pub fn estimate_op_cost(
+    rent: &Rent,
+    lamports_per_signature: u64,
+    instruction: &ProgramInstruction,
+    approvals_required: u8,
+) -> Option<OpCostEstimate> {
+    Some(OpCostEstimate {
+        op_account_rent: rent_for_op(rent, instruction)?,
+        init_fee: lamports_per_signature.saturating_mul(2),
+        approval_fees: lamports_per_signature.saturating_mul(u64::from(approvals_required)),
+        finalize_fee: lamports_per_signature,
+    })
+}
+
+/// Creates the op account the given instruction starts an op in, funded by
+/// the payer. Returns `None` if the instruction doesn't start an op.
+pub fn create_op_account(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent: &Rent,
+    instruction: &ProgramInstruction,
+) -> Option<Instruction> {
+    let space = MultisigOp::space_required(instruction)?;
+    Some(system_instruction::create_account(
+        payer,
+        multisig_op_account,
+        rent.minimum_balance(space),
+        space as u64,
+        program_id,
+    ))
+}
+
+/// Creates the op account for the op with the given params at the address
+/// derived from them, funded by the payer. Returns the address along with
+/// the instruction; the op is then started in it as usual. The params hash
+/// version has to be the one the wallet's governed parameters ask for.
+pub fn create_op_account_for_params(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    params: &MultisigOpParams,
+    params_hash_version: ParamsHashVersion,
+    nonce: u64,
+) -> (Pubkey, Instruction) {
+    let params_hash = params.hash_with(params_hash_version);
+    let (multisig_op_account, _) =
+        MultisigOp::find_address(params.wallet_address(), &params_hash, nonce, program_id);
+    (
+        multisig_op_account,
+        Instruction {
+            program_id: *program_id,
+            accounts: with_program_config(
+                program_id,
+                vec![
+                    AccountMeta::new(multisig_op_account, false),
+                    AccountMeta::new_readonly(*params.wallet_address(), false),
+                    AccountMeta::new(*payer, true),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                ],
+            ),
+            data: ProgramInstruction::CreateOpAccountForParams { params_hash, nonce }.pack(),
+        },
+    )
+}
+
+/// Creates the buffer account a chunked address book update of `update_len`
+/// bytes is streamed into, funded by the payer.
+pub fn create_address_book_update_buffer_account(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    buffer_account: &Pubkey,
+    rent: &Rent,
+    update_len: u32,
+) -> Instruction {
+    let space = AddressBookUpdateBuffer::space_required(update_len as usize);
+    system_instruction::create_account(
+        payer,
+        buffer_account,
+        rent.minimum_balance(space),
+        space as u64,
+        program_id,
+    )
+}
+
+/// Submits the approver's disposition on the op to its pending disposition
+/// account, without write-locking the op account.
+pub fn submit_approval_disposition(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    approver: &Pubkey,
+    disposition: ApprovalDisposition,
+    params_hash: Hash,
+    denial_reason: DenialReason,
+    reason_hash: Option<Hash>,
+) -> Instruction {
+    let (pending_disposition_account, _) =
+        PendingDisposition::find_address(multisig_op_account, approver, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![
+                AccountMeta::new_readonly(*multisig_op_account, false),
+                AccountMeta::new(pending_disposition_account, false),
+                AccountMeta::new(*approver, true),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        ),
+        data: ProgramInstruction::SubmitApprovalDisposition {
+            disposition,
+            params_hash,
+            denial_reason,
+            reason_hash,
+        }
+        .pack(),
+    }
+}
+
+/// Applies the dispositions the given approvers submitted on the op.
+pub fn collect_approval_dispositions(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    approvers: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+    ];
+    for approver in approvers {
+        let (pending_disposition_account, _) =
+            PendingDisposition::find_address(multisig_op_account, approver, program_id);
+        accounts.push(AccountMeta::new(pending_disposition_account, false));
+        accounts.push(AccountMeta::new(*approver, false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data: ProgramInstruction::CollectApprovalDispositions {
+            dispositions: approvers.len() as u8,
+        }
+        .pack(),
+    }
+}
+
+/// Sends an instance of the template in the given slot from its balance
+/// account, passing along the accounts the instance needs.
+pub fn execute_dapp_template(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    balance_account: &Pubkey,
+    assistant_account: &Pubkey,
+    template_slot: SlotId<DAppTemplate>,
+    instruction: Instruction,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*balance_account, false),
+        AccountMeta::new_readonly(*assistant_account, true),
+    ];
+    // the balance account signs through the program, not the transaction
+    accounts.extend(instruction.accounts.iter().map(|account| AccountMeta {
+        pubkey: account.pubkey,
+        is_signer: false,
+        is_writable: account.is_writable,
+    }));
+    accounts.push(AccountMeta::new_readonly(instruction.program_id, false));
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data: ProgramInstruction::ExecuteDAppTemplate {
+            template_slot,
+            instruction,
+        }
+        .pack(),
+    }
+}
+
+/// Verifies that the name hashes to the entry's stored name hash, logging the
+/// name on success so that indexers can label the entry's account.
+pub fn verify_name_hash(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    entry: NamedEntry,
+    name: &str,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![AccountMeta::new_readonly(*wallet_account, false)],
+        data: ProgramInstruction::VerifyNameHash {
+            entry,
+            name: name.to_string(),
+        }
+        .pack(),
+    }
+}
+
+#[test]
+fn test_every_op_is_sized_from_its_instruction() {
+    use crate::instruction::{
+        AddressBookUpdate, ApproverDelegationUpdate, BalanceAccountCreation,
+        BalanceAccountPolicyUpdate, CombinedTransfer, DAppBookUpdate, DAppTemplatesUpdate,
+        GovernedParameterUpdate, InitialWalletConfig, SignerOrganizationsUpdate, SignersUpdate,
+        ViewersUpdate, WalletConfigPolicyUpdate, WalletSignerDirectoryUpdate,
+    };
+    use crate::model::address_book::{
+        AddressBookEntryNameHash, DAppBookEntry, DAppInstructionPrefixes,
+    };
+    use crate::model::balance_account::{BalanceAccountGuidHash, BalanceAccountNameHash};
+    use crate::model::idempotency::IdempotencyKey;
+    use crate::model::multisig_op::{AmountSpec, BooleanSetting, SlotUpdateType, WrapDirection};
+    use crate::model::signer::Signer;
+    use solana_program::program_pack::Pack;
+    use std::time::Duration;
+
+    let account_guid_hash = BalanceAccountGuidHash::zero();
+    let signer = Signer::new(Pubkey::new_unique());
+    let address_book_update = AddressBookUpdate {
+        add_address_book_entries: vec![],
+        remove_address_book_entries: vec![],
+        balance_account_whitelist_updates: vec![],
+        add_destination_token_mints: vec![],
+        remove_destination_token_mints: vec![],
+        add_destination_namespaces: vec![],
+        remove_destination_namespaces: vec![],
+    };
+    let op_instructions = vec![
+        ProgramInstruction::InitBalanceAccountCreation {
+            account_guid_hash,
+            creation_params: BalanceAccountCreation {
+                slot_id: SlotId::new(0),
+                name_hash: BalanceAccountNameHash::zero(),
+                approvals_required_for_transfer: 1,
+                approval_timeout_for_transfer: Duration::from_secs(3600),
+                transfer_approvers: vec![(SlotId::new(0), signer)],
+                whitelist_enabled: BooleanSetting::Off,
+                dapps_enabled: BooleanSetting::Off,
+                address_book_slot_id: SlotId::new(0),
+            },
+        },
+        ProgramInstruction::InitTransfer {
+            account_guid_hash,
+            amount: 1,
+            destination_name_hash: AddressBookEntryNameHash::zero(),
+            approval_timeout: None,
+            memo_hash: None,
+            ata_rent_payer: None,
+            amount_spec: AmountSpec::Exact,
+            travel_rule_hash: None,
+        },
+        ProgramInstruction::InitWrapUnwrap {
+            account_guid_hash,
+            amount: 1,
+            direction: WrapDirection::WRAP,
+            approval_timeout: None,
+            destination: None,
+            travel_rule_hash: None,
+        },
+        ProgramInstruction::InitUpdateSigner {
+            slot_update_type: SlotUpdateType::SetIfEmpty,
+            slot_id: SlotId::new(0),
+            signer,
+        },
+        ProgramInstruction::InitWalletConfigPolicyUpdate {
+            update: WalletConfigPolicyUpdate {
+                approvals_required_for_config: None,
+                approval_timeout_for_config: None,
+                add_config_approvers: vec![],
+                remove_config_approvers: vec![],
+                replace_config_approvers: None,
+                destination_verification_delay: None,
+                denial_reason_threshold: None,
+                approvals_required_for_assistant_config: None,
+                approvals_required_for_address_book: None,
+                add_address_book_approvers: vec![],
+                remove_address_book_approvers: vec![],
+                add_default_transfer_approvers: vec![],
+                remove_default_transfer_approvers: vec![],
+                add_veto_approvers: vec![],
+                remove_veto_approvers: vec![],
+                approvals_required_for_destructive_config: None,
+                add_destructive_config_approvers: vec![],
+                remove_destructive_config_approvers: vec![],
+            },
+        },
+        ProgramInstruction::InitDAppTransaction {
+            account_guid_hash,
+            instructions: vec![],
+            dapp: DAppBookEntry {
+                address: Pubkey::new_unique(),
+                name_hash: AddressBookEntryNameHash::zero(),
+                instruction_prefixes: DAppInstructionPrefixes::default(),
+            },
+            rent_funding_limit: 0,
+        },
+        ProgramInstruction::InitAccountSettingsUpdate {
+            account_guid_hash,
+            whitelist_enabled: None,
+            dapps_enabled: None,
+            sol_transfers_enabled: None,
+            spl_transfers_enabled: None,
+        },
+        ProgramInstruction::InitDAppBookUpdate {
+            update: DAppBookUpdate {
+                add_dapps: vec![],
+                remove_dapps: vec![],
+                add_value_limits: vec![],
+                remove_value_limits: vec![],
+                add_inner_programs: vec![],
+                remove_inner_programs: vec![],
+                add_approval_policies: vec![],
+                remove_approval_policies: vec![],
+            },
+        },
+        ProgramInstruction::InitAddressBookUpdate {
+            update: address_book_update.clone(),
+        },
+        ProgramInstruction::InitBalanceAccountNameUpdate {
+            account_guid_hash,
+            account_name_hash: BalanceAccountNameHash::zero(),
+        },
+        ProgramInstruction::InitBalanceAccountPolicyUpdate {
+            account_guid_hash,
+            update: BalanceAccountPolicyUpdate {
+                approvals_required_for_transfer: None,
+                approval_timeout_for_transfer: None,
+                add_transfer_approvers: None,
+                remove_transfer_approvers: None,
+                use_config_approvers: None,
+                token_mint_allowlist_enabled: None,
+                add_allowed_token_mints: None,
+                remove_allowed_token_mints: None,
+                max_outstanding_transfer_value: None,
+                max_finalize_fee_reimbursement: None,
+                policy_rules: None,
+                large_transfer_cooldown: None,
+            },
+        },
+        ProgramInstruction::InitAddressBookVerification { entries: vec![] },
+        ProgramInstruction::InitActivityCheckpoint {
+            checkpoint_index: 0,
+        },
+        ProgramInstruction::InitAddressBookPageUpdate {
+            page_index: 0,
+            update: address_book_update,
+        },
+        ProgramInstruction::InitWalletRepair,
+        ProgramInstruction::InitApproverDelegationUpdate {
+            update: ApproverDelegationUpdate {
+                add_delegations: vec![],
+                remove_delegations: vec![],
+            },
+        },
+        ProgramInstruction::InitWalletSignerDirectoryUpdate {
+            update: WalletSignerDirectoryUpdate {
+                signer_directory: None,
+                directory_signers: vec![],
+            },
+        },
+        ProgramInstruction::InitWalletCanaryUpdate { canary_key: None },
+        ProgramInstruction::InitInternalTransfer {
+            source_account_guid_hash: account_guid_hash,
+            destination_account_guid_hash: BalanceAccountGuidHash::new(&[1; 32]),
+            amount: 1,
+            approval_timeout: None,
+        },
+        ProgramInstruction::InitGovernedParameterUpdate {
+            update: GovernedParameterUpdate { values: vec![] },
+        },
+        ProgramInstruction::InitUpdateSigners {
+            update: SignersUpdate {
+                add_signers: vec![(SlotId::new(0), signer)],
+                remove_signers: vec![],
+            },
+        },
+        ProgramInstruction::InitViewersUpdate {
+            update: ViewersUpdate {
+                add_viewers: vec![],
+                remove_viewers: vec![],
+            },
+        },
+        ProgramInstruction::InitSignerOrganizationsUpdate {
+            update: SignerOrganizationsUpdate {
+                organizations: vec![],
+            },
+        },
+        ProgramInstruction::InitAssistantUpdate {
+            assistant: Pubkey::new_unique(),
+        },
+        ProgramInstruction::InitAddressBookUpdateChunked {
+            update_hash: Hash::new_unique(),
+            update_len: 1024,
+        },
+        ProgramInstruction::InitWalletStateExport {
+            target_program_id: Pubkey::new_unique(),
+        },
+        ProgramInstruction::InitCombinedTransfer {
+            account_guid_hash: BalanceAccountGuidHash::zero(),
+            destination_name_hash: AddressBookEntryNameHash::zero(),
+            transfer: CombinedTransfer {
+                lamports: 1,
+                token_amounts: vec![(Pubkey::new_unique(), 2)],
+                travel_rule_hash: None,
+            },
+        },
+        ProgramInstruction::InitWalletMetadataUpdate {
+            metadata_hash: Hash::new_unique(),
+        },
+        ProgramInstruction::InitDAppTemplatesUpdate {
+            update: DAppTemplatesUpdate {
+                add_templates: vec![],
+                remove_templates: vec![],
+            },
+        },
+        ProgramInstruction::InitWalletClose {
+            rent_collector: Pubkey::new_unique(),
+        },
+    ];
+
+    let rent = Rent::default();
+    let program_id = Pubkey::new_unique();
+    for instruction in op_instructions {
+        assert_eq!(
+            MultisigOp::space_required(&instruction),
+            Some(MultisigOp::LEN),
+            "{:?}",
+            instruction
+        );
+        assert_eq!(
+            rent_for_op(&rent, &instruction),
+            Some(rent.minimum_balance(MultisigOp::LEN))
+        );
+        assert!(create_op_account(
+            &program_id,
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &rent,
+            &instruction
+        )
+        .is_some());
+
+        // validate-only runs need the same op account as the real thing
+        let validate_only = ProgramInstruction::ValidateOnly {
+            instruction: Box::new(ProgramInstruction::unpack(&instruction.pack()).unwrap()),
+        };
+        assert_eq!(
+            MultisigOp::space_required(&validate_only),
+            Some(MultisigOp::LEN)
+        );
+        let with_prerequisite = ProgramInstruction::InitWithPrerequisite {
+            prerequisite: Hash::default(),
+            instruction: Box::new(instruction),
+        };
+        assert_eq!(
+            MultisigOp::space_required(&with_prerequisite),
+            Some(MultisigOp::LEN)
+        );
+        let with_idempotency_key = ProgramInstruction::InitWithIdempotencyKey {
+            idempotency_key: IdempotencyKey::new(&[1; 16]),
+            instruction: Box::new(with_prerequisite),
+        };
+        assert_eq!(
+            MultisigOp::space_required(&with_idempotency_key),
+            Some(MultisigOp::LEN)
+        );
+    }
+
+    for instruction in vec![
+        ProgramInstruction::InitWallet {
+            initial_config: InitialWalletConfig {
+                approvals_required_for_config: 1,
+                approval_timeout_for_config: Duration::from_secs(3600),
+                signers: vec![(SlotId::new(0), signer)],
+                config_approvers: vec![(SlotId::new(0), signer)],
+            },
+        },
+        ProgramInstruction::FinalizeWalletRepair,
+        ProgramInstruction::CreateOpAccount,
+        ProgramInstruction::CreateOpAccountForParams {
+            params_hash: Hash::default(),
+            nonce: 0,
+        },
+        ProgramInstruction::InitSignerDirectory,
+        ProgramInstruction::InitPendingOps,
+        ProgramInstruction::CleanupPendingOps,
+        ProgramInstruction::ImportWalletState,
+        ProgramInstruction::VerifyNameHash {
+            entry: NamedEntry::AddressBookEntry(Pubkey::new_unique()),
+            name: "Name".to_string(),
+        },
+        ProgramInstruction::ExpireOp,
+        ProgramInstruction::SubmitApprovalDisposition {
+            disposition: ApprovalDisposition::APPROVE,
+            params_hash: Hash::default(),
+            denial_reason: DenialReason::None,
+            reason_hash: None,
+        },
+        ProgramInstruction::CollectApprovalDispositions { dispositions: 2 },
+        ProgramInstruction::VerifyCoSignature {
+            disposition: ApprovalDisposition::APPROVE,
+            params_hash: Hash::default(),
+            denial_reason: DenialReason::None,
+            reason_hash: None,
+        },
+        ProgramInstruction::FinalizeWalletClose {
+            rent_collector: Pubkey::new_unique(),
+        },
+        ProgramInstruction::ExecuteDAppChunk {
+            account_guid_hash,
+            instructions: vec![],
+            dapp: DAppBookEntry {
+                address: Pubkey::new_unique(),
+                name_hash: AddressBookEntryNameHash::zero(),
+                instruction_prefixes: DAppInstructionPrefixes::default(),
+            },
+            rent_funding_limit: 0,
+            chunk_len: 1,
+        },
+        ProgramInstruction::ExecuteDAppTemplate {
+            template_slot: SlotId::new(0),
+            instruction: Instruction {
+                program_id: Pubkey::new_unique(),
+                accounts: vec![],
+                data: vec![],
+            },
+        },
+    ] {
+        assert_eq!(MultisigOp::space_required(&instruction), None);
+        assert_eq!(rent_for_op(&rent, &instruction), None);
+    }
+}
+
+#[test]
+fn test_estimate_op_cost() {
+    let rent = Rent::default();
+    let instruction = ProgramInstruction::InitActivityCheckpoint {
+        checkpoint_index: 0,
+    };
+    let estimate =
+        estimate_op_cost(&rent, DEFAULT_LAMPORTS_PER_SIGNATURE, &instruction, 3).unwrap();
+    assert_eq!(
+        estimate.op_account_rent,
+        ProgramAccount::MultisigOp.rent(&rent)
+    );
+    assert_eq!(estimate.fees(), 6 * DEFAULT_LAMPORTS_PER_SIGNATURE);
+    assert_eq!(
+        estimate.total(),
+        rent.minimum_balance(MultisigOp::LEN) + 6 * DEFAULT_LAMPORTS_PER_SIGNATURE
+    );
+    assert_eq!(
+        estimate_op_cost(
+            &rent,
+            DEFAULT_LAMPORTS_PER_SIGNATURE,
+            &ProgramInstruction::ExpireOp,
+            3
+        ),
+        None
+    );
+
+    assert_eq!(
+        ProgramAccount::Wallet.rent(&rent),
+        rent.minimum_balance(Wallet::LEN)
+    );
+    assert_eq!(
+        ProgramAccount::AddressBookUpdateBuffer { update_len: 100 }.space(),
+        AddressBookUpdateBuffer::space_required(100)
+    );
+}
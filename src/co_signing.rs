@@ -0,0 +1,490 @@
+//! Co-signing for approvers whose keys are kept on air-gapped machines. A
+//! `CoSigningPayload` describing the op and the disposition is carried to the
+//! signer as base64 text, the signer returns a `CoSignature` the same way,
+//! and anyone can then submit it with `VerifyCoSignature`, which records the
+//! disposition once the ed25519 program has checked the signature in the
+//! same transaction.
+
+use crate::error::WalletError;
+use crate::instruction::builders::with_program_config;
+use crate::instruction::ProgramInstruction;
+use crate::model::multisig_op::{ApprovalDisposition, DenialReason, MultisigOp};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::hash::{Hash, HASH_BYTES};
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{Pack, Sealed};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+use solana_program::sysvar;
+use std::convert::TryInto;
+
+// the ed25519 signature verification program, without depending on
+// solana-sdk for its id
+pub mod ed25519_program {
+    solana_program::declare_id!("Ed25519SigVerify111111111111111111111111111");
+}
+
+pub const SIGNATURE_BYTES: usize = 64;
+
+/// An approver's disposition on an op, as handed to the approver's signer
+/// along with what it needs to show about the op.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CoSigningPayload {
+    pub approver: Pubkey,
+    pub multisig_op: Pubkey,
+    /// The wallet the op belongs to, which records the approver's activity.
+    pub wallet: Pubkey,
+    pub params_hash: Hash,
+    /// Ties the signature to this run of the op, as an op account can be
+    /// reused for the same params once an earlier run is over.
+    pub started_at: i64,
+    /// The approver's disposition change count on the op when they signed,
+    /// see `ApprovalDispositionRecord::disposition_changes`. The signature
+    /// can't be replayed once the approver has changed their disposition.
+    pub disposition_changes: u16,
+    pub expires_at: i64,
+    pub op_type: u8,
+    pub initiator: Pubkey,
+    pub disposition: ApprovalDisposition,
+    pub denial_reason: DenialReason,
+    pub reason_hash: Option<Hash>,
+}
+
+impl CoSigningPayload {
+    pub const MESSAGE_DOMAIN: &'static [u8] = b"strike-wallet:co-signature:v1";
+
+    /// A payload for the approver's disposition on the op, given how many
+    /// times the approver has changed their disposition on it so far, see
+    /// `MultisigOp::disposition_record_of`.
+    pub fn new(
+        multisig_op_address: &Pubkey,
+        multisig_op: &MultisigOp,
+        approver: &Pubkey,
+        disposition_changes: u16,
+        disposition: ApprovalDisposition,
+        denial_reason: DenialReason,
+        reason_hash: Option<Hash>,
+    ) -> CoSigningPayload {
+        CoSigningPayload {
+            approver: *approver,
+            multisig_op: *multisig_op_address,
+            wallet: multisig_op.wallet_address,
+            params_hash: multisig_op.params_hash,
+            started_at: multisig_op.started_at,
+            disposition_changes,
+            expires_at: multisig_op.expires_at,
+            op_type: multisig_op.op_type,
+            initiator: multisig_op.initiator,
+            disposition,
+            denial_reason,
+            reason_hash,
+        }
+    }
+
+    /// The bytes the approver signs. The wallet, expiry, op type and
+    /// initiator are only there for the signer to show, so they are left out.
+    pub fn message(&self) -> Vec<u8> {
+        let mut message = Vec::new();
+        message.extend_from_slice(CoSigningPayload::MESSAGE_DOMAIN);
+        message.extend_from_slice(self.multisig_op.as_ref());
+        message.extend_from_slice(self.params_hash.as_ref());
+        message.extend_from_slice(&self.started_at.to_le_bytes());
+        message.extend_from_slice(&self.disposition_changes.to_le_bytes());
+        message.push(self.disposition.to_u8());
+        message.push(self.denial_reason.to_u8());
+        match self.reason_hash {
+            Some(reason_hash) => {
+                message.push(1);
+                message.extend_from_slice(reason_hash.as_ref());
+            }
+            None => message.push(0),
+        }
+        message
+    }
+
+    pub fn to_base64(&self) -> String {
+        let mut bytes = vec![0; CoSigningPayload::LEN];
+        self.pack_into_slice(&mut bytes);
+        base64::encode(bytes)
+    }
+
+    pub fn from_base64(text: &str) -> Result<CoSigningPayload, ProgramError> {
+        unpack_base64(text)
+    }
+}
+
+impl Sealed for CoSigningPayload {}
+
+impl Pack for CoSigningPayload {
+    const LEN: usize = PUBKEY_BYTES + // approver
+        PUBKEY_BYTES + // multisig_op
+        PUBKEY_BYTES + // wallet
+        HASH_BYTES + // params_hash
+        8 + // started_at
+        2 + // disposition_changes
+        8 + // expires_at
+        1 + // op_type
+        PUBKEY_BYTES + // initiator
+        1 + // disposition
+        1 + // denial_reason
+        1 + // has reason_hash
+        HASH_BYTES; // reason_hash
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, CoSigningPayload::LEN];
+        let (
+            approver_dst,
+            multisig_op_dst,
+            wallet_dst,
+            params_hash_dst,
+            started_at_dst,
+            disposition_changes_dst,
+            expires_at_dst,
+            op_type_dst,
+            initiator_dst,
+            disposition_dst,
+            denial_reason_dst,
+            has_reason_hash_dst,
+            reason_hash_dst,
+        ) = mut_array_refs![
+            dst,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            HASH_BYTES,
+            8,
+            2,
+            8,
+            1,
+            PUBKEY_BYTES,
+            1,
+            1,
+            1,
+            HASH_BYTES
+        ];
+        approver_dst.copy_from_slice(self.approver.as_ref());
+        multisig_op_dst.copy_from_slice(self.multisig_op.as_ref());
+        wallet_dst.copy_from_slice(self.wallet.as_ref());
+        params_hash_dst.copy_from_slice(self.params_hash.as_ref());
+        *started_at_dst = self.started_at.to_le_bytes();
+        *disposition_changes_dst = self.disposition_changes.to_le_bytes();
+        *expires_at_dst = self.expires_at.to_le_bytes();
+        op_type_dst[0] = self.op_type;
+        initiator_dst.copy_from_slice(self.initiator.as_ref());
+        disposition_dst[0] = self.disposition.to_u8();
+        denial_reason_dst[0] = self.denial_reason.to_u8();
+        has_reason_hash_dst[0] = self.reason_hash.is_some() as u8;
+        reason_hash_dst.copy_from_slice(self.reason_hash.unwrap_or_default().as_ref());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, CoSigningPayload::LEN];
+        let (
+            approver,
+            multisig_op,
+            wallet,
+            params_hash,
+            started_at,
+            disposition_changes,
+            expires_at,
+            op_type,
+            initiator,
+            disposition,
+            denial_reason,
+            has_reason_hash,
+            reason_hash,
+        ) = array_refs![
+            src,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            HASH_BYTES,
+            8,
+            2,
+            8,
+            1,
+            PUBKEY_BYTES,
+            1,
+            1,
+            1,
+            HASH_BYTES
+        ];
+        Ok(CoSigningPayload {
+            approver: Pubkey::new_from_array(*approver),
+            multisig_op: Pubkey::new_from_array(*multisig_op),
+            wallet: Pubkey::new_from_array(*wallet),
+            params_hash: Hash::new_from_array(*params_hash),
+            started_at: i64::from_le_bytes(*started_at),
+            disposition_changes: u16::from_le_bytes(*disposition_changes),
+            expires_at: i64::from_le_bytes(*expires_at),
+            op_type: op_type[0],
+            initiator: Pubkey::new_from_array(*initiator),
+            disposition: ApprovalDisposition::from_u8(disposition[0]),
+            denial_reason: DenialReason::from_u8(denial_reason[0]),
+            reason_hash: if has_reason_hash[0] == 1 {
+                Some(Hash::new_from_array(*reason_hash))
+            } else {
+                None
+            },
+        })
+    }
+}
+
+/// The approver's signature over a payload's message, as returned by the
+/// approver's signer.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CoSignature {
+    pub payload: CoSigningPayload,
+    pub signature: [u8; SIGNATURE_BYTES],
+}
+
+impl CoSignature {
+    pub fn to_base64(&self) -> String {
+        let mut bytes = vec![0; CoSignature::LEN];
+        self.pack_into_slice(&mut bytes);
+        base64::encode(bytes)
+    }
+
+    pub fn from_base64(text: &str) -> Result<CoSignature, ProgramError> {
+        unpack_base64(text)
+    }
+
+    /// The ed25519 program instruction checking the signature, followed by
+    /// the instruction recording the disposition. They have to be the first
+    /// instructions of their transaction, which anyone can pay for.
+    pub fn instructions(&self, program_id: &Pubkey) -> Vec<Instruction> {
+        vec![
+            ed25519_instruction(
+                &self.payload.approver,
+                &self.signature,
+                &self.payload.message(),
+                0,
+            ),
+            Instruction {
+                program_id: *program_id,
+                accounts: with_program_config(
+                    program_id,
+                    vec![
+                        AccountMeta::new(self.payload.multisig_op, false),
+                        AccountMeta::new_readonly(self.payload.approver, false),
+                        AccountMeta::new_readonly(sysvar::instructions::id(), false),
+                        AccountMeta::new(self.payload.wallet, false),
+                    ],
+                ),
+                data: ProgramInstruction::VerifyCoSignature {
+                    disposition: self.payload.disposition,
+                    params_hash: self.payload.params_hash,
+                    denial_reason: self.payload.denial_reason,
+                    reason_hash: self.payload.reason_hash,
+                }
+                .pack(),
+            },
+        ]
+    }
+}
+
+impl Sealed for CoSignature {}
+
+impl Pack for CoSignature {
+    const LEN: usize = CoSigningPayload::LEN + SIGNATURE_BYTES;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, CoSignature::LEN];
+        let (payload_dst, signature_dst) =
+            mut_array_refs![dst, CoSigningPayload::LEN, SIGNATURE_BYTES];
+        self.payload.pack_into_slice(payload_dst);
+        *signature_dst = self.signature;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, CoSignature::LEN];
+        let (payload, signature) = array_refs![src, CoSigningPayload::LEN, SIGNATURE_BYTES];
+        Ok(CoSignature {
+            payload: CoSigningPayload::unpack_from_slice(payload)?,
+            signature: *signature,
+        })
+    }
+}
+
+fn unpack_base64<T: Pack>(text: &str) -> Result<T, ProgramError> {
+    let bytes = base64::decode(text.trim()).map_err(|_| ProgramError::InvalidArgument)?;
+    if bytes.len() != T::LEN {
+        return Err(ProgramError::InvalidArgument);
+    }
+    T::unpack_from_slice(&bytes)
+}
+
+// the layout of the ed25519 program's instruction data: a signature count,
+// a padding byte, the offsets of each signature and then the data itself
+const ED25519_OFFSETS_START: usize = 2;
+const ED25519_OFFSETS_LEN: usize = 14;
+const ED25519_DATA_START: usize = ED25519_OFFSETS_START + ED25519_OFFSETS_LEN;
+
+/// An ed25519 program instruction checking a single signature, with the key,
+/// signature and message all in its own data. `instruction_index` is where
+/// the instruction will be in its transaction.
+pub fn ed25519_instruction(
+    public_key: &Pubkey,
+    signature: &[u8; SIGNATURE_BYTES],
+    message: &[u8],
+    instruction_index: u16,
+) -> Instruction {
+    let public_key_offset = ED25519_DATA_START;
+    let signature_offset = public_key_offset + PUBKEY_BYTES;
+    let message_offset = signature_offset + SIGNATURE_BYTES;
+
+    let mut data = Vec::with_capacity(message_offset + message.len());
+    data.push(1);
+    data.push(0);
+    for value in vec![
+        signature_offset as u16,
+        instruction_index,
+        public_key_offset as u16,
+        instruction_index,
+        message_offset as u16,
+        message.len() as u16,
+        instruction_index,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(public_key.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
+/// The key and message of the single signature an ed25519 program
+/// instruction at `instruction_index` checked, as long as they were taken
+/// from the instruction's own data.
+pub fn ed25519_verified_message(
+    data: &[u8],
+    instruction_index: u16,
+) -> Result<(Pubkey, &[u8]), ProgramError> {
+    if data.len() < ED25519_DATA_START || data[0] != 1 {
+        msg!("Expected a single ed25519 signature");
+        return Err(WalletError::InvalidSignature.into());
+    }
+    let offsets = data[ED25519_OFFSETS_START..ED25519_DATA_START]
+        .chunks_exact(2)
+        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+        .collect::<Vec<_>>();
+    let (public_key_offset, message_offset, message_len) = (
+        usize::from(offsets[2]),
+        usize::from(offsets[4]),
+        usize::from(offsets[5]),
+    );
+    // newer runtimes also take u16::MAX to mean the instruction itself
+    if [offsets[1], offsets[3], offsets[6]]
+        .iter()
+        .any(|index| *index != instruction_index && *index != u16::MAX)
+    {
+        msg!("The ed25519 signature must be checked against its own instruction data");
+        return Err(WalletError::InvalidSignature.into());
+    }
+    let public_key = data
+        .get(public_key_offset..public_key_offset + PUBKEY_BYTES)
+        .and_then(|slice| slice.try_into().ok())
+        .map(Pubkey::new_from_array)
+        .ok_or(WalletError::InvalidSignature)?;
+    let message = data
+        .get(message_offset..message_offset + message_len)
+        .ok_or(WalletError::InvalidSignature)?;
+    Ok((public_key, message))
+}
+
+#[test]
+fn test_co_signature_round_trip() {
+    let payload = CoSigningPayload {
+        approver: Pubkey::new_unique(),
+        multisig_op: Pubkey::new_unique(),
+        wallet: Pubkey::new_unique(),
+        params_hash: Hash::new_unique(),
+        started_at: 100,
+        disposition_changes: 0,
+        expires_at: 3700,
+        op_type: 1,
+        initiator: Pubkey::new_unique(),
+        disposition: ApprovalDisposition::DENY,
+        denial_reason: DenialReason::SuspectedFraud,
+        reason_hash: Some(Hash::new_unique()),
+    };
+    assert_eq!(
+        CoSigningPayload::from_base64(&payload.to_base64()).unwrap(),
+        payload
+    );
+    assert_eq!(
+        CoSigningPayload::from_base64("not a payload"),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    let co_signature = CoSignature {
+        payload,
+        signature: [7; SIGNATURE_BYTES],
+    };
+    let text = format!("{}\n", co_signature.to_base64());
+    assert_eq!(CoSignature::from_base64(&text).unwrap(), co_signature);
+
+    let program_id = Pubkey::new_unique();
+    let instructions = co_signature.instructions(&program_id);
+    assert_eq!(instructions[0].program_id, ed25519_program::id());
+    assert_eq!(
+        ed25519_verified_message(&instructions[0].data, 0).unwrap(),
+        (payload.approver, payload.message().as_slice())
+    );
+    assert_eq!(
+        ed25519_verified_message(&instructions[0].data, 1),
+        Err(WalletError::InvalidSignature.into())
+    );
+    match ProgramInstruction::unpack(&instructions[1].data).unwrap() {
+        ProgramInstruction::VerifyCoSignature {
+            disposition,
+            params_hash,
+            denial_reason,
+            reason_hash,
+        } => {
+            assert_eq!(disposition, ApprovalDisposition::DENY);
+            assert_eq!(params_hash, payload.params_hash);
+            assert_eq!(denial_reason, DenialReason::SuspectedFraud);
+            assert_eq!(reason_hash, payload.reason_hash);
+        }
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+    let (program_config, _) =
+        crate::model::program_config::ProgramConfig::find_address(&program_id);
+    assert_eq!(
+        instructions[1].accounts,
+        vec![
+            AccountMeta::new(payload.multisig_op, false),
+            AccountMeta::new_readonly(payload.approver, false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+            AccountMeta::new(payload.wallet, false),
+            AccountMeta::new_readonly(program_config, false),
+        ]
+    );
+
+    // a different disposition, run of the op or change count is a different
+    // message
+    let approve = CoSigningPayload {
+        disposition: ApprovalDisposition::APPROVE,
+        denial_reason: DenialReason::None,
+        reason_hash: None,
+        ..payload
+    };
+    assert_ne!(approve.message(), payload.message());
+    let rerun = CoSigningPayload {
+        started_at: 200,
+        ..payload
+    };
+    assert_ne!(rerun.message(), payload.message());
+    let changed = CoSigningPayload {
+        disposition_changes: 1,
+        ..payload
+    };
+    assert_ne!(changed.message(), payload.message());
+}
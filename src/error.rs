@@ -1,109 +1,350 @@
 use solana_program::program_error::ProgramError;
 use thiserror::Error;
 
-#[derive(Error, Debug, Copy, Clone)]
+/// Errors returned by the program as `ProgramError::Custom(code)`. The codes
+/// are part of the program's interface: clients map them back to variants
+/// with `WalletError::from_code`, so existing codes never change and new
+/// variants are only ever appended.
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum WalletError {
-    // 0
     /// Unexpected account in instruction.
     #[error("Account Not Recognized")]
-    AccountNotRecognized,
+    AccountNotRecognized = 0,
     /// Source account for transfer not recognized.
     #[error("Invalid Source Account")]
-    InvalidSourceAccount,
+    InvalidSourceAccount = 1,
     /// Approver account is not configured as a Signer or, more generally, a
     /// required signature is invalid or missing.
     #[error("Invalid Signature")]
-    InvalidSignature,
+    InvalidSignature = 2,
     /// Account included in instruction is not a valid Approver.
     #[error("Invalid Approver")]
-    InvalidApprover,
+    InvalidApprover = 3,
     /// The disposition of an Approval is invalid for the executing instruction.
     #[error("Invalid Approval Disposition")]
-    InvalidDisposition,
-
-    // 5
+    InvalidDisposition = 4,
     /// Attempting to set Approval timeout beyond allowed min and max.
     #[error("Invalid Approval Timeout")]
-    InvalidApprovalTimeout,
+    InvalidApprovalTimeout = 5,
     /// Tried to set the number of approvals required to invalid number, like
     /// zero or more than the total number of Signers available.
     #[error("Invalid Approver Count")]
-    InvalidApproverCount,
+    InvalidApproverCount = 6,
     /// Tried to access an element of a collection that is either out-of-bounds
     /// or unauthorized. E.G.: AddressBook entries, Transfer Approvers.
     #[error("Invalid Slot")]
-    InvalidSlot,
-    /// TransferDispositionNotFinal used internally in MultisigOp finalization.
+    InvalidSlot = 7,
+    /// No longer returned; finalizing a pending op fails with NotApproved.
     #[error("Transfer Disposition Not Final")]
-    TransferDispositionNotFinal,
+    TransferDispositionNotFinal = 8,
     /// Attempting to transfer an invalid amount between accounts.
     #[error("Amount Overflow")]
-    AmountOverflow,
-
-    // 10
+    AmountOverflow = 9,
     /// Insufficient balance for a transfer.
     #[error("InSufficient Balance")]
-    InsufficientBalance,
+    InsufficientBalance = 10,
     /// Destination address not allowed in a transfer.
     #[error("Destination Not Allowed")]
-    DestinationNotAllowed,
+    DestinationNotAllowed = 11,
     /// Balance Account referenced by an instruction does not exist.
     #[error("Balance Account Not Found")]
-    BalanceAccountNotFound,
+    BalanceAccountNotFound = 12,
     /// Invalid SPL source token account.
     #[error("Invalid Source Token Account")]
-    InvalidSourceTokenAccount,
+    InvalidSourceTokenAccount = 13,
     /// Invalid SPL destination token account.
     #[error("Invalid Destination Token Account")]
-    InvalidDestinationTokenAccount,
-
-    // 15
+    InvalidDestinationTokenAccount = 14,
     /// Invalid SPL token mint account.
     #[error("Invalid Token Mint Account")]
-    InvalidTokenMintAccount,
+    InvalidTokenMintAccount = 15,
     /// Only one policy config change can be initiated at a time.
     #[error("Concurrent Operations Not Allowed")]
-    ConcurrentOperationsNotAllowed,
+    ConcurrentOperationsNotAllowed = 16,
     /// Simulation of MultisigOp finalization completed normally.
     #[error("Simulation Finished Successfully")]
-    SimulationFinished,
+    SimulationFinished = 17,
     /// Cannot whitelist an address when Whitelisting is not enabled.
     #[error("Whitelist Is Disabled")]
-    WhitelistDisabled,
+    WhitelistDisabled = 18,
     /// Cannot disable Whitelisting while one or more address is whitelisted.
     #[error("Whitelisting In Use")]
-    WhitelistedAddressInUse,
-
-    // 20
+    WhitelistedAddressInUse = 19,
     /// The set of Approvers for transfers or config changes must not be empty.
     #[error("No Approvers Enabled")]
-    NoApproversEnabled,
+    NoApproversEnabled = 20,
     /// DApp transactions are disabled.
     #[error("DApp Transactions Are Disabled")]
-    DAppsDisabled,
+    DAppsDisabled = 21,
     /// Destination Already In Use
     #[error("Destination Already In Use")]
-    DestinationInUse,
-    /// Unknown Signer
+    DestinationInUse = 22,
+    /// An approver, delegate or directory-backed slot names a key that is not
+    /// one of the wallet's signers.
     #[error("Unknown Signer")]
-    UnknownSigner,
+    UnknownSigner = 23,
     /// DApp Not Allowed
     #[error("DApp Not Allowed")]
-    DAppNotAllowed,
-
-    // 25
-    /// Slot Cannot Be Inserted
+    DAppNotAllowed = 24,
+    /// A slot being filled is already taken, or the collection is full.
     #[error("Slot Cannot Be Inserted")]
-    SlotCannotBeInserted,
-    /// Slot Cannot Be Removed
+    SlotCannotBeInserted = 25,
+    /// A slot being cleared doesn't hold the given value.
     #[error("Slot Cannot Be Removed")]
-    SlotCannotBeRemoved,
+    SlotCannotBeRemoved = 26,
     /// Signer Is A Config Approver
     #[error("Signer Is A Config Approver")]
-    SignerIsConfigApprover,
+    SignerIsConfigApprover = 27,
     /// Signer Is A Transfer Approver
     #[error("Signer Is A Transfer Approver")]
-    SignerIsTransferApprover,
+    SignerIsTransferApprover = 28,
+    /// A replace-all approvers update cannot be combined with incremental changes
+    #[error("Conflicting Approver Updates")]
+    ConflictingApproverUpdates = 29,
+    /// DApp instructions would spend more on account creation than allowed
+    #[error("Rent Funding Limit Exceeded")]
+    RentFundingLimitExceeded = 30,
+    /// Address book entries can't be verified until the verification delay has passed
+    #[error("Destination Verification Delay Not Elapsed")]
+    DestinationVerificationDelayNotElapsed = 31,
+    /// Activity for the current period can't be tracked until a checkpoint is emitted
+    #[error("Activity Checkpoint Required")]
+    ActivityCheckpointRequired = 32,
+    /// Checkpoint index does not match the wallet's next reporting period
+    #[error("Invalid Checkpoint Index")]
+    InvalidCheckpointIndex = 33,
+    /// DENY dispositions on operations above the wallet's threshold must include a reason
+    #[error("Denial Reason Required")]
+    DenialReasonRequired = 34,
+    /// The program has been paused by the program config authority
+    #[error("Program Paused")]
+    ProgramPaused = 35,
+    /// Signer is not the program config authority
+    #[error("Invalid Program Config Authority")]
+    InvalidProgramConfigAuthority = 36,
+    /// Program config limits must stay within the wallet's storage capacity
+    #[error("Invalid Policy Limit")]
+    InvalidPolicyLimit = 37,
+    /// The destination would not be rent exempt after the transfer
+    #[error("Destination Not Rent Exempt")]
+    DestinationNotRentExempt = 38,
+    /// A multisig op can only be renewed once
+    #[error("Multisig Op Already Renewed")]
+    MultisigOpAlreadyRenewed = 39,
+    /// The token mint is not on the balance account's allowlist
+    #[error("Token Mint Not Allowed")]
+    TokenMintNotAllowed = 40,
+    /// A dapp transaction moved more out of the balance account than its instructions approve
+    #[error("Unapproved Outflow")]
+    UnapprovedOutflow = 41,
+    /// The transfer would push the balance account's open transfers above its cap
+    #[error("Outstanding Transfer Value Exceeded")]
+    OutstandingTransferValueExceeded = 42,
+    /// Approver delegations must be between distinct signers and expire within the allowed period
+    #[error("Invalid Approver Delegation")]
+    InvalidApproverDelegation = 43,
+    /// The wallet's signers are backed by a signer directory that was not passed along
+    #[error("Signer Directory Required")]
+    SignerDirectoryRequired = 44,
+    /// Directory-backed signer slots must hold the same key in the wallet and the directory
+    #[error("Invalid Signer Directory")]
+    InvalidSignerDirectory = 45,
+    /// The wallet was frozen after an instruction was signed by its canary key
+    #[error("Wallet Frozen")]
+    WalletFrozen = 46,
+    /// The canary key can't be one of the wallet's signers or its assistant
+    #[error("Invalid Canary Key")]
+    InvalidCanaryKey = 47,
+    /// The finalizer asked to be reimbursed more than the balance account's policy allows
+    #[error("Fee Reimbursement Exceeded")]
+    FeeReimbursementExceeded = 48,
+    /// An internal transfer has to be between two different balance accounts of the wallet
+    #[error("Invalid Internal Transfer")]
+    InvalidInternalTransfer = 49,
+    /// A balance account policy rule is malformed, or there are too many of them
+    #[error("Invalid Policy Rule")]
+    InvalidPolicyRule = 50,
+    /// The op was started outside of the balance account policy's time windows
+    #[error("Policy Time Window Closed")]
+    PolicyTimeWindowClosed = 51,
+    /// The wallet's live configuration does not match the policy manifest it was checked against
+    #[error("Policy Manifest Mismatch")]
+    PolicyManifestMismatch = 52,
+    /// A dapp instruction's data does not begin with any of the dapp's allowed instruction prefixes
+    #[error("DApp Instruction Not Allowed")]
+    DAppInstructionNotAllowed = 53,
+    /// A governed parameter update sets an invalid value, or names a parameter more than once
+    #[error("Invalid Governed Parameter")]
+    InvalidGovernedParameter = 54,
+    /// A transfer is being finalized against a different token program than it was started with
+    #[error("Token Program Mismatch")]
+    TokenProgramMismatch = 55,
+    /// The multisig op account was created for an op with different params
+    #[error("Op Params Mismatch")]
+    OpParamsMismatch = 56,
+    /// The signer of a VerifyAccess instruction is not one of the wallet's viewers
+    #[error("Unknown Viewer")]
+    UnknownViewer = 57,
+    /// The multisig op account has been closed by an earlier finalize
+    #[error("Already Finalized")]
+    AlreadyFinalized = 58,
+    /// The multisig op was approved, but expired before it was finalized
+    #[error("Op Expired")]
+    OpExpired = 59,
+    /// The multisig op is still waiting for approvals
+    #[error("Not Approved")]
+    NotApproved = 60,
+    /// The assistant can't be the given key, as it is the canary key or, when
+    /// the wallet's governed parameters forbid it, one of the signers
+    #[error("Invalid Assistant")]
+    InvalidAssistant = 61,
+    /// A chunk of a chunked address book update was supplied out of order or
+    /// past its end, or the supplied update doesn't match the approved hash
+    #[error("Invalid Update Chunk")]
+    InvalidUpdateChunk = 62,
+    /// A dapp transaction would move more out of the balance account than the
+    /// dapp's value limit allows in the current period
+    #[error("DApp Value Limit Exceeded")]
+    DAppValueLimitExceeded = 63,
+    /// An op was executed before the op it depends on was
+    #[error("Prerequisite Not Executed")]
+    PrerequisiteNotExecuted = 64,
+    /// Transfers of SOL or of SPL tokens are disabled for the balance account
+    #[error("Transfers Disabled")]
+    TransfersDisabled = 65,
+    /// A wallet can only be exported to, or imported from, a program in the
+    /// program config's migration allowlist
+    #[error("Migration Program Not Allowed")]
+    MigrationProgramNotAllowed = 66,
+    /// The given name does not hash to the stored name hash
+    #[error("Name Hash Mismatch")]
+    NameHashMismatch = 67,
+    /// The instruction is not an instance of the dapp template
+    #[error("DApp Template Mismatch")]
+    DAppTemplateMismatch = 68,
+    /// A field of a dapp template instance is above its cap
+    #[error("DApp Template Cap Exceeded")]
+    DAppTemplateCapExceeded = 69,
+    /// The destination's address book entries are all tagged with namespaces
+    /// the transfer doesn't fit
+    #[error("Destination Namespace Mismatch")]
+    DestinationNamespaceMismatch = 70,
+    /// A large transfer was attempted within the balance account's cooldown
+    /// after the previous one
+    #[error("Large Transfer Cooldown")]
+    LargeTransferCooldown = 71,
+    /// A balance account still holds funds, so the wallet can't be closed
+    #[error("Balance Account Not Empty")]
+    BalanceAccountNotEmpty = 72,
+    /// An init instruction was already submitted with the idempotency key
+    #[error("Duplicate Idempotency Key")]
+    DuplicateIdempotencyKey = 73,
+    /// An address book update makes more changes than fit in the compute
+    /// budget of the instruction applying it
+    #[error("Address Book Update Too Large")]
+    AddressBookUpdateTooLarge = 74,
+    /// More dapp instructions were to run in a single instruction than fit in
+    /// its compute budget
+    #[error("Too Many DApp Instructions")]
+    TooManyDAppInstructions = 75,
+    /// A transfer to a destination tagged as an exchange doesn't carry a travel
+    /// rule data hash
+    #[error("Travel Rule Data Required")]
+    TravelRuleDataRequired = 76,
+    /// The program config account was left out of an instruction that is
+    /// subject to the program-wide pause and limits
+    #[error("Program Config Account Required")]
+    ProgramConfigAccountRequired = 77,
+}
+
+impl WalletError {
+    /// Every variant, indexed by its code.
+    const ALL: [WalletError; 78] = [
+        WalletError::AccountNotRecognized,
+        WalletError::InvalidSourceAccount,
+        WalletError::InvalidSignature,
+        WalletError::InvalidApprover,
+        WalletError::InvalidDisposition,
+        WalletError::InvalidApprovalTimeout,
+        WalletError::InvalidApproverCount,
+        WalletError::InvalidSlot,
+        WalletError::TransferDispositionNotFinal,
+        WalletError::AmountOverflow,
+        WalletError::InsufficientBalance,
+        WalletError::DestinationNotAllowed,
+        WalletError::BalanceAccountNotFound,
+        WalletError::InvalidSourceTokenAccount,
+        WalletError::InvalidDestinationTokenAccount,
+        WalletError::InvalidTokenMintAccount,
+        WalletError::ConcurrentOperationsNotAllowed,
+        WalletError::SimulationFinished,
+        WalletError::WhitelistDisabled,
+        WalletError::WhitelistedAddressInUse,
+        WalletError::NoApproversEnabled,
+        WalletError::DAppsDisabled,
+        WalletError::DestinationInUse,
+        WalletError::UnknownSigner,
+        WalletError::DAppNotAllowed,
+        WalletError::SlotCannotBeInserted,
+        WalletError::SlotCannotBeRemoved,
+        WalletError::SignerIsConfigApprover,
+        WalletError::SignerIsTransferApprover,
+        WalletError::ConflictingApproverUpdates,
+        WalletError::RentFundingLimitExceeded,
+        WalletError::DestinationVerificationDelayNotElapsed,
+        WalletError::ActivityCheckpointRequired,
+        WalletError::InvalidCheckpointIndex,
+        WalletError::DenialReasonRequired,
+        WalletError::ProgramPaused,
+        WalletError::InvalidProgramConfigAuthority,
+        WalletError::InvalidPolicyLimit,
+        WalletError::DestinationNotRentExempt,
+        WalletError::MultisigOpAlreadyRenewed,
+        WalletError::TokenMintNotAllowed,
+        WalletError::UnapprovedOutflow,
+        WalletError::OutstandingTransferValueExceeded,
+        WalletError::InvalidApproverDelegation,
+        WalletError::SignerDirectoryRequired,
+        WalletError::InvalidSignerDirectory,
+        WalletError::WalletFrozen,
+        WalletError::InvalidCanaryKey,
+        WalletError::FeeReimbursementExceeded,
+        WalletError::InvalidInternalTransfer,
+        WalletError::InvalidPolicyRule,
+        WalletError::PolicyTimeWindowClosed,
+        WalletError::PolicyManifestMismatch,
+        WalletError::DAppInstructionNotAllowed,
+        WalletError::InvalidGovernedParameter,
+        WalletError::TokenProgramMismatch,
+        WalletError::OpParamsMismatch,
+        WalletError::UnknownViewer,
+        WalletError::AlreadyFinalized,
+        WalletError::OpExpired,
+        WalletError::NotApproved,
+        WalletError::InvalidAssistant,
+        WalletError::InvalidUpdateChunk,
+        WalletError::DAppValueLimitExceeded,
+        WalletError::PrerequisiteNotExecuted,
+        WalletError::TransfersDisabled,
+        WalletError::MigrationProgramNotAllowed,
+        WalletError::NameHashMismatch,
+        WalletError::DAppTemplateMismatch,
+        WalletError::DAppTemplateCapExceeded,
+        WalletError::DestinationNamespaceMismatch,
+        WalletError::LargeTransferCooldown,
+        WalletError::BalanceAccountNotEmpty,
+        WalletError::DuplicateIdempotencyKey,
+        WalletError::AddressBookUpdateTooLarge,
+        WalletError::TooManyDAppInstructions,
+        WalletError::TravelRuleDataRequired,
+        WalletError::ProgramConfigAccountRequired,
+    ];
+
+    /// The variant behind a `ProgramError::Custom` code, if the code is one
+    /// of the program's.
+    pub fn from_code(code: u32) -> Option<WalletError> {
+        WalletError::ALL.get(code as usize).copied()
+    }
 }
 
 impl From<WalletError> for ProgramError {
@@ -111,3 +352,19 @@ impl From<WalletError> for ProgramError {
         ProgramError::Custom(e as u32)
     }
 }
+
+#[test]
+fn test_error_codes_are_stable() {
+    for (code, error) in WalletError::ALL.iter().enumerate() {
+        assert_eq!(*error as usize, code);
+        assert_eq!(WalletError::from_code(code as u32), Some(*error));
+    }
+    assert_eq!(WalletError::from_code(WalletError::ALL.len() as u32), None);
+
+    // spot checks against codes clients already depend on
+    assert_eq!(WalletError::InvalidSignature as u32, 2);
+    assert_eq!(WalletError::InvalidApproverCount as u32, 6);
+    assert_eq!(WalletError::UnknownSigner as u32, 23);
+    assert_eq!(WalletError::SlotCannotBeInserted as u32, 25);
+    assert_eq!(WalletError::NotApproved as u32, 60);
+}
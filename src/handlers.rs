@@ -1,14 +1,46 @@
+pub mod activity_checkpoint_handler;
+pub mod address_book_page_update_handler;
+pub mod address_book_update_chunked_handler;
 pub mod address_book_update_handler;
+pub mod address_book_verification_handler;
 pub mod approval_disposition_handler;
+pub mod approver_delegation_update_handler;
+pub mod assistant_update_handler;
 pub mod balance_account_creation_handler;
 pub mod balance_account_name_update_handler;
 pub mod balance_account_policy_update_handler;
 pub mod balance_account_settings_update_handler;
+pub mod capacity_report_handler;
+pub mod co_signature_handler;
+pub mod combined_transfer_handler;
 pub mod dapp_book_update_handler;
+pub mod dapp_template_update_handler;
 pub mod dapp_transaction_handler;
+pub mod deposit_consolidation_handler;
+pub mod governed_parameter_update_handler;
 pub mod init_wallet_handler;
+pub mod internal_transfer_handler;
+pub mod multisig_op_expiry_handler;
+pub mod multisig_op_renewal_handler;
+pub mod name_verification_handler;
+pub mod op_account_creation_handler;
+pub mod pending_disposition_handler;
+pub mod pending_ops_handler;
+pub mod policy_manifest_handler;
+pub mod program_config_handler;
+pub mod signer_activity_report_handler;
+pub mod signer_directory_handler;
+pub mod signer_organizations_update_handler;
 pub mod transfer_handler;
 pub mod update_signer_handler;
 pub mod utils;
+pub mod validation;
+pub mod viewer_handler;
+pub mod wallet_canary_handler;
+pub mod wallet_close_handler;
 pub mod wallet_config_policy_update_handler;
+pub mod wallet_export_handler;
+pub mod wallet_metadata_update_handler;
+pub mod wallet_repair_handler;
+pub mod wallet_signer_directory_update_handler;
 pub mod wrap_unwrap_handler;
@@ -0,0 +1,139 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{
+    finalize_multisig_op, get_clock, next_multisig_op_account_info, next_program_account_info,
+    start_multisig_config_op, unpack_wallet,
+};
+use crate::model::activity::ActivityCheckpoint;
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::wallet::Wallet;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::hash;
+use solana_program::msg;
+use solana_program::program::invoke_signed;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_program::system_program;
+use solana_program::sysvar::Sysvar;
+
+fn validate_checkpoint_index(wallet: &Wallet, checkpoint_index: u32) -> ProgramResult {
+    if wallet.activity.checkpoint_count != checkpoint_index {
+        msg!(
+            "Expected checkpoint index {} but got {}",
+            wallet.activity.checkpoint_count,
+            checkpoint_index
+        );
+        return Err(WalletError::InvalidCheckpointIndex.into());
+    }
+    Ok(())
+}
+
+pub fn init(program_id: &Pubkey, accounts: &[AccountInfo], checkpoint_index: u32) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let initiator_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    wallet.validate_config_initiator(initiator_account_info)?;
+    validate_checkpoint_index(&wallet, checkpoint_index)?;
+
+    start_multisig_config_op(
+        &multisig_op_account_info,
+        &wallet,
+        initiator_account_info,
+        clock,
+        MultisigOpParams::ActivityCheckpoint {
+            wallet_address: *wallet_account_info.key,
+            checkpoint_index,
+        },
+    )
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    checkpoint_index: u32,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let checkpoint_account_info = next_account_info(accounts_iter)?;
+    let rent_collector_account_info = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    if system_program_account.key != &system_program::id() {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    let now = clock.unix_timestamp;
+
+    finalize_multisig_op(
+        &multisig_op_account_info,
+        &rent_collector_account_info,
+        clock,
+        MultisigOpParams::ActivityCheckpoint {
+            wallet_address: *wallet_account_info.key,
+            checkpoint_index,
+        },
+        || -> ProgramResult {
+            let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+            validate_checkpoint_index(&wallet, checkpoint_index)?;
+
+            let (checkpoint_address, bump_seed) = ActivityCheckpoint::find_address(
+                wallet_account_info.key,
+                checkpoint_index,
+                program_id,
+            );
+            if checkpoint_address != *checkpoint_account_info.key {
+                msg!("Checkpoint account does not match the expected address");
+                return Err(WalletError::AccountNotRecognized.into());
+            }
+
+            let state_hash = hash(&wallet_account_info.data.borrow());
+
+            invoke_signed(
+                &system_instruction::create_account(
+                    rent_collector_account_info.key,
+                    &checkpoint_address,
+                    Rent::get()?.minimum_balance(ActivityCheckpoint::LEN),
+                    ActivityCheckpoint::LEN as u64,
+                    program_id,
+                ),
+                &[
+                    rent_collector_account_info.clone(),
+                    checkpoint_account_info.clone(),
+                    system_program_account.clone(),
+                ],
+                &[&[
+                    wallet_account_info.key.as_ref(),
+                    ActivityCheckpoint::SEED,
+                    &checkpoint_index.to_le_bytes(),
+                    &[bump_seed],
+                ]],
+            )?;
+
+            ActivityCheckpoint::pack(
+                ActivityCheckpoint {
+                    is_initialized: true,
+                    wallet_address: *wallet_account_info.key,
+                    checkpoint_index,
+                    period_start: wallet.activity.period_start,
+                    period_end: now,
+                    config_change_count: wallet.activity.config_change_count,
+                    transfer_totals: wallet.activity.transfer_totals.clone(),
+                    state_hash,
+                },
+                &mut checkpoint_account_info.data.borrow_mut(),
+            )?;
+
+            wallet.activity.start_next_period(now);
+            Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+            Ok(())
+        },
+    )
+}
@@ -0,0 +1,147 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{
+    finalize_multisig_op, get_clock, next_multisig_op_account_info, next_program_account_info,
+    start_multisig_address_book_op, unpack_wallet,
+};
+use crate::instruction::AddressBookUpdate;
+use crate::model::address_book_page::AddressBookPage;
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::wallet::Wallet;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_program::system_program;
+use solana_program::sysvar::Sysvar;
+
+/// Returns the page stored in the given account, or a new empty page if the
+/// account has not been created yet.
+fn load_page(
+    program_id: &Pubkey,
+    wallet_address: &Pubkey,
+    page_index: u8,
+    page_account_info: &AccountInfo,
+) -> Result<AddressBookPage, ProgramError> {
+    let (page_address, bump_seed) =
+        AddressBookPage::find_address(wallet_address, page_index, program_id);
+    if page_address != *page_account_info.key {
+        msg!("Address book page account does not match the expected address");
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+    if page_account_info.data_is_empty() {
+        return Ok(AddressBookPage::new(wallet_address, page_index, bump_seed));
+    }
+    if page_account_info.owner != program_id {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+    AddressBookPage::unpack(&page_account_info.data.borrow())
+}
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    page_index: u8,
+    update: &AddressBookUpdate,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let page_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    wallet.validate_config_initiator(initiator_account_info)?;
+    let page = load_page(
+        program_id,
+        wallet_account_info.key,
+        page_index,
+        page_account_info,
+    )?;
+    page.validate_update(&wallet, update)?;
+
+    start_multisig_address_book_op(
+        &multisig_op_account_info,
+        &wallet,
+        initiator_account_info,
+        clock,
+        MultisigOpParams::AddressBookPageUpdate {
+            wallet_address: *wallet_account_info.key,
+            page_index,
+            update: update.clone(),
+        },
+    )
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    page_index: u8,
+    update: &AddressBookUpdate,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let page_account_info = next_account_info(accounts_iter)?;
+    let rent_collector_account_info = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    if system_program_account.key != &system_program::id() {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    finalize_multisig_op(
+        &multisig_op_account_info,
+        &rent_collector_account_info,
+        clock,
+        MultisigOpParams::AddressBookPageUpdate {
+            wallet_address: *wallet_account_info.key,
+            page_index,
+            update: update.clone(),
+        },
+        || -> ProgramResult {
+            let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+            let mut page = load_page(
+                program_id,
+                wallet_account_info.key,
+                page_index,
+                page_account_info,
+            )?;
+            page.update(&wallet, update)?;
+
+            if page_account_info.data_is_empty() {
+                invoke_signed(
+                    &system_instruction::create_account(
+                        rent_collector_account_info.key,
+                        page_account_info.key,
+                        Rent::get()?.minimum_balance(AddressBookPage::LEN),
+                        AddressBookPage::LEN as u64,
+                        program_id,
+                    ),
+                    &[
+                        rent_collector_account_info.clone(),
+                        page_account_info.clone(),
+                        system_program_account.clone(),
+                    ],
+                    &[&[
+                        wallet_account_info.key.as_ref(),
+                        AddressBookPage::SEED,
+                        &[page_index],
+                        &[page.bump_seed],
+                    ]],
+                )?;
+            }
+            AddressBookPage::pack(page, &mut page_account_info.data.borrow_mut())?;
+
+            wallet.record_config_change();
+            Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+            Ok(())
+        },
+    )
+}
@@ -0,0 +1,158 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{
+    collect_remaining_balance, finalize_multisig_op, get_clock, next_multisig_op_account_info,
+    next_program_account_info, start_multisig_address_book_op, unpack_wallet,
+};
+use crate::instruction::AddressBookUpdate;
+use crate::model::address_book_update_buffer::AddressBookUpdateBuffer;
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::wallet::Wallet;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::Hash;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+fn unpack_buffer(
+    buffer_account_info: &AccountInfo,
+) -> Result<AddressBookUpdateBuffer, ProgramError> {
+    let data = buffer_account_info.data.borrow();
+    let header = data
+        .get(..AddressBookUpdateBuffer::LEN)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    AddressBookUpdateBuffer::unpack_from_slice(header)
+}
+
+fn pack_buffer(buffer: &AddressBookUpdateBuffer, buffer_account_info: &AccountInfo) {
+    buffer.pack_into_slice(
+        &mut buffer_account_info.data.borrow_mut()[..AddressBookUpdateBuffer::LEN],
+    );
+}
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    update_hash: &Hash,
+    update_len: u32,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let initiator_account_info = next_account_info(accounts_iter)?;
+    let buffer_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let clock = get_clock(accounts_iter)?;
+
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    wallet.validate_config_initiator(initiator_account_info)?;
+
+    if buffer_account_info.data_len()
+        != AddressBookUpdateBuffer::space_required(update_len as usize)
+    {
+        msg!(
+            "Buffer account has no room for an update of {} bytes",
+            update_len
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if unpack_buffer(buffer_account_info)?.is_initialized {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    pack_buffer(
+        &AddressBookUpdateBuffer::new(
+            multisig_op_account_info.key,
+            initiator_account_info.key,
+            *update_hash,
+            update_len,
+        ),
+        buffer_account_info,
+    );
+
+    start_multisig_address_book_op(
+        &multisig_op_account_info,
+        &wallet,
+        initiator_account_info,
+        clock,
+        MultisigOpParams::AddressBookUpdateChunked {
+            wallet_address: *wallet_account_info.key,
+            update_hash: *update_hash,
+        },
+    )
+}
+
+pub fn supply(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    offset: u32,
+    chunk: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let buffer_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let initiator_account_info = next_account_info(accounts_iter)?;
+
+    let mut buffer = unpack_buffer(buffer_account_info)?;
+    if !buffer.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if !initiator_account_info.is_signer || buffer.initiator != *initiator_account_info.key {
+        msg!("Chunks can only be supplied by the initiator of the update");
+        return Err(WalletError::InvalidSignature.into());
+    }
+
+    buffer.supply(offset, chunk.len())?;
+    let start = AddressBookUpdateBuffer::LEN + offset as usize;
+    buffer_account_info.data.borrow_mut()[start..start + chunk.len()].copy_from_slice(chunk);
+    pack_buffer(&buffer, buffer_account_info);
+
+    Ok(())
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    update_hash: &Hash,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let account_to_return_rent_to = next_account_info(accounts_iter)?;
+    let buffer_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let clock = get_clock(accounts_iter)?;
+    let now = clock.unix_timestamp;
+
+    let buffer = unpack_buffer(buffer_account_info)?;
+    if !buffer.is_initialized || buffer.multisig_op != *multisig_op_account_info.key {
+        msg!("Buffer account does not belong to this op");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    finalize_multisig_op(
+        &multisig_op_account_info,
+        &account_to_return_rent_to,
+        clock,
+        MultisigOpParams::AddressBookUpdateChunked {
+            wallet_address: *wallet_account_info.key,
+            update_hash: *update_hash,
+        },
+        || -> ProgramResult {
+            let data = buffer_account_info.data.borrow();
+            let update_bytes = &data[AddressBookUpdateBuffer::LEN..];
+            if !buffer.is_complete()
+                || AddressBookUpdateBuffer::hash_update(update_bytes) != *update_hash
+            {
+                msg!("Supplied update does not match the approved hash");
+                return Err(WalletError::InvalidUpdateChunk.into());
+            }
+            let update = AddressBookUpdate::unpack(update_bytes)?;
+
+            let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow_mut())?;
+            wallet.update_address_book(&update, now)?;
+            wallet.record_config_change();
+            Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+            Ok(())
+        },
+    )?;
+
+    collect_remaining_balance(&buffer_account_info, &account_to_return_rent_to)
+}
@@ -1,6 +1,6 @@
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    start_multisig_config_op,
+    finalize_multisig_op, get_clock, next_multisig_op_account_info, next_program_account_info,
+    start_multisig_address_book_op, unpack_wallet,
 };
 use crate::instruction::AddressBookUpdate;
 use crate::model::multisig_op::MultisigOpParams;
@@ -19,15 +19,16 @@ pub fn init(
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let initiator_account_info = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
     wallet.validate_config_initiator(initiator_account_info)?;
     wallet.validate_address_book_update(update)?;
 
-    start_multisig_config_op(
+    start_multisig_address_book_op(
         &multisig_op_account_info,
         &wallet,
+        initiator_account_info,
         clock,
         MultisigOpParams::AddressBookUpdate {
             wallet_address: *wallet_account_info.key,
@@ -42,10 +43,11 @@ pub fn finalize(
     update: &AddressBookUpdate,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let account_to_return_rent_to = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+    let now = clock.unix_timestamp;
 
     finalize_multisig_op(
         &multisig_op_account_info,
@@ -57,7 +59,8 @@ pub fn finalize(
         },
         || -> ProgramResult {
             let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow_mut())?;
-            wallet.update_address_book(update)?;
+            wallet.update_address_book(update, now)?;
+            wallet.record_config_change();
             Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
             Ok(())
         },
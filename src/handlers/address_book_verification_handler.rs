@@ -0,0 +1,71 @@
+use crate::handlers::utils::{
+    finalize_multisig_op, get_clock, next_multisig_op_account_info, next_program_account_info,
+    start_multisig_address_book_op, unpack_wallet,
+};
+use crate::model::address_book::AddressBookEntry;
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::wallet::Wallet;
+use crate::utils::SlotId;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    entries: &Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let initiator_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    wallet.validate_config_initiator(initiator_account_info)?;
+    wallet.validate_address_book_verification(entries)?;
+
+    start_multisig_address_book_op(
+        &multisig_op_account_info,
+        &wallet,
+        initiator_account_info,
+        clock,
+        MultisigOpParams::VerifyAddressBookEntries {
+            wallet_address: *wallet_account_info.key,
+            entries: entries.clone(),
+        },
+    )
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    entries: &Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let account_to_return_rent_to = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    let now = clock.unix_timestamp;
+
+    finalize_multisig_op(
+        &multisig_op_account_info,
+        &account_to_return_rent_to,
+        clock,
+        MultisigOpParams::VerifyAddressBookEntries {
+            wallet_address: *wallet_account_info.key,
+            entries: entries.clone(),
+        },
+        || -> ProgramResult {
+            let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow_mut())?;
+            wallet.validate_destination_verification_delay(entries, now)?;
+            wallet.verify_address_book_entries(entries)?;
+            wallet.record_config_change();
+            Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+            Ok(())
+        },
+    )
+}
@@ -1,9 +1,19 @@
+#[cfg(feature = "log-compute-units")]
 use crate::error::WalletError;
-use crate::handlers::utils::{get_clock_from_next_account, next_program_account_info};
-use crate::model::multisig_op::{ApprovalDisposition, MultisigOp};
+use crate::handlers::utils::{get_clock, next_program_account_info, record_signer_activity};
+use crate::instruction::ApprovalDispositionUpdate;
+use crate::model::multisig_op::{ApprovalDisposition, DenialReason, MultisigOp};
+use crate::model::signer_directory::SignerDirectory;
 use solana_program::account_info::{next_account_info, AccountInfo};
+#[cfg(feature = "log-compute-units")]
+use solana_program::clock::Clock;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::hash::Hash;
+#[cfg(feature = "log-compute-units")]
+use solana_program::log::sol_log_compute_units;
+#[cfg(feature = "log-compute-units")]
+use solana_program::msg;
+#[cfg(feature = "log-compute-units")]
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 
@@ -12,24 +22,124 @@ pub fn handle(
     accounts: &[AccountInfo],
     disposition: ApprovalDisposition,
     params_hash: Hash,
+    denial_reason: DenialReason,
+    reason_hash: Option<Hash>,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let signer_account_info = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+    let signer_directory = SignerDirectory::from_accounts(program_id, accounts)?;
 
-    let mut multisig_op = MultisigOp::unpack(&multisig_op_account_info.data.borrow())?;
-
-    if params_hash != multisig_op.params_hash {
-        return Err(WalletError::InvalidSignature.into());
+    #[cfg(feature = "log-compute-units")]
+    {
+        log_unpacked_path_compute_units(
+            &multisig_op_account_info.data.borrow(),
+            &params_hash,
+            &signer_account_info,
+            disposition,
+            denial_reason,
+            reason_hash,
+            signer_directory.as_ref(),
+            &clock,
+        )?;
+        msg!("Recording the disposition in place");
+        sol_log_compute_units();
     }
-
-    multisig_op.validate_and_record_approval_disposition(
+    MultisigOp::record_approval_disposition_in_place(
+        &mut multisig_op_account_info.data.borrow_mut(),
+        &params_hash,
         &signer_account_info,
         disposition,
+        denial_reason,
+        reason_hash,
+        signer_directory.as_ref(),
         &clock,
     )?;
-    MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+    #[cfg(feature = "log-compute-units")]
+    sol_log_compute_units();
+    let wallet_address =
+        MultisigOp::wallet_address_in_place(&multisig_op_account_info.data.borrow())?;
+    record_signer_activity(
+        program_id,
+        accounts,
+        &wallet_address,
+        &[*signer_account_info.key],
+        clock.unix_timestamp,
+    )
+}
+
+pub fn handle_many(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    dispositions: &Vec<ApprovalDispositionUpdate>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let signer_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+    let signer_directory = SignerDirectory::from_accounts(program_id, accounts)?;
 
+    let mut wallet_addresses: Vec<Pubkey> = Vec::new();
+    for update in dispositions.iter() {
+        let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+        MultisigOp::record_approval_disposition_in_place(
+            &mut multisig_op_account_info.data.borrow_mut(),
+            &update.params_hash,
+            &signer_account_info,
+            update.disposition,
+            update.denial_reason,
+            update.reason_hash,
+            signer_directory.as_ref(),
+            &clock,
+        )?;
+        let wallet_address =
+            MultisigOp::wallet_address_in_place(&multisig_op_account_info.data.borrow())?;
+        if !wallet_addresses.contains(&wallet_address) {
+            wallet_addresses.push(wallet_address);
+        }
+    }
+    for wallet_address in wallet_addresses.iter() {
+        record_signer_activity(
+            program_id,
+            accounts,
+            wallet_address,
+            &[*signer_account_info.key],
+            clock.unix_timestamp,
+        )?;
+    }
+    Ok(())
+}
+
+/// Logs the compute units recording the disposition takes by unpacking the op
+/// and packing it again, as dispositions were recorded before they could be
+/// recorded in place. It runs on a copy of the op, so nothing is written.
+#[cfg(feature = "log-compute-units")]
+fn log_unpacked_path_compute_units(
+    data: &[u8],
+    params_hash: &Hash,
+    approver: &AccountInfo,
+    disposition: ApprovalDisposition,
+    denial_reason: DenialReason,
+    reason_hash: Option<Hash>,
+    signer_directory: Option<&(Pubkey, SignerDirectory)>,
+    clock: &Clock,
+) -> ProgramResult {
+    msg!("Recording the disposition on the unpacked op");
+    sol_log_compute_units();
+    let mut multisig_op = MultisigOp::unpack(data)?;
+    if multisig_op.params_hash != *params_hash {
+        return Err(WalletError::InvalidSignature.into());
+    }
+    multisig_op.validate_and_record_approval_disposition(
+        approver,
+        disposition,
+        denial_reason,
+        reason_hash,
+        signer_directory,
+        clock,
+    )?;
+    let mut packed = vec![0; MultisigOp::LEN];
+    MultisigOp::pack(multisig_op, &mut packed)?;
+    sol_log_compute_units();
     Ok(())
 }
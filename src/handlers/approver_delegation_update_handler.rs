@@ -0,0 +1,68 @@
+use crate::handlers::utils::{
+    finalize_multisig_op, get_clock, next_multisig_op_account_info, next_program_account_info,
+    start_multisig_config_op, unpack_wallet,
+};
+use crate::instruction::ApproverDelegationUpdate;
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::wallet::Wallet;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    update: &ApproverDelegationUpdate,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let initiator_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    wallet.validate_config_initiator(initiator_account_info)?;
+    wallet.validate_approver_delegation_update(update, clock.unix_timestamp)?;
+
+    start_multisig_config_op(
+        &multisig_op_account_info,
+        &wallet,
+        initiator_account_info,
+        clock,
+        MultisigOpParams::UpdateApproverDelegations {
+            wallet_address: *wallet_account_info.key,
+            update: update.clone(),
+        },
+    )
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    update: &ApproverDelegationUpdate,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let account_to_return_rent_to = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+    let now = clock.unix_timestamp;
+
+    finalize_multisig_op(
+        &multisig_op_account_info,
+        &account_to_return_rent_to,
+        clock,
+        MultisigOpParams::UpdateApproverDelegations {
+            wallet_address: *wallet_account_info.key,
+            update: update.clone(),
+        },
+        || -> ProgramResult {
+            let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+            wallet.update_approver_delegations(update, now)?;
+            wallet.record_config_change();
+            Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+            Ok(())
+        },
+    )
+}
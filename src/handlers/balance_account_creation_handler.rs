@@ -1,10 +1,11 @@
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    start_multisig_config_op,
+    finalize_multisig_op, get_clock, next_multisig_op_account_info, next_program_account_info,
+    start_multisig_config_op, unpack_wallet,
 };
 use crate::instruction::BalanceAccountCreation;
 use crate::model::balance_account::BalanceAccountGuidHash;
 use crate::model::multisig_op::MultisigOpParams;
+use crate::model::program_config::PolicyLimits;
 use crate::model::wallet::Wallet;
 use solana_program::account_info::{next_account_info, AccountInfo};
 use solana_program::entrypoint::ProgramResult;
@@ -16,20 +17,27 @@ pub fn init(
     accounts: &[AccountInfo],
     account_guid_hash: &BalanceAccountGuidHash,
     creation_params: &BalanceAccountCreation,
+    limits: &PolicyLimits,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let initiator_account_info = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
     wallet.validate_config_initiator(initiator_account_info)?;
-    wallet.validate_balance_account_creation(account_guid_hash, creation_params, program_id)?;
+    wallet.validate_balance_account_creation(
+        account_guid_hash,
+        creation_params,
+        program_id,
+        limits,
+    )?;
 
     start_multisig_config_op(
         &multisig_op_account_info,
         &wallet,
+        initiator_account_info,
         clock,
         MultisigOpParams::CreateBalanceAccount {
             account_guid_hash: *account_guid_hash,
@@ -44,12 +52,13 @@ pub fn finalize(
     accounts: &[AccountInfo],
     account_guid_hash: &BalanceAccountGuidHash,
     creation_params: &BalanceAccountCreation,
+    limits: &PolicyLimits,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let rent_collector_account_info = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
     finalize_multisig_op(
         &multisig_op_account_info,
@@ -62,7 +71,13 @@ pub fn finalize(
         },
         || -> ProgramResult {
             let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
-            wallet.create_balance_account(account_guid_hash, creation_params, program_id)?;
+            wallet.create_balance_account(
+                account_guid_hash,
+                creation_params,
+                program_id,
+                limits,
+            )?;
+            wallet.record_config_change();
             Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
             Ok(())
         },
@@ -1,6 +1,6 @@
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    start_multisig_config_op,
+    finalize_pinned_multisig_op, get_clock, next_multisig_op_account_info,
+    next_program_account_info, start_multisig_config_op, unpack_wallet,
 };
 use crate::model::balance_account::{BalanceAccountGuidHash, BalanceAccountNameHash};
 use crate::model::multisig_op::MultisigOpParams;
@@ -20,9 +20,9 @@ pub fn init(
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let initiator_account_info = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
-    let wallet: Wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let wallet: Wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
 
     // ensure GUID references valid account for this wallet
     wallet.get_balance_account(account_guid_hash)?;
@@ -31,6 +31,7 @@ pub fn init(
     start_multisig_config_op(
         &multisig_op_account_info,
         &wallet,
+        initiator_account_info,
         clock,
         MultisigOpParams::UpdateBalanceAccountName {
             wallet_address: *wallet_account_info.key,
@@ -51,13 +52,14 @@ pub fn finalize(
     account_name_hash: &BalanceAccountNameHash,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let account_to_return_rent_to = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
-    finalize_multisig_op(
+    finalize_pinned_multisig_op(
         &multisig_op_account_info,
+        &wallet_account_info,
         &account_to_return_rent_to,
         clock,
         MultisigOpParams::UpdateBalanceAccountName {
@@ -68,6 +70,8 @@ pub fn finalize(
         || -> ProgramResult {
             let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
             wallet.update_balance_account_name_hash(account_guid_hash, account_name_hash)?;
+            wallet.record_balance_account_change(account_guid_hash)?;
+            wallet.record_config_change();
             Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
             Ok(())
         },
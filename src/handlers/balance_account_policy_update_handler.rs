@@ -1,10 +1,11 @@
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    start_multisig_config_op,
+    finalize_pinned_multisig_op, get_clock, next_multisig_op_account_info,
+    next_program_account_info, start_multisig_config_op, unpack_wallet,
 };
 use crate::instruction::BalanceAccountPolicyUpdate;
 use crate::model::balance_account::BalanceAccountGuidHash;
 use crate::model::multisig_op::MultisigOpParams;
+use crate::model::program_config::PolicyLimits;
 use crate::model::wallet::Wallet;
 use solana_program::account_info::{next_account_info, AccountInfo};
 use solana_program::entrypoint::ProgramResult;
@@ -16,21 +17,23 @@ pub fn init(
     accounts: &[AccountInfo],
     account_guid_hash: &BalanceAccountGuidHash,
     update: &BalanceAccountPolicyUpdate,
+    limits: &PolicyLimits,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let initiator_account_info = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
-    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let mut wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
     wallet.validate_config_initiator(initiator_account_info)?;
     wallet.lock_balance_account_policy_updates(account_guid_hash)?;
-    wallet.validate_balance_account_policy_update(account_guid_hash, update)?;
+    wallet.validate_balance_account_policy_update(account_guid_hash, update, limits)?;
 
     start_multisig_config_op(
         &multisig_op_account_info,
         &wallet,
+        initiator_account_info,
         clock,
         MultisigOpParams::UpdateBalanceAccountPolicy {
             wallet_address: *wallet_account_info.key,
@@ -49,17 +52,19 @@ pub fn finalize(
     accounts: &[AccountInfo],
     account_guid_hash: &BalanceAccountGuidHash,
     update: &BalanceAccountPolicyUpdate,
+    limits: &PolicyLimits,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let rent_collector_account_info = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
     let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow_mut())?;
 
-    finalize_multisig_op(
+    finalize_pinned_multisig_op(
         &multisig_op_account_info,
+        &wallet_account_info,
         &rent_collector_account_info,
         clock,
         MultisigOpParams::UpdateBalanceAccountPolicy {
@@ -68,7 +73,9 @@ pub fn finalize(
             update: update.clone(),
         },
         || -> ProgramResult {
-            wallet.update_balance_account_policy(account_guid_hash, update)?;
+            wallet.update_balance_account_policy(account_guid_hash, update, limits)?;
+            wallet.record_balance_account_change(account_guid_hash)?;
+            wallet.record_config_change();
             Ok(())
         },
     )?;
@@ -1,6 +1,6 @@
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    start_multisig_config_op,
+    finalize_pinned_multisig_op, get_clock, next_multisig_op_account_info,
+    next_program_account_info, start_multisig_config_op, unpack_wallet,
 };
 use crate::model::balance_account::BalanceAccountGuidHash;
 use crate::model::multisig_op::{BooleanSetting, MultisigOpParams};
@@ -16,14 +16,16 @@ pub fn init(
     account_guid_hash: &BalanceAccountGuidHash,
     whitelist_enabled: Option<BooleanSetting>,
     dapps_enabled: Option<BooleanSetting>,
+    sol_transfers_enabled: Option<BooleanSetting>,
+    spl_transfers_enabled: Option<BooleanSetting>,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let initiator_account_info = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
     wallet.validate_config_initiator(initiator_account_info)?;
     if let Some(status) = whitelist_enabled {
         wallet.validate_whitelist_enabled_update(account_guid_hash, status)?;
@@ -32,12 +34,15 @@ pub fn init(
     start_multisig_config_op(
         &multisig_op_account_info,
         &wallet,
+        initiator_account_info,
         clock,
         MultisigOpParams::UpdateBalanceAccountSettings {
             wallet_address: *wallet_account_info.key,
             account_guid_hash: *account_guid_hash,
             whitelist_enabled,
             dapps_enabled,
+            sol_transfers_enabled,
+            spl_transfers_enabled,
         },
     )
 }
@@ -48,15 +53,18 @@ pub fn finalize(
     account_guid_hash: &BalanceAccountGuidHash,
     whitelist_enabled: Option<BooleanSetting>,
     dapps_enabled: Option<BooleanSetting>,
+    sol_transfers_enabled: Option<BooleanSetting>,
+    spl_transfers_enabled: Option<BooleanSetting>,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let account_to_return_rent_to = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
-    finalize_multisig_op(
+    finalize_pinned_multisig_op(
         &multisig_op_account_info,
+        &wallet_account_info,
         &account_to_return_rent_to,
         clock,
         MultisigOpParams::UpdateBalanceAccountSettings {
@@ -64,6 +72,8 @@ pub fn finalize(
             account_guid_hash: *account_guid_hash,
             whitelist_enabled,
             dapps_enabled,
+            sol_transfers_enabled,
+            spl_transfers_enabled,
         },
         || -> ProgramResult {
             let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow_mut())?;
@@ -73,6 +83,14 @@ pub fn finalize(
             if let Some(enabled) = dapps_enabled {
                 wallet.update_dapps_enabled(&account_guid_hash, enabled)?;
             }
+            if let Some(enabled) = sol_transfers_enabled {
+                wallet.update_sol_transfers_enabled(&account_guid_hash, enabled)?;
+            }
+            if let Some(enabled) = spl_transfers_enabled {
+                wallet.update_spl_transfers_enabled(&account_guid_hash, enabled)?;
+            }
+            wallet.record_balance_account_change(account_guid_hash)?;
+            wallet.record_config_change();
             Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
             Ok(())
         },
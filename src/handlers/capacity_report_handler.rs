@@ -0,0 +1,36 @@
+use crate::handlers::utils::next_program_account_info;
+use crate::model::wallet::Wallet;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+/// Logs the wallet's capacity usage, warning about collections that are at
+/// least `warning_threshold_percent` full. Nothing is written.
+pub fn handle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    warning_threshold_percent: u8,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    for usage in wallet.capacity_usage() {
+        msg!(
+            "{}: {} used, {} free",
+            usage.collection,
+            usage.used,
+            usage.free()
+        );
+        if usage.is_at_or_past(warning_threshold_percent) {
+            msg!(
+                "Capacity warning: {} is {}% full",
+                usage.collection,
+                usage.percent_used()
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use crate::co_signing::{ed25519_program, ed25519_verified_message, CoSigningPayload};
+use crate::error::WalletError;
+use crate::handlers::utils::{get_clock, next_program_account_info, record_signer_activity};
+use crate::model::multisig_op::{ApprovalDisposition, DenialReason, MultisigOp};
+use crate::model::signer_directory::SignerDirectory;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::Hash;
+use solana_program::msg;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar;
+use solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+
+pub fn handle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    disposition: ApprovalDisposition,
+    params_hash: Hash,
+    denial_reason: DenialReason,
+    reason_hash: Option<Hash>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let approver_account_info = next_account_info(accounts_iter)?;
+    let instructions_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+    let signer_directory = SignerDirectory::from_accounts(program_id, accounts)?;
+
+    if *instructions_account_info.key != sysvar::instructions::id() {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    let mut multisig_op = MultisigOp::unpack(&multisig_op_account_info.data.borrow())?;
+    if multisig_op.params_hash != params_hash {
+        return Err(WalletError::InvalidSignature.into());
+    }
+    // a co-signature is only good for recording its disposition once, and
+    // not at all after the approver has changed their disposition since
+    let record = multisig_op
+        .disposition_record_of(
+            approver_account_info.key,
+            signer_directory.as_ref(),
+            clock.unix_timestamp,
+        )?
+        .copied();
+    if record.map(|record| record.disposition) == Some(disposition) {
+        msg!("Co-signed disposition has already been recorded");
+        return Err(WalletError::InvalidSignature.into());
+    }
+    let payload = CoSigningPayload::new(
+        multisig_op_account_info.key,
+        &multisig_op,
+        approver_account_info.key,
+        record.map_or(0, |record| record.disposition_changes),
+        disposition,
+        denial_reason,
+        reason_hash,
+    );
+
+    let current_index = load_current_index_checked(instructions_account_info)?;
+    if current_index == 0 {
+        msg!("Co-signatures must follow the ed25519 instruction checking them");
+        return Err(WalletError::InvalidSignature.into());
+    }
+    let ed25519_instruction =
+        load_instruction_at_checked(usize::from(current_index - 1), instructions_account_info)?;
+    if ed25519_instruction.program_id != ed25519_program::id() {
+        msg!("Co-signatures must follow the ed25519 instruction checking them");
+        return Err(WalletError::InvalidSignature.into());
+    }
+    let (signer, message) = ed25519_verified_message(&ed25519_instruction.data, current_index - 1)?;
+    if signer != *approver_account_info.key || message != payload.message().as_slice() {
+        msg!("Co-signature is not the approver's signature over this disposition");
+        return Err(WalletError::InvalidSignature.into());
+    }
+
+    // the ed25519 program has checked the approver's signature, which stands
+    // in for the approver signing the transaction
+    let mut approver_account_info = approver_account_info.clone();
+    approver_account_info.is_signer = true;
+    multisig_op.validate_and_record_approval_disposition(
+        &approver_account_info,
+        disposition,
+        denial_reason,
+        reason_hash,
+        signer_directory.as_ref(),
+        &clock,
+    )?;
+    let wallet_address = multisig_op.wallet_address;
+    MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+
+    record_signer_activity(
+        program_id,
+        accounts,
+        &wallet_address,
+        &[*approver_account_info.key],
+        clock.unix_timestamp,
+    )
+}
@@ -0,0 +1,260 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{
+    finalize_pinned_multisig_op, get_clock, next_multisig_op_account_info,
+    next_program_account_info, start_multisig_transfer_op, transfer_sol_checked, unpack_wallet,
+    validate_balance_account_and_get_seed,
+};
+use crate::instruction::CombinedTransfer;
+use crate::model::address_book::AddressBookEntryNameHash;
+use crate::model::balance_account::{BalanceAccount, BalanceAccountGuidHash};
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::policy::{Destination, PolicyRequest};
+use crate::model::wallet::Wallet;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::id as SPL_TOKEN_ID;
+use spl_token::instruction as spl_instruction;
+use spl_token::state::Account as SPLAccount;
+
+/// Every asset the transfer moves has to be transferable out of the balance
+/// account and to the destination. Token mints have to be distinct, and SOL
+/// can only be moved as lamports.
+fn validate_transfer_assets(
+    wallet: &Wallet,
+    balance_account: &BalanceAccount,
+    destination: &Pubkey,
+    transfer: &CombinedTransfer,
+) -> ProgramResult {
+    if transfer.token_amounts.is_empty()
+        || transfer.token_amounts.len() > CombinedTransfer::MAX_TOKEN_AMOUNTS
+    {
+        msg!(
+            "A combined transfer moves between 1 and {} token amounts",
+            CombinedTransfer::MAX_TOKEN_AMOUNTS
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    // combined transfers go to associated token accounts and carry no memo
+    if wallet.is_token_account_destination(destination) {
+        msg!("Combined transfers can't go to a token account destination");
+        return Err(WalletError::DestinationNamespaceMismatch.into());
+    }
+    if transfer.lamports > 0 {
+        balance_account.validate_transfers_enabled(&Pubkey::default())?;
+        wallet.validate_destination_token_mint(destination, &Pubkey::default())?;
+        wallet.validate_destination_namespace(
+            destination,
+            &Pubkey::default(),
+            None,
+            transfer.travel_rule_hash.as_ref(),
+        )?;
+    }
+    for (index, (token_mint, _)) in transfer.token_amounts.iter().enumerate() {
+        if *token_mint == Pubkey::default()
+            || transfer.token_amounts[..index]
+                .iter()
+                .any(|(other_token_mint, _)| other_token_mint == token_mint)
+        {
+            msg!("Token mint {} can't be transferred here", token_mint);
+            return Err(ProgramError::InvalidArgument);
+        }
+        if !wallet.token_mint_allowed(balance_account, token_mint) {
+            msg!("Token mint is not allowed for this balance account");
+            return Err(WalletError::TokenMintNotAllowed.into());
+        }
+        balance_account.validate_transfers_enabled(token_mint)?;
+        wallet.validate_destination_token_mint(destination, token_mint)?;
+        wallet.validate_destination_namespace(
+            destination,
+            token_mint,
+            None,
+            transfer.travel_rule_hash.as_ref(),
+        )?;
+    }
+    Ok(())
+}
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+    destination_name_hash: &AddressBookEntryNameHash,
+    transfer: &CombinedTransfer,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let source_account = next_account_info(accounts_iter)?;
+    let destination_account = next_account_info(accounts_iter)?;
+    let initiator_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    let mut wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    let balance_account = wallet.get_balance_account(account_guid_hash)?;
+    validate_balance_account_and_get_seed(source_account, &balance_account, program_id)?;
+
+    let total = transfer.total()?;
+    let whitelisted = wallet.destination_allowed(
+        &balance_account,
+        destination_account.key,
+        destination_name_hash,
+    )?;
+    let policy = wallet.evaluate_policy(
+        &balance_account,
+        &PolicyRequest {
+            amount: total,
+            destination: Destination::Address { whitelisted },
+            now: clock.unix_timestamp,
+        },
+    )?;
+
+    wallet.validate_transfer_initiator(initiator_account_info)?;
+    validate_transfer_assets(&wallet, &balance_account, destination_account.key, transfer)?;
+
+    wallet.reserve_outstanding_transfer_value(account_guid_hash, total)?;
+
+    start_multisig_transfer_op(
+        &multisig_op_account_info,
+        &wallet,
+        policy,
+        initiator_account_info,
+        clock,
+        MultisigOpParams::CombinedTransfer {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            destination: *destination_account.key,
+            transfer: transfer.clone(),
+        },
+    )?;
+
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+    transfer: &CombinedTransfer,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let source_account = next_account_info(accounts_iter)?;
+    let destination_account = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+    let rent_collector_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+    let now = clock.unix_timestamp;
+
+    if system_program_account.key != &system_program::id() {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    finalize_pinned_multisig_op(
+        &multisig_op_account_info,
+        &wallet_account_info,
+        &rent_collector_account_info,
+        clock,
+        MultisigOpParams::CombinedTransfer {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            destination: *destination_account.key,
+            transfer: transfer.clone(),
+        },
+        || -> ProgramResult {
+            // settings and bindings may have changed since the transfer was initiated
+            let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+            let balance_account = wallet.get_balance_account(account_guid_hash)?;
+            let bump_seed = validate_balance_account_and_get_seed(
+                source_account,
+                &balance_account,
+                program_id,
+            )?;
+            balance_account.policy_rules.validate_settlement(now)?;
+            validate_transfer_assets(&wallet, &balance_account, destination_account.key, transfer)?;
+            wallet.record_large_transfer(account_guid_hash, transfer.total()?, now)?;
+
+            if transfer.lamports > 0 {
+                transfer_sol_checked(
+                    source_account.clone(),
+                    account_guid_hash,
+                    bump_seed,
+                    system_program_account.clone(),
+                    destination_account.clone(),
+                    transfer.lamports,
+                )?;
+                wallet
+                    .activity
+                    .record_transfer(&Pubkey::default(), transfer.lamports)?;
+            }
+
+            let spl_token_program = next_account_info(accounts_iter)?;
+            if *spl_token_program.key != SPL_TOKEN_ID() {
+                return Err(WalletError::TokenProgramMismatch.into());
+            }
+            for (token_mint, amount) in transfer.token_amounts.iter() {
+                let source_token_account = next_account_info(accounts_iter)?;
+                if *source_token_account.key
+                    != get_associated_token_address(source_account.key, token_mint)
+                {
+                    return Err(WalletError::InvalidSourceTokenAccount.into());
+                }
+                let source_token_account_data =
+                    SPLAccount::unpack(&source_token_account.data.borrow())?;
+                if source_token_account_data.amount < *amount {
+                    msg!(
+                        "Source token account only has {} tokens of {} requested",
+                        source_token_account_data.amount,
+                        amount
+                    );
+                    return Err(WalletError::InsufficientBalance.into());
+                }
+                let destination_token_account = next_account_info(accounts_iter)?;
+                if *destination_token_account.key
+                    != get_associated_token_address(destination_account.key, token_mint)
+                    || *destination_token_account.owner != SPL_TOKEN_ID()
+                {
+                    return Err(WalletError::InvalidDestinationTokenAccount.into());
+                }
+
+                invoke_signed(
+                    &spl_instruction::transfer(
+                        &SPL_TOKEN_ID(),
+                        source_token_account.key,
+                        destination_token_account.key,
+                        source_account.key,
+                        &[],
+                        *amount,
+                    )?,
+                    &[
+                        source_token_account.clone(),
+                        destination_token_account.clone(),
+                        source_account.clone(),
+                        spl_token_program.clone(),
+                    ],
+                    &[&[&account_guid_hash.to_bytes(), &[bump_seed]]],
+                )?;
+                wallet.activity.record_transfer(token_mint, *amount)?;
+            }
+            if let Some(travel_rule_hash) = transfer.travel_rule_hash {
+                msg!("Travel rule data hash: {}", travel_rule_hash);
+            }
+
+            Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+            Ok(())
+        },
+    )?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.release_outstanding_transfer_value(account_guid_hash, transfer.total()?)?;
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    start_multisig_config_op,
+    finalize_multisig_op, get_clock, next_multisig_op_account_info, next_program_account_info,
+    start_multisig_address_book_op, unpack_wallet,
 };
 use crate::instruction::DAppBookUpdate;
 use crate::model::multisig_op::MultisigOpParams;
@@ -19,16 +19,17 @@ pub fn init(
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let initiator_account_info = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
 
     wallet.validate_config_initiator(initiator_account_info)?;
     wallet.validate_dapp_book_update(update)?;
 
-    start_multisig_config_op(
+    start_multisig_address_book_op(
         &multisig_op_account_info,
         &wallet,
+        initiator_account_info,
         clock,
         MultisigOpParams::UpdateDAppBook {
             wallet_address: *wallet_account_info.key,
@@ -47,10 +48,10 @@ pub fn finalize(
     update: &DAppBookUpdate,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let account_to_return_rent_to = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
     let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow_mut())?;
 
@@ -64,6 +65,7 @@ pub fn finalize(
         },
         || -> ProgramResult {
             wallet.update_dapp_book(update)?;
+            wallet.record_config_change();
             Ok(())
         },
     )?;
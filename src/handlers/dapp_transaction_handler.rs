@@ -1,22 +1,36 @@
 use bitvec::macros::internal::funty::Fundamental;
 
 use crate::error::WalletError;
+use crate::handlers::pending_ops_handler::validate_prerequisite_executed;
 use crate::handlers::utils::{
-    calculate_expires, collect_remaining_balance, get_clock_from_next_account,
-    next_program_account_info, validate_balance_account_and_get_seed,
+    collect_remaining_balance, get_clock, next_multisig_op_account_info, next_program_account_info,
+    start_multisig_transfer_op, unpack_wallet, validate_balance_account_and_get_seed,
+    validate_rent_collector,
 };
-use crate::model::address_book::DAppBookEntry;
+use crate::instruction::ProgramInstruction;
+use crate::model::address_book::{DAppBookEntry, DAppInstructionPrefixes};
 use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::dapp_inner_programs::DAppInnerPrograms;
+use crate::model::dapp_template::DAppTemplate;
 use crate::model::multisig_op::{MultisigOp, MultisigOpParams};
+use crate::model::pending_ops::PendingOps;
+use crate::model::policy::{Destination, PolicyRequest};
 use crate::model::wallet::Wallet;
+use crate::utils::SlotId;
 use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::clock::Clock;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::instruction::Instruction;
 use solana_program::msg;
 use solana_program::program::invoke_signed;
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::Pack;
+use solana_program::program_utils::limited_deserialize;
 use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction::SystemInstruction;
+use solana_program::system_program;
+use solana_program::sysvar::Sysvar;
+use spl_token::instruction::TokenInstruction;
 use spl_token::state::Account as SPLAccount;
 
 pub fn init(
@@ -25,48 +39,382 @@ pub fn init(
     account_guid_hash: &BalanceAccountGuidHash,
     dapp: DAppBookEntry,
     instructions: Vec<Instruction>,
+    rent_funding_limit: u64,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let initiator_account_info = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
     let balance_account = wallet.get_balance_account(account_guid_hash)?;
 
-    if balance_account.are_dapps_disabled() {
-        return Err(WalletError::DAppsDisabled.into());
+    let mut policy = wallet.evaluate_policy(
+        &balance_account,
+        &PolicyRequest {
+            amount: 0,
+            destination: Destination::DApp {
+                listed: wallet.dapp_allowed(dapp),
+            },
+            now: clock.unix_timestamp,
+        },
+    )?;
+    // the dapp's own approvers take the place of the balance account's
+    if let Some(dapp_policy) = wallet.dapp_approval_policy(&dapp) {
+        policy.approvers = wallet.get_dapp_approvers_keys(&dapp_policy);
+        policy.approvals_required = dapp_policy.approvals_required;
     }
 
     wallet.validate_transfer_initiator(initiator_account_info)?;
 
-    if !balance_account.is_whitelist_disabled() {
-        if !wallet.dapp_allowed(dapp) {
-            return Err(WalletError::DAppNotAllowed.into());
-        }
+    validate_instruction_prefixes(
+        &dapp.address,
+        &wallet.dapp_instruction_prefixes(&dapp),
+        &instructions,
+    )?;
+    validate_inner_programs(
+        &dapp.address,
+        &wallet.dapp_inner_programs(&dapp),
+        &instructions,
+        accounts_iter.as_slice(),
+    )?;
+
+    let max_rent_funding_limit = wallet.governed_parameters.max_dapp_rent_funding_limit();
+    if rent_funding_limit > max_rent_funding_limit {
+        msg!(
+            "Rent funding limit can't be more than {} lamports",
+            max_rent_funding_limit
+        );
+        return Err(WalletError::RentFundingLimitExceeded.into());
     }
+    let (balance_account_pda, _) = balance_account.find_address(program_id)?;
+    validate_rent_funding(&balance_account_pda, &instructions, rent_funding_limit)?;
 
-    let mut multisig_op = MultisigOp::unpack_unchecked(&multisig_op_account_info.data.borrow())?;
-    multisig_op.init(
-        wallet.get_transfer_approvers_keys(&balance_account),
-        1,
-        clock.unix_timestamp,
-        calculate_expires(
-            clock.unix_timestamp,
-            balance_account.approval_timeout_for_transfer,
-        )?,
+    start_multisig_transfer_op(
+        &multisig_op_account_info,
+        &wallet,
+        policy,
+        initiator_account_info,
+        clock,
         MultisigOpParams::DAppTransaction {
             wallet_address: *wallet_account_info.key,
             account_guid_hash: *account_guid_hash,
             dapp,
             instructions,
+            rent_funding_limit,
         },
-    )?;
-    MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+    )
+}
+
+/// At most `MAX_DAPP_INSTRUCTIONS_PER_CALL` dapp instructions can run in one
+/// instruction, checked before any of them runs rather than failing with the
+/// compute budget exhausted part way through.
+fn validate_instruction_count(instruction_count: usize) -> ProgramResult {
+    if instruction_count > ProgramInstruction::MAX_DAPP_INSTRUCTIONS_PER_CALL {
+        msg!(
+            "Can't run {} dapp instructions at once, at most {}; run the transaction in chunks",
+            instruction_count,
+            ProgramInstruction::MAX_DAPP_INSTRUCTIONS_PER_CALL
+        );
+        return Err(WalletError::TooManyDAppInstructions.into());
+    }
+    Ok(())
+}
+
+/// Instructions to the dapp's program have to begin with one of its allowed
+/// instruction prefixes, if it has any.
+fn validate_instruction_prefixes(
+    dapp_address: &Pubkey,
+    prefixes: &DAppInstructionPrefixes,
+    instructions: &Vec<Instruction>,
+) -> ProgramResult {
+    if instructions.iter().any(|instruction| {
+        instruction.program_id == *dapp_address && !prefixes.allow(&instruction.data)
+    }) {
+        msg!("DApp instruction data does not begin with an allowed prefix");
+        return Err(WalletError::DAppInstructionNotAllowed.into());
+    }
+    Ok(())
+}
+
+/// A dapp restricted to a set of inner programs can only be handed those as
+/// executable accounts, since a program can only invoke programs it is handed.
+/// Every account its instructions reference has to be passed along so this
+/// can be checked, and no other executable account may be passed besides the
+/// programs the instructions are sent to.
+fn validate_inner_programs(
+    dapp_address: &Pubkey,
+    inner_programs: &Option<DAppInnerPrograms>,
+    instructions: &Vec<Instruction>,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let inner_programs = match inner_programs {
+        Some(inner_programs) => inner_programs,
+        None => return Ok(()),
+    };
+    let allowed =
+        |program_id: &Pubkey| *program_id == *dapp_address || inner_programs.allow(program_id);
+    for instruction in instructions
+        .iter()
+        .filter(|instruction| instruction.program_id == *dapp_address)
+    {
+        for meta in instruction.accounts.iter() {
+            let account = match accounts.iter().find(|account| *account.key == meta.pubkey) {
+                Some(account) => account,
+                None => {
+                    msg!("Account {} of a dapp instruction is missing", meta.pubkey);
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                }
+            };
+            if account.executable && !allowed(account.key) {
+                msg!("Program {} is not an allowed inner program", account.key);
+                return Err(WalletError::DAppInstructionNotAllowed.into());
+            }
+        }
+    }
+    for account in accounts.iter().filter(|account| account.executable) {
+        if !allowed(account.key)
+            && !instructions
+                .iter()
+                .any(|instruction| instruction.program_id == *account.key)
+        {
+            msg!("Unexpected executable account {}", account.key);
+            return Err(WalletError::DAppInstructionNotAllowed.into());
+        }
+    }
+    Ok(())
+}
+
+/// Returns the lamports the balance account spends funding an account created by
+/// the given instruction (either `create_account` or `create_account_with_seed`,
+/// where the balance account PDA may also act as the base).
+fn rent_funded_by(balance_account: &Pubkey, instruction: &Instruction) -> u64 {
+    if instruction.program_id != system_program::id()
+        || instruction
+            .accounts
+            .first()
+            .map_or(true, |funder| funder.pubkey != *balance_account)
+    {
+        return 0;
+    }
+    match limited_deserialize::<SystemInstruction>(&instruction.data) {
+        Ok(SystemInstruction::CreateAccount { lamports, .. }) => lamports,
+        Ok(SystemInstruction::CreateAccountWithSeed { lamports, .. }) => lamports,
+        _ => 0,
+    }
+}
+
+fn validate_rent_funding(
+    balance_account: &Pubkey,
+    instructions: &Vec<Instruction>,
+    rent_funding_limit: u64,
+) -> ProgramResult {
+    let mut rent_funding: u64 = 0;
+    for instruction in instructions.iter() {
+        rent_funding = rent_funding
+            .checked_add(rent_funded_by(balance_account, instruction))
+            .ok_or(WalletError::AmountOverflow)?;
+    }
+    if rent_funding > rent_funding_limit {
+        msg!(
+            "DApp instructions fund {} lamports of rent, limit is {}",
+            rent_funding,
+            rent_funding_limit
+        );
+        return Err(WalletError::RentFundingLimitExceeded.into());
+    }
+    Ok(())
+}
+
+/// The most the approved instructions can move out of the balance account and
+/// the token accounts it owns. Only system and token program instructions are
+/// credited with outflow, so a dapp can only pull funds that are sent to it or
+/// approved to a delegate explicitly.
+struct MaxOutflow {
+    lamports: u64,
+    tokens: Vec<(Pubkey, u64)>,
+}
+
+impl MaxOutflow {
+    fn from_instructions(
+        balance_account: &Pubkey,
+        instructions: &Vec<Instruction>,
+    ) -> Result<MaxOutflow, ProgramError> {
+        let mut max_outflow = MaxOutflow {
+            lamports: 0,
+            tokens: Vec::new(),
+        };
+        for instruction in instructions.iter() {
+            max_outflow.lamports = max_outflow
+                .lamports
+                .checked_add(lamports_sent_by(balance_account, instruction))
+                .ok_or(WalletError::AmountOverflow)?;
+            if let Some((token_account, amount)) = tokens_sent_by(balance_account, instruction) {
+                match max_outflow
+                    .tokens
+                    .iter_mut()
+                    .find(|(account, _)| *account == token_account)
+                {
+                    Some((_, total)) => {
+                        *total = total
+                            .checked_add(amount)
+                            .ok_or(WalletError::AmountOverflow)?;
+                    }
+                    None => max_outflow.tokens.push((token_account, amount)),
+                }
+            }
+        }
+        Ok(max_outflow)
+    }
+
+    fn tokens_from(&self, token_account: &Pubkey) -> u64 {
+        self.tokens
+            .iter()
+            .find(|(account, _)| account == token_account)
+            .map_or(0, |(_, amount)| *amount)
+    }
+}
+
+fn lamports_sent_by(balance_account: &Pubkey, instruction: &Instruction) -> u64 {
+    if instruction.program_id == system_program::id()
+        && instruction
+            .accounts
+            .first()
+            .map_or(false, |from| from.pubkey == *balance_account)
+    {
+        if let Ok(SystemInstruction::Transfer { lamports }) =
+            limited_deserialize::<SystemInstruction>(&instruction.data)
+        {
+            return lamports;
+        }
+    }
+    rent_funded_by(balance_account, instruction)
+}
+
+/// Returns the token account and amount the given instruction transfers, burns
+/// or approves to a delegate on the balance account's authority.
+fn tokens_sent_by(balance_account: &Pubkey, instruction: &Instruction) -> Option<(Pubkey, u64)> {
+    if instruction.program_id != spl_token::id() {
+        return None;
+    }
+    let (amount, authority_index) = match TokenInstruction::unpack(&instruction.data).ok()? {
+        TokenInstruction::Transfer { amount } => (amount, 2),
+        TokenInstruction::TransferChecked { amount, .. } => (amount, 3),
+        TokenInstruction::Approve { amount } => (amount, 2),
+        TokenInstruction::ApproveChecked { amount, .. } => (amount, 3),
+        TokenInstruction::Burn { amount } => (amount, 2),
+        TokenInstruction::BurnChecked { amount, .. } => (amount, 2),
+        _ => return None,
+    };
+    let source = instruction.accounts.first()?;
+    let authority = instruction.accounts.get(authority_index)?;
+    if authority.pubkey == *balance_account {
+        Some((source.pubkey, amount))
+    } else {
+        None
+    }
+}
+
+fn validate_outflow(
+    balance_account: &AccountInfo,
+    starting_lamports: u64,
+    starting_spl_balances: &Vec<SplBalance>,
+    ending_spl_balances: &Vec<SplBalance>,
+    max_outflow: &MaxOutflow,
+) -> ProgramResult {
+    let lamports_outflow = starting_lamports.saturating_sub(balance_account.lamports());
+    if lamports_outflow > max_outflow.lamports {
+        msg!(
+            "Balance account lost {} lamports, instructions approve {}",
+            lamports_outflow,
+            max_outflow.lamports
+        );
+        return Err(WalletError::UnapprovedOutflow.into());
+    }
+    for start in starting_spl_balances
+        .iter()
+        .filter(|start| start.owner == *balance_account.key)
+    {
+        let ending_balance = ending_spl_balances
+            .iter()
+            .find(|end| end.account == start.account && end.token_mint == start.token_mint)
+            .map_or(0, |end| end.balance);
+        let token_outflow = start.balance.saturating_sub(ending_balance);
+        if token_outflow > max_outflow.tokens_from(&start.account) {
+            msg!(
+                "Token account {} lost {} tokens, instructions approve {}",
+                start.account,
+                token_outflow,
+                max_outflow.tokens_from(&start.account)
+            );
+            return Err(WalletError::UnapprovedOutflow.into());
+        }
+    }
     Ok(())
 }
 
+/// The nominal value the balance account and the token accounts it owns lost
+/// while the instructions ran: lamports plus token amounts of every mint.
+fn observed_outflow(
+    balance_account: &AccountInfo,
+    starting_lamports: u64,
+    starting_spl_balances: &Vec<SplBalance>,
+    ending_spl_balances: &Vec<SplBalance>,
+) -> Result<u64, ProgramError> {
+    let mut outflow = starting_lamports.saturating_sub(balance_account.lamports());
+    for start in starting_spl_balances
+        .iter()
+        .filter(|start| start.owner == *balance_account.key)
+    {
+        let ending_balance = ending_spl_balances
+            .iter()
+            .find(|end| end.account == start.account && end.token_mint == start.token_mint)
+            .map_or(0, |end| end.balance);
+        outflow = outflow
+            .checked_add(start.balance.saturating_sub(ending_balance))
+            .ok_or(WalletError::AmountOverflow)?;
+    }
+    Ok(outflow)
+}
+
+/// Adds what the balance account and the token accounts it owns lost while
+/// the instructions ran to the wallet's activity, as a transfer per mint.
+/// Returns whether anything was recorded.
+fn record_outflow_activity(
+    wallet: &mut Wallet,
+    balance_account: &AccountInfo,
+    starting_lamports: u64,
+    starting_spl_balances: &Vec<SplBalance>,
+    ending_spl_balances: &Vec<SplBalance>,
+) -> Result<bool, ProgramError> {
+    let mut recorded = false;
+    let lamports_outflow = starting_lamports.saturating_sub(balance_account.lamports());
+    if lamports_outflow > 0 {
+        wallet
+            .activity
+            .record_transfer(&Pubkey::default(), lamports_outflow)?;
+        recorded = true;
+    }
+    for start in starting_spl_balances
+        .iter()
+        .filter(|start| start.owner == *balance_account.key)
+    {
+        let ending_balance = ending_spl_balances
+            .iter()
+            .find(|end| end.account == start.account && end.token_mint == start.token_mint)
+            .map_or(0, |end| end.balance);
+        let token_outflow = start.balance.saturating_sub(ending_balance);
+        if token_outflow > 0 {
+            wallet
+                .activity
+                .record_transfer(&start.token_mint, token_outflow)?;
+            recorded = true;
+        }
+    }
+    Ok(recorded)
+}
+
 fn account_balances(accounts: &[AccountInfo]) -> Vec<u64> {
     accounts.iter().map(|a| a.lamports()).collect()
 }
@@ -80,6 +428,7 @@ fn spl_balances(accounts: &[AccountInfo]) -> Vec<SplBalance> {
                     .ok()
                     .map(|account_data| SplBalance {
                         account: *a.key,
+                        owner: account_data.owner,
                         token_mint: account_data.mint,
                         balance: account_data.amount,
                     })
@@ -150,42 +499,98 @@ fn balance_changes_from_simulation(
     )
 }
 
+/// Fails if any token account owned by the balance account changed balance
+/// in a token mint that is not on the balance account's allowlist.
+fn validate_token_mints_allowed(
+    wallet: &Wallet,
+    balance_account_address: &Pubkey,
+    account_guid_hash: &BalanceAccountGuidHash,
+    starting_spl_balances: &Vec<SplBalance>,
+    ending_spl_balances: &Vec<SplBalance>,
+) -> ProgramResult {
+    let balance_account = wallet.get_balance_account(account_guid_hash)?;
+    if !balance_account.is_token_mint_allowlist_enabled() {
+        return Ok(());
+    }
+    for end in ending_spl_balances
+        .iter()
+        .filter(|end| end.owner == *balance_account_address)
+    {
+        let starting_balance = starting_spl_balances
+            .iter()
+            .find(|start| start.account == end.account && start.token_mint == end.token_mint)
+            .map(|start| start.balance)
+            .unwrap_or(0);
+        if end.balance != starting_balance
+            && !wallet.token_mint_allowed(&balance_account, &end.token_mint)
+        {
+            msg!("Token mint {} is not allowed", end.token_mint);
+            return Err(WalletError::TokenMintNotAllowed.into());
+        }
+    }
+    Ok(())
+}
+
 pub fn finalize(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     account_guid_hash: &BalanceAccountGuidHash,
     dapp: DAppBookEntry,
     instructions: &Vec<Instruction>,
+    rent_funding_limit: u64,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let balance_account = next_account_info(accounts_iter)?;
     let rent_collector_account_info = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
-    if !rent_collector_account_info.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    validate_rent_collector(
+        rent_collector_account_info,
+        wallet_account_info.key,
+        program_id,
+    )?;
 
     let multisig_op = MultisigOp::unpack(&multisig_op_account_info.data.borrow())?;
+    if multisig_op.instructions_executed > 0 {
+        msg!("DApp transaction is running in chunks and has to be finished with them");
+        return Err(ProgramError::InvalidArgument);
+    }
 
     let expected_params = MultisigOpParams::DAppTransaction {
         wallet_address: *wallet_account_info.key,
         account_guid_hash: *account_guid_hash,
         instructions: instructions.clone(),
         dapp,
+        rent_funding_limit,
     };
 
-    const NOT_FINAL: u32 = WalletError::TransferDispositionNotFinal as u32;
+    const NOT_FINAL: u32 = WalletError::NotApproved as u32;
     let (is_approved, is_final) = match multisig_op.approved(&expected_params, &clock) {
         Ok(a) => (a, true),
         Err(ProgramError::Custom(NOT_FINAL)) => (false, false),
         Err(e) => return Err(e),
     };
 
-    let bump_seed =
-        validate_balance_account_and_get_seed(balance_account, account_guid_hash, program_id)?;
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let bump_seed = validate_balance_account_and_get_seed(
+        balance_account,
+        &wallet.get_balance_account(account_guid_hash)?,
+        program_id,
+    )?;
+    validate_rent_funding(balance_account.key, instructions, rent_funding_limit)?;
+    validate_inner_programs(
+        &dapp.address,
+        &wallet.dapp_inner_programs(&dapp),
+        instructions,
+        accounts_iter.as_slice(),
+    )?;
+    // an op started under an earlier wallet config is closed without running
+    let is_approved = is_approved && multisig_op.started_under(&wallet);
+    let token_mint_allowlist_enabled = wallet
+        .get_balance_account(account_guid_hash)?
+        .is_token_mint_allowlist_enabled();
 
     let starting_balances: Vec<u64> = if is_final {
         Vec::new()
@@ -193,14 +598,12 @@ pub fn finalize(
         account_balances(accounts)
     };
 
-    let starting_spl_balances: Vec<SplBalance> = if is_final {
-        Vec::new()
-    } else {
-        spl_balances(accounts)
-    };
+    let starting_lamports = balance_account.lamports();
+    let starting_spl_balances: Vec<SplBalance> = spl_balances(accounts);
 
     // actually run instructions if action is approved or this is a simulation (we are not final)
     if is_approved || !is_final {
+        validate_instruction_count(instructions.len())?;
         for instruction in instructions.iter() {
             invoke_signed(
                 &instruction,
@@ -208,6 +611,50 @@ pub fn finalize(
                 &[&[&account_guid_hash.to_bytes(), &[bump_seed]]],
             )?;
         }
+
+        let ending_spl_balances = spl_balances(accounts);
+        if token_mint_allowlist_enabled {
+            validate_token_mints_allowed(
+                &wallet,
+                balance_account.key,
+                account_guid_hash,
+                &starting_spl_balances,
+                &ending_spl_balances,
+            )?;
+        }
+        if is_final {
+            validate_outflow(
+                balance_account,
+                starting_lamports,
+                &starting_spl_balances,
+                &ending_spl_balances,
+                &MaxOutflow::from_instructions(balance_account.key, instructions)?,
+            )?;
+        }
+        // checked when simulating too, but only recorded once the op is final
+        let value_limited = wallet.record_dapp_value(
+            account_guid_hash,
+            &dapp,
+            observed_outflow(
+                balance_account,
+                starting_lamports,
+                &starting_spl_balances,
+                &ending_spl_balances,
+            )?,
+            clock.unix_timestamp,
+        )?;
+        if is_final {
+            let outflow_recorded = record_outflow_activity(
+                &mut wallet,
+                balance_account,
+                starting_lamports,
+                &starting_spl_balances,
+                &ending_spl_balances,
+            )?;
+            if value_limited || outflow_recorded {
+                Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+            }
+        }
     }
 
     if is_final {
@@ -226,8 +673,249 @@ pub fn finalize(
     }
 }
 
+/// Runs the next `chunk_len` instructions of an approved dapp transaction, for
+/// transactions too large to run in one go. Instructions run in the order they
+/// were approved in, and the op account is only closed after the last chunk.
+/// Once the first chunk ran the rest of the transaction is committed to, so
+/// later chunks only check the instructions are the approved ones. Outflow is
+/// checked per chunk, so a delegate approval has to be in the same chunk as the
+/// transfers it allows.
+pub fn execute_chunk(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+    dapp: DAppBookEntry,
+    instructions: &Vec<Instruction>,
+    rent_funding_limit: u64,
+    chunk_len: u8,
+) -> ProgramResult {
+    validate_instruction_count(usize::from(chunk_len))?;
+
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let balance_account = next_account_info(accounts_iter)?;
+    let rent_collector_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    validate_rent_collector(
+        rent_collector_account_info,
+        wallet_account_info.key,
+        program_id,
+    )?;
+
+    let mut multisig_op = MultisigOp::unpack(&multisig_op_account_info.data.borrow())?;
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+
+    let expected_params = MultisigOpParams::DAppTransaction {
+        wallet_address: *wallet_account_info.key,
+        account_guid_hash: *account_guid_hash,
+        instructions: instructions.clone(),
+        dapp,
+        rent_funding_limit,
+    };
+
+    if multisig_op.instructions_executed == 0 {
+        // an op started under an earlier wallet config is closed without running
+        if !multisig_op.approved(&expected_params, &clock)? || !multisig_op.started_under(&wallet) {
+            return collect_remaining_balance(
+                &multisig_op_account_info,
+                &rent_collector_account_info,
+            );
+        }
+        if let Some(prerequisite) = multisig_op.prerequisite {
+            let registry = PendingOps::from_accounts(program_id, accounts)?
+                .filter(|(_, registry)| registry.wallet == *wallet_account_info.key);
+            validate_prerequisite_executed(&registry, &prerequisite)?;
+        }
+    } else if expected_params.hash_with(multisig_op.params_hash_version) != multisig_op.params_hash
+    {
+        msg!("Instructions do not match the ones the dapp transaction was approved with");
+        return Err(WalletError::InvalidSignature.into());
+    }
+
+    let start = usize::from(multisig_op.instructions_executed);
+    let end = start
+        .saturating_add(usize::from(chunk_len))
+        .min(instructions.len());
+    let chunk = instructions[start.min(end)..end].to_vec();
+
+    let bump_seed = validate_balance_account_and_get_seed(
+        balance_account,
+        &wallet.get_balance_account(account_guid_hash)?,
+        program_id,
+    )?;
+    validate_rent_funding(balance_account.key, instructions, rent_funding_limit)?;
+    validate_inner_programs(
+        &dapp.address,
+        &wallet.dapp_inner_programs(&dapp),
+        &chunk,
+        accounts_iter.as_slice(),
+    )?;
+    let token_mint_allowlist_enabled = wallet
+        .get_balance_account(account_guid_hash)?
+        .is_token_mint_allowlist_enabled();
+
+    let starting_lamports = balance_account.lamports();
+    let starting_spl_balances: Vec<SplBalance> = spl_balances(accounts);
+
+    for instruction in chunk.iter() {
+        invoke_signed(
+            &instruction,
+            &accounts,
+            &[&[&account_guid_hash.to_bytes(), &[bump_seed]]],
+        )?;
+    }
+
+    let ending_spl_balances = spl_balances(accounts);
+    if token_mint_allowlist_enabled {
+        validate_token_mints_allowed(
+            &wallet,
+            balance_account.key,
+            account_guid_hash,
+            &starting_spl_balances,
+            &ending_spl_balances,
+        )?;
+    }
+    validate_outflow(
+        balance_account,
+        starting_lamports,
+        &starting_spl_balances,
+        &ending_spl_balances,
+        &MaxOutflow::from_instructions(balance_account.key, &chunk)?,
+    )?;
+    let value_limited = wallet.record_dapp_value(
+        account_guid_hash,
+        &dapp,
+        observed_outflow(
+            balance_account,
+            starting_lamports,
+            &starting_spl_balances,
+            &ending_spl_balances,
+        )?,
+        clock.unix_timestamp,
+    )?;
+    let outflow_recorded = record_outflow_activity(
+        &mut wallet,
+        balance_account,
+        starting_lamports,
+        &starting_spl_balances,
+        &ending_spl_balances,
+    )?;
+    if value_limited || outflow_recorded {
+        Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    }
+
+    if end == instructions.len() {
+        msg!("Executed the last {} dapp instructions", chunk.len());
+        collect_remaining_balance(&multisig_op_account_info, &rent_collector_account_info)
+    } else {
+        msg!(
+            "Executed dapp instructions {} to {} of {}",
+            start,
+            end,
+            instructions.len()
+        );
+        multisig_op.instructions_executed = end as u16;
+        MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())
+    }
+}
+
+/// Sends an instance of an approved template from its balance account. The
+/// template's approval stands in for an op, so only the assistant can do this.
+/// Instances are held to the same outflow checks and dapp value limits as dapp
+/// transactions, so repeated instances can't drain the balance account.
+pub fn execute_template(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    template_slot: SlotId<DAppTemplate>,
+    instruction: &Instruction,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let balance_account = next_account_info(accounts_iter)?;
+    let assistant_account_info = next_account_info(accounts_iter)?;
+
+    let mut wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    if !assistant_account_info.is_signer || *assistant_account_info.key != wallet.assistant.key {
+        msg!("DApp templates can only be executed by the wallet's assistant");
+        return Err(WalletError::InvalidSignature.into());
+    }
+
+    let template = wallet.get_dapp_template(template_slot)?;
+    template.validate_instance(instruction)?;
+    let template_balance_account = wallet.get_balance_account(&template.account_guid_hash)?;
+    let token_mint_allowlist_enabled = template_balance_account.is_token_mint_allowlist_enabled();
+    let bump_seed = validate_balance_account_and_get_seed(
+        balance_account,
+        &template_balance_account,
+        program_id,
+    )?;
+
+    let starting_lamports = balance_account.lamports();
+    let starting_spl_balances = spl_balances(accounts);
+    invoke_signed(
+        instruction,
+        accounts,
+        &[&[&template.account_guid_hash.to_bytes(), &[bump_seed]]],
+    )?;
+    let ending_spl_balances = spl_balances(accounts);
+    if token_mint_allowlist_enabled {
+        validate_token_mints_allowed(
+            &wallet,
+            balance_account.key,
+            &template.account_guid_hash,
+            &starting_spl_balances,
+            &ending_spl_balances,
+        )?;
+    }
+    validate_outflow(
+        balance_account,
+        starting_lamports,
+        &starting_spl_balances,
+        &ending_spl_balances,
+        &MaxOutflow::from_instructions(balance_account.key, &vec![instruction.clone()])?,
+    )?;
+    // instances count against the value limit of the dapp the template sends to
+    let dapp = wallet
+        .dapp_book
+        .filled_slots()
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .find(|entry| {
+            entry.address == template.program_id && wallet.dapp_value_limit(entry).is_some()
+        });
+    let value_limited = match dapp {
+        Some(dapp) => wallet.record_dapp_value(
+            &template.account_guid_hash,
+            &dapp,
+            observed_outflow(
+                balance_account,
+                starting_lamports,
+                &starting_spl_balances,
+                &ending_spl_balances,
+            )?,
+            Clock::get()?.unix_timestamp,
+        )?,
+        None => false,
+    };
+    let outflow_recorded = record_outflow_activity(
+        &mut wallet,
+        balance_account,
+        starting_lamports,
+        &starting_spl_balances,
+        &ending_spl_balances,
+    )?;
+    if value_limited || outflow_recorded {
+        Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    }
+    msg!("Executed dapp template {}", template_slot.value);
+    Ok(())
+}
+
 struct SplBalance {
     account: Pubkey,
+    owner: Pubkey,
     token_mint: Pubkey,
     balance: u64,
 }
@@ -268,12 +956,14 @@ fn test_balance_changes() {
             vec![],
             vec![SplBalance {
                 account,
+                owner,
                 token_mint,
                 balance: 0
             }],
             vec![],
             vec![SplBalance {
                 account,
+                owner,
                 token_mint,
                 balance: 100
             }],
@@ -291,12 +981,14 @@ fn test_balance_changes() {
             vec![],
             vec![SplBalance {
                 account,
+                owner,
                 token_mint,
                 balance: 200
             }],
             vec![],
             vec![SplBalance {
                 account,
+                owner,
                 token_mint,
                 balance: 100
             }],
@@ -310,12 +1002,14 @@ fn test_balance_changes() {
             vec![],
             vec![SplBalance {
                 account: other_account,
+                owner,
                 token_mint,
                 balance: 200
             }],
             vec![],
             vec![SplBalance {
                 account,
+                owner,
                 token_mint,
                 balance: 100
             }],
@@ -323,3 +1017,58 @@ fn test_balance_changes() {
         )
     ];
 }
+
+#[test]
+fn test_max_outflow() {
+    let balance_account = Pubkey::new_unique();
+    let other_account = Pubkey::new_unique();
+    let token_account = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+
+    let max_outflow = MaxOutflow::from_instructions(
+        &balance_account,
+        &vec![
+            solana_program::system_instruction::transfer(&balance_account, &destination, 100),
+            solana_program::system_instruction::transfer(&other_account, &destination, 50),
+            solana_program::system_instruction::create_account(
+                &balance_account,
+                &destination,
+                10,
+                0,
+                &system_program::id(),
+            ),
+            spl_token::instruction::transfer(
+                &spl_token::id(),
+                &token_account,
+                &destination,
+                &balance_account,
+                &[],
+                7,
+            )
+            .unwrap(),
+            spl_token::instruction::approve(
+                &spl_token::id(),
+                &token_account,
+                &destination,
+                &balance_account,
+                &[],
+                7,
+            )
+            .unwrap(),
+            spl_token::instruction::transfer(
+                &spl_token::id(),
+                &other_account,
+                &destination,
+                &other_account,
+                &[],
+                3,
+            )
+            .unwrap(),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(max_outflow.lamports, 110);
+    assert_eq!(max_outflow.tokens_from(&token_account), 14);
+    assert_eq!(max_outflow.tokens_from(&other_account), 0);
+}
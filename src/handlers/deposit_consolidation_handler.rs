@@ -0,0 +1,140 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{
+    next_program_account_info, unpack_wallet, validate_balance_account_and_get_seed,
+};
+use crate::model::balance_account::BalanceAccountGuidHash;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program::invoke_signed;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::id as SPL_TOKEN_ID;
+use spl_token::instruction as spl_instruction;
+use spl_token::state::Account as SPLAccount;
+
+/// Moves everything held by the given deposit sub-addresses into the balance
+/// account. Funds never leave the wallet, so this needs no approval and is
+/// not recorded as activity.
+pub fn handle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+    deposit_indexes: &[u32],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let balance_account = next_account_info(accounts_iter)?;
+    let initiator_account_info = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+    let token_mint = next_account_info(accounts_iter)?;
+
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    wallet.validate_transfer_initiator(initiator_account_info)?;
+    validate_balance_account_and_get_seed(
+        balance_account,
+        &wallet.get_balance_account(account_guid_hash)?,
+        program_id,
+    )?;
+
+    if *token_mint.key == Pubkey::default() {
+        for index in deposit_indexes {
+            let deposit_account = next_account_info(accounts_iter)?;
+            let (deposit_address, bump_seed) =
+                account_guid_hash.find_deposit_address(*index, program_id);
+            if *deposit_account.key != deposit_address {
+                msg!(
+                    "Account is not deposit address {} of the balance account",
+                    index
+                );
+                return Err(WalletError::AccountNotRecognized.into());
+            }
+            let lamports = deposit_account.lamports();
+            if lamports == 0 {
+                continue;
+            }
+            invoke_signed(
+                &system_instruction::transfer(deposit_account.key, balance_account.key, lamports),
+                &[
+                    deposit_account.clone(),
+                    balance_account.clone(),
+                    system_program_account.clone(),
+                ],
+                &[&[
+                    account_guid_hash.to_bytes(),
+                    BalanceAccountGuidHash::DEPOSIT_ADDRESS_SEED,
+                    &index.to_le_bytes(),
+                    &[bump_seed],
+                ]],
+            )?;
+            msg!(
+                "Consolidated {} lamports from deposit address {}",
+                lamports,
+                index
+            );
+        }
+    } else {
+        let balance_token_account = next_account_info(accounts_iter)?;
+        if *balance_token_account.key
+            != get_associated_token_address(balance_account.key, token_mint.key)
+            || *balance_token_account.owner != SPL_TOKEN_ID()
+        {
+            return Err(WalletError::InvalidDestinationTokenAccount.into());
+        }
+        let spl_token_program = next_account_info(accounts_iter)?;
+
+        for index in deposit_indexes {
+            let deposit_account = next_account_info(accounts_iter)?;
+            let deposit_token_account = next_account_info(accounts_iter)?;
+            let (deposit_address, bump_seed) =
+                account_guid_hash.find_deposit_address(*index, program_id);
+            if *deposit_account.key != deposit_address {
+                msg!(
+                    "Account is not deposit address {} of the balance account",
+                    index
+                );
+                return Err(WalletError::AccountNotRecognized.into());
+            }
+            if *deposit_token_account.key
+                != get_associated_token_address(&deposit_address, token_mint.key)
+            {
+                return Err(WalletError::InvalidSourceTokenAccount.into());
+            }
+            let amount = SPLAccount::unpack(&deposit_token_account.data.borrow())?.amount;
+            if amount == 0 {
+                continue;
+            }
+            invoke_signed(
+                &spl_instruction::transfer(
+                    &SPL_TOKEN_ID(),
+                    deposit_token_account.key,
+                    balance_token_account.key,
+                    deposit_account.key,
+                    &[],
+                    amount,
+                )?,
+                &[
+                    deposit_token_account.clone(),
+                    balance_token_account.clone(),
+                    deposit_account.clone(),
+                    spl_token_program.clone(),
+                ],
+                &[&[
+                    account_guid_hash.to_bytes(),
+                    BalanceAccountGuidHash::DEPOSIT_ADDRESS_SEED,
+                    &index.to_le_bytes(),
+                    &[bump_seed],
+                ]],
+            )?;
+            msg!(
+                "Consolidated {} tokens from deposit address {}",
+                amount,
+                index
+            );
+        }
+    }
+
+    Ok(())
+}
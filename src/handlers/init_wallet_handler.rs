@@ -1,17 +1,21 @@
 use crate::handlers::utils::next_program_account_info;
 use crate::instruction::InitialWalletConfig;
+use crate::model::program_config::PolicyLimits;
 use crate::model::signer::Signer;
 use crate::model::wallet::Wallet;
 use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::clock::Clock;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{IsInitialized, Pack};
 use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
 
 pub fn handle(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     update: &InitialWalletConfig,
+    limits: &PolicyLimits,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
@@ -27,7 +31,10 @@ pub fn handle(
     wallet.assistant = Signer {
         key: *assistant_account_info.key,
     };
-    wallet.initialize(update)?;
+    wallet.initialize(update, limits)?;
+    let now = Clock::get()?.unix_timestamp;
+    wallet.activity.period_start = now;
+    wallet.start_tracking_signer_activity(now);
     Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
 
     Ok(())
@@ -0,0 +1,237 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{
+    finalize_pinned_multisig_op, get_approval_timeout, get_clock, next_multisig_op_account_info,
+    next_program_account_info, start_multisig_transfer_op, transfer_sol_checked, unpack_wallet,
+    validate_balance_account_and_get_seed,
+};
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::policy::{Destination, PolicyRequest};
+use crate::model::program_config::PolicyLimits;
+use crate::model::wallet::Wallet;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program::invoke_signed;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::id as SPL_TOKEN_ID;
+use spl_token::instruction as spl_instruction;
+use spl_token::state::Account as SPLAccount;
+use std::time::Duration;
+
+/// Both balance accounts have to be different balance accounts of the
+/// wallet, and the destination account must be the PDA of its guid hash.
+fn validate_balance_accounts(
+    program_id: &Pubkey,
+    wallet: &Wallet,
+    source_account_guid_hash: &BalanceAccountGuidHash,
+    destination_account_guid_hash: &BalanceAccountGuidHash,
+    destination_account: &AccountInfo,
+) -> ProgramResult {
+    if source_account_guid_hash == destination_account_guid_hash {
+        msg!("Source and destination balance accounts must be different");
+        return Err(WalletError::InvalidInternalTransfer.into());
+    }
+    let (destination_pda, _) = wallet
+        .get_balance_account(destination_account_guid_hash)?
+        .find_address(program_id)?;
+    if destination_pda != *destination_account.key {
+        msg!("Destination account is not the destination balance account");
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+    Ok(())
+}
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    source_account_guid_hash: &BalanceAccountGuidHash,
+    destination_account_guid_hash: &BalanceAccountGuidHash,
+    amount: u64,
+    approval_timeout: Option<Duration>,
+    limits: &PolicyLimits,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let source_account = next_account_info(accounts_iter)?;
+    let destination_account = next_account_info(accounts_iter)?;
+    let initiator_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+    let token_mint = next_account_info(accounts_iter)?;
+
+    let mut wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    let balance_account = wallet.get_balance_account(source_account_guid_hash)?;
+    validate_balance_account_and_get_seed(source_account, &balance_account, program_id)?;
+    validate_balance_accounts(
+        program_id,
+        &wallet,
+        source_account_guid_hash,
+        destination_account_guid_hash,
+        destination_account,
+    )?;
+
+    wallet.validate_transfer_initiator(initiator_account_info)?;
+
+    if *token_mint.key != Pubkey::default()
+        && !wallet.token_mint_allowed(&balance_account, token_mint.key)
+    {
+        msg!("Token mint is not allowed for this balance account");
+        return Err(WalletError::TokenMintNotAllowed.into());
+    }
+    balance_account.validate_transfers_enabled(token_mint.key)?;
+
+    let mut policy = wallet.evaluate_policy(
+        &balance_account,
+        &PolicyRequest {
+            amount,
+            destination: Destination::BalanceAccount,
+            now: clock.unix_timestamp,
+        },
+    )?;
+    policy.approval_timeout =
+        get_approval_timeout(limits, policy.approval_timeout, approval_timeout)?;
+
+    wallet.reserve_outstanding_transfer_value(source_account_guid_hash, amount)?;
+
+    start_multisig_transfer_op(
+        &multisig_op_account_info,
+        &wallet,
+        policy,
+        initiator_account_info,
+        clock,
+        MultisigOpParams::InternalTransfer {
+            wallet_address: *wallet_account_info.key,
+            source_account_guid_hash: *source_account_guid_hash,
+            destination_account_guid_hash: *destination_account_guid_hash,
+            amount,
+            token_mint: *token_mint.key,
+        },
+    )?;
+
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    source_account_guid_hash: &BalanceAccountGuidHash,
+    destination_account_guid_hash: &BalanceAccountGuidHash,
+    amount: u64,
+    token_mint: Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let source_account = next_account_info(accounts_iter)?;
+    let destination_account = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+    let rent_collector_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    if system_program_account.key != &system_program::id() {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    finalize_pinned_multisig_op(
+        &multisig_op_account_info,
+        &wallet_account_info,
+        &rent_collector_account_info,
+        clock,
+        MultisigOpParams::InternalTransfer {
+            wallet_address: *wallet_account_info.key,
+            source_account_guid_hash: *source_account_guid_hash,
+            destination_account_guid_hash: *destination_account_guid_hash,
+            amount,
+            token_mint,
+        },
+        || -> ProgramResult {
+            let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+            let balance_account = wallet.get_balance_account(source_account_guid_hash)?;
+            let bump_seed = validate_balance_account_and_get_seed(
+                source_account,
+                &balance_account,
+                program_id,
+            )?;
+            validate_balance_accounts(
+                program_id,
+                &wallet,
+                source_account_guid_hash,
+                destination_account_guid_hash,
+                destination_account,
+            )?;
+            balance_account.validate_transfers_enabled(&token_mint)?;
+
+            if token_mint.to_bytes() != [0; 32] {
+                let source_token_account = next_account_info(accounts_iter)?;
+                let source_token_account_key =
+                    get_associated_token_address(source_account.key, &token_mint);
+                if *source_token_account.key != source_token_account_key {
+                    return Err(WalletError::InvalidSourceTokenAccount.into());
+                }
+                let source_token_account_data =
+                    SPLAccount::unpack(&source_token_account.data.borrow())?;
+                if source_token_account_data.amount < amount {
+                    msg!(
+                        "Source token account only has {} tokens of {} requested",
+                        source_token_account_data.amount,
+                        amount
+                    );
+                    return Err(WalletError::InsufficientBalance.into());
+                }
+                // the destination balance account's token account has to exist already
+                let destination_token_account = next_account_info(accounts_iter)?;
+                let destination_token_account_key =
+                    get_associated_token_address(destination_account.key, &token_mint);
+                if *destination_token_account.key != destination_token_account_key
+                    || *destination_token_account.owner != SPL_TOKEN_ID()
+                {
+                    return Err(WalletError::InvalidDestinationTokenAccount.into());
+                }
+                let spl_token_program = next_account_info(accounts_iter)?;
+
+                invoke_signed(
+                    &spl_instruction::transfer(
+                        &SPL_TOKEN_ID(),
+                        &source_token_account_key,
+                        &destination_token_account_key,
+                        source_account.key,
+                        &[],
+                        amount,
+                    )?,
+                    &[
+                        source_token_account.clone(),
+                        destination_token_account.clone(),
+                        source_account.clone(),
+                        spl_token_program.clone(),
+                    ],
+                    &[&[&source_account_guid_hash.to_bytes(), &[bump_seed]]],
+                )?;
+            } else {
+                transfer_sol_checked(
+                    source_account.clone(),
+                    source_account_guid_hash,
+                    bump_seed,
+                    system_program_account.clone(),
+                    destination_account.clone(),
+                    amount,
+                )?;
+            }
+            // the funds leave the source balance account, so they count toward
+            // the wallet's outflows even though the wallet still holds them
+            let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+            wallet.activity.record_transfer(&token_mint, amount)?;
+            Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+            Ok(())
+        },
+    )?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.release_outstanding_transfer_value(source_account_guid_hash, amount)?;
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{
+    collect_remaining_balance, get_clock, next_multisig_op_account_info, next_program_account_info,
+    validate_rent_collector,
+};
+use crate::model::multisig_op::MultisigOp;
+use crate::model::wallet::Wallet;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+/// Closes an op that expired without reaching a final disposition, or that was
+/// approved but lapsed after its one renewal, releasing the outstanding
+/// transfer value it held. Anyone can do so once it expired.
+pub fn handle(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let rent_collector_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    validate_rent_collector(
+        rent_collector_account_info,
+        wallet_account_info.key,
+        program_id,
+    )?;
+
+    let mut multisig_op = MultisigOp::unpack(&multisig_op_account_info.data.borrow())?;
+    multisig_op.expire(&clock)?;
+
+    if let Some(held_transfer_value) = multisig_op.held_transfer_value {
+        if held_transfer_value.wallet_address != *wallet_account_info.key {
+            msg!("Operation holds transfer value of another wallet");
+            return Err(WalletError::AccountNotRecognized.into());
+        }
+        let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+        wallet.release_outstanding_transfer_value(
+            &held_transfer_value.account_guid_hash,
+            held_transfer_value.amount,
+        )?;
+        Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    }
+
+    collect_remaining_balance(multisig_op_account_info, rent_collector_account_info)
+}
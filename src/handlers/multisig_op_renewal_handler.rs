@@ -0,0 +1,42 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{get_clock, next_program_account_info};
+use crate::model::multisig_op::MultisigOp;
+use crate::model::wallet::Wallet;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::Hash;
+use solana_program::msg;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use std::time::Duration;
+
+pub fn handle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    params_hash: &Hash,
+    extension: Duration,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let renewer_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    if !renewer_account_info.is_signer {
+        return Err(WalletError::InvalidSignature.into());
+    }
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    if !wallet
+        .get_config_approvers_keys()
+        .contains(renewer_account_info.key)
+    {
+        msg!("Only a config approver can renew an operation");
+        return Err(WalletError::InvalidApprover.into());
+    }
+
+    let mut multisig_op = MultisigOp::unpack(&multisig_op_account_info.data.borrow())?;
+    multisig_op.renew(params_hash, renewer_account_info.key, extension, &clock)?;
+    MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
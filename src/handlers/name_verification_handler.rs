@@ -0,0 +1,52 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{next_program_account_info, unpack_wallet};
+use crate::instruction::NamedEntry;
+use crate::model::address_book::AddressBookEntryNameHash;
+use crate::model::balance_account::BalanceAccountNameHash;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::Hash;
+use solana_program::msg;
+use solana_program::pubkey::Pubkey;
+
+/// Succeeds only if the name hashes to the entry's stored name hash, in which
+/// case the name is logged for indexers. Nothing is written.
+pub fn handle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    entry: &NamedEntry,
+    name: &str,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    match entry {
+        NamedEntry::BalanceAccount(account_guid_hash) => {
+            let balance_account = wallet.get_balance_account(account_guid_hash)?;
+            if balance_account.name_hash != BalanceAccountNameHash::of_name(name) {
+                return Err(WalletError::NameHashMismatch.into());
+            }
+            msg!(
+                "BalanceAccountName: [{}] [{}]",
+                Hash::new_from_array(*account_guid_hash.to_bytes()),
+                name
+            );
+        }
+        NamedEntry::AddressBookEntry(address) => {
+            let name_hash = AddressBookEntryNameHash::of_name(name);
+            if wallet
+                .address_book
+                .find_by(|address_book_entry| {
+                    address_book_entry.address == *address
+                        && address_book_entry.name_hash == name_hash
+                })
+                .is_none()
+            {
+                return Err(WalletError::NameHashMismatch.into());
+            }
+            msg!("AddressBookEntryName: [{}] [{}]", address, name);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{next_program_account_info, unpack_wallet};
+use crate::model::multisig_op::MultisigOp;
+use crate::model::wallet::Wallet;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::Hash;
+use solana_program::msg;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_program::system_program;
+use solana_program::sysvar::Sysvar;
+
+pub fn handle(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_account_info(accounts_iter)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let fee_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+
+    if system_program_account.key != &system_program::id() {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    // only the wallet's own signers and assistant may spend its fee account
+    wallet.validate_config_initiator(initiator_account_info)?;
+
+    let (fee_account_address, bump_seed) =
+        Wallet::find_fee_account_address(wallet_account_info.key, program_id);
+    if *fee_account_info.key != fee_account_address {
+        msg!("Fee account does not match the expected address");
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            fee_account_info.key,
+            multisig_op_account_info.key,
+            Rent::get()?.minimum_balance(MultisigOp::LEN),
+            MultisigOp::LEN as u64,
+            program_id,
+        ),
+        &[
+            fee_account_info.clone(),
+            multisig_op_account_info.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[
+            wallet_account_info.key.as_ref(),
+            Wallet::FEE_ACCOUNT_SEED,
+            &[bump_seed],
+        ]],
+    )
+}
+
+pub fn handle_for_params(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    params_hash: &Hash,
+    nonce: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_account_info(accounts_iter)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let payer_account_info = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+
+    if system_program_account.key != &system_program::id() {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+    Wallet::unpack(&wallet_account_info.data.borrow())?;
+
+    let (multisig_op_address, bump_seed) =
+        MultisigOp::find_address(wallet_account_info.key, params_hash, nonce, program_id);
+    if *multisig_op_account_info.key != multisig_op_address {
+        msg!("Multisig op account does not match the address derived from the op's params");
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+    if multisig_op_account_info.owner == program_id {
+        msg!("An op with these params and nonce has already been started");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_account_info.key,
+            multisig_op_account_info.key,
+            Rent::get()?.minimum_balance(MultisigOp::LEN),
+            MultisigOp::LEN as u64,
+            program_id,
+        ),
+        &[
+            payer_account_info.clone(),
+            multisig_op_account_info.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[
+            wallet_account_info.key.as_ref(),
+            MultisigOp::ADDRESS_SEED,
+            params_hash.as_ref(),
+            &nonce.to_le_bytes(),
+            &[bump_seed],
+        ]],
+    )?;
+
+    // bind the account to the op's params until the op is started in it
+    let mut multisig_op = MultisigOp::unpack_unchecked(&multisig_op_account_info.data.borrow())?;
+    multisig_op.params_hash = *params_hash;
+    MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())
+}
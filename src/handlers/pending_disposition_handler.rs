@@ -0,0 +1,182 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{
+    collect_remaining_balance, get_clock, next_program_account_info, record_signer_activity,
+};
+use crate::model::multisig_op::{ApprovalDisposition, DenialReason, MultisigOp};
+use crate::model::pending_disposition::PendingDisposition;
+use crate::model::signer_directory::SignerDirectory;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::Hash;
+use solana_program::msg;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_program::system_program;
+use solana_program::sysvar::Sysvar;
+
+pub fn submit(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    disposition: ApprovalDisposition,
+    params_hash: Hash,
+    denial_reason: DenialReason,
+    reason_hash: Option<Hash>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let pending_disposition_account_info = next_account_info(accounts_iter)?;
+    let approver_account_info = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+    let signer_directory = SignerDirectory::from_accounts(program_id, accounts)?;
+
+    if system_program_account.key != &system_program::id() {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    let (pending_disposition_address, bump_seed) = PendingDisposition::find_address(
+        multisig_op_account_info.key,
+        approver_account_info.key,
+        program_id,
+    );
+    if *pending_disposition_account_info.key != pending_disposition_address {
+        msg!("Pending disposition account does not match the expected address");
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+    if pending_disposition_account_info.owner == program_id {
+        msg!("Approver already submitted a disposition on this operation");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // record the disposition on a copy of the op, so that one that would
+    // fail to apply is rejected now rather than when it is collected
+    let mut multisig_op = MultisigOp::unpack(&multisig_op_account_info.data.borrow())?;
+    if multisig_op.params_hash != params_hash {
+        return Err(WalletError::InvalidSignature.into());
+    }
+    multisig_op.validate_and_record_approval_disposition(
+        approver_account_info,
+        disposition,
+        denial_reason,
+        reason_hash,
+        signer_directory.as_ref(),
+        &clock,
+    )?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            approver_account_info.key,
+            pending_disposition_account_info.key,
+            Rent::get()?.minimum_balance(PendingDisposition::LEN),
+            PendingDisposition::LEN as u64,
+            program_id,
+        ),
+        &[
+            approver_account_info.clone(),
+            pending_disposition_account_info.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[
+            multisig_op_account_info.key.as_ref(),
+            PendingDisposition::SEED,
+            approver_account_info.key.as_ref(),
+            &[bump_seed],
+        ]],
+    )?;
+
+    PendingDisposition::pack(
+        PendingDisposition {
+            is_initialized: true,
+            multisig_op: *multisig_op_account_info.key,
+            approver: *approver_account_info.key,
+            params_hash,
+            disposition,
+            denial_reason,
+            reason_hash,
+        },
+        &mut pending_disposition_account_info.data.borrow_mut(),
+    )
+}
+
+/// Applies submitted dispositions to the op and closes their accounts. Once
+/// the op account has been closed, the accounts are only closed.
+pub fn collect(program_id: &Pubkey, accounts: &[AccountInfo], dispositions: u8) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_account_info(accounts_iter)?;
+    let wallet_account_info = next_account_info(accounts_iter)?;
+    let pending_dispositions = (0..dispositions)
+        .map(|_| {
+            Ok((
+                next_account_info(accounts_iter)?,
+                next_account_info(accounts_iter)?,
+            ))
+        })
+        .collect::<Result<Vec<_>, ProgramError>>()?;
+    let clock = get_clock(accounts_iter)?;
+    let signer_directory = SignerDirectory::from_accounts(program_id, accounts)?;
+
+    let op_closed =
+        multisig_op_account_info.lamports() == 0 && multisig_op_account_info.data_is_empty();
+    if !op_closed && multisig_op_account_info.owner != program_id {
+        msg!("Account does not belong to the program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mut multisig_op = if op_closed {
+        None
+    } else {
+        Some(MultisigOp::unpack(&multisig_op_account_info.data.borrow())?)
+    };
+
+    let wallet_address = multisig_op
+        .as_ref()
+        .map(|multisig_op| multisig_op.wallet_address);
+    if wallet_address.map_or(false, |wallet_address| {
+        wallet_address != *wallet_account_info.key
+    }) {
+        msg!("Wallet account does not match the op's wallet");
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+    let mut approvers: Vec<Pubkey> = Vec::new();
+    for (pending_disposition_account_info, approver_account_info) in pending_dispositions {
+        if pending_disposition_account_info.owner != program_id {
+            msg!("Account does not belong to the program");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let pending_disposition =
+            PendingDisposition::unpack(&pending_disposition_account_info.data.borrow())?;
+        if pending_disposition.multisig_op != *multisig_op_account_info.key
+            || pending_disposition.approver != *approver_account_info.key
+        {
+            msg!("Pending disposition was submitted for another operation or approver");
+            return Err(WalletError::AccountNotRecognized.into());
+        }
+
+        if let Some(multisig_op) = multisig_op.as_mut() {
+            multisig_op.record_pending_disposition(
+                &pending_disposition,
+                signer_directory.as_ref(),
+                &clock,
+            )?;
+            approvers.push(pending_disposition.approver);
+        }
+        collect_remaining_balance(pending_disposition_account_info, approver_account_info)?;
+    }
+
+    if let Some(multisig_op) = multisig_op {
+        MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+    }
+    match wallet_address {
+        Some(wallet_address) => record_signer_activity(
+            program_id,
+            accounts,
+            &wallet_address,
+            &approvers,
+            clock.unix_timestamp,
+        ),
+        None => Ok(()),
+    }
+}
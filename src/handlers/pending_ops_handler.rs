@@ -0,0 +1,213 @@
+use crate::error::WalletError;
+use crate::handlers::utils::next_program_account_info;
+use crate::instruction::ProgramInstruction;
+use crate::model::multisig_op::MultisigOp;
+use crate::model::pending_ops::{PendingOp, PendingOps};
+use crate::model::wallet::Wallet;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::Hash;
+use solana_program::msg;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_program::system_program;
+use solana_program::sysvar::Sysvar;
+
+pub fn init(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let registry_account_info = next_account_info(accounts_iter)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let payer_account_info = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+
+    if system_program_account.key != &system_program::id() {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+    Wallet::unpack(&wallet_account_info.data.borrow())?;
+
+    let (registry_address, bump_seed) =
+        PendingOps::find_address(wallet_account_info.key, program_id);
+    if registry_address != *registry_account_info.key {
+        msg!("Pending ops registry account does not match the expected address");
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_account_info.key,
+            &registry_address,
+            Rent::get()?.minimum_balance(PendingOps::LEN),
+            PendingOps::LEN as u64,
+            program_id,
+        ),
+        &[
+            payer_account_info.clone(),
+            registry_account_info.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[
+            wallet_account_info.key.as_ref(),
+            PendingOps::SEED,
+            &[bump_seed],
+        ]],
+    )?;
+
+    PendingOps::pack(
+        PendingOps::new(wallet_account_info.key, bump_seed),
+        &mut registry_account_info.data.borrow_mut(),
+    )?;
+
+    Ok(())
+}
+
+pub fn cleanup(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let registry_account_info = next_program_account_info(accounts_iter, program_id)?;
+
+    let (_, mut registry) =
+        PendingOps::from_accounts(program_id, std::slice::from_ref(registry_account_info))?
+            .ok_or(WalletError::AccountNotRecognized)?;
+
+    for op_account_info in accounts_iter {
+        let pending = match registry
+            .ops
+            .find_by(|pending| pending.multisig_op == *op_account_info.key)
+        {
+            Some((_, pending)) => pending,
+            None => continue,
+        };
+        if pending_op_in(program_id, op_account_info) != Some(pending.params_hash) {
+            registry.remove(op_account_info.key);
+            msg!("Removed {} from the pending ops", op_account_info.key);
+        }
+    }
+
+    PendingOps::pack(registry, &mut registry_account_info.data.borrow_mut())
+}
+
+/// Whether the instruction starts or renews the op in its first account.
+pub fn starts_op(instruction: &ProgramInstruction) -> bool {
+    match instruction {
+        ProgramInstruction::RenewMultisigOp { .. } => true,
+        ProgramInstruction::ValidateOnly { .. } => false,
+        _ => MultisigOp::space_required(instruction).is_some(),
+    }
+}
+
+/// The params hash and prerequisite of the op in the first account, if it is
+/// approved and so would be executed by an instruction closing its account.
+pub fn executable_op(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> Result<Option<(Hash, Option<Hash>)>, ProgramError> {
+    match accounts.first() {
+        Some(op_account_info)
+            if op_account_info.owner == program_id
+                && op_account_info.data_len() == MultisigOp::LEN =>
+        {
+            Ok(MultisigOp::executable_in_place(
+                &op_account_info.data.borrow(),
+                Clock::get()?.unix_timestamp,
+            ))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Makes the op just started in the first account depend on the op with the
+/// given params hash.
+pub fn set_prerequisite(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    prerequisite: &Hash,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let mut multisig_op = MultisigOp::unpack(&multisig_op_account_info.data.borrow())?;
+    multisig_op.prerequisite = Some(*prerequisite);
+    MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())
+}
+
+/// Brings the wallet's pending ops registry up to date after an instruction
+/// went through, if the registry is among the accounts. Ops are always in the
+/// first account: an op an instruction started or renewed for the registry's
+/// wallet is recorded, and a listed op whose account was closed is removed.
+///
+/// `executable` is what `executable_op` returned before the instruction ran.
+/// If the instruction closed that op's account, the op was executed: it is
+/// remembered as such, and its prerequisite, if any, must have been.
+pub fn record(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    starts_op: bool,
+    executable: Option<(Hash, Option<Hash>)>,
+) -> ProgramResult {
+    let (op_account_info, wallet_account_info) = match accounts {
+        [op_account_info, wallet_account_info, ..] => (op_account_info, wallet_account_info),
+        _ => return Ok(()),
+    };
+    let executed_op = executable.filter(|_| op_account_info.data_is_empty());
+    let registry = PendingOps::from_accounts(program_id, accounts)?
+        .filter(|(_, registry)| registry.wallet == *wallet_account_info.key);
+
+    if let Some((_, Some(prerequisite))) = executed_op {
+        validate_prerequisite_executed(&registry, &prerequisite)?;
+    }
+
+    let (registry_account_info, mut registry) = match registry {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+    let mut changed = match pending_op_in(program_id, op_account_info) {
+        None => registry.remove(op_account_info.key),
+        Some(_) if starts_op => {
+            let multisig_op = MultisigOp::unpack(&op_account_info.data.borrow())?;
+            registry.record(PendingOp {
+                multisig_op: *op_account_info.key,
+                params_hash: multisig_op.params_hash,
+                expires_at: multisig_op.expires_at,
+            })?;
+            true
+        }
+        Some(_) => false,
+    };
+    if let Some((params_hash, _)) = executed_op {
+        registry.record_executed(params_hash);
+        changed = true;
+    }
+    if changed {
+        PendingOps::pack(registry, &mut registry_account_info.data.borrow_mut())?;
+    }
+    Ok(())
+}
+
+/// Fails unless the registry has the prerequisite recorded as executed.
+pub fn validate_prerequisite_executed(
+    registry: &Option<(&AccountInfo, PendingOps)>,
+    prerequisite: &Hash,
+) -> ProgramResult {
+    if !matches!(registry, Some((_, registry)) if registry.was_executed(prerequisite)) {
+        msg!(
+            "Operation can't be executed before its prerequisite {}",
+            prerequisite
+        );
+        return Err(WalletError::PrerequisiteNotExecuted.into());
+    }
+    Ok(())
+}
+
+/// The params hash of the op held by the given account, or `None` if the
+/// account doesn't hold an initialized op.
+fn pending_op_in(program_id: &Pubkey, op_account_info: &AccountInfo) -> Option<Hash> {
+    if op_account_info.owner != program_id || op_account_info.data_is_empty() {
+        return None;
+    }
+    MultisigOp::unpack(&op_account_info.data.borrow())
+        .ok()
+        .map(|multisig_op| multisig_op.params_hash)
+}
@@ -0,0 +1,30 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{next_program_account_info, unpack_wallet};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::Hash;
+use solana_program::msg;
+use solana_program::pubkey::Pubkey;
+
+/// Succeeds only if the wallet's live configuration still hashes to the
+/// policy manifest hash that was signed off on. Nothing is written.
+pub fn handle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    manifest_hash: &Hash,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    let live_manifest_hash = wallet.policy_manifest_hash();
+    if live_manifest_hash != *manifest_hash {
+        msg!(
+            "Wallet policy manifest hash is {}, not {}",
+            live_manifest_hash,
+            manifest_hash
+        );
+        return Err(WalletError::PolicyManifestMismatch.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use crate::error::WalletError;
+use crate::handlers::utils::next_program_account_info;
+use crate::instruction::ProgramConfigUpdate;
+use crate::model::program_config::ProgramConfig;
+use crate::model::wallet::Wallet;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::bpf_loader_upgradeable;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_program::system_program;
+use solana_program::sysvar::Sysvar;
+
+/// Reads the upgrade authority from the header of a bincode-encoded
+/// `UpgradeableLoaderState::ProgramData` account.
+fn get_upgrade_authority(
+    program_id: &Pubkey,
+    program_data_account_info: &AccountInfo,
+) -> Result<Option<Pubkey>, ProgramError> {
+    let (program_data_address, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    if *program_data_account_info.key != program_data_address
+        || *program_data_account_info.owner != bpf_loader_upgradeable::id()
+    {
+        msg!("Program data account does not belong to this program");
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    let data = program_data_account_info.data.borrow();
+    // 4 bytes for the enum tag, 8 for the deployment slot, 1 for the option tag
+    // followed by the authority
+    let header = data
+        .get(..4 + 8 + 1 + 32)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    if header[..4] != 3u32.to_le_bytes() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(match header[12] {
+        0 => None,
+        1 => Some(Pubkey::new(&header[13..])),
+        _ => return Err(ProgramError::InvalidAccountData),
+    })
+}
+
+fn apply_update(program_config: &mut ProgramConfig, update: &ProgramConfigUpdate) -> ProgramResult {
+    if let Some(min_approval_timeout) = update.min_approval_timeout {
+        program_config.min_approval_timeout = min_approval_timeout;
+    }
+    if let Some(max_approval_timeout) = update.max_approval_timeout {
+        program_config.max_approval_timeout = max_approval_timeout;
+    }
+    if program_config.min_approval_timeout.as_secs() == 0
+        || program_config.min_approval_timeout > program_config.max_approval_timeout
+    {
+        msg!("Program approval timeout limits are out of range");
+        return Err(WalletError::InvalidApprovalTimeout.into());
+    }
+    if let Some(max_signers) = update.max_signers {
+        if max_signers == 0 || usize::from(max_signers) > Wallet::MAX_SIGNERS {
+            msg!("Max signers must be between 1 and {}", Wallet::MAX_SIGNERS);
+            return Err(WalletError::InvalidPolicyLimit.into());
+        }
+        program_config.max_signers = max_signers;
+    }
+    if let Some(max_balance_accounts) = update.max_balance_accounts {
+        if max_balance_accounts == 0
+            || usize::from(max_balance_accounts) > Wallet::MAX_BALANCE_ACCOUNTS
+        {
+            msg!(
+                "Max balance accounts must be between 1 and {}",
+                Wallet::MAX_BALANCE_ACCOUNTS
+            );
+            return Err(WalletError::InvalidPolicyLimit.into());
+        }
+        program_config.max_balance_accounts = max_balance_accounts;
+    }
+    if let Some(fee_collector) = update.fee_collector {
+        program_config.fee_collector = fee_collector;
+    }
+    if let Some(paused) = update.paused {
+        program_config.paused = paused;
+    }
+    program_config
+        .migration_programs
+        .retain(|migration_program| !update.remove_migration_programs.contains(migration_program));
+    for migration_program in &update.add_migration_programs {
+        if !program_config
+            .migration_programs
+            .contains(migration_program)
+        {
+            program_config.migration_programs.push(*migration_program);
+        }
+    }
+    if program_config.migration_programs.len() > ProgramConfig::MAX_MIGRATION_PROGRAMS {
+        msg!(
+            "There can be at most {} migration programs",
+            ProgramConfig::MAX_MIGRATION_PROGRAMS
+        );
+        return Err(WalletError::InvalidPolicyLimit.into());
+    }
+    Ok(())
+}
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    update: &ProgramConfigUpdate,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let program_config_account_info = next_account_info(accounts_iter)?;
+    let program_data_account_info = next_account_info(accounts_iter)?;
+    let authority_account_info = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+
+    if system_program_account.key != &system_program::id() {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    if !authority_account_info.is_signer
+        || get_upgrade_authority(program_id, program_data_account_info)?
+            != Some(*authority_account_info.key)
+    {
+        msg!("Program config can only be created by the program upgrade authority");
+        return Err(WalletError::InvalidProgramConfigAuthority.into());
+    }
+
+    let (program_config_address, bump_seed) = ProgramConfig::find_address(program_id);
+    if program_config_address != *program_config_account_info.key {
+        msg!("Program config account does not match the expected address");
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority_account_info.key,
+            &program_config_address,
+            Rent::get()?.minimum_balance(ProgramConfig::LEN),
+            ProgramConfig::LEN as u64,
+            program_id,
+        ),
+        &[
+            authority_account_info.clone(),
+            program_config_account_info.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[ProgramConfig::SEED, &[bump_seed]]],
+    )?;
+
+    let mut program_config = ProgramConfig::new(authority_account_info.key, bump_seed);
+    apply_update(&mut program_config, update)?;
+    ProgramConfig::pack(
+        program_config,
+        &mut program_config_account_info.data.borrow_mut(),
+    )?;
+
+    Ok(())
+}
+
+pub fn update(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    update: &ProgramConfigUpdate,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let program_config_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let authority_account_info = next_account_info(accounts_iter)?;
+
+    let mut program_config = ProgramConfig::from_accounts(
+        program_id,
+        std::slice::from_ref(program_config_account_info),
+    )?
+    .ok_or(WalletError::AccountNotRecognized)?;
+
+    if !authority_account_info.is_signer || program_config.authority != *authority_account_info.key
+    {
+        return Err(WalletError::InvalidProgramConfigAuthority.into());
+    }
+
+    apply_update(&mut program_config, update)?;
+    ProgramConfig::pack(
+        program_config,
+        &mut program_config_account_info.data.borrow_mut(),
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use crate::handlers::utils::{get_clock, next_program_account_info};
+use crate::model::wallet::Wallet;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use std::time::Duration;
+
+/// Logs when each of the wallet's signers last gave a disposition, warning
+/// about those inactive for more than `inactive_after_days`. Nothing is written.
+pub fn handle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    inactive_after_days: u16,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let clock = get_clock(accounts_iter)?;
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let period = Duration::from_secs(u64::from(inactive_after_days) * 24 * 60 * 60);
+    for (slot_id, signer) in wallet.signers.filled_slots() {
+        match wallet.signer_activity.get(slot_id) {
+            Some(at) => msg!(
+                "Signer {} in slot {}: last active at {}",
+                signer.key,
+                slot_id.value,
+                at
+            ),
+            None => msg!(
+                "Signer {} in slot {}: no activity recorded",
+                signer.key,
+                slot_id.value
+            ),
+        }
+        if wallet
+            .signer_activity
+            .is_inactive(slot_id, clock.unix_timestamp, period)
+        {
+            msg!(
+                "Inactivity warning: signer {} has been inactive for more than {} days",
+                signer.key,
+                inactive_after_days
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use crate::error::WalletError;
+use crate::handlers::utils::next_program_account_info;
+use crate::instruction::SignerDirectoryUpdate;
+use crate::model::signer_directory::SignerDirectory;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program::invoke_signed;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_program::system_program;
+use solana_program::sysvar::Sysvar;
+
+pub fn init(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let directory_account_info = next_account_info(accounts_iter)?;
+    let authority_account_info = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+
+    if system_program_account.key != &system_program::id() {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    if !authority_account_info.is_signer {
+        msg!("Signer directory can only be created by its authority");
+        return Err(WalletError::InvalidSignerDirectory.into());
+    }
+
+    let (directory_address, bump_seed) =
+        SignerDirectory::find_address(authority_account_info.key, program_id);
+    if directory_address != *directory_account_info.key {
+        msg!("Signer directory account does not match the expected address");
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority_account_info.key,
+            &directory_address,
+            Rent::get()?.minimum_balance(SignerDirectory::LEN),
+            SignerDirectory::LEN as u64,
+            program_id,
+        ),
+        &[
+            authority_account_info.clone(),
+            directory_account_info.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[
+            authority_account_info.key.as_ref(),
+            SignerDirectory::SEED,
+            &[bump_seed],
+        ]],
+    )?;
+
+    SignerDirectory::pack(
+        SignerDirectory::new(authority_account_info.key, bump_seed),
+        &mut directory_account_info.data.borrow_mut(),
+    )?;
+
+    Ok(())
+}
+
+pub fn update(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    update: &SignerDirectoryUpdate,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let directory_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let authority_account_info = next_account_info(accounts_iter)?;
+
+    let (_, mut directory) =
+        SignerDirectory::from_accounts(program_id, std::slice::from_ref(directory_account_info))?
+            .ok_or(WalletError::AccountNotRecognized)?;
+
+    if !authority_account_info.is_signer || directory.authority != *authority_account_info.key {
+        msg!("Signer directory can only be updated by its authority");
+        return Err(WalletError::InvalidSignerDirectory.into());
+    }
+
+    directory.update(update)?;
+    SignerDirectory::pack(directory, &mut directory_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
@@ -1,16 +1,22 @@
 use crate::error::WalletError;
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    start_multisig_transfer_op, transfer_sol_checked, validate_balance_account_and_get_seed,
+    finalize_pinned_multisig_op, get_approval_timeout, get_clock, next_multisig_op_account_info,
+    next_program_account_info, start_multisig_transfer_op, transfer_sol_checked, unpack_wallet,
+    validate_balance_account_and_get_seed,
 };
 use crate::model::address_book::AddressBookEntryNameHash;
+use crate::model::address_book_page::AddressBookPage;
 use crate::model::balance_account::BalanceAccountGuidHash;
-use crate::model::multisig_op::MultisigOpParams;
+use crate::model::multisig_op::{AmountSpec, AtaRentPayer, MultisigOpParams};
+use crate::model::policy::{Destination, PolicyRequest};
+use crate::model::program_config::{PolicyLimits, ProgramConfig};
 use crate::model::wallet::Wallet;
 use solana_program::account_info::{next_account_info, AccountInfo};
 use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::Hash;
 use solana_program::instruction::{AccountMeta, Instruction};
 use solana_program::program::{invoke, invoke_signed};
+use solana_program::program_error::ProgramError;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
@@ -20,6 +26,7 @@ use spl_associated_token_account::get_associated_token_address;
 use spl_token::id as SPL_TOKEN_ID;
 use spl_token::instruction as spl_instruction;
 use spl_token::state::{Account as SPLAccount, Account};
+use std::time::Duration;
 
 pub fn init(
     program_id: &Pubkey,
@@ -27,6 +34,12 @@ pub fn init(
     account_guid_hash: &BalanceAccountGuidHash,
     amount: u64,
     destination_name_hash: &AddressBookEntryNameHash,
+    approval_timeout: Option<Duration>,
+    memo_hash: Option<Hash>,
+    ata_rent_payer: Option<AtaRentPayer>,
+    amount_spec: AmountSpec,
+    travel_rule_hash: Option<Hash>,
+    limits: &PolicyLimits,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
@@ -34,69 +47,104 @@ pub fn init(
     let source_account = next_account_info(accounts_iter)?;
     let destination_account = next_account_info(accounts_iter)?;
     let initiator_account_info = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
     let token_mint = next_account_info(accounts_iter)?;
     let destination_token_account = next_account_info(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
-    let balance_account = wallet.get_balance_account(account_guid_hash)?;
+    let mut wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    let (balance_account_slot_id, balance_account) =
+        wallet.get_balance_account_with_slot_id(account_guid_hash)?;
 
-    if !wallet.destination_allowed(
+    let whitelisted = wallet.destination_allowed(
         &balance_account,
         destination_account.key,
         destination_name_hash,
-    )? {
-        msg!("Destination account is not whitelisted");
-        return Err(WalletError::DestinationNotAllowed.into());
-    }
+    )? || AddressBookPage::from_accounts(
+        program_id,
+        wallet_account_info.key,
+        accounts_iter.as_slice(),
+    )?
+    .iter()
+    .any(|page| {
+        page.destination_allowed(
+            &balance_account_slot_id,
+            destination_account.key,
+            destination_name_hash,
+        )
+    });
+    let mut policy = wallet.evaluate_policy(
+        &balance_account,
+        &PolicyRequest {
+            amount,
+            destination: Destination::Address { whitelisted },
+            now: clock.unix_timestamp,
+        },
+    )?;
+    policy.approval_timeout =
+        get_approval_timeout(limits, policy.approval_timeout, approval_timeout)?;
 
     wallet.validate_transfer_initiator(initiator_account_info)?;
 
-    if *token_mint.key != Pubkey::default() && *destination_token_account.owner == Pubkey::default()
+    if *token_mint.key != Pubkey::default()
+        && !wallet.token_mint_allowed(&balance_account, token_mint.key)
+    {
+        msg!("Token mint is not allowed for this balance account");
+        return Err(WalletError::TokenMintNotAllowed.into());
+    }
+    balance_account.validate_transfers_enabled(token_mint.key)?;
+    wallet.validate_destination_token_mint(destination_account.key, token_mint.key)?;
+    wallet.validate_destination_namespace(
+        destination_account.key,
+        token_mint.key,
+        memo_hash.as_ref(),
+        travel_rule_hash.as_ref(),
+    )?;
+
+    let source_may_fund_token_account = wallet
+        .governed_parameters
+        .balance_account_may_fund_token_accounts();
+    if ata_rent_payer == Some(AtaRentPayer::Source) && !source_may_fund_token_account {
+        msg!("Balance accounts may not fund their destinations' token accounts");
+        return Err(WalletError::RentFundingLimitExceeded.into());
+    }
+
+    wallet.reserve_outstanding_transfer_value(account_guid_hash, amount)?;
+
+    // with a rent payer committed to the op, the token account is created at finalize instead
+    if *token_mint.key != Pubkey::default()
+        && *destination_token_account.owner == Pubkey::default()
+        && ata_rent_payer.is_none()
     {
         // we need to create the destination token account (if it had been created already
         // it would be owned by the Token program).
         // frst check if the source account has sufficient funds to create it
         let rent = Rent::get()?;
-        if rent.is_exempt(source_account.lamports(), Account::LEN) {
-            let (source_account_pda, bump_seed) =
-                Pubkey::find_program_address(&[&account_guid_hash.to_bytes()], program_id);
-            if &source_account_pda != source_account.key {
-                return Err(WalletError::InvalidSourceAccount.into());
-            }
+        if source_may_fund_token_account && rent.is_exempt(source_account.lamports(), Account::LEN)
+        {
+            let bump_seed = validate_balance_account_and_get_seed(
+                source_account,
+                &balance_account,
+                program_id,
+            )?;
             invoke_signed(
-                &Instruction {
-                    program_id: spl_associated_token_account::id(),
-                    accounts: vec![
-                        AccountMeta::new(source_account_pda, true),
-                        AccountMeta::new(*destination_token_account.key, false),
-                        AccountMeta::new_readonly(*destination_account.key, false),
-                        AccountMeta::new_readonly(*token_mint.key, false),
-                        AccountMeta::new_readonly(solana_program::system_program::id(), false),
-                        AccountMeta::new_readonly(spl_token::id(), false),
-                        AccountMeta::new_readonly(sysvar::rent::id(), false),
-                    ],
-                    data: vec![],
-                },
+                &create_destination_token_account(
+                    source_account.key,
+                    destination_token_account.key,
+                    destination_account.key,
+                    token_mint.key,
+                ),
                 accounts,
                 &[&[&account_guid_hash.to_bytes(), &[bump_seed]]],
             )?;
         } else {
             let fee_payer_account = next_account_info(accounts_iter)?;
             invoke(
-                &Instruction {
-                    program_id: spl_associated_token_account::id(),
-                    accounts: vec![
-                        AccountMeta::new(*fee_payer_account.key, true),
-                        AccountMeta::new(*destination_token_account.key, false),
-                        AccountMeta::new_readonly(*destination_account.key, false),
-                        AccountMeta::new_readonly(*token_mint.key, false),
-                        AccountMeta::new_readonly(solana_program::system_program::id(), false),
-                        AccountMeta::new_readonly(spl_token::id(), false),
-                        AccountMeta::new_readonly(sysvar::rent::id(), false),
-                    ],
-                    data: vec![],
-                },
+                &create_destination_token_account(
+                    fee_payer_account.key,
+                    destination_token_account.key,
+                    destination_account.key,
+                    token_mint.key,
+                ),
                 accounts,
             )?;
         }
@@ -105,7 +153,8 @@ pub fn init(
     start_multisig_transfer_op(
         &multisig_op_account_info,
         &wallet,
-        &balance_account,
+        policy,
+        initiator_account_info,
         clock,
         MultisigOpParams::Transfer {
             wallet_address: *wallet_account_info.key,
@@ -113,8 +162,58 @@ pub fn init(
             destination: *destination_account.key,
             amount,
             token_mint: *token_mint.key,
+            memo_hash,
+            ata_rent_payer,
+            // approvers approve the transfer through the program that owns the mint
+            token_program: if *token_mint.key == Pubkey::default() {
+                system_program::id()
+            } else {
+                *token_mint.owner
+            },
+            amount_spec,
+            travel_rule_hash,
         },
-    )
+    )?;
+
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Where SPL transfers to the destination go: the destination itself if it's
+/// in the address book as a token account, its associated token account if not.
+fn destination_token_account_address(
+    wallet: &Wallet,
+    destination: &Pubkey,
+    token_mint: &Pubkey,
+) -> Pubkey {
+    if wallet.is_token_account_destination(destination) {
+        *destination
+    } else {
+        get_associated_token_address(destination, token_mint)
+    }
+}
+
+/// Creates the destination's associated token account, with its rent paid by
+/// the funder.
+fn create_destination_token_account(
+    funder: &Pubkey,
+    destination_token_account: &Pubkey,
+    destination: &Pubkey,
+    token_mint: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: spl_associated_token_account::id(),
+        accounts: vec![
+            AccountMeta::new(*funder, true),
+            AccountMeta::new(*destination_token_account, false),
+            AccountMeta::new_readonly(*destination, false),
+            AccountMeta::new_readonly(*token_mint, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+        data: vec![],
+    }
 }
 
 pub fn finalize(
@@ -123,24 +222,37 @@ pub fn finalize(
     account_guid_hash: &BalanceAccountGuidHash,
     amount: u64,
     token_mint: Pubkey,
+    memo_hash: Option<Hash>,
+    fee_reimbursement: u64,
+    ata_rent_payer: Option<AtaRentPayer>,
+    token_program: Option<Pubkey>,
+    amount_spec: AmountSpec,
+    travel_rule_hash: Option<Hash>,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let source_account = next_account_info(accounts_iter)?;
     let destination_account = next_account_info(accounts_iter)?;
     let system_program_account = next_account_info(accounts_iter)?;
     let rent_collector_account_info = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+    let now = clock.unix_timestamp;
 
     let is_spl = token_mint.to_bytes() != [0; 32];
+    let token_program = if is_spl {
+        token_program.unwrap_or_else(SPL_TOKEN_ID)
+    } else {
+        system_program::id()
+    };
 
     if system_program_account.key != &system_program::id() {
         return Err(WalletError::AccountNotRecognized.into());
     }
 
-    finalize_multisig_op(
+    finalize_pinned_multisig_op(
         &multisig_op_account_info,
+        &wallet_account_info,
         &rent_collector_account_info,
         clock,
         MultisigOpParams::Transfer {
@@ -149,14 +261,37 @@ pub fn finalize(
             destination: *destination_account.key,
             amount,
             token_mint,
+            memo_hash,
+            ata_rent_payer,
+            token_program,
+            amount_spec,
+            travel_rule_hash,
         },
         || -> ProgramResult {
+            // the destination may have been bound to other token mints or tagged
+            // since, and transfers may have been disabled for the account; either
+            // way they only settle within the policy's settlement windows
+            let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+            let balance_account = wallet.get_balance_account(account_guid_hash)?;
             let bump_seed = validate_balance_account_and_get_seed(
                 source_account,
-                account_guid_hash,
+                &balance_account,
                 program_id,
             )?;
-            if is_spl {
+            balance_account.policy_rules.validate_settlement(now)?;
+            wallet.validate_destination_token_mint(destination_account.key, &token_mint)?;
+            wallet.validate_destination_namespace(
+                destination_account.key,
+                &token_mint,
+                memo_hash.as_ref(),
+                travel_rule_hash.as_ref(),
+            )?;
+            balance_account.validate_transfers_enabled(&token_mint)?;
+            let amount = if is_spl {
+                if !wallet.token_mint_allowed(&balance_account, &token_mint) {
+                    msg!("Token mint is not allowed for this balance account");
+                    return Err(WalletError::TokenMintNotAllowed.into());
+                }
                 let source_token_account = next_account_info(accounts_iter)?;
                 let source_token_account_key =
                     get_associated_token_address(source_account.key, &token_mint);
@@ -165,6 +300,9 @@ pub fn finalize(
                 }
                 let source_token_account_data =
                     SPLAccount::unpack(&source_token_account.data.borrow())?;
+                let amount = amount_to_transfer(amount, amount_spec, || {
+                    Ok(source_token_account_data.amount)
+                })?;
                 if source_token_account_data.amount < amount {
                     msg!(
                         "Source token account only has {} tokens of {} requested",
@@ -174,18 +312,82 @@ pub fn finalize(
                     return Err(WalletError::InsufficientBalance.into());
                 }
                 let destination_token_account = next_account_info(accounts_iter)?;
-                let destination_token_account_key =
-                    get_associated_token_address(&destination_account.key, &token_mint);
+                let destination_token_account_key = destination_token_account_address(
+                    &wallet,
+                    destination_account.key,
+                    &token_mint,
+                );
                 if *destination_token_account.key != destination_token_account_key {
                     return Err(WalletError::InvalidDestinationTokenAccount.into());
                 }
+                if destination_token_account_key == *destination_account.key {
+                    // a token account destination has to exist and hold the mint
+                    if *destination_token_account.owner != token_program
+                        || SPLAccount::unpack(&destination_token_account.data.borrow())?.mint
+                            != token_mint
+                    {
+                        msg!("Destination is not a token account of mint {}", token_mint);
+                        return Err(WalletError::InvalidDestinationTokenAccount.into());
+                    }
+                } else if *destination_token_account.owner != SPL_TOKEN_ID() {
+                    match ata_rent_payer {
+                        Some(AtaRentPayer::Source) => {
+                            // the governed parameter may have been turned off since
+                            if !wallet
+                                .governed_parameters
+                                .balance_account_may_fund_token_accounts()
+                            {
+                                msg!("Balance accounts may not fund their destinations' token accounts");
+                                return Err(WalletError::RentFundingLimitExceeded.into());
+                            }
+                            invoke_signed(
+                                &create_destination_token_account(
+                                    source_account.key,
+                                    &destination_token_account_key,
+                                    destination_account.key,
+                                    &token_mint,
+                                ),
+                                accounts,
+                                &[&[&account_guid_hash.to_bytes(), &[bump_seed]]],
+                            )?
+                        }
+                        Some(AtaRentPayer::Payer) => {
+                            if !rent_collector_account_info.is_signer {
+                                return Err(ProgramError::MissingRequiredSignature);
+                            }
+                            invoke(
+                                &create_destination_token_account(
+                                    rent_collector_account_info.key,
+                                    &destination_token_account_key,
+                                    destination_account.key,
+                                    &token_mint,
+                                ),
+                                accounts,
+                            )?
+                        }
+                        Some(AtaRentPayer::Destination) => {
+                            msg!("The destination has to create its token account first");
+                            return Err(WalletError::InvalidDestinationTokenAccount.into());
+                        }
+                        // the token account was created when the transfer was initiated
+                        None => {}
+                    }
+                }
 
                 let spl_token_program = next_account_info(accounts_iter)?;
+                if *spl_token_program.key != token_program {
+                    msg!(
+                        "Transfer was initiated for token program {}, not {}",
+                        token_program,
+                        spl_token_program.key
+                    );
+                    return Err(WalletError::TokenProgramMismatch.into());
+                }
                 let token_mint_authority = next_account_info(accounts_iter)?;
 
                 invoke_signed(
                     &spl_instruction::transfer(
-                        &SPL_TOKEN_ID(),
+                        &token_program,
                         &source_token_account_key,
                         &destination_token_account_key,
                         source_account.key,
@@ -202,7 +404,15 @@ pub fn finalize(
                     ],
                     &[&[&account_guid_hash.to_bytes(), &[bump_seed]]],
                 )?;
+                amount
             } else {
+                // the source has to stay rent exempt and still cover the fee reimbursement
+                let amount = amount_to_transfer(amount, amount_spec, || {
+                    Ok(source_account
+                        .lamports()
+                        .saturating_sub(Rent::get()?.minimum_balance(source_account.data_len()))
+                        .saturating_sub(fee_reimbursement))
+                })?;
                 if source_account.lamports() < amount {
                     msg!(
                         "Source account only has {} lamports of {} requested",
@@ -220,8 +430,320 @@ pub fn finalize(
                     destination_account.clone(),
                     amount,
                 )?;
+                amount
+            };
+            if let Some(memo_hash) = memo_hash {
+                write_memo(accounts, &memo_hash)?;
+            }
+            if let Some(travel_rule_hash) = travel_rule_hash {
+                msg!("Travel rule data hash: {}", travel_rule_hash);
             }
+            if fee_reimbursement > 0 {
+                // only the relayer that signed (and so paid for) the finalize is reimbursed
+                if !rent_collector_account_info.is_signer {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+                let balance_account = wallet.get_balance_account(account_guid_hash)?;
+                if fee_reimbursement > balance_account.max_finalize_fee_reimbursement {
+                    msg!(
+                        "Fee reimbursement of {} lamports exceeds the limit of {}",
+                        fee_reimbursement,
+                        balance_account.max_finalize_fee_reimbursement
+                    );
+                    return Err(WalletError::FeeReimbursementExceeded.into());
+                }
+                transfer_sol_checked(
+                    source_account.clone(),
+                    account_guid_hash,
+                    bump_seed,
+                    system_program_account.clone(),
+                    rent_collector_account_info.clone(),
+                    fee_reimbursement,
+                )?;
+            }
+            let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+            wallet.record_large_transfer(account_guid_hash, amount, now)?;
+            wallet.activity.record_transfer(&token_mint, amount)?;
+            Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
             Ok(())
         },
+    )?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.release_outstanding_transfer_value(account_guid_hash, amount)?;
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Works out how much a transfer moves: exactly its amount, or for a max
+/// transfer whatever the source can spend, up to that amount.
+fn amount_to_transfer<F>(
+    amount: u64,
+    amount_spec: AmountSpec,
+    spendable: F,
+) -> Result<u64, ProgramError>
+where
+    F: FnOnce() -> Result<u64, ProgramError>,
+{
+    if amount_spec == AmountSpec::Exact {
+        return Ok(amount);
+    }
+    let spendable = spendable()?;
+    if spendable == 0 {
+        msg!("Source account has nothing to transfer");
+        return Err(WalletError::InsufficientBalance.into());
+    }
+    let amount = amount.min(spendable);
+    msg!(
+        "Transferring {} of the {} the source account can spend",
+        amount,
+        spendable
+    );
+    Ok(amount)
+}
+
+mod spl_memo {
+    solana_program::declare_id!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+}
+
+/// Writes the transfer's memo hash to the transaction log through the SPL
+/// Memo program, so the payment can be reconciled against what it pays for.
+fn write_memo(accounts: &[AccountInfo], memo_hash: &Hash) -> ProgramResult {
+    let memo_program = accounts
+        .iter()
+        .find(|account_info| *account_info.key == spl_memo::id())
+        .ok_or_else(|| {
+            msg!("The SPL Memo program must be passed along to finalize a transfer with a memo");
+            WalletError::AccountNotRecognized
+        })?;
+    invoke(
+        &Instruction {
+            program_id: spl_memo::id(),
+            accounts: vec![],
+            data: memo_hash.to_string().into_bytes(),
+        },
+        &[memo_program.clone()],
     )
 }
+
+/// Runs the checks a transfer has to pass to be initiated and finalized,
+/// logging the outcome of each one and then an overall GO or NO-GO. Nothing
+/// is written; on NO-GO the first failed check's error is returned.
+pub fn preflight(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+    amount: u64,
+    token_mint: Pubkey,
+    destination_name_hash: &AddressBookEntryNameHash,
+    program_config: Option<&ProgramConfig>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    // the multisig op account is not read, the op need not exist yet
+    next_account_info(accounts_iter)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let source_account = next_account_info(accounts_iter)?;
+    let destination_account = next_account_info(accounts_iter)?;
+    let _system_program_account = next_account_info(accounts_iter)?;
+    let _rent_collector_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let (balance_account_slot_id, balance_account) =
+        match wallet.get_balance_account_with_slot_id(account_guid_hash) {
+            Ok(balance_account) => balance_account,
+            Err(error) => {
+                msg!("Preflight balance account: failed");
+                msg!("Preflight result: NO-GO");
+                return Err(error);
+            }
+        };
+
+    let mut checks: Vec<(&str, ProgramResult)> = Vec::new();
+
+    checks.push((
+        "program config",
+        match program_config {
+            Some(program_config) if program_config.is_paused() => {
+                Err(WalletError::ProgramPaused.into())
+            }
+            _ => Ok(()),
+        },
+    ));
+
+    checks.push((
+        "outstanding value",
+        balance_account
+            .outstanding_transfer_value_after(amount)
+            .map(|_| ()),
+    ));
+
+    checks.push((
+        "source account",
+        validate_balance_account_and_get_seed(source_account, &balance_account, program_id)
+            .map(|_| ()),
+    ));
+
+    let whitelisted =
+        wallet.destination_allowed(
+            &balance_account,
+            destination_account.key,
+            destination_name_hash,
+        )? || AddressBookPage::from_accounts(program_id, wallet_account_info.key, accounts)?
+            .iter()
+            .any(|page| {
+                page.destination_allowed(
+                    &balance_account_slot_id,
+                    destination_account.key,
+                    destination_name_hash,
+                )
+            });
+    checks.push((
+        "policy",
+        wallet
+            .evaluate_policy(
+                &balance_account,
+                &PolicyRequest {
+                    amount,
+                    destination: Destination::Address { whitelisted },
+                    now: clock.unix_timestamp,
+                },
+            )
+            .map(|_| ()),
+    ));
+
+    checks.push((
+        "settlement window",
+        balance_account
+            .policy_rules
+            .validate_settlement(clock.unix_timestamp),
+    ));
+
+    checks.push((
+        "large transfer cooldown",
+        balance_account.validate_large_transfer(amount, clock.unix_timestamp),
+    ));
+
+    checks.push((
+        "destination token mint",
+        wallet.validate_destination_token_mint(destination_account.key, &token_mint),
+    ));
+
+    checks.push((
+        "transfers enabled",
+        balance_account.validate_transfers_enabled(&token_mint),
+    ));
+
+    if token_mint.to_bytes() != [0; 32] {
+        let source_token_account = next_account_info(accounts_iter)?;
+        let destination_token_account = next_account_info(accounts_iter)?;
+        checks.push((
+            "token mint",
+            if wallet.token_mint_allowed(&balance_account, &token_mint) {
+                Ok(())
+            } else {
+                Err(WalletError::TokenMintNotAllowed.into())
+            },
+        ));
+        checks.push((
+            "balance",
+            preflight_spl_balance(source_account, source_token_account, &token_mint, amount),
+        ));
+        checks.push((
+            "destination",
+            preflight_spl_destination(
+                &destination_token_account_address(&wallet, destination_account.key, &token_mint),
+                destination_token_account,
+            ),
+        ));
+    } else {
+        checks.push((
+            "balance",
+            if source_account.lamports() < amount {
+                msg!(
+                    "Source account only has {} lamports of {} requested",
+                    source_account.lamports(),
+                    amount
+                );
+                Err(WalletError::InsufficientBalance.into())
+            } else {
+                Ok(())
+            },
+        ));
+        checks.push((
+            "destination",
+            preflight_destination_rent(destination_account, amount),
+        ));
+    }
+
+    for (check, result) in checks.iter() {
+        msg!(
+            "Preflight {}: {}",
+            check,
+            if result.is_ok() { "ok" } else { "failed" }
+        );
+    }
+    match checks.into_iter().find_map(|(_, result)| result.err()) {
+        None => {
+            msg!("Preflight result: GO");
+            Ok(())
+        }
+        Some(error) => {
+            msg!("Preflight result: NO-GO");
+            Err(error)
+        }
+    }
+}
+
+fn preflight_spl_balance(
+    source_account: &AccountInfo,
+    source_token_account: &AccountInfo,
+    token_mint: &Pubkey,
+    amount: u64,
+) -> ProgramResult {
+    if *source_token_account.key != get_associated_token_address(source_account.key, token_mint)
+        || *source_token_account.owner != SPL_TOKEN_ID()
+    {
+        return Err(WalletError::InvalidSourceTokenAccount.into());
+    }
+    let source_token_account_data = SPLAccount::unpack(&source_token_account.data.borrow())?;
+    if source_token_account_data.amount < amount {
+        msg!(
+            "Source token account only has {} tokens of {} requested",
+            source_token_account_data.amount,
+            amount
+        );
+        return Err(WalletError::InsufficientBalance.into());
+    }
+    Ok(())
+}
+
+fn preflight_spl_destination(
+    destination_token_account_key: &Pubkey,
+    destination_token_account: &AccountInfo,
+) -> ProgramResult {
+    if destination_token_account.key != destination_token_account_key {
+        return Err(WalletError::InvalidDestinationTokenAccount.into());
+    }
+    if *destination_token_account.owner != SPL_TOKEN_ID() {
+        msg!("Destination token account does not exist");
+        return Err(WalletError::InvalidDestinationTokenAccount.into());
+    }
+    Ok(())
+}
+
+fn preflight_destination_rent(destination_account: &AccountInfo, amount: u64) -> ProgramResult {
+    let balance_after = destination_account
+        .lamports()
+        .checked_add(amount)
+        .ok_or(WalletError::AmountOverflow)?;
+    if !Rent::get()?.is_exempt(balance_after, destination_account.data_len()) {
+        msg!(
+            "Destination would hold {} lamports, which is not rent exempt",
+            balance_after
+        );
+        return Err(WalletError::DestinationNotRentExempt.into());
+    }
+    Ok(())
+}
@@ -1,8 +1,10 @@
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    start_multisig_config_op,
+    finalize_multisig_op, get_clock, next_multisig_op_account_info, next_program_account_info,
+    start_multisig_config_op, unpack_wallet,
 };
+use crate::instruction::SignersUpdate;
 use crate::model::multisig_op::{MultisigOpParams, SlotUpdateType};
+use crate::model::program_config::PolicyLimits;
 use crate::model::signer::Signer;
 use crate::model::wallet::Wallet;
 use crate::utils::SlotId;
@@ -17,23 +19,25 @@ pub fn init(
     slot_update_type: SlotUpdateType,
     slot_id: SlotId<Signer>,
     signer: Signer,
+    limits: &PolicyLimits,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let initiator_account_info = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
     wallet.validate_config_initiator(initiator_account_info)?;
     match slot_update_type {
-        SlotUpdateType::SetIfEmpty => wallet.validate_add_signer((slot_id, signer))?,
+        SlotUpdateType::SetIfEmpty => wallet.validate_add_signer((slot_id, signer), limits)?,
         SlotUpdateType::Clear => wallet.validate_remove_signer((slot_id, signer))?,
     }
 
     start_multisig_config_op(
         &multisig_op_account_info,
         &wallet,
+        initiator_account_info,
         clock,
         MultisigOpParams::UpdateSigner {
             wallet_address: *wallet_account_info.key,
@@ -50,12 +54,14 @@ pub fn finalize(
     slot_update_type: SlotUpdateType,
     slot_id: SlotId<Signer>,
     signer: Signer,
+    limits: &PolicyLimits,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let account_to_return_rent_to = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+    let now = clock.unix_timestamp;
 
     finalize_multisig_op(
         &multisig_op_account_info,
@@ -70,9 +76,71 @@ pub fn finalize(
         || -> ProgramResult {
             let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow_mut())?;
             match slot_update_type {
-                SlotUpdateType::SetIfEmpty => wallet.add_signer((slot_id, signer))?,
+                SlotUpdateType::SetIfEmpty => wallet.add_signer((slot_id, signer), limits)?,
                 SlotUpdateType::Clear => wallet.remove_signer((slot_id, signer))?,
             }
+            wallet.start_tracking_signer_activity(now);
+            wallet.record_config_change();
+            Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+            Ok(())
+        },
+    )
+}
+
+pub fn init_many(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    update: &SignersUpdate,
+    limits: &PolicyLimits,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let initiator_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    wallet.validate_config_initiator(initiator_account_info)?;
+    wallet.validate_signers_update(update, limits)?;
+
+    start_multisig_config_op(
+        &multisig_op_account_info,
+        &wallet,
+        initiator_account_info,
+        clock,
+        MultisigOpParams::UpdateSigners {
+            wallet_address: *wallet_account_info.key,
+            update: update.clone(),
+        },
+    )
+}
+
+pub fn finalize_many(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    update: &SignersUpdate,
+    limits: &PolicyLimits,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let account_to_return_rent_to = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+    let now = clock.unix_timestamp;
+
+    finalize_multisig_op(
+        &multisig_op_account_info,
+        &account_to_return_rent_to,
+        clock,
+        MultisigOpParams::UpdateSigners {
+            wallet_address: *wallet_account_info.key,
+            update: update.clone(),
+        },
+        || -> ProgramResult {
+            let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow_mut())?;
+            wallet.update_signers(update, limits)?;
+            wallet.start_tracking_signer_activity(now);
+            wallet.record_config_change();
             Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
             Ok(())
         },
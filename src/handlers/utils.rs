@@ -1,6 +1,9 @@
 use crate::error::WalletError;
 use crate::model::balance_account::{BalanceAccount, BalanceAccountGuidHash};
 use crate::model::multisig_op::{MultisigOp, MultisigOpParams};
+use crate::model::policy::PolicyDecision;
+use crate::model::program_config::PolicyLimits;
+use crate::model::signer_directory::SignerDirectory;
 use crate::model::wallet::Wallet;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
@@ -29,6 +32,61 @@ pub fn collect_remaining_balance(from: &AccountInfo, to: &AccountInfo) -> Progra
     Ok(())
 }
 
+/// Records that the signers gave a disposition in the wallet the op belongs
+/// to. The wallet has to be among the accounts, and writable, so that an
+/// approver's activity can't go unrecorded by leaving it out.
+pub fn record_signer_activity(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    wallet_address: &Pubkey,
+    signers: &[Pubkey],
+    now: i64,
+) -> ProgramResult {
+    let wallet_account_info = accounts
+        .iter()
+        .find(|account_info| account_info.key == wallet_address)
+        .ok_or_else(|| {
+            msg!("The op's wallet account {} is required", wallet_address);
+            ProgramError::from(WalletError::AccountNotRecognized)
+        })?;
+    if wallet_account_info.owner != program_id {
+        msg!("Account does not belong to the program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !wallet_account_info.is_writable {
+        msg!(
+            "The op's wallet account {} must be writable",
+            wallet_address
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let mut recorded = false;
+    for signer in signers.iter() {
+        recorded |= wallet.record_signer_activity(signer, now);
+    }
+    if recorded {
+        Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    }
+    Ok(())
+}
+
+/// Op rent can go back to any signer, or to the wallet's fee account which
+/// can't sign outside of the program.
+pub fn validate_rent_collector(
+    rent_collector: &AccountInfo,
+    wallet_address: &Pubkey,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    if rent_collector.is_signer
+        || *rent_collector.key == Wallet::find_fee_account_address(wallet_address, program_id).0
+    {
+        Ok(())
+    } else {
+        Err(ProgramError::MissingRequiredSignature)
+    }
+}
+
 pub fn next_program_account_info<'a, 'b, I: Iterator<Item = &'a AccountInfo<'b>>>(
     iter: &mut I,
     program_id: &Pubkey,
@@ -41,13 +99,36 @@ pub fn next_program_account_info<'a, 'b, I: Iterator<Item = &'a AccountInfo<'b>>
     Ok(account_info)
 }
 
-pub fn get_clock_from_next_account(iter: &mut Iter<AccountInfo>) -> Result<Clock, ProgramError> {
+/// Like `next_program_account_info`, but reports an op account that an
+/// earlier finalize has already closed as `AlreadyFinalized`, so a client
+/// retrying a finalize can tell that the first attempt went through.
+pub fn next_multisig_op_account_info<'a, 'b, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    iter: &mut I,
+    program_id: &Pubkey,
+) -> Result<I::Item, ProgramError> {
     let account_info = next_account_info(iter)?;
-    if solana_program::sysvar::clock::id() != *account_info.key {
-        msg!("Invalid clock account");
-        return Err(WalletError::AccountNotRecognized.into());
+    if account_info.lamports() == 0 && account_info.data_is_empty() {
+        msg!("Operation account is closed; the operation has already been finalized");
+        return Err(WalletError::AlreadyFinalized.into());
+    }
+    if account_info.owner != program_id {
+        msg!("Account does not belong to the program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(account_info)
+}
+
+/// Returns the current clock. Older clients pass the clock sysvar account as
+/// the next account, in which case it is consumed and read; otherwise the clock
+/// is read with the syscall and the iterator is left where it was.
+pub fn get_clock(iter: &mut Iter<AccountInfo>) -> Result<Clock, ProgramError> {
+    match iter.clone().next() {
+        Some(account_info) if *account_info.key == solana_program::sysvar::clock::id() => {
+            iter.next();
+            Clock::from_account_info(account_info)
+        }
+        _ => Clock::get(),
     }
-    Clock::from_account_info(&account_info)
 }
 
 pub fn calculate_expires(start: i64, duration: Duration) -> Result<i64, ProgramError> {
@@ -59,40 +140,127 @@ pub fn calculate_expires(start: i64, duration: Duration) -> Result<i64, ProgramE
     Ok(expires_at.unwrap())
 }
 
+/// Returns the approval timeout to use for a new operation: the given default,
+/// or the override if one was supplied. An override may only shorten the
+/// default, and must be within the program's approval timeout limits.
+pub fn get_approval_timeout(
+    limits: &PolicyLimits,
+    default_timeout: Duration,
+    timeout_override: Option<Duration>,
+) -> Result<Duration, ProgramError> {
+    match timeout_override {
+        None => Ok(default_timeout),
+        Some(timeout) => {
+            limits.validate_approval_timeout(&timeout)?;
+            if timeout > default_timeout {
+                msg!(
+                    "Approval timeout override can't be more than {} seconds",
+                    default_timeout.as_secs()
+                );
+                return Err(WalletError::InvalidApprovalTimeout.into());
+            }
+            Ok(timeout)
+        }
+    }
+}
+
+/// Checks the account is the balance account's PDA, returning its bump seed.
 pub fn validate_balance_account_and_get_seed(
-    balance_account: &AccountInfo,
-    account_guid_hash: &BalanceAccountGuidHash,
+    balance_account_info: &AccountInfo,
+    balance_account: &BalanceAccount,
     program_id: &Pubkey,
 ) -> Result<u8, ProgramError> {
-    let (account_pda, bump_seed) =
-        Pubkey::find_program_address(&[&account_guid_hash.to_bytes()], program_id);
-    if &account_pda != balance_account.key {
+    let (account_pda, bump_seed) = balance_account.find_address(program_id)?;
+    if &account_pda != balance_account_info.key {
         Err(WalletError::InvalidSourceAccount.into())
     } else {
         Ok(bump_seed)
     }
 }
 
+/// Unpacks the wallet with its directory-backed signer slots resolved against
+/// the signer directory, which must then be among the given accounts.
+pub fn unpack_wallet(
+    program_id: &Pubkey,
+    wallet_account_info: &AccountInfo,
+    accounts: &[AccountInfo],
+) -> Result<Wallet, ProgramError> {
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    if let Some(directory_address) = wallet.signer_directory {
+        match SignerDirectory::from_accounts(program_id, accounts)? {
+            Some((address, directory)) if address == directory_address => {
+                wallet.resolve_directory_signers(&directory)
+            }
+            _ => {
+                msg!("The wallet's signer directory must be passed along");
+                return Err(WalletError::SignerDirectoryRequired.into());
+            }
+        }
+    }
+    Ok(wallet)
+}
+
+/// Carries the wallet's approver delegations, directory-backed signer slots,
+/// signer organizations and veto approvers over to an op that is being started.
+pub fn snapshot_wallet_approvers(multisig_op: &mut MultisigOp, wallet: &Wallet, now: i64) {
+    multisig_op.delegate_approvals(&wallet.active_approver_delegations(now));
+    multisig_op.use_signer_directory(wallet);
+    multisig_op.require_distinct_organizations(wallet);
+    multisig_op.allow_vetoes(wallet);
+}
+
+/// Unpacks the account a new op is about to be started in. An account still
+/// holding a denied or expired op may be reused, but only with the op
+/// account's signature, so nobody can take over an account they don't hold.
+/// The op's params will be hashed the way the wallet currently asks for.
+pub fn unpack_multisig_op_for_init(
+    multisig_op_account_info: &AccountInfo,
+    wallet: &Wallet,
+) -> Result<MultisigOp, ProgramError> {
+    let mut multisig_op = MultisigOp::unpack_unchecked(&multisig_op_account_info.data.borrow())?;
+    if multisig_op.is_initialized && !multisig_op_account_info.is_signer {
+        msg!("Reusing a multisig op account requires its signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    multisig_op.params_hash_version = wallet.governed_parameters.params_hash_version();
+    multisig_op.disposition_changes_allowed =
+        wallet.governed_parameters.disposition_changes_allowed();
+    Ok(multisig_op)
+}
+
 pub fn start_multisig_transfer_op(
     multisig_op_account_info: &AccountInfo,
     wallet: &Wallet,
-    balance_account: &BalanceAccount,
+    policy: PolicyDecision,
+    initiator: &AccountInfo,
     clock: Clock,
     params: MultisigOpParams,
 ) -> ProgramResult {
-    let mut multisig_op = MultisigOp::unpack_unchecked(&multisig_op_account_info.data.borrow())?;
+    let mut multisig_op = unpack_multisig_op_for_init(multisig_op_account_info, wallet)?;
+    let denial_reason_required = wallet.denial_reason_required(params.value());
+    let account_guid_hash = params.account_guid_hash();
 
     multisig_op.init(
-        wallet.get_transfer_approvers_keys(balance_account),
-        balance_account.approvals_required_for_transfer,
-        clock.unix_timestamp,
-        calculate_expires(
+        wallet.exclude_inactive_approvers(
+            policy.approvers,
+            policy.approvals_required,
             clock.unix_timestamp,
-            balance_account.approval_timeout_for_transfer,
-        )?,
+        ),
+        policy.approvals_required,
+        clock.unix_timestamp,
+        calculate_expires(clock.unix_timestamp, policy.approval_timeout)?,
         params,
+        denial_reason_required,
+        wallet.initiator_class(initiator),
+        *initiator.key,
     )?;
+    snapshot_wallet_approvers(&mut multisig_op, wallet, clock.unix_timestamp);
+    multisig_op.pin_config_version(wallet);
+    if let Some(account_guid_hash) = account_guid_hash {
+        multisig_op.pin_account_sequence(wallet, &account_guid_hash)?;
+    }
     MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+    wallet.log_metadata_hash();
 
     Ok(())
 }
@@ -100,19 +268,67 @@ pub fn start_multisig_transfer_op(
 pub fn start_multisig_config_op(
     multisig_op_account_info: &AccountInfo,
     wallet: &Wallet,
+    initiator: &AccountInfo,
     clock: Clock,
     params: MultisigOpParams,
 ) -> ProgramResult {
-    let mut multisig_op = MultisigOp::unpack_unchecked(&multisig_op_account_info.data.borrow())?;
+    let mut multisig_op = unpack_multisig_op_for_init(multisig_op_account_info, wallet)?;
+    let initiator_class = wallet.initiator_class(initiator);
+    let (approvers, approvals_required) = wallet.config_op_approvers(&params, initiator_class);
+    let approvers =
+        wallet.exclude_inactive_approvers(approvers, approvals_required, clock.unix_timestamp);
+    let account_guid_hash = params.account_guid_hash();
 
     multisig_op.init(
-        wallet.get_config_approvers_keys(),
-        wallet.approvals_required_for_config,
+        approvers,
+        approvals_required,
         clock.unix_timestamp,
         calculate_expires(clock.unix_timestamp, wallet.approval_timeout_for_config)?,
         params,
+        false,
+        initiator_class,
+        *initiator.key,
     )?;
+    snapshot_wallet_approvers(&mut multisig_op, wallet, clock.unix_timestamp);
+    if let Some(account_guid_hash) = account_guid_hash {
+        multisig_op.pin_account_sequence(wallet, &account_guid_hash)?;
+    }
     MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+    wallet.log_metadata_hash();
+
+    Ok(())
+}
+
+/// Starts an address book or dapp book op, seeded with the address book
+/// approvers rather than the config approvers.
+pub fn start_multisig_address_book_op(
+    multisig_op_account_info: &AccountInfo,
+    wallet: &Wallet,
+    initiator: &AccountInfo,
+    clock: Clock,
+    params: MultisigOpParams,
+) -> ProgramResult {
+    let mut multisig_op = unpack_multisig_op_for_init(multisig_op_account_info, wallet)?;
+    let initiator_class = wallet.initiator_class(initiator);
+    let approvals_required = wallet.approvals_required_for_address_book_op(initiator_class);
+
+    multisig_op.init(
+        wallet.exclude_inactive_approvers(
+            wallet.get_address_book_approvers_keys(),
+            approvals_required,
+            clock.unix_timestamp,
+        ),
+        approvals_required,
+        clock.unix_timestamp,
+        calculate_expires(clock.unix_timestamp, wallet.approval_timeout_for_config)?,
+        params,
+        false,
+        initiator_class,
+        *initiator.key,
+    )?;
+    snapshot_wallet_approvers(&mut multisig_op, wallet, clock.unix_timestamp);
+    MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+    wallet.log_metadata_hash();
 
     Ok(())
 }
@@ -122,19 +338,76 @@ pub fn finalize_multisig_op<F>(
     account_to_return_rent_to: &AccountInfo,
     clock: Clock,
     expected_params: MultisigOpParams,
+    on_op_approved: F,
+) -> ProgramResult
+where
+    F: FnMut() -> ProgramResult,
+{
+    finalize_op(
+        multisig_op_account_info,
+        None,
+        account_to_return_rent_to,
+        clock,
+        expected_params,
+        on_op_approved,
+    )
+}
+
+/// Finalizes an op that was tied to the wallet's config version or to its
+/// balance account's op sequence when it started. If a config op or another
+/// op on the account was finalized since, the op is closed without running,
+/// as it was approved under a policy that may no longer apply, or could
+/// replay an old approval over a newer change.
+pub fn finalize_pinned_multisig_op<F>(
+    multisig_op_account_info: &AccountInfo,
+    wallet_account_info: &AccountInfo,
+    account_to_return_rent_to: &AccountInfo,
+    clock: Clock,
+    expected_params: MultisigOpParams,
+    on_op_approved: F,
+) -> ProgramResult
+where
+    F: FnMut() -> ProgramResult,
+{
+    finalize_op(
+        multisig_op_account_info,
+        Some(wallet_account_info),
+        account_to_return_rent_to,
+        clock,
+        expected_params,
+        on_op_approved,
+    )
+}
+
+fn finalize_op<F>(
+    multisig_op_account_info: &AccountInfo,
+    wallet_account_info: Option<&AccountInfo>,
+    account_to_return_rent_to: &AccountInfo,
+    clock: Clock,
+    expected_params: MultisigOpParams,
     mut on_op_approved: F,
 ) -> ProgramResult
 where
     F: FnMut() -> ProgramResult,
 {
-    if !account_to_return_rent_to.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    validate_rent_collector(
+        account_to_return_rent_to,
+        expected_params.wallet_address(),
+        multisig_op_account_info.owner,
+    )?;
 
     let multisig_op = MultisigOp::unpack(&multisig_op_account_info.data.borrow())?;
 
     if multisig_op.approved(&expected_params, &clock)? {
-        on_op_approved()?
+        let started_under_wallet_state = match wallet_account_info {
+            Some(wallet_account_info) => {
+                multisig_op.started_under(&Wallet::unpack(&wallet_account_info.data.borrow())?)
+            }
+            None => true,
+        };
+        if started_under_wallet_state {
+            on_op_approved()?
+        }
     }
 
     collect_remaining_balance(&multisig_op_account_info, &account_to_return_rent_to)?;
@@ -0,0 +1,317 @@
+use crate::error::WalletError;
+use crate::instruction::ProgramInstruction;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountOwner {
+    Any,
+    Program,
+    /// Program-owned, except that an account an earlier finalize has closed
+    /// is reported as `AlreadyFinalized`.
+    MultisigOp,
+}
+
+/// What an instruction expects of one of its accounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountSpec {
+    pub owner: AccountOwner,
+    pub writable: bool,
+    pub signer: bool,
+}
+
+const ANY: AccountSpec = AccountSpec {
+    owner: AccountOwner::Any,
+    writable: false,
+    signer: false,
+};
+
+const SIGNER: AccountSpec = AccountSpec {
+    owner: AccountOwner::Any,
+    writable: false,
+    signer: true,
+};
+
+const PROGRAM_OWNED: AccountSpec = AccountSpec {
+    owner: AccountOwner::Program,
+    writable: false,
+    signer: false,
+};
+
+const PROGRAM_OWNED_WRITABLE: AccountSpec = AccountSpec {
+    owner: AccountOwner::Program,
+    writable: true,
+    signer: false,
+};
+
+const OP_TO_FINALIZE: AccountSpec = AccountSpec {
+    owner: AccountOwner::MultisigOp,
+    writable: true,
+    signer: false,
+};
+
+const WALLET: AccountSpec = PROGRAM_OWNED;
+const WALLET_WRITABLE: AccountSpec = PROGRAM_OWNED_WRITABLE;
+const OP: AccountSpec = PROGRAM_OWNED_WRITABLE;
+
+const STARTS_OP: &[AccountSpec] = &[OP, WALLET];
+const STARTS_OP_UPDATING_WALLET: &[AccountSpec] = &[OP, WALLET_WRITABLE];
+const FINALIZES_OP: &[AccountSpec] = &[OP_TO_FINALIZE, WALLET];
+const FINALIZES_OP_UPDATING_WALLET: &[AccountSpec] = &[OP_TO_FINALIZE, WALLET_WRITABLE];
+
+/// The leading accounts of each instruction, with the ownership and
+/// writability its documentation gives them. Only the op, wallet and other
+/// accounts at fixed positions ahead of the optional clock sysvar are covered,
+/// since the clock shifts everything after it. The handlers check the
+/// remaining accounts, signers included, themselves.
+pub fn account_specs(instruction: &ProgramInstruction) -> &'static [AccountSpec] {
+    match instruction {
+        // these update the wallet as they start the op
+        ProgramInstruction::InitWalletConfigPolicyUpdate { .. }
+        | ProgramInstruction::InitBalanceAccountPolicyUpdate { .. }
+        | ProgramInstruction::InitTransfer { .. }
+        | ProgramInstruction::InitInternalTransfer { .. }
+        | ProgramInstruction::InitCombinedTransfer { .. } => STARTS_OP_UPDATING_WALLET,
+
+        ProgramInstruction::InitBalanceAccountCreation { .. }
+        | ProgramInstruction::InitBalanceAccountNameUpdate { .. }
+        | ProgramInstruction::InitWalletRepair
+        | ProgramInstruction::InitWrapUnwrap { .. }
+        | ProgramInstruction::InitUpdateSigner { .. }
+        | ProgramInstruction::InitUpdateSigners { .. }
+        | ProgramInstruction::InitDAppTransaction { .. }
+        | ProgramInstruction::InitAccountSettingsUpdate { .. }
+        | ProgramInstruction::InitDAppBookUpdate { .. }
+        | ProgramInstruction::InitAddressBookUpdate { .. }
+        | ProgramInstruction::InitAddressBookVerification { .. }
+        | ProgramInstruction::InitActivityCheckpoint { .. }
+        | ProgramInstruction::InitAddressBookPageUpdate { .. }
+        | ProgramInstruction::InitApproverDelegationUpdate { .. }
+        | ProgramInstruction::InitWalletSignerDirectoryUpdate { .. }
+        | ProgramInstruction::InitWalletCanaryUpdate { .. }
+        | ProgramInstruction::InitGovernedParameterUpdate { .. }
+        | ProgramInstruction::InitViewersUpdate { .. }
+        | ProgramInstruction::InitSignerOrganizationsUpdate { .. }
+        | ProgramInstruction::InitAssistantUpdate { .. }
+        | ProgramInstruction::InitAddressBookUpdateChunked { .. }
+        | ProgramInstruction::InitWalletStateExport { .. }
+        | ProgramInstruction::InitWalletMetadataUpdate { .. }
+        | ProgramInstruction::InitDAppTemplatesUpdate { .. }
+        | ProgramInstruction::InitWalletClose { .. }
+        | ProgramInstruction::RenewMultisigOp { .. } => STARTS_OP,
+
+        ProgramInstruction::FinalizeWalletConfigPolicyUpdate { .. }
+        | ProgramInstruction::FinalizeBalanceAccountCreation { .. }
+        | ProgramInstruction::FinalizeBalanceAccountNameUpdate { .. }
+        | ProgramInstruction::FinalizeBalanceAccountPolicyUpdate { .. }
+        | ProgramInstruction::FinalizeTransfer { .. }
+        | ProgramInstruction::FinalizeWalletRepair
+        | ProgramInstruction::FinalizeWrapUnwrap { .. }
+        | ProgramInstruction::FinalizeUpdateSigner { .. }
+        | ProgramInstruction::FinalizeUpdateSigners { .. }
+        | ProgramInstruction::FinalizeDAppTransaction { .. }
+        | ProgramInstruction::FinalizeAccountSettingsUpdate { .. }
+        | ProgramInstruction::FinalizeDAppBookUpdate { .. }
+        | ProgramInstruction::FinalizeAddressBookUpdate { .. }
+        | ProgramInstruction::FinalizeAddressBookVerification { .. }
+        | ProgramInstruction::FinalizeActivityCheckpoint { .. }
+        | ProgramInstruction::FinalizeAddressBookPageUpdate { .. }
+        | ProgramInstruction::FinalizeApproverDelegationUpdate { .. }
+        | ProgramInstruction::FinalizeWalletSignerDirectoryUpdate { .. }
+        | ProgramInstruction::FinalizeWalletCanaryUpdate { .. }
+        | ProgramInstruction::FinalizeInternalTransfer { .. }
+        | ProgramInstruction::FinalizeGovernedParameterUpdate { .. }
+        | ProgramInstruction::FinalizeViewersUpdate { .. }
+        | ProgramInstruction::FinalizeSignerOrganizationsUpdate { .. }
+        | ProgramInstruction::FinalizeAssistantUpdate { .. }
+        | ProgramInstruction::FinalizeAddressBookUpdateChunked { .. }
+        | ProgramInstruction::FinalizeCombinedTransfer { .. }
+        | ProgramInstruction::FinalizeWalletMetadataUpdate { .. }
+        | ProgramInstruction::FinalizeDAppTemplatesUpdate { .. }
+        | ProgramInstruction::FinalizeWalletClose { .. }
+        | ProgramInstruction::ExecuteDAppChunk { .. }
+        | ProgramInstruction::ExpireOp => FINALIZES_OP_UPDATING_WALLET,
+
+        ProgramInstruction::FinalizeWalletStateExport { .. } => FINALIZES_OP,
+
+        ProgramInstruction::SetApprovalDisposition { .. }
+        | ProgramInstruction::VerifyCoSignature { .. } => &[OP],
+
+        ProgramInstruction::SubmitApprovalDisposition { .. } => &[PROGRAM_OWNED],
+
+        // the op account may already have been closed
+        ProgramInstruction::CollectApprovalDispositions { .. } => &[ANY],
+
+        ProgramInstruction::InitWallet { .. } => &[PROGRAM_OWNED_WRITABLE],
+
+        ProgramInstruction::ImportWalletState => &[PROGRAM_OWNED_WRITABLE, ANY, SIGNER],
+
+        ProgramInstruction::CheckWalletIntegrity
+        | ProgramInstruction::AssertPolicyManifest { .. }
+        | ProgramInstruction::ReportCapacity { .. }
+        | ProgramInstruction::ReportSignerActivity { .. }
+        | ProgramInstruction::VerifyAccess
+        | ProgramInstruction::VerifyNameHash { .. }
+        | ProgramInstruction::ConsolidateDeposits { .. } => &[WALLET],
+
+        ProgramInstruction::ExecuteDAppTemplate { .. } => &[WALLET_WRITABLE],
+
+        ProgramInstruction::PreflightTransfer { .. }
+        | ProgramInstruction::CreateOpAccount
+        | ProgramInstruction::CreateOpAccountForParams { .. }
+        | ProgramInstruction::InitPendingOps => &[ANY, WALLET],
+
+        ProgramInstruction::CleanupPendingOps => &[PROGRAM_OWNED_WRITABLE],
+
+        ProgramInstruction::UpdateProgramConfig { .. }
+        | ProgramInstruction::UpdateSignerDirectory { .. }
+        | ProgramInstruction::SupplyAddressBookChunk { .. } => &[PROGRAM_OWNED],
+
+        // the signer leads and the op accounts follow in any number
+        ProgramInstruction::SetApprovalDispositions { .. } => &[],
+
+        // these create their account from scratch and check it themselves
+        ProgramInstruction::InitProgramConfig { .. } | ProgramInstruction::InitSignerDirectory => {
+            &[]
+        }
+
+        // the wrapped instruction is validated when it is dispatched
+        ProgramInstruction::ValidateOnly { .. }
+        | ProgramInstruction::InitWithPrerequisite { .. }
+        | ProgramInstruction::InitWithIdempotencyKey { .. } => &[],
+    }
+}
+
+pub fn validate_account(
+    program_id: &Pubkey,
+    account_info: &AccountInfo,
+    spec: &AccountSpec,
+) -> ProgramResult {
+    match spec.owner {
+        AccountOwner::Any => {}
+        AccountOwner::MultisigOp
+            if account_info.lamports() == 0 && account_info.data_is_empty() =>
+        {
+            msg!("Operation account is closed; the operation has already been finalized");
+            return Err(WalletError::AlreadyFinalized.into());
+        }
+        AccountOwner::Program | AccountOwner::MultisigOp => {
+            if account_info.owner != program_id {
+                msg!(
+                    "Account {} does not belong to the program",
+                    account_info.key
+                );
+                return Err(ProgramError::IncorrectProgramId);
+            }
+        }
+    }
+    if spec.writable && !account_info.is_writable {
+        msg!("Account {} has to be writable", account_info.key);
+        return Err(ProgramError::InvalidArgument);
+    }
+    if spec.signer && !account_info.is_signer {
+        msg!("Account {} has to sign", account_info.key);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+/// Checks the leading accounts against what the instruction expects of them,
+/// before any handler logic runs, so that a substituted account fails the
+/// same way whichever instruction it is passed to.
+pub fn validate_accounts(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction: &ProgramInstruction,
+) -> ProgramResult {
+    let specs = account_specs(instruction);
+    if accounts.len() < specs.len() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    for (account_info, spec) in accounts.iter().zip(specs) {
+        validate_account(program_id, account_info, spec)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_validate_finalize_accounts() {
+    let program_id = Pubkey::new_unique();
+    let other_program_id = Pubkey::new_unique();
+    let op_key = Pubkey::new_unique();
+    let wallet_key = Pubkey::new_unique();
+    let (mut op_lamports, mut wallet_lamports) = (1, 1);
+    let (mut op_data, mut wallet_data) = (vec![0; 1], vec![0; 1]);
+    let mut op = AccountInfo::new(
+        &op_key,
+        false,
+        true,
+        &mut op_lamports,
+        &mut op_data,
+        &program_id,
+        false,
+        0,
+    );
+    let mut wallet = AccountInfo::new(
+        &wallet_key,
+        false,
+        true,
+        &mut wallet_lamports,
+        &mut wallet_data,
+        &program_id,
+        false,
+        0,
+    );
+    let instruction = ProgramInstruction::ExpireOp;
+
+    assert_eq!(
+        validate_accounts(&program_id, &[op.clone(), wallet.clone()], &instruction),
+        Ok(())
+    );
+    assert_eq!(
+        validate_accounts(&program_id, &[op.clone()], &instruction),
+        Err(ProgramError::NotEnoughAccountKeys)
+    );
+    assert_eq!(
+        validate_accounts(
+            &other_program_id,
+            &[op.clone(), wallet.clone()],
+            &instruction
+        ),
+        Err(ProgramError::IncorrectProgramId)
+    );
+
+    // expiring an op releases what it held in the wallet
+    wallet.is_writable = false;
+    assert_eq!(
+        validate_accounts(&program_id, &[op.clone(), wallet.clone()], &instruction),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(
+        validate_accounts(
+            &program_id,
+            &[op.clone(), wallet.clone()],
+            &ProgramInstruction::FinalizeWalletStateExport {
+                target_program_id: Pubkey::new_unique()
+            }
+        ),
+        Ok(())
+    );
+    wallet.is_writable = true;
+
+    op.is_writable = false;
+    assert_eq!(
+        validate_accounts(&program_id, &[op.clone(), wallet.clone()], &instruction),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    **op.lamports.borrow_mut() = 0;
+    *op.data.borrow_mut() = &mut [];
+    assert_eq!(
+        validate_accounts(&program_id, &[op, wallet], &instruction),
+        Err(WalletError::AlreadyFinalized.into())
+    );
+}
@@ -0,0 +1,107 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{
+    finalize_multisig_op, get_clock, next_multisig_op_account_info, next_program_account_info,
+    start_multisig_config_op, unpack_wallet,
+};
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::wallet::Wallet;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+/// Freezes every wallet among the accounts whose canary key signed the
+/// instruction, and returns whether any did. The instruction itself must then
+/// not be carried out, but it has to succeed for the freeze to be kept, so a
+/// wallet that is not writable here can only be protected by failing.
+pub fn freeze_on_canary_signature(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> Result<bool, ProgramError> {
+    let mut canary_signed = false;
+    for account_info in accounts.iter().filter(|a| a.owner == program_id) {
+        let canary_key = match Wallet::unpack_canary(&account_info.data.borrow()) {
+            Some((Some(canary_key), _)) => canary_key,
+            _ => continue,
+        };
+        if !accounts.iter().any(|a| a.is_signer && *a.key == canary_key) {
+            continue;
+        }
+        if !account_info.is_writable {
+            msg!("Instruction was signed by the canary key of a wallet that can't be frozen");
+            return Err(WalletError::WalletFrozen.into());
+        }
+        msg!("Instruction was signed by the canary key, freezing the wallet");
+        Wallet::freeze_in_place(&mut account_info.data.borrow_mut());
+        canary_signed = true;
+    }
+    Ok(canary_signed)
+}
+
+pub fn validate_wallets_not_frozen(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    for account_info in accounts.iter().filter(|a| a.owner == program_id) {
+        if let Some((_, true)) = Wallet::unpack_canary(&account_info.data.borrow()) {
+            msg!("Wallet is frozen until its canary key is updated");
+            return Err(WalletError::WalletFrozen.into());
+        }
+    }
+    Ok(())
+}
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    canary_key: &Option<Pubkey>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let initiator_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    wallet.validate_config_initiator(initiator_account_info)?;
+    wallet.validate_canary_key(canary_key)?;
+
+    start_multisig_config_op(
+        &multisig_op_account_info,
+        &wallet,
+        initiator_account_info,
+        clock,
+        MultisigOpParams::UpdateWalletCanary {
+            wallet_address: *wallet_account_info.key,
+            canary_key: *canary_key,
+        },
+    )
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    canary_key: &Option<Pubkey>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let account_to_return_rent_to = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    finalize_multisig_op(
+        &multisig_op_account_info,
+        &account_to_return_rent_to,
+        clock,
+        MultisigOpParams::UpdateWalletCanary {
+            wallet_address: *wallet_account_info.key,
+            canary_key: *canary_key,
+        },
+        || -> ProgramResult {
+            let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+            wallet.update_canary_key(*canary_key)?;
+            wallet.record_config_change();
+            Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+            Ok(())
+        },
+    )
+}
@@ -0,0 +1,247 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{
+    collect_remaining_balance, finalize_pinned_multisig_op, get_clock,
+    next_multisig_op_account_info, next_program_account_info, start_multisig_config_op,
+    transfer_sol_checked, unpack_wallet,
+};
+use crate::model::balance_account::BalanceAccount;
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::wallet::Wallet;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_program::sysvar::Sysvar;
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::id as SPL_TOKEN_ID;
+use spl_token::instruction as spl_instruction;
+use spl_token::state::Account as SPLAccount;
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    rent_collector: &Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let initiator_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+
+    if rent_collector == wallet_account_info.key {
+        msg!("The wallet can't collect its own rent");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    wallet.validate_config_initiator(initiator_account_info)?;
+
+    start_multisig_config_op(
+        &multisig_op_account_info,
+        &wallet,
+        initiator_account_info,
+        clock,
+        MultisigOpParams::CloseWallet {
+            wallet_address: *wallet_account_info.key,
+            rent_collector: *rent_collector,
+        },
+    )
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    rent_collector: &Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let account_to_return_rent_to = next_account_info(accounts_iter)?;
+    let wallet_rent_collector_info = next_account_info(accounts_iter)?;
+    let system_program_account_info = next_account_info(accounts_iter)?;
+    let token_program_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
+    let remaining_accounts: Vec<&AccountInfo> = accounts_iter.collect();
+
+    if wallet_rent_collector_info.key != rent_collector {
+        msg!("Account is not the rent collector the close was approved with");
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+    if *token_program_account_info.key != SPL_TOKEN_ID() {
+        msg!("Account is not the SPL token program");
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    finalize_pinned_multisig_op(
+        &multisig_op_account_info,
+        &wallet_account_info,
+        &account_to_return_rent_to,
+        clock,
+        MultisigOpParams::CloseWallet {
+            wallet_address: *wallet_account_info.key,
+            rent_collector: *rent_collector,
+        },
+        || -> ProgramResult {
+            let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+            // a balance account without data only holds what it takes to stay
+            // rent exempt once it is empty
+            let rent_exempt_minimum = Rent::get()?.minimum_balance(0);
+            let tracked_token_mints = wallet.tracked_token_mints();
+
+            for (_, balance_account) in wallet.balance_accounts.filled_slots() {
+                let guid_hash = balance_account.guid_hash;
+                let (balance_account_info, bump_seed) =
+                    find_balance_account_info(&remaining_accounts, &balance_account, program_id)?;
+                if balance_account_info.lamports() > rent_exempt_minimum {
+                    msg!(
+                        "Balance account {} still holds {} lamports",
+                        balance_account_info.key,
+                        balance_account_info.lamports()
+                    );
+                    return Err(WalletError::BalanceAccountNotEmpty.into());
+                }
+                validate_token_accounts_passed(
+                    &remaining_accounts,
+                    balance_account_info.key,
+                    &tracked_token_mints,
+                )?;
+
+                for token_account_info in remaining_accounts
+                    .iter()
+                    .filter(|a| *a.owner == SPL_TOKEN_ID())
+                {
+                    let token_account = SPLAccount::unpack(&token_account_info.data.borrow())?;
+                    if token_account.owner != *balance_account_info.key {
+                        continue;
+                    }
+                    if token_account.amount > 0 {
+                        msg!(
+                            "Token account {} still holds {} tokens",
+                            token_account_info.key,
+                            token_account.amount
+                        );
+                        return Err(WalletError::BalanceAccountNotEmpty.into());
+                    }
+                    invoke_signed(
+                        &spl_instruction::close_account(
+                            &SPL_TOKEN_ID(),
+                            token_account_info.key,
+                            wallet_rent_collector_info.key,
+                            balance_account_info.key,
+                            &[],
+                        )?,
+                        &[
+                            (*token_account_info).clone(),
+                            wallet_rent_collector_info.clone(),
+                            balance_account_info.clone(),
+                            token_program_account_info.clone(),
+                        ],
+                        &[&[guid_hash.to_bytes(), &[bump_seed]]],
+                    )?;
+                }
+
+                if balance_account_info.lamports() > 0 {
+                    transfer_sol_checked(
+                        balance_account_info.clone(),
+                        &guid_hash,
+                        bump_seed,
+                        system_program_account_info.clone(),
+                        wallet_rent_collector_info.clone(),
+                        balance_account_info.lamports(),
+                    )?;
+                }
+            }
+
+            sweep_fee_account(
+                &remaining_accounts,
+                wallet_account_info.key,
+                wallet_rent_collector_info,
+                system_program_account_info,
+                program_id,
+            )?;
+
+            msg!("Closing wallet {}", wallet_account_info.key);
+            wallet_account_info.data.borrow_mut().fill(0);
+            collect_remaining_balance(&wallet_account_info, &wallet_rent_collector_info)
+        },
+    )
+}
+
+/// Finds the given balance account among the accounts, along with its bump
+/// seed. Every balance account has to be passed, as it
+/// could otherwise be left holding funds once the wallet is gone.
+fn find_balance_account_info<'a, 'b>(
+    accounts: &[&'a AccountInfo<'b>],
+    balance_account: &BalanceAccount,
+    program_id: &Pubkey,
+) -> Result<(&'a AccountInfo<'b>, u8), ProgramError> {
+    let (address, bump_seed) = balance_account.find_address(program_id)?;
+    match accounts.iter().find(|a| *a.key == address) {
+        Some(account_info) => Ok((*account_info, bump_seed)),
+        None => {
+            msg!("Balance account {} was not passed", address);
+            Err(WalletError::AccountNotRecognized.into())
+        }
+    }
+}
+
+/// Checks that the balance account's associated token account for every mint
+/// the wallet tracks was passed, whether or not it has been created, so that
+/// none is left holding tokens once the wallet is gone.
+fn validate_token_accounts_passed(
+    accounts: &[&AccountInfo],
+    balance_account: &Pubkey,
+    token_mints: &[Pubkey],
+) -> ProgramResult {
+    for token_mint in token_mints {
+        let address = get_associated_token_address(balance_account, token_mint);
+        if !accounts.iter().any(|a| *a.key == address) {
+            msg!(
+                "Token account {} of balance account {} was not passed",
+                address,
+                balance_account
+            );
+            return Err(WalletError::AccountNotRecognized.into());
+        }
+    }
+    Ok(())
+}
+
+/// Moves whatever the wallet's fee account holds to the rent collector, when
+/// the fee account is among the accounts.
+fn sweep_fee_account<'a>(
+    accounts: &[&AccountInfo<'a>],
+    wallet_address: &Pubkey,
+    rent_collector: &AccountInfo<'a>,
+    system_program_account_info: &AccountInfo<'a>,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let (fee_account_address, bump_seed) =
+        Wallet::find_fee_account_address(wallet_address, program_id);
+    let fee_account_info = match accounts.iter().find(|a| *a.key == fee_account_address) {
+        Some(fee_account_info) if fee_account_info.lamports() > 0 => fee_account_info,
+        _ => return Ok(()),
+    };
+    invoke_signed(
+        &system_instruction::transfer(
+            fee_account_info.key,
+            rent_collector.key,
+            fee_account_info.lamports(),
+        ),
+        &[
+            (*fee_account_info).clone(),
+            rent_collector.clone(),
+            system_program_account_info.clone(),
+        ],
+        &[&[
+            wallet_address.as_ref(),
+            Wallet::FEE_ACCOUNT_SEED,
+            &[bump_seed],
+        ]],
+    )
+}
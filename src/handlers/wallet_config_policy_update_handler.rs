@@ -1,9 +1,10 @@
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    start_multisig_config_op,
+    finalize_multisig_op, get_clock, next_multisig_op_account_info, next_program_account_info,
+    start_multisig_config_op, unpack_wallet,
 };
 use crate::instruction::WalletConfigPolicyUpdate;
 use crate::model::multisig_op::MultisigOpParams;
+use crate::model::program_config::PolicyLimits;
 use crate::model::wallet::Wallet;
 use solana_program::account_info::{next_account_info, AccountInfo};
 use solana_program::entrypoint::ProgramResult;
@@ -14,22 +15,24 @@ pub fn init(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     update: &WalletConfigPolicyUpdate,
+    limits: &PolicyLimits,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let initiator_account_info = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
-    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let mut wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
 
     wallet.validate_config_initiator(initiator_account_info)?;
     wallet.lock_config_policy_updates()?;
-    wallet.validate_config_policy_update(update)?;
+    wallet.validate_config_policy_update(update, limits)?;
 
     start_multisig_config_op(
         &multisig_op_account_info,
         &wallet,
+        initiator_account_info,
         clock,
         MultisigOpParams::UpdateWalletConfigPolicy {
             wallet_address: *wallet_account_info.key,
@@ -46,12 +49,13 @@ pub fn finalize(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     update: &WalletConfigPolicyUpdate,
+    limits: &PolicyLimits,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let account_to_return_rent_to = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
     let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow_mut())?;
 
@@ -64,7 +68,8 @@ pub fn finalize(
             update: update.clone(),
         },
         || -> ProgramResult {
-            wallet.update_config_policy(update)?;
+            wallet.update_config_policy(update, limits)?;
+            wallet.record_config_change();
             Ok(())
         },
     )?;
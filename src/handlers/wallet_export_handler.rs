@@ -0,0 +1,151 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{
+    calculate_expires, finalize_multisig_op, get_clock, next_multisig_op_account_info,
+    next_program_account_info, snapshot_wallet_approvers, unpack_multisig_op_for_init,
+    unpack_wallet,
+};
+use crate::model::multisig_op::{MultisigOp, MultisigOpParams};
+use crate::model::program_config::ProgramConfig;
+use crate::model::wallet::Wallet;
+use crate::model::wallet_export::WalletExport;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack};
+use solana_program::pubkey::Pubkey;
+use std::slice::Iter;
+
+/// Skips the program config account, which the processor has already looked
+/// up, and returns the config it found.
+fn next_program_config<'a>(
+    iter: &mut Iter<AccountInfo>,
+    program_config: Option<&'a ProgramConfig>,
+) -> Result<&'a ProgramConfig, ProgramError> {
+    next_account_info(iter)?;
+    program_config.ok_or_else(|| {
+        msg!("Migrating a wallet requires the program config account");
+        WalletError::MigrationProgramNotAllowed.into()
+    })
+}
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    target_program_id: &Pubkey,
+    program_config: Option<&ProgramConfig>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let initiator_account_info = next_account_info(accounts_iter)?;
+    let program_config = next_program_config(accounts_iter, program_config)?;
+    let clock = get_clock(accounts_iter)?;
+
+    program_config.validate_migration_program(target_program_id)?;
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
+    wallet.validate_config_initiator(initiator_account_info)?;
+    if wallet.signer_directory.is_some() {
+        msg!("A wallet backed by a signer directory can't be exported");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // unlike other config ops, exporting takes every config approver
+    let mut multisig_op = unpack_multisig_op_for_init(multisig_op_account_info, &wallet)?;
+    let config_approvers = wallet.get_config_approvers_keys();
+    let approvals_required = config_approvers.len() as u8;
+    multisig_op.init(
+        config_approvers,
+        approvals_required,
+        clock.unix_timestamp,
+        calculate_expires(clock.unix_timestamp, wallet.approval_timeout_for_config)?,
+        MultisigOpParams::ExportWalletState {
+            wallet_address: *wallet_account_info.key,
+            target_program_id: *target_program_id,
+        },
+        false,
+        wallet.initiator_class(initiator_account_info),
+        *initiator_account_info.key,
+    )?;
+    snapshot_wallet_approvers(&mut multisig_op, &wallet, clock.unix_timestamp);
+    MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    target_program_id: &Pubkey,
+    program_config: Option<&ProgramConfig>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let account_to_return_rent_to = next_account_info(accounts_iter)?;
+    let export_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let program_config = next_program_config(accounts_iter, program_config)?;
+    let clock = get_clock(accounts_iter)?;
+    let exported_at = clock.unix_timestamp;
+
+    finalize_multisig_op(
+        &multisig_op_account_info,
+        &account_to_return_rent_to,
+        clock,
+        MultisigOpParams::ExportWalletState {
+            wallet_address: *wallet_account_info.key,
+            target_program_id: *target_program_id,
+        },
+        || -> ProgramResult {
+            // the allowlist may have changed since the export was approved
+            program_config.validate_migration_program(target_program_id)?;
+
+            let export = WalletExport::unpack_unchecked(&export_account_info.data.borrow())?;
+            if export.is_initialized && export.wallet_address != *wallet_account_info.key {
+                msg!("Export account holds the export of another wallet");
+                return Err(WalletError::AccountNotRecognized.into());
+            }
+
+            WalletExport::pack(
+                WalletExport {
+                    is_initialized: true,
+                    wallet_address: *wallet_account_info.key,
+                    target_program_id: *target_program_id,
+                    exported_at,
+                    wallet: Wallet::unpack(&wallet_account_info.data.borrow())?,
+                },
+                &mut export_account_info.data.borrow_mut(),
+            )
+        },
+    )
+}
+
+pub fn import(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    program_config: Option<&ProgramConfig>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let export_account_info = next_account_info(accounts_iter)?;
+    let assistant_account_info = next_account_info(accounts_iter)?;
+    let program_config = next_program_config(accounts_iter, program_config)?;
+
+    // only an allowlisted program can have written the export account
+    program_config.validate_migration_program(export_account_info.owner)?;
+    let export = WalletExport::unpack(&export_account_info.data.borrow())?;
+    if export.target_program_id != *program_id {
+        msg!("Wallet state was exported to another program");
+        return Err(WalletError::MigrationProgramNotAllowed.into());
+    }
+    if !assistant_account_info.is_signer
+        || export.wallet.assistant.key != *assistant_account_info.key
+    {
+        msg!("Importing a wallet requires the signature of its assistant");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let wallet = Wallet::unpack_unchecked(&wallet_account_info.data.borrow())?;
+    if wallet.is_initialized() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    Wallet::pack(export.wallet, &mut wallet_account_info.data.borrow_mut())
+}
@@ -1,13 +1,18 @@
 use crate::error::WalletError;
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    start_multisig_transfer_op, transfer_sol_checked, validate_balance_account_and_get_seed,
+    finalize_pinned_multisig_op, get_approval_timeout, get_clock, next_multisig_op_account_info,
+    next_program_account_info, start_multisig_transfer_op, transfer_sol_checked, unpack_wallet,
+    validate_balance_account_and_get_seed,
 };
+use crate::model::address_book::AddressBookEntry;
 use crate::model::balance_account::BalanceAccountGuidHash;
 use crate::model::multisig_op::{MultisigOpParams, WrapDirection};
+use crate::model::policy::{Destination, PolicyRequest};
+use crate::model::program_config::PolicyLimits;
 use crate::model::wallet::Wallet;
 use solana_program::account_info::{next_account_info, AccountInfo};
 use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::Hash;
 use solana_program::instruction::{AccountMeta, Instruction};
 use solana_program::program::{invoke, invoke_signed};
 use solana_program::program_error::ProgramError;
@@ -17,6 +22,36 @@ use solana_program::system_program;
 use solana_program::{msg, sysvar};
 use spl_associated_token_account::get_associated_token_address;
 use spl_token::state::Account as SPLAccount;
+use std::time::Duration;
+
+/// Creates the balance account's wrapped SOL account, paying its rent from the
+/// balance account. `accounts` must contain the native mint, the rent sysvar and
+/// the SPL token and associated token programs.
+fn create_wrapped_sol_account(
+    accounts: &[AccountInfo],
+    balance_account_info: &AccountInfo,
+    wrapped_sol_account_info: &AccountInfo,
+    account_guid_hash: &BalanceAccountGuidHash,
+    bump_seed: u8,
+) -> ProgramResult {
+    invoke_signed(
+        &Instruction {
+            program_id: spl_associated_token_account::id(),
+            accounts: vec![
+                AccountMeta::new(*balance_account_info.key, true),
+                AccountMeta::new(*wrapped_sol_account_info.key, false),
+                AccountMeta::new_readonly(*balance_account_info.key, false),
+                AccountMeta::new_readonly(spl_token::native_mint::id(), false),
+                AccountMeta::new_readonly(solana_program::system_program::id(), false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(sysvar::rent::id(), false),
+            ],
+            data: vec![],
+        },
+        accounts,
+        &[&[&account_guid_hash.to_bytes(), &[bump_seed]]],
+    )
+}
 
 pub fn init(
     program_id: &Pubkey,
@@ -24,6 +59,10 @@ pub fn init(
     account_guid_hash: &BalanceAccountGuidHash,
     amount: u64,
     direction: WrapDirection,
+    approval_timeout: Option<Duration>,
+    destination: Option<AddressBookEntry>,
+    travel_rule_hash: Option<Hash>,
+    limits: &PolicyLimits,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
@@ -37,13 +76,51 @@ pub fn init(
     }
 
     let initiator_account = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let wallet = unpack_wallet(program_id, wallet_account_info, accounts)?;
     let balance_account = wallet.get_balance_account(&account_guid_hash)?;
 
     wallet.validate_transfer_initiator(initiator_account)?;
 
+    let policy_destination = match destination {
+        None if travel_rule_hash.is_some() => {
+            msg!("Only an unwrap to a destination can carry travel rule data");
+            return Err(ProgramError::InvalidArgument);
+        }
+        None => Destination::BalanceAccount,
+        Some(_) if direction == WrapDirection::WRAP => {
+            msg!("Only an unwrap can have a destination");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Some(destination) => {
+            wallet.validate_destination_token_mint(&destination.address, &Pubkey::default())?;
+            wallet.validate_destination_namespace(
+                &destination.address,
+                &Pubkey::default(),
+                None,
+                travel_rule_hash.as_ref(),
+            )?;
+            Destination::Address {
+                whitelisted: wallet.destination_allowed(
+                    &balance_account,
+                    &destination.address,
+                    &destination.name_hash,
+                )?,
+            }
+        }
+    };
+    let mut policy = wallet.evaluate_policy(
+        &balance_account,
+        &PolicyRequest {
+            amount,
+            destination: policy_destination,
+            now: clock.unix_timestamp,
+        },
+    )?;
+    policy.approval_timeout =
+        get_approval_timeout(limits, policy.approval_timeout, approval_timeout)?;
+
     if direction == WrapDirection::WRAP && *wrapped_sol_account_info.owner == Pubkey::default() {
         // we need to create the wrapped SOL account (if it had been created already
         // it would be owned by the Token program). Since this is an attempt to wrap
@@ -51,38 +128,31 @@ pub fn init(
         // they have enough to create this account (if they don't, it will just fail)
         let bump_seed = validate_balance_account_and_get_seed(
             balance_account_info,
-            account_guid_hash,
+            &balance_account,
             program_id,
         )?;
-        invoke_signed(
-            &Instruction {
-                program_id: spl_associated_token_account::id(),
-                accounts: vec![
-                    AccountMeta::new(*balance_account_info.key, true),
-                    AccountMeta::new(*wrapped_sol_account_info.key, false),
-                    AccountMeta::new_readonly(*balance_account_info.key, false),
-                    AccountMeta::new_readonly(*native_mint_account_info.key, false),
-                    AccountMeta::new_readonly(solana_program::system_program::id(), false),
-                    AccountMeta::new_readonly(spl_token::id(), false),
-                    AccountMeta::new_readonly(sysvar::rent::id(), false),
-                ],
-                data: vec![],
-            },
+        create_wrapped_sol_account(
             accounts,
-            &[&[&account_guid_hash.to_bytes(), &[bump_seed]]],
+            balance_account_info,
+            wrapped_sol_account_info,
+            account_guid_hash,
+            bump_seed,
         )?;
     }
 
     start_multisig_transfer_op(
         &multisig_op_account_info,
         &wallet,
-        &balance_account,
+        policy,
+        initiator_account,
         clock,
         MultisigOpParams::Wrap {
             wallet_address: *wallet_account_info.key,
             account_guid_hash: *account_guid_hash,
             amount,
             direction,
+            destination: destination.map(|destination| destination.address),
+            travel_rule_hash,
         },
     )
 }
@@ -93,22 +163,34 @@ pub fn finalize(
     account_guid_hash: &BalanceAccountGuidHash,
     amount: u64,
     direction: WrapDirection,
+    destination: Option<Pubkey>,
+    travel_rule_hash: Option<Hash>,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let multisig_op_account_info = next_multisig_op_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
     let balance_account_info = next_account_info(accounts_iter)?;
     let system_program_account_info = next_account_info(accounts_iter)?;
     let rent_collector_account_info = next_account_info(accounts_iter)?;
-    let clock = get_clock_from_next_account(accounts_iter)?;
+    let clock = get_clock(accounts_iter)?;
     let wrapped_sol_account_info = next_account_info(accounts_iter)?;
 
     if system_program_account_info.key != &system_program::id() {
         return Err(WalletError::AccountNotRecognized.into());
     }
+    let destination_account_info = match destination {
+        Some(destination) => Some(
+            accounts
+                .iter()
+                .find(|account_info| *account_info.key == destination)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?,
+        ),
+        None => None,
+    };
 
-    finalize_multisig_op(
+    finalize_pinned_multisig_op(
         &multisig_op_account_info,
+        &wallet_account_info,
         &rent_collector_account_info,
         clock,
         MultisigOpParams::Wrap {
@@ -116,11 +198,14 @@ pub fn finalize(
             account_guid_hash: *account_guid_hash,
             amount,
             direction,
+            destination,
+            travel_rule_hash,
         },
         || -> ProgramResult {
+            let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
             let bump_seed = validate_balance_account_and_get_seed(
                 balance_account_info,
-                account_guid_hash,
+                &wallet.get_balance_account(account_guid_hash)?,
                 program_id,
             )?;
 
@@ -133,6 +218,16 @@ pub fn finalize(
             }
 
             if direction == WrapDirection::WRAP {
+                if *wrapped_sol_account_info.owner == Pubkey::default() {
+                    // the account was closed by a full unwrap after this wrap was started
+                    create_wrapped_sol_account(
+                        accounts,
+                        balance_account_info,
+                        wrapped_sol_account_info,
+                        account_guid_hash,
+                        bump_seed,
+                    )?;
+                }
                 transfer_sol_checked(
                     balance_account_info.clone(),
                     account_guid_hash,
@@ -154,7 +249,10 @@ pub fn finalize(
                 }
 
                 // the only way to transfer lamports out of a token account is to close it, so we first
-                // close it and then transfer back whatever is remaining
+                // close it and then transfer back whatever is remaining. On a full unwrap the account
+                // stays closed and its rent is reclaimed by the balance account. An unwrap with a
+                // destination sends the unwrapped amount on from the balance account.
+                let full_unwrap = wrapped_sol_account_data.amount == amount;
                 let remaining = wrapped_sol_account_info
                     .lamports()
                     .checked_sub(amount)
@@ -174,6 +272,27 @@ pub fn finalize(
                     ],
                     &[&[&account_guid_hash.to_bytes(), &[bump_seed]]],
                 )?;
+                if let Some(destination_account_info) = destination_account_info {
+                    transfer_sol_checked(
+                        balance_account_info.clone(),
+                        account_guid_hash,
+                        bump_seed,
+                        system_program_account_info.clone(),
+                        destination_account_info.clone(),
+                        amount,
+                    )?;
+                    if let Some(travel_rule_hash) = travel_rule_hash {
+                        msg!("Travel rule data hash: {}", travel_rule_hash);
+                    }
+                    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+                    wallet
+                        .activity
+                        .record_transfer(&Pubkey::default(), amount)?;
+                    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+                }
+                if full_unwrap {
+                    return Ok(());
+                }
 
                 transfer_sol_checked(
                     balance_account_info.clone(),
@@ -5,26 +5,52 @@ use std::time::Duration;
 
 use bitvec::macros::internal::funty::Fundamental;
 use bytes::BufMut;
-use solana_program::hash::Hash;
+use solana_program::hash::{Hash, HASH_BYTES};
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::Pack;
+use solana_program::pubkey::PUBKEY_BYTES;
 use solana_program::{instruction::AccountMeta, instruction::Instruction, pubkey::Pubkey};
 
-use crate::model::address_book::{AddressBookEntry, AddressBookEntryNameHash, DAppBookEntry};
+use crate::error::WalletError;
+use crate::model::address_book::{
+    AddressBookEntry, AddressBookEntryNameHash, DAppBookEntry, DestinationNamespace,
+    DestinationTokenMint,
+};
+use crate::model::approver_delegation::ApproverDelegation;
 use crate::model::balance_account::{
-    BalanceAccount, BalanceAccountGuidHash, BalanceAccountNameHash,
+    BalanceAccount, BalanceAccountGuidHash, BalanceAccountNameHash, LargeTransferCooldown,
 };
+use crate::model::dapp_approval_policy::DAppApprovalPolicy;
+use crate::model::dapp_inner_programs::DAppInnerPrograms;
+use crate::model::dapp_template::DAppTemplate;
+use crate::model::dapp_value_limit::DAppValueLimit;
+use crate::model::governed_parameter::GovernedParameter;
+use crate::model::idempotency::IdempotencyKey;
 use crate::model::multisig_op::{
-    ApprovalDisposition, BooleanSetting, SlotUpdateType, WrapDirection,
+    AmountSpec, ApprovalDisposition, AtaRentPayer, BooleanSetting, DenialReason, SlotUpdateType,
+    WrapDirection,
 };
+use crate::model::policy::PolicyRule;
 use crate::model::signer::Signer;
+use crate::model::token_mint::TokenMint;
+use crate::model::viewer::Viewer;
 use crate::serialization_utils::{
-    append_duration, append_optional_duration, append_optional_u8, pack_option, read_duration,
-    read_fixed_size_array, read_optional_duration, read_optional_u8, read_slice, read_u16, read_u8,
-    unpack_option,
+    append_duration, append_optional_duration, append_optional_u64, append_optional_u8,
+    pack_option, read_duration, read_fixed_size_array, read_optional_duration, read_optional_u64,
+    read_optional_u8, read_slice, read_u16, read_u8, unpack_option,
 };
 use crate::utils::{unique_account_metas, SlotId};
 
+pub mod builders;
+
+/// The sysvar clock account listed for many instructions may be left out, in
+/// which case the accounts after it move up one position and the clock is read
+/// with the `Clock` syscall instead.
+///
+/// Besides the accounts listed, every instruction other than the program
+/// config's own and the read-only reports has to include the program config
+/// account (the PDA derived from the program id) anywhere in its accounts,
+/// whether or not the program config has been created yet.
 #[derive(Debug)]
 pub enum ProgramInstruction {
     /// 0. `[writable]` The wallet account
@@ -34,7 +60,7 @@ pub enum ProgramInstruction {
     /// 0. `[writable]` The multisig operation account
     /// 1. `[]` The wallet account
     /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
-    /// 3. `[]` The sysvar clock account
+    /// 3. `[]` The sysvar clock account (optional)
     InitBalanceAccountCreation {
         account_guid_hash: BalanceAccountGuidHash,
         creation_params: BalanceAccountCreation,
@@ -49,11 +75,11 @@ pub enum ProgramInstruction {
     },
 
     /// 0. `[writable]` The multisig operation account
-    /// 1. `[]` The wallet account
+    /// 1. `[writable]` The wallet account
     /// 2. `[writable]` The source account
     /// 3. `[]` The destination account
     /// 4. `[signer]` The initiator account (either the transaction assistant or an approver)
-    /// 5. `[]` The sysvar clock account
+    /// 5. `[]` The sysvar clock account (optional)
     /// 6. `[]` The token mint (for SPL transfers, use system account otherwise)
     /// 7. `[writable]` The destination token account (only used for SPL transfers)
     /// 8. `[signer, writable]` The fee payer, used if we need to create destination token account
@@ -63,35 +89,96 @@ pub enum ProgramInstruction {
     /// 10. `[]` The SPL token program (only used for SPL transfers)
     /// 11. `[]` The Rent sysvar program (only used for SPL transfers)
     /// 12. `[]` The SPL associated token program (only used for SPL transfers)
+    /// 13+. `[]` Address book page accounts holding the destination, if it is not in the
+    ///     wallet's own address book
+    /// 14+. `[]` The program config account
+    ///
+    /// `approval_timeout` optionally shortens the balance account's approval timeout for
+    /// this transfer only; it must be within the program's approval timeout limits.
+    ///
+    /// `memo_hash` optionally commits the transfer to a memo (e.g. the hash of an invoice),
+    /// which approvers sign along with the rest of the transfer and which is written with
+    /// the SPL Memo program when the transfer is finalized.
+    ///
+    /// `ata_rent_payer` optionally defers creating the destination token account of an
+    /// SPL transfer to finalize time and commits the op to who pays its rent. Without it
+    /// the token account is created here, funded by the source account if it can afford
+    /// the rent and by the fee payer otherwise.
+    ///
+    /// With `amount_spec` `Max`, `amount` is the most the transfer can move; it is what
+    /// the balance account's policy is evaluated against and what is reserved until the
+    /// transfer is finalized.
+    ///
+    /// `travel_rule_hash` commits the transfer to the hash of the travel rule data collected
+    /// for it, which approvers sign along with the rest of the transfer and which is logged
+    /// when the transfer is initiated and finalized. Transfers to destinations tagged as
+    /// exchanges require one.
     InitTransfer {
         account_guid_hash: BalanceAccountGuidHash,
         amount: u64,
         destination_name_hash: AddressBookEntryNameHash,
+        approval_timeout: Option<Duration>,
+        memo_hash: Option<Hash>,
+        ata_rent_payer: Option<AtaRentPayer>,
+        amount_spec: AmountSpec,
+        travel_rule_hash: Option<Hash>,
     },
 
     /// 0. `[writable]` The multisig operation account
     /// 1. `[signer]` The approver account
-    /// 2. `[]` The sysvar clock account
+    /// 2. `[]` The sysvar clock account (optional)
+    /// 3. `[writable]` The op's wallet account, to record the approver's activity
     SetApprovalDisposition {
         disposition: ApprovalDisposition,
         params_hash: Hash,
+        denial_reason: DenialReason,
+        /// The hash of an off-chain justification or comment, recorded with
+        /// the disposition.
+        reason_hash: Option<Hash>,
     },
 
     /// 0. `[writable]` The multisig operation account
-    /// 1. `[]` The wallet account
+    /// 1. `[writable]` The wallet account
     /// 2. `[writable]` The source account
     /// 3. `[writable]` The destination account
     /// 4. `[]` The system program
     /// 5. `[signer]` The rent collector account
-    /// 6. `[]` The sysvar clock account
+    /// 6. `[]` The sysvar clock account (optional)
     /// 7. `[writable]` The source token account, if this is an SPL transfer
     /// 8. `[writable]` The destination token account, if this is an SPL transfer
     /// 9. `[]` The SPL token program account, if this is an SPL transfer
     /// 10. `[]` The token mint authority, if this is an SPL transfer
+    /// 11. `[]` The SPL Memo program, if the transfer has a memo
+    /// 12+. `[]` The token mint, the Rent sysvar and the SPL associated token program, if
+    ///     the destination token account is created here
+    ///
+    /// `fee_reimbursement` optionally pays the rent collector back for the transaction
+    /// fee out of the balance account, so relayers can finalize transfers on a wallet's
+    /// behalf. It is not part of the approved op and is bounded by the balance account's
+    /// `max_finalize_fee_reimbursement` instead.
+    ///
+    /// `ata_rent_payer` has to match the op's. If the destination token account does not
+    /// exist yet it is created, funded by the source account for `Source` or by the rent
+    /// collector for `Payer`; for `Destination` the transfer fails instead.
+    ///
+    /// `token_program` has to be the program the token mint belonged to when the transfer
+    /// was initiated, and the SPL token program account has to be that program. It is
+    /// ignored for SOL transfers and defaults to the legacy SPL token program.
+    ///
+    /// `amount_spec` has to match the op's. With `Max`, the source account's whole
+    /// spendable balance is transferred, up to `amount`.
+    ///
+    /// `travel_rule_hash` has to match the op's.
     FinalizeTransfer {
         account_guid_hash: BalanceAccountGuidHash,
         amount: u64,
         token_mint: Pubkey,
+        memo_hash: Option<Hash>,
+        fee_reimbursement: u64,
+        ata_rent_payer: Option<AtaRentPayer>,
+        token_program: Option<Pubkey>,
+        amount_spec: AmountSpec,
+        travel_rule_hash: Option<Hash>,
     },
 
     /// 0. `[writable]` The multisig operation account
@@ -100,34 +187,59 @@ pub enum ProgramInstruction {
     /// 3. `[writable]` The associated wrapped SOL account
     /// 4. `[]` The native mint account
     /// 5. `[signer]` The initiator account (either the transaction assistant or an approver)
-    /// 6. `[]` The sysvar clock account
+    /// 6. `[]` The sysvar clock account (optional)
     /// 7. `[]` The system program
     /// 8. `[]` The SPL token program
     /// 9. `[]` The Rent sysvar program
     /// 10. `[]` The SPL associated token program
+    /// 11. `[]` The program config account
+    ///
+    /// `approval_timeout` optionally shortens the balance account's approval timeout for
+    /// this operation only; it must be within the program's approval timeout limits.
+    ///
+    /// An unwrap may name an address book entry as its `destination`, in which case the
+    /// unwrapped SOL is sent on to it rather than staying in the balance account. The
+    /// entry is subject to the balance account's whitelist like any transfer destination.
+    /// Unwraps to destinations tagged as exchanges require a `travel_rule_hash`, as with
+    /// `InitTransfer`; it is only sent along with a destination.
     InitWrapUnwrap {
         account_guid_hash: BalanceAccountGuidHash,
         amount: u64,
         direction: WrapDirection,
+        approval_timeout: Option<Duration>,
+        destination: Option<AddressBookEntry>,
+        travel_rule_hash: Option<Hash>,
     },
 
     /// 0. `[writable]` The multisig operation account
-    /// 1. `[]` The wallet account
+    /// 1. `[writable]` The wallet account, which records what an unwrap sends to its destination
     /// 2. `[writable]` The balance account
     /// 3. `[]` The system program
     /// 4. `[signer]` The rent collector account
-    /// 5. `[]` The sysvar clock account
+    /// 5. `[]` The sysvar clock account (optional)
     /// 6. `[writable]` The wrapped SOL token account
-    /// 7. `[]` The SPL token account
+    /// 7. `[]` The SPL token program
+    /// 8. `[]` The native mint account, if a wrap has to create the wrapped SOL account
+    /// 9. `[]` The Rent sysvar program, if a wrap has to create the wrapped SOL account
+    /// 10. `[]` The SPL associated token program, if a wrap has to create the wrapped SOL account
+    /// 11. `[writable]` The destination account, if the unwrap has a `destination`
+    ///
+    /// A wrap creates the balance account's wrapped SOL account if it does not exist.
+    /// An unwrap of the whole wrapped balance closes the account, returning its rent
+    /// to the balance account.
+    ///
+    /// `destination` and `travel_rule_hash` have to match the op's.
     FinalizeWrapUnwrap {
         account_guid_hash: BalanceAccountGuidHash,
         amount: u64,
         direction: WrapDirection,
+        destination: Option<Pubkey>,
+        travel_rule_hash: Option<Hash>,
     },
     /// 0. `[writable]` The multisig operation account
     /// 1. `[]` The wallet account
     /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
-    /// 3. `[]` The sysvar clock account
+    /// 3. `[]` The sysvar clock account (optional)
     InitUpdateSigner {
         slot_update_type: SlotUpdateType,
         slot_id: SlotId<Signer>,
@@ -146,7 +258,7 @@ pub enum ProgramInstruction {
     /// 0  `[writable]` The multisig operation account
     /// 1. `[writable]` The wallet account
     /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
-    /// 3. `[]` The sysvar clock account
+    /// 3. `[]` The sysvar clock account (optional)
     InitWalletConfigPolicyUpdate { update: WalletConfigPolicyUpdate },
 
     /// 0  `[writable]` The multisig operation account
@@ -157,32 +269,39 @@ pub enum ProgramInstruction {
     /// 0. `[writable]` The multisig operation account
     /// 1. `[]` The wallet account
     /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
-    /// 3. `[]` The sysvar clock account
+    /// 3. `[]` The sysvar clock account (optional)
+    ///
+    /// `rent_funding_limit` is the maximum number of lamports the balance account may
+    /// spend funding accounts created by the dApp instructions.
     InitDAppTransaction {
         account_guid_hash: BalanceAccountGuidHash,
         instructions: Vec<Instruction>,
         dapp: DAppBookEntry,
+        rent_funding_limit: u64,
     },
 
     /// 0. `[writable]` The multisig operation account
-    /// 1. `[]` The wallet account
+    /// 1. `[writable]` The wallet account, which records usage of the dapp's value limit
     /// 2. `[writable]` The balance account
     /// 3. `[signer]` The rent collector account
-    /// 4. `[]` The sysvar clock account
+    /// 4. `[]` The sysvar clock account (optional)
     FinalizeDAppTransaction {
         account_guid_hash: BalanceAccountGuidHash,
         instructions: Vec<Instruction>,
         dapp: DAppBookEntry,
+        rent_funding_limit: u64,
     },
 
     /// 0  `[writable]` The multisig operation account
     /// 1. `[]` The wallet account
     /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
-    /// 3. `[]` The sysvar clock account
+    /// 3. `[]` The sysvar clock account (optional)
     InitAccountSettingsUpdate {
         account_guid_hash: BalanceAccountGuidHash,
         whitelist_enabled: Option<BooleanSetting>,
         dapps_enabled: Option<BooleanSetting>,
+        sol_transfers_enabled: Option<BooleanSetting>,
+        spl_transfers_enabled: Option<BooleanSetting>,
     },
 
     /// 0  `[writable]` The multisig operation account
@@ -192,36 +311,38 @@ pub enum ProgramInstruction {
         account_guid_hash: BalanceAccountGuidHash,
         whitelist_enabled: Option<BooleanSetting>,
         dapps_enabled: Option<BooleanSetting>,
+        sol_transfers_enabled: Option<BooleanSetting>,
+        spl_transfers_enabled: Option<BooleanSetting>,
     },
 
     /// 0. `[writable]` The multisig operation account
     /// 1. `[]` The wallet account
     /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
-    /// 3. `[]` The sysvar clock account
+    /// 3. `[]` The sysvar clock account (optional)
     InitDAppBookUpdate { update: DAppBookUpdate },
 
     /// 0. `[writable]` The multisig operation account
     /// 1. `[writable]` The wallet account
     /// 2. `[signer]` The rent collector account
-    /// 3. `[]` The sysvar clock account
+    /// 3. `[]` The sysvar clock account (optional)
     FinalizeDAppBookUpdate { update: DAppBookUpdate },
 
     /// 0. `[writable]` The multisig operation account
     /// 1. `[]` The wallet account
     /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
-    /// 3. `[]` The sysvar clock account
+    /// 3. `[]` The sysvar clock account (optional)
     InitAddressBookUpdate { update: AddressBookUpdate },
 
     /// 0. `[writable]` The multisig operation account
     /// 1. `[writable]` The wallet account
     /// 2. `[signer]` The rent collector account
-    /// 3. `[]` The sysvar clock account
+    /// 3. `[]` The sysvar clock account (optional)
     FinalizeAddressBookUpdate { update: AddressBookUpdate },
 
     /// 0. `[writable]` The multisig operation account
     /// 1. `[]` The wallet account
     /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
-    /// 3. `[]` The sysvar clock account
+    /// 3. `[]` The sysvar clock account (optional)
     InitBalanceAccountNameUpdate {
         account_guid_hash: BalanceAccountGuidHash,
         account_name_hash: BalanceAccountNameHash,
@@ -230,7 +351,7 @@ pub enum ProgramInstruction {
     /// 0. `[writable]` The multisig operation account
     /// 1. `[writable]` The wallet account
     /// 2. `[signer]` The rent collector account
-    /// 3. `[]` The sysvar clock account
+    /// 3. `[]` The sysvar clock account (optional)
     FinalizeBalanceAccountNameUpdate {
         account_guid_hash: BalanceAccountGuidHash,
         account_name_hash: BalanceAccountNameHash,
@@ -239,7 +360,7 @@ pub enum ProgramInstruction {
     /// 0. `[writable]` The multisig operation account
     /// 1. `[writable]` The wallet account
     /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
-    /// 3. `[]` The sysvar clock account
+    /// 3. `[]` The sysvar clock account (optional)
     InitBalanceAccountPolicyUpdate {
         account_guid_hash: BalanceAccountGuidHash,
         update: BalanceAccountPolicyUpdate,
@@ -248,14 +369,704 @@ pub enum ProgramInstruction {
     /// 0. `[writable]` The multisig operation account
     /// 1. `[writable]` The wallet account
     /// 2. `[signer]` The rent collector account
-    /// 3. `[]` The sysvar clock account
+    /// 3. `[]` The sysvar clock account (optional)
     FinalizeBalanceAccountPolicyUpdate {
         account_guid_hash: BalanceAccountGuidHash,
         update: BalanceAccountPolicyUpdate,
     },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account (optional)
+    InitAddressBookVerification {
+        entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The rent collector account
+    /// 3. `[]` The sysvar clock account (optional)
+    FinalizeAddressBookVerification {
+        entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account (optional)
+    InitActivityCheckpoint { checkpoint_index: u32 },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[writable]` The checkpoint account (PDA derived from the wallet address and index)
+    /// 3. `[signer, writable]` The rent collector account, which also funds the checkpoint account
+    /// 4. `[]` The system program
+    /// 5. `[]` The sysvar clock account (optional)
+    FinalizeActivityCheckpoint { checkpoint_index: u32 },
+
+    /// 0. `[writable]` The program config account (PDA derived from the program id)
+    /// 1. `[]` The program data account of this program
+    /// 2. `[signer, writable]` The program upgrade authority, which also funds the config account
+    /// 3. `[]` The system program
+    InitProgramConfig { update: ProgramConfigUpdate },
+
+    /// 0. `[writable]` The program config account
+    /// 1. `[signer]` The program config authority
+    UpdateProgramConfig { update: ProgramConfigUpdate },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[]` The address book page account (PDA derived from the wallet address and page index)
+    /// 3. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 4. `[]` The sysvar clock account (optional)
+    InitAddressBookPageUpdate {
+        page_index: u8,
+        update: AddressBookUpdate,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[writable]` The address book page account
+    /// 3. `[signer, writable]` The rent collector account, which also funds the page account
+    /// 4. `[]` The system program
+    /// 5. `[]` The sysvar clock account (optional)
+    FinalizeAddressBookPageUpdate {
+        page_index: u8,
+        update: AddressBookUpdate,
+    },
+
+    /// 0. `[signer]` The approver account
+    /// 1. `[]` The sysvar clock account (optional)
+    /// 2+. `[writable]` The multisig operation accounts, one per disposition in the same order,
+    ///     followed by the wallets of the ops, to record the approver's activity
+    SetApprovalDispositions {
+        dispositions: Vec<ApprovalDispositionUpdate>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account (optional)
+    InitWalletRepair,
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The rent collector account
+    /// 3. `[]` The sysvar clock account (optional)
+    FinalizeWalletRepair,
+
+    /// Logs the dangling references a wallet repair would clear, without
+    /// modifying the wallet.
+    ///
+    /// 0. `[]` The wallet account
+    CheckWalletIntegrity,
+
+    /// Runs all the validation of an init instruction (initiator, policy and
+    /// slot checks) and then fails with `SimulationFinished` so that no state
+    /// is written, letting clients pre-flight an update before asking
+    /// approvers to sign it.
+    ///
+    /// Takes the same accounts as the wrapped instruction.
+    ValidateOnly {
+        instruction: Box<ProgramInstruction>,
+    },
+
+    /// Checks whether a transfer would go through (balance account and source
+    /// PDA, whitelist status, program pause, source balance, and destination
+    /// token account existence or rent exemption) without writing anything.
+    /// Each check and the overall GO/NO-GO result are logged; on NO-GO the
+    /// first failed check's error is returned.
+    ///
+    /// Takes the same accounts as `FinalizeTransfer`:
+    /// 0. `[]` The multisig operation account (not read, the op need not exist)
+    /// 1. `[]` The wallet account
+    /// 2. `[]` The source account
+    /// 3. `[]` The destination account
+    /// 4. `[]` The system program
+    /// 5. `[]` The rent collector account (not read)
+    /// 6. `[]` The sysvar clock account (optional)
+    /// 7. `[]` The source token account, if this is an SPL transfer
+    /// 8. `[]` The destination token account, if this is an SPL transfer
+    /// 9+. `[]` Address book page accounts, if any, and the program config account
+    PreflightTransfer {
+        account_guid_hash: BalanceAccountGuidHash,
+        amount: u64,
+        token_mint: Pubkey,
+        destination_name_hash: AddressBookEntryNameHash,
+    },
+
+    /// Extends the expiry of a pending or expired multisig op by at most its
+    /// original approval timeout, keeping the dispositions recorded so far. An
+    /// op can only be renewed once, and only by one of the wallet's config
+    /// approvers who is also an approver of the op.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The config approver account
+    /// 3. `[]` The sysvar clock account (optional)
+    RenewMultisigOp {
+        params_hash: Hash,
+        extension: Duration,
+    },
+
+    /// Creates a multisig op account with its rent paid by the wallet's fee
+    /// account, a PDA customers can prefund instead of the initiator fronting
+    /// the rent. Finalizing the op with the fee account as rent collector
+    /// returns the rent to it.
+    ///
+    /// 0. `[writable, signer]` The multisig operation account to create
+    /// 1. `[]` The wallet account
+    /// 2. `[writable]` The wallet fee account
+    /// 3. `[signer]` The initiator account (either the transaction assistant or a signer)
+    /// 4. `[]` The system program
+    CreateOpAccount,
+
+    /// Adds or removes delegations letting a signer give dispositions in place
+    /// of an approver until the delegation expires.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account (optional)
+    InitApproverDelegationUpdate { update: ApproverDelegationUpdate },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The rent collector account
+    /// 3. `[]` The sysvar clock account (optional)
+    FinalizeApproverDelegationUpdate { update: ApproverDelegationUpdate },
+
+    /// 0. `[writable]` The signer directory account (PDA derived from the authority)
+    /// 1. `[signer, writable]` The directory authority, which also funds the directory account
+    /// 2. `[]` The system program
+    InitSignerDirectory,
+
+    /// Adds and removes directory signers. Rotating a key is removing it and
+    /// adding the new key in the same slot.
+    ///
+    /// 0. `[writable]` The signer directory account
+    /// 1. `[signer]` The directory authority
+    UpdateSignerDirectory { update: SignerDirectoryUpdate },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account (optional)
+    /// 4. `[]` The signer directory account, when the update links one
+    InitWalletSignerDirectoryUpdate { update: WalletSignerDirectoryUpdate },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The rent collector account
+    /// 3. `[]` The sysvar clock account (optional)
+    /// 4. `[]` The signer directory account, when the update links one
+    FinalizeWalletSignerDirectoryUpdate { update: WalletSignerDirectoryUpdate },
+
+    /// Sets, rotates or clears the wallet's canary key. This can be started
+    /// and finalized while the wallet is frozen, and finalizing it unfreezes
+    /// the wallet.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account (optional)
+    InitWalletCanaryUpdate { canary_key: Option<Pubkey> },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The rent collector account
+    /// 3. `[]` The sysvar clock account (optional)
+    FinalizeWalletCanaryUpdate { canary_key: Option<Pubkey> },
+
+    /// Moves funds between two balance accounts of the same wallet. The destination
+    /// does not have to be in the address book, but the transfer is approved like any
+    /// other transfer out of the source balance account.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[]` The source balance account
+    /// 3. `[]` The destination balance account
+    /// 4. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 5. `[]` The sysvar clock account (optional)
+    /// 6. `[]` The token mint (or the system program for a SOL transfer)
+    /// 7. `[]` The program config account
+    InitInternalTransfer {
+        source_account_guid_hash: BalanceAccountGuidHash,
+        destination_account_guid_hash: BalanceAccountGuidHash,
+        amount: u64,
+        approval_timeout: Option<Duration>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[writable]` The source balance account
+    /// 3. `[writable]` The destination balance account
+    /// 4. `[]` The system program
+    /// 5. `[signer]` The rent collector account
+    /// 6. `[]` The sysvar clock account (optional)
+    /// 7. `[writable]` The source token account, if this is an SPL transfer
+    /// 8. `[writable]` The destination token account, if this is an SPL transfer; it
+    ///     must already exist
+    /// 9. `[]` The SPL token program account, if this is an SPL transfer
+    FinalizeInternalTransfer {
+        source_account_guid_hash: BalanceAccountGuidHash,
+        destination_account_guid_hash: BalanceAccountGuidHash,
+        amount: u64,
+        token_mint: Pubkey,
+    },
+
+    /// Fails unless the wallet's live configuration matches the policy manifest with
+    /// the given hash (see `Wallet::policy_manifest_hash`), so auditors can verify that
+    /// the wallet is still configured the way they signed off on. Nothing is written.
+    ///
+    /// 0. `[]` The wallet account
+    /// 1. `[]` The signer directory account, if the wallet has one
+    AssertPolicyManifest { manifest_hash: Hash },
+
+    /// Sets or resets governed parameters, the wallet's small tunables (see
+    /// `GovernedParameter`). Approved like other config ops.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account (optional)
+    InitGovernedParameterUpdate { update: GovernedParameterUpdate },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The rent collector account
+    /// 3. `[]` The sysvar clock account (optional)
+    FinalizeGovernedParameterUpdate { update: GovernedParameterUpdate },
+
+    /// Creates a multisig op account at the address derived from the wallet,
+    /// the op's params hash and a nonce (see `MultisigOp::find_address`), with
+    /// its rent paid by the payer. The account can then only be used to start
+    /// the op with those params, so submitting the same op twice fails here
+    /// and pending ops can be looked up by their params.
+    ///
+    /// 0. `[writable]` The multisig operation account to create
+    /// 1. `[]` The wallet account
+    /// 2. `[writable, signer]` The payer account
+    /// 3. `[]` The system program
+    CreateOpAccountForParams { params_hash: Hash, nonce: u64 },
+
+    /// Logs how much of each of the wallet's fixed-size collections (signers,
+    /// address book, balance accounts, dapp book, token mints, approver
+    /// delegations and each balance account's whitelist) is used and free,
+    /// with a warning for each one that is at least `warning_threshold_percent`
+    /// full. Nothing is written.
+    ///
+    /// 0. `[]` The wallet account
+    ReportCapacity { warning_threshold_percent: u8 },
+
+    /// Adds and removes any number of signers in a single op, approved like
+    /// `InitUpdateSigner`. Signers are removed before they are added.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account (optional)
+    InitUpdateSigners { update: SignersUpdate },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The rent collector account
+    /// 3. `[]` The sysvar clock account (optional)
+    FinalizeUpdateSigners { update: SignersUpdate },
+
+    /// Adds and removes viewers, the keys registered for read-only access.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account (optional)
+    InitViewersUpdate { update: ViewersUpdate },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The rent collector account
+    /// 3. `[]` The sysvar clock account (optional)
+    FinalizeViewersUpdate { update: ViewersUpdate },
+
+    /// Succeeds only if signed by one of the wallet's viewers, so a viewer can
+    /// prove their access grant on-chain. Nothing is written.
+    ///
+    /// 0. `[]` The wallet account
+    /// 1. `[signer]` The viewer account
+    VerifyAccess,
+
+    /// Tags signer slots with the organization their signer belongs to, or
+    /// clears their tag. Together with the `MinDistinctApproverOrganizations`
+    /// governed parameter, this requires approvals from several organizations.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account (optional)
+    InitSignerOrganizationsUpdate { update: SignerOrganizationsUpdate },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The rent collector account
+    /// 3. `[]` The sysvar clock account (optional)
+    FinalizeSignerOrganizationsUpdate { update: SignerOrganizationsUpdate },
+
+    /// Rotates the wallet's assistant key. Unless the `AssistantMayBeSigner`
+    /// governed parameter allows it, the new assistant can't be a signer.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account (optional)
+    InitAssistantUpdate { assistant: Pubkey },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The rent collector account
+    /// 3. `[]` The sysvar clock account (optional)
+    FinalizeAssistantUpdate { assistant: Pubkey },
+
+    /// Starts an address book update too large for a single transaction. The
+    /// op commits to the hash of the packed `AddressBookUpdate`, which is then
+    /// streamed into the buffer account with `SupplyAddressBookChunk`. The
+    /// buffer account has to be created owned by the program, with room for
+    /// the buffer header and `update_len` bytes.
+    /// Entries the address book already holds in the same slot are skipped,
+    /// so the whole address book can be imported this way in one op.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[writable]` The address book update buffer account
+    /// 4. `[]` The sysvar clock account (optional)
+    InitAddressBookUpdateChunked { update_hash: Hash, update_len: u32 },
+
+    /// Writes the next chunk of a chunked address book update. Chunks have to
+    /// be supplied in order, by the initiator of the op.
+    ///
+    /// 0. `[writable]` The address book update buffer account
+    /// 1. `[signer]` The initiator account
+    SupplyAddressBookChunk { offset: u32, chunk: Vec<u8> },
+
+    /// Applies a chunked address book update once it has been approved and
+    /// fully supplied, and closes the buffer account.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The rent collector account
+    /// 3. `[writable]` The address book update buffer account
+    /// 4. `[]` The sysvar clock account (optional)
+    FinalizeAddressBookUpdateChunked { update_hash: Hash },
+
+    /// Sweeps the whole balance of the given deposit sub-addresses of a
+    /// balance account into the balance account itself. Pass the system
+    /// program as the token mint to sweep SOL.
+    ///
+    /// 0. `[]` The wallet account
+    /// 1. `[writable]` The balance account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or a signer)
+    /// 3. `[]` The system program
+    /// 4. `[]` The token mint
+    /// 5. `[writable]` The balance account's token account (SPL only)
+    /// 6. `[]` The SPL token program (SPL only)
+    /// 7. `[writable]` One account per deposit index: the deposit sub-address for SOL,
+    ///    or the sub-address followed by its token account for SPL
+    ConsolidateDeposits {
+        account_guid_hash: BalanceAccountGuidHash,
+        deposit_indexes: Vec<u32>,
+    },
+
+    /// Creates the wallet's pending ops registry (see `PendingOps`). From then
+    /// on, any instruction that starts, renews or finalizes an op of the
+    /// wallet and carries the registry as an extra writable account keeps it
+    /// up to date.
+    ///
+    /// 0. `[writable]` The pending ops registry account (PDA derived from the wallet)
+    /// 1. `[]` The wallet account
+    /// 2. `[signer, writable]` The payer account
+    /// 3. `[]` The system program
+    InitPendingOps,
+
+    /// Removes ops from the pending ops registry whose accounts were closed or
+    /// reused for another op without the registry being passed along.
+    ///
+    /// 0. `[writable]` The pending ops registry account
+    /// 1. `[]` The op accounts to check, any number of them
+    CleanupPendingOps,
+
+    /// Starts an op with the wrapped init instruction that can only be
+    /// executed once the op with the given params hash has been. The
+    /// prerequisite counts as executed if it was finalized with the wallet's
+    /// pending ops registry passed along, and the registry must be passed
+    /// along when finalizing this op as well, so that ops approved together
+    /// (e.g. adding a destination, then transferring to it) run in order.
+    ///
+    /// Takes the same accounts as the wrapped instruction.
+    InitWithPrerequisite {
+        prerequisite: Hash,
+        instruction: Box<ProgramInstruction>,
+    },
+
+    /// Starts exporting the wallet's state so it can be imported into the
+    /// target program, e.g. a redeployment of this one. Every config approver
+    /// has to approve, and the target program must be in the program config's
+    /// migration allowlist, both now and when the export is finalized.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The program config account
+    /// 4. `[]` The sysvar clock account (optional)
+    InitWalletStateExport { target_program_id: Pubkey },
+
+    /// Writes the wallet's state to the export account, which has to be
+    /// created owned by the program with room for a `WalletExport`. An export
+    /// account of an earlier export of the same wallet may be written again.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The rent collector account
+    /// 3. `[writable]` The export account
+    /// 4. `[]` The program config account
+    /// 5. `[]` The sysvar clock account (optional)
+    FinalizeWalletStateExport { target_program_id: Pubkey },
+
+    /// Creates a wallet from a wallet state exported to this program by a
+    /// program in the program config's migration allowlist. The new wallet
+    /// account has to be created owned by the program, as for `InitWallet`.
+    ///
+    /// 0. `[writable]` The new wallet account
+    /// 1. `[]` The export account, owned by the exporting program
+    /// 2. `[signer]` The exported wallet's transaction assistant account
+    /// 3. `[]` The program config account
+    ImportWalletState,
+
+    /// Fails unless the given name hashes to the name hash stored for the
+    /// balance account or address book entry, and otherwise logs the name so
+    /// indexers can label the account with it. The name is UTF-8 of at most
+    /// 255 bytes. Nothing is written.
+    ///
+    /// 0. `[]` The wallet account
+    VerifyNameHash { entry: NamedEntry, name: String },
+
+    /// Closes a multisig op that expired without reaching a final disposition,
+    /// or that was approved but lapsed after it was renewed, releasing the
+    /// outstanding transfer value held for it. Anyone can expire an op once its
+    /// approval timeout has passed.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[writable]` The rent collector account (a signer, or the wallet's fee account)
+    /// 3. `[]` The sysvar clock account (optional)
+    ExpireOp,
+
+    /// Starts a transfer of lamports along with amounts of one or more SPL tokens
+    /// from a balance account to a destination in the wallet's address book, approved
+    /// as one op. The balance account's policy is evaluated against, and its
+    /// outstanding transfer value reserves, the sum of all amounts.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[]` The source account
+    /// 3. `[]` The destination account
+    /// 4. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 5. `[]` The sysvar clock account (optional)
+    ///
+    /// Transfers to destinations tagged as exchanges require the transfer's
+    /// `travel_rule_hash`, as with `InitTransfer`.
+    InitCombinedTransfer {
+        account_guid_hash: BalanceAccountGuidHash,
+        destination_name_hash: AddressBookEntryNameHash,
+        transfer: CombinedTransfer,
+    },
+
+    /// Moves the lamports and tokens of an approved combined transfer. The
+    /// destination token accounts have to exist already, and the tokens have to
+    /// belong to the legacy SPL token program.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[writable]` The source account
+    /// 3. `[writable]` The destination account
+    /// 4. `[]` The system program
+    /// 5. `[signer]` The rent collector account
+    /// 6. `[]` The sysvar clock account (optional)
+    /// 7. `[]` The SPL token program
+    /// 8+. `[writable]` The source and destination token accounts of each token, in the
+    ///     order of the transfer's token amounts
+    FinalizeCombinedTransfer {
+        account_guid_hash: BalanceAccountGuidHash,
+        transfer: CombinedTransfer,
+    },
+
+    /// Sets the hash of the off-chain policy document the wallet's
+    /// configuration is governed by. The default hash clears it.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account (optional)
+    InitWalletMetadataUpdate { metadata_hash: Hash },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The rent collector account
+    /// 3. `[]` The sysvar clock account (optional)
+    FinalizeWalletMetadataUpdate { metadata_hash: Hash },
+
+    /// Submits an approver's disposition on an op to a pending disposition
+    /// account of its own, derived from the op and approver addresses, leaving
+    /// the op account read-only so that approvals don't contend for its write
+    /// lock. The disposition only counts once `CollectApprovalDispositions`
+    /// has applied it to the op.
+    ///
+    /// 0. `[]` The multisig operation account
+    /// 1. `[writable]` The pending disposition account
+    /// 2. `[writable, signer]` The approver account, which pays for the pending disposition account
+    /// 3. `[]` The system program
+    /// 4. `[]` The sysvar clock account (optional)
+    SubmitApprovalDisposition {
+        disposition: ApprovalDisposition,
+        params_hash: Hash,
+        denial_reason: DenialReason,
+        reason_hash: Option<Hash>,
+    },
+
+    /// Applies submitted dispositions to their op, and closes their accounts,
+    /// returning the rent to the approvers. Anyone can collect dispositions.
+    /// Dispositions submitted on an op whose account has already been closed
+    /// are only closed.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The op's wallet account, to record the approvers' activity (not read
+    ///     once the op account has been closed)
+    /// 2+. `[writable]` `dispositions` pairs of a pending disposition account and its
+    ///     approver's account
+    /// 2 + 2 * `dispositions`. `[]` The sysvar clock account (optional)
+    CollectApprovalDispositions { dispositions: u8 },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account (optional)
+    InitDAppTemplatesUpdate { update: DAppTemplatesUpdate },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The rent collector account
+    /// 3. `[]` The sysvar clock account (optional)
+    FinalizeDAppTemplatesUpdate { update: DAppTemplatesUpdate },
+
+    /// Sends an instance of an approved dapp template from its balance
+    /// account, without an op of its own. Instances count against the value
+    /// limit of the dapp the template sends to, like dapp transactions.
+    ///
+    /// 0. `[writable]` The wallet account
+    /// 1. `[writable]` The balance account
+    /// 2. `[signer]` The assistant account
+    /// 3+. The accounts of the instruction, and its program
+    ExecuteDAppTemplate {
+        template_slot: SlotId<DAppTemplate>,
+        instruction: Instruction,
+    },
+
+    /// Records a disposition an approver signed offline, as a
+    /// `co_signing::CoSignature`. The instruction before it in the
+    /// transaction has to be an ed25519 program instruction checking the
+    /// approver's signature over the disposition, so anyone can submit it.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The approver account
+    /// 2. `[]` The sysvar instructions account
+    /// 3. `[]` The sysvar clock account (optional)
+    /// 4. `[writable]` The op's wallet account, to record the approver's activity
+    VerifyCoSignature {
+        disposition: ApprovalDisposition,
+        params_hash: Hash,
+        denial_reason: DenialReason,
+        reason_hash: Option<Hash>,
+    },
+
+    /// Starts decommissioning the wallet, which takes every config approver.
+    /// Once finalized, all the wallet's rent goes to the given collector.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account (optional)
+    InitWalletClose { rent_collector: Pubkey },
+
+    /// Closes the wallet. Every balance account has to be passed along with
+    /// all the token accounts it owns, and they all have to be empty. Its
+    /// associated token account for every mint the wallet tracks (allowed for
+    /// a balance account, bound to a destination or transferred this activity
+    /// period) is required even if it hasn't been created. The
+    /// token accounts are closed, and the rent of the token accounts and the
+    /// wallet, along with what is left in the balance accounts and the fee
+    /// account when it is passed, goes to the wallet's rent collector.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The rent collector account
+    /// 3. `[writable]` The wallet's rent collector the close was approved with
+    /// 4. `[]` The system program
+    /// 5. `[]` The SPL token program
+    /// 6. `[]` The sysvar clock account (optional)
+    /// 7+. `[writable]` The balance accounts, their token accounts and, optionally,
+    ///     the wallet's fee account
+    FinalizeWalletClose { rent_collector: Pubkey },
+
+    /// Logs when each of the wallet's signers last gave a disposition, with a
+    /// warning for each one that has been inactive for more than
+    /// `inactive_after_days`. Nothing is written.
+    ///
+    /// 0. `[]` The wallet account
+    /// 1. `[]` The sysvar clock account (optional)
+    ReportSignerActivity { inactive_after_days: u16 },
+
+    /// Runs the next `chunk_len` instructions of an approved dapp transaction,
+    /// for transactions too large to run in a single finalize. Instructions
+    /// run in the order they were approved in, and the op account is closed
+    /// once the last of them has run. Only the accounts of this chunk's
+    /// instructions have to be passed. `chunk_len` can be at most
+    /// `MAX_DAPP_INSTRUCTIONS_PER_CALL`, which is also the most instructions
+    /// a `FinalizeDAppTransaction` runs.
+    ///
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account, which records usage of the dapp's value limit
+    /// 2. `[writable]` The balance account
+    /// 3. `[signer]` The rent collector account
+    /// 4. `[]` The sysvar clock account (optional)
+    ExecuteDAppChunk {
+        account_guid_hash: BalanceAccountGuidHash,
+        instructions: Vec<Instruction>,
+        dapp: DAppBookEntry,
+        rent_funding_limit: u64,
+        chunk_len: u8,
+    },
+
+    /// Starts an op with the wrapped init instruction unless an init
+    /// instruction was recently submitted to the wallet with the same
+    /// idempotency key, so clients can retry a submission without risking a
+    /// second op. The wallet remembers the last keys it was given, and the
+    /// zero key is not accepted.
+    ///
+    /// Takes the same accounts as the wrapped instruction, except that the
+    /// wallet account has to be writable.
+    InitWithIdempotencyKey {
+        idempotency_key: IdempotencyKey,
+        instruction: Box<ProgramInstruction>,
+    },
 }
 
 impl ProgramInstruction {
+    /// The most dapp instructions a single `FinalizeDAppTransaction` or
+    /// `ExecuteDAppChunk` runs, so that a large dapp transaction is rejected
+    /// up front instead of running out of compute part way through. Larger
+    /// transactions are run in chunks of at most this many instructions.
+    pub const MAX_DAPP_INSTRUCTIONS_PER_CALL: usize = 8;
+
     pub fn pack(&self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(size_of::<Self>());
         match self {
@@ -270,10 +1081,16 @@ impl ProgramInstruction {
             &ProgramInstruction::SetApprovalDisposition {
                 ref disposition,
                 ref params_hash,
+                ref denial_reason,
+                ref reason_hash,
             } => {
                 buf.push(9);
                 buf.push(disposition.to_u8());
                 buf.extend_from_slice(params_hash.as_ref());
+                buf.push(denial_reason.to_u8());
+                if let Some(reason_hash) = reason_hash {
+                    buf.extend_from_slice(reason_hash.as_ref());
+                }
             }
             &ProgramInstruction::InitBalanceAccountCreation {
                 ref account_guid_hash,
@@ -299,42 +1116,81 @@ impl ProgramInstruction {
                 ref account_guid_hash,
                 ref amount,
                 ref destination_name_hash,
+                ref approval_timeout,
+                ref memo_hash,
+                ref ata_rent_payer,
+                ref amount_spec,
+                ref travel_rule_hash,
             } => {
                 buf.push(7);
                 buf.extend_from_slice(account_guid_hash.to_bytes());
                 buf.extend_from_slice(&amount.to_le_bytes());
                 buf.extend_from_slice(destination_name_hash.to_bytes());
+                append_optional_duration(approval_timeout, &mut buf);
+                append_optional_memo_hash(memo_hash, &mut buf);
+                append_optional_ata_rent_payer(ata_rent_payer, &mut buf);
+                append_amount_spec_and_travel_rule_hash(amount_spec, travel_rule_hash, &mut buf);
             }
             &ProgramInstruction::FinalizeTransfer {
                 ref account_guid_hash,
                 ref amount,
                 ref token_mint,
+                ref memo_hash,
+                ref fee_reimbursement,
+                ref ata_rent_payer,
+                ref token_program,
+                ref amount_spec,
+                ref travel_rule_hash,
             } => {
                 buf.push(8);
                 buf.extend_from_slice(account_guid_hash.to_bytes());
                 buf.extend_from_slice(&amount.to_le_bytes());
                 buf.extend_from_slice(&token_mint.to_bytes());
-                buf.push(0);
+                append_optional_memo_hash(memo_hash, &mut buf);
+                buf.extend_from_slice(&fee_reimbursement.to_le_bytes());
+                append_optional_ata_rent_payer(ata_rent_payer, &mut buf);
+                // with a travel rule hash the token program is always sent, as the
+                // default pubkey if there is none, so the lengths stay distinct
+                if token_program.is_some() || travel_rule_hash.is_some() {
+                    buf.extend_from_slice(token_program.unwrap_or_default().as_ref());
+                }
+                append_amount_spec_and_travel_rule_hash(amount_spec, travel_rule_hash, &mut buf);
             }
             &ProgramInstruction::InitWrapUnwrap {
                 ref account_guid_hash,
                 ref amount,
                 ref direction,
+                ref approval_timeout,
+                ref destination,
+                ref travel_rule_hash,
             } => {
                 buf.push(10);
                 buf.extend_from_slice(&account_guid_hash.to_bytes());
                 buf.extend_from_slice(&amount.to_le_bytes());
                 buf.push(direction.to_u8());
+                append_optional_duration(approval_timeout, &mut buf);
+                if let Some(destination) = destination {
+                    let mut destination_bytes = [0; AddressBookEntry::LEN];
+                    destination.pack_into_slice(&mut destination_bytes);
+                    buf.extend_from_slice(&destination_bytes);
+                    append_optional_travel_rule_hash(travel_rule_hash, &mut buf);
+                }
             }
             &ProgramInstruction::FinalizeWrapUnwrap {
                 ref account_guid_hash,
                 ref amount,
                 ref direction,
+                ref destination,
+                ref travel_rule_hash,
             } => {
                 buf.push(11);
                 buf.extend_from_slice(&account_guid_hash.to_bytes());
                 buf.extend_from_slice(&amount.to_le_bytes());
                 buf.push(direction.to_u8());
+                if let Some(destination) = destination {
+                    buf.extend_from_slice(destination.as_ref());
+                    append_optional_travel_rule_hash(travel_rule_hash, &mut buf);
+                }
             }
             &ProgramInstruction::InitUpdateSigner {
                 ref slot_update_type,
@@ -372,81 +1228,55 @@ impl ProgramInstruction {
                 ref account_guid_hash,
                 ref dapp,
                 ref instructions,
+                ref rent_funding_limit,
             } => {
                 buf.push(16);
                 buf.extend_from_slice(&account_guid_hash.to_bytes());
                 let mut buf2 = vec![0; DAppBookEntry::LEN];
                 dapp.pack_into_slice(buf2.as_mut_slice());
                 buf.extend_from_slice(&buf2[..]);
-                let account_metas = unique_account_metas(instructions, &Vec::new());
-                buf.put_u8(account_metas.len().as_u8());
-                for account_meta in account_metas.iter() {
-                    let mut account_meta_buf = vec![0; 1 + Signer::LEN];
-                    account_meta_buf[0] = 0;
-                    if account_meta.is_signer {
-                        account_meta_buf[0] |= 2;
-                    }
-                    if account_meta.is_writable {
-                        account_meta_buf[0] |= 1;
-                    }
-                    Signer::new(account_meta.pubkey)
-                        .pack_into_slice(&mut account_meta_buf[1..1 + Signer::LEN]);
-                    buf.extend_from_slice(account_meta_buf.as_slice());
-                }
-                buf.put_u16_le(instructions.len() as u16);
-                for instruction in instructions.iter() {
-                    append_instruction(instruction, &account_metas, &mut buf);
-                }
+                buf.extend_from_slice(&rent_funding_limit.to_le_bytes());
+                append_instructions(instructions, &mut buf);
             }
             &ProgramInstruction::FinalizeDAppTransaction {
                 ref account_guid_hash,
                 ref dapp,
                 ref instructions,
+                ref rent_funding_limit,
             } => {
                 buf.push(17);
                 buf.extend_from_slice(&account_guid_hash.to_bytes());
                 let mut buf2 = vec![0; DAppBookEntry::LEN];
                 dapp.pack_into_slice(buf2.as_mut_slice());
                 buf.extend_from_slice(&buf2[..]);
-                let account_metas = unique_account_metas(instructions, &Vec::new());
-                buf.put_u8(account_metas.len().as_u8());
-                for account_meta in account_metas.iter() {
-                    let mut account_meta_buf = vec![0; 1 + Signer::LEN];
-                    account_meta_buf[0] = 0;
-                    if account_meta.is_signer {
-                        account_meta_buf[0] |= 2;
-                    }
-                    if account_meta.is_writable {
-                        account_meta_buf[0] |= 1;
-                    }
-                    Signer::new(account_meta.pubkey)
-                        .pack_into_slice(&mut account_meta_buf[1..1 + Signer::LEN]);
-                    buf.extend_from_slice(account_meta_buf.as_slice());
-                }
-                buf.put_u16_le(instructions.len() as u16);
-                for instruction in instructions.iter() {
-                    append_instruction(instruction, &account_metas, &mut buf);
-                }
+                buf.extend_from_slice(&rent_funding_limit.to_le_bytes());
+                append_instructions(instructions, &mut buf);
             }
             &ProgramInstruction::InitAccountSettingsUpdate {
                 ref account_guid_hash,
                 ref whitelist_enabled,
                 ref dapps_enabled,
+                ref sol_transfers_enabled,
+                ref spl_transfers_enabled,
             } => {
                 buf.push(18);
                 buf.extend_from_slice(&account_guid_hash.to_bytes());
                 pack_option(whitelist_enabled.as_ref(), &mut buf);
                 pack_option(dapps_enabled.as_ref(), &mut buf);
+                pack_transfer_settings(sol_transfers_enabled, spl_transfers_enabled, &mut buf);
             }
             &ProgramInstruction::FinalizeAccountSettingsUpdate {
                 ref account_guid_hash,
                 ref whitelist_enabled,
                 ref dapps_enabled,
+                ref sol_transfers_enabled,
+                ref spl_transfers_enabled,
             } => {
                 buf.push(19);
                 buf.extend_from_slice(&account_guid_hash.to_bytes());
                 pack_option(whitelist_enabled.as_ref(), &mut buf);
                 pack_option(dapps_enabled.as_ref(), &mut buf);
+                pack_transfer_settings(sol_transfers_enabled, spl_transfers_enabled, &mut buf);
             }
             &ProgramInstruction::InitDAppBookUpdate { ref update } => {
                 buf.push(20);
@@ -508,6 +1338,386 @@ impl ProgramInstruction {
                 buf.extend_from_slice(account_guid_hash.to_bytes());
                 buf.extend_from_slice(&update_bytes);
             }
+            &ProgramInstruction::InitAddressBookVerification { ref entries } => {
+                buf.push(28);
+                append_address_book_entries(entries, &mut buf);
+            }
+            &ProgramInstruction::FinalizeAddressBookVerification { ref entries } => {
+                buf.push(29);
+                append_address_book_entries(entries, &mut buf);
+            }
+            &ProgramInstruction::InitActivityCheckpoint { checkpoint_index } => {
+                buf.push(30);
+                buf.extend_from_slice(&checkpoint_index.to_le_bytes());
+            }
+            &ProgramInstruction::FinalizeActivityCheckpoint { checkpoint_index } => {
+                buf.push(31);
+                buf.extend_from_slice(&checkpoint_index.to_le_bytes());
+            }
+            &ProgramInstruction::InitProgramConfig { ref update } => {
+                buf.push(32);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::UpdateProgramConfig { ref update } => {
+                buf.push(33);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::InitAddressBookPageUpdate {
+                page_index,
+                ref update,
+            } => {
+                buf.push(34);
+                buf.push(page_index);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::FinalizeAddressBookPageUpdate {
+                page_index,
+                ref update,
+            } => {
+                buf.push(35);
+                buf.push(page_index);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::SetApprovalDispositions { ref dispositions } => {
+                buf.push(36);
+                buf.push(dispositions.len() as u8);
+                for disposition in dispositions.iter() {
+                    disposition.pack(&mut buf);
+                }
+            }
+            &ProgramInstruction::InitWalletRepair => {
+                buf.push(37);
+            }
+            &ProgramInstruction::FinalizeWalletRepair => {
+                buf.push(38);
+            }
+            &ProgramInstruction::CheckWalletIntegrity => {
+                buf.push(39);
+            }
+            &ProgramInstruction::ValidateOnly { ref instruction } => {
+                buf.push(40);
+                buf.extend_from_slice(&instruction.pack());
+            }
+            &ProgramInstruction::PreflightTransfer {
+                ref account_guid_hash,
+                ref amount,
+                ref token_mint,
+                ref destination_name_hash,
+            } => {
+                buf.push(41);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&token_mint.to_bytes());
+                buf.extend_from_slice(destination_name_hash.to_bytes());
+            }
+            &ProgramInstruction::RenewMultisigOp {
+                ref params_hash,
+                ref extension,
+            } => {
+                buf.push(42);
+                buf.extend_from_slice(params_hash.as_ref());
+                append_duration(extension, &mut buf);
+            }
+            &ProgramInstruction::CreateOpAccount => {
+                buf.push(43);
+            }
+            &ProgramInstruction::InitApproverDelegationUpdate { ref update } => {
+                buf.push(44);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::FinalizeApproverDelegationUpdate { ref update } => {
+                buf.push(45);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::InitSignerDirectory => {
+                buf.push(46);
+            }
+            &ProgramInstruction::UpdateSignerDirectory { ref update } => {
+                buf.push(47);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::InitWalletSignerDirectoryUpdate { ref update } => {
+                buf.push(48);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::FinalizeWalletSignerDirectoryUpdate { ref update } => {
+                buf.push(49);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::InitWalletCanaryUpdate { ref canary_key } => {
+                buf.push(50);
+                append_optional_pubkey(canary_key, &mut buf);
+            }
+            &ProgramInstruction::FinalizeWalletCanaryUpdate { ref canary_key } => {
+                buf.push(51);
+                append_optional_pubkey(canary_key, &mut buf);
+            }
+            &ProgramInstruction::InitInternalTransfer {
+                ref source_account_guid_hash,
+                ref destination_account_guid_hash,
+                ref amount,
+                ref approval_timeout,
+            } => {
+                buf.push(52);
+                buf.extend_from_slice(source_account_guid_hash.to_bytes());
+                buf.extend_from_slice(destination_account_guid_hash.to_bytes());
+                buf.extend_from_slice(&amount.to_le_bytes());
+                append_optional_duration(approval_timeout, &mut buf);
+            }
+            &ProgramInstruction::FinalizeInternalTransfer {
+                ref source_account_guid_hash,
+                ref destination_account_guid_hash,
+                ref amount,
+                ref token_mint,
+            } => {
+                buf.push(53);
+                buf.extend_from_slice(source_account_guid_hash.to_bytes());
+                buf.extend_from_slice(destination_account_guid_hash.to_bytes());
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&token_mint.to_bytes());
+            }
+            &ProgramInstruction::AssertPolicyManifest { ref manifest_hash } => {
+                buf.push(54);
+                buf.extend_from_slice(manifest_hash.as_ref());
+            }
+            &ProgramInstruction::InitGovernedParameterUpdate { ref update } => {
+                buf.push(55);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::FinalizeGovernedParameterUpdate { ref update } => {
+                buf.push(56);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::CreateOpAccountForParams {
+                ref params_hash,
+                ref nonce,
+            } => {
+                buf.push(57);
+                buf.extend_from_slice(params_hash.as_ref());
+                buf.extend_from_slice(&nonce.to_le_bytes());
+            }
+            &ProgramInstruction::ReportCapacity {
+                ref warning_threshold_percent,
+            } => {
+                buf.push(58);
+                buf.push(*warning_threshold_percent);
+            }
+            &ProgramInstruction::InitUpdateSigners { ref update } => {
+                buf.push(59);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::FinalizeUpdateSigners { ref update } => {
+                buf.push(60);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::InitViewersUpdate { ref update } => {
+                buf.push(61);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::FinalizeViewersUpdate { ref update } => {
+                buf.push(62);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::VerifyAccess => {
+                buf.push(63);
+            }
+            &ProgramInstruction::InitSignerOrganizationsUpdate { ref update } => {
+                buf.push(64);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::FinalizeSignerOrganizationsUpdate { ref update } => {
+                buf.push(65);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::InitAssistantUpdate { ref assistant } => {
+                buf.push(66);
+                buf.extend_from_slice(assistant.as_ref());
+            }
+            &ProgramInstruction::FinalizeAssistantUpdate { ref assistant } => {
+                buf.push(67);
+                buf.extend_from_slice(assistant.as_ref());
+            }
+            &ProgramInstruction::InitAddressBookUpdateChunked {
+                ref update_hash,
+                update_len,
+            } => {
+                buf.push(68);
+                buf.extend_from_slice(update_hash.as_ref());
+                buf.extend_from_slice(&update_len.to_le_bytes());
+            }
+            &ProgramInstruction::SupplyAddressBookChunk { offset, ref chunk } => {
+                buf.push(69);
+                buf.extend_from_slice(&offset.to_le_bytes());
+                buf.extend_from_slice(chunk);
+            }
+            &ProgramInstruction::FinalizeAddressBookUpdateChunked { ref update_hash } => {
+                buf.push(70);
+                buf.extend_from_slice(update_hash.as_ref());
+            }
+            &ProgramInstruction::ConsolidateDeposits {
+                ref account_guid_hash,
+                ref deposit_indexes,
+            } => {
+                buf.push(71);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.push(deposit_indexes.len() as u8);
+                for index in deposit_indexes {
+                    buf.extend_from_slice(&index.to_le_bytes());
+                }
+            }
+            &ProgramInstruction::InitPendingOps => {
+                buf.push(72);
+            }
+            &ProgramInstruction::CleanupPendingOps => {
+                buf.push(73);
+            }
+            &ProgramInstruction::InitWithPrerequisite {
+                ref prerequisite,
+                ref instruction,
+            } => {
+                buf.push(74);
+                buf.extend_from_slice(prerequisite.as_ref());
+                buf.extend_from_slice(&instruction.pack());
+            }
+            &ProgramInstruction::InitWalletStateExport {
+                ref target_program_id,
+            } => {
+                buf.push(75);
+                buf.extend_from_slice(target_program_id.as_ref());
+            }
+            &ProgramInstruction::FinalizeWalletStateExport {
+                ref target_program_id,
+            } => {
+                buf.push(76);
+                buf.extend_from_slice(target_program_id.as_ref());
+            }
+            &ProgramInstruction::ImportWalletState => {
+                buf.push(77);
+            }
+            &ProgramInstruction::VerifyNameHash {
+                ref entry,
+                ref name,
+            } => {
+                buf.push(78);
+                entry.pack(&mut buf);
+                buf.push(name.len() as u8);
+                buf.extend_from_slice(name.as_bytes());
+            }
+            &ProgramInstruction::ExpireOp => {
+                buf.push(79);
+            }
+            &ProgramInstruction::InitCombinedTransfer {
+                ref account_guid_hash,
+                ref destination_name_hash,
+                ref transfer,
+            } => {
+                buf.push(80);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(destination_name_hash.to_bytes());
+                transfer.pack(&mut buf);
+            }
+            &ProgramInstruction::FinalizeCombinedTransfer {
+                ref account_guid_hash,
+                ref transfer,
+            } => {
+                buf.push(81);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                transfer.pack(&mut buf);
+            }
+            &ProgramInstruction::InitWalletMetadataUpdate { ref metadata_hash } => {
+                buf.push(82);
+                buf.extend_from_slice(metadata_hash.as_ref());
+            }
+            &ProgramInstruction::FinalizeWalletMetadataUpdate { ref metadata_hash } => {
+                buf.push(83);
+                buf.extend_from_slice(metadata_hash.as_ref());
+            }
+            &ProgramInstruction::SubmitApprovalDisposition {
+                ref disposition,
+                ref params_hash,
+                ref denial_reason,
+                ref reason_hash,
+            } => {
+                buf.push(84);
+                buf.push(disposition.to_u8());
+                buf.extend_from_slice(params_hash.as_ref());
+                buf.push(denial_reason.to_u8());
+                if let Some(reason_hash) = reason_hash {
+                    buf.extend_from_slice(reason_hash.as_ref());
+                }
+            }
+            &ProgramInstruction::CollectApprovalDispositions { dispositions } => {
+                buf.push(85);
+                buf.push(dispositions);
+            }
+            &ProgramInstruction::InitDAppTemplatesUpdate { ref update } => {
+                buf.push(86);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::FinalizeDAppTemplatesUpdate { ref update } => {
+                buf.push(87);
+                update.pack(&mut buf);
+            }
+            &ProgramInstruction::ExecuteDAppTemplate {
+                ref template_slot,
+                ref instruction,
+            } => {
+                buf.push(88);
+                buf.push(template_slot.value as u8);
+                append_instructions(&vec![instruction.clone()], &mut buf);
+            }
+            &ProgramInstruction::VerifyCoSignature {
+                ref disposition,
+                ref params_hash,
+                ref denial_reason,
+                ref reason_hash,
+            } => {
+                buf.push(89);
+                buf.push(disposition.to_u8());
+                buf.extend_from_slice(params_hash.as_ref());
+                buf.push(denial_reason.to_u8());
+                if let Some(reason_hash) = reason_hash {
+                    buf.extend_from_slice(reason_hash.as_ref());
+                }
+            }
+            &ProgramInstruction::InitWalletClose { ref rent_collector } => {
+                buf.push(90);
+                buf.extend_from_slice(rent_collector.as_ref());
+            }
+            &ProgramInstruction::FinalizeWalletClose { ref rent_collector } => {
+                buf.push(91);
+                buf.extend_from_slice(rent_collector.as_ref());
+            }
+            &ProgramInstruction::ReportSignerActivity {
+                ref inactive_after_days,
+            } => {
+                buf.push(92);
+                buf.extend_from_slice(&inactive_after_days.to_le_bytes());
+            }
+            &ProgramInstruction::ExecuteDAppChunk {
+                ref account_guid_hash,
+                ref dapp,
+                ref instructions,
+                ref rent_funding_limit,
+                ref chunk_len,
+            } => {
+                buf.push(93);
+                buf.push(*chunk_len);
+                buf.extend_from_slice(&account_guid_hash.to_bytes());
+                let mut buf2 = vec![0; DAppBookEntry::LEN];
+                dapp.pack_into_slice(buf2.as_mut_slice());
+                buf.extend_from_slice(&buf2[..]);
+                buf.extend_from_slice(&rent_funding_limit.to_le_bytes());
+                append_instructions(instructions, &mut buf);
+            }
+            &ProgramInstruction::InitWithIdempotencyKey {
+                ref idempotency_key,
+                ref instruction,
+            } => {
+                buf.push(94);
+                buf.extend_from_slice(idempotency_key.to_bytes());
+                buf.extend_from_slice(&instruction.pack());
+            }
         }
         buf
     }
@@ -541,10 +1751,270 @@ impl ProgramInstruction {
             25 => Self::unpack_finalize_balance_account_name_update_instruction(rest)?,
             26 => Self::unpack_init_balance_account_policy_update_instruction(rest)?,
             27 => Self::unpack_finalize_balance_account_policy_update_instruction(rest)?,
+            28 => Self::unpack_init_address_book_verification_instruction(rest)?,
+            29 => Self::unpack_finalize_address_book_verification_instruction(rest)?,
+            30 => Self::unpack_init_activity_checkpoint_instruction(rest)?,
+            31 => Self::unpack_finalize_activity_checkpoint_instruction(rest)?,
+            32 => Self::unpack_init_program_config_instruction(rest)?,
+            33 => Self::unpack_update_program_config_instruction(rest)?,
+            34 => Self::unpack_init_address_book_page_update_instruction(rest)?,
+            35 => Self::unpack_finalize_address_book_page_update_instruction(rest)?,
+            36 => Self::unpack_set_approval_dispositions_instruction(rest)?,
+            37 => Self::InitWalletRepair,
+            38 => Self::FinalizeWalletRepair,
+            39 => Self::CheckWalletIntegrity,
+            40 => Self::unpack_validate_only_instruction(rest)?,
+            41 => Self::unpack_preflight_transfer_instruction(rest)?,
+            42 => Self::unpack_renew_multisig_op_instruction(rest)?,
+            43 => Self::CreateOpAccount,
+            44 => Self::unpack_init_approver_delegation_update_instruction(rest)?,
+            45 => Self::unpack_finalize_approver_delegation_update_instruction(rest)?,
+            46 => Self::InitSignerDirectory,
+            47 => Self::UpdateSignerDirectory {
+                update: SignerDirectoryUpdate::unpack(rest)?,
+            },
+            48 => Self::InitWalletSignerDirectoryUpdate {
+                update: WalletSignerDirectoryUpdate::unpack(rest)?,
+            },
+            49 => Self::FinalizeWalletSignerDirectoryUpdate {
+                update: WalletSignerDirectoryUpdate::unpack(rest)?,
+            },
+            50 => Self::InitWalletCanaryUpdate {
+                canary_key: read_optional_pubkey(&mut rest.iter())?,
+            },
+            51 => Self::FinalizeWalletCanaryUpdate {
+                canary_key: read_optional_pubkey(&mut rest.iter())?,
+            },
+            52 => Self::unpack_init_internal_transfer_instruction(rest)?,
+            53 => Self::unpack_finalize_internal_transfer_instruction(rest)?,
+            54 => Self::AssertPolicyManifest {
+                manifest_hash: Hash::new_from_array(
+                    *read_fixed_size_array::<32>(&mut rest.iter())
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                ),
+            },
+            55 => Self::InitGovernedParameterUpdate {
+                update: GovernedParameterUpdate::unpack(rest)?,
+            },
+            56 => Self::FinalizeGovernedParameterUpdate {
+                update: GovernedParameterUpdate::unpack(rest)?,
+            },
+            57 => Self::CreateOpAccountForParams {
+                params_hash: Hash::new_from_array(
+                    *read_fixed_size_array::<32>(&mut rest.iter())
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                ),
+                nonce: rest
+                    .get(32..40)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u64::from_le_bytes)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            },
+            58 => Self::ReportCapacity {
+                warning_threshold_percent: rest
+                    .first()
+                    .filter(|percent| **percent <= 100)
+                    .copied()
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            },
+            59 => Self::InitUpdateSigners {
+                update: SignersUpdate::unpack(rest)?,
+            },
+            60 => Self::FinalizeUpdateSigners {
+                update: SignersUpdate::unpack(rest)?,
+            },
+            61 => Self::InitViewersUpdate {
+                update: ViewersUpdate::unpack(rest)?,
+            },
+            62 => Self::FinalizeViewersUpdate {
+                update: ViewersUpdate::unpack(rest)?,
+            },
+            63 => Self::VerifyAccess,
+            64 => Self::InitSignerOrganizationsUpdate {
+                update: SignerOrganizationsUpdate::unpack(rest)?,
+            },
+            65 => Self::FinalizeSignerOrganizationsUpdate {
+                update: SignerOrganizationsUpdate::unpack(rest)?,
+            },
+            66 => Self::InitAssistantUpdate {
+                assistant: Pubkey::new(
+                    read_slice(&mut rest.iter(), 32).ok_or(ProgramError::InvalidInstructionData)?,
+                ),
+            },
+            67 => Self::FinalizeAssistantUpdate {
+                assistant: Pubkey::new(
+                    read_slice(&mut rest.iter(), 32).ok_or(ProgramError::InvalidInstructionData)?,
+                ),
+            },
+            68 => Self::InitAddressBookUpdateChunked {
+                update_hash: unpack_update_hash(rest)?,
+                update_len: unpack_u32(
+                    rest.get(32..).ok_or(ProgramError::InvalidInstructionData)?,
+                )?,
+            },
+            69 => Self::SupplyAddressBookChunk {
+                offset: unpack_u32(rest)?,
+                chunk: rest
+                    .get(4..)
+                    .ok_or(ProgramError::InvalidInstructionData)?
+                    .to_vec(),
+            },
+            70 => Self::FinalizeAddressBookUpdateChunked {
+                update_hash: unpack_update_hash(rest)?,
+            },
+            71 => Self::unpack_consolidate_deposits_instruction(rest)?,
+            72 => Self::InitPendingOps,
+            73 => Self::CleanupPendingOps,
+            74 => Self::unpack_init_with_prerequisite_instruction(rest)?,
+            75 => Self::InitWalletStateExport {
+                target_program_id: Pubkey::new(
+                    read_slice(&mut rest.iter(), 32).ok_or(ProgramError::InvalidInstructionData)?,
+                ),
+            },
+            76 => Self::FinalizeWalletStateExport {
+                target_program_id: Pubkey::new(
+                    read_slice(&mut rest.iter(), 32).ok_or(ProgramError::InvalidInstructionData)?,
+                ),
+            },
+            77 => Self::ImportWalletState,
+            78 => Self::unpack_verify_name_hash_instruction(rest)?,
+            79 => Self::ExpireOp,
+            80 => Self::unpack_init_combined_transfer_instruction(rest)?,
+            81 => Self::unpack_finalize_combined_transfer_instruction(rest)?,
+            82 => Self::InitWalletMetadataUpdate {
+                metadata_hash: unpack_update_hash(rest)?,
+            },
+            83 => Self::FinalizeWalletMetadataUpdate {
+                metadata_hash: unpack_update_hash(rest)?,
+            },
+            84 => Self::unpack_submit_approval_disposition_instruction(rest)?,
+            85 => Self::CollectApprovalDispositions {
+                dispositions: rest
+                    .first()
+                    .copied()
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            },
+            86 => Self::InitDAppTemplatesUpdate {
+                update: DAppTemplatesUpdate::unpack(rest)?,
+            },
+            87 => Self::FinalizeDAppTemplatesUpdate {
+                update: DAppTemplatesUpdate::unpack(rest)?,
+            },
+            88 => Self::unpack_execute_dapp_template_instruction(rest)?,
+            89 => Self::unpack_verify_co_signature_instruction(rest)?,
+            90 => Self::InitWalletClose {
+                rent_collector: Pubkey::new(
+                    read_slice(&mut rest.iter(), 32).ok_or(ProgramError::InvalidInstructionData)?,
+                ),
+            },
+            91 => Self::FinalizeWalletClose {
+                rent_collector: Pubkey::new(
+                    read_slice(&mut rest.iter(), 32).ok_or(ProgramError::InvalidInstructionData)?,
+                ),
+            },
+            92 => Self::ReportSignerActivity {
+                inactive_after_days: rest
+                    .get(0..2)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u16::from_le_bytes)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            },
+            93 => Self::unpack_execute_dapp_chunk_instruction(rest)?,
+            94 => Self::unpack_init_with_idempotency_key_instruction(rest)?,
             _ => return Err(ProgramError::InvalidInstructionData),
         })
     }
 
+    fn unpack_validate_only_instruction(bytes: &[u8]) -> Result<ProgramInstruction, ProgramError> {
+        match Self::unpack(bytes)? {
+            Self::ValidateOnly { .. } => Err(ProgramError::InvalidInstructionData),
+            instruction => Ok(Self::ValidateOnly {
+                instruction: Box::new(instruction),
+            }),
+        }
+    }
+
+    fn unpack_init_combined_transfer_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let mut iter = bytes.iter();
+        let account_guid_hash = BalanceAccountGuidHash::new(
+            read_fixed_size_array::<32>(&mut iter).ok_or(ProgramError::InvalidInstructionData)?,
+        );
+        let destination_name_hash = AddressBookEntryNameHash::new(
+            read_fixed_size_array::<32>(&mut iter).ok_or(ProgramError::InvalidInstructionData)?,
+        );
+        Ok(Self::InitCombinedTransfer {
+            account_guid_hash,
+            destination_name_hash,
+            transfer: CombinedTransfer::unpack(&mut iter)?,
+        })
+    }
+
+    fn unpack_finalize_combined_transfer_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let mut iter = bytes.iter();
+        let account_guid_hash = BalanceAccountGuidHash::new(
+            read_fixed_size_array::<32>(&mut iter).ok_or(ProgramError::InvalidInstructionData)?,
+        );
+        Ok(Self::FinalizeCombinedTransfer {
+            account_guid_hash,
+            transfer: CombinedTransfer::unpack(&mut iter)?,
+        })
+    }
+
+    fn unpack_verify_name_hash_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let mut iter = bytes.iter();
+        let entry = NamedEntry::unpack(&mut iter)?;
+        let name_len = *read_u8(&mut iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let name = read_slice(&mut iter, usize::from(name_len))
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        Ok(Self::VerifyNameHash {
+            entry,
+            name: String::from_utf8(name.to_vec())
+                .map_err(|_| ProgramError::InvalidInstructionData)?,
+        })
+    }
+
+    fn unpack_init_with_prerequisite_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let prerequisite = unpack_update_hash(bytes)?;
+        match Self::unpack(&bytes[32..])? {
+            Self::ValidateOnly { .. } | Self::InitWithPrerequisite { .. } => {
+                Err(ProgramError::InvalidInstructionData)
+            }
+            instruction => Ok(Self::InitWithPrerequisite {
+                prerequisite,
+                instruction: Box::new(instruction),
+            }),
+        }
+    }
+
+    fn unpack_init_with_idempotency_key_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let idempotency_key = IdempotencyKey::new(
+            read_fixed_size_array::<{ IdempotencyKey::LEN }>(&mut bytes.iter())
+                .ok_or(ProgramError::InvalidInstructionData)?,
+        );
+        if idempotency_key == IdempotencyKey::zero() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        match Self::unpack(&bytes[IdempotencyKey::LEN..])? {
+            Self::ValidateOnly { .. } | Self::InitWithIdempotencyKey { .. } => {
+                Err(ProgramError::InvalidInstructionData)
+            }
+            instruction => Ok(Self::InitWithIdempotencyKey {
+                idempotency_key,
+                instruction: Box::new(instruction),
+            }),
+        }
+    }
+
     fn unpack_init_wallet_instruction(bytes: &[u8]) -> Result<ProgramInstruction, ProgramError> {
         Ok(Self::InitWallet {
             initial_config: InitialWalletConfig::unpack(bytes)?,
@@ -624,32 +2094,125 @@ impl ProgramInstruction {
             })
             .ok_or(ProgramError::InvalidInstructionData)?;
 
+        let memo_hash = unpack_optional_memo_hash(bytes, 81)?;
+        let ata_rent_payer_offset = 81 + if memo_hash.is_some() { 1 + 32 } else { 1 };
+        let ata_rent_payer = unpack_optional_ata_rent_payer(bytes, ata_rent_payer_offset)?;
+        let amount_spec_offset =
+            ata_rent_payer_offset + if ata_rent_payer.is_some() { 2 } else { 1 };
+        let (amount_spec, travel_rule_hash) = unpack_amount_spec_and_travel_rule_hash(
+            bytes.get(amount_spec_offset..).unwrap_or(&[]),
+        )?;
         Ok(Self::InitTransfer {
             account_guid_hash,
             amount,
             destination_name_hash,
+            approval_timeout: unpack_approval_timeout_override(bytes, 72)?,
+            memo_hash,
+            ata_rent_payer,
+            amount_spec,
+            travel_rule_hash,
         })
     }
 
     fn unpack_set_approval_disposition_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        let (disposition, rest) = bytes
+        let (disposition, params_hash, denial_reason, reason_hash) =
+            unpack_approval_disposition(bytes)?;
+        Ok(Self::SetApprovalDisposition {
+            disposition,
+            params_hash,
+            denial_reason,
+            reason_hash,
+        })
+    }
+
+    fn unpack_execute_dapp_template_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.iter();
+        let template_slot = SlotId::new(usize::from(
+            *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?,
+        ));
+        let mut instructions = read_instructions(iter)?;
+        if instructions.len() != 1 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(Self::ExecuteDAppTemplate {
+            template_slot,
+            instruction: instructions.remove(0),
+        })
+    }
+
+    fn unpack_submit_approval_disposition_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let (disposition, params_hash, denial_reason, reason_hash) =
+            unpack_approval_disposition(bytes)?;
+        Ok(Self::SubmitApprovalDisposition {
+            disposition,
+            params_hash,
+            denial_reason,
+            reason_hash,
+        })
+    }
+
+    fn unpack_verify_co_signature_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let (disposition, params_hash, denial_reason, reason_hash) =
+            unpack_approval_disposition(bytes)?;
+        Ok(Self::VerifyCoSignature {
+            disposition,
+            params_hash,
+            denial_reason,
+            reason_hash,
+        })
+    }
+
+    fn unpack_set_approval_dispositions_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let (count, rest) = bytes
             .split_first()
             .ok_or(ProgramError::InvalidInstructionData)?;
-        Ok(Self::SetApprovalDisposition {
-            disposition: ApprovalDisposition::from_u8(*disposition),
-            params_hash: Hash::new_from_array(
-                rest.get(0..32)
-                    .and_then(|slice| slice.try_into().ok())
-                    .ok_or(ProgramError::InvalidInstructionData)?,
-            ),
+        if *count == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(Self::SetApprovalDispositions {
+            dispositions: rest
+                .get(..usize::from(*count) * ApprovalDispositionUpdate::LEN)
+                .ok_or(ProgramError::InvalidInstructionData)?
+                .chunks_exact(ApprovalDispositionUpdate::LEN)
+                .map(ApprovalDispositionUpdate::unpack)
+                .collect::<Result<Vec<_>, _>>()?,
         })
     }
 
     fn unpack_finalize_transfer_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
+        let memo_hash = unpack_optional_memo_hash(bytes, 72)?;
+        let fee_reimbursement_offset = 72 + if memo_hash.is_some() { 1 + 32 } else { 1 };
+        let ata_rent_payer = unpack_optional_ata_rent_payer(bytes, fee_reimbursement_offset + 8)?;
+        let token_program_offset =
+            fee_reimbursement_offset + 8 + if ata_rent_payer.is_some() { 2 } else { 1 };
+        // older clients send nothing after the rent payer; the token program and the
+        // amount spec are each only sent when needed, so they are told apart by length
+        let (token_program, (amount_spec, travel_rule_hash)) =
+            match bytes.get(token_program_offset..) {
+                None | Some([]) => (None, (AmountSpec::Exact, None)),
+                Some(rest) if rest.len() == 1 => (None, (unpack_amount_spec(rest)?, None)),
+                Some(rest) => (
+                    Some(Pubkey::new_from_array(
+                        rest.get(..32)
+                            .and_then(|slice| slice.try_into().ok())
+                            .ok_or(ProgramError::InvalidInstructionData)?,
+                    ))
+                    .filter(|token_program| *token_program != Pubkey::default()),
+                    unpack_amount_spec_and_travel_rule_hash(&rest[32..])?,
+                ),
+            };
         Ok(Self::FinalizeTransfer {
             account_guid_hash: unpack_account_guid_hash(bytes)?,
             amount: bytes
@@ -663,45 +2226,176 @@ impl ProgramInstruction {
                     .and_then(|slice| slice.try_into().ok())
                     .ok_or(ProgramError::InvalidInstructionData)?,
             ),
+            memo_hash,
+            fee_reimbursement: match bytes.get(fee_reimbursement_offset..) {
+                None | Some([]) => 0,
+                Some(rest) => rest
+                    .get(..8)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u64::from_le_bytes)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            },
+            ata_rent_payer,
+            token_program,
+            amount_spec,
+            travel_rule_hash,
         })
     }
 
-    fn unpack_init_wrap_unwrap_instruction(
+    fn unpack_preflight_transfer_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        if let Some(direction) = bytes.get(40) {
-            Ok(Self::InitWrapUnwrap {
-                account_guid_hash: unpack_account_guid_hash(bytes)?,
-                amount: bytes
-                    .get(32..40)
+        Ok(Self::PreflightTransfer {
+            account_guid_hash: unpack_account_guid_hash(bytes)?,
+            amount: bytes
+                .get(32..40)
+                .and_then(|slice| slice.try_into().ok())
+                .map(u64::from_le_bytes)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            token_mint: Pubkey::new_from_array(
+                bytes
+                    .get(40..72)
                     .and_then(|slice| slice.try_into().ok())
-                    .map(u64::from_le_bytes)
                     .ok_or(ProgramError::InvalidInstructionData)?,
-                direction: WrapDirection::from_u8(*direction),
-            })
-        } else {
-            Err(ProgramError::InvalidInstructionData)
-        }
+            ),
+            destination_name_hash: bytes
+                .get(72..104)
+                .and_then(|slice| slice.try_into().ok())
+                .map(|bytes| AddressBookEntryNameHash::new(bytes))
+                .ok_or(ProgramError::InvalidInstructionData)?,
+        })
     }
 
-    fn unpack_finalize_wrap_unwrap_instruction(
+    fn unpack_init_approver_delegation_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        if let Some(direction) = bytes.get(40) {
-            Ok(Self::FinalizeWrapUnwrap {
-                account_guid_hash: unpack_account_guid_hash(bytes)?,
+        Ok(Self::InitApproverDelegationUpdate {
+            update: ApproverDelegationUpdate::unpack(bytes)?,
+        })
+    }
+
+    fn unpack_finalize_approver_delegation_update_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::FinalizeApproverDelegationUpdate {
+            update: ApproverDelegationUpdate::unpack(bytes)?,
+        })
+    }
+
+    fn unpack_renew_multisig_op_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.iter();
+        let params_hash = read_fixed_size_array::<32>(iter)
+            .map(|bytes| Hash::new_from_array(*bytes))
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        Ok(Self::RenewMultisigOp {
+            params_hash,
+            extension: read_duration(iter).ok_or(ProgramError::InvalidInstructionData)?,
+        })
+    }
+
+    fn unpack_init_wrap_unwrap_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        if let Some(direction) = bytes.get(40) {
+            // older clients send nothing past the approval timeout
+            let (destination, travel_rule_hash) = match bytes.get(50..) {
+                None | Some([]) => (None, None),
+                Some(rest) if rest.len() >= AddressBookEntry::LEN => (
+                    Some(AddressBookEntry::unpack_from_slice(rest)?),
+                    unpack_optional_travel_rule_hash(&rest[AddressBookEntry::LEN..])?,
+                ),
+                Some(_) => return Err(ProgramError::InvalidInstructionData),
+            };
+            Ok(Self::InitWrapUnwrap {
+                account_guid_hash: unpack_account_guid_hash(bytes)?,
+                amount: bytes
+                    .get(32..40)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u64::from_le_bytes)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+                direction: WrapDirection::from_u8(*direction),
+                approval_timeout: unpack_approval_timeout_override(bytes, 41)?,
+                destination,
+                travel_rule_hash,
+            })
+        } else {
+            Err(ProgramError::InvalidInstructionData)
+        }
+    }
+
+    fn unpack_finalize_wrap_unwrap_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        if let Some(direction) = bytes.get(40) {
+            let (destination, travel_rule_hash) = match bytes.get(41..) {
+                None | Some([]) => (None, None),
+                Some(rest) if rest.len() >= PUBKEY_BYTES => (
+                    Some(Pubkey::new(&rest[..PUBKEY_BYTES])),
+                    unpack_optional_travel_rule_hash(&rest[PUBKEY_BYTES..])?,
+                ),
+                Some(_) => return Err(ProgramError::InvalidInstructionData),
+            };
+            Ok(Self::FinalizeWrapUnwrap {
+                account_guid_hash: unpack_account_guid_hash(bytes)?,
                 amount: bytes
                     .get(32..40)
                     .and_then(|slice| slice.try_into().ok())
                     .map(u64::from_le_bytes)
                     .ok_or(ProgramError::InvalidInstructionData)?,
                 direction: WrapDirection::from_u8(*direction),
+                destination,
+                travel_rule_hash,
             })
         } else {
             Err(ProgramError::InvalidInstructionData)
         }
     }
 
+    fn unpack_init_internal_transfer_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::InitInternalTransfer {
+            source_account_guid_hash: unpack_account_guid_hash(bytes)?,
+            destination_account_guid_hash: unpack_account_guid_hash(
+                bytes
+                    .get(32..)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            )?,
+            amount: bytes
+                .get(64..72)
+                .and_then(|slice| slice.try_into().ok())
+                .map(u64::from_le_bytes)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            approval_timeout: unpack_approval_timeout_override(bytes, 72)?,
+        })
+    }
+
+    fn unpack_finalize_internal_transfer_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::FinalizeInternalTransfer {
+            source_account_guid_hash: unpack_account_guid_hash(bytes)?,
+            destination_account_guid_hash: unpack_account_guid_hash(
+                bytes
+                    .get(32..)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            )?,
+            amount: bytes
+                .get(64..72)
+                .and_then(|slice| slice.try_into().ok())
+                .map(u64::from_le_bytes)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            token_mint: Pubkey::new_from_array(
+                bytes
+                    .get(72..104)
+                    .and_then(|slice| slice.try_into().ok())
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            ),
+        })
+    }
+
     fn unpack_init_update_signer_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
@@ -750,6 +2444,78 @@ impl ProgramInstruction {
         })
     }
 
+    fn unpack_init_address_book_verification_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::InitAddressBookVerification {
+            entries: read_address_book_entries(&mut bytes.iter())?,
+        })
+    }
+
+    fn unpack_finalize_address_book_verification_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::FinalizeAddressBookVerification {
+            entries: read_address_book_entries(&mut bytes.iter())?,
+        })
+    }
+
+    fn unpack_init_activity_checkpoint_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::InitActivityCheckpoint {
+            checkpoint_index: unpack_u32(bytes)?,
+        })
+    }
+
+    fn unpack_finalize_activity_checkpoint_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::FinalizeActivityCheckpoint {
+            checkpoint_index: unpack_u32(bytes)?,
+        })
+    }
+
+    fn unpack_init_program_config_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::InitProgramConfig {
+            update: ProgramConfigUpdate::unpack(bytes)?,
+        })
+    }
+
+    fn unpack_update_program_config_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::UpdateProgramConfig {
+            update: ProgramConfigUpdate::unpack(bytes)?,
+        })
+    }
+
+    fn unpack_init_address_book_page_update_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let (page_index, rest) = bytes
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        Ok(Self::InitAddressBookPageUpdate {
+            page_index: *page_index,
+            update: AddressBookUpdate::unpack(rest)?,
+        })
+    }
+
+    fn unpack_finalize_address_book_page_update_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let (page_index, rest) = bytes
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        Ok(Self::FinalizeAddressBookPageUpdate {
+            page_index: *page_index,
+            update: AddressBookUpdate::unpack(rest)?,
+        })
+    }
+
     fn unpack_init_dapp_transaction_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
@@ -760,9 +2526,13 @@ impl ProgramInstruction {
         let dapp = DAppBookEntry::unpack_from_slice(
             read_slice(iter, DAppBookEntry::LEN).ok_or(ProgramError::InvalidInstructionData)?,
         )?;
+        let rent_funding_limit = read_fixed_size_array::<8>(iter)
+            .map(|bytes| u64::from_le_bytes(*bytes))
+            .ok_or(ProgramError::InvalidInstructionData)?;
         Ok(Self::InitDAppTransaction {
             account_guid_hash,
             dapp,
+            rent_funding_limit,
             instructions: read_instructions(iter)?,
         })
     }
@@ -777,23 +2547,56 @@ impl ProgramInstruction {
         let dapp = DAppBookEntry::unpack_from_slice(
             read_slice(iter, DAppBookEntry::LEN).ok_or(ProgramError::InvalidInstructionData)?,
         )?;
+        let rent_funding_limit = read_fixed_size_array::<8>(iter)
+            .map(|bytes| u64::from_le_bytes(*bytes))
+            .ok_or(ProgramError::InvalidInstructionData)?;
         Ok(Self::FinalizeDAppTransaction {
             account_guid_hash,
             dapp,
+            rent_funding_limit,
             instructions: read_instructions(iter)?,
         })
     }
 
+    fn unpack_execute_dapp_chunk_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let (chunk_len, rest) = bytes
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        match Self::unpack_finalize_dapp_transaction_instruction(rest)? {
+            Self::FinalizeDAppTransaction {
+                account_guid_hash,
+                dapp,
+                instructions,
+                rent_funding_limit,
+            } if *chunk_len > 0 => Ok(Self::ExecuteDAppChunk {
+                account_guid_hash,
+                dapp,
+                instructions,
+                rent_funding_limit,
+                chunk_len: *chunk_len,
+            }),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+
     fn unpack_init_account_settings_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
         let iter = &mut bytes.into_iter();
+        let account_guid_hash = unpack_account_guid_hash(
+            read_slice(iter, 32).ok_or(ProgramError::InvalidInstructionData)?,
+        )?;
+        let whitelist_enabled = unpack_option::<BooleanSetting>(iter)?;
+        let dapps_enabled = unpack_option::<BooleanSetting>(iter)?;
+        let (sol_transfers_enabled, spl_transfers_enabled) = unpack_transfer_settings(iter)?;
         Ok(Self::InitAccountSettingsUpdate {
-            account_guid_hash: unpack_account_guid_hash(
-                read_slice(iter, 32).ok_or(ProgramError::InvalidInstructionData)?,
-            )?,
-            whitelist_enabled: unpack_option::<BooleanSetting>(iter)?,
-            dapps_enabled: unpack_option::<BooleanSetting>(iter)?,
+            account_guid_hash,
+            whitelist_enabled,
+            dapps_enabled,
+            sol_transfers_enabled,
+            spl_transfers_enabled,
         })
     }
 
@@ -801,12 +2604,18 @@ impl ProgramInstruction {
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
         let iter = &mut bytes.into_iter();
+        let account_guid_hash = unpack_account_guid_hash(
+            read_slice(iter, 32).ok_or(ProgramError::InvalidInstructionData)?,
+        )?;
+        let whitelist_enabled = unpack_option::<BooleanSetting>(iter)?;
+        let dapps_enabled = unpack_option::<BooleanSetting>(iter)?;
+        let (sol_transfers_enabled, spl_transfers_enabled) = unpack_transfer_settings(iter)?;
         Ok(Self::FinalizeAccountSettingsUpdate {
-            account_guid_hash: unpack_account_guid_hash(
-                read_slice(iter, 32).ok_or(ProgramError::InvalidInstructionData)?,
-            )?,
-            whitelist_enabled: unpack_option::<BooleanSetting>(iter)?,
-            dapps_enabled: unpack_option::<BooleanSetting>(iter)?,
+            account_guid_hash,
+            whitelist_enabled,
+            dapps_enabled,
+            sol_transfers_enabled,
+            spl_transfers_enabled,
         })
     }
 
@@ -826,6 +2635,23 @@ impl ProgramInstruction {
         })
     }
 
+    fn unpack_consolidate_deposits_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let account_guid_hash = unpack_account_guid_hash(bytes)?;
+        let (count, rest) = bytes
+            .get(32..)
+            .and_then(|rest| rest.split_first())
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let deposit_indexes = (0..*count as usize)
+            .map(|i| unpack_u32(rest.get(i * 4..).unwrap_or_default()))
+            .collect::<Result<Vec<u32>, ProgramError>>()?;
+        Ok(Self::ConsolidateDeposits {
+            account_guid_hash,
+            deposit_indexes,
+        })
+    }
+
     fn unpack_init_address_book_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
@@ -938,20 +2764,77 @@ pub struct AddressBookUpdate {
     pub add_address_book_entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
     pub remove_address_book_entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
     pub balance_account_whitelist_updates: Vec<BalanceAccountWhitelistUpdate>,
+    /// Binds address book entries to the token mints they may receive. Only
+    /// the wallet's own address book supports bindings, not its pages.
+    pub add_destination_token_mints: Vec<(SlotId<DestinationTokenMint>, DestinationTokenMint)>,
+    pub remove_destination_token_mints: Vec<(SlotId<DestinationTokenMint>, DestinationTokenMint)>,
+    /// Tags address book entries with namespaces, such as token accounts or
+    /// memo-keyed deposit addresses. Also only supported by the wallet's own
+    /// address book.
+    pub add_destination_namespaces: Vec<(SlotId<DestinationNamespace>, DestinationNamespace)>,
+    pub remove_destination_namespaces: Vec<(SlotId<DestinationNamespace>, DestinationNamespace)>,
 }
 
 impl AddressBookUpdate {
-    fn unpack(bytes: &[u8]) -> Result<AddressBookUpdate, ProgramError> {
+    /// The most entries, whitelist changes, token mint bindings and namespace
+    /// tags a single update can add and remove, so that applying it fits in
+    /// the compute budget of one instruction. Larger changes are split across
+    /// several updates, or sent as a chunked update, which is exempt as it
+    /// is assembled in a buffer account rather than sent whole.
+    pub const MAX_CHANGES: usize = 64;
+
+    /// The number of changes the update makes, counted against `MAX_CHANGES`.
+    pub fn change_count(&self) -> usize {
+        self.add_address_book_entries.len()
+            + self.remove_address_book_entries.len()
+            + self
+                .balance_account_whitelist_updates
+                .iter()
+                .map(|update| {
+                    update.add_allowed_destinations.len() + update.remove_allowed_destinations.len()
+                })
+                .sum::<usize>()
+            + self.add_destination_token_mints.len()
+            + self.remove_destination_token_mints.len()
+            + self.add_destination_namespaces.len()
+            + self.remove_destination_namespaces.len()
+    }
+
+    pub(crate) fn unpack(bytes: &[u8]) -> Result<AddressBookUpdate, ProgramError> {
         let mut iter = bytes.iter();
 
         let add_address_book_entries = read_address_book_entries(&mut iter)?;
         let remove_address_book_entries = read_address_book_entries(&mut iter)?;
         let balance_account_whitelist_updates = read_balance_account_whitelist_updates(&mut iter)?;
+        // older clients send no token mint bindings
+        let (add_destination_token_mints, remove_destination_token_mints) =
+            if iter.as_slice().is_empty() {
+                (Vec::new(), Vec::new())
+            } else {
+                (
+                    read_address_book_entries(&mut iter)?,
+                    read_address_book_entries(&mut iter)?,
+                )
+            };
+        // nor namespaces
+        let (add_destination_namespaces, remove_destination_namespaces) =
+            if iter.as_slice().is_empty() {
+                (Vec::new(), Vec::new())
+            } else {
+                (
+                    read_address_book_entries(&mut iter)?,
+                    read_address_book_entries(&mut iter)?,
+                )
+            };
 
         Ok(AddressBookUpdate {
             add_address_book_entries,
             remove_address_book_entries,
             balance_account_whitelist_updates,
+            add_destination_token_mints,
+            remove_destination_token_mints,
+            add_destination_namespaces,
+            remove_destination_namespaces,
         })
     }
 
@@ -959,6 +2842,20 @@ impl AddressBookUpdate {
         append_address_book_entries(&self.add_address_book_entries, dst);
         append_address_book_entries(&self.remove_address_book_entries, dst);
         append_balance_account_whitelist_updates(&self.balance_account_whitelist_updates, dst);
+        // left out when empty, so updates without bindings hash as they used to
+        let has_namespaces = !self.add_destination_namespaces.is_empty()
+            || !self.remove_destination_namespaces.is_empty();
+        if has_namespaces
+            || !self.add_destination_token_mints.is_empty()
+            || !self.remove_destination_token_mints.is_empty()
+        {
+            append_address_book_entries(&self.add_destination_token_mints, dst);
+            append_address_book_entries(&self.remove_destination_token_mints, dst);
+        }
+        if has_namespaces {
+            append_address_book_entries(&self.add_destination_namespaces, dst);
+            append_address_book_entries(&self.remove_destination_namespaces, dst);
+        }
     }
 }
 
@@ -968,6 +2865,36 @@ pub struct WalletConfigPolicyUpdate {
     pub approval_timeout_for_config: Option<Duration>,
     pub add_config_approvers: Vec<(SlotId<Signer>, Signer)>,
     pub remove_config_approvers: Vec<(SlotId<Signer>, Signer)>,
+    /// When set, the config approvers are replaced by exactly this set in a
+    /// single step. Must not be combined with add/remove and requires
+    /// `approvals_required_for_config` to be provided.
+    pub replace_config_approvers: Option<Vec<(SlotId<Signer>, Signer)>>,
+    /// Minimum time a newly added address book entry has to wait before it
+    /// can be confirmed and used as a transfer destination. Zero disables
+    /// destination verification.
+    pub destination_verification_delay: Option<Duration>,
+    /// Operations moving at least this amount require DENY dispositions to
+    /// include a denial reason. Zero disables the requirement.
+    pub denial_reason_threshold: Option<u64>,
+    /// Approvals required for config ops initiated by the assistant. Zero
+    /// applies `approvals_required_for_config` to them as well.
+    pub approvals_required_for_assistant_config: Option<u8>,
+    /// Approvals required for address book and dapp book ops once dedicated
+    /// address book approvers are configured.
+    pub approvals_required_for_address_book: Option<u8>,
+    pub add_address_book_approvers: Vec<(SlotId<Signer>, Signer)>,
+    pub remove_address_book_approvers: Vec<(SlotId<Signer>, Signer)>,
+    pub add_default_transfer_approvers: Vec<(SlotId<Signer>, Signer)>,
+    pub remove_default_transfer_approvers: Vec<(SlotId<Signer>, Signer)>,
+    /// Signers any one of whom can deny an op outright, whether or not they
+    /// approve ops themselves.
+    pub add_veto_approvers: Vec<(SlotId<Signer>, Signer)>,
+    pub remove_veto_approvers: Vec<(SlotId<Signer>, Signer)>,
+    /// Approvals required for destructive config ops once dedicated
+    /// destructive config approvers are configured.
+    pub approvals_required_for_destructive_config: Option<u8>,
+    pub add_destructive_config_approvers: Vec<(SlotId<Signer>, Signer)>,
+    pub remove_destructive_config_approvers: Vec<(SlotId<Signer>, Signer)>,
 }
 
 impl WalletConfigPolicyUpdate {
@@ -977,12 +2904,55 @@ impl WalletConfigPolicyUpdate {
         let approval_timeout_for_config = read_optional_duration(&mut iter)?;
         let add_config_approvers = read_signers(&mut iter)?;
         let remove_config_approvers = read_signers(&mut iter)?;
+        let replace_config_approvers = read_optional_signers(&mut iter)?;
+        let destination_verification_delay = read_optional_duration(&mut iter)?;
+        let denial_reason_threshold = read_optional_u64(&mut iter)?;
+        let approvals_required_for_assistant_config = read_optional_u8(&mut iter)?;
+        let approvals_required_for_address_book = read_optional_u8(&mut iter)?;
+        let add_address_book_approvers = read_signers(&mut iter)?;
+        let remove_address_book_approvers = read_signers(&mut iter)?;
+        let add_default_transfer_approvers = read_signers(&mut iter)?;
+        let remove_default_transfer_approvers = read_signers(&mut iter)?;
+        // older clients send no veto approver updates
+        let (add_veto_approvers, remove_veto_approvers) = if iter.as_slice().is_empty() {
+            (Vec::new(), Vec::new())
+        } else {
+            (read_signers(&mut iter)?, read_signers(&mut iter)?)
+        };
+        // nor destructive config approver updates
+        let (
+            approvals_required_for_destructive_config,
+            add_destructive_config_approvers,
+            remove_destructive_config_approvers,
+        ) = if iter.as_slice().is_empty() {
+            (None, Vec::new(), Vec::new())
+        } else {
+            (
+                read_optional_u8(&mut iter)?,
+                read_signers(&mut iter)?,
+                read_signers(&mut iter)?,
+            )
+        };
 
         Ok(WalletConfigPolicyUpdate {
             approvals_required_for_config,
             approval_timeout_for_config,
             add_config_approvers,
             remove_config_approvers,
+            replace_config_approvers,
+            destination_verification_delay,
+            denial_reason_threshold,
+            approvals_required_for_assistant_config,
+            approvals_required_for_address_book,
+            add_address_book_approvers,
+            remove_address_book_approvers,
+            add_default_transfer_approvers,
+            remove_default_transfer_approvers,
+            add_veto_approvers,
+            remove_veto_approvers,
+            approvals_required_for_destructive_config,
+            add_destructive_config_approvers,
+            remove_destructive_config_approvers,
         })
     }
 
@@ -991,6 +2961,87 @@ impl WalletConfigPolicyUpdate {
         append_optional_duration(&self.approval_timeout_for_config, dst);
         append_signers(&self.add_config_approvers, dst);
         append_signers(&self.remove_config_approvers, dst);
+        append_optional_signers(&self.replace_config_approvers, dst);
+        append_optional_duration(&self.destination_verification_delay, dst);
+        append_optional_u64(&self.denial_reason_threshold, dst);
+        append_optional_u8(&self.approvals_required_for_assistant_config, dst);
+        append_optional_u8(&self.approvals_required_for_address_book, dst);
+        append_signers(&self.add_address_book_approvers, dst);
+        append_signers(&self.remove_address_book_approvers, dst);
+        append_signers(&self.add_default_transfer_approvers, dst);
+        append_signers(&self.remove_default_transfer_approvers, dst);
+        // left out when untouched, so such updates pack and hash as they used to
+        let destructive_config_untouched = self.approvals_required_for_destructive_config.is_none()
+            && self.add_destructive_config_approvers.is_empty()
+            && self.remove_destructive_config_approvers.is_empty();
+        if !self.add_veto_approvers.is_empty()
+            || !self.remove_veto_approvers.is_empty()
+            || !destructive_config_untouched
+        {
+            append_signers(&self.add_veto_approvers, dst);
+            append_signers(&self.remove_veto_approvers, dst);
+        }
+        if !destructive_config_untouched {
+            append_optional_u8(&self.approvals_required_for_destructive_config, dst);
+            append_signers(&self.add_destructive_config_approvers, dst);
+            append_signers(&self.remove_destructive_config_approvers, dst);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ProgramConfigUpdate {
+    pub min_approval_timeout: Option<Duration>,
+    pub max_approval_timeout: Option<Duration>,
+    pub fee_collector: Option<Pubkey>,
+    /// While paused, the program rejects every instruction except for program
+    /// config updates and the read-only reports.
+    pub paused: Option<BooleanSetting>,
+    pub max_signers: Option<u8>,
+    pub max_balance_accounts: Option<u8>,
+    pub add_migration_programs: Vec<Pubkey>,
+    pub remove_migration_programs: Vec<Pubkey>,
+}
+
+impl ProgramConfigUpdate {
+    fn unpack(bytes: &[u8]) -> Result<ProgramConfigUpdate, ProgramError> {
+        let mut iter = bytes.iter();
+        let min_approval_timeout = read_optional_duration(&mut iter)?;
+        let max_approval_timeout = read_optional_duration(&mut iter)?;
+        let fee_collector = read_optional_pubkey(&mut iter)?;
+        let paused = unpack_option::<BooleanSetting>(&mut iter)?;
+        let max_signers = read_optional_u8(&mut iter)?;
+        let max_balance_accounts = read_optional_u8(&mut iter)?;
+        // older clients send no migration program updates
+        let (add_migration_programs, remove_migration_programs) = if iter.as_slice().is_empty() {
+            (Vec::new(), Vec::new())
+        } else {
+            (read_pubkeys(&mut iter)?, read_pubkeys(&mut iter)?)
+        };
+
+        Ok(ProgramConfigUpdate {
+            min_approval_timeout,
+            max_approval_timeout,
+            fee_collector,
+            paused,
+            max_signers,
+            max_balance_accounts,
+            add_migration_programs,
+            remove_migration_programs,
+        })
+    }
+
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        append_optional_duration(&self.min_approval_timeout, dst);
+        append_optional_duration(&self.max_approval_timeout, dst);
+        append_optional_pubkey(&self.fee_collector, dst);
+        pack_option(self.paused.as_ref(), dst);
+        append_optional_u8(&self.max_signers, dst);
+        append_optional_u8(&self.max_balance_accounts, dst);
+        if !self.add_migration_programs.is_empty() || !self.remove_migration_programs.is_empty() {
+            append_pubkeys(&self.add_migration_programs, dst);
+            append_pubkeys(&self.remove_migration_programs, dst);
+        }
     }
 }
 
@@ -1050,10 +3101,30 @@ impl BalanceAccountCreation {
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct BalanceAccountPolicyUpdate {
+    /// Fields that are `None` are left unchanged, so unrelated updates to the
+    /// same balance account don't have to resend the full policy.
     pub approvals_required_for_transfer: Option<u8>,
     pub approval_timeout_for_transfer: Option<Duration>,
-    pub add_transfer_approvers: Vec<(SlotId<Signer>, Signer)>,
-    pub remove_transfer_approvers: Vec<(SlotId<Signer>, Signer)>,
+    pub add_transfer_approvers: Option<Vec<(SlotId<Signer>, Signer)>>,
+    pub remove_transfer_approvers: Option<Vec<(SlotId<Signer>, Signer)>>,
+    /// When On, transfers are approved by the wallet's default transfer
+    /// approvers instead of the account's own transfer approvers.
+    pub use_config_approvers: Option<BooleanSetting>,
+    /// When On, only SPL tokens of the allowed token mints can be transferred.
+    pub token_mint_allowlist_enabled: Option<BooleanSetting>,
+    pub add_allowed_token_mints: Option<Vec<(SlotId<TokenMint>, TokenMint)>>,
+    pub remove_allowed_token_mints: Option<Vec<(SlotId<TokenMint>, TokenMint)>>,
+    /// Cap on the total amount of transfers initiated but not yet finalized.
+    /// Zero removes the cap.
+    pub max_outstanding_transfer_value: Option<u64>,
+    /// Most lamports a transfer's finalizer can be reimbursed for the
+    /// transaction fee. Zero turns reimbursement off.
+    pub max_finalize_fee_reimbursement: Option<u64>,
+    /// Replaces the balance account's policy rules. An empty list removes them.
+    pub policy_rules: Option<Vec<PolicyRule>>,
+    /// Replaces the balance account's large transfer cooldown. A zero period
+    /// removes it.
+    pub large_transfer_cooldown: Option<LargeTransferCooldown>,
 }
 
 impl BalanceAccountPolicyUpdate {
@@ -1064,22 +3135,99 @@ impl BalanceAccountPolicyUpdate {
         let mut iter = bytes.iter();
         let approvals_required_for_transfer = read_optional_u8(&mut iter)?;
         let approval_timeout_for_transfer = read_optional_duration(&mut iter)?;
-        let add_approvers = read_signers(&mut iter)?;
-        let remove_approvers = read_signers(&mut iter)?;
+        let add_approvers = read_optional_signers(&mut iter)?;
+        let remove_approvers = read_optional_signers(&mut iter)?;
+        let use_config_approvers = unpack_option::<BooleanSetting>(&mut iter)?;
+        let token_mint_allowlist_enabled = unpack_option::<BooleanSetting>(&mut iter)?;
+        let add_allowed_token_mints = read_optional_token_mints(&mut iter)?;
+        let remove_allowed_token_mints = read_optional_token_mints(&mut iter)?;
+        let max_outstanding_transfer_value = read_optional_u64(&mut iter)?;
+        let max_finalize_fee_reimbursement = read_optional_u64(&mut iter)?;
+        let policy_rules = read_optional_policy_rules(&mut iter)?;
+        // updates packed before large transfer cooldowns existed end here
+        let large_transfer_cooldown = if iter.as_slice().is_empty() {
+            None
+        } else {
+            read_optional_large_transfer_cooldown(&mut iter)?
+        };
 
         Ok(BalanceAccountPolicyUpdate {
             approvals_required_for_transfer,
             approval_timeout_for_transfer,
             add_transfer_approvers: add_approvers,
             remove_transfer_approvers: remove_approvers,
+            use_config_approvers,
+            token_mint_allowlist_enabled,
+            add_allowed_token_mints,
+            remove_allowed_token_mints,
+            max_outstanding_transfer_value,
+            max_finalize_fee_reimbursement,
+            policy_rules,
+            large_transfer_cooldown,
         })
     }
 
     pub fn pack(&self, dst: &mut Vec<u8>) {
         append_optional_u8(&self.approvals_required_for_transfer, dst);
         append_optional_duration(&self.approval_timeout_for_transfer, dst);
-        append_signers(&self.add_transfer_approvers, dst);
-        append_signers(&self.remove_transfer_approvers, dst);
+        append_optional_signers(&self.add_transfer_approvers, dst);
+        append_optional_signers(&self.remove_transfer_approvers, dst);
+        pack_option(self.use_config_approvers.as_ref(), dst);
+        pack_option(self.token_mint_allowlist_enabled.as_ref(), dst);
+        append_optional_token_mints(&self.add_allowed_token_mints, dst);
+        append_optional_token_mints(&self.remove_allowed_token_mints, dst);
+        append_optional_u64(&self.max_outstanding_transfer_value, dst);
+        append_optional_u64(&self.max_finalize_fee_reimbursement, dst);
+        append_optional_policy_rules(&self.policy_rules, dst);
+        if self.large_transfer_cooldown.is_some() {
+            append_optional_large_transfer_cooldown(&self.large_transfer_cooldown, dst);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ApprovalDispositionUpdate {
+    pub disposition: ApprovalDisposition,
+    pub params_hash: Hash,
+    pub denial_reason: DenialReason,
+    /// The hash of an off-chain justification or comment, recorded with the
+    /// disposition as with `SetApprovalDisposition`.
+    pub reason_hash: Option<Hash>,
+}
+
+impl ApprovalDispositionUpdate {
+    // the reason hash always takes its space, so that updates can be
+    // unpacked in fixed-size chunks
+    const LEN: usize = 1 + 32 + 1 + 1 + 32;
+
+    fn unpack(bytes: &[u8]) -> Result<ApprovalDispositionUpdate, ProgramError> {
+        let mut iter = bytes.iter();
+        let disposition = *read_u8(&mut iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let params_hash =
+            *read_fixed_size_array::<32>(&mut iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let denial_reason = *read_u8(&mut iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let has_reason_hash = *read_u8(&mut iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let reason_hash =
+            *read_fixed_size_array::<32>(&mut iter).ok_or(ProgramError::InvalidInstructionData)?;
+
+        Ok(ApprovalDispositionUpdate {
+            disposition: ApprovalDisposition::from_u8(disposition),
+            params_hash: Hash::new_from_array(params_hash),
+            denial_reason: DenialReason::from_u8(denial_reason),
+            reason_hash: match has_reason_hash {
+                0 => None,
+                1 => Some(Hash::new_from_array(reason_hash)),
+                _ => return Err(ProgramError::InvalidInstructionData),
+            },
+        })
+    }
+
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        dst.push(self.disposition.to_u8());
+        dst.extend_from_slice(self.params_hash.as_ref());
+        dst.push(self.denial_reason.to_u8());
+        dst.push(self.reason_hash.is_some() as u8);
+        dst.extend_from_slice(self.reason_hash.unwrap_or_default().as_ref());
     }
 }
 
@@ -1087,6 +3235,18 @@ impl BalanceAccountPolicyUpdate {
 pub struct DAppBookUpdate {
     pub add_dapps: Vec<(SlotId<DAppBookEntry>, DAppBookEntry)>,
     pub remove_dapps: Vec<(SlotId<DAppBookEntry>, DAppBookEntry)>,
+    /// Caps on the value each dapp's transactions can move out of a balance
+    /// account per period. A limit goes in the slot of the dapp it applies to.
+    pub add_value_limits: Vec<(SlotId<DAppValueLimit>, DAppValueLimit)>,
+    pub remove_value_limits: Vec<(SlotId<DAppValueLimit>, DAppValueLimit)>,
+    /// Programs each dapp's instructions may hand it as accounts. These go
+    /// in the slot of the dapp they apply to.
+    pub add_inner_programs: Vec<(SlotId<DAppInnerPrograms>, DAppInnerPrograms)>,
+    pub remove_inner_programs: Vec<(SlotId<DAppInnerPrograms>, DAppInnerPrograms)>,
+    /// Approvers and quorum of each dapp's transactions, overriding those of
+    /// the balance account. These go in the slot of the dapp they apply to.
+    pub add_approval_policies: Vec<(SlotId<DAppApprovalPolicy>, DAppApprovalPolicy)>,
+    pub remove_approval_policies: Vec<(SlotId<DAppApprovalPolicy>, DAppApprovalPolicy)>,
 }
 
 impl DAppBookUpdate {
@@ -1097,111 +3257,699 @@ impl DAppBookUpdate {
         let mut iter = bytes.iter();
         let add_dapps = read_address_book_entries(&mut iter)?;
         let remove_dapps = read_address_book_entries(&mut iter)?;
+        // older clients send no value limits
+        let (add_value_limits, remove_value_limits) = if iter.as_slice().is_empty() {
+            (Vec::new(), Vec::new())
+        } else {
+            (
+                read_address_book_entries(&mut iter)?,
+                read_address_book_entries(&mut iter)?,
+            )
+        };
+        // nor inner programs
+        let (add_inner_programs, remove_inner_programs) = if iter.as_slice().is_empty() {
+            (Vec::new(), Vec::new())
+        } else {
+            (
+                read_address_book_entries(&mut iter)?,
+                read_address_book_entries(&mut iter)?,
+            )
+        };
+        // nor approval policies
+        let (add_approval_policies, remove_approval_policies) = if iter.as_slice().is_empty() {
+            (Vec::new(), Vec::new())
+        } else {
+            (
+                read_address_book_entries(&mut iter)?,
+                read_address_book_entries(&mut iter)?,
+            )
+        };
 
         Ok(DAppBookUpdate {
             add_dapps,
             remove_dapps,
+            add_value_limits,
+            remove_value_limits,
+            add_inner_programs,
+            remove_inner_programs,
+            add_approval_policies,
+            remove_approval_policies,
         })
     }
 
     pub fn pack(&self, dst: &mut Vec<u8>) {
         append_address_book_entries(&self.add_dapps, dst);
         append_address_book_entries(&self.remove_dapps, dst);
+        // left out when empty, so updates without limits hash as they used to
+        let has_approval_policies =
+            !self.add_approval_policies.is_empty() || !self.remove_approval_policies.is_empty();
+        let has_inner_programs = has_approval_policies
+            || !self.add_inner_programs.is_empty()
+            || !self.remove_inner_programs.is_empty();
+        if has_inner_programs
+            || !self.add_value_limits.is_empty()
+            || !self.remove_value_limits.is_empty()
+        {
+            append_address_book_entries(&self.add_value_limits, dst);
+            append_address_book_entries(&self.remove_value_limits, dst);
+        }
+        if has_inner_programs {
+            append_address_book_entries(&self.add_inner_programs, dst);
+            append_address_book_entries(&self.remove_inner_programs, dst);
+        }
+        if has_approval_policies {
+            append_address_book_entries(&self.add_approval_policies, dst);
+            append_address_book_entries(&self.remove_approval_policies, dst);
+        }
     }
 }
 
-fn read_signers(iter: &mut Iter<u8>) -> Result<Vec<(SlotId<Signer>, Signer)>, ProgramError> {
-    let signers_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
-    read_slice(iter, usize::from(signers_count) * (1 + Signer::LEN))
-        .ok_or(ProgramError::InvalidInstructionData)?
-        .chunks_exact(1 + Signer::LEN)
-        .map(|chunk| {
-            Signer::unpack_from_slice(&chunk[1..1 + Signer::LEN])
-                .map(|signer| (SlotId::new(usize::from(chunk[0])), signer))
-        })
-        .collect()
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DAppTemplatesUpdate {
+    pub add_templates: Vec<(SlotId<DAppTemplate>, DAppTemplate)>,
+    pub remove_templates: Vec<(SlotId<DAppTemplate>, DAppTemplate)>,
 }
 
-fn append_signers(signers: &Vec<(SlotId<Signer>, Signer)>, dst: &mut Vec<u8>) {
-    dst.push(signers.len() as u8);
-    for (slot_id, signer) in signers.iter() {
-        let mut buf = vec![0; 1 + Signer::LEN];
-        buf[0] = slot_id.value as u8;
-        signer.pack_into_slice(&mut buf[1..1 + Signer::LEN]);
-        dst.extend_from_slice(buf.as_slice());
-    }
-}
+impl DAppTemplatesUpdate {
+    fn unpack(bytes: &[u8]) -> Result<DAppTemplatesUpdate, ProgramError> {
+        let mut iter = bytes.iter();
+        let add_templates = read_address_book_entries(&mut iter)?;
+        let remove_templates = read_address_book_entries(&mut iter)?;
 
-fn read_instructions(iter: &mut Iter<u8>) -> Result<Vec<Instruction>, ProgramError> {
-    let account_meta_count = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
-    let account_metas = (0..*account_meta_count)
-        .map(|_| read_account_meta(iter).unwrap())
-        .collect();
+        Ok(DAppTemplatesUpdate {
+            add_templates,
+            remove_templates,
+        })
+    }
 
-    let instruction_count = read_u16(iter).ok_or(ProgramError::InvalidInstructionData)?;
-    Ok((0..instruction_count)
-        .map(|_| read_instruction(iter, &account_metas).unwrap())
-        .collect())
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        append_address_book_entries(&self.add_templates, dst);
+        append_address_book_entries(&self.remove_templates, dst);
+    }
 }
 
-fn read_account_meta(iter: &mut Iter<u8>) -> Result<AccountMeta, ProgramError> {
-    let flags = *read_u8(iter)
-        .ok_or(ProgramError::InvalidInstructionData)
-        .unwrap();
-    let pubkey = Pubkey::new(
-        read_slice(iter, 32)
-            .ok_or(ProgramError::InvalidInstructionData)
-            .unwrap()
-            .try_into()
-            .ok()
-            .unwrap(),
-    );
-    Ok(AccountMeta {
-        is_writable: (flags & 1) == 1,
-        is_signer: (flags & 2) == 2,
-        pubkey,
-    })
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ApproverDelegationUpdate {
+    pub add_delegations: Vec<(SlotId<ApproverDelegation>, ApproverDelegation)>,
+    pub remove_delegations: Vec<(SlotId<ApproverDelegation>, ApproverDelegation)>,
 }
 
-fn read_instruction(
-    iter: &mut Iter<u8>,
-    account_metas: &Vec<AccountMeta>,
-) -> Result<Instruction, ProgramError> {
-    let program_id_index = *read_u8(iter)
-        .ok_or(ProgramError::InvalidInstructionData)
-        .unwrap();
-    let program_id = account_metas[program_id_index.as_usize()].pubkey;
-    let account_count = read_u16(iter).ok_or(ProgramError::InvalidInstructionData)?;
-    let accounts = (0..account_count)
-        .map(|_| {
-            let index = *read_u8(iter)
-                .ok_or(ProgramError::InvalidInstructionData)
-                .unwrap();
-            account_metas[index.as_usize()].clone()
+impl ApproverDelegationUpdate {
+    fn unpack(bytes: &[u8]) -> Result<ApproverDelegationUpdate, ProgramError> {
+        let mut iter = bytes.iter();
+        let add_delegations = read_approver_delegations(&mut iter)?;
+        let remove_delegations = read_approver_delegations(&mut iter)?;
+
+        Ok(ApproverDelegationUpdate {
+            add_delegations,
+            remove_delegations,
         })
-        .collect();
-    let data_len = read_u16(iter).ok_or(ProgramError::InvalidInstructionData)?;
-    let data = read_slice(iter, data_len.try_into().unwrap())
-        .ok_or(ProgramError::InvalidInstructionData)?
-        .to_vec();
-    Ok(Instruction {
-        program_id,
-        accounts,
-        data,
-    })
+    }
+
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        append_approver_delegations(&self.add_delegations, dst);
+        append_approver_delegations(&self.remove_delegations, dst);
+    }
 }
 
-fn index_of_account(account_metas: &Vec<AccountMeta>, pubkey: &Pubkey) -> u8 {
-    account_metas
-        .iter()
-        .position(|a| a.pubkey == *pubkey)
-        .unwrap()
-        .as_u8()
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GovernedParameterUpdate {
+    /// The parameters to set, or with `None`, to reset to their default.
+    pub values: Vec<(GovernedParameter, Option<u64>)>,
 }
 
-pub fn append_instruction(
-    instruction: &Instruction,
-    account_metas: &Vec<AccountMeta>,
+impl GovernedParameterUpdate {
+    fn unpack(bytes: &[u8]) -> Result<GovernedParameterUpdate, ProgramError> {
+        let mut iter = bytes.iter();
+        let count = *read_u8(&mut iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let mut values = Vec::with_capacity(usize::from(count));
+        for _ in 0..count {
+            let key = GovernedParameter::from_u8(
+                *read_u8(&mut iter).ok_or(ProgramError::InvalidInstructionData)?,
+            )?;
+            values.push((key, read_optional_u64(&mut iter)?));
+        }
+        Ok(GovernedParameterUpdate { values })
+    }
+
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        dst.push(self.values.len() as u8);
+        for (key, value) in self.values.iter() {
+            dst.push(key.to_u8());
+            append_optional_u64(value, dst);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SignersUpdate {
+    pub add_signers: Vec<(SlotId<Signer>, Signer)>,
+    pub remove_signers: Vec<(SlotId<Signer>, Signer)>,
+}
+
+impl SignersUpdate {
+    fn unpack(bytes: &[u8]) -> Result<SignersUpdate, ProgramError> {
+        let mut iter = bytes.iter();
+        let add_signers = read_signers(&mut iter)?;
+        let remove_signers = read_signers(&mut iter)?;
+
+        Ok(SignersUpdate {
+            add_signers,
+            remove_signers,
+        })
+    }
+
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        append_signers(&self.add_signers, dst);
+        append_signers(&self.remove_signers, dst);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ViewersUpdate {
+    pub add_viewers: Vec<(SlotId<Viewer>, Viewer)>,
+    pub remove_viewers: Vec<(SlotId<Viewer>, Viewer)>,
+}
+
+impl ViewersUpdate {
+    fn unpack(bytes: &[u8]) -> Result<ViewersUpdate, ProgramError> {
+        let mut iter = bytes.iter();
+        let add_viewers = read_viewers(&mut iter)?;
+        let remove_viewers = read_viewers(&mut iter)?;
+
+        Ok(ViewersUpdate {
+            add_viewers,
+            remove_viewers,
+        })
+    }
+
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        append_viewers(&self.add_viewers, dst);
+        append_viewers(&self.remove_viewers, dst);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SignerOrganizationsUpdate {
+    /// The organization to tag each signer slot with, or `None` to clear its tag.
+    pub organizations: Vec<(SlotId<Signer>, Option<u8>)>,
+}
+
+impl SignerOrganizationsUpdate {
+    fn unpack(bytes: &[u8]) -> Result<SignerOrganizationsUpdate, ProgramError> {
+        let mut iter = bytes.iter();
+        let count = *read_u8(&mut iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let mut organizations = Vec::with_capacity(usize::from(count));
+        for _ in 0..count {
+            let slot_id = SlotId::new(usize::from(
+                *read_u8(&mut iter).ok_or(ProgramError::InvalidInstructionData)?,
+            ));
+            organizations.push((slot_id, read_optional_u8(&mut iter)?));
+        }
+        Ok(SignerOrganizationsUpdate { organizations })
+    }
+
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        dst.push(self.organizations.len() as u8);
+        for (slot_id, organization) in self.organizations.iter() {
+            dst.push(slot_id.value as u8);
+            append_optional_u8(organization, dst);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SignerDirectoryUpdate {
+    pub add_signers: Vec<(SlotId<Signer>, Signer)>,
+    pub remove_signers: Vec<(SlotId<Signer>, Signer)>,
+}
+
+impl SignerDirectoryUpdate {
+    fn unpack(bytes: &[u8]) -> Result<SignerDirectoryUpdate, ProgramError> {
+        let mut iter = bytes.iter();
+        let add_signers = read_signers(&mut iter)?;
+        let remove_signers = read_signers(&mut iter)?;
+
+        Ok(SignerDirectoryUpdate {
+            add_signers,
+            remove_signers,
+        })
+    }
+
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        append_signers(&self.add_signers, dst);
+        append_signers(&self.remove_signers, dst);
+    }
+}
+
+/// Links a wallet to a signer directory, replacing the previous link. The
+/// given signer slots become directory-backed and must hold the same key in
+/// the wallet and the directory at the time of the update.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WalletSignerDirectoryUpdate {
+    pub signer_directory: Option<Pubkey>,
+    pub directory_signers: Vec<(SlotId<Signer>, Signer)>,
+}
+
+impl WalletSignerDirectoryUpdate {
+    fn unpack(bytes: &[u8]) -> Result<WalletSignerDirectoryUpdate, ProgramError> {
+        let mut iter = bytes.iter();
+        let signer_directory = read_optional_pubkey(&mut iter)?;
+        let directory_signers = read_signers(&mut iter)?;
+
+        Ok(WalletSignerDirectoryUpdate {
+            signer_directory,
+            directory_signers,
+        })
+    }
+
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        append_optional_pubkey(&self.signer_directory, dst);
+        append_signers(&self.directory_signers, dst);
+    }
+}
+
+/// What a combined transfer moves: lamports along with amounts of SPL tokens,
+/// each of a different token mint.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CombinedTransfer {
+    pub lamports: u64,
+    pub token_amounts: Vec<(Pubkey, u64)>,
+    /// The hash of the travel rule data collected for the transfer, which
+    /// transfers to destinations tagged as exchanges require. It is packed
+    /// last and only if there is one.
+    pub travel_rule_hash: Option<Hash>,
+}
+
+impl CombinedTransfer {
+    /// The most token amounts a combined transfer can move, bounded by the
+    /// accounts its finalize can pass.
+    pub const MAX_TOKEN_AMOUNTS: usize = 4;
+
+    /// The sum of the lamports and all token amounts.
+    pub fn total(&self) -> Result<u64, ProgramError> {
+        self.token_amounts
+            .iter()
+            .try_fold(self.lamports, |total, (_, amount)| {
+                total.checked_add(*amount)
+            })
+            .ok_or_else(|| WalletError::AmountOverflow.into())
+    }
+
+    fn unpack(iter: &mut Iter<u8>) -> Result<CombinedTransfer, ProgramError> {
+        let lamports = u64::from_le_bytes(
+            *read_fixed_size_array::<8>(iter).ok_or(ProgramError::InvalidInstructionData)?,
+        );
+        let count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let mut token_amounts = Vec::with_capacity(usize::from(count));
+        for _ in 0..count {
+            let token_mint = Pubkey::new_from_array(
+                *read_fixed_size_array::<32>(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            );
+            let amount = u64::from_le_bytes(
+                *read_fixed_size_array::<8>(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            );
+            token_amounts.push((token_mint, amount));
+        }
+        let travel_rule_hash = if iter.as_slice().is_empty() {
+            None
+        } else {
+            Some(Hash::new_from_array(
+                *read_fixed_size_array::<HASH_BYTES>(iter)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            ))
+        };
+        Ok(CombinedTransfer {
+            lamports,
+            token_amounts,
+            travel_rule_hash,
+        })
+    }
+
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        dst.extend_from_slice(&self.lamports.to_le_bytes());
+        dst.push(self.token_amounts.len() as u8);
+        for (token_mint, amount) in self.token_amounts.iter() {
+            dst.extend_from_slice(token_mint.as_ref());
+            dst.extend_from_slice(&amount.to_le_bytes());
+        }
+        if let Some(travel_rule_hash) = self.travel_rule_hash {
+            dst.extend_from_slice(travel_rule_hash.as_ref());
+        }
+    }
+}
+
+/// A wallet entry whose human-readable name is stored only as a hash.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum NamedEntry {
+    BalanceAccount(BalanceAccountGuidHash),
+    /// The address book entry with the given address.
+    AddressBookEntry(Pubkey),
+}
+
+impl NamedEntry {
+    fn unpack(iter: &mut Iter<u8>) -> Result<NamedEntry, ProgramError> {
+        let kind = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let bytes =
+            read_fixed_size_array::<32>(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        match kind {
+            0 => Ok(NamedEntry::BalanceAccount(BalanceAccountGuidHash::new(
+                bytes,
+            ))),
+            1 => Ok(NamedEntry::AddressBookEntry(Pubkey::new_from_array(*bytes))),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        match self {
+            NamedEntry::BalanceAccount(guid_hash) => {
+                dst.push(0);
+                dst.extend_from_slice(guid_hash.to_bytes());
+            }
+            NamedEntry::AddressBookEntry(address) => {
+                dst.push(1);
+                dst.extend_from_slice(address.as_ref());
+            }
+        }
+    }
+}
+
+fn read_approver_delegations(
+    iter: &mut Iter<u8>,
+) -> Result<Vec<(SlotId<ApproverDelegation>, ApproverDelegation)>, ProgramError> {
+    let delegations_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    read_slice(
+        iter,
+        usize::from(delegations_count) * (1 + ApproverDelegation::LEN),
+    )
+    .ok_or(ProgramError::InvalidInstructionData)?
+    .chunks_exact(1 + ApproverDelegation::LEN)
+    .map(|chunk| {
+        ApproverDelegation::unpack_from_slice(&chunk[1..1 + ApproverDelegation::LEN])
+            .map(|delegation| (SlotId::new(usize::from(chunk[0])), delegation))
+    })
+    .collect()
+}
+
+fn append_approver_delegations(
+    delegations: &Vec<(SlotId<ApproverDelegation>, ApproverDelegation)>,
+    dst: &mut Vec<u8>,
+) {
+    dst.push(delegations.len() as u8);
+    for (slot_id, delegation) in delegations.iter() {
+        let mut buf = vec![0; 1 + ApproverDelegation::LEN];
+        buf[0] = slot_id.value as u8;
+        delegation.pack_into_slice(&mut buf[1..1 + ApproverDelegation::LEN]);
+        dst.extend_from_slice(buf.as_slice());
+    }
+}
+
+fn read_signers(iter: &mut Iter<u8>) -> Result<Vec<(SlotId<Signer>, Signer)>, ProgramError> {
+    let signers_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    read_slice(iter, usize::from(signers_count) * (1 + Signer::LEN))
+        .ok_or(ProgramError::InvalidInstructionData)?
+        .chunks_exact(1 + Signer::LEN)
+        .map(|chunk| {
+            Signer::unpack_from_slice(&chunk[1..1 + Signer::LEN])
+                .map(|signer| (SlotId::new(usize::from(chunk[0])), signer))
+        })
+        .collect()
+}
+
+fn append_signers(signers: &Vec<(SlotId<Signer>, Signer)>, dst: &mut Vec<u8>) {
+    dst.push(signers.len() as u8);
+    for (slot_id, signer) in signers.iter() {
+        let mut buf = vec![0; 1 + Signer::LEN];
+        buf[0] = slot_id.value as u8;
+        signer.pack_into_slice(&mut buf[1..1 + Signer::LEN]);
+        dst.extend_from_slice(buf.as_slice());
+    }
+}
+
+fn read_viewers(iter: &mut Iter<u8>) -> Result<Vec<(SlotId<Viewer>, Viewer)>, ProgramError> {
+    let viewers_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    read_slice(iter, usize::from(viewers_count) * (1 + Viewer::LEN))
+        .ok_or(ProgramError::InvalidInstructionData)?
+        .chunks_exact(1 + Viewer::LEN)
+        .map(|chunk| {
+            Viewer::unpack_from_slice(&chunk[1..1 + Viewer::LEN])
+                .map(|viewer| (SlotId::new(usize::from(chunk[0])), viewer))
+        })
+        .collect()
+}
+
+fn append_viewers(viewers: &Vec<(SlotId<Viewer>, Viewer)>, dst: &mut Vec<u8>) {
+    dst.push(viewers.len() as u8);
+    for (slot_id, viewer) in viewers.iter() {
+        let mut buf = vec![0; 1 + Viewer::LEN];
+        buf[0] = slot_id.value as u8;
+        viewer.pack_into_slice(&mut buf[1..1 + Viewer::LEN]);
+        dst.extend_from_slice(buf.as_slice());
+    }
+}
+
+fn read_optional_signers(
+    iter: &mut Iter<u8>,
+) -> Result<Option<Vec<(SlotId<Signer>, Signer)>>, ProgramError> {
+    let has_value = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    Ok(if has_value == 0 {
+        None
+    } else {
+        Some(read_signers(iter)?)
+    })
+}
+
+fn append_optional_signers(
+    maybe_signers: &Option<Vec<(SlotId<Signer>, Signer)>>,
+    dst: &mut Vec<u8>,
+) {
+    if let Some(signers) = maybe_signers {
+        dst.push(1);
+        append_signers(signers, dst);
+    } else {
+        dst.push(0);
+    }
+}
+
+fn read_optional_token_mints(
+    iter: &mut Iter<u8>,
+) -> Result<Option<Vec<(SlotId<TokenMint>, TokenMint)>>, ProgramError> {
+    let has_value = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    if has_value == 0 {
+        return Ok(None);
+    }
+    let token_mints_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    read_slice(iter, usize::from(token_mints_count) * (1 + TokenMint::LEN))
+        .ok_or(ProgramError::InvalidInstructionData)?
+        .chunks_exact(1 + TokenMint::LEN)
+        .map(|chunk| {
+            TokenMint::unpack_from_slice(&chunk[1..1 + TokenMint::LEN])
+                .map(|token_mint| (SlotId::new(usize::from(chunk[0])), token_mint))
+        })
+        .collect::<Result<Vec<_>, ProgramError>>()
+        .map(Some)
+}
+
+fn append_optional_token_mints(
+    maybe_token_mints: &Option<Vec<(SlotId<TokenMint>, TokenMint)>>,
+    dst: &mut Vec<u8>,
+) {
+    if let Some(token_mints) = maybe_token_mints {
+        dst.push(1);
+        dst.push(token_mints.len() as u8);
+        for (slot_id, token_mint) in token_mints.iter() {
+            let mut buf = vec![0; 1 + TokenMint::LEN];
+            buf[0] = slot_id.value as u8;
+            token_mint.pack_into_slice(&mut buf[1..1 + TokenMint::LEN]);
+            dst.extend_from_slice(buf.as_slice());
+        }
+    } else {
+        dst.push(0);
+    }
+}
+
+fn read_optional_policy_rules(
+    iter: &mut Iter<u8>,
+) -> Result<Option<Vec<PolicyRule>>, ProgramError> {
+    let has_value = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    if has_value == 0 {
+        return Ok(None);
+    }
+    let rules_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    read_slice(iter, usize::from(rules_count) * PolicyRule::LEN)
+        .ok_or(ProgramError::InvalidInstructionData)?
+        .chunks_exact(PolicyRule::LEN)
+        .map(|chunk| {
+            PolicyRule::unpack_from_slice(chunk).map_err(|_| ProgramError::InvalidInstructionData)
+        })
+        .collect::<Result<Vec<_>, ProgramError>>()
+        .map(Some)
+}
+
+fn read_optional_large_transfer_cooldown(
+    iter: &mut Iter<u8>,
+) -> Result<Option<LargeTransferCooldown>, ProgramError> {
+    let has_value = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    if has_value == 0 {
+        return Ok(None);
+    }
+    read_slice(iter, LargeTransferCooldown::LEN)
+        .ok_or(ProgramError::InvalidInstructionData)
+        .and_then(LargeTransferCooldown::unpack_from_slice)
+        .map(Some)
+}
+
+fn append_optional_large_transfer_cooldown(
+    maybe_cooldown: &Option<LargeTransferCooldown>,
+    dst: &mut Vec<u8>,
+) {
+    if let Some(cooldown) = maybe_cooldown {
+        dst.push(1);
+        let mut buf = vec![0; LargeTransferCooldown::LEN];
+        cooldown.pack_into_slice(&mut buf);
+        dst.extend_from_slice(buf.as_slice());
+    } else {
+        dst.push(0);
+    }
+}
+
+fn append_optional_policy_rules(maybe_rules: &Option<Vec<PolicyRule>>, dst: &mut Vec<u8>) {
+    if let Some(rules) = maybe_rules {
+        dst.push(1);
+        dst.push(rules.len() as u8);
+        for rule in rules.iter() {
+            let mut buf = vec![0; PolicyRule::LEN];
+            rule.pack_into_slice(&mut buf);
+            dst.extend_from_slice(buf.as_slice());
+        }
+    } else {
+        dst.push(0);
+    }
+}
+
+fn read_pubkeys(iter: &mut Iter<u8>) -> Result<Vec<Pubkey>, ProgramError> {
+    let count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    Ok(read_slice(iter, usize::from(count) * 32)
+        .ok_or(ProgramError::InvalidInstructionData)?
+        .chunks_exact(32)
+        .map(Pubkey::new)
+        .collect())
+}
+
+fn append_pubkeys(pubkeys: &Vec<Pubkey>, dst: &mut Vec<u8>) {
+    dst.push(pubkeys.len() as u8);
+    for pubkey in pubkeys {
+        dst.extend_from_slice(pubkey.as_ref());
+    }
+}
+
+fn read_optional_pubkey(iter: &mut Iter<u8>) -> Result<Option<Pubkey>, ProgramError> {
+    let has_value = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    Ok(if has_value == 0 {
+        None
+    } else {
+        Some(Pubkey::new(
+            read_slice(iter, 32).ok_or(ProgramError::InvalidInstructionData)?,
+        ))
+    })
+}
+
+pub fn append_optional_pubkey(maybe_pubkey: &Option<Pubkey>, dst: &mut Vec<u8>) {
+    if let Some(pubkey) = maybe_pubkey {
+        dst.push(1);
+        dst.extend_from_slice(pubkey.as_ref());
+    } else {
+        dst.push(0);
+    }
+}
+
+fn read_instructions(iter: &mut Iter<u8>) -> Result<Vec<Instruction>, ProgramError> {
+    let account_meta_count = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    let account_metas = (0..*account_meta_count)
+        .map(|_| read_account_meta(iter))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let instruction_count = read_u16(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    (0..instruction_count)
+        .map(|_| read_instruction(iter, &account_metas))
+        .collect()
+}
+
+/// The counterpart of `read_instructions`: the distinct account metas of the
+/// instructions, followed by the instructions referring to them by index.
+fn append_instructions(instructions: &Vec<Instruction>, dst: &mut Vec<u8>) {
+    let account_metas = unique_account_metas(instructions, &Vec::new());
+    dst.put_u8(account_metas.len().as_u8());
+    for account_meta in account_metas.iter() {
+        let mut account_meta_buf = vec![0; 1 + Signer::LEN];
+        account_meta_buf[0] = 0;
+        if account_meta.is_signer {
+            account_meta_buf[0] |= 2;
+        }
+        if account_meta.is_writable {
+            account_meta_buf[0] |= 1;
+        }
+        Signer::new(account_meta.pubkey).pack_into_slice(&mut account_meta_buf[1..1 + Signer::LEN]);
+        dst.extend_from_slice(account_meta_buf.as_slice());
+    }
+    dst.put_u16_le(instructions.len() as u16);
+    for instruction in instructions.iter() {
+        append_instruction(instruction, &account_metas, dst);
+    }
+}
+
+fn read_account_meta(iter: &mut Iter<u8>) -> Result<AccountMeta, ProgramError> {
+    let flags = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    let pubkey = Pubkey::new(read_slice(iter, 32).ok_or(ProgramError::InvalidInstructionData)?);
+    Ok(AccountMeta {
+        is_writable: (flags & 1) == 1,
+        is_signer: (flags & 2) == 2,
+        pubkey,
+    })
+}
+
+fn read_indexed_account_meta<'a>(
+    iter: &mut Iter<u8>,
+    account_metas: &'a Vec<AccountMeta>,
+) -> Result<&'a AccountMeta, ProgramError> {
+    let index = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    account_metas
+        .get(index.as_usize())
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
+fn read_instruction(
+    iter: &mut Iter<u8>,
+    account_metas: &Vec<AccountMeta>,
+) -> Result<Instruction, ProgramError> {
+    let program_id = read_indexed_account_meta(iter, account_metas)?.pubkey;
+    let account_count = read_u16(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    let accounts = (0..account_count)
+        .map(|_| read_indexed_account_meta(iter, account_metas).map(AccountMeta::clone))
+        .collect::<Result<Vec<_>, _>>()?;
+    let data_len = read_u16(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    let data = read_slice(iter, data_len.as_usize())
+        .ok_or(ProgramError::InvalidInstructionData)?
+        .to_vec();
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+fn index_of_account(account_metas: &Vec<AccountMeta>, pubkey: &Pubkey) -> u8 {
+    account_metas
+        .iter()
+        .position(|a| a.pubkey == *pubkey)
+        .unwrap()
+        .as_u8()
+}
+
+pub fn append_instruction(
+    instruction: &Instruction,
+    account_metas: &Vec<AccountMeta>,
     dst: &mut Vec<u8>,
 ) {
     dst.put_u8(index_of_account(account_metas, &instruction.program_id));
@@ -1232,36 +3980,73 @@ pub fn append_instruction_expanded(instruction: &Instruction, dst: &mut Vec<u8>)
     dst.extend_from_slice(instruction.data.as_slice());
 }
 
-fn read_address_book_entries(
+fn read_address_book_entries<T: Pack>(
     iter: &mut Iter<u8>,
-) -> Result<Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>, ProgramError> {
+) -> Result<Vec<(SlotId<T>, T)>, ProgramError> {
     let entries_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
-    read_slice(
-        iter,
-        usize::from(entries_count) * (1 + AddressBookEntry::LEN),
-    )
-    .ok_or(ProgramError::InvalidInstructionData)?
-    .chunks_exact(1 + AddressBookEntry::LEN)
-    .map(|chunk| {
-        AddressBookEntry::unpack_from_slice(&chunk[1..1 + AddressBookEntry::LEN])
-            .map(|entry| (SlotId::new(usize::from(chunk[0])), entry))
-    })
-    .collect()
+    read_slice(iter, usize::from(entries_count) * (1 + T::LEN))
+        .ok_or(ProgramError::InvalidInstructionData)?
+        .chunks_exact(1 + T::LEN)
+        .map(|chunk| {
+            T::unpack_from_slice(&chunk[1..1 + T::LEN])
+                .map(|entry| (SlotId::new(usize::from(chunk[0])), entry))
+        })
+        .collect()
 }
 
-fn append_address_book_entries(
-    entries: &Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
-    dst: &mut Vec<u8>,
-) {
+pub fn append_address_book_entries<T: Pack>(entries: &Vec<(SlotId<T>, T)>, dst: &mut Vec<u8>) {
     dst.push(entries.len() as u8);
     for (slot_id, entry) in entries.iter() {
-        let mut buf = vec![0; 1 + AddressBookEntry::LEN];
+        let mut buf = vec![0; 1 + T::LEN];
         buf[0] = slot_id.value as u8;
-        entry.pack_into_slice(&mut buf[1..1 + AddressBookEntry::LEN]);
+        entry.pack_into_slice(&mut buf[1..1 + T::LEN]);
         dst.extend_from_slice(buf.as_slice());
     }
 }
 
+fn unpack_u32(bytes: &[u8]) -> Result<u32, ProgramError> {
+    bytes
+        .get(..4)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u32::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
+fn unpack_approval_disposition(
+    bytes: &[u8],
+) -> Result<(ApprovalDisposition, Hash, DenialReason, Option<Hash>), ProgramError> {
+    let (disposition, rest) = bytes
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    Ok((
+        ApprovalDisposition::from_u8(*disposition),
+        Hash::new_from_array(
+            rest.get(0..32)
+                .and_then(|slice| slice.try_into().ok())
+                .ok_or(ProgramError::InvalidInstructionData)?,
+        ),
+        // older clients don't send a denial reason
+        rest.get(32)
+            .map(|reason| DenialReason::from_u8(*reason))
+            .unwrap_or(DenialReason::None),
+        // nor a reason hash
+        match rest.get(33..) {
+            None | Some([]) => None,
+            Some(bytes) => Some(Hash::new_from_array(
+                bytes
+                    .try_into()
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            )),
+        },
+    ))
+}
+
+fn unpack_update_hash(bytes: &[u8]) -> Result<Hash, ProgramError> {
+    read_fixed_size_array::<32>(&mut bytes.iter())
+        .map(|bytes| Hash::new_from_array(*bytes))
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
 fn unpack_account_guid_hash(bytes: &[u8]) -> Result<BalanceAccountGuidHash, ProgramError> {
     bytes
         .get(..32)
@@ -1274,6 +4059,152 @@ fn unpack_account_guid_hash(bytes: &[u8]) -> Result<BalanceAccountGuidHash, Prog
         .ok_or(ProgramError::InvalidInstructionData)
 }
 
+/// Reads the optional approval timeout override at the given offset; older clients
+/// omit it entirely, in which case there is no override.
+fn unpack_approval_timeout_override(
+    bytes: &[u8],
+    offset: usize,
+) -> Result<Option<Duration>, ProgramError> {
+    match bytes.get(offset..) {
+        None | Some([]) => Ok(None),
+        Some(rest) if rest.len() < 9 => Err(ProgramError::InvalidInstructionData),
+        Some(rest) => read_optional_duration(&mut rest.iter()),
+    }
+}
+
+/// Reads the optional transfer memo hash at the given offset; older clients
+/// send nothing there, which is the same as no memo.
+fn unpack_optional_memo_hash(bytes: &[u8], offset: usize) -> Result<Option<Hash>, ProgramError> {
+    match bytes.get(offset..) {
+        None | Some([]) | Some([0, ..]) => Ok(None),
+        Some([1, rest @ ..]) => Ok(Some(Hash::new_from_array(
+            rest.get(..32)
+                .and_then(|slice| slice.try_into().ok())
+                .ok_or(ProgramError::InvalidInstructionData)?,
+        ))),
+        Some(_) => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn append_optional_memo_hash(memo_hash: &Option<Hash>, dst: &mut Vec<u8>) {
+    if let Some(memo_hash) = memo_hash {
+        dst.push(1);
+        dst.extend_from_slice(memo_hash.as_ref());
+    } else {
+        dst.push(0);
+    }
+}
+
+/// Packs the SOL and SPL transfer settings of an account settings update. They
+/// are left out when neither is set, so updates that don't touch them pack
+/// and hash as they used to.
+pub(crate) fn pack_transfer_settings(
+    sol_transfers_enabled: &Option<BooleanSetting>,
+    spl_transfers_enabled: &Option<BooleanSetting>,
+    dst: &mut Vec<u8>,
+) {
+    if sol_transfers_enabled.is_some() || spl_transfers_enabled.is_some() {
+        pack_option(sol_transfers_enabled.as_ref(), dst);
+        pack_option(spl_transfers_enabled.as_ref(), dst);
+    }
+}
+
+fn unpack_transfer_settings(
+    iter: &mut Iter<u8>,
+) -> Result<(Option<BooleanSetting>, Option<BooleanSetting>), ProgramError> {
+    // older clients send no transfer settings
+    if iter.as_slice().is_empty() {
+        return Ok((None, None));
+    }
+    Ok((
+        unpack_option::<BooleanSetting>(iter)?,
+        unpack_option::<BooleanSetting>(iter)?,
+    ))
+}
+
+/// Reads the optional destination token account rent payer at the given
+/// offset; older clients send nothing there, which is the same as none.
+fn unpack_optional_ata_rent_payer(
+    bytes: &[u8],
+    offset: usize,
+) -> Result<Option<AtaRentPayer>, ProgramError> {
+    match bytes.get(offset..) {
+        None | Some([]) | Some([0, ..]) => Ok(None),
+        Some([1, payer, ..]) => AtaRentPayer::from_u8(*payer).map(Some),
+        Some(_) => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn append_optional_ata_rent_payer(ata_rent_payer: &Option<AtaRentPayer>, dst: &mut Vec<u8>) {
+    if let Some(ata_rent_payer) = ata_rent_payer {
+        dst.push(1);
+        dst.push(ata_rent_payer.to_u8());
+    } else {
+        dst.push(0);
+    }
+}
+
+/// Reads a transfer's amount spec from the remaining bytes; older clients
+/// send nothing, which is an exact amount.
+fn unpack_amount_spec(bytes: &[u8]) -> Result<AmountSpec, ProgramError> {
+    match bytes {
+        [] => Ok(AmountSpec::Exact),
+        [amount_spec] => AmountSpec::from_u8(*amount_spec),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+/// Appends a transfer's amount spec, which is left out for exact amounts.
+fn append_amount_spec(amount_spec: &AmountSpec, dst: &mut Vec<u8>) {
+    if *amount_spec != AmountSpec::Exact {
+        dst.push(amount_spec.to_u8());
+    }
+}
+
+/// Appends a transfer's amount spec followed by its travel rule hash. The
+/// amount spec is always sent along with a travel rule hash, and both are left
+/// out as before when there is neither.
+fn append_amount_spec_and_travel_rule_hash(
+    amount_spec: &AmountSpec,
+    travel_rule_hash: &Option<Hash>,
+    dst: &mut Vec<u8>,
+) {
+    match travel_rule_hash {
+        Some(travel_rule_hash) => {
+            dst.push(amount_spec.to_u8());
+            dst.extend_from_slice(travel_rule_hash.as_ref());
+        }
+        None => append_amount_spec(amount_spec, dst),
+    }
+}
+
+fn append_optional_travel_rule_hash(travel_rule_hash: &Option<Hash>, dst: &mut Vec<u8>) {
+    if let Some(travel_rule_hash) = travel_rule_hash {
+        dst.extend_from_slice(travel_rule_hash.as_ref());
+    }
+}
+
+/// Unpacks a travel rule hash sent last, which is either there whole or not at all.
+fn unpack_optional_travel_rule_hash(bytes: &[u8]) -> Result<Option<Hash>, ProgramError> {
+    match bytes.len() {
+        0 => Ok(None),
+        HASH_BYTES => Ok(Some(Hash::new(bytes))),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn unpack_amount_spec_and_travel_rule_hash(
+    bytes: &[u8],
+) -> Result<(AmountSpec, Option<Hash>), ProgramError> {
+    match bytes {
+        [amount_spec, travel_rule_hash @ ..] if travel_rule_hash.len() == HASH_BYTES => Ok((
+            AmountSpec::from_u8(*amount_spec)?,
+            Some(Hash::new(travel_rule_hash)),
+        )),
+        _ => Ok((unpack_amount_spec(bytes)?, None)),
+    }
+}
+
 fn unpack_account_name_hash(bytes: &[u8]) -> Result<BalanceAccountNameHash, ProgramError> {
     bytes
         .get(..32)
@@ -1306,3 +4237,783 @@ fn read_balance_account_whitelist_updates(
     }
     Ok(updates)
 }
+
+#[test]
+fn test_unpack_rejects_out_of_range_durations() {
+    use crate::serialization_utils::MAX_DURATION;
+
+    let too_long = MAX_DURATION + Duration::from_secs(1);
+    let guid_hash = BalanceAccountGuidHash::new(&[1; 32]);
+    let wallet_config_update = WalletConfigPolicyUpdate {
+        approvals_required_for_config: None,
+        approval_timeout_for_config: None,
+        add_config_approvers: vec![],
+        remove_config_approvers: vec![],
+        replace_config_approvers: None,
+        destination_verification_delay: None,
+        denial_reason_threshold: None,
+        approvals_required_for_assistant_config: None,
+        approvals_required_for_address_book: None,
+        add_address_book_approvers: vec![],
+        remove_address_book_approvers: vec![],
+        add_default_transfer_approvers: vec![],
+        remove_default_transfer_approvers: vec![],
+        add_veto_approvers: vec![],
+        remove_veto_approvers: vec![],
+        approvals_required_for_destructive_config: None,
+        add_destructive_config_approvers: vec![],
+        remove_destructive_config_approvers: vec![],
+    };
+    let program_config_update = ProgramConfigUpdate {
+        min_approval_timeout: None,
+        max_approval_timeout: None,
+        fee_collector: None,
+        paused: None,
+        max_signers: None,
+        max_balance_accounts: None,
+        add_migration_programs: vec![],
+        remove_migration_programs: vec![],
+    };
+    let creation_params = |duration| BalanceAccountCreation {
+        slot_id: SlotId::new(0),
+        name_hash: BalanceAccountNameHash::new(&[2; 32]),
+        approvals_required_for_transfer: 1,
+        approval_timeout_for_transfer: duration,
+        transfer_approvers: vec![],
+        whitelist_enabled: BooleanSetting::Off,
+        dapps_enabled: BooleanSetting::Off,
+        address_book_slot_id: SlotId::new(0),
+    };
+
+    let instructions: Vec<Box<dyn Fn(Duration) -> ProgramInstruction>> = vec![
+        Box::new(|duration| ProgramInstruction::InitWallet {
+            initial_config: InitialWalletConfig {
+                approvals_required_for_config: 1,
+                approval_timeout_for_config: duration,
+                signers: vec![],
+                config_approvers: vec![],
+            },
+        }),
+        Box::new(|duration| ProgramInstruction::InitBalanceAccountCreation {
+            account_guid_hash: guid_hash,
+            creation_params: creation_params(duration),
+        }),
+        Box::new(
+            |duration| ProgramInstruction::FinalizeBalanceAccountCreation {
+                account_guid_hash: guid_hash,
+                creation_params: creation_params(duration),
+            },
+        ),
+        Box::new(|duration| ProgramInstruction::InitTransfer {
+            account_guid_hash: guid_hash,
+            amount: 1,
+            destination_name_hash: AddressBookEntryNameHash::new(&[3; 32]),
+            approval_timeout: Some(duration),
+            memo_hash: None,
+            ata_rent_payer: None,
+            amount_spec: AmountSpec::Exact,
+            travel_rule_hash: None,
+        }),
+        Box::new(|duration| ProgramInstruction::InitWrapUnwrap {
+            account_guid_hash: guid_hash,
+            amount: 1,
+            direction: WrapDirection::WRAP,
+            approval_timeout: Some(duration),
+            destination: None,
+            travel_rule_hash: None,
+        }),
+        Box::new(|duration| ProgramInstruction::InitInternalTransfer {
+            source_account_guid_hash: guid_hash,
+            destination_account_guid_hash: BalanceAccountGuidHash::new(&[5; 32]),
+            amount: 1,
+            approval_timeout: Some(duration),
+        }),
+        Box::new(
+            |duration| ProgramInstruction::InitWalletConfigPolicyUpdate {
+                update: WalletConfigPolicyUpdate {
+                    approval_timeout_for_config: Some(duration),
+                    ..wallet_config_update.clone()
+                },
+            },
+        ),
+        Box::new(
+            |duration| ProgramInstruction::InitWalletConfigPolicyUpdate {
+                update: WalletConfigPolicyUpdate {
+                    destination_verification_delay: Some(duration),
+                    ..wallet_config_update.clone()
+                },
+            },
+        ),
+        Box::new(
+            |duration| ProgramInstruction::InitBalanceAccountPolicyUpdate {
+                account_guid_hash: guid_hash,
+                update: BalanceAccountPolicyUpdate {
+                    approvals_required_for_transfer: None,
+                    approval_timeout_for_transfer: Some(duration),
+                    add_transfer_approvers: None,
+                    remove_transfer_approvers: None,
+                    use_config_approvers: None,
+                    token_mint_allowlist_enabled: None,
+                    add_allowed_token_mints: None,
+                    remove_allowed_token_mints: None,
+                    max_outstanding_transfer_value: None,
+                    max_finalize_fee_reimbursement: None,
+                    policy_rules: None,
+                    large_transfer_cooldown: None,
+                },
+            },
+        ),
+        Box::new(|duration| ProgramInstruction::UpdateProgramConfig {
+            update: ProgramConfigUpdate {
+                min_approval_timeout: Some(duration),
+                ..program_config_update.clone()
+            },
+        }),
+        Box::new(|duration| ProgramInstruction::UpdateProgramConfig {
+            update: ProgramConfigUpdate {
+                max_approval_timeout: Some(duration),
+                ..program_config_update.clone()
+            },
+        }),
+        Box::new(|duration| ProgramInstruction::RenewMultisigOp {
+            params_hash: Hash::new_from_array([4; 32]),
+            extension: duration,
+        }),
+    ];
+
+    for instruction in instructions.iter() {
+        assert!(ProgramInstruction::unpack(&instruction(MAX_DURATION).pack()).is_ok());
+        assert_eq!(
+            ProgramInstruction::unpack(&instruction(too_long).pack()).unwrap_err(),
+            ProgramError::InvalidInstructionData
+        );
+        assert_eq!(
+            ProgramInstruction::unpack(&instruction(Duration::from_secs(u64::MAX)).pack())
+                .unwrap_err(),
+            ProgramError::InvalidInstructionData
+        );
+    }
+}
+
+#[test]
+fn test_unpack_rejects_truncated_optional_duration() {
+    let bytes = [1, 60, 0, 0, 0];
+    assert_eq!(
+        read_optional_duration(&mut bytes.iter()).unwrap_err(),
+        ProgramError::InvalidInstructionData
+    );
+}
+
+#[test]
+fn test_transfer_memo_hash_is_optional() {
+    let memo_hash = Hash::new_from_array([5; 32]);
+    let instruction = ProgramInstruction::InitTransfer {
+        account_guid_hash: BalanceAccountGuidHash::new(&[1; 32]),
+        amount: 1,
+        destination_name_hash: AddressBookEntryNameHash::new(&[2; 32]),
+        approval_timeout: None,
+        memo_hash: Some(memo_hash),
+        ata_rent_payer: None,
+        amount_spec: AmountSpec::Exact,
+        travel_rule_hash: None,
+    };
+    let bytes = instruction.pack();
+    match ProgramInstruction::unpack(&bytes).unwrap() {
+        ProgramInstruction::InitTransfer {
+            memo_hash: Some(unpacked),
+            ..
+        } => assert_eq!(unpacked, memo_hash),
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+
+    // clients from before memos existed send no memo hash at all
+    match ProgramInstruction::unpack(&bytes[..1 + 81]).unwrap() {
+        ProgramInstruction::InitTransfer {
+            memo_hash: None, ..
+        } => {}
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+
+    // but a truncated memo hash is rejected
+    assert_eq!(
+        ProgramInstruction::unpack(&bytes[..1 + 81 + 32]).unwrap_err(),
+        ProgramError::InvalidInstructionData
+    );
+}
+
+#[test]
+fn test_finalize_transfer_fee_reimbursement_is_optional() {
+    let instruction = ProgramInstruction::FinalizeTransfer {
+        account_guid_hash: BalanceAccountGuidHash::new(&[1; 32]),
+        amount: 1,
+        token_mint: Pubkey::new_from_array([2; 32]),
+        memo_hash: Some(Hash::new_from_array([5; 32])),
+        fee_reimbursement: 5000,
+        ata_rent_payer: None,
+        token_program: None,
+        amount_spec: AmountSpec::Exact,
+        travel_rule_hash: None,
+    };
+    let bytes = instruction.pack();
+    match ProgramInstruction::unpack(&bytes).unwrap() {
+        ProgramInstruction::FinalizeTransfer {
+            memo_hash: Some(_),
+            fee_reimbursement: 5000,
+            ..
+        } => {}
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+
+    // clients from before reimbursements existed send nothing after the memo hash
+    let reimbursement_offset = 1 + 72 + 1 + 32;
+    match ProgramInstruction::unpack(&bytes[..reimbursement_offset]).unwrap() {
+        ProgramInstruction::FinalizeTransfer {
+            fee_reimbursement: 0,
+            ..
+        } => {}
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+
+    // but a truncated amount is rejected
+    assert_eq!(
+        ProgramInstruction::unpack(&bytes[..reimbursement_offset + 7]).unwrap_err(),
+        ProgramError::InvalidInstructionData
+    );
+}
+
+#[test]
+fn test_transfer_ata_rent_payer_is_optional() {
+    let instruction = ProgramInstruction::FinalizeTransfer {
+        account_guid_hash: BalanceAccountGuidHash::new(&[1; 32]),
+        amount: 1,
+        token_mint: Pubkey::new_from_array([2; 32]),
+        memo_hash: None,
+        fee_reimbursement: 0,
+        ata_rent_payer: Some(AtaRentPayer::Payer),
+        token_program: None,
+        amount_spec: AmountSpec::Exact,
+        travel_rule_hash: None,
+    };
+    let bytes = instruction.pack();
+    match ProgramInstruction::unpack(&bytes).unwrap() {
+        ProgramInstruction::FinalizeTransfer {
+            ata_rent_payer: Some(AtaRentPayer::Payer),
+            ..
+        } => {}
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+
+    // clients from before rent payers existed send nothing after the reimbursement
+    match ProgramInstruction::unpack(&bytes[..bytes.len() - 2]).unwrap() {
+        ProgramInstruction::FinalizeTransfer {
+            ata_rent_payer: None,
+            ..
+        } => {}
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+
+    // but a truncated or unknown rent payer is rejected
+    assert_eq!(
+        ProgramInstruction::unpack(&bytes[..bytes.len() - 1]).unwrap_err(),
+        ProgramError::InvalidInstructionData
+    );
+    let mut unknown_payer = bytes.clone();
+    *unknown_payer.last_mut().unwrap() = 3;
+    assert_eq!(
+        ProgramInstruction::unpack(&unknown_payer).unwrap_err(),
+        ProgramError::InvalidInstructionData
+    );
+}
+
+#[test]
+fn test_finalize_transfer_token_program_is_optional() {
+    let token_program = Pubkey::new_unique();
+    let instruction = ProgramInstruction::FinalizeTransfer {
+        account_guid_hash: BalanceAccountGuidHash::new(&[1; 32]),
+        amount: 1,
+        token_mint: Pubkey::new_from_array([2; 32]),
+        memo_hash: None,
+        fee_reimbursement: 0,
+        ata_rent_payer: None,
+        token_program: Some(token_program),
+        amount_spec: AmountSpec::Exact,
+        travel_rule_hash: None,
+    };
+    let bytes = instruction.pack();
+    match ProgramInstruction::unpack(&bytes).unwrap() {
+        ProgramInstruction::FinalizeTransfer {
+            token_program: Some(unpacked),
+            ..
+        } => assert_eq!(unpacked, token_program),
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+
+    // clients from before the token program was recorded send nothing after the rent payer
+    match ProgramInstruction::unpack(&bytes[..bytes.len() - 32]).unwrap() {
+        ProgramInstruction::FinalizeTransfer {
+            token_program: None,
+            ..
+        } => {}
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+
+    // but a truncated token program is rejected
+    assert_eq!(
+        ProgramInstruction::unpack(&bytes[..bytes.len() - 1]).unwrap_err(),
+        ProgramError::InvalidInstructionData
+    );
+}
+
+#[test]
+fn test_transfer_amount_spec_is_optional() {
+    let instruction = ProgramInstruction::InitTransfer {
+        account_guid_hash: BalanceAccountGuidHash::new(&[1; 32]),
+        amount: 1,
+        destination_name_hash: AddressBookEntryNameHash::new(&[2; 32]),
+        approval_timeout: None,
+        memo_hash: None,
+        ata_rent_payer: Some(AtaRentPayer::Source),
+        amount_spec: AmountSpec::Max,
+        travel_rule_hash: None,
+    };
+    let bytes = instruction.pack();
+    match ProgramInstruction::unpack(&bytes).unwrap() {
+        ProgramInstruction::InitTransfer {
+            ata_rent_payer: Some(AtaRentPayer::Source),
+            amount_spec: AmountSpec::Max,
+            ..
+        } => {}
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+
+    // clients from before max transfers existed send nothing after the rent payer
+    match ProgramInstruction::unpack(&bytes[..bytes.len() - 1]).unwrap() {
+        ProgramInstruction::InitTransfer {
+            amount_spec: AmountSpec::Exact,
+            ..
+        } => {}
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+
+    let token_program = Pubkey::new_unique();
+    for token_program in vec![None, Some(token_program)] {
+        let instruction = ProgramInstruction::FinalizeTransfer {
+            account_guid_hash: BalanceAccountGuidHash::new(&[1; 32]),
+            amount: 1,
+            token_mint: Pubkey::new_from_array([2; 32]),
+            memo_hash: None,
+            fee_reimbursement: 0,
+            ata_rent_payer: None,
+            token_program,
+            amount_spec: AmountSpec::Max,
+            travel_rule_hash: None,
+        };
+        let bytes = instruction.pack();
+        match ProgramInstruction::unpack(&bytes).unwrap() {
+            ProgramInstruction::FinalizeTransfer {
+                amount_spec: AmountSpec::Max,
+                token_program: unpacked,
+                ..
+            } => assert_eq!(unpacked, token_program),
+            unpacked => panic!("unexpected instruction {:?}", unpacked),
+        }
+        match ProgramInstruction::unpack(&bytes[..bytes.len() - 1]).unwrap() {
+            ProgramInstruction::FinalizeTransfer {
+                amount_spec: AmountSpec::Exact,
+                token_program: unpacked,
+                ..
+            } => assert_eq!(unpacked, token_program),
+            unpacked => panic!("unexpected instruction {:?}", unpacked),
+        }
+
+        // an unknown amount spec is rejected
+        let mut unknown_amount_spec = bytes.clone();
+        *unknown_amount_spec.last_mut().unwrap() = 2;
+        assert_eq!(
+            ProgramInstruction::unpack(&unknown_amount_spec).unwrap_err(),
+            ProgramError::InvalidInstructionData
+        );
+    }
+}
+
+#[test]
+fn test_transfer_travel_rule_hash_is_optional() {
+    let travel_rule_hash = Hash::new_from_array([7; 32]);
+    let instruction = ProgramInstruction::InitTransfer {
+        account_guid_hash: BalanceAccountGuidHash::new(&[1; 32]),
+        amount: 1,
+        destination_name_hash: AddressBookEntryNameHash::new(&[2; 32]),
+        approval_timeout: None,
+        memo_hash: None,
+        ata_rent_payer: None,
+        amount_spec: AmountSpec::Exact,
+        travel_rule_hash: Some(travel_rule_hash),
+    };
+    let bytes = instruction.pack();
+    match ProgramInstruction::unpack(&bytes).unwrap() {
+        ProgramInstruction::InitTransfer {
+            travel_rule_hash: Some(unpacked),
+            ..
+        } => assert_eq!(unpacked, travel_rule_hash),
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+    // the amount spec is sent along with the hash, and nothing is sent without it
+    match ProgramInstruction::unpack(&bytes[..bytes.len() - 32]).unwrap() {
+        ProgramInstruction::InitTransfer {
+            amount_spec: AmountSpec::Exact,
+            travel_rule_hash: None,
+            ..
+        } => {}
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+    assert_eq!(
+        ProgramInstruction::unpack(&bytes[..bytes.len() - 1]).unwrap_err(),
+        ProgramError::InvalidInstructionData
+    );
+
+    let token_program = Pubkey::new_unique();
+    for token_program in vec![None, Some(token_program)] {
+        for amount_spec in vec![AmountSpec::Exact, AmountSpec::Max] {
+            let instruction = ProgramInstruction::FinalizeTransfer {
+                account_guid_hash: BalanceAccountGuidHash::new(&[1; 32]),
+                amount: 1,
+                token_mint: Pubkey::new_from_array([2; 32]),
+                memo_hash: None,
+                fee_reimbursement: 0,
+                ata_rent_payer: None,
+                token_program,
+                amount_spec,
+                travel_rule_hash: Some(travel_rule_hash),
+            };
+            match ProgramInstruction::unpack(&instruction.pack()).unwrap() {
+                ProgramInstruction::FinalizeTransfer {
+                    token_program: unpacked_token_program,
+                    amount_spec: unpacked_amount_spec,
+                    travel_rule_hash: Some(unpacked),
+                    ..
+                } => {
+                    assert_eq!(unpacked_token_program, token_program);
+                    assert_eq!(unpacked_amount_spec, amount_spec);
+                    assert_eq!(unpacked, travel_rule_hash);
+                }
+                unpacked => panic!("unexpected instruction {:?}", unpacked),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_balance_account_policy_update_with_policy_rules() {
+    let update = BalanceAccountPolicyUpdate {
+        approvals_required_for_transfer: None,
+        approval_timeout_for_transfer: None,
+        add_transfer_approvers: None,
+        remove_transfer_approvers: None,
+        use_config_approvers: None,
+        token_mint_allowlist_enabled: None,
+        add_allowed_token_mints: None,
+        remove_allowed_token_mints: None,
+        max_outstanding_transfer_value: None,
+        max_finalize_fee_reimbursement: None,
+        policy_rules: Some(vec![
+            PolicyRule::AmountTier {
+                min_amount: 1000,
+                approvals_required: 2,
+            },
+            PolicyRule::TimeWindow {
+                start_secs: 8 * 3600,
+                end_secs: 18 * 3600,
+            },
+        ]),
+        large_transfer_cooldown: None,
+    };
+    let mut bytes = Vec::new();
+    update.pack(&mut bytes);
+    assert_eq!(BalanceAccountPolicyUpdate::unpack(&bytes).unwrap(), update);
+    assert_eq!(
+        BalanceAccountPolicyUpdate::unpack(&bytes[..bytes.len() - 1]).unwrap_err(),
+        ProgramError::InvalidInstructionData
+    );
+
+    // updates packed without a cooldown are unchanged, and one can be appended
+    let legacy_len = bytes.len();
+    let update = BalanceAccountPolicyUpdate {
+        large_transfer_cooldown: Some(LargeTransferCooldown {
+            min_amount: 1_000_000,
+            period: Duration::from_secs(3600),
+        }),
+        ..update
+    };
+    let mut bytes = Vec::new();
+    update.pack(&mut bytes);
+    assert_eq!(bytes.len(), legacy_len + 1 + LargeTransferCooldown::LEN);
+    assert_eq!(BalanceAccountPolicyUpdate::unpack(&bytes).unwrap(), update);
+    assert_eq!(
+        BalanceAccountPolicyUpdate::unpack(&bytes[..bytes.len() - 1]).unwrap_err(),
+        ProgramError::InvalidInstructionData
+    );
+}
+
+#[test]
+fn test_account_settings_transfer_settings_are_optional() {
+    let instruction = ProgramInstruction::InitAccountSettingsUpdate {
+        account_guid_hash: BalanceAccountGuidHash::new(&[1; 32]),
+        whitelist_enabled: Some(BooleanSetting::On),
+        dapps_enabled: None,
+        sol_transfers_enabled: None,
+        spl_transfers_enabled: None,
+    };
+    // updates that don't touch the transfer settings pack as they used to
+    assert_eq!(instruction.pack().len(), 1 + 32 + 2 + 1);
+
+    let instruction = ProgramInstruction::InitAccountSettingsUpdate {
+        account_guid_hash: BalanceAccountGuidHash::new(&[1; 32]),
+        whitelist_enabled: None,
+        dapps_enabled: None,
+        sol_transfers_enabled: Some(BooleanSetting::Off),
+        spl_transfers_enabled: None,
+    };
+    match ProgramInstruction::unpack(&instruction.pack()).unwrap() {
+        ProgramInstruction::InitAccountSettingsUpdate {
+            sol_transfers_enabled: Some(BooleanSetting::Off),
+            spl_transfers_enabled: None,
+            ..
+        } => {}
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+}
+
+#[test]
+fn test_pack_unpack_verify_name_hash() {
+    let entry = NamedEntry::AddressBookEntry(Pubkey::new_unique());
+    let bytes = ProgramInstruction::VerifyNameHash {
+        entry,
+        name: "Treasury ✓".to_string(),
+    }
+    .pack();
+    match ProgramInstruction::unpack(&bytes).unwrap() {
+        ProgramInstruction::VerifyNameHash {
+            entry: unpacked_entry,
+            name,
+        } => {
+            assert_eq!(unpacked_entry, entry);
+            assert_eq!(name, "Treasury ✓");
+        }
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+
+    // names have to be valid UTF-8
+    let mut bytes = bytes;
+    let last = bytes.len() - 1;
+    bytes[last] = 0xff;
+    assert_eq!(
+        ProgramInstruction::unpack(&bytes).unwrap_err(),
+        ProgramError::InvalidInstructionData
+    );
+}
+
+#[test]
+fn test_pack_unpack_set_approval_dispositions() {
+    let dispositions = vec![
+        ApprovalDispositionUpdate {
+            disposition: ApprovalDisposition::APPROVE,
+            params_hash: Hash::new_unique(),
+            denial_reason: DenialReason::None,
+            reason_hash: None,
+        },
+        ApprovalDispositionUpdate {
+            disposition: ApprovalDisposition::DENY,
+            params_hash: Hash::new_unique(),
+            denial_reason: DenialReason::SuspectedFraud,
+            reason_hash: Some(Hash::new_unique()),
+        },
+    ];
+    let bytes = ProgramInstruction::SetApprovalDispositions {
+        dispositions: dispositions.clone(),
+    }
+    .pack();
+    match ProgramInstruction::unpack(&bytes).unwrap() {
+        ProgramInstruction::SetApprovalDispositions {
+            dispositions: unpacked,
+        } => assert_eq!(dispositions, unpacked),
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+
+    // the reason hash flag can only be set or clear
+    let mut bytes = bytes;
+    bytes[2 + 1 + 32 + 1] = 2;
+    assert_eq!(
+        ProgramInstruction::unpack(&bytes).unwrap_err(),
+        ProgramError::InvalidInstructionData
+    );
+}
+
+#[test]
+fn test_pack_unpack_execute_dapp_template() {
+    let instruction = Instruction {
+        program_id: Pubkey::new_unique(),
+        accounts: vec![
+            AccountMeta::new(Pubkey::new_unique(), true),
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+        ],
+        data: vec![7, 1, 2, 3],
+    };
+    let bytes = ProgramInstruction::ExecuteDAppTemplate {
+        template_slot: SlotId::new(3),
+        instruction: instruction.clone(),
+    }
+    .pack();
+    match ProgramInstruction::unpack(&bytes).unwrap() {
+        ProgramInstruction::ExecuteDAppTemplate {
+            template_slot,
+            instruction: unpacked_instruction,
+        } => {
+            assert_eq!(template_slot, SlotId::new(3));
+            assert_eq!(unpacked_instruction, instruction);
+        }
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+}
+
+#[test]
+fn test_pack_unpack_execute_dapp_chunk() {
+    let dapp = DAppBookEntry {
+        address: Pubkey::new_unique(),
+        name_hash: AddressBookEntryNameHash::zero(),
+        instruction_prefixes: crate::model::address_book::DAppInstructionPrefixes::default(),
+    };
+    let instructions = vec![Instruction {
+        program_id: dapp.address,
+        accounts: vec![AccountMeta::new(Pubkey::new_unique(), false)],
+        data: vec![1, 2, 3],
+    }];
+    let chunk = |chunk_len| {
+        ProgramInstruction::ExecuteDAppChunk {
+            account_guid_hash: BalanceAccountGuidHash::new(&[1; 32]),
+            instructions: instructions.clone(),
+            dapp,
+            rent_funding_limit: 10,
+            chunk_len,
+        }
+        .pack()
+    };
+    match ProgramInstruction::unpack(&chunk(2)).unwrap() {
+        ProgramInstruction::ExecuteDAppChunk {
+            account_guid_hash,
+            instructions: unpacked_instructions,
+            dapp: unpacked_dapp,
+            rent_funding_limit,
+            chunk_len,
+        } => {
+            assert_eq!(account_guid_hash, BalanceAccountGuidHash::new(&[1; 32]));
+            assert_eq!(unpacked_instructions, instructions);
+            assert_eq!(unpacked_dapp, dapp);
+            assert_eq!(rent_funding_limit, 10);
+            assert_eq!(chunk_len, 2);
+        }
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+    // a chunk has to run at least one instruction
+    assert_eq!(
+        ProgramInstruction::unpack(&chunk(0)).unwrap_err(),
+        ProgramError::InvalidInstructionData
+    );
+}
+
+#[test]
+fn test_pack_unpack_unwrap_destination() {
+    let account_guid_hash = BalanceAccountGuidHash::new(&[1; 32]);
+    let destination = AddressBookEntry {
+        address: Pubkey::new_unique(),
+        name_hash: AddressBookEntryNameHash::new(&[2; 32]),
+    };
+    let travel_rule_hash = Hash::new_from_array([3; 32]);
+    let init = |destination, travel_rule_hash| {
+        ProgramInstruction::InitWrapUnwrap {
+            account_guid_hash,
+            amount: 5,
+            direction: WrapDirection::UNWRAP,
+            approval_timeout: None,
+            destination,
+            travel_rule_hash,
+        }
+        .pack()
+    };
+    match ProgramInstruction::unpack(&init(Some(destination), None)).unwrap() {
+        ProgramInstruction::InitWrapUnwrap {
+            destination: unpacked_destination,
+            travel_rule_hash: None,
+            ..
+        } => assert_eq!(unpacked_destination, Some(destination)),
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+    let with_travel_rule_hash = init(Some(destination), Some(travel_rule_hash));
+    match ProgramInstruction::unpack(&with_travel_rule_hash).unwrap() {
+        ProgramInstruction::InitWrapUnwrap {
+            destination: unpacked_destination,
+            travel_rule_hash: unpacked_travel_rule_hash,
+            ..
+        } => {
+            assert_eq!(unpacked_destination, Some(destination));
+            assert_eq!(unpacked_travel_rule_hash, Some(travel_rule_hash));
+        }
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+    assert!(
+        ProgramInstruction::unpack(&with_travel_rule_hash[..with_travel_rule_hash.len() - 1])
+            .is_err()
+    );
+    // instructions from clients that don't know about destinations
+    match ProgramInstruction::unpack(&init(None, None)[..42]).unwrap() {
+        ProgramInstruction::InitWrapUnwrap { destination, .. } => assert_eq!(destination, None),
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+
+    for travel_rule_hash in vec![None, Some(travel_rule_hash)] {
+        let finalize = ProgramInstruction::FinalizeWrapUnwrap {
+            account_guid_hash,
+            amount: 5,
+            direction: WrapDirection::UNWRAP,
+            destination: Some(destination.address),
+            travel_rule_hash,
+        }
+        .pack();
+        match ProgramInstruction::unpack(&finalize).unwrap() {
+            ProgramInstruction::FinalizeWrapUnwrap {
+                destination: unpacked_destination,
+                travel_rule_hash: unpacked_travel_rule_hash,
+                ..
+            } => {
+                assert_eq!(unpacked_destination, Some(destination.address));
+                assert_eq!(unpacked_travel_rule_hash, travel_rule_hash);
+            }
+            unpacked => panic!("unexpected instruction {:?}", unpacked),
+        }
+        assert!(ProgramInstruction::unpack(&finalize[..finalize.len() - 1]).is_err());
+    }
+}
+
+#[test]
+fn test_pack_unpack_init_with_idempotency_key() {
+    let with_key = |idempotency_key| {
+        ProgramInstruction::InitWithIdempotencyKey {
+            idempotency_key,
+            instruction: Box::new(ProgramInstruction::InitWalletRepair),
+        }
+        .pack()
+    };
+    match ProgramInstruction::unpack(&with_key(IdempotencyKey::new(&[3; 16]))).unwrap() {
+        ProgramInstruction::InitWithIdempotencyKey {
+            idempotency_key,
+            instruction,
+        } => {
+            assert_eq!(idempotency_key, IdempotencyKey::new(&[3; 16]));
+            assert!(matches!(*instruction, ProgramInstruction::InitWalletRepair));
+        }
+        unpacked => panic!("unexpected instruction {:?}", unpacked),
+    }
+    assert_eq!(
+        ProgramInstruction::unpack(&with_key(IdempotencyKey::zero())).unwrap_err(),
+        ProgramError::InvalidInstructionData
+    );
+}
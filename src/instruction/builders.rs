@@ -0,0 +1,2470 @@
+//! Builders for the instructions of every op and of the program's other
+//! instructions, passing the accounts in the order documented on the
+//! [`ProgramInstruction`] variant each of them packs. The optional sysvar
+//! clock account is always passed.
+
+use crate::instruction::{BalanceAccountCreation, BalanceAccountPolicyUpdate};
+use crate::model::balance_account::BalanceAccount;
+use crate::model::idempotency::IdempotencyKey;
+use crate::model::program_config::ProgramConfig;
+use crate::{
+    instruction::{
+        AddressBookUpdate, ApprovalDispositionUpdate, ApproverDelegationUpdate,
+        BalanceAccountWhitelistUpdate, CombinedTransfer, DAppBookUpdate, DAppTemplatesUpdate,
+        GovernedParameterUpdate, InitialWalletConfig, ProgramConfigUpdate, ProgramInstruction,
+        SignerDirectoryUpdate, SignerOrganizationsUpdate, SignersUpdate, ViewersUpdate,
+        WalletConfigPolicyUpdate, WalletSignerDirectoryUpdate,
+    },
+    model::{
+        address_book::{AddressBookEntry, AddressBookEntryNameHash, DAppBookEntry},
+        balance_account::{BalanceAccountGuidHash, BalanceAccountNameHash},
+        multisig_op::{
+            AmountSpec, ApprovalDisposition, BooleanSetting, DenialReason, SlotUpdateType,
+            WrapDirection,
+        },
+        signer::Signer,
+    },
+    utils,
+    utils::SlotId,
+};
+use solana_program::hash::Hash;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::pubkey::Pubkey;
+use solana_program::{system_program, sysvar};
+use std::borrow::Borrow;
+use std::time::Duration;
+
+// the SPL programs the builders pass along, without depending on spl-token and
+// spl-associated-token-account, which only the program needs
+mod spl_token {
+    solana_program::declare_id!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+    pub mod native_mint {
+        solana_program::declare_id!("So11111111111111111111111111111111111111112");
+    }
+}
+
+mod spl_associated_token_account {
+    use solana_program::pubkey::Pubkey;
+
+    solana_program::declare_id!("ATokenGPvbdGVxr1b2hvZbsiqW3WjwE2oLLaXEDPMD8L");
+
+    pub fn get_associated_token_address(wallet_address: &Pubkey, token_mint: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                &wallet_address.to_bytes(),
+                &super::spl_token::id().to_bytes(),
+                &token_mint.to_bytes(),
+            ],
+            &id(),
+        )
+        .0
+    }
+}
+
+/// Appends the program config account, which the program requires of every
+/// instruction it doesn't exempt, unless the accounts already include it.
+pub fn with_program_config(
+    program_id: &Pubkey,
+    mut accounts: Vec<AccountMeta>,
+) -> Vec<AccountMeta> {
+    let (program_config_account, _) = ProgramConfig::find_address(program_id);
+    if !accounts
+        .iter()
+        .any(|account| account.pubkey == program_config_account)
+    {
+        accounts.push(AccountMeta::new_readonly(program_config_account, false));
+    }
+    accounts
+}
+
+/// Initializes a wallet with [`ProgramInstruction::InitWallet`], signed by its assistant.
+pub fn init_wallet(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    assistant_account: &Pubkey,
+    initial_config: InitialWalletConfig,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*assistant_account, true),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data: ProgramInstruction::InitWallet { initial_config }
+            .borrow()
+            .pack(),
+    }
+}
+
+fn init_multisig_op(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    program_instruction: ProgramInstruction,
+) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(*multisig_op_account, false)];
+    accounts.push(AccountMeta::new_readonly(*wallet_account, false));
+    accounts.push(AccountMeta::new_readonly(*initiator_account, true));
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data: program_instruction.borrow().pack(),
+    }
+}
+
+/// Wraps an init instruction in [`ProgramInstruction::ValidateOnly`], which checks it would start
+/// its op without doing so.
+pub fn validate_only(instruction: Instruction) -> Instruction {
+    Instruction {
+        data: ProgramInstruction::ValidateOnly {
+            instruction: Box::new(ProgramInstruction::unpack(&instruction.data).unwrap()),
+        }
+        .borrow()
+        .pack(),
+        ..instruction
+    }
+}
+
+/// Creates an op account for the initiator with [`ProgramInstruction::CreateOpAccount`], paid for
+/// from the wallet's fee account.
+pub fn create_op_account(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    wallet_account: &Pubkey,
+    fee_account: &Pubkey,
+    initiator_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![
+                AccountMeta::new(*multisig_op_account, true),
+                AccountMeta::new_readonly(*wallet_account, false),
+                AccountMeta::new(*fee_account, false),
+                AccountMeta::new_readonly(*initiator_account, true),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        ),
+        data: ProgramInstruction::CreateOpAccount.borrow().pack(),
+    }
+}
+
+/// Approves or denies an op with [`ProgramInstruction::SetApprovalDisposition`], without a denial
+/// reason.
+pub fn set_approval_disposition(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    approver: &Pubkey,
+    disposition: ApprovalDisposition,
+    params_hash: Hash,
+) -> Instruction {
+    set_approval_disposition_with_reason(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        approver,
+        disposition,
+        params_hash,
+        DenialReason::None,
+        None,
+    )
+}
+
+/// Sets the approver's dispositions on several ops at once with
+/// [`ProgramInstruction::SetApprovalDispositions`].
+pub fn set_approval_dispositions(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    approver: &Pubkey,
+    dispositions: Vec<(Pubkey, ApprovalDispositionUpdate)>,
+) -> Instruction {
+    let data = ProgramInstruction::SetApprovalDispositions {
+        dispositions: dispositions
+            .iter()
+            .map(|(_, disposition)| disposition.clone())
+            .collect(),
+    }
+    .borrow()
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*approver, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    accounts.extend(
+        dispositions
+            .iter()
+            .map(|(multisig_op_account, _)| AccountMeta::new(*multisig_op_account, false)),
+    );
+    accounts.push(AccountMeta::new(*wallet_account, false));
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Approves or denies an op with [`ProgramInstruction::SetApprovalDisposition`], giving a denial
+/// reason.
+pub fn set_approval_disposition_with_reason(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    approver: &Pubkey,
+    disposition: ApprovalDisposition,
+    params_hash: Hash,
+    denial_reason: DenialReason,
+    reason_hash: Option<Hash>,
+) -> Instruction {
+    let data = ProgramInstruction::SetApprovalDisposition {
+        disposition,
+        params_hash,
+        denial_reason,
+        reason_hash,
+    }
+    .borrow()
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new_readonly(*approver, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(*wallet_account, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts a balance account creation op with [`ProgramInstruction::InitBalanceAccountCreation`].
+pub fn init_balance_account_creation(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    slot_id: SlotId<BalanceAccount>,
+    account_guid_hash: BalanceAccountGuidHash,
+    name_hash: BalanceAccountNameHash,
+    approvals_required_for_transfer: u8,
+    approval_timeout_for_transfer: Duration,
+    approvers: Vec<(SlotId<Signer>, Signer)>,
+    whitelist_enabled: BooleanSetting,
+    dapps_enabled: BooleanSetting,
+    address_book_slot_id: SlotId<AddressBookEntry>,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitBalanceAccountCreation {
+            account_guid_hash,
+            creation_params: BalanceAccountCreation {
+                slot_id,
+                name_hash,
+                approvals_required_for_transfer,
+                approval_timeout_for_transfer,
+                transfer_approvers: approvers.clone(),
+                whitelist_enabled,
+                dapps_enabled,
+                address_book_slot_id,
+            },
+        },
+    )
+}
+
+/// Finalizes a balance account creation op with
+/// [`ProgramInstruction::FinalizeBalanceAccountCreation`].
+pub fn finalize_balance_account_creation(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    creation_params: BalanceAccountCreation,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeBalanceAccountCreation {
+        account_guid_hash,
+        creation_params,
+    }
+    .borrow()
+    .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts a dapp book update op with [`ProgramInstruction::InitDAppBookUpdate`].
+pub fn init_dapp_book_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    update: DAppBookUpdate,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitDAppBookUpdate { update },
+    )
+}
+
+/// Finalizes a dapp book update op with [`ProgramInstruction::FinalizeDAppBookUpdate`].
+pub fn finalize_dapp_book_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    update: DAppBookUpdate,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeDAppBookUpdate { update }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts an approver delegation update op with
+/// [`ProgramInstruction::InitApproverDelegationUpdate`].
+pub fn init_approver_delegation_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    update: ApproverDelegationUpdate,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitApproverDelegationUpdate { update },
+    )
+}
+
+/// Finalizes an approver delegation update op with
+/// [`ProgramInstruction::FinalizeApproverDelegationUpdate`].
+pub fn finalize_approver_delegation_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    update: ApproverDelegationUpdate,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeApproverDelegationUpdate { update }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts an op adding and removing several signers with [`ProgramInstruction::InitUpdateSigners`].
+pub fn init_update_signers(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    update: SignersUpdate,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitUpdateSigners { update },
+    )
+}
+
+/// Finalizes a signers update op with [`ProgramInstruction::FinalizeUpdateSigners`].
+pub fn finalize_update_signers(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    update: SignersUpdate,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeUpdateSigners { update }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts a viewers update op with [`ProgramInstruction::InitViewersUpdate`].
+pub fn init_viewers_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    update: ViewersUpdate,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitViewersUpdate { update },
+    )
+}
+
+/// Finalizes a viewers update op with [`ProgramInstruction::FinalizeViewersUpdate`].
+pub fn finalize_viewers_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    update: ViewersUpdate,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeViewersUpdate { update }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Checks that the viewer may read the wallet with [`ProgramInstruction::VerifyAccess`].
+pub fn verify_access(program_id: &Pubkey, wallet_account: &Pubkey, viewer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![
+                AccountMeta::new_readonly(*wallet_account, false),
+                AccountMeta::new_readonly(*viewer, true),
+            ],
+        ),
+        data: ProgramInstruction::VerifyAccess.pack(),
+    }
+}
+
+/// Starts a signer organizations update op with
+/// [`ProgramInstruction::InitSignerOrganizationsUpdate`].
+pub fn init_signer_organizations_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    update: SignerOrganizationsUpdate,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitSignerOrganizationsUpdate { update },
+    )
+}
+
+/// Finalizes a signer organizations update op with
+/// [`ProgramInstruction::FinalizeSignerOrganizationsUpdate`].
+pub fn finalize_signer_organizations_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    update: SignerOrganizationsUpdate,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeSignerOrganizationsUpdate { update }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts an op replacing the wallet's assistant with [`ProgramInstruction::InitAssistantUpdate`].
+pub fn init_assistant_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    assistant: Pubkey,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitAssistantUpdate { assistant },
+    )
+}
+
+/// Finalizes an assistant update op with [`ProgramInstruction::FinalizeAssistantUpdate`].
+pub fn finalize_assistant_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    assistant: Pubkey,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeAssistantUpdate { assistant }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts an address book update op whose update is supplied in chunks, with
+/// [`ProgramInstruction::InitAddressBookUpdateChunked`].
+pub fn init_address_book_update_chunked(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    buffer_account: &Pubkey,
+    update_hash: Hash,
+    update_len: u32,
+) -> Instruction {
+    let data = ProgramInstruction::InitAddressBookUpdateChunked {
+        update_hash,
+        update_len,
+    }
+    .borrow()
+    .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new_readonly(*initiator_account, true),
+        AccountMeta::new(*buffer_account, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Writes a chunk of a chunked address book update to its buffer account with
+/// [`ProgramInstruction::SupplyAddressBookChunk`].
+pub fn supply_address_book_chunk(
+    program_id: &Pubkey,
+    buffer_account: &Pubkey,
+    initiator_account: &Pubkey,
+    offset: u32,
+    chunk: Vec<u8>,
+) -> Instruction {
+    let data = ProgramInstruction::SupplyAddressBookChunk { offset, chunk }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*buffer_account, false),
+        AccountMeta::new_readonly(*initiator_account, true),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Finalizes a chunked address book update op with
+/// [`ProgramInstruction::FinalizeAddressBookUpdateChunked`].
+pub fn finalize_address_book_update_chunked(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    buffer_account: &Pubkey,
+    update_hash: Hash,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeAddressBookUpdateChunked { update_hash }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new(*buffer_account, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Sweeps SOL deposits into the balance account with [`ProgramInstruction::ConsolidateDeposits`].
+pub fn consolidate_sol_deposits(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    balance_account: &Pubkey,
+    initiator_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    deposit_indexes: Vec<u32>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new(*balance_account, false),
+        AccountMeta::new_readonly(*initiator_account, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    for index in deposit_indexes.iter() {
+        let (deposit_address, _) = account_guid_hash.find_deposit_address(*index, program_id);
+        accounts.push(AccountMeta::new(deposit_address, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data: ProgramInstruction::ConsolidateDeposits {
+            account_guid_hash,
+            deposit_indexes,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+/// Starts a governed parameter update op with [`ProgramInstruction::InitGovernedParameterUpdate`].
+pub fn init_governed_parameter_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    update: GovernedParameterUpdate,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitGovernedParameterUpdate { update },
+    )
+}
+
+/// Finalizes a governed parameter update op with
+/// [`ProgramInstruction::FinalizeGovernedParameterUpdate`].
+pub fn finalize_governed_parameter_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    update: GovernedParameterUpdate,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeGovernedParameterUpdate { update }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts an op setting or clearing the wallet's canary with
+/// [`ProgramInstruction::InitWalletCanaryUpdate`].
+pub fn init_wallet_canary_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    canary_key: Option<Pubkey>,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitWalletCanaryUpdate { canary_key },
+    )
+}
+
+/// Finalizes a wallet canary update op with [`ProgramInstruction::FinalizeWalletCanaryUpdate`].
+pub fn finalize_wallet_canary_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    canary_key: Option<Pubkey>,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeWalletCanaryUpdate { canary_key }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts a balance account policy update op with
+/// [`ProgramInstruction::InitBalanceAccountPolicyUpdate`].
+pub fn init_balance_account_policy_update_instruction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    update: BalanceAccountPolicyUpdate,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![
+                AccountMeta::new(*multisig_op_account, false),
+                AccountMeta::new(*wallet_account, false),
+                AccountMeta::new_readonly(*initiator_account, true),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+            ],
+        ),
+        data: ProgramInstruction::InitBalanceAccountPolicyUpdate {
+            account_guid_hash,
+            update: update.clone(),
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+/// Finalizes a balance account policy update op with
+/// [`ProgramInstruction::FinalizeBalanceAccountPolicyUpdate`].
+pub fn finalize_balance_account_policy_update_instruction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    update: BalanceAccountPolicyUpdate,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data: ProgramInstruction::FinalizeBalanceAccountPolicyUpdate {
+            account_guid_hash,
+            update,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+/// Starts a transfer op with [`ProgramInstruction::InitTransfer`]. For SPL transfers the
+/// destination token account is the destination's associated token account.
+pub fn init_transfer(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    destination_name_hash: AddressBookEntryNameHash,
+    token_mint: &Pubkey,
+    fee_payer: &Pubkey,
+    approval_timeout: Option<Duration>,
+) -> Instruction {
+    let data = ProgramInstruction::InitTransfer {
+        account_guid_hash,
+        amount,
+        destination_name_hash,
+        approval_timeout,
+        memo_hash: None,
+        ata_rent_payer: None,
+        amount_spec: AmountSpec::Exact,
+        travel_rule_hash: None,
+    }
+    .borrow()
+    .pack();
+
+    let destination_token_account =
+        spl_associated_token_account::get_associated_token_address(destination_account, token_mint);
+
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*source_account, false),
+        AccountMeta::new_readonly(*destination_account, false),
+        AccountMeta::new_readonly(*initiator_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*token_mint, false),
+        AccountMeta::new(destination_token_account, false),
+        AccountMeta::new(*fee_payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Finalizes a transfer op with [`ProgramInstruction::FinalizeTransfer`].
+pub fn finalize_transfer(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    wallet_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    token_mint: &Pubkey,
+    token_authority: Option<&Pubkey>,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeTransfer {
+        account_guid_hash,
+        amount,
+        token_mint: *token_mint,
+        memo_hash: None,
+        fee_reimbursement: 0,
+        ata_rent_payer: None,
+        token_program: None,
+        amount_spec: AmountSpec::Exact,
+        travel_rule_hash: None,
+    }
+    .borrow()
+    .pack();
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*source_account, false),
+        AccountMeta::new(*destination_account, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    if *token_mint != system_program::id() {
+        // SPL
+        accounts.extend_from_slice(&[
+            AccountMeta::new(
+                spl_associated_token_account::get_associated_token_address(
+                    source_account,
+                    &token_mint,
+                ),
+                false,
+            ),
+            AccountMeta::new(
+                spl_associated_token_account::get_associated_token_address(
+                    destination_account,
+                    &token_mint,
+                ),
+                false,
+            ),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(*token_authority.unwrap(), false),
+        ])
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Checks whether a transfer would go through, without starting an op, with
+/// [`ProgramInstruction::PreflightTransfer`].
+pub fn preflight_transfer(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    token_mint: &Pubkey,
+    destination_name_hash: AddressBookEntryNameHash,
+) -> Instruction {
+    let data = ProgramInstruction::PreflightTransfer {
+        account_guid_hash,
+        amount,
+        token_mint: *token_mint,
+        destination_name_hash,
+    }
+    .borrow()
+    .pack();
+    let mut accounts = vec![
+        // the multisig op and rent collector accounts are not read
+        AccountMeta::new_readonly(Pubkey::default(), false),
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new_readonly(*source_account, false),
+        AccountMeta::new_readonly(*destination_account, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(Pubkey::default(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    if *token_mint != system_program::id() {
+        accounts.extend_from_slice(&[
+            AccountMeta::new_readonly(
+                spl_associated_token_account::get_associated_token_address(
+                    source_account,
+                    &token_mint,
+                ),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                spl_associated_token_account::get_associated_token_address(
+                    destination_account,
+                    &token_mint,
+                ),
+                false,
+            ),
+        ])
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts an op wrapping or unwrapping SOL with [`ProgramInstruction::InitWrapUnwrap`].
+pub fn init_wrap_unwrap(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    balance_account: &Pubkey,
+    account_guid_hash: &BalanceAccountGuidHash,
+    amount: u64,
+    direction: WrapDirection,
+    approval_timeout: Option<Duration>,
+    destination: Option<AddressBookEntry>,
+    travel_rule_hash: Option<Hash>,
+) -> Instruction {
+    let data = ProgramInstruction::InitWrapUnwrap {
+        account_guid_hash: *account_guid_hash,
+        amount,
+        direction,
+        approval_timeout,
+        destination,
+        travel_rule_hash,
+    }
+    .borrow()
+    .pack();
+
+    let wrapped_sol_account = spl_associated_token_account::get_associated_token_address(
+        balance_account,
+        &spl_token::native_mint::id(),
+    );
+
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new(*balance_account, false),
+        AccountMeta::new(wrapped_sol_account, false),
+        AccountMeta::new_readonly(spl_token::native_mint::id(), false),
+        AccountMeta::new_readonly(*initiator_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Finalizes a wrap or unwrap op with [`ProgramInstruction::FinalizeWrapUnwrap`].
+pub fn finalize_wrap_unwrap(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    wallet_account: &Pubkey,
+    balance_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    account_guid_hash: &BalanceAccountGuidHash,
+    amount: u64,
+    direction: WrapDirection,
+    destination: Option<Pubkey>,
+    travel_rule_hash: Option<Hash>,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeWrapUnwrap {
+        account_guid_hash: *account_guid_hash,
+        amount,
+        direction,
+        destination,
+        travel_rule_hash,
+    }
+    .borrow()
+    .pack();
+
+    let wrapped_sol_account = spl_associated_token_account::get_associated_token_address(
+        balance_account,
+        &spl_token::native_mint::id(),
+    );
+
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*balance_account, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(wrapped_sol_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_token::native_mint::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+    if let Some(destination) = destination {
+        accounts.push(AccountMeta::new(destination, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts an op adding or removing a single signer with [`ProgramInstruction::InitUpdateSigner`].
+pub fn init_update_signer(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    assistant_account: &Pubkey,
+    slot_update_type: SlotUpdateType,
+    slot_id: SlotId<Signer>,
+    signer: Signer,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        assistant_account,
+        ProgramInstruction::InitUpdateSigner {
+            slot_update_type,
+            slot_id,
+            signer,
+        },
+    )
+}
+
+/// Finalizes a single signer update op with [`ProgramInstruction::FinalizeUpdateSigner`].
+pub fn finalize_update_signer(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    slot_update_type: SlotUpdateType,
+    slot_id: SlotId<Signer>,
+    signer: Signer,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeUpdateSigner {
+        slot_update_type,
+        slot_id,
+        signer,
+    }
+    .borrow()
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts a wallet config policy update op with
+/// [`ProgramInstruction::InitWalletConfigPolicyUpdate`].
+pub fn init_wallet_config_policy_update_instruction(
+    program_id: Pubkey,
+    wallet_account: Pubkey,
+    multisig_op_account: Pubkey,
+    initiator_account: Pubkey,
+    update: &WalletConfigPolicyUpdate,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(multisig_op_account, false),
+            AccountMeta::new(wallet_account, false),
+            AccountMeta::new_readonly(initiator_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::InitWalletConfigPolicyUpdate {
+            update: update.clone(),
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+/// Finalizes a wallet config policy update op with
+/// [`ProgramInstruction::FinalizeWalletConfigPolicyUpdate`].
+pub fn finalize_wallet_config_policy_update_instruction(
+    program_id: Pubkey,
+    wallet_account: Pubkey,
+    multisig_op_account: Pubkey,
+    rent_collector_account: Pubkey,
+    update: &WalletConfigPolicyUpdate,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(multisig_op_account, false),
+            AccountMeta::new(wallet_account, false),
+            AccountMeta::new_readonly(rent_collector_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeWalletConfigPolicyUpdate {
+            update: update.clone(),
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+/// Starts a dapp transaction op with [`ProgramInstruction::InitDAppTransaction`].
+pub fn init_dapp_transaction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    account_guid_hash: &BalanceAccountGuidHash,
+    dapp: DAppBookEntry,
+    instructions: Vec<Instruction>,
+    rent_funding_limit: u64,
+) -> Instruction {
+    let data = ProgramInstruction::InitDAppTransaction {
+        account_guid_hash: *account_guid_hash,
+        dapp,
+        instructions,
+        rent_funding_limit,
+    }
+    .borrow()
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new_readonly(*initiator_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Finalizes a dapp transaction op with [`ProgramInstruction::FinalizeDAppTransaction`], passing
+/// the accounts of its instructions after the listed ones.
+pub fn finalize_dapp_transaction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    balance_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    account_guid_hash: &BalanceAccountGuidHash,
+    dapp: DAppBookEntry,
+    instructions: &Vec<Instruction>,
+    rent_funding_limit: u64,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeDAppTransaction {
+        account_guid_hash: *account_guid_hash,
+        dapp,
+        instructions: instructions.clone(),
+        rent_funding_limit,
+    }
+    .borrow()
+    .pack();
+
+    // the accounts below are expected below in this order by finalize
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*balance_account, false),
+        AccountMeta::new(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    // we also need to include any accounts referenced by the dapp instructions, but we don't
+    // want to repeat keys
+    let keys_to_skip = vec![
+        *multisig_op_account,
+        *wallet_account,
+        *balance_account,
+        *rent_collector_account,
+        sysvar::clock::id(),
+    ];
+
+    accounts.extend(utils::unique_account_metas(&instructions, &keys_to_skip));
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts a balance account settings update op with
+/// [`ProgramInstruction::InitAccountSettingsUpdate`].
+pub fn init_account_settings_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    whitelist_status: Option<BooleanSetting>,
+    dapps_enabled: Option<BooleanSetting>,
+    sol_transfers_enabled: Option<BooleanSetting>,
+    spl_transfers_enabled: Option<BooleanSetting>,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitAccountSettingsUpdate {
+            account_guid_hash,
+            whitelist_enabled: whitelist_status,
+            dapps_enabled,
+            sol_transfers_enabled,
+            spl_transfers_enabled,
+        },
+    )
+}
+
+/// Finalizes a balance account settings update op with
+/// [`ProgramInstruction::FinalizeAccountSettingsUpdate`].
+pub fn finalize_account_settings_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    whitelist_status: Option<BooleanSetting>,
+    dapps_enabled: Option<BooleanSetting>,
+    sol_transfers_enabled: Option<BooleanSetting>,
+    spl_transfers_enabled: Option<BooleanSetting>,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeAccountSettingsUpdate {
+        account_guid_hash,
+        whitelist_enabled: whitelist_status,
+        dapps_enabled,
+        sol_transfers_enabled,
+        spl_transfers_enabled,
+    }
+    .borrow()
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts an op renaming a balance account with
+/// [`ProgramInstruction::InitBalanceAccountNameUpdate`].
+pub fn init_balance_account_name_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    account_name_hash: BalanceAccountNameHash,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitBalanceAccountNameUpdate {
+            account_guid_hash,
+            account_name_hash,
+        },
+    )
+}
+
+/// Finalizes a balance account name update op with
+/// [`ProgramInstruction::FinalizeBalanceAccountNameUpdate`].
+pub fn finalize_balance_account_name_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    account_name_hash: BalanceAccountNameHash,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeBalanceAccountNameUpdate {
+        account_guid_hash,
+        account_name_hash,
+    }
+    .borrow()
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts an address book update op with [`ProgramInstruction::InitAddressBookUpdate`].
+pub fn init_address_book_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    add_address_book_entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    remove_address_book_entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    balance_account_whitelist_updates: Vec<BalanceAccountWhitelistUpdate>,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitAddressBookUpdate {
+            update: AddressBookUpdate {
+                add_address_book_entries: add_address_book_entries.clone(),
+                remove_address_book_entries: remove_address_book_entries.clone(),
+                balance_account_whitelist_updates: balance_account_whitelist_updates.clone(),
+                add_destination_token_mints: vec![],
+                remove_destination_token_mints: vec![],
+                add_destination_namespaces: vec![],
+                remove_destination_namespaces: vec![],
+            },
+        },
+    )
+}
+
+/// Finalizes an address book update op with [`ProgramInstruction::FinalizeAddressBookUpdate`].
+pub fn finalize_address_book_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    update: AddressBookUpdate,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeAddressBookUpdate { update }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts an address book verification op with [`ProgramInstruction::InitAddressBookVerification`].
+pub fn init_address_book_verification(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitAddressBookVerification { entries },
+    )
+}
+
+/// Finalizes an address book verification op with
+/// [`ProgramInstruction::FinalizeAddressBookVerification`].
+pub fn finalize_address_book_verification(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeAddressBookVerification { entries }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts an activity checkpoint op with [`ProgramInstruction::InitActivityCheckpoint`].
+pub fn init_activity_checkpoint(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    checkpoint_index: u32,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitActivityCheckpoint { checkpoint_index },
+    )
+}
+
+/// Finalizes an activity checkpoint op with [`ProgramInstruction::FinalizeActivityCheckpoint`].
+pub fn finalize_activity_checkpoint(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    checkpoint_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    checkpoint_index: u32,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeActivityCheckpoint { checkpoint_index }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*checkpoint_account, false),
+        AccountMeta::new(*rent_collector_account, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts an op updating one of the wallet's address book pages with
+/// [`ProgramInstruction::InitAddressBookPageUpdate`].
+pub fn init_address_book_page_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    page_account: &Pubkey,
+    initiator_account: &Pubkey,
+    page_index: u8,
+    update: AddressBookUpdate,
+) -> Instruction {
+    let data = ProgramInstruction::InitAddressBookPageUpdate { page_index, update }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new_readonly(*page_account, false),
+        AccountMeta::new_readonly(*initiator_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Finalizes an address book page update op with
+/// [`ProgramInstruction::FinalizeAddressBookPageUpdate`].
+pub fn finalize_address_book_page_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    page_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    page_index: u8,
+    update: AddressBookUpdate,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeAddressBookPageUpdate { page_index, update }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*page_account, false),
+        AccountMeta::new(*rent_collector_account, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Creates the program config account with [`ProgramInstruction::InitProgramConfig`].
+pub fn init_program_config(
+    program_id: &Pubkey,
+    program_config_account: &Pubkey,
+    program_data_account: &Pubkey,
+    authority_account: &Pubkey,
+    update: ProgramConfigUpdate,
+) -> Instruction {
+    let data = ProgramInstruction::InitProgramConfig { update }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*program_config_account, false),
+        AccountMeta::new_readonly(*program_data_account, false),
+        AccountMeta::new(*authority_account, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Updates the program config with [`ProgramInstruction::UpdateProgramConfig`].
+pub fn update_program_config(
+    program_id: &Pubkey,
+    program_config_account: &Pubkey,
+    authority_account: &Pubkey,
+    update: ProgramConfigUpdate,
+) -> Instruction {
+    let data = ProgramInstruction::UpdateProgramConfig { update }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*program_config_account, false),
+        AccountMeta::new_readonly(*authority_account, true),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a signer directory with [`ProgramInstruction::InitSignerDirectory`].
+pub fn init_signer_directory(
+    program_id: &Pubkey,
+    signer_directory_account: &Pubkey,
+    authority_account: &Pubkey,
+) -> Instruction {
+    let data = ProgramInstruction::InitSignerDirectory.borrow().pack();
+    let accounts = vec![
+        AccountMeta::new(*signer_directory_account, false),
+        AccountMeta::new(*authority_account, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Updates a signer directory with [`ProgramInstruction::UpdateSignerDirectory`].
+pub fn update_signer_directory(
+    program_id: &Pubkey,
+    signer_directory_account: &Pubkey,
+    authority_account: &Pubkey,
+    update: SignerDirectoryUpdate,
+) -> Instruction {
+    let data = ProgramInstruction::UpdateSignerDirectory { update }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*signer_directory_account, false),
+        AccountMeta::new_readonly(*authority_account, true),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts an op linking or unlinking a signer directory with
+/// [`ProgramInstruction::InitWalletSignerDirectoryUpdate`].
+pub fn init_wallet_signer_directory_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    signer_directory_account: &Pubkey,
+    update: WalletSignerDirectoryUpdate,
+) -> Instruction {
+    let mut instruction = init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitWalletSignerDirectoryUpdate { update },
+    );
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(*signer_directory_account, false));
+    instruction
+}
+
+/// Finalizes a wallet signer directory update op with
+/// [`ProgramInstruction::FinalizeWalletSignerDirectoryUpdate`].
+pub fn finalize_wallet_signer_directory_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    signer_directory_account: &Pubkey,
+    update: WalletSignerDirectoryUpdate,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeWalletSignerDirectoryUpdate { update }
+        .borrow()
+        .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*signer_directory_account, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Starts a transfer between two of the wallet's balance accounts with
+/// [`ProgramInstruction::InitInternalTransfer`].
+pub fn init_internal_transfer(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    source_account_guid_hash: BalanceAccountGuidHash,
+    destination_account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    token_mint: &Pubkey,
+) -> Instruction {
+    let data = ProgramInstruction::InitInternalTransfer {
+        source_account_guid_hash,
+        destination_account_guid_hash,
+        amount,
+        approval_timeout: None,
+    }
+    .borrow()
+    .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*source_account, false),
+        AccountMeta::new_readonly(*destination_account, false),
+        AccountMeta::new_readonly(*initiator_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*token_mint, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Finalizes an internal transfer op with [`ProgramInstruction::FinalizeInternalTransfer`].
+pub fn finalize_internal_transfer(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    source_account_guid_hash: BalanceAccountGuidHash,
+    destination_account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    token_mint: &Pubkey,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeInternalTransfer {
+        source_account_guid_hash,
+        destination_account_guid_hash,
+        amount,
+        token_mint: *token_mint,
+    }
+    .borrow()
+    .pack();
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*source_account, false),
+        AccountMeta::new(*destination_account, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    if *token_mint != system_program::id() {
+        accounts.extend_from_slice(&[
+            AccountMeta::new(
+                spl_associated_token_account::get_associated_token_address(
+                    source_account,
+                    token_mint,
+                ),
+                false,
+            ),
+            AccountMeta::new(
+                spl_associated_token_account::get_associated_token_address(
+                    destination_account,
+                    token_mint,
+                ),
+                false,
+            ),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ])
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data,
+    }
+}
+
+/// Creates the wallet's pending ops registry with [`ProgramInstruction::InitPendingOps`].
+pub fn init_pending_ops(
+    program_id: &Pubkey,
+    registry_account: &Pubkey,
+    wallet_account: &Pubkey,
+    payer_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![
+                AccountMeta::new(*registry_account, false),
+                AccountMeta::new_readonly(*wallet_account, false),
+                AccountMeta::new(*payer_account, true),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        ),
+        data: ProgramInstruction::InitPendingOps.pack(),
+    }
+}
+
+/// Drops the given ops from the pending ops registry with
+/// [`ProgramInstruction::CleanupPendingOps`].
+pub fn cleanup_pending_ops(
+    program_id: &Pubkey,
+    registry_account: &Pubkey,
+    op_accounts: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(*registry_account, false)];
+    accounts.extend(
+        op_accounts
+            .iter()
+            .map(|op_account| AccountMeta::new_readonly(*op_account, false)),
+    );
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data: ProgramInstruction::CleanupPendingOps.pack(),
+    }
+}
+
+/// Wraps an init instruction in [`ProgramInstruction::InitWithPrerequisite`], so that its op only
+/// runs once the prerequisite has.
+pub fn with_prerequisite(instruction: Instruction, prerequisite: Hash) -> Instruction {
+    Instruction {
+        data: ProgramInstruction::InitWithPrerequisite {
+            prerequisite,
+            instruction: Box::new(ProgramInstruction::unpack(&instruction.data).unwrap()),
+        }
+        .pack(),
+        ..instruction
+    }
+}
+
+/// Fails unless the wallet's policy manifest has the given hash, with
+/// [`ProgramInstruction::AssertPolicyManifest`].
+pub fn assert_policy_manifest(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    manifest_hash: Hash,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![AccountMeta::new_readonly(*wallet_account, false)],
+        ),
+        data: ProgramInstruction::AssertPolicyManifest { manifest_hash }.pack(),
+    }
+}
+
+/// Logs how full the wallet's slots are with [`ProgramInstruction::ReportCapacity`].
+pub fn report_capacity(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    warning_threshold_percent: u8,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![AccountMeta::new_readonly(*wallet_account, false)],
+        ),
+        data: ProgramInstruction::ReportCapacity {
+            warning_threshold_percent,
+        }
+        .pack(),
+    }
+}
+
+/// Starts an op exporting the wallet's state to another program with
+/// [`ProgramInstruction::InitWalletStateExport`].
+pub fn init_wallet_state_export(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    target_program_id: &Pubkey,
+) -> Instruction {
+    let (program_config_account, _) = ProgramConfig::find_address(program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![
+                AccountMeta::new(*multisig_op_account, false),
+                AccountMeta::new_readonly(*wallet_account, false),
+                AccountMeta::new_readonly(*initiator_account, true),
+                AccountMeta::new_readonly(program_config_account, false),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+            ],
+        ),
+        data: ProgramInstruction::InitWalletStateExport {
+            target_program_id: *target_program_id,
+        }
+        .pack(),
+    }
+}
+
+/// Finalizes a wallet state export op with [`ProgramInstruction::FinalizeWalletStateExport`].
+pub fn finalize_wallet_state_export(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    export_account: &Pubkey,
+    target_program_id: &Pubkey,
+) -> Instruction {
+    let (program_config_account, _) = ProgramConfig::find_address(program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![
+                AccountMeta::new(*multisig_op_account, false),
+                AccountMeta::new_readonly(*wallet_account, false),
+                AccountMeta::new_readonly(*rent_collector_account, true),
+                AccountMeta::new(*export_account, false),
+                AccountMeta::new_readonly(program_config_account, false),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+            ],
+        ),
+        data: ProgramInstruction::FinalizeWalletStateExport {
+            target_program_id: *target_program_id,
+        }
+        .pack(),
+    }
+}
+
+/// Imports a wallet exported by another program with [`ProgramInstruction::ImportWalletState`].
+pub fn import_wallet_state(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    export_account: &Pubkey,
+    assistant_account: &Pubkey,
+) -> Instruction {
+    let (program_config_account, _) = ProgramConfig::find_address(program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![
+                AccountMeta::new(*wallet_account, false),
+                AccountMeta::new_readonly(*export_account, false),
+                AccountMeta::new_readonly(*assistant_account, true),
+                AccountMeta::new_readonly(program_config_account, false),
+            ],
+        ),
+        data: ProgramInstruction::ImportWalletState.pack(),
+    }
+}
+
+/// Closes an expired op with [`ProgramInstruction::ExpireOp`].
+pub fn expire_op(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    wallet_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![
+                AccountMeta::new(*multisig_op_account, false),
+                AccountMeta::new(*wallet_account, false),
+                AccountMeta::new(*rent_collector_account, true),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+            ],
+        ),
+        data: ProgramInstruction::ExpireOp.pack(),
+    }
+}
+
+/// Starts an op transferring SOL and tokens together with
+/// [`ProgramInstruction::InitCombinedTransfer`].
+pub fn init_combined_transfer(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    destination_name_hash: AddressBookEntryNameHash,
+    transfer: CombinedTransfer,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![
+                AccountMeta::new(*multisig_op_account, false),
+                AccountMeta::new(*wallet_account, false),
+                AccountMeta::new_readonly(*source_account, false),
+                AccountMeta::new_readonly(*destination_account, false),
+                AccountMeta::new_readonly(*initiator_account, true),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+            ],
+        ),
+        data: ProgramInstruction::InitCombinedTransfer {
+            account_guid_hash,
+            destination_name_hash,
+            transfer,
+        }
+        .pack(),
+    }
+}
+
+/// Finalizes a combined transfer op with [`ProgramInstruction::FinalizeCombinedTransfer`].
+pub fn finalize_combined_transfer(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    wallet_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    transfer: CombinedTransfer,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*source_account, false),
+        AccountMeta::new(*destination_account, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    for (token_mint, _) in transfer.token_amounts.iter() {
+        accounts.push(AccountMeta::new(
+            spl_associated_token_account::get_associated_token_address(source_account, token_mint),
+            false,
+        ));
+        accounts.push(AccountMeta::new(
+            spl_associated_token_account::get_associated_token_address(
+                destination_account,
+                token_mint,
+            ),
+            false,
+        ));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data: ProgramInstruction::FinalizeCombinedTransfer {
+            account_guid_hash,
+            transfer,
+        }
+        .pack(),
+    }
+}
+
+/// Starts a wallet metadata update op with [`ProgramInstruction::InitWalletMetadataUpdate`].
+pub fn init_wallet_metadata_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    metadata_hash: Hash,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitWalletMetadataUpdate { metadata_hash },
+    )
+}
+
+/// Finalizes a wallet metadata update op with [`ProgramInstruction::FinalizeWalletMetadataUpdate`].
+pub fn finalize_wallet_metadata_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    metadata_hash: Hash,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![
+                AccountMeta::new(*multisig_op_account, false),
+                AccountMeta::new(*wallet_account, false),
+                AccountMeta::new_readonly(*rent_collector_account, true),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+            ],
+        ),
+        data: ProgramInstruction::FinalizeWalletMetadataUpdate { metadata_hash }.pack(),
+    }
+}
+
+/// Starts a dapp templates update op with [`ProgramInstruction::InitDAppTemplatesUpdate`].
+pub fn init_dapp_templates_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    update: DAppTemplatesUpdate,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitDAppTemplatesUpdate { update },
+    )
+}
+
+/// Finalizes a dapp templates update op with [`ProgramInstruction::FinalizeDAppTemplatesUpdate`].
+pub fn finalize_dapp_templates_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    update: DAppTemplatesUpdate,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![
+                AccountMeta::new(*multisig_op_account, false),
+                AccountMeta::new(*wallet_account, false),
+                AccountMeta::new_readonly(*rent_collector_account, true),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+            ],
+        ),
+        data: ProgramInstruction::FinalizeDAppTemplatesUpdate { update }.pack(),
+    }
+}
+
+/// Starts a wallet repair op with [`ProgramInstruction::InitWalletRepair`].
+pub fn init_wallet_repair(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitWalletRepair,
+    )
+}
+
+/// Finalizes a wallet repair op with [`ProgramInstruction::FinalizeWalletRepair`].
+pub fn finalize_wallet_repair(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![
+                AccountMeta::new(*multisig_op_account, false),
+                AccountMeta::new(*wallet_account, false),
+                AccountMeta::new_readonly(*rent_collector_account, true),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+            ],
+        ),
+        data: ProgramInstruction::FinalizeWalletRepair.pack(),
+    }
+}
+
+/// Logs what a wallet repair would clear with [`ProgramInstruction::CheckWalletIntegrity`].
+pub fn check_wallet_integrity(program_id: &Pubkey, wallet_account: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![AccountMeta::new_readonly(*wallet_account, false)],
+        ),
+        data: ProgramInstruction::CheckWalletIntegrity.pack(),
+    }
+}
+
+/// Extends the expiry of an op with [`ProgramInstruction::RenewMultisigOp`], signed by a config
+/// approver.
+pub fn renew_multisig_op(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    config_approver: &Pubkey,
+    params_hash: Hash,
+    extension: Duration,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![
+                AccountMeta::new(*multisig_op_account, false),
+                AccountMeta::new_readonly(*wallet_account, false),
+                AccountMeta::new_readonly(*config_approver, true),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+            ],
+        ),
+        data: ProgramInstruction::RenewMultisigOp {
+            params_hash,
+            extension,
+        }
+        .pack(),
+    }
+}
+
+/// Starts an op closing the wallet with [`ProgramInstruction::InitWalletClose`].
+pub fn init_wallet_close(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_collector: &Pubkey,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        ProgramInstruction::InitWalletClose {
+            rent_collector: *rent_collector,
+        },
+    )
+}
+
+/// Finalizes a wallet close op with [`ProgramInstruction::FinalizeWalletClose`]. The closed
+/// accounts are the balance accounts, their token accounts and optionally the fee account.
+pub fn finalize_wallet_close(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    rent_collector: &Pubkey,
+    closed_accounts: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*rent_collector_account, true),
+        AccountMeta::new(*rent_collector, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    accounts.extend(
+        closed_accounts
+            .iter()
+            .map(|account| AccountMeta::new(*account, false)),
+    );
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data: ProgramInstruction::FinalizeWalletClose {
+            rent_collector: *rent_collector,
+        }
+        .pack(),
+    }
+}
+
+/// Logs when the wallet's signers were last active with
+/// [`ProgramInstruction::ReportSignerActivity`].
+pub fn report_signer_activity(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    inactive_after_days: u16,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(
+            program_id,
+            vec![
+                AccountMeta::new_readonly(*wallet_account, false),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+            ],
+        ),
+        data: ProgramInstruction::ReportSignerActivity {
+            inactive_after_days,
+        }
+        .pack(),
+    }
+}
+
+/// Runs the next chunk of an approved dapp transaction with
+/// [`ProgramInstruction::ExecuteDAppChunk`], passing the accounts of the instructions after the
+/// first `instructions_executed`, up to `chunk_len` of them.
+pub fn execute_dapp_chunk(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    balance_account: &Pubkey,
+    rent_collector_account: &Pubkey,
+    account_guid_hash: &BalanceAccountGuidHash,
+    dapp: DAppBookEntry,
+    instructions: &Vec<Instruction>,
+    rent_funding_limit: u64,
+    instructions_executed: u16,
+    chunk_len: u8,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*balance_account, false),
+        AccountMeta::new(*rent_collector_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    // only the accounts of the instructions that run in this chunk are needed
+    let start = usize::from(instructions_executed).min(instructions.len());
+    let end = start
+        .saturating_add(usize::from(chunk_len))
+        .min(instructions.len());
+    let keys_to_skip = vec![
+        *multisig_op_account,
+        *wallet_account,
+        *balance_account,
+        *rent_collector_account,
+        sysvar::clock::id(),
+    ];
+    accounts.extend(utils::unique_account_metas(
+        &instructions[start..end].to_vec(),
+        &keys_to_skip,
+    ));
+
+    Instruction {
+        program_id: *program_id,
+        accounts: with_program_config(program_id, accounts),
+        data: ProgramInstruction::ExecuteDAppChunk {
+            account_guid_hash: *account_guid_hash,
+            instructions: instructions.clone(),
+            dapp,
+            rent_funding_limit,
+            chunk_len,
+        }
+        .pack(),
+    }
+}
+
+/// Wraps an init instruction in [`ProgramInstruction::InitWithIdempotencyKey`], marking the wallet
+/// account writable.
+pub fn with_idempotency_key(
+    instruction: Instruction,
+    idempotency_key: IdempotencyKey,
+) -> Instruction {
+    // the wallet records the key, so it has to be writable
+    let mut accounts = instruction.accounts.clone();
+    accounts[1].is_writable = true;
+    Instruction {
+        data: ProgramInstruction::InitWithIdempotencyKey {
+            idempotency_key,
+            instruction: Box::new(ProgramInstruction::unpack(&instruction.data).unwrap()),
+        }
+        .pack(),
+        accounts,
+        ..instruction
+    }
+}
+
+#[cfg(feature = "program")]
+#[test]
+fn test_spl_program_ids() {
+    assert_eq!(spl_token::id(), ::spl_token::id());
+    assert_eq!(spl_token::native_mint::id(), ::spl_token::native_mint::id());
+    assert_eq!(
+        spl_associated_token_account::id(),
+        ::spl_associated_token_account::id()
+    );
+    let (wallet_address, token_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    assert_eq!(
+        spl_associated_token_account::get_associated_token_address(&wallet_address, &token_mint),
+        ::spl_associated_token_account::get_associated_token_address(&wallet_address, &token_mint)
+    );
+}
@@ -1,9 +1,27 @@
+//! The Strike wallet program.
+//!
+//! Everything but the processor, the handlers and the entrypoint builds with
+//! `default-features = false`, which leaves out the `program` feature and the
+//! SPL program crates it needs. That core of account models, instruction
+//! encoding and client helpers only depends on `solana-program`, so signing
+//! backends and dashboards can decode accounts and compute params hashes
+//! without pulling in the program itself.
+
+#[cfg(any(test, feature = "arbitrary"))]
+pub mod arbitrary;
+pub mod client;
+pub mod co_signing;
 pub mod error;
 pub mod instruction;
 pub mod model;
+#[cfg(feature = "program")]
 pub mod processor;
 pub mod serialization_utils;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
 pub mod utils;
 
+#[cfg(feature = "program")]
 mod entrypoint;
+#[cfg(feature = "program")]
 mod handlers;
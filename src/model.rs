@@ -1,5 +1,29 @@
+pub mod account_type;
+pub mod activity;
 pub mod address_book;
+pub mod address_book_page;
+pub mod address_book_update_buffer;
+pub mod approver_delegation;
 pub mod balance_account;
+pub mod capacity;
+pub mod dapp_approval_policy;
+pub mod dapp_inner_programs;
+pub mod dapp_template;
+pub mod dapp_value_limit;
+pub mod diff;
+pub mod governed_parameter;
+pub mod idempotency;
+pub mod manifest;
 pub mod multisig_op;
+pub mod op_state;
+pub mod pending_disposition;
+pub mod pending_ops;
+pub mod policy;
+pub mod program_config;
 pub mod signer;
+pub mod signer_directory;
+pub mod summary;
+pub mod token_mint;
+pub mod viewer;
 pub mod wallet;
+pub mod wallet_export;
@@ -0,0 +1,39 @@
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+
+/// Discriminator stored in the first byte of program-owned state accounts. The
+/// byte is zero while the account is uninitialized, so an initialized account
+/// of one type can never be read as another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountType {
+    Wallet = 1,
+    MultisigOp = 2,
+    SignerDirectory = 3,
+    AddressBookUpdateBuffer = 4,
+    PendingOps = 5,
+    WalletExport = 6,
+    PendingDisposition = 7,
+}
+
+impl AccountType {
+    pub fn to_discriminator(self, is_initialized: bool) -> u8 {
+        if is_initialized {
+            self as u8
+        } else {
+            0
+        }
+    }
+
+    /// Returns whether the account is initialized, failing if it holds a
+    /// different type of account.
+    pub fn from_discriminator(self, discriminator: u8) -> Result<bool, ProgramError> {
+        match discriminator {
+            0 => Ok(false),
+            value if value == self as u8 => Ok(true),
+            _ => {
+                msg!("Account is not a {:?} account", self);
+                Err(ProgramError::InvalidAccountData)
+            }
+        }
+    }
+}
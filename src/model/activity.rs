@@ -0,0 +1,319 @@
+use crate::error::WalletError;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::{Hash, HASH_BYTES};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TransferTotal {
+    pub token_mint: Pubkey,
+    pub count: u32,
+    pub amount: u64,
+}
+
+impl Sealed for TransferTotal {}
+
+impl Pack for TransferTotal {
+    const LEN: usize = PUBKEY_BYTES + 4 + 8;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, TransferTotal::LEN];
+        let (token_mint_dst, count_dst, amount_dst) = mut_array_refs![dst, PUBKEY_BYTES, 4, 8];
+        token_mint_dst.copy_from_slice(self.token_mint.as_ref());
+        *count_dst = self.count.to_le_bytes();
+        *amount_dst = self.amount.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, TransferTotal::LEN];
+        let (token_mint_src, count_src, amount_src) = array_refs![src, PUBKEY_BYTES, 4, 8];
+        Ok(TransferTotal {
+            token_mint: Pubkey::new(token_mint_src),
+            count: u32::from_le_bytes(*count_src),
+            amount: u64::from_le_bytes(*amount_src),
+        })
+    }
+}
+
+fn pack_transfer_totals(transfer_totals: &Vec<TransferTotal>, dst: &mut [u8]) {
+    dst[0] = transfer_totals.len() as u8;
+    dst[1..].fill(0);
+    for (i, total) in transfer_totals.iter().enumerate() {
+        let offset = 1 + i * TransferTotal::LEN;
+        total.pack_into_slice(&mut dst[offset..offset + TransferTotal::LEN]);
+    }
+}
+
+fn unpack_transfer_totals(src: &[u8]) -> Result<Vec<TransferTotal>, ProgramError> {
+    let count = usize::from(src[0]);
+    if count > WalletActivity::MAX_TOKEN_MINTS {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    src[1..1 + count * TransferTotal::LEN]
+        .chunks_exact(TransferTotal::LEN)
+        .map(|chunk| TransferTotal::unpack_from_slice(chunk))
+        .collect()
+}
+
+/// Activity accumulated by a wallet since its last checkpoint.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WalletActivity {
+    pub period_start: i64,
+    pub checkpoint_count: u32,
+    pub config_change_count: u32,
+    pub transfer_totals: Vec<TransferTotal>,
+}
+
+impl WalletActivity {
+    pub const MAX_TOKEN_MINTS: usize = 10;
+    const TRANSFER_TOTALS_LEN: usize = 1 + WalletActivity::MAX_TOKEN_MINTS * TransferTotal::LEN;
+
+    /// Stands in for the token mint of the total that transfers of mints are
+    /// added to once `MAX_TOKEN_MINTS` are tracked in a period. Its amount is
+    /// the sum of the amounts of all those mints.
+    pub fn other_token_mints() -> Pubkey {
+        Pubkey::new_from_array([0xff; PUBKEY_BYTES])
+    }
+
+    /// Adds the transfer to its mint's total. Once the period has totals for
+    /// `MAX_TOKEN_MINTS` mints, transfers of further mints go to a shared
+    /// total in the last slot instead, so that a transfer never fails for
+    /// want of a checkpoint.
+    pub fn record_transfer(&mut self, token_mint: &Pubkey, amount: u64) -> ProgramResult {
+        if let Some(total) = self
+            .transfer_totals
+            .iter_mut()
+            .find(|total| total.token_mint == *token_mint)
+        {
+            total.count = total
+                .count
+                .checked_add(1)
+                .ok_or(WalletError::AmountOverflow)?;
+            total.amount = total
+                .amount
+                .checked_add(amount)
+                .ok_or(WalletError::AmountOverflow)?;
+        } else if self.transfer_totals.len() < WalletActivity::MAX_TOKEN_MINTS {
+            self.transfer_totals.push(TransferTotal {
+                token_mint: *token_mint,
+                count: 1,
+                amount,
+            });
+        } else if let Some(other) = self.transfer_totals.last_mut() {
+            // amounts of different mints don't add up to anything exact, so
+            // the shared total saturates rather than failing the transfer
+            other.token_mint = WalletActivity::other_token_mints();
+            other.count = other.count.saturating_add(1);
+            other.amount = other.amount.saturating_add(amount);
+        }
+        Ok(())
+    }
+
+    pub fn record_config_change(&mut self) {
+        self.config_change_count = self.config_change_count.saturating_add(1);
+    }
+
+    pub fn start_next_period(&mut self, period_start: i64) {
+        self.period_start = period_start;
+        self.checkpoint_count += 1;
+        self.config_change_count = 0;
+        self.transfer_totals = Vec::new();
+    }
+}
+
+impl Sealed for WalletActivity {}
+
+impl Pack for WalletActivity {
+    const LEN: usize = 8 + // period_start
+        4 + // checkpoint_count
+        4 + // config_change_count
+        WalletActivity::TRANSFER_TOTALS_LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, WalletActivity::LEN];
+        let (period_start_dst, checkpoint_count_dst, config_change_count_dst, transfer_totals_dst) =
+            mut_array_refs![dst, 8, 4, 4, WalletActivity::TRANSFER_TOTALS_LEN];
+        *period_start_dst = self.period_start.to_le_bytes();
+        *checkpoint_count_dst = self.checkpoint_count.to_le_bytes();
+        *config_change_count_dst = self.config_change_count.to_le_bytes();
+        pack_transfer_totals(&self.transfer_totals, transfer_totals_dst);
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, WalletActivity::LEN];
+        let (period_start_src, checkpoint_count_src, config_change_count_src, transfer_totals_src) =
+            array_refs![src, 8, 4, 4, WalletActivity::TRANSFER_TOTALS_LEN];
+        Ok(WalletActivity {
+            period_start: i64::from_le_bytes(*period_start_src),
+            checkpoint_count: u32::from_le_bytes(*checkpoint_count_src),
+            config_change_count: u32::from_le_bytes(*config_change_count_src),
+            transfer_totals: unpack_transfer_totals(transfer_totals_src)?,
+        })
+    }
+}
+
+/// Immutable record of a wallet's activity over one reporting period, stored in
+/// a PDA derived from the wallet address and the checkpoint index.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ActivityCheckpoint {
+    pub is_initialized: bool,
+    pub wallet_address: Pubkey,
+    pub checkpoint_index: u32,
+    pub period_start: i64,
+    pub period_end: i64,
+    pub config_change_count: u32,
+    pub transfer_totals: Vec<TransferTotal>,
+    pub state_hash: Hash,
+}
+
+impl ActivityCheckpoint {
+    pub const SEED: &'static [u8] = b"checkpoint";
+
+    pub fn find_address(
+        wallet_address: &Pubkey,
+        checkpoint_index: u32,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                wallet_address.as_ref(),
+                ActivityCheckpoint::SEED,
+                &checkpoint_index.to_le_bytes(),
+            ],
+            program_id,
+        )
+    }
+}
+
+impl Sealed for ActivityCheckpoint {}
+
+impl IsInitialized for ActivityCheckpoint {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for ActivityCheckpoint {
+    const LEN: usize = 1 + // is_initialized
+        PUBKEY_BYTES + // wallet_address
+        4 + // checkpoint_index
+        8 + // period_start
+        8 + // period_end
+        4 + // config_change_count
+        WalletActivity::TRANSFER_TOTALS_LEN +
+        HASH_BYTES; // state_hash
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, ActivityCheckpoint::LEN];
+        let (
+            is_initialized_dst,
+            wallet_address_dst,
+            checkpoint_index_dst,
+            period_start_dst,
+            period_end_dst,
+            config_change_count_dst,
+            transfer_totals_dst,
+            state_hash_dst,
+        ) = mut_array_refs![
+            dst,
+            1,
+            PUBKEY_BYTES,
+            4,
+            8,
+            8,
+            4,
+            WalletActivity::TRANSFER_TOTALS_LEN,
+            HASH_BYTES
+        ];
+        is_initialized_dst[0] = self.is_initialized as u8;
+        wallet_address_dst.copy_from_slice(self.wallet_address.as_ref());
+        *checkpoint_index_dst = self.checkpoint_index.to_le_bytes();
+        *period_start_dst = self.period_start.to_le_bytes();
+        *period_end_dst = self.period_end.to_le_bytes();
+        *config_change_count_dst = self.config_change_count.to_le_bytes();
+        pack_transfer_totals(&self.transfer_totals, transfer_totals_dst);
+        state_hash_dst.copy_from_slice(self.state_hash.as_ref());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, ActivityCheckpoint::LEN];
+        let (
+            is_initialized_src,
+            wallet_address_src,
+            checkpoint_index_src,
+            period_start_src,
+            period_end_src,
+            config_change_count_src,
+            transfer_totals_src,
+            state_hash_src,
+        ) = array_refs![
+            src,
+            1,
+            PUBKEY_BYTES,
+            4,
+            8,
+            8,
+            4,
+            WalletActivity::TRANSFER_TOTALS_LEN,
+            HASH_BYTES
+        ];
+        Ok(ActivityCheckpoint {
+            is_initialized: is_initialized_src[0] == 1,
+            wallet_address: Pubkey::new(wallet_address_src),
+            checkpoint_index: u32::from_le_bytes(*checkpoint_index_src),
+            period_start: i64::from_le_bytes(*period_start_src),
+            period_end: i64::from_le_bytes(*period_end_src),
+            config_change_count: u32::from_le_bytes(*config_change_count_src),
+            transfer_totals: unpack_transfer_totals(transfer_totals_src)?,
+            state_hash: Hash::new_from_array(*state_hash_src),
+        })
+    }
+}
+
+#[test]
+fn test_record_transfer_beyond_max_token_mints() {
+    let mut activity = WalletActivity {
+        period_start: 0,
+        checkpoint_count: 0,
+        config_change_count: 0,
+        transfer_totals: Vec::new(),
+    };
+    let token_mints: Vec<Pubkey> = (0..WalletActivity::MAX_TOKEN_MINTS + 2)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    for token_mint in token_mints.iter() {
+        activity.record_transfer(token_mint, 100).unwrap();
+    }
+    activity.record_transfer(&token_mints[0], 50).unwrap();
+
+    assert_eq!(
+        activity.transfer_totals.len(),
+        WalletActivity::MAX_TOKEN_MINTS
+    );
+    assert_eq!(
+        activity.transfer_totals[0],
+        TransferTotal {
+            token_mint: token_mints[0],
+            count: 2,
+            amount: 150,
+        }
+    );
+    // the last tracked mint and the two after it share a total
+    assert_eq!(
+        activity.transfer_totals[WalletActivity::MAX_TOKEN_MINTS - 1],
+        TransferTotal {
+            token_mint: WalletActivity::other_token_mints(),
+            count: 3,
+            amount: 300,
+        }
+    );
+
+    let mut packed = [0; WalletActivity::LEN];
+    activity.pack_into_slice(&mut packed);
+    assert_eq!(
+        WalletActivity::unpack_from_slice(&packed).unwrap(),
+        activity
+    );
+}
@@ -1,6 +1,7 @@
 use crate::model::wallet::Wallet;
-use crate::utils::Slots;
+use crate::utils::{SlotId, Slots};
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::hash::{hash, Hash, HASH_BYTES};
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{Pack, Sealed};
 use solana_program::pubkey::Pubkey;
@@ -8,6 +9,10 @@ use std::convert::TryFrom;
 
 pub type AddressBook = Slots<AddressBookEntry, { Wallet::MAX_ADDRESS_BOOK_ENTRIES }>;
 pub type DAppBook = Slots<DAppBookEntry, { Wallet::MAX_DAPP_BOOK_ENTRIES }>;
+pub type DestinationTokenMints =
+    Slots<DestinationTokenMint, { Wallet::MAX_DESTINATION_TOKEN_MINTS }>;
+pub type DestinationNamespaces =
+    Slots<DestinationNamespace, { Wallet::MAX_DESTINATION_NAMESPACES }>;
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Copy)]
 pub struct AddressBookEntryNameHash([u8; 32]);
@@ -21,6 +26,11 @@ impl AddressBookEntryNameHash {
         Self::new(&[0; 32])
     }
 
+    /// The hash stored for the given human-readable name.
+    pub fn of_name(name: &str) -> Self {
+        Self(hash(name.as_bytes()).to_bytes())
+    }
+
     pub fn to_bytes(&self) -> &[u8; 32] {
         <&[u8; 32]>::try_from(&self.0[..]).unwrap()
     }
@@ -56,5 +66,304 @@ impl Pack for AddressBookEntry {
     }
 }
 
-pub type DAppBookEntry = AddressBookEntry;
+/// When the entry in each address book slot was added while pending
+/// verification, so that its verification delay runs from then rather than
+/// from when the op verifying it was started.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct PendingAddressBookEntryTimes {
+    added_at: [i64; Wallet::MAX_ADDRESS_BOOK_ENTRIES],
+}
+
+impl Default for PendingAddressBookEntryTimes {
+    fn default() -> Self {
+        PendingAddressBookEntryTimes {
+            added_at: [0; Wallet::MAX_ADDRESS_BOOK_ENTRIES],
+        }
+    }
+}
+
+impl PendingAddressBookEntryTimes {
+    pub fn get(&self, slot_id: SlotId<AddressBookEntry>) -> i64 {
+        self.added_at[slot_id.value]
+    }
+
+    pub fn record(&mut self, slot_id: SlotId<AddressBookEntry>, at: i64) {
+        self.added_at[slot_id.value] = at;
+    }
+
+    pub fn clear(&mut self, slot_id: SlotId<AddressBookEntry>) {
+        self.added_at[slot_id.value] = 0;
+    }
+}
+
+impl Sealed for PendingAddressBookEntryTimes {}
+
+impl Pack for PendingAddressBookEntryTimes {
+    const LEN: usize = Wallet::MAX_ADDRESS_BOOK_ENTRIES * 8;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        for (chunk, at) in dst.chunks_exact_mut(8).zip(self.added_at.iter()) {
+            chunk.copy_from_slice(&at.to_le_bytes());
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let mut times = PendingAddressBookEntryTimes::default();
+        for (at, chunk) in times
+            .added_at
+            .iter_mut()
+            .zip(src[..PendingAddressBookEntryTimes::LEN].chunks_exact(8))
+        {
+            *at = i64::from_le_bytes(*array_ref![chunk, 0, 8]);
+        }
+        Ok(times)
+    }
+}
+
+/// Binds an address book entry to a token mint. Once an entry is bound to
+/// any mints, only those can be transferred to its address; SOL transfers
+/// count as the default pubkey's.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
+pub struct DestinationTokenMint {
+    pub address_book_entry: SlotId<AddressBookEntry>,
+    pub token_mint: Pubkey,
+}
+
+impl Sealed for DestinationTokenMint {}
+
+impl Pack for DestinationTokenMint {
+    const LEN: usize = 1 + 32;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, DestinationTokenMint::LEN];
+        let (address_book_entry_dst, token_mint_dst) = mut_array_refs![dst, 1, 32];
+
+        address_book_entry_dst[0] = self.address_book_entry.value as u8;
+        token_mint_dst.copy_from_slice(self.token_mint.as_ref());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, DestinationTokenMint::LEN];
+        let (address_book_entry_bytes, token_mint_bytes) = array_refs![src, 1, 32];
+
+        Ok(DestinationTokenMint {
+            address_book_entry: SlotId::new(usize::from(address_book_entry_bytes[0])),
+            token_mint: Pubkey::new_from_array(*token_mint_bytes),
+        })
+    }
+}
+
+/// What an address in the address book stands for. Untagged entries are
+/// wallet addresses, which receive SOL directly and SPL tokens through their
+/// associated token accounts.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
+pub enum AddressNamespace {
+    /// The address is a token account, which SPL tokens of its mint are
+    /// transferred to directly. It receives neither SOL nor other mints.
+    TokenAccount,
+    /// The address is a shared deposit address, such as an exchange's, that
+    /// only credits transfers carrying the memo with the given hash.
+    DepositMemo(Hash),
+    /// The address belongs to an exchange, so transfers to it have to carry
+    /// the hash of the travel rule data collected for them. The tag doesn't
+    /// restrict what the address receives, and the requirement holds whatever
+    /// the address's other entries are tagged with.
+    Exchange,
+}
+
+impl AddressNamespace {
+    /// Whether a transfer of the mint (the default pubkey for SOL) with the
+    /// given memo fits the namespace.
+    pub fn accepts(&self, token_mint: &Pubkey, memo_hash: Option<&Hash>) -> bool {
+        match self {
+            AddressNamespace::TokenAccount => *token_mint != Pubkey::default(),
+            AddressNamespace::DepositMemo(deposit_memo_hash) => {
+                memo_hash == Some(deposit_memo_hash)
+            }
+            AddressNamespace::Exchange => true,
+        }
+    }
+}
+
+/// Tags an address book entry with a namespace. Once every entry for an
+/// address is tagged, transfers to it have to fit one of their namespaces.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
+pub struct DestinationNamespace {
+    pub address_book_entry: SlotId<AddressBookEntry>,
+    pub namespace: AddressNamespace,
+}
+
+impl Sealed for DestinationNamespace {}
+
+impl Pack for DestinationNamespace {
+    const LEN: usize = 1 + 1 + HASH_BYTES;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, DestinationNamespace::LEN];
+        let (address_book_entry_dst, kind_dst, memo_hash_dst) =
+            mut_array_refs![dst, 1, 1, HASH_BYTES];
+
+        address_book_entry_dst[0] = self.address_book_entry.value as u8;
+        match self.namespace {
+            AddressNamespace::TokenAccount => {
+                kind_dst[0] = 0;
+                memo_hash_dst.fill(0);
+            }
+            AddressNamespace::DepositMemo(memo_hash) => {
+                kind_dst[0] = 1;
+                memo_hash_dst.copy_from_slice(memo_hash.as_ref());
+            }
+            AddressNamespace::Exchange => {
+                kind_dst[0] = 2;
+                memo_hash_dst.fill(0);
+            }
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, DestinationNamespace::LEN];
+        let (address_book_entry_bytes, kind_bytes, memo_hash_bytes) =
+            array_refs![src, 1, 1, HASH_BYTES];
+
+        Ok(DestinationNamespace {
+            address_book_entry: SlotId::new(usize::from(address_book_entry_bytes[0])),
+            namespace: match kind_bytes[0] {
+                0 => AddressNamespace::TokenAccount,
+                1 => AddressNamespace::DepositMemo(Hash::new_from_array(*memo_hash_bytes)),
+                2 => AddressNamespace::Exchange,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+        })
+    }
+}
+
 pub type DAppBookEntryNameHash = AddressBookEntryNameHash;
+
+/// The instruction data prefixes (typically 8-byte instruction discriminators)
+/// a dapp's instructions are restricted to. A dapp without any is unrestricted.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd, Default)]
+pub struct DAppInstructionPrefixes {
+    count: u8,
+    prefixes: [[u8; DAppInstructionPrefixes::PREFIX_LEN]; DAppInstructionPrefixes::MAX_PREFIXES],
+}
+
+impl DAppInstructionPrefixes {
+    pub const PREFIX_LEN: usize = 8;
+    pub const MAX_PREFIXES: usize = 8;
+
+    pub fn new(prefixes: &[[u8; Self::PREFIX_LEN]]) -> Result<Self, ProgramError> {
+        if prefixes.len() > Self::MAX_PREFIXES {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let mut result = Self::default();
+        result.count = prefixes.len() as u8;
+        result.prefixes[..prefixes.len()].copy_from_slice(prefixes);
+        Ok(result)
+    }
+
+    pub fn as_slice(&self) -> &[[u8; Self::PREFIX_LEN]] {
+        &self.prefixes[..usize::from(self.count)]
+    }
+
+    pub fn is_restricted(&self) -> bool {
+        self.count > 0
+    }
+
+    /// Whether instruction data is allowed, i.e. begins with one of the prefixes
+    /// (or there are no prefixes to begin with).
+    pub fn allow(&self, data: &[u8]) -> bool {
+        !self.is_restricted()
+            || self
+                .as_slice()
+                .iter()
+                .any(|prefix| data.starts_with(prefix))
+    }
+}
+
+impl Sealed for DAppInstructionPrefixes {}
+
+impl Pack for DAppInstructionPrefixes {
+    const LEN: usize = 1 + Self::PREFIX_LEN * Self::MAX_PREFIXES;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[0] = self.count;
+        for (chunk, prefix) in dst[1..Self::LEN]
+            .chunks_exact_mut(Self::PREFIX_LEN)
+            .zip(self.prefixes.iter())
+        {
+            chunk.copy_from_slice(prefix);
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let count = usize::from(src[0]);
+        if count > Self::MAX_PREFIXES {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut result = Self::default();
+        result.count = src[0];
+        for (prefix, chunk) in result.prefixes[..count]
+            .iter_mut()
+            .zip(src[1..Self::LEN].chunks_exact(Self::PREFIX_LEN))
+        {
+            prefix.copy_from_slice(chunk);
+        }
+        Ok(result)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
+pub struct DAppBookEntry {
+    pub address: Pubkey,
+    pub name_hash: DAppBookEntryNameHash,
+    /// Restricts the instructions sent to `address` in dapp transactions.
+    pub instruction_prefixes: DAppInstructionPrefixes,
+}
+
+impl Sealed for DAppBookEntry {}
+
+impl Pack for DAppBookEntry {
+    const LEN: usize = 64 + DAppInstructionPrefixes::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, DAppBookEntry::LEN];
+        let (address_dst, name_hash_dst, instruction_prefixes_dst) =
+            mut_array_refs![dst, 32, 32, DAppInstructionPrefixes::LEN];
+
+        address_dst.copy_from_slice(self.address.as_ref());
+        name_hash_dst.copy_from_slice(self.name_hash.to_bytes());
+        self.instruction_prefixes
+            .pack_into_slice(instruction_prefixes_dst);
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, DAppBookEntry::LEN];
+        let (address_bytes, name_hash_bytes, instruction_prefixes_bytes) =
+            array_refs![src, 32, 32, DAppInstructionPrefixes::LEN];
+
+        Ok(DAppBookEntry {
+            address: Pubkey::new_from_array(*address_bytes),
+            name_hash: DAppBookEntryNameHash::new(name_hash_bytes),
+            instruction_prefixes: DAppInstructionPrefixes::unpack_from_slice(
+                instruction_prefixes_bytes,
+            )?,
+        })
+    }
+}
+
+#[test]
+fn test_dapp_instruction_prefixes() {
+    let prefixes = DAppInstructionPrefixes::new(&[[1; 8], [2; 8]]).unwrap();
+    assert!(prefixes.allow(&[2, 2, 2, 2, 2, 2, 2, 2, 9]));
+    assert!(!prefixes.allow(&[3; 16]));
+    assert!(!prefixes.allow(&[1; 4]));
+    assert!(DAppInstructionPrefixes::default().allow(&[]));
+    assert!(DAppInstructionPrefixes::new(&[[0; 8]; 9]).is_err());
+
+    let mut buf = [0; DAppInstructionPrefixes::LEN];
+    prefixes.pack_into_slice(&mut buf);
+    assert_eq!(
+        DAppInstructionPrefixes::unpack_from_slice(&buf).unwrap(),
+        prefixes
+    );
+}
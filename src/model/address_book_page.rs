@@ -0,0 +1,272 @@
+use crate::error::WalletError;
+use crate::instruction::AddressBookUpdate;
+use crate::model::address_book::{AddressBook, AddressBookEntry, AddressBookEntryNameHash};
+use crate::model::balance_account::{AllowedDestinations, BalanceAccount};
+use crate::model::wallet::Wallet;
+use crate::utils::{GetSlotIds, SlotId};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+/// Additional address book entries of a wallet, stored in a PDA derived from
+/// the wallet address and the page index. Each page has the capacity of the
+/// wallet's own address book and keeps its own whitelist per balance account,
+/// indexed by the balance account's slot in the wallet.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AddressBookPage {
+    pub is_initialized: bool,
+    pub wallet_address: Pubkey,
+    pub page_index: u8,
+    pub bump_seed: u8,
+    pub entries: AddressBook,
+    pub allowed_destinations: Vec<AllowedDestinations>,
+}
+
+impl AddressBookPage {
+    pub const SEED: &'static [u8] = b"address-book-page";
+    const ALLOWED_DESTINATIONS_LEN: usize =
+        AllowedDestinations::STORAGE_SIZE * Wallet::MAX_BALANCE_ACCOUNTS;
+
+    pub fn find_address(
+        wallet_address: &Pubkey,
+        page_index: u8,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                wallet_address.as_ref(),
+                AddressBookPage::SEED,
+                &[page_index],
+            ],
+            program_id,
+        )
+    }
+
+    pub fn new(wallet_address: &Pubkey, page_index: u8, bump_seed: u8) -> AddressBookPage {
+        AddressBookPage {
+            is_initialized: true,
+            wallet_address: *wallet_address,
+            page_index,
+            bump_seed,
+            entries: AddressBook::new(),
+            allowed_destinations: vec![AllowedDestinations::zero(); Wallet::MAX_BALANCE_ACCOUNTS],
+        }
+    }
+
+    /// Returns the initialized pages of the given wallet that are among the
+    /// given accounts. Page addresses are checked against the bump seed stored
+    /// in the account.
+    pub fn from_accounts(
+        program_id: &Pubkey,
+        wallet_address: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> Result<Vec<AddressBookPage>, ProgramError> {
+        let mut pages = Vec::new();
+        for account_info in accounts {
+            if account_info.owner != program_id || account_info.data_len() != AddressBookPage::LEN {
+                continue;
+            }
+            let page = AddressBookPage::unpack_from_slice(&account_info.data.borrow())?;
+            if !page.is_initialized || page.wallet_address != *wallet_address {
+                continue;
+            }
+            let expected_address = Pubkey::create_program_address(
+                &[
+                    wallet_address.as_ref(),
+                    AddressBookPage::SEED,
+                    &[page.page_index],
+                    &[page.bump_seed],
+                ],
+                program_id,
+            )?;
+            if expected_address == *account_info.key {
+                pages.push(page);
+            }
+        }
+        Ok(pages)
+    }
+
+    pub fn destination_allowed(
+        &self,
+        balance_account_slot_id: &SlotId<BalanceAccount>,
+        address: &Pubkey,
+        name_hash: &AddressBookEntryNameHash,
+    ) -> bool {
+        match self.entries.find_id(&AddressBookEntry {
+            address: *address,
+            name_hash: *name_hash,
+        }) {
+            Some(entry_ref) => {
+                self.allowed_destinations[balance_account_slot_id.value].is_enabled(&entry_ref)
+            }
+            None => false,
+        }
+    }
+
+    pub fn validate_update(&self, wallet: &Wallet, update: &AddressBookUpdate) -> ProgramResult {
+        Wallet::validate_address_book_update_size(update)?;
+        let mut self_clone = self.clone();
+        self_clone.update(wallet, update)
+    }
+
+    pub fn update(&mut self, wallet: &Wallet, update: &AddressBookUpdate) -> ProgramResult {
+        if !update.add_destination_token_mints.is_empty()
+            || !update.remove_destination_token_mints.is_empty()
+        {
+            msg!("Address book page entries can't be bound to token mints");
+            return Err(WalletError::InvalidSlot.into());
+        }
+        if !update.add_destination_namespaces.is_empty()
+            || !update.remove_destination_namespaces.is_empty()
+        {
+            msg!("Address book page entries can't be tagged with namespaces");
+            return Err(WalletError::InvalidSlot.into());
+        }
+        if !self
+            .entries
+            .can_be_inserted(&update.add_address_book_entries)
+        {
+            msg!("Failed to add address book entries: at least one of the provided slots is already taken");
+            return Err(WalletError::SlotCannotBeInserted.into());
+        }
+        self.entries.insert_many(&update.add_address_book_entries);
+
+        for whitelist_update in update.balance_account_whitelist_updates.iter() {
+            let (slot_id, balance_account) =
+                wallet.get_balance_account_with_slot_id(&whitelist_update.guid_hash)?;
+            let allowed_destinations = &mut self.allowed_destinations[slot_id.value];
+            for (id, entry) in whitelist_update.remove_allowed_destinations.iter() {
+                if self.entries[*id] == Some(*entry) || self.entries[*id] == None {
+                    allowed_destinations.disable(id);
+                } else {
+                    msg!("Failed to disable transfer destinations: unexpected slot value");
+                    return Err(WalletError::InvalidSlot.into());
+                }
+            }
+            if !self
+                .entries
+                .contains(&whitelist_update.add_allowed_destinations)
+            {
+                msg!("Failed to enable transfer destinations: address book page does not contain one of the given destinations");
+                return Err(WalletError::InvalidSlot.into());
+            }
+            if !whitelist_update.add_allowed_destinations.is_empty()
+                && balance_account.is_whitelist_disabled()
+            {
+                msg!("Cannot add destinations when whitelisting status is Off");
+                return Err(WalletError::WhitelistDisabled.into());
+            }
+            allowed_destinations.enable_many(&whitelist_update.add_allowed_destinations.slot_ids());
+        }
+
+        if !self
+            .entries
+            .can_be_removed(&update.remove_address_book_entries)
+        {
+            msg!("Failed to remove address book entries: at least one of the provided entries is not present in the page");
+            return Err(WalletError::SlotCannotBeRemoved.into());
+        }
+        let slot_ids = update.remove_address_book_entries.slot_ids();
+        if self
+            .allowed_destinations
+            .iter()
+            .any(|allowed_destinations| allowed_destinations.any_enabled(&slot_ids))
+        {
+            msg!("Failed to remove address book entries: at least one address is currently in use");
+            return Err(WalletError::DestinationInUse.into());
+        }
+        self.entries
+            .remove_many(&update.remove_address_book_entries);
+
+        Ok(())
+    }
+}
+
+impl Sealed for AddressBookPage {}
+
+impl IsInitialized for AddressBookPage {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for AddressBookPage {
+    const LEN: usize = 1 + // is_initialized
+        PUBKEY_BYTES + // wallet_address
+        1 + // page_index
+        1 + // bump_seed
+        AddressBook::LEN +
+        AddressBookPage::ALLOWED_DESTINATIONS_LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, AddressBookPage::LEN];
+        let (
+            is_initialized_dst,
+            wallet_address_dst,
+            page_index_dst,
+            bump_seed_dst,
+            entries_dst,
+            allowed_destinations_dst,
+        ) = mut_array_refs![
+            dst,
+            1,
+            PUBKEY_BYTES,
+            1,
+            1,
+            AddressBook::LEN,
+            AddressBookPage::ALLOWED_DESTINATIONS_LEN
+        ];
+
+        is_initialized_dst[0] = self.is_initialized as u8;
+        wallet_address_dst.copy_from_slice(self.wallet_address.as_ref());
+        page_index_dst[0] = self.page_index;
+        bump_seed_dst[0] = self.bump_seed;
+        self.entries.pack_into_slice(entries_dst);
+        allowed_destinations_dst.fill(0);
+        allowed_destinations_dst
+            .chunks_exact_mut(AllowedDestinations::STORAGE_SIZE)
+            .zip(self.allowed_destinations.iter())
+            .for_each(|(chunk, allowed_destinations)| {
+                chunk.copy_from_slice(allowed_destinations.as_bytes())
+            });
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, AddressBookPage::LEN];
+        let (is_initialized, wallet_address, page_index, bump_seed, entries, allowed_destinations) = array_refs![
+            src,
+            1,
+            PUBKEY_BYTES,
+            1,
+            1,
+            AddressBook::LEN,
+            AddressBookPage::ALLOWED_DESTINATIONS_LEN
+        ];
+
+        Ok(AddressBookPage {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            wallet_address: Pubkey::new(wallet_address),
+            page_index: page_index[0],
+            bump_seed: bump_seed[0],
+            entries: AddressBook::unpack_from_slice(entries)?,
+            allowed_destinations: allowed_destinations
+                .chunks_exact(AllowedDestinations::STORAGE_SIZE)
+                .map(|chunk| {
+                    AllowedDestinations::new(*array_ref![
+                        chunk,
+                        0,
+                        AllowedDestinations::STORAGE_SIZE
+                    ])
+                })
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,166 @@
+use crate::error::WalletError;
+use crate::model::account_type::AccountType;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::{hash, Hash, HASH_BYTES};
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+use std::convert::TryFrom;
+
+/// Header of the account a chunked address book update is streamed into.
+/// The packed `AddressBookUpdate` follows the header, so the account is
+/// `LEN` bytes plus the length of the update. The op only commits to the hash
+/// of the update, which is checked against the supplied bytes on finalize.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AddressBookUpdateBuffer {
+    pub is_initialized: bool,
+    pub multisig_op: Pubkey,
+    pub initiator: Pubkey,
+    pub update_hash: Hash,
+    pub update_len: u32,
+    pub supplied_len: u32,
+}
+
+impl AddressBookUpdateBuffer {
+    pub fn new(
+        multisig_op: &Pubkey,
+        initiator: &Pubkey,
+        update_hash: Hash,
+        update_len: u32,
+    ) -> AddressBookUpdateBuffer {
+        AddressBookUpdateBuffer {
+            is_initialized: true,
+            multisig_op: *multisig_op,
+            initiator: *initiator,
+            update_hash,
+            update_len,
+            supplied_len: 0,
+        }
+    }
+
+    pub fn space_required(update_len: usize) -> usize {
+        AddressBookUpdateBuffer::LEN + update_len
+    }
+
+    pub fn hash_update(update_bytes: &[u8]) -> Hash {
+        hash(update_bytes)
+    }
+
+    /// Records a chunk of `chunk_len` bytes written at `offset`. Chunks have to
+    /// be supplied in order and can't run past the end of the update.
+    pub fn supply(&mut self, offset: u32, chunk_len: usize) -> ProgramResult {
+        if offset != self.supplied_len {
+            msg!(
+                "Expected a chunk at offset {}, got one at {}",
+                self.supplied_len,
+                offset
+            );
+            return Err(WalletError::InvalidUpdateChunk.into());
+        }
+        let supplied_len = u32::try_from(chunk_len)
+            .ok()
+            .and_then(|chunk_len| self.supplied_len.checked_add(chunk_len))
+            .filter(|supplied_len| *supplied_len <= self.update_len)
+            .ok_or_else(|| {
+                msg!("Chunk runs past the end of the update");
+                WalletError::InvalidUpdateChunk
+            })?;
+        self.supplied_len = supplied_len;
+        Ok(())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.supplied_len == self.update_len
+    }
+}
+
+impl Sealed for AddressBookUpdateBuffer {}
+
+impl IsInitialized for AddressBookUpdateBuffer {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for AddressBookUpdateBuffer {
+    const LEN: usize = 1 + // is_initialized
+        PUBKEY_BYTES + // multisig_op
+        PUBKEY_BYTES + // initiator
+        HASH_BYTES + // update_hash
+        4 + // update_len
+        4; // supplied_len
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, AddressBookUpdateBuffer::LEN];
+        let (
+            is_initialized_dst,
+            multisig_op_dst,
+            initiator_dst,
+            update_hash_dst,
+            update_len_dst,
+            supplied_len_dst,
+        ) = mut_array_refs![dst, 1, PUBKEY_BYTES, PUBKEY_BYTES, HASH_BYTES, 4, 4];
+
+        is_initialized_dst[0] =
+            AccountType::AddressBookUpdateBuffer.to_discriminator(self.is_initialized);
+        multisig_op_dst.copy_from_slice(self.multisig_op.as_ref());
+        initiator_dst.copy_from_slice(self.initiator.as_ref());
+        update_hash_dst.copy_from_slice(self.update_hash.as_ref());
+        *update_len_dst = self.update_len.to_le_bytes();
+        *supplied_len_dst = self.supplied_len.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, AddressBookUpdateBuffer::LEN];
+        let (
+            is_initialized_src,
+            multisig_op_src,
+            initiator_src,
+            update_hash_src,
+            update_len_src,
+            supplied_len_src,
+        ) = array_refs![src, 1, PUBKEY_BYTES, PUBKEY_BYTES, HASH_BYTES, 4, 4];
+
+        Ok(AddressBookUpdateBuffer {
+            is_initialized: AccountType::AddressBookUpdateBuffer
+                .from_discriminator(is_initialized_src[0])?,
+            multisig_op: Pubkey::new_from_array(*multisig_op_src),
+            initiator: Pubkey::new_from_array(*initiator_src),
+            update_hash: Hash::new_from_array(*update_hash_src),
+            update_len: u32::from_le_bytes(*update_len_src),
+            supplied_len: u32::from_le_bytes(*supplied_len_src),
+        })
+    }
+}
+
+#[test]
+fn test_chunks_are_supplied_in_order() {
+    let mut buffer = AddressBookUpdateBuffer::new(
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        Hash::new_unique(),
+        10,
+    );
+
+    assert_eq!(
+        buffer.supply(4, 4),
+        Err(WalletError::InvalidUpdateChunk.into())
+    );
+    buffer.supply(0, 4).unwrap();
+    assert!(!buffer.is_complete());
+    assert_eq!(
+        buffer.supply(4, 7),
+        Err(WalletError::InvalidUpdateChunk.into())
+    );
+    buffer.supply(4, 6).unwrap();
+    assert!(buffer.is_complete());
+
+    let mut packed = vec![0; AddressBookUpdateBuffer::LEN];
+    buffer.pack_into_slice(&mut packed);
+    assert_eq!(
+        AddressBookUpdateBuffer::unpack_from_slice(&packed).unwrap(),
+        buffer
+    );
+}
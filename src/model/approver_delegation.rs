@@ -0,0 +1,53 @@
+use crate::model::wallet::Wallet;
+use crate::utils::Slots;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{Pack, Sealed};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+use std::time::Duration;
+
+pub type ApproverDelegations = Slots<ApproverDelegation, { Wallet::MAX_APPROVER_DELEGATIONS }>;
+
+/// Lets `delegate` give dispositions in place of `approver` on ops started
+/// before `expires_at`, for as long as the delegation has not expired.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
+pub struct ApproverDelegation {
+    pub approver: Pubkey,
+    pub delegate: Pubkey,
+    pub expires_at: i64,
+}
+
+impl ApproverDelegation {
+    pub const MAX_PERIOD: Duration = Duration::from_secs(60 * 60 * 24 * 30);
+
+    pub fn is_active(&self, now: i64) -> bool {
+        now < self.expires_at
+    }
+}
+
+impl Sealed for ApproverDelegation {}
+
+impl Pack for ApproverDelegation {
+    const LEN: usize = PUBKEY_BYTES + PUBKEY_BYTES + 8;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, ApproverDelegation::LEN];
+        let (approver_dst, delegate_dst, expires_at_dst) =
+            mut_array_refs![dst, PUBKEY_BYTES, PUBKEY_BYTES, 8];
+
+        approver_dst.copy_from_slice(self.approver.as_ref());
+        delegate_dst.copy_from_slice(self.delegate.as_ref());
+        *expires_at_dst = self.expires_at.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, ApproverDelegation::LEN];
+        let (approver, delegate, expires_at) = array_refs![src, PUBKEY_BYTES, PUBKEY_BYTES, 8];
+
+        Ok(ApproverDelegation {
+            approver: Pubkey::new_from_array(*approver),
+            delegate: Pubkey::new_from_array(*delegate),
+            expires_at: i64::from_le_bytes(*expires_at),
+        })
+    }
+}
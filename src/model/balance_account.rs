@@ -1,10 +1,18 @@
+use crate::error::WalletError;
 use crate::model::address_book::{AddressBook, AddressBookEntry};
+use crate::model::dapp_value_limit::DAppValueUsages;
 use crate::model::multisig_op::BooleanSetting;
+use crate::model::policy::PolicyRules;
+use crate::model::token_mint::AllowedTokenMints;
 use crate::model::wallet::Approvers;
 use crate::utils::SlotFlags;
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::hash;
+use solana_program::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{Pack, Sealed};
+use solana_program::pubkey::Pubkey;
 use std::convert::TryFrom;
 use std::time::Duration;
 
@@ -12,6 +20,12 @@ pub type AllowedDestinations = SlotFlags<AddressBookEntry, { AddressBook::FLAGS_
 
 const WHITELIST_SETTING_BIT: u8 = 0;
 const DAPPS_SETTING_BIT: u8 = 1;
+const USE_CONFIG_APPROVERS_SETTING_BIT: u8 = 2;
+const TOKEN_MINT_ALLOWLIST_SETTING_BIT: u8 = 3;
+// set when disabled, so that accounts packed before these settings existed
+// keep transferring
+const SOL_TRANSFERS_DISABLED_BIT: u8 = 4;
+const SPL_TRANSFERS_DISABLED_BIT: u8 = 5;
 
 #[derive(Debug, Clone, Eq, PartialEq, Copy, Ord, PartialOrd)]
 pub struct BalanceAccountGuidHash([u8; 32]);
@@ -28,6 +42,23 @@ impl BalanceAccountGuidHash {
     pub fn to_bytes(&self) -> &[u8] {
         &self.0[..]
     }
+
+    pub const DEPOSIT_ADDRESS_SEED: &'static [u8] = b"deposit";
+
+    /// The balance account's deposit sub-address with the given index. Funds
+    /// sent there are swept into the balance account with
+    /// `ConsolidateDeposits`, so each depositor can be handed an address of
+    /// their own without the wallet having to track it.
+    pub fn find_deposit_address(&self, index: u32, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                self.to_bytes(),
+                BalanceAccountGuidHash::DEPOSIT_ADDRESS_SEED,
+                &index.to_le_bytes(),
+            ],
+            program_id,
+        )
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Copy, Ord, PartialOrd)]
@@ -42,11 +73,46 @@ impl BalanceAccountNameHash {
         Self::new(&[0; 32])
     }
 
+    /// The hash stored for the given human-readable name.
+    pub fn of_name(name: &str) -> Self {
+        Self(hash(name.as_bytes()).to_bytes())
+    }
+
     pub fn to_bytes(&self) -> &[u8; 32] {
         <&[u8; 32]>::try_from(&self.0[..]).unwrap()
     }
 }
 
+/// Allows at most one transfer of at least `min_amount` out of a balance
+/// account per `period`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Ord, PartialOrd)]
+pub struct LargeTransferCooldown {
+    pub min_amount: u64,
+    pub period: Duration,
+}
+
+impl Sealed for LargeTransferCooldown {}
+
+impl Pack for LargeTransferCooldown {
+    const LEN: usize = 8 + 8;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, LargeTransferCooldown::LEN];
+        let (min_amount_dst, period_dst) = mut_array_refs![dst, 8, 8];
+        *min_amount_dst = self.min_amount.to_le_bytes();
+        *period_dst = self.period.as_secs().to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, LargeTransferCooldown::LEN];
+        let (min_amount_src, period_src) = array_refs![src, 8, 8];
+        Ok(LargeTransferCooldown {
+            min_amount: u64::from_le_bytes(*min_amount_src),
+            period: Duration::from_secs(u64::from_le_bytes(*period_src)),
+        })
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Ord, PartialOrd)]
 pub struct BalanceAccount {
     pub guid_hash: BalanceAccountGuidHash,
@@ -57,7 +123,38 @@ pub struct BalanceAccount {
     pub allowed_destinations: AllowedDestinations,
     pub whitelist_enabled: BooleanSetting,
     pub dapps_enabled: BooleanSetting,
+    /// When Off, no SOL can be transferred out of the account.
+    pub sol_transfers_enabled: BooleanSetting,
+    /// When Off, no SPL tokens can be transferred out of the account.
+    pub spl_transfers_enabled: BooleanSetting,
+    pub use_config_approvers: BooleanSetting,
     pub policy_update_locked: bool,
+    pub allowed_token_mints: AllowedTokenMints,
+    /// When On, only SPL tokens of the allowed mints can be transferred.
+    pub token_mint_allowlist_enabled: BooleanSetting,
+    /// Sum of the amounts of transfers initiated but not yet finalized.
+    pub outstanding_transfer_value: u64,
+    /// Cap on `outstanding_transfer_value`; zero means no cap.
+    pub max_outstanding_transfer_value: u64,
+    /// Most lamports a transfer's finalizer can be reimbursed from the balance
+    /// account for the transaction fee; zero means fees are not reimbursed.
+    pub max_finalize_fee_reimbursement: u64,
+    /// Amount tiers, time windows and destination classes that tighten the
+    /// approval policy of ops moving funds out of the account.
+    pub policy_rules: PolicyRules,
+    /// Value moved out by each dapp's transactions in its current limit period.
+    pub dapp_value_usage: DAppValueUsages,
+    /// Bumped by every finalized op that changes the account, so that ops on
+    /// it that started before can tell.
+    pub op_sequence: u64,
+    pub large_transfer_cooldown: Option<LargeTransferCooldown>,
+    /// When the last transfer subject to `large_transfer_cooldown` was
+    /// finalized; zero if there has been none.
+    pub last_large_transfer_at: i64,
+    /// Canonical bump seed of the account's PDA, stored at creation so it
+    /// doesn't have to be searched for again. `None` for accounts created
+    /// before it was stored.
+    pub bump_seed: Option<u8>,
 }
 
 impl Sealed for BalanceAccount {}
@@ -70,7 +167,17 @@ impl Pack for BalanceAccount {
         Approvers::STORAGE_SIZE + // transfer approvers
         AllowedDestinations::STORAGE_SIZE +  // allowed destinations
         1 + // boolean settings
-        1; // policy_update_locked flag
+        1 + // policy_update_locked flag
+        AllowedTokenMints::STORAGE_SIZE + // allowed token mints
+        8 + // outstanding_transfer_value
+        8 + // max_outstanding_transfer_value
+        8 + // max_finalize_fee_reimbursement
+        PolicyRules::STORAGE_SIZE + // policy_rules
+        DAppValueUsages::LEN + // dapp_value_usage
+        8 + // op_sequence
+        1 + LargeTransferCooldown::LEN + // large_transfer_cooldown
+        8 + // last_large_transfer_at
+        1 + 1; // bump_seed
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let dst = array_mut_ref![dst, 0, BalanceAccount::LEN];
@@ -83,6 +190,18 @@ impl Pack for BalanceAccount {
             allowed_destinations_dst,
             boolean_settings_dst,
             policy_update_locked_dst,
+            allowed_token_mints_dst,
+            outstanding_transfer_value_dst,
+            max_outstanding_transfer_value_dst,
+            max_finalize_fee_reimbursement_dst,
+            policy_rules_dst,
+            dapp_value_usage_dst,
+            op_sequence_dst,
+            has_large_transfer_cooldown_dst,
+            large_transfer_cooldown_dst,
+            last_large_transfer_at_dst,
+            has_bump_seed_dst,
+            bump_seed_dst,
         ) = mut_array_refs![
             dst,
             32,
@@ -92,6 +211,18 @@ impl Pack for BalanceAccount {
             Approvers::STORAGE_SIZE,
             AllowedDestinations::STORAGE_SIZE,
             1,
+            1,
+            AllowedTokenMints::STORAGE_SIZE,
+            8,
+            8,
+            8,
+            PolicyRules::STORAGE_SIZE,
+            DAppValueUsages::LEN,
+            8,
+            1,
+            LargeTransferCooldown::LEN,
+            8,
+            1,
             1
         ];
 
@@ -106,7 +237,32 @@ impl Pack for BalanceAccount {
         allowed_destinations_dst.copy_from_slice(self.allowed_destinations.as_bytes());
         boolean_settings_dst[0] |= self.whitelist_enabled.to_u8() << WHITELIST_SETTING_BIT;
         boolean_settings_dst[0] |= self.dapps_enabled.to_u8() << DAPPS_SETTING_BIT;
-        policy_update_locked_dst[0] = if self.policy_update_locked { 1 } else { 0 }
+        boolean_settings_dst[0] |=
+            self.use_config_approvers.to_u8() << USE_CONFIG_APPROVERS_SETTING_BIT;
+        boolean_settings_dst[0] |=
+            self.token_mint_allowlist_enabled.to_u8() << TOKEN_MINT_ALLOWLIST_SETTING_BIT;
+        boolean_settings_dst[0] |=
+            (self.are_sol_transfers_disabled() as u8) << SOL_TRANSFERS_DISABLED_BIT;
+        boolean_settings_dst[0] |=
+            (self.are_spl_transfers_disabled() as u8) << SPL_TRANSFERS_DISABLED_BIT;
+        policy_update_locked_dst[0] = if self.policy_update_locked { 1 } else { 0 };
+        allowed_token_mints_dst.copy_from_slice(self.allowed_token_mints.as_bytes());
+        *outstanding_transfer_value_dst = self.outstanding_transfer_value.to_le_bytes();
+        *max_outstanding_transfer_value_dst = self.max_outstanding_transfer_value.to_le_bytes();
+        *max_finalize_fee_reimbursement_dst = self.max_finalize_fee_reimbursement.to_le_bytes();
+        self.policy_rules.pack_into_slice(policy_rules_dst);
+        self.dapp_value_usage.pack_into_slice(dapp_value_usage_dst);
+        *op_sequence_dst = self.op_sequence.to_le_bytes();
+        if let Some(cooldown) = self.large_transfer_cooldown {
+            has_large_transfer_cooldown_dst[0] = 1;
+            cooldown.pack_into_slice(large_transfer_cooldown_dst);
+        } else {
+            has_large_transfer_cooldown_dst[0] = 0;
+            *large_transfer_cooldown_dst = [0; LargeTransferCooldown::LEN];
+        }
+        *last_large_transfer_at_dst = self.last_large_transfer_at.to_le_bytes();
+        has_bump_seed_dst[0] = self.bump_seed.is_some() as u8;
+        bump_seed_dst[0] = self.bump_seed.unwrap_or_default();
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
@@ -120,6 +276,18 @@ impl Pack for BalanceAccount {
             allowed_destinations_src,
             boolean_settings_src,
             policy_update_locked_src,
+            allowed_token_mints_src,
+            outstanding_transfer_value_src,
+            max_outstanding_transfer_value_src,
+            max_finalize_fee_reimbursement_src,
+            policy_rules_src,
+            dapp_value_usage_src,
+            op_sequence_src,
+            has_large_transfer_cooldown_src,
+            large_transfer_cooldown_src,
+            last_large_transfer_at_src,
+            has_bump_seed_src,
+            bump_seed_src,
         ) = array_refs![
             src,
             32,
@@ -129,6 +297,18 @@ impl Pack for BalanceAccount {
             Approvers::STORAGE_SIZE,
             AllowedDestinations::STORAGE_SIZE,
             1,
+            1,
+            AllowedTokenMints::STORAGE_SIZE,
+            8,
+            8,
+            8,
+            PolicyRules::STORAGE_SIZE,
+            DAppValueUsages::LEN,
+            8,
+            1,
+            LargeTransferCooldown::LEN,
+            8,
+            1,
             1
         ];
 
@@ -147,16 +327,66 @@ impl Pack for BalanceAccount {
             dapps_enabled: BooleanSetting::from_u8(
                 boolean_settings_src[0] & (1 << DAPPS_SETTING_BIT),
             ),
+            sol_transfers_enabled: BooleanSetting::from_u8(
+                (boolean_settings_src[0] & (1 << SOL_TRANSFERS_DISABLED_BIT) == 0) as u8,
+            ),
+            spl_transfers_enabled: BooleanSetting::from_u8(
+                (boolean_settings_src[0] & (1 << SPL_TRANSFERS_DISABLED_BIT) == 0) as u8,
+            ),
+            use_config_approvers: BooleanSetting::from_u8(
+                boolean_settings_src[0] & (1 << USE_CONFIG_APPROVERS_SETTING_BIT),
+            ),
             policy_update_locked: if policy_update_locked_src[0] == 1 {
                 true
             } else {
                 false
             },
+            allowed_token_mints: AllowedTokenMints::new(*allowed_token_mints_src),
+            token_mint_allowlist_enabled: BooleanSetting::from_u8(
+                boolean_settings_src[0] & (1 << TOKEN_MINT_ALLOWLIST_SETTING_BIT),
+            ),
+            outstanding_transfer_value: u64::from_le_bytes(*outstanding_transfer_value_src),
+            max_outstanding_transfer_value: u64::from_le_bytes(*max_outstanding_transfer_value_src),
+            max_finalize_fee_reimbursement: u64::from_le_bytes(*max_finalize_fee_reimbursement_src),
+            policy_rules: PolicyRules::unpack_from_slice(policy_rules_src)?,
+            dapp_value_usage: DAppValueUsages::unpack_from_slice(dapp_value_usage_src)?,
+            op_sequence: u64::from_le_bytes(*op_sequence_src),
+            large_transfer_cooldown: if has_large_transfer_cooldown_src[0] == 1 {
+                Some(LargeTransferCooldown::unpack_from_slice(
+                    large_transfer_cooldown_src,
+                )?)
+            } else {
+                None
+            },
+            last_large_transfer_at: i64::from_le_bytes(*last_large_transfer_at_src),
+            bump_seed: match has_bump_seed_src[0] {
+                0 => None,
+                1 => Some(bump_seed_src[0]),
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
         })
     }
 }
 
 impl BalanceAccount {
+    /// The account's PDA and bump seed. The stored bump seed only takes one
+    /// hash to check, so the search is left to accounts without one.
+    pub fn find_address(&self, program_id: &Pubkey) -> Result<(Pubkey, u8), ProgramError> {
+        match self.bump_seed {
+            Some(bump_seed) => Ok((
+                Pubkey::create_program_address(
+                    &[self.guid_hash.to_bytes(), &[bump_seed]],
+                    program_id,
+                )?,
+                bump_seed,
+            )),
+            None => Ok(Pubkey::find_program_address(
+                &[self.guid_hash.to_bytes()],
+                program_id,
+            )),
+        }
+    }
+
     pub fn is_whitelist_disabled(&self) -> bool {
         return self.whitelist_enabled == BooleanSetting::Off;
     }
@@ -165,7 +395,143 @@ impl BalanceAccount {
         return self.dapps_enabled == BooleanSetting::Off;
     }
 
+    pub fn are_sol_transfers_disabled(&self) -> bool {
+        return self.sol_transfers_enabled == BooleanSetting::Off;
+    }
+
+    pub fn are_spl_transfers_disabled(&self) -> bool {
+        return self.spl_transfers_enabled == BooleanSetting::Off;
+    }
+
+    /// Fails if transfers of the given token mint out of the account are
+    /// disabled, the system program standing in for SOL.
+    pub fn validate_transfers_enabled(&self, token_mint: &Pubkey) -> ProgramResult {
+        let (asset, disabled) = if *token_mint == Pubkey::default() {
+            ("SOL", self.are_sol_transfers_disabled())
+        } else {
+            ("SPL token", self.are_spl_transfers_disabled())
+        };
+        if disabled {
+            msg!("{} transfers are disabled for this balance account", asset);
+            return Err(WalletError::TransfersDisabled.into());
+        }
+        Ok(())
+    }
+
+    pub fn uses_config_approvers(&self) -> bool {
+        return self.use_config_approvers == BooleanSetting::On;
+    }
+
+    pub fn is_token_mint_allowlist_enabled(&self) -> bool {
+        return self.token_mint_allowlist_enabled == BooleanSetting::On;
+    }
+
+    /// Returns the outstanding transfer value once a transfer of the given
+    /// amount is initiated, or an error if that would exceed the cap.
+    pub fn outstanding_transfer_value_after(&self, amount: u64) -> Result<u64, ProgramError> {
+        let outstanding_transfer_value = self
+            .outstanding_transfer_value
+            .checked_add(amount)
+            .ok_or(WalletError::AmountOverflow)?;
+        if self.max_outstanding_transfer_value > 0
+            && outstanding_transfer_value > self.max_outstanding_transfer_value
+        {
+            msg!(
+                "Outstanding transfer value {} would exceed the cap of {}",
+                outstanding_transfer_value,
+                self.max_outstanding_transfer_value
+            );
+            return Err(WalletError::OutstandingTransferValueExceeded.into());
+        }
+        Ok(outstanding_transfer_value)
+    }
+
     pub fn has_whitelisted_destinations(&self) -> bool {
         return self.allowed_destinations.count_enabled() > 0;
     }
+
+    /// Fails if a transfer of the given amount at `now` would fall within the
+    /// large transfer cooldown started by the last one.
+    pub fn validate_large_transfer(&self, amount: u64, now: i64) -> ProgramResult {
+        if let Some(cooldown) = self.large_transfer_cooldown {
+            if amount >= cooldown.min_amount && self.last_large_transfer_at != 0 {
+                let cooldown_ends_at = self
+                    .last_large_transfer_at
+                    .saturating_add(cooldown.period.as_secs() as i64);
+                if now < cooldown_ends_at {
+                    msg!(
+                        "Transfers of {} or more are not allowed until {}",
+                        cooldown.min_amount,
+                        cooldown_ends_at
+                    );
+                    return Err(WalletError::LargeTransferCooldown.into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates a transfer of the given amount being finalized at `now` and,
+    /// if it is subject to the large transfer cooldown, starts a new one.
+    pub fn record_large_transfer(&mut self, amount: u64, now: i64) -> ProgramResult {
+        self.validate_large_transfer(amount, now)?;
+        if let Some(cooldown) = self.large_transfer_cooldown {
+            if amount >= cooldown.min_amount {
+                self.last_large_transfer_at = now;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_large_transfer_cooldown() {
+    let mut balance_account = BalanceAccount::unpack_from_slice(&[0; BalanceAccount::LEN]).unwrap();
+    assert_eq!(balance_account.large_transfer_cooldown, None);
+    assert_eq!(balance_account.record_large_transfer(u64::MAX, 100), Ok(()));
+    assert_eq!(balance_account.last_large_transfer_at, 0);
+
+    balance_account.large_transfer_cooldown = Some(LargeTransferCooldown {
+        min_amount: 1000,
+        period: Duration::from_secs(60),
+    });
+    let mut bytes = [0; BalanceAccount::LEN];
+    balance_account.pack_into_slice(&mut bytes);
+    assert_eq!(
+        BalanceAccount::unpack_from_slice(&bytes).unwrap(),
+        balance_account
+    );
+
+    assert_eq!(balance_account.record_large_transfer(999, 100), Ok(()));
+    assert_eq!(balance_account.last_large_transfer_at, 0);
+    assert_eq!(balance_account.record_large_transfer(1000, 100), Ok(()));
+    assert_eq!(balance_account.last_large_transfer_at, 100);
+    assert_eq!(balance_account.record_large_transfer(999, 120), Ok(()));
+    assert_eq!(
+        balance_account.record_large_transfer(1000, 159),
+        Err(WalletError::LargeTransferCooldown.into())
+    );
+    assert_eq!(balance_account.last_large_transfer_at, 100);
+    assert_eq!(balance_account.record_large_transfer(5000, 160), Ok(()));
+    assert_eq!(balance_account.last_large_transfer_at, 160);
+}
+
+#[test]
+fn test_stored_bump_seed() {
+    let program_id = Pubkey::new_unique();
+    let mut balance_account = BalanceAccount::unpack_from_slice(&[0; BalanceAccount::LEN]).unwrap();
+    balance_account.guid_hash = BalanceAccountGuidHash::new(&[7; 32]);
+    assert_eq!(balance_account.bump_seed, None);
+    let derived =
+        Pubkey::find_program_address(&[balance_account.guid_hash.to_bytes()], &program_id);
+    assert_eq!(balance_account.find_address(&program_id), Ok(derived));
+
+    balance_account.bump_seed = Some(derived.1);
+    let mut bytes = [0; BalanceAccount::LEN];
+    balance_account.pack_into_slice(&mut bytes);
+    assert_eq!(
+        BalanceAccount::unpack_from_slice(&bytes).unwrap(),
+        balance_account
+    );
+    assert_eq!(balance_account.find_address(&program_id), Ok(derived));
 }
@@ -0,0 +1,129 @@
+use crate::model::wallet::Wallet;
+use solana_program::hash::Hash;
+
+/// How much of one of the wallet's fixed-size collections is in use.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CapacityUsage {
+    pub collection: String,
+    pub used: usize,
+    pub capacity: usize,
+}
+
+impl CapacityUsage {
+    fn new(collection: impl Into<String>, used: usize, capacity: usize) -> Self {
+        CapacityUsage {
+            collection: collection.into(),
+            used,
+            capacity,
+        }
+    }
+
+    pub fn free(&self) -> usize {
+        self.capacity.saturating_sub(self.used)
+    }
+
+    /// The share of the capacity in use, rounded down.
+    pub fn percent_used(&self) -> usize {
+        self.used * 100 / self.capacity
+    }
+
+    pub fn is_at_or_past(&self, threshold_percent: u8) -> bool {
+        self.used * 100 >= self.capacity * usize::from(threshold_percent)
+    }
+}
+
+impl Wallet {
+    /// The usage of each of the wallet's fixed-size collections, including
+    /// each balance account's whitelist, so that wallets nearing a limit can
+    /// be moved to a larger tier before an add fails.
+    pub fn capacity_usage(&self) -> Vec<CapacityUsage> {
+        let mut usage = vec![
+            CapacityUsage::new(
+                "signers",
+                self.signers.filled_slots().len(),
+                Wallet::MAX_SIGNERS,
+            ),
+            CapacityUsage::new(
+                "address book",
+                self.address_book.filled_slots().len(),
+                Wallet::MAX_ADDRESS_BOOK_ENTRIES,
+            ),
+            CapacityUsage::new(
+                "balance accounts",
+                self.balance_accounts.filled_slots().len(),
+                Wallet::MAX_BALANCE_ACCOUNTS,
+            ),
+            CapacityUsage::new(
+                "dapp book",
+                self.dapp_book.filled_slots().len(),
+                Wallet::MAX_DAPP_BOOK_ENTRIES,
+            ),
+            CapacityUsage::new(
+                "token mints",
+                self.token_mint_book.filled_slots().len(),
+                Wallet::MAX_TOKEN_MINTS,
+            ),
+            CapacityUsage::new(
+                "approver delegations",
+                self.approver_delegations.filled_slots().len(),
+                Wallet::MAX_APPROVER_DELEGATIONS,
+            ),
+            CapacityUsage::new(
+                "viewers",
+                self.viewers.filled_slots().len(),
+                Wallet::MAX_VIEWERS,
+            ),
+            CapacityUsage::new(
+                "destination token mints",
+                self.destination_token_mints.filled_slots().len(),
+                Wallet::MAX_DESTINATION_TOKEN_MINTS,
+            ),
+            CapacityUsage::new(
+                "destination namespaces",
+                self.destination_namespaces.filled_slots().len(),
+                Wallet::MAX_DESTINATION_NAMESPACES,
+            ),
+        ];
+        for (_, balance_account) in self.balance_accounts.filled_slots() {
+            usage.push(CapacityUsage::new(
+                format!(
+                    "whitelist of balance account {}",
+                    Hash::new(balance_account.guid_hash.to_bytes())
+                ),
+                balance_account.allowed_destinations.count_enabled(),
+                Wallet::MAX_ADDRESS_BOOK_ENTRIES,
+            ));
+        }
+        usage
+    }
+}
+
+#[test]
+fn test_capacity_usage() {
+    use crate::model::signer::Signer;
+    use crate::utils::SlotId;
+    use solana_program::program_pack::Pack;
+    use solana_program::pubkey::Pubkey;
+
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    for i in 0..18 {
+        wallet
+            .signers
+            .insert(SlotId::new(i), Signer::new(Pubkey::new_unique()));
+    }
+
+    let usage = wallet.capacity_usage();
+    let signers = usage
+        .iter()
+        .find(|usage| usage.collection == "signers")
+        .unwrap();
+    assert_eq!(signers.used, 18);
+    assert_eq!(signers.free(), Wallet::MAX_SIGNERS - 18);
+    assert_eq!(signers.percent_used(), 75);
+    assert!(signers.is_at_or_past(75));
+    assert!(!signers.is_at_or_past(76));
+    assert!(usage
+        .iter()
+        .filter(|usage| usage.collection != "signers")
+        .all(|usage| usage.used == 0 && !usage.is_at_or_past(1)));
+}
@@ -0,0 +1,48 @@
+use crate::model::address_book::DAppBookEntry;
+use crate::model::wallet::{Approvers, Wallet};
+use crate::utils::{SlotId, Slots};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{Pack, Sealed};
+
+/// Approval policies of the dapp in the same slot of the dapp book. A dapp
+/// without one is approved under the balance account's transfer policy.
+pub type DAppApprovalPolicies = Slots<DAppApprovalPolicy, { Wallet::MAX_DAPP_BOOK_ENTRIES }>;
+
+/// Who approves a dapp's transactions, e.g. the signers of a DeFi desk, in
+/// place of the balance account's transfer approvers and quorum.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Ord, PartialOrd)]
+pub struct DAppApprovalPolicy {
+    pub approvers: Approvers,
+    pub approvals_required: u8,
+}
+
+impl DAppApprovalPolicy {
+    /// The approval policy applying to the dapp in the given dapp book slot.
+    pub fn slot_id(dapp: SlotId<DAppBookEntry>) -> SlotId<DAppApprovalPolicy> {
+        SlotId::new(dapp.value)
+    }
+}
+
+impl Sealed for DAppApprovalPolicy {}
+
+impl Pack for DAppApprovalPolicy {
+    const LEN: usize = Approvers::STORAGE_SIZE + 1;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, DAppApprovalPolicy::LEN];
+        let (approvers_dst, approvals_required_dst) =
+            mut_array_refs![dst, Approvers::STORAGE_SIZE, 1];
+        approvers_dst.copy_from_slice(self.approvers.as_bytes());
+        approvals_required_dst[0] = self.approvals_required;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, DAppApprovalPolicy::LEN];
+        let (approvers_src, approvals_required_src) = array_refs![src, Approvers::STORAGE_SIZE, 1];
+        Ok(DAppApprovalPolicy {
+            approvers: Approvers::new(*approvers_src),
+            approvals_required: approvals_required_src[0],
+        })
+    }
+}
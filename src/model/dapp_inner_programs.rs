@@ -0,0 +1,113 @@
+use crate::model::address_book::DAppBookEntry;
+use crate::model::wallet::Wallet;
+use crate::utils::{SlotId, Slots};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{Pack, Sealed};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+/// The programs the dapp in the same slot of the dapp book may be handed as
+/// accounts, and so invoke. A dapp without an entry may be handed any program.
+pub type DAppInnerProgramSets = Slots<DAppInnerPrograms, { Wallet::MAX_DAPP_BOOK_ENTRIES }>;
+
+/// Executable accounts a dapp's instructions may reference besides the dapp
+/// itself. An empty set allows none.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd, Default)]
+pub struct DAppInnerPrograms {
+    count: u8,
+    program_ids: [Pubkey; DAppInnerPrograms::MAX_INNER_PROGRAMS],
+}
+
+impl DAppInnerPrograms {
+    pub const MAX_INNER_PROGRAMS: usize = 4;
+
+    /// The inner programs applying to the dapp in the given dapp book slot.
+    pub fn slot_id(dapp: SlotId<DAppBookEntry>) -> SlotId<DAppInnerPrograms> {
+        SlotId::new(dapp.value)
+    }
+
+    pub fn new(program_ids: &[Pubkey]) -> Result<DAppInnerPrograms, ProgramError> {
+        if program_ids.len() > DAppInnerPrograms::MAX_INNER_PROGRAMS {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let mut inner_programs = DAppInnerPrograms::default();
+        inner_programs.program_ids[..program_ids.len()].copy_from_slice(program_ids);
+        inner_programs.count = program_ids.len() as u8;
+        Ok(inner_programs)
+    }
+
+    pub fn as_slice(&self) -> &[Pubkey] {
+        &self.program_ids[..usize::from(self.count)]
+    }
+
+    pub fn allow(&self, program_id: &Pubkey) -> bool {
+        self.as_slice().contains(program_id)
+    }
+}
+
+impl Sealed for DAppInnerPrograms {}
+
+impl Pack for DAppInnerPrograms {
+    const LEN: usize = 1 + PUBKEY_BYTES * DAppInnerPrograms::MAX_INNER_PROGRAMS;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, DAppInnerPrograms::LEN];
+        let (count_dst, program_ids_dst) =
+            mut_array_refs![dst, 1, PUBKEY_BYTES * DAppInnerPrograms::MAX_INNER_PROGRAMS];
+        count_dst[0] = self.count;
+        for (program_id, chunk) in self
+            .program_ids
+            .iter()
+            .zip(program_ids_dst.chunks_exact_mut(PUBKEY_BYTES))
+        {
+            chunk.copy_from_slice(program_id.as_ref());
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, DAppInnerPrograms::LEN];
+        let (count_src, program_ids_src) =
+            array_refs![src, 1, PUBKEY_BYTES * DAppInnerPrograms::MAX_INNER_PROGRAMS];
+        let count = count_src[0];
+        if usize::from(count) > DAppInnerPrograms::MAX_INNER_PROGRAMS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut inner_programs = DAppInnerPrograms {
+            count,
+            ..DAppInnerPrograms::default()
+        };
+        for (program_id, chunk) in inner_programs
+            .program_ids
+            .iter_mut()
+            .zip(program_ids_src.chunks_exact(PUBKEY_BYTES))
+        {
+            *program_id = Pubkey::new(chunk);
+        }
+        Ok(inner_programs)
+    }
+}
+
+#[test]
+fn test_dapp_inner_programs_pack() {
+    let program_ids = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let inner_programs = DAppInnerPrograms::new(&program_ids).unwrap();
+    assert!(inner_programs.allow(&program_ids[1]));
+    assert!(!inner_programs.allow(&Pubkey::default()));
+
+    let mut bytes = [0; DAppInnerPrograms::LEN];
+    inner_programs.pack_into_slice(&mut bytes);
+    assert_eq!(
+        DAppInnerPrograms::unpack_from_slice(&bytes).unwrap(),
+        inner_programs
+    );
+
+    bytes[0] = DAppInnerPrograms::MAX_INNER_PROGRAMS as u8 + 1;
+    assert_eq!(
+        DAppInnerPrograms::unpack_from_slice(&bytes),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert_eq!(
+        DAppInnerPrograms::new(&[Pubkey::new_unique(); 5]),
+        Err(ProgramError::InvalidArgument)
+    );
+}
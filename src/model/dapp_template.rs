@@ -0,0 +1,294 @@
+use crate::error::WalletError;
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::wallet::Wallet;
+use crate::utils::Slots;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::{hash, Hash, HASH_BYTES};
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{Pack, Sealed};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+use std::convert::TryInto;
+
+/// Instruction templates approved for execution by the assistant.
+pub type DAppTemplates = Slots<DAppTemplate, { Wallet::MAX_DAPP_TEMPLATES }>;
+
+/// A little-endian u64 in a template's instruction data that instances may
+/// set to any value up to `max_value`, such as the amount of a deposit.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Ord, PartialOrd, Default)]
+pub struct DAppTemplateField {
+    pub offset: u16,
+    pub max_value: u64,
+}
+
+impl DAppTemplateField {
+    const LEN: usize = 2 + 8;
+
+    fn range(&self) -> std::ops::Range<usize> {
+        usize::from(self.offset)..usize::from(self.offset) + 8
+    }
+
+    fn value(&self, data: &[u8]) -> u64 {
+        u64::from_le_bytes(data[self.range()].try_into().unwrap())
+    }
+}
+
+/// An instruction a balance account can send without an op of its own, once
+/// approved. Instances have to match the template's program, accounts and
+/// data exactly, except for the fields, which only have to stay within their
+/// caps. The accounts and the data outside the fields are kept as hashes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Ord, PartialOrd)]
+pub struct DAppTemplate {
+    pub account_guid_hash: BalanceAccountGuidHash,
+    pub program_id: Pubkey,
+    pub accounts_hash: Hash,
+    pub data_len: u16,
+    /// Hash of the instruction data with every field zeroed out.
+    pub data_hash: Hash,
+    field_count: u8,
+    fields: [DAppTemplateField; DAppTemplate::MAX_FIELDS],
+}
+
+impl DAppTemplate {
+    pub const MAX_FIELDS: usize = 4;
+
+    /// The template of which `instruction` is an instance, where the values
+    /// of the given fields don't matter.
+    pub fn new(
+        account_guid_hash: BalanceAccountGuidHash,
+        instruction: &Instruction,
+        fields: &[DAppTemplateField],
+    ) -> Result<DAppTemplate, ProgramError> {
+        if fields.len() > Self::MAX_FIELDS || instruction.data.len() > usize::from(u16::MAX) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        for (i, field) in fields.iter().enumerate() {
+            if field.range().end > instruction.data.len()
+                || fields[..i].iter().any(|other| {
+                    other.range().start < field.range().end
+                        && field.range().start < other.range().end
+                })
+            {
+                msg!("Template fields must lie within the data and not overlap");
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+        let mut template = DAppTemplate {
+            account_guid_hash,
+            program_id: instruction.program_id,
+            accounts_hash: Self::hash_accounts(&instruction.accounts),
+            data_len: instruction.data.len() as u16,
+            data_hash: Hash::default(),
+            field_count: fields.len() as u8,
+            fields: [DAppTemplateField::default(); Self::MAX_FIELDS],
+        };
+        template.fields[..fields.len()].copy_from_slice(fields);
+        template.data_hash = template.hash_fixed_data(&instruction.data);
+        Ok(template)
+    }
+
+    pub fn fields(&self) -> &[DAppTemplateField] {
+        &self.fields[..usize::from(self.field_count)]
+    }
+
+    /// Fails unless the instruction is an instance of the template with every
+    /// field within its cap.
+    pub fn validate_instance(&self, instruction: &Instruction) -> ProgramResult {
+        if instruction.program_id != self.program_id
+            || Self::hash_accounts(&instruction.accounts) != self.accounts_hash
+            || instruction.data.len() != usize::from(self.data_len)
+            || self.hash_fixed_data(&instruction.data) != self.data_hash
+        {
+            msg!("Instruction is not an instance of the template");
+            return Err(WalletError::DAppTemplateMismatch.into());
+        }
+        for field in self.fields() {
+            let value = field.value(&instruction.data);
+            if value > field.max_value {
+                msg!(
+                    "Template field at offset {} is {}, capped at {}",
+                    field.offset,
+                    value,
+                    field.max_value
+                );
+                return Err(WalletError::DAppTemplateCapExceeded.into());
+            }
+        }
+        Ok(())
+    }
+
+    fn hash_accounts(accounts: &[AccountMeta]) -> Hash {
+        let mut bytes = Vec::with_capacity(accounts.len() * (1 + PUBKEY_BYTES));
+        for account in accounts {
+            bytes.push((account.is_writable as u8) | (account.is_signer as u8) << 1);
+            bytes.extend_from_slice(account.pubkey.as_ref());
+        }
+        hash(&bytes)
+    }
+
+    fn hash_fixed_data(&self, data: &[u8]) -> Hash {
+        let mut fixed_data = data.to_vec();
+        for field in self.fields() {
+            fixed_data[field.range()].fill(0);
+        }
+        hash(&fixed_data)
+    }
+}
+
+impl Sealed for DAppTemplate {}
+
+impl Pack for DAppTemplate {
+    const LEN: usize = 32 + // account_guid_hash
+        PUBKEY_BYTES + // program_id
+        HASH_BYTES + // accounts_hash
+        2 + // data_len
+        HASH_BYTES + // data_hash
+        1 + // field_count
+        DAppTemplate::MAX_FIELDS * DAppTemplateField::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, DAppTemplate::LEN];
+        let (
+            account_guid_hash_dst,
+            program_id_dst,
+            accounts_hash_dst,
+            data_len_dst,
+            data_hash_dst,
+            field_count_dst,
+            fields_dst,
+        ) = mut_array_refs![
+            dst,
+            32,
+            PUBKEY_BYTES,
+            HASH_BYTES,
+            2,
+            HASH_BYTES,
+            1,
+            DAppTemplate::MAX_FIELDS * DAppTemplateField::LEN
+        ];
+
+        account_guid_hash_dst.copy_from_slice(self.account_guid_hash.to_bytes());
+        program_id_dst.copy_from_slice(self.program_id.as_ref());
+        accounts_hash_dst.copy_from_slice(self.accounts_hash.as_ref());
+        *data_len_dst = self.data_len.to_le_bytes();
+        data_hash_dst.copy_from_slice(self.data_hash.as_ref());
+        field_count_dst[0] = self.field_count;
+        for (field, chunk) in self
+            .fields
+            .iter()
+            .zip(fields_dst.chunks_exact_mut(DAppTemplateField::LEN))
+        {
+            chunk[..2].copy_from_slice(&field.offset.to_le_bytes());
+            chunk[2..].copy_from_slice(&field.max_value.to_le_bytes());
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, DAppTemplate::LEN];
+        let (
+            account_guid_hash,
+            program_id,
+            accounts_hash,
+            data_len,
+            data_hash,
+            field_count,
+            fields,
+        ) = array_refs![
+            src,
+            32,
+            PUBKEY_BYTES,
+            HASH_BYTES,
+            2,
+            HASH_BYTES,
+            1,
+            DAppTemplate::MAX_FIELDS * DAppTemplateField::LEN
+        ];
+        if usize::from(field_count[0]) > DAppTemplate::MAX_FIELDS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let mut template = DAppTemplate {
+            account_guid_hash: BalanceAccountGuidHash::new(account_guid_hash),
+            program_id: Pubkey::new_from_array(*program_id),
+            accounts_hash: Hash::new_from_array(*accounts_hash),
+            data_len: u16::from_le_bytes(*data_len),
+            data_hash: Hash::new_from_array(*data_hash),
+            field_count: field_count[0],
+            fields: [DAppTemplateField::default(); DAppTemplate::MAX_FIELDS],
+        };
+        for (field, chunk) in template
+            .fields
+            .iter_mut()
+            .zip(fields.chunks_exact(DAppTemplateField::LEN))
+        {
+            *field = DAppTemplateField {
+                offset: u16::from_le_bytes(chunk[..2].try_into().unwrap()),
+                max_value: u64::from_le_bytes(chunk[2..].try_into().unwrap()),
+            };
+        }
+        if template
+            .fields()
+            .iter()
+            .any(|field| field.range().end > usize::from(template.data_len))
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(template)
+    }
+}
+
+#[test]
+fn test_dapp_template_instances() {
+    let account_guid_hash = BalanceAccountGuidHash::new(&[1; 32]);
+    let program_id = Pubkey::new_unique();
+    let accounts = vec![
+        AccountMeta::new(Pubkey::new_unique(), true),
+        AccountMeta::new_readonly(Pubkey::new_unique(), false),
+    ];
+    let instance = |amount: u64, tag: u8| Instruction {
+        program_id,
+        accounts: accounts.clone(),
+        data: [&[tag][..], &amount.to_le_bytes()[..]].concat(),
+    };
+    let template = DAppTemplate::new(
+        account_guid_hash,
+        &instance(0, 7),
+        &[DAppTemplateField {
+            offset: 1,
+            max_value: 1000,
+        }],
+    )
+    .unwrap();
+
+    assert_eq!(template.validate_instance(&instance(1000, 7)), Ok(()));
+    assert_eq!(
+        template.validate_instance(&instance(1001, 7)),
+        Err(WalletError::DAppTemplateCapExceeded.into())
+    );
+    assert_eq!(
+        template.validate_instance(&instance(5, 8)),
+        Err(WalletError::DAppTemplateMismatch.into())
+    );
+    let mut other_accounts = instance(5, 7);
+    other_accounts.accounts[1].is_writable = true;
+    assert_eq!(
+        template.validate_instance(&other_accounts),
+        Err(WalletError::DAppTemplateMismatch.into())
+    );
+
+    assert!(DAppTemplate::new(
+        account_guid_hash,
+        &instance(0, 7),
+        &[DAppTemplateField {
+            offset: 2,
+            max_value: 1000,
+        }],
+    )
+    .is_err());
+
+    let mut packed = vec![0; DAppTemplate::LEN];
+    template.pack_into_slice(&mut packed);
+    assert_eq!(DAppTemplate::unpack_from_slice(&packed), Ok(template));
+}
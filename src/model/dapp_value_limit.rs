@@ -0,0 +1,178 @@
+use crate::error::WalletError;
+use crate::model::address_book::DAppBookEntry;
+use crate::model::wallet::Wallet;
+use crate::utils::{SlotId, Slots};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{Pack, Sealed};
+use std::time::Duration;
+
+/// Limits on the value moved out of a balance account by the dapp in the same
+/// slot of the dapp book. A dapp without a limit is unlimited.
+pub type DAppValueLimits = Slots<DAppValueLimit, { Wallet::MAX_DAPP_BOOK_ENTRIES }>;
+
+/// The most a dapp's transactions can move out of a balance account in each
+/// period. Value is nominal: lamports and token amounts of any mint are
+/// simply added up.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
+pub struct DAppValueLimit {
+    pub max_value: u64,
+    pub period: Duration,
+}
+
+impl DAppValueLimit {
+    /// The limit applying to the dapp in the given dapp book slot.
+    pub fn slot_id(dapp: SlotId<DAppBookEntry>) -> SlotId<DAppValueLimit> {
+        SlotId::new(dapp.value)
+    }
+}
+
+impl Sealed for DAppValueLimit {}
+
+impl Pack for DAppValueLimit {
+    const LEN: usize = 8 + 8;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, DAppValueLimit::LEN];
+        let (max_value_dst, period_dst) = mut_array_refs![dst, 8, 8];
+        *max_value_dst = self.max_value.to_le_bytes();
+        *period_dst = self.period.as_secs().to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, DAppValueLimit::LEN];
+        let (max_value_src, period_src) = array_refs![src, 8, 8];
+        Ok(DAppValueLimit {
+            max_value: u64::from_le_bytes(*max_value_src),
+            period: Duration::from_secs(u64::from_le_bytes(*period_src)),
+        })
+    }
+}
+
+/// Value moved out of a balance account by one dapp's transactions in the
+/// period starting at `period_start`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Ord, PartialOrd, Default)]
+pub struct DAppValueUsage {
+    pub period_start: i64,
+    pub value: u64,
+}
+
+impl DAppValueUsage {
+    /// Adds `value` moved out at `now`, starting a new period first if the
+    /// current one is over. Fails if the period's total would exceed the limit.
+    pub fn record(&mut self, limit: &DAppValueLimit, value: u64, now: i64) -> ProgramResult {
+        if now
+            >= self
+                .period_start
+                .saturating_add(limit.period.as_secs() as i64)
+        {
+            *self = DAppValueUsage {
+                period_start: now,
+                value: 0,
+            };
+        }
+        let total = self
+            .value
+            .checked_add(value)
+            .ok_or(WalletError::AmountOverflow)?;
+        if total > limit.max_value {
+            msg!(
+                "DApp transactions would move {} out of the balance account this period, limit is {}",
+                total,
+                limit.max_value
+            );
+            return Err(WalletError::DAppValueLimitExceeded.into());
+        }
+        self.value = total;
+        Ok(())
+    }
+}
+
+impl Sealed for DAppValueUsage {}
+
+impl Pack for DAppValueUsage {
+    const LEN: usize = 8 + 8;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, DAppValueUsage::LEN];
+        let (period_start_dst, value_dst) = mut_array_refs![dst, 8, 8];
+        *period_start_dst = self.period_start.to_le_bytes();
+        *value_dst = self.value.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, DAppValueUsage::LEN];
+        let (period_start_src, value_src) = array_refs![src, 8, 8];
+        Ok(DAppValueUsage {
+            period_start: i64::from_le_bytes(*period_start_src),
+            value: u64::from_le_bytes(*value_src),
+        })
+    }
+}
+
+/// A balance account's usage of each dapp's value limit, by dapp book slot.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Ord, PartialOrd)]
+pub struct DAppValueUsages([DAppValueUsage; Wallet::MAX_DAPP_BOOK_ENTRIES]);
+
+impl DAppValueUsages {
+    pub const LEN: usize = Wallet::MAX_DAPP_BOOK_ENTRIES * DAppValueUsage::LEN;
+
+    pub fn new() -> DAppValueUsages {
+        DAppValueUsages([DAppValueUsage::default(); Wallet::MAX_DAPP_BOOK_ENTRIES])
+    }
+
+    pub fn get_mut(&mut self, dapp: SlotId<DAppBookEntry>) -> &mut DAppValueUsage {
+        &mut self.0[dapp.value]
+    }
+
+    pub fn reset(&mut self, dapp: SlotId<DAppBookEntry>) {
+        self.0[dapp.value] = DAppValueUsage::default();
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        for (usage, chunk) in self.0.iter().zip(dst.chunks_exact_mut(DAppValueUsage::LEN)) {
+            usage.pack_into_slice(chunk);
+        }
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Result<DAppValueUsages, ProgramError> {
+        let mut usages = DAppValueUsages::new();
+        for (usage, chunk) in usages
+            .0
+            .iter_mut()
+            .zip(src.chunks_exact(DAppValueUsage::LEN))
+        {
+            *usage = DAppValueUsage::unpack_from_slice(chunk)?;
+        }
+        Ok(usages)
+    }
+}
+
+#[test]
+fn test_dapp_value_usage_resets_each_period() {
+    let limit = DAppValueLimit {
+        max_value: 100,
+        period: Duration::from_secs(60),
+    };
+    let mut usage = DAppValueUsage::default();
+
+    usage.record(&limit, 60, 1000).unwrap();
+    assert_eq!(
+        usage.record(&limit, 41, 1059),
+        Err(WalletError::DAppValueLimitExceeded.into())
+    );
+    usage.record(&limit, 40, 1059).unwrap();
+    assert_eq!(usage.value, 100);
+
+    // a new period starts once the current one is over
+    usage.record(&limit, 100, 1060).unwrap();
+    assert_eq!(
+        usage,
+        DAppValueUsage {
+            period_start: 1060,
+            value: 100
+        }
+    );
+}
@@ -0,0 +1,263 @@
+use crate::instruction::{AddressBookUpdate, WalletConfigPolicyUpdate};
+use crate::model::program_config::PolicyLimits;
+use crate::model::summary::{BalanceAccountSummary, WalletSummary};
+use crate::model::wallet::Wallet;
+use itertools::Itertools;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+/// The keys added to and removed from a set of keys, in sorted order.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct KeysDiff {
+    pub added: Vec<Pubkey>,
+    pub removed: Vec<Pubkey>,
+}
+
+impl KeysDiff {
+    fn between(before: &[Pubkey], after: &[Pubkey]) -> KeysDiff {
+        KeysDiff {
+            added: after
+                .iter()
+                .filter(|key| !before.contains(key))
+                .cloned()
+                .sorted()
+                .collect_vec(),
+            removed: before
+                .iter()
+                .filter(|key| !after.contains(key))
+                .cloned()
+                .sorted()
+                .collect_vec(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// A setting before and after an update, whether or not it changed, so that
+/// the resulting value can always be shown.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ValueChange<T> {
+    pub before: T,
+    pub after: T,
+}
+
+impl<T: PartialEq> ValueChange<T> {
+    pub fn is_changed(&self) -> bool {
+        self.before != self.after
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct BalanceAccountDiff {
+    pub guid_hash: [u8; 32],
+    pub transfer_approvers: KeysDiff,
+    pub allowed_destinations: KeysDiff,
+}
+
+/// What an update changes in a wallet, with every slot reference resolved to
+/// the key or address it points at. Only balance accounts that change are listed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct WalletDiff {
+    pub signers: KeysDiff,
+    pub config_approvers: KeysDiff,
+    pub approvals_required_for_config: ValueChange<u8>,
+    pub approvals_required_for_assistant_config: ValueChange<u8>,
+    pub approval_timeout_for_config_secs: ValueChange<u64>,
+    pub address_book_approvers: KeysDiff,
+    pub approvals_required_for_address_book: ValueChange<u8>,
+    pub default_transfer_approvers: KeysDiff,
+    pub veto_approvers: KeysDiff,
+    pub destructive_config_approvers: KeysDiff,
+    pub approvals_required_for_destructive_config: ValueChange<u8>,
+    pub destination_verification_delay_secs: ValueChange<u64>,
+    pub denial_reason_threshold: ValueChange<u64>,
+    pub address_book: KeysDiff,
+    pub balance_accounts: Vec<BalanceAccountDiff>,
+}
+
+impl WalletSummary {
+    pub fn diff(&self, after: &WalletSummary) -> WalletDiff {
+        WalletDiff {
+            signers: KeysDiff::between(&self.signers, &after.signers),
+            config_approvers: KeysDiff::between(&self.config_approvers, &after.config_approvers),
+            approvals_required_for_config: ValueChange {
+                before: self.approvals_required_for_config,
+                after: after.approvals_required_for_config,
+            },
+            approvals_required_for_assistant_config: ValueChange {
+                before: self.approvals_required_for_assistant_config,
+                after: after.approvals_required_for_assistant_config,
+            },
+            approval_timeout_for_config_secs: ValueChange {
+                before: self.approval_timeout_for_config_secs,
+                after: after.approval_timeout_for_config_secs,
+            },
+            address_book_approvers: KeysDiff::between(
+                &self.address_book_approvers,
+                &after.address_book_approvers,
+            ),
+            approvals_required_for_address_book: ValueChange {
+                before: self.approvals_required_for_address_book,
+                after: after.approvals_required_for_address_book,
+            },
+            default_transfer_approvers: KeysDiff::between(
+                &self.default_transfer_approvers,
+                &after.default_transfer_approvers,
+            ),
+            veto_approvers: KeysDiff::between(&self.veto_approvers, &after.veto_approvers),
+            destructive_config_approvers: KeysDiff::between(
+                &self.destructive_config_approvers,
+                &after.destructive_config_approvers,
+            ),
+            approvals_required_for_destructive_config: ValueChange {
+                before: self.approvals_required_for_destructive_config,
+                after: after.approvals_required_for_destructive_config,
+            },
+            destination_verification_delay_secs: ValueChange {
+                before: self.destination_verification_delay_secs,
+                after: after.destination_verification_delay_secs,
+            },
+            denial_reason_threshold: ValueChange {
+                before: self.denial_reason_threshold,
+                after: after.denial_reason_threshold,
+            },
+            address_book: KeysDiff::between(&self.address_book, &after.address_book),
+            balance_accounts: self
+                .balance_accounts
+                .iter()
+                .chain(after.balance_accounts.iter())
+                .map(|balance_account| balance_account.guid_hash)
+                .unique()
+                .map(|guid_hash| {
+                    let find = |summary: &WalletSummary| -> Option<BalanceAccountSummary> {
+                        summary
+                            .balance_accounts
+                            .iter()
+                            .find(|balance_account| balance_account.guid_hash == guid_hash)
+                            .cloned()
+                    };
+                    let keys = |balance_account: &Option<BalanceAccountSummary>| {
+                        balance_account.as_ref().map_or((vec![], vec![]), |b| {
+                            (b.transfer_approvers.clone(), b.allowed_destinations.clone())
+                        })
+                    };
+                    let (approvers_before, destinations_before) = keys(&find(self));
+                    let (approvers_after, destinations_after) = keys(&find(after));
+                    BalanceAccountDiff {
+                        guid_hash,
+                        transfer_approvers: KeysDiff::between(&approvers_before, &approvers_after),
+                        allowed_destinations: KeysDiff::between(
+                            &destinations_before,
+                            &destinations_after,
+                        ),
+                    }
+                })
+                .filter(|diff| {
+                    !diff.transfer_approvers.is_empty() || !diff.allowed_destinations.is_empty()
+                })
+                .collect_vec(),
+        }
+    }
+}
+
+impl Wallet {
+    /// What the given config policy update would change, computed by applying
+    /// it the same way its finalize does. Fails like the update would.
+    pub fn preview_config_policy_update(
+        &self,
+        update: &WalletConfigPolicyUpdate,
+        limits: &PolicyLimits,
+    ) -> Result<WalletDiff, ProgramError> {
+        let mut updated = self.clone();
+        updated.update_config_policy(update, limits)?;
+        Ok(self.summary().diff(&updated.summary()))
+    }
+
+    /// What the given address book update would change, computed by applying
+    /// it the same way its finalize does. Fails like the update would.
+    pub fn preview_address_book_update(
+        &self,
+        update: &AddressBookUpdate,
+    ) -> Result<WalletDiff, ProgramError> {
+        let mut updated = self.clone();
+        // when the entries are added doesn't show in the summary
+        updated.update_address_book(update, 0)?;
+        Ok(self.summary().diff(&updated.summary()))
+    }
+}
+
+#[test]
+fn test_preview_config_policy_update() {
+    use crate::model::signer::Signer;
+    use crate::utils::SlotId;
+    use solana_program::program_pack::Pack;
+
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    let signers = (0..3)
+        .map(|i| (SlotId::new(i), Signer::new(Pubkey::new_unique())))
+        .collect_vec();
+    for (slot_id, signer) in signers.iter() {
+        wallet.signers.insert(*slot_id, *signer);
+    }
+    wallet.config_approvers.enable(&signers[0].0);
+    wallet.config_approvers.enable(&signers[1].0);
+    wallet.approvals_required_for_config = 1;
+
+    let update = WalletConfigPolicyUpdate {
+        approvals_required_for_config: Some(2),
+        approval_timeout_for_config: None,
+        add_config_approvers: vec![signers[2]],
+        remove_config_approvers: vec![signers[0]],
+        replace_config_approvers: None,
+        destination_verification_delay: None,
+        denial_reason_threshold: None,
+        approvals_required_for_assistant_config: None,
+        approvals_required_for_address_book: None,
+        add_address_book_approvers: vec![],
+        remove_address_book_approvers: vec![],
+        add_default_transfer_approvers: vec![],
+        remove_default_transfer_approvers: vec![],
+        add_veto_approvers: vec![],
+        remove_veto_approvers: vec![],
+        approvals_required_for_destructive_config: None,
+        add_destructive_config_approvers: vec![],
+        remove_destructive_config_approvers: vec![],
+    };
+    let diff = wallet
+        .preview_config_policy_update(&update, &PolicyLimits::default())
+        .unwrap();
+
+    assert!(diff.signers.is_empty());
+    assert_eq!(diff.config_approvers.added, vec![signers[2].1.key]);
+    assert_eq!(diff.config_approvers.removed, vec![signers[0].1.key]);
+    assert_eq!(
+        diff.approvals_required_for_config,
+        ValueChange {
+            before: 1,
+            after: 2
+        }
+    );
+    assert!(!diff.denial_reason_threshold.is_changed());
+    // the wallet itself is left as it was
+    assert_eq!(wallet.approvals_required_for_config, 1);
+
+    // an update the program would reject can't be previewed either
+    assert!(wallet
+        .preview_config_policy_update(
+            &WalletConfigPolicyUpdate {
+                approvals_required_for_config: Some(3),
+                ..update
+            },
+            &PolicyLimits::default()
+        )
+        .is_err());
+}
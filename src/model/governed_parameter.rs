@@ -0,0 +1,246 @@
+use crate::error::WalletError;
+use crate::model::approver_delegation::ApproverDelegation;
+use crate::model::multisig_op::ParamsHashVersion;
+use crate::model::wallet::Wallet;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{Pack, Sealed};
+use std::time::Duration;
+
+/// A wallet tunable that is set by a governed parameter update op rather than
+/// by an op of its own. Parameters that are not set take their default value.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum GovernedParameter {
+    /// The longest an approver delegation may run, in seconds. It can only be
+    /// shortened from `ApproverDelegation::MAX_PERIOD`.
+    MaxApproverDelegationPeriodSecs = 0,
+    /// The most rent a dapp transaction may fund from a balance account, in lamports.
+    MaxDAppRentFundingLimit = 1,
+    /// The fewest distinct organizations an op's approvals must come from,
+    /// counting only signers tagged with an organization. Zero disables it.
+    MinDistinctApproverOrganizations = 2,
+    /// Whether the assistant may also be one of the wallet's signers; one if it
+    /// may, zero if it may not.
+    AssistantMayBeSigner = 3,
+    /// Whether a balance account may pay the rent of the token accounts its
+    /// transfers create for their destinations; one if it may, zero if the
+    /// finalizer or the destination has to.
+    BalanceAccountMayFundTokenAccounts = 4,
+    /// How the params of newly started ops are hashed, as a
+    /// `ParamsHashVersion`. Ops already started keep the version they were
+    /// started with.
+    ParamsHashVersion = 5,
+    /// Whether an approver may switch between approving and denying an op
+    /// while its outcome is undecided; one if they may, zero if their first
+    /// disposition is final. Ops keep the setting they were started with.
+    DispositionChangesAllowed = 6,
+    /// How many days a signer can go without giving a disposition before it
+    /// is left out of the approvers of new ops, as long as enough approvers
+    /// remain to reach the quorum. Zero disables it.
+    SignerInactivityDays = 7,
+}
+
+impl GovernedParameter {
+    pub fn from_u8(value: u8) -> Result<GovernedParameter, ProgramError> {
+        match value {
+            0 => Ok(GovernedParameter::MaxApproverDelegationPeriodSecs),
+            1 => Ok(GovernedParameter::MaxDAppRentFundingLimit),
+            2 => Ok(GovernedParameter::MinDistinctApproverOrganizations),
+            3 => Ok(GovernedParameter::AssistantMayBeSigner),
+            4 => Ok(GovernedParameter::BalanceAccountMayFundTokenAccounts),
+            5 => Ok(GovernedParameter::ParamsHashVersion),
+            6 => Ok(GovernedParameter::DispositionChangesAllowed),
+            7 => Ok(GovernedParameter::SignerInactivityDays),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn default_value(&self) -> u64 {
+        match self {
+            GovernedParameter::MaxApproverDelegationPeriodSecs => {
+                ApproverDelegation::MAX_PERIOD.as_secs()
+            }
+            GovernedParameter::MaxDAppRentFundingLimit => u64::MAX,
+            GovernedParameter::MinDistinctApproverOrganizations => 0,
+            GovernedParameter::AssistantMayBeSigner => 1,
+            GovernedParameter::BalanceAccountMayFundTokenAccounts => 1,
+            GovernedParameter::ParamsHashVersion => ParamsHashVersion::Legacy.to_u8() as u64,
+            GovernedParameter::DispositionChangesAllowed => 0,
+            GovernedParameter::SignerInactivityDays => 0,
+        }
+    }
+
+    pub fn validate(&self, value: u64) -> Result<(), ProgramError> {
+        let valid = match self {
+            GovernedParameter::MaxApproverDelegationPeriodSecs => {
+                value > 0 && value <= ApproverDelegation::MAX_PERIOD.as_secs()
+            }
+            GovernedParameter::MaxDAppRentFundingLimit => true,
+            GovernedParameter::MinDistinctApproverOrganizations => {
+                value <= Wallet::MAX_SIGNERS as u64
+            }
+            GovernedParameter::AssistantMayBeSigner => value <= 1,
+            GovernedParameter::BalanceAccountMayFundTokenAccounts => value <= 1,
+            GovernedParameter::ParamsHashVersion => {
+                value <= ParamsHashVersion::Compact.to_u8() as u64
+            }
+            GovernedParameter::DispositionChangesAllowed => value <= 1,
+            GovernedParameter::SignerInactivityDays => {
+                value <= GovernedParameters::MAX_SIGNER_INACTIVITY_DAYS
+            }
+        };
+        if !valid {
+            msg!("Invalid value {} for {:?}", value, self);
+            return Err(WalletError::InvalidGovernedParameter.into());
+        }
+        Ok(())
+    }
+}
+
+/// The values of the wallet's governed parameters, indexed by key. There is
+/// room for more keys than there are today, so adding one doesn't change the
+/// wallet's layout.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub struct GovernedParameters {
+    values: [Option<u64>; GovernedParameters::CAPACITY],
+}
+
+impl GovernedParameters {
+    pub const CAPACITY: usize = 16;
+    pub const MAX_SIGNER_INACTIVITY_DAYS: u64 = 3650;
+
+    pub fn get(&self, key: GovernedParameter) -> u64 {
+        self.values[usize::from(key.to_u8())].unwrap_or_else(|| key.default_value())
+    }
+
+    /// Sets a parameter, or resets it to its default if `value` is `None`.
+    pub fn set(&mut self, key: GovernedParameter, value: Option<u64>) {
+        self.values[usize::from(key.to_u8())] = value;
+    }
+
+    /// The parameters that are set, by key.
+    pub fn set_values(&self) -> Vec<(GovernedParameter, u64)> {
+        self.values
+            .iter()
+            .enumerate()
+            .filter_map(|(key, value)| {
+                let key = GovernedParameter::from_u8(key as u8).ok()?;
+                value.map(|value| (key, value))
+            })
+            .collect()
+    }
+
+    pub fn max_approver_delegation_period(&self) -> Duration {
+        Duration::from_secs(self.get(GovernedParameter::MaxApproverDelegationPeriodSecs))
+    }
+
+    pub fn max_dapp_rent_funding_limit(&self) -> u64 {
+        self.get(GovernedParameter::MaxDAppRentFundingLimit)
+    }
+
+    pub fn min_distinct_approver_organizations(&self) -> u8 {
+        self.get(GovernedParameter::MinDistinctApproverOrganizations) as u8
+    }
+
+    pub fn assistant_may_be_signer(&self) -> bool {
+        self.get(GovernedParameter::AssistantMayBeSigner) != 0
+    }
+
+    pub fn balance_account_may_fund_token_accounts(&self) -> bool {
+        self.get(GovernedParameter::BalanceAccountMayFundTokenAccounts) != 0
+    }
+
+    pub fn params_hash_version(&self) -> ParamsHashVersion {
+        ParamsHashVersion::from_u8(self.get(GovernedParameter::ParamsHashVersion) as u8)
+            .unwrap_or(ParamsHashVersion::Legacy)
+    }
+
+    pub fn disposition_changes_allowed(&self) -> bool {
+        self.get(GovernedParameter::DispositionChangesAllowed) != 0
+    }
+
+    /// How long a signer can be inactive before new ops leave it out, if at all.
+    pub fn signer_inactivity_period(&self) -> Option<Duration> {
+        match self.get(GovernedParameter::SignerInactivityDays) {
+            0 => None,
+            days => Some(Duration::from_secs(days * 24 * 60 * 60)),
+        }
+    }
+}
+
+impl Sealed for GovernedParameters {}
+
+impl Pack for GovernedParameters {
+    const LEN: usize = GovernedParameters::CAPACITY * (1 + 8);
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        for (chunk, value) in dst.chunks_exact_mut(1 + 8).zip(self.values.iter()) {
+            match value {
+                Some(value) => {
+                    chunk[0] = 1;
+                    chunk[1..].copy_from_slice(&value.to_le_bytes());
+                }
+                None => chunk.fill(0),
+            }
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let mut parameters = GovernedParameters::default();
+        for (value, chunk) in parameters
+            .values
+            .iter_mut()
+            .zip(src[..GovernedParameters::LEN].chunks_exact(1 + 8))
+        {
+            *value = match chunk[0] {
+                0 => None,
+                1 => {
+                    let mut bytes = [0; 8];
+                    bytes.copy_from_slice(&chunk[1..]);
+                    Some(u64::from_le_bytes(bytes))
+                }
+                _ => return Err(ProgramError::InvalidAccountData),
+            };
+        }
+        Ok(parameters)
+    }
+}
+
+#[test]
+fn test_governed_parameters() {
+    let mut parameters = GovernedParameters::default();
+    assert_eq!(
+        parameters.max_approver_delegation_period(),
+        ApproverDelegation::MAX_PERIOD
+    );
+    parameters.set(GovernedParameter::MaxDAppRentFundingLimit, Some(5000));
+    assert_eq!(parameters.max_dapp_rent_funding_limit(), 5000);
+
+    let mut buf = [0; GovernedParameters::LEN];
+    parameters.pack_into_slice(&mut buf);
+    let unpacked = GovernedParameters::unpack_from_slice(&buf).unwrap();
+    assert_eq!(unpacked, parameters);
+    assert_eq!(
+        unpacked.set_values(),
+        vec![(GovernedParameter::MaxDAppRentFundingLimit, 5000)]
+    );
+
+    parameters.set(GovernedParameter::MaxDAppRentFundingLimit, None);
+    assert_eq!(parameters.max_dapp_rent_funding_limit(), u64::MAX);
+    assert!(parameters.balance_account_may_fund_token_accounts());
+    parameters.set(
+        GovernedParameter::BalanceAccountMayFundTokenAccounts,
+        Some(0),
+    );
+    assert!(!parameters.balance_account_may_fund_token_accounts());
+    assert!(GovernedParameter::BalanceAccountMayFundTokenAccounts
+        .validate(2)
+        .is_err());
+    assert!(GovernedParameter::MaxApproverDelegationPeriodSecs
+        .validate(ApproverDelegation::MAX_PERIOD.as_secs() + 1)
+        .is_err());
+}
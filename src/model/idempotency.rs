@@ -0,0 +1,107 @@
+use arrayref::array_ref;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{Pack, Sealed};
+
+/// A key chosen by the client that an init instruction can be submitted
+/// with, so that retrying a submission that already went through is rejected
+/// instead of starting a second op.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub struct IdempotencyKey([u8; 16]);
+
+impl IdempotencyKey {
+    pub const LEN: usize = 16;
+
+    pub fn new(bytes: &[u8; 16]) -> Self {
+        Self(*bytes)
+    }
+
+    pub fn zero() -> Self {
+        Self::new(&[0; 16])
+    }
+
+    pub fn to_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+}
+
+/// The idempotency keys most recently used with a wallet, the oldest being
+/// overwritten once all of them are taken. The zero key marks an unused entry.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub struct RecentIdempotencyKeys {
+    keys: [IdempotencyKey; RecentIdempotencyKeys::CAPACITY],
+    next: u8,
+}
+
+impl RecentIdempotencyKeys {
+    pub const CAPACITY: usize = 16;
+
+    pub fn contains(&self, key: &IdempotencyKey) -> bool {
+        *key != IdempotencyKey::zero() && self.keys.contains(key)
+    }
+
+    pub fn record(&mut self, key: IdempotencyKey) {
+        self.keys[usize::from(self.next)] = key;
+        self.next = ((usize::from(self.next) + 1) % RecentIdempotencyKeys::CAPACITY) as u8;
+    }
+}
+
+impl Sealed for RecentIdempotencyKeys {}
+
+impl Pack for RecentIdempotencyKeys {
+    const LEN: usize = 1 + RecentIdempotencyKeys::CAPACITY * IdempotencyKey::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[0] = self.next;
+        for (chunk, key) in dst[1..]
+            .chunks_exact_mut(IdempotencyKey::LEN)
+            .zip(self.keys.iter())
+        {
+            chunk.copy_from_slice(key.to_bytes());
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let mut recent = RecentIdempotencyKeys::default();
+        recent.next = src[0];
+        if usize::from(recent.next) >= RecentIdempotencyKeys::CAPACITY {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        for (key, chunk) in recent
+            .keys
+            .iter_mut()
+            .zip(src[1..RecentIdempotencyKeys::LEN].chunks_exact(IdempotencyKey::LEN))
+        {
+            *key = IdempotencyKey::new(array_ref![chunk, 0, IdempotencyKey::LEN]);
+        }
+        Ok(recent)
+    }
+}
+
+#[test]
+fn test_recent_idempotency_keys() {
+    let mut recent = RecentIdempotencyKeys::default();
+    assert!(!recent.contains(&IdempotencyKey::zero()));
+
+    let keys: Vec<IdempotencyKey> = (1..=RecentIdempotencyKeys::CAPACITY as u8 + 1)
+        .map(|i| IdempotencyKey::new(&[i; 16]))
+        .collect();
+    for key in keys[..RecentIdempotencyKeys::CAPACITY].iter() {
+        recent.record(*key);
+    }
+    assert!(keys[..RecentIdempotencyKeys::CAPACITY]
+        .iter()
+        .all(|key| recent.contains(key)));
+
+    // the oldest key makes room for the next one
+    recent.record(keys[RecentIdempotencyKeys::CAPACITY]);
+    assert!(!recent.contains(&keys[0]));
+    assert!(recent.contains(&keys[1]));
+    assert!(recent.contains(&keys[RecentIdempotencyKeys::CAPACITY]));
+
+    let mut bytes = [0; RecentIdempotencyKeys::LEN];
+    recent.pack_into_slice(&mut bytes);
+    assert_eq!(
+        RecentIdempotencyKeys::unpack_from_slice(&bytes).unwrap(),
+        recent
+    );
+}
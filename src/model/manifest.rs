@@ -0,0 +1,502 @@
+use crate::model::address_book::{AddressNamespace, DAppBookEntry};
+use crate::model::approver_delegation::ApproverDelegation;
+use crate::model::dapp_inner_programs::DAppInnerPrograms;
+use crate::model::dapp_template::DAppTemplate;
+use crate::model::dapp_value_limit::DAppValueLimit;
+use crate::model::policy::{DestinationClass, PolicyRule};
+use crate::model::summary::BalanceAccountSummary;
+use crate::model::wallet::Wallet;
+use itertools::Itertools;
+use solana_program::hash::{hash, Hash};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+/// Writes the fields of a policy manifest, both as the compact bytes its hash
+/// is computed over and, off-chain, as the text auditors read. Both come from
+/// the same calls, so the text always describes exactly what was hashed.
+struct ManifestWriter {
+    bytes: Vec<u8>,
+    text: Option<String>,
+    indent: &'static str,
+}
+
+impl ManifestWriter {
+    fn new(with_text: bool) -> Self {
+        ManifestWriter {
+            bytes: Vec::new(),
+            text: if with_text { Some(String::new()) } else { None },
+            indent: "",
+        }
+    }
+
+    fn field(&mut self, name: &str, value_bytes: &[u8], value_text: impl FnOnce() -> String) {
+        self.bytes.push(name.len() as u8);
+        self.bytes.extend_from_slice(name.as_bytes());
+        self.bytes.extend_from_slice(value_bytes);
+        if let Some(ref mut text) = self.text {
+            text.push_str(&format!("{}{}: {}\n", self.indent, name, value_text()));
+        }
+    }
+
+    fn section(&mut self, name: &str, id: &[u8; 32]) {
+        self.indent = "";
+        self.field(name, id, || Hash::new_from_array(*id).to_string());
+        self.indent = "  ";
+    }
+
+    fn u64(&mut self, name: &str, value: u64) {
+        self.field(name, &value.to_le_bytes(), || value.to_string());
+    }
+
+    fn u8(&mut self, name: &str, value: u8) {
+        self.field(name, &[value], || value.to_string());
+    }
+
+    fn bool(&mut self, name: &str, value: bool) {
+        self.field(name, &[value as u8], || value.to_string());
+    }
+
+    fn hash(&mut self, name: &str, value: &[u8; 32]) {
+        self.field(name, value, || Hash::new_from_array(*value).to_string());
+    }
+
+    fn key(&mut self, name: &str, key: &Option<Pubkey>) {
+        match key {
+            Some(key) => {
+                let mut bytes = vec![1];
+                bytes.extend_from_slice(key.as_ref());
+                self.field(name, &bytes, || key.to_string())
+            }
+            None => self.field(name, &[0], || "none".to_string()),
+        }
+    }
+
+    /// Keys are written in sorted order, so the manifest does not depend on
+    /// the slots they happen to be stored in.
+    fn keys(&mut self, name: &str, keys: &[Pubkey]) {
+        let keys = keys.iter().sorted().collect_vec();
+        let mut bytes = vec![keys.len() as u8];
+        keys.iter()
+            .for_each(|key| bytes.extend_from_slice(key.as_ref()));
+        self.field(name, &bytes, || keys.iter().join(", "));
+    }
+
+    fn policy_rules(&mut self, name: &str, rules: &[PolicyRule]) {
+        let rules = rules.iter().sorted().collect_vec();
+        let mut bytes = vec![rules.len() as u8];
+        for rule in rules.iter() {
+            let mut rule_bytes = [0; PolicyRule::LEN];
+            rule.pack_into_slice(&mut rule_bytes);
+            bytes.extend_from_slice(&rule_bytes);
+        }
+        self.field(name, &bytes, || {
+            rules
+                .iter()
+                .map(|rule| match rule {
+                    PolicyRule::AmountTier {
+                        min_amount,
+                        approvals_required,
+                    } => format!("amount >= {} needs {}", min_amount, approvals_required),
+                    PolicyRule::TimeWindow {
+                        start_secs,
+                        end_secs,
+                    } => format!("started from {}s to {}s UTC", start_secs, end_secs),
+                    PolicyRule::DestinationClass {
+                        class,
+                        approvals_required,
+                    } => format!(
+                        "{} needs {}",
+                        match class {
+                            DestinationClass::Address => "address",
+                            DestinationClass::BalanceAccount => "balance account",
+                            DestinationClass::DApp => "dapp",
+                        },
+                        approvals_required
+                    ),
+                    PolicyRule::SettlementWindow {
+                        days_mask,
+                        start_hour,
+                        end_hour,
+                    } => format!(
+                        "finalized on {} from {}h to {}h UTC",
+                        ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+                            .iter()
+                            .enumerate()
+                            .filter(|(day, _)| *days_mask >> day & 1 == 1)
+                            .map(|(_, name)| name)
+                            .join("/"),
+                        start_hour,
+                        end_hour
+                    ),
+                })
+                .join(", ")
+        });
+    }
+
+    fn balance_account(&mut self, summary: &BalanceAccountSummary) {
+        self.section("balance_account", &summary.guid_hash);
+        self.hash("name_hash", &summary.name_hash);
+        self.keys("transfer_approvers", &summary.transfer_approvers);
+        self.u8(
+            "approvals_required_for_transfer",
+            summary.approvals_required_for_transfer,
+        );
+        self.u64(
+            "approval_timeout_for_transfer_secs",
+            summary.approval_timeout_for_transfer_secs,
+        );
+        self.bool("use_config_approvers", summary.use_config_approvers);
+        self.bool("policy_update_locked", summary.policy_update_locked);
+        self.bool("whitelist_enabled", summary.whitelist_enabled);
+        self.keys("allowed_destinations", &summary.allowed_destinations);
+        self.bool("dapps_enabled", summary.dapps_enabled);
+        self.bool("sol_transfers_enabled", summary.sol_transfers_enabled);
+        self.bool("spl_transfers_enabled", summary.spl_transfers_enabled);
+        self.bool(
+            "token_mint_allowlist_enabled",
+            summary.token_mint_allowlist_enabled,
+        );
+        self.keys("allowed_token_mints", &summary.allowed_token_mints);
+        self.u64(
+            "max_outstanding_transfer_value",
+            summary.max_outstanding_transfer_value,
+        );
+        self.u64(
+            "max_finalize_fee_reimbursement",
+            summary.max_finalize_fee_reimbursement,
+        );
+        self.policy_rules("policy_rules", &summary.policy_rules);
+        self.u64(
+            "large_transfer_min_amount",
+            summary.large_transfer_min_amount,
+        );
+        self.u64(
+            "large_transfer_cooldown_secs",
+            summary.large_transfer_cooldown_secs,
+        );
+    }
+}
+
+impl Wallet {
+    /// The wallet's configuration as a canonical, human-readable manifest:
+    /// signers, quorums, books and settings, with keys and balance accounts in
+    /// sorted order. State that changes with use (activity, outstanding
+    /// transfer value, pending address book verification) is left out.
+    pub fn policy_manifest(&self) -> String {
+        self.write_policy_manifest(true).text.unwrap_or_default()
+    }
+
+    /// The hash auditors sign off on. It is computed over a compact binary
+    /// form of the manifest's fields, so it can be checked on-chain cheaply.
+    pub fn policy_manifest_hash(&self) -> Hash {
+        hash(&self.write_policy_manifest(false).bytes)
+    }
+
+    fn write_policy_manifest(&self, with_text: bool) -> ManifestWriter {
+        let summary = self.summary();
+        let mut writer = ManifestWriter::new(with_text);
+        writer.keys("signers", &summary.signers);
+        writer.key("assistant", &Some(summary.assistant));
+        writer.keys("config_approvers", &summary.config_approvers);
+        writer.u8(
+            "approvals_required_for_config",
+            summary.approvals_required_for_config,
+        );
+        writer.u8(
+            "approvals_required_for_assistant_config",
+            summary.approvals_required_for_assistant_config,
+        );
+        writer.u64(
+            "approval_timeout_for_config_secs",
+            summary.approval_timeout_for_config_secs,
+        );
+        writer.bool(
+            "config_policy_update_locked",
+            summary.config_policy_update_locked,
+        );
+        writer.keys("address_book_approvers", &summary.address_book_approvers);
+        writer.u8(
+            "approvals_required_for_address_book",
+            summary.approvals_required_for_address_book,
+        );
+        writer.keys(
+            "default_transfer_approvers",
+            &summary.default_transfer_approvers,
+        );
+        writer.keys("veto_approvers", &summary.veto_approvers);
+        writer.keys(
+            "destructive_config_approvers",
+            &summary.destructive_config_approvers,
+        );
+        writer.u8(
+            "approvals_required_for_destructive_config",
+            summary.approvals_required_for_destructive_config,
+        );
+        writer.keys("address_book", &summary.address_book);
+        for (address, token_mint) in self
+            .destination_token_mints
+            .filled_slots()
+            .iter()
+            .filter_map(|(_, binding)| {
+                self.address_book[binding.address_book_entry]
+                    .map(|entry| (entry.address, binding.token_mint))
+            })
+            .sorted()
+        {
+            let mut bytes = address.to_bytes().to_vec();
+            bytes.extend_from_slice(token_mint.as_ref());
+            writer.field("destination_token_mint", &bytes, || {
+                format!("{} accepts {}", address, token_mint)
+            });
+        }
+        for (address, namespace) in self
+            .destination_namespaces
+            .filled_slots()
+            .iter()
+            .filter_map(|(_, tag)| {
+                self.address_book[tag.address_book_entry]
+                    .map(|entry| (entry.address, tag.namespace))
+            })
+            .sorted()
+        {
+            let mut bytes = address.to_bytes().to_vec();
+            match namespace {
+                AddressNamespace::TokenAccount => bytes.push(0),
+                AddressNamespace::DepositMemo(memo_hash) => {
+                    bytes.push(1);
+                    bytes.extend_from_slice(memo_hash.as_ref());
+                }
+                AddressNamespace::Exchange => bytes.push(2),
+            }
+            writer.field("destination_namespace", &bytes, || match namespace {
+                AddressNamespace::TokenAccount => format!("{} is a token account", address),
+                AddressNamespace::DepositMemo(memo_hash) => {
+                    format!("{} takes deposits with memo {}", address, memo_hash)
+                }
+                AddressNamespace::Exchange => {
+                    format!("{} is an exchange requiring travel rule data", address)
+                }
+            });
+        }
+        writer.keys("dapps", &summary.dapps);
+        for (_, dapp) in self
+            .dapp_book
+            .filled_slots()
+            .iter()
+            .filter(|(_, dapp)| dapp.instruction_prefixes.is_restricted())
+            .sorted_by_key(|(_, dapp)| *dapp)
+        {
+            let mut bytes = [0; DAppBookEntry::LEN];
+            dapp.pack_into_slice(&mut bytes);
+            writer.field("dapp_instruction_prefixes", &bytes, || {
+                format!(
+                    "{} limited to {}",
+                    dapp.address,
+                    dapp.instruction_prefixes
+                        .as_slice()
+                        .iter()
+                        .map(|prefix| prefix.iter().map(|byte| format!("{:02x}", byte)).join(""))
+                        .join(", ")
+                )
+            });
+        }
+        for (dapp, limit) in self
+            .dapp_value_limits
+            .filled_slots()
+            .iter()
+            .filter_map(|(slot_id, limit)| {
+                self.dapp_book[SlotId::new(slot_id.value)].map(|dapp| (dapp, *limit))
+            })
+            .sorted()
+        {
+            let mut bytes = [0; DAppBookEntry::LEN + DAppValueLimit::LEN];
+            dapp.pack_into_slice(&mut bytes[..DAppBookEntry::LEN]);
+            limit.pack_into_slice(&mut bytes[DAppBookEntry::LEN..]);
+            writer.field("dapp_value_limit", &bytes, || {
+                format!(
+                    "{} limited to {} per {}s",
+                    dapp.address,
+                    limit.max_value,
+                    limit.period.as_secs()
+                )
+            });
+        }
+        for (dapp, inner_programs) in self
+            .dapp_inner_programs
+            .filled_slots()
+            .iter()
+            .filter_map(|(slot_id, inner_programs)| {
+                self.dapp_book[SlotId::new(slot_id.value)].map(|dapp| (dapp, *inner_programs))
+            })
+            .sorted()
+        {
+            let mut bytes = [0; DAppBookEntry::LEN + DAppInnerPrograms::LEN];
+            dapp.pack_into_slice(&mut bytes[..DAppBookEntry::LEN]);
+            inner_programs.pack_into_slice(&mut bytes[DAppBookEntry::LEN..]);
+            writer.field("dapp_inner_programs", &bytes, || {
+                format!(
+                    "{} may invoke {}",
+                    dapp.address,
+                    inner_programs.as_slice().iter().join(", ")
+                )
+            });
+        }
+        for (dapp, policy) in self
+            .dapp_approval_policies
+            .filled_slots()
+            .iter()
+            .filter_map(|(slot_id, policy)| {
+                self.dapp_book[SlotId::new(slot_id.value)].map(|dapp| (dapp, *policy))
+            })
+            .sorted()
+        {
+            let approvers = self
+                .get_dapp_approvers_keys(&policy)
+                .into_iter()
+                .sorted()
+                .collect_vec();
+            let mut bytes = vec![0; DAppBookEntry::LEN];
+            dapp.pack_into_slice(&mut bytes);
+            bytes.push(policy.approvals_required);
+            approvers
+                .iter()
+                .for_each(|key| bytes.extend_from_slice(key.as_ref()));
+            writer.field("dapp_approval_policy", &bytes, || {
+                format!(
+                    "{} needs {} of {}",
+                    dapp.address,
+                    policy.approvals_required,
+                    approvers.iter().join(", ")
+                )
+            });
+        }
+        for (_, template) in self
+            .dapp_templates
+            .filled_slots()
+            .iter()
+            .sorted_by_key(|(_, template)| *template)
+        {
+            let mut bytes = [0; DAppTemplate::LEN];
+            template.pack_into_slice(&mut bytes);
+            writer.field("dapp_template", &bytes, || {
+                format!(
+                    "{} to {} with fields {}",
+                    Hash::new(template.account_guid_hash.to_bytes()),
+                    template.program_id,
+                    template
+                        .fields()
+                        .iter()
+                        .map(|field| format!("@{} <= {}", field.offset, field.max_value))
+                        .join(", ")
+                )
+            });
+        }
+        writer.keys(
+            "token_mints",
+            &self
+                .token_mint_book
+                .filled_slots()
+                .iter()
+                .map(|(_, token_mint)| token_mint.address)
+                .collect_vec(),
+        );
+        writer.u64(
+            "destination_verification_delay_secs",
+            summary.destination_verification_delay_secs,
+        );
+        writer.u64("denial_reason_threshold", summary.denial_reason_threshold);
+        writer.key("signer_directory", &self.signer_directory);
+        for (_, delegation) in self
+            .approver_delegations
+            .filled_slots()
+            .iter()
+            .sorted_by_key(|(_, delegation)| *delegation)
+        {
+            let mut bytes = [0; ApproverDelegation::LEN];
+            delegation.pack_into_slice(&mut bytes);
+            writer.field("approver_delegation", &bytes, || {
+                format!(
+                    "{} by {} until {}",
+                    delegation.approver, delegation.delegate, delegation.expires_at
+                )
+            });
+        }
+        for (key, value) in self.governed_parameters.set_values() {
+            let mut bytes = vec![key.to_u8()];
+            bytes.extend_from_slice(&value.to_le_bytes());
+            writer.field("governed_parameter", &bytes, || {
+                format!("{:?} = {}", key, value)
+            });
+        }
+        for (key, organization) in self
+            .signers
+            .filled_slots()
+            .iter()
+            .filter_map(|(slot_id, signer)| {
+                self.signer_organizations
+                    .get(*slot_id)
+                    .map(|organization| (signer.key, organization))
+            })
+            .sorted()
+        {
+            let mut bytes = key.to_bytes().to_vec();
+            bytes.push(organization);
+            writer.field("signer_organization", &bytes, || {
+                format!("{} in {}", key, organization)
+            });
+        }
+        writer.key("canary_key", &self.canary_key);
+        writer.bool("frozen", self.frozen);
+        for balance_account in summary
+            .balance_accounts
+            .iter()
+            .sorted_by_key(|balance_account| balance_account.guid_hash)
+        {
+            writer.balance_account(balance_account);
+        }
+        writer
+    }
+}
+
+#[test]
+fn test_policy_manifest_does_not_depend_on_slots() {
+    use crate::model::balance_account::BalanceAccount;
+    use crate::model::signer::Signer;
+    use crate::utils::SlotId;
+
+    let keys = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+    let wallet_with_signers_in = |slots: [usize; 2]| {
+        let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+        let mut balance_account =
+            BalanceAccount::unpack_from_slice(&[0; BalanceAccount::LEN]).unwrap();
+        for (key, slot) in keys.iter().zip(slots.iter()) {
+            wallet.signers.insert(SlotId::new(*slot), Signer::new(*key));
+            wallet.config_approvers.enable(&SlotId::new(*slot));
+            balance_account
+                .transfer_approvers
+                .enable(&SlotId::new(*slot));
+        }
+        wallet
+            .balance_accounts
+            .insert(SlotId::new(0), balance_account);
+        wallet
+    };
+
+    let wallet = wallet_with_signers_in([0, 1]);
+    let same_wallet = wallet_with_signers_in([5, 2]);
+    assert_eq!(wallet.policy_manifest(), same_wallet.policy_manifest());
+    assert_eq!(
+        wallet.policy_manifest_hash(),
+        same_wallet.policy_manifest_hash()
+    );
+
+    let mut changed_wallet = wallet.clone();
+    changed_wallet.approvals_required_for_config = 2;
+    assert_ne!(
+        wallet.policy_manifest_hash(),
+        changed_wallet.policy_manifest_hash()
+    );
+    assert!(changed_wallet
+        .policy_manifest()
+        .contains("approvals_required_for_config: 2\n"));
+}
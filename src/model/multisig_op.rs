@@ -1,11 +1,19 @@
 use crate::error::WalletError;
 use crate::instruction::{
-    append_instruction_expanded, AddressBookUpdate, BalanceAccountCreation,
-    BalanceAccountPolicyUpdate, DAppBookUpdate, WalletConfigPolicyUpdate,
+    append_address_book_entries, append_instruction_expanded, append_optional_pubkey,
+    pack_transfer_settings, AddressBookUpdate, ApproverDelegationUpdate, BalanceAccountCreation,
+    BalanceAccountPolicyUpdate, CombinedTransfer, DAppBookUpdate, DAppTemplatesUpdate,
+    GovernedParameterUpdate, ProgramInstruction, SignerOrganizationsUpdate, SignersUpdate,
+    ViewersUpdate, WalletConfigPolicyUpdate, WalletSignerDirectoryUpdate,
 };
-use crate::model::address_book::DAppBookEntry;
+use crate::model::account_type::AccountType;
+use crate::model::address_book::{AddressBookEntry, DAppBookEntry};
+use crate::model::approver_delegation::ApproverDelegation;
 use crate::model::balance_account::{BalanceAccountGuidHash, BalanceAccountNameHash};
+use crate::model::op_state::{OpEvent, OpState, Tally};
+use crate::model::pending_disposition::PendingDisposition;
 use crate::model::signer::Signer;
+use crate::model::signer_directory::SignerDirectory;
 use crate::model::wallet::Wallet;
 use crate::serialization_utils::pack_option;
 use crate::utils::SlotId;
@@ -15,12 +23,14 @@ use bytes::BufMut;
 use solana_program::account_info::AccountInfo;
 use solana_program::clock::Clock;
 use solana_program::entrypoint::ProgramResult;
-use solana_program::hash::{hash, Hash};
+use solana_program::hash::{hash, hashv, Hash, HASH_BYTES};
 use solana_program::instruction::Instruction;
 use solana_program::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{IsInitialized, Pack, Sealed};
 use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+use solana_program::system_program;
+use std::time::Duration;
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum ApprovalDisposition {
@@ -76,10 +86,69 @@ impl OperationDisposition {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum DenialReason {
+    None = 0,
+    PolicyViolation = 1,
+    SuspectedFraud = 2,
+    IncorrectDetails = 3,
+    Other = 4,
+}
+
+impl DenialReason {
+    pub fn from_u8(value: u8) -> DenialReason {
+        match value {
+            1 => DenialReason::PolicyViolation,
+            2 => DenialReason::SuspectedFraud,
+            3 => DenialReason::IncorrectDetails,
+            4 => DenialReason::Other,
+            _ => DenialReason::None,
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            DenialReason::None => 0,
+            DenialReason::PolicyViolation => 1,
+            DenialReason::SuspectedFraud => 2,
+            DenialReason::IncorrectDetails => 3,
+            DenialReason::Other => 4,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct ApprovalDispositionRecord {
     pub approver: Pubkey,
     pub disposition: ApprovalDisposition,
+    pub denial_reason: DenialReason,
+    /// The hash of an off-chain justification or comment the approver gave
+    /// with their disposition, if any.
+    pub reason_hash: Option<Hash>,
+    /// How many times the approver has changed their disposition. Co-signed
+    /// dispositions are bound to it, so that they can't be replayed once the
+    /// approver has changed their mind.
+    pub disposition_changes: u16,
+}
+
+/// An approver delegation that was active when the op was started. The
+/// delegate can give the approver's disposition until the delegation expires.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct DelegatedApproval {
+    pub approver: Pubkey,
+    pub delegate: Pubkey,
+    pub expires_at: i64,
+    /// Whether the approver's recorded disposition was given by the delegate.
+    pub signed_by_delegate: bool,
+}
+
+/// Outstanding transfer value reserved on a wallet's balance account by a
+/// transfer op, see `BalanceAccount::outstanding_transfer_value`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct HeldTransferValue {
+    pub wallet_address: Pubkey,
+    pub account_guid_hash: BalanceAccountGuidHash,
+    pub amount: u64,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
@@ -104,6 +173,116 @@ impl WrapDirection {
     }
 }
 
+/// Who pays the rent of the destination token account of an SPL transfer
+/// when it is created at finalize time.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum AtaRentPayer {
+    /// The balance account the tokens are transferred from, unless the
+    /// wallet's governed parameters forbid it.
+    Source = 0,
+    /// Whoever finalizes the transfer, who has to sign as the rent collector.
+    Payer = 1,
+    /// The destination, which has to create the token account before the
+    /// transfer is finalized.
+    Destination = 2,
+}
+
+impl AtaRentPayer {
+    pub fn from_u8(value: u8) -> Result<AtaRentPayer, ProgramError> {
+        match value {
+            0 => Ok(AtaRentPayer::Source),
+            1 => Ok(AtaRentPayer::Payer),
+            2 => Ok(AtaRentPayer::Destination),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+}
+
+/// How much a transfer moves when it is finalized.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum AmountSpec {
+    /// Exactly the transfer's amount.
+    Exact = 0,
+    /// Whatever the source account holds, less what it needs to stay rent exempt
+    /// and any fee reimbursement, up to the transfer's amount. This empties a
+    /// balance account without leaving dust behind.
+    Max = 1,
+}
+
+impl AmountSpec {
+    pub fn from_u8(value: u8) -> Result<AmountSpec, ProgramError> {
+        match value {
+            0 => Ok(AmountSpec::Exact),
+            1 => Ok(AmountSpec::Max),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+}
+
+/// Who initiated an operation. Config ops initiated by the assistant can be
+/// subject to a different approval quorum than those initiated by an approver.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum InitiatorClass {
+    Approver = 0,
+    Assistant = 1,
+}
+
+impl InitiatorClass {
+    pub fn from_u8(value: u8) -> InitiatorClass {
+        match value {
+            1 => InitiatorClass::Assistant,
+            _ => InitiatorClass::Approver,
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            InitiatorClass::Approver => 0,
+            InitiatorClass::Assistant => 1,
+        }
+    }
+}
+
+/// Whether a config op only extends the wallet's configuration or weakens
+/// it, such as by removing signers or approvers, lowering a quorum or
+/// turning off a whitelist. Destructive ops can require their own approvers.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ConfigChangeClass {
+    Additive,
+    Destructive,
+}
+
+/// How an op's params are hashed. Legacy hashes concatenate the params the
+/// way each op type always has; compact hashes are the params digest, whose
+/// preimage signers can parse and display on their own.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ParamsHashVersion {
+    Legacy = 0,
+    Compact = 1,
+}
+
+impl ParamsHashVersion {
+    pub fn from_u8(value: u8) -> Result<ParamsHashVersion, ProgramError> {
+        match value {
+            0 => Ok(ParamsHashVersion::Legacy),
+            1 => Ok(ParamsHashVersion::Compact),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum SlotUpdateType {
     SetIfEmpty = 0,
@@ -180,27 +359,155 @@ impl Pack for BooleanSetting {
 }
 
 impl ApprovalDispositionRecord {
-    pub(crate) const LEN: usize = 1 + PUBKEY_BYTES;
+    pub(crate) const LEN: usize = 1 + PUBKEY_BYTES + 1 + 1 + 32 + 2;
 
     pub fn pack_into_slice(&self, dst: &mut [u8]) {
         let dst = array_mut_ref![dst, 0, ApprovalDispositionRecord::LEN];
-        let (approver_dst, disposition_dst) = mut_array_refs![dst, 32, 1];
+        let (
+            approver_dst,
+            disposition_dst,
+            denial_reason_dst,
+            has_reason_hash_dst,
+            reason_hash_dst,
+            disposition_changes_dst,
+        ) = mut_array_refs![dst, 32, 1, 1, 1, 32, 2];
 
         approver_dst.copy_from_slice(&self.approver.to_bytes());
         disposition_dst[0] = self.disposition.to_u8();
+        denial_reason_dst[0] = self.denial_reason.to_u8();
+        has_reason_hash_dst[0] = self.reason_hash.is_some() as u8;
+        reason_hash_dst.copy_from_slice(self.reason_hash.unwrap_or_default().as_ref());
+        *disposition_changes_dst = self.disposition_changes.to_le_bytes();
     }
 
     pub fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
         let src = array_ref![src, 0, ApprovalDispositionRecord::LEN];
-        let (approver_bytes, disposition_bytes) = array_refs![src, 32, 1];
+        let (
+            approver_bytes,
+            disposition_bytes,
+            denial_reason_bytes,
+            has_reason_hash,
+            reason_hash,
+            disposition_changes,
+        ) = array_refs![src, 32, 1, 1, 1, 32, 2];
 
         Ok(ApprovalDispositionRecord {
             approver: Pubkey::new(approver_bytes),
             disposition: ApprovalDisposition::from_u8(disposition_bytes[0]),
+            denial_reason: DenialReason::from_u8(denial_reason_bytes[0]),
+            reason_hash: if has_reason_hash[0] == 1 {
+                Some(Hash::new_from_array(*reason_hash))
+            } else {
+                None
+            },
+            disposition_changes: u16::from_le_bytes(*disposition_changes),
+        })
+    }
+}
+
+impl DelegatedApproval {
+    pub(crate) const LEN: usize = PUBKEY_BYTES + PUBKEY_BYTES + 8 + 1;
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, DelegatedApproval::LEN];
+        let (approver_dst, delegate_dst, expires_at_dst, signed_by_delegate_dst) =
+            mut_array_refs![dst, PUBKEY_BYTES, PUBKEY_BYTES, 8, 1];
+
+        approver_dst.copy_from_slice(self.approver.as_ref());
+        delegate_dst.copy_from_slice(self.delegate.as_ref());
+        *expires_at_dst = self.expires_at.to_le_bytes();
+        signed_by_delegate_dst[0] = self.signed_by_delegate as u8;
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, DelegatedApproval::LEN];
+        let (approver, delegate, expires_at, signed_by_delegate) =
+            array_refs![src, PUBKEY_BYTES, PUBKEY_BYTES, 8, 1];
+
+        Ok(DelegatedApproval {
+            approver: Pubkey::new_from_array(*approver),
+            delegate: Pubkey::new_from_array(*delegate),
+            expires_at: i64::from_le_bytes(*expires_at),
+            signed_by_delegate: signed_by_delegate[0] == 1,
         })
     }
 }
 
+impl HeldTransferValue {
+    pub(crate) const LEN: usize = PUBKEY_BYTES + 32 + 8;
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, HeldTransferValue::LEN];
+        let (wallet_address_dst, account_guid_hash_dst, amount_dst) =
+            mut_array_refs![dst, PUBKEY_BYTES, 32, 8];
+
+        wallet_address_dst.copy_from_slice(self.wallet_address.as_ref());
+        account_guid_hash_dst.copy_from_slice(self.account_guid_hash.to_bytes());
+        *amount_dst = self.amount.to_le_bytes();
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Self {
+        let src = array_ref![src, 0, HeldTransferValue::LEN];
+        let (wallet_address, account_guid_hash, amount) = array_refs![src, PUBKEY_BYTES, 32, 8];
+
+        HeldTransferValue {
+            wallet_address: Pubkey::new_from_array(*wallet_address),
+            account_guid_hash: BalanceAccountGuidHash::new(account_guid_hash),
+            amount: u64::from_le_bytes(*amount),
+        }
+    }
+}
+
+/// Returns the index of the disposition record the signer gives a disposition
+/// for, along with the index of the delegation they act under when they are
+/// not that record's approver. Directory-backed approvers are matched by the
+/// key the signer directory currently holds for their slot.
+fn resolve_approver(
+    signer: &Pubkey,
+    approvers: &[Pubkey],
+    directory_slots: &[Option<SlotId<Signer>>],
+    signer_directory: Option<&SignerDirectory>,
+    delegated_approvals: &[DelegatedApproval],
+    now: i64,
+) -> Option<(usize, Option<usize>)> {
+    let current_key = |index: usize| match (
+        directory_slots.get(index).copied().flatten(),
+        signer_directory,
+    ) {
+        (Some(slot_id), Some(directory)) => directory.signers[slot_id].map(|signer| signer.key),
+        _ => Some(approvers[index]),
+    };
+    if let Some(index) = (0..approvers.len()).find(|index| current_key(*index) == Some(*signer)) {
+        return Some((index, None));
+    }
+    let delegated_index = delegated_approvals
+        .iter()
+        .position(|delegated| delegated.delegate == *signer && now < delegated.expires_at)?;
+    let delegating_approver = delegated_approvals[delegated_index].approver;
+    approvers
+        .iter()
+        .position(|approver| *approver == delegating_approver)
+        .map(|index| (index, Some(delegated_index)))
+}
+
+/// Returns the signer directory an op's directory-backed approvers resolve
+/// against, failing if the op has one and it was not passed along.
+fn signer_directory_for_op<'a>(
+    op_signer_directory: Option<Pubkey>,
+    signer_directory: Option<&'a (Pubkey, SignerDirectory)>,
+) -> Result<Option<&'a SignerDirectory>, ProgramError> {
+    match op_signer_directory {
+        None => Ok(None),
+        Some(expected_address) => match signer_directory {
+            Some((address, directory)) if *address == expected_address => Ok(Some(directory)),
+            _ => {
+                msg!("The operation's signer directory must be passed along");
+                Err(WalletError::SignerDirectoryRequired.into())
+            }
+        },
+    }
+}
+
 #[derive(Debug)]
 pub struct MultisigOp {
     pub is_initialized: bool,
@@ -210,9 +517,128 @@ pub struct MultisigOp {
     pub started_at: i64,
     pub expires_at: i64,
     pub operation_disposition: OperationDisposition,
+    pub denial_reason_required: bool,
+    pub initiator_class: InitiatorClass,
+    /// The config approver who renewed the op, if it has been renewed.
+    pub renewed_by: Option<Pubkey>,
+    pub renewed_at: i64,
+    /// The approval timeout the op was started with, unaffected by renewal.
+    pub approval_timeout: Duration,
+    pub delegated_approvals: Vec<DelegatedApproval>,
+    /// The signer directory of the wallet the op was started for, if any.
+    pub signer_directory: Option<Pubkey>,
+    /// The directory slot of each disposition record's approver, for
+    /// approvers whose wallet signer slot is directory-backed.
+    pub directory_slots: Vec<Option<SlotId<Signer>>>,
+    /// The key that started the op.
+    pub initiator: Pubkey,
+    /// The type code of the op's params, see `MultisigOpParams::type_code`.
+    /// Like the initiator it is kept apart from the params hash, so it can
+    /// be read without knowing the params.
+    pub op_type: u8,
+    /// The fewest distinct organizations the op's approvals must come from.
+    pub organizations_required: u8,
+    /// The organization of each disposition record's approver, if tagged.
+    pub approver_organizations: Vec<Option<u8>>,
+    /// The params hash of an op that must have been executed before this one
+    /// can be, so that ops approved together run in order.
+    pub prerequisite: Option<Hash>,
+    /// The outstanding transfer value the op reserved when it was started,
+    /// released once it is finalized or expired.
+    pub held_transfer_value: Option<HeldTransferValue>,
+    /// The wallet's veto approvers when the op started, any of whom can deny
+    /// it outright whether or not they are among its approvers.
+    pub veto_approvers: Vec<Pubkey>,
+    /// The wallet's config version when the op started, for ops whose policy
+    /// was taken from the wallet's configuration at that point.
+    pub config_version: Option<u64>,
+    /// How the op's params hash was computed. It is set before the op is
+    /// started and kept through it, since approvers sign the hash itself.
+    pub params_hash_version: ParamsHashVersion,
+    /// The balance account the op acts on and its op sequence when the op
+    /// started, for ops that must not run once the account has changed.
+    pub account_sequence: Option<(BalanceAccountGuidHash, u64)>,
+    /// Whether approvers may switch between approving and denying the op
+    /// while its outcome is undecided. Like the params hash version it is
+    /// set before the op is started, from the wallet's governed parameters.
+    pub disposition_changes_allowed: bool,
+    /// How many of a dapp transaction's instructions have been run so far by
+    /// `ExecuteDAppChunk`. The op stays open until the last of them has run.
+    pub instructions_executed: u16,
+    /// The wallet the op belongs to, taken from its params when it starts, so
+    /// that dispositions on it can record the approver's activity there.
+    pub wallet_address: Pubkey,
 }
 
 impl MultisigOp {
+    pub const ADDRESS_SEED: &'static [u8] = b"multisig-op";
+
+    /// The address of the op account created for the op with the given params
+    /// hash, so that the same op can't be started twice while it is pending
+    /// and clients can find it from its params alone. Different nonces let
+    /// ops with the same params run side by side.
+    pub fn find_address(
+        wallet_address: &Pubkey,
+        params_hash: &Hash,
+        nonce: u64,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                wallet_address.as_ref(),
+                MultisigOp::ADDRESS_SEED,
+                params_hash.as_ref(),
+                &nonce.to_le_bytes(),
+            ],
+            program_id,
+        )
+    }
+
+    /// The size of the account an op started by the given instruction needs,
+    /// or `None` if the instruction doesn't start an op. Ops are all the same
+    /// size for now, but clients should size op accounts through this rather
+    /// than `MultisigOp::LEN` so that variable-size ops don't break them.
+    pub fn space_required(instruction: &ProgramInstruction) -> Option<usize> {
+        match instruction {
+            ProgramInstruction::InitBalanceAccountCreation { .. }
+            | ProgramInstruction::InitTransfer { .. }
+            | ProgramInstruction::InitWrapUnwrap { .. }
+            | ProgramInstruction::InitUpdateSigner { .. }
+            | ProgramInstruction::InitWalletConfigPolicyUpdate { .. }
+            | ProgramInstruction::InitDAppTransaction { .. }
+            | ProgramInstruction::InitAccountSettingsUpdate { .. }
+            | ProgramInstruction::InitDAppBookUpdate { .. }
+            | ProgramInstruction::InitAddressBookUpdate { .. }
+            | ProgramInstruction::InitBalanceAccountNameUpdate { .. }
+            | ProgramInstruction::InitBalanceAccountPolicyUpdate { .. }
+            | ProgramInstruction::InitAddressBookVerification { .. }
+            | ProgramInstruction::InitActivityCheckpoint { .. }
+            | ProgramInstruction::InitAddressBookPageUpdate { .. }
+            | ProgramInstruction::InitWalletRepair
+            | ProgramInstruction::InitApproverDelegationUpdate { .. }
+            | ProgramInstruction::InitWalletSignerDirectoryUpdate { .. }
+            | ProgramInstruction::InitWalletCanaryUpdate { .. }
+            | ProgramInstruction::InitInternalTransfer { .. }
+            | ProgramInstruction::InitGovernedParameterUpdate { .. }
+            | ProgramInstruction::InitUpdateSigners { .. }
+            | ProgramInstruction::InitViewersUpdate { .. }
+            | ProgramInstruction::InitSignerOrganizationsUpdate { .. }
+            | ProgramInstruction::InitAssistantUpdate { .. }
+            | ProgramInstruction::InitAddressBookUpdateChunked { .. }
+            | ProgramInstruction::InitWalletStateExport { .. }
+            | ProgramInstruction::InitCombinedTransfer { .. }
+            | ProgramInstruction::InitWalletMetadataUpdate { .. }
+            | ProgramInstruction::InitDAppTemplatesUpdate { .. }
+            | ProgramInstruction::InitWalletClose { .. } => Some(MultisigOp::LEN),
+            ProgramInstruction::ValidateOnly { instruction }
+            | ProgramInstruction::InitWithPrerequisite { instruction, .. }
+            | ProgramInstruction::InitWithIdempotencyKey { instruction, .. } => {
+                MultisigOp::space_required(instruction)
+            }
+            _ => None,
+        }
+    }
+
     pub fn get_disposition_count(&self, disposition: ApprovalDisposition) -> u8 {
         self.disposition_records
             .iter()
@@ -227,131 +653,468 @@ impl MultisigOp {
         started_at: i64,
         expires_at: i64,
         params: MultisigOpParams,
+        denial_reason_required: bool,
+        initiator_class: InitiatorClass,
+        initiator: Pubkey,
     ) -> ProgramResult {
+        if self.is_initialized && !self.is_reusable(started_at) {
+            msg!("Multisig op account is already initialized");
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        let params_hash = params.hash_with(self.params_hash_version);
+        // an account created for the params of an op can only hold that op
+        if !self.is_initialized
+            && self.params_hash != Hash::default()
+            && self.params_hash != params_hash
+        {
+            msg!("Multisig op account was created for an op with different params");
+            return Err(WalletError::OpParamsMismatch.into());
+        }
         self.disposition_records = approvers
             .iter()
             .map(|approver| ApprovalDispositionRecord {
                 approver: *approver,
                 disposition: ApprovalDisposition::NONE,
+                denial_reason: DenialReason::None,
+                reason_hash: None,
+                disposition_changes: 0,
             })
             .collect::<Vec<_>>();
         self.dispositions_required = approvals_required;
-        self.params_hash = params.hash();
+        self.params_hash = params_hash;
         self.is_initialized = true;
         self.started_at = started_at;
         self.expires_at = expires_at;
         self.operation_disposition = OperationDisposition::NONE;
+        self.denial_reason_required = denial_reason_required;
+        self.initiator_class = initiator_class;
+        self.renewed_by = None;
+        self.renewed_at = 0;
+        self.approval_timeout =
+            Duration::from_secs(expires_at.saturating_sub(started_at).max(0) as u64);
+        self.delegated_approvals = Vec::new();
+        self.signer_directory = None;
+        self.directory_slots = Vec::new();
+        self.initiator = initiator;
+        self.op_type = params.type_code();
+        self.organizations_required = 0;
+        self.approver_organizations = Vec::new();
+        self.prerequisite = None;
+        self.held_transfer_value = params.held_transfer_value();
+        self.veto_approvers = Vec::new();
+        self.config_version = None;
+        self.account_sequence = None;
+        self.instructions_executed = 0;
+        self.wallet_address = *params.wallet_address();
 
         Ok(())
     }
 
-    pub fn validate_and_record_approval_disposition(
+    /// Lets directory-backed approvers give their dispositions with whatever
+    /// key the signer directory holds for them, so a key rotated while the op
+    /// is pending is rotated for the op as well.
+    pub fn use_signer_directory(&mut self, wallet: &Wallet) {
+        self.signer_directory = wallet.signer_directory;
+        self.directory_slots = self
+            .disposition_records
+            .iter()
+            .map(|record| wallet.directory_slot(&record.approver))
+            .collect();
+    }
+
+    /// Requires the op's approvals to come from as many distinct organizations
+    /// as the wallet asks for, by the organizations its approvers are tagged
+    /// with when the op starts.
+    pub fn require_distinct_organizations(&mut self, wallet: &Wallet) {
+        self.organizations_required = wallet
+            .governed_parameters
+            .min_distinct_approver_organizations();
+        self.approver_organizations = self
+            .disposition_records
+            .iter()
+            .map(|record| wallet.signer_organization(&record.approver))
+            .collect();
+    }
+
+    /// Lets the wallet's veto approvers deny the op on their own.
+    pub fn allow_vetoes(&mut self, wallet: &Wallet) {
+        self.veto_approvers = wallet.get_veto_approvers_keys();
+    }
+
+    /// Ties the op to the wallet's current configuration, so that it won't
+    /// run once a config op has been finalized in the meantime.
+    pub fn pin_config_version(&mut self, wallet: &Wallet) {
+        self.config_version = Some(wallet.config_version);
+    }
+
+    /// Ties the op to the balance account's current op sequence, so that it
+    /// won't run once another op has changed the account in the meantime.
+    pub fn pin_account_sequence(
         &mut self,
-        approver: &AccountInfo,
-        disposition: ApprovalDisposition,
-        clock: &Clock,
+        wallet: &Wallet,
+        account_guid_hash: &BalanceAccountGuidHash,
     ) -> ProgramResult {
-        if disposition != ApprovalDisposition::APPROVE && disposition != ApprovalDisposition::DENY {
-            msg!("Invalid Disposition provided");
-            return Err(WalletError::InvalidDisposition.into());
+        let balance_account = wallet.get_balance_account(account_guid_hash)?;
+        self.account_sequence = Some((*account_guid_hash, balance_account.op_sequence));
+        Ok(())
+    }
+
+    /// Whether the wallet and the op's balance account are still as when the
+    /// op started. Ops that aren't tied to either always are.
+    pub fn started_under(&self, wallet: &Wallet) -> bool {
+        if let Some(config_version) = self.config_version {
+            if config_version != wallet.config_version {
+                msg!(
+                    "Wallet config changed since the op started, from version {} to {}",
+                    config_version,
+                    wallet.config_version
+                );
+                return false;
+            }
         }
+        if let Some((account_guid_hash, op_sequence)) = self.account_sequence {
+            let current = wallet
+                .get_balance_account(&account_guid_hash)
+                .map(|balance_account| balance_account.op_sequence);
+            if current != Ok(op_sequence) {
+                msg!("Balance account changed since the op started");
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Lets the delegates of the given delegations give dispositions for the
+    /// op's approvers. Delegates who are approvers of the op themselves are
+    /// skipped, so that nobody can give two dispositions on the same op.
+    pub fn delegate_approvals(&mut self, delegations: &[ApproverDelegation]) {
+        let disposition_records = &self.disposition_records;
+        let is_approver = |key: &Pubkey| {
+            disposition_records
+                .iter()
+                .any(|record| record.approver == *key)
+        };
+        self.delegated_approvals = delegations
+            .iter()
+            .filter(|delegation| {
+                is_approver(&delegation.approver) && !is_approver(&delegation.delegate)
+            })
+            .take(Wallet::MAX_APPROVER_DELEGATIONS)
+            .map(|delegation| DelegatedApproval {
+                approver: delegation.approver,
+                delegate: delegation.delegate,
+                expires_at: delegation.expires_at,
+                signed_by_delegate: false,
+            })
+            .collect();
+    }
+
+    /// A denied or expired op will never be executed, so its account can be
+    /// reused for a new op instead of being closed and created again. A dapp
+    /// transaction whose chunks have started running is kept until it is done.
+    pub fn is_reusable(&self, now: i64) -> bool {
+        (self.operation_disposition == OperationDisposition::DENIED || now > self.expires_at)
+            && self.instructions_executed == 0
+    }
 
-        if !approver.is_signer {
+    /// Extends the expiry of a pending or expired op, including one that was
+    /// approved but not finalized in time, keeping the dispositions
+    /// recorded so far. An op can only be renewed once, by at most its
+    /// original approval timeout.
+    pub fn renew(
+        &mut self,
+        params_hash: &Hash,
+        renewer: &Pubkey,
+        extension: Duration,
+        clock: &Clock,
+    ) -> ProgramResult {
+        if *params_hash != self.params_hash {
             return Err(WalletError::InvalidSignature.into());
         }
-
-        if let Some(record) = self
+        let renewed = self.state().apply(
+            OpEvent::Renew {
+                extension_secs: extension.as_secs() as i64,
+            },
+            clock.unix_timestamp,
+        )?;
+        if !self
             .disposition_records
-            .iter_mut()
-            .find(|r| r.approver == *approver.key)
+            .iter()
+            .any(|record| record.approver == *renewer)
         {
-            if record.disposition == ApprovalDisposition::NONE {
-                record.disposition = disposition
-            } else if record.disposition != disposition {
-                msg!("Approver already registered a different disposition");
-                return Err(WalletError::InvalidDisposition.into());
-            }
-        } else {
-            msg!("Approver is not a configured approver");
+            msg!("Renewer is not an approver of this operation");
             return Err(WalletError::InvalidApprover.into());
         }
+        let original_timeout = self.approval_timeout.as_secs() as i64;
+        if extension.as_secs() == 0 || extension.as_secs() as i64 > original_timeout {
+            msg!(
+                "Renewal can extend the operation by at most {} seconds",
+                original_timeout
+            );
+            return Err(WalletError::InvalidApprovalTimeout.into());
+        }
+
+        self.set_state(renewed);
+        self.renewed_by = Some(*renewer);
+        self.renewed_at = clock.unix_timestamp;
         self.update_operation_disposition(clock);
+        msg!("Operation renewed until {}", self.expires_at);
 
         Ok(())
     }
 
-    pub fn update_operation_disposition(&mut self, clock: &Clock) -> OperationDisposition {
-        if self.operation_disposition != OperationDisposition::NONE {
-            return self.operation_disposition;
-        }
-        if clock.unix_timestamp > self.expires_at {
-            self.operation_disposition = OperationDisposition::EXPIRED
-        } else if self.get_disposition_count(ApprovalDisposition::APPROVE)
-            == self.dispositions_required
-        {
-            self.operation_disposition = OperationDisposition::APPROVED
-        } else if self.get_disposition_count(ApprovalDisposition::DENY)
-            == self.dispositions_required
-        {
-            self.operation_disposition = OperationDisposition::DENIED
-        }
-        return self.operation_disposition;
+    /// Settles an op that expired without reaching a final disposition, so
+    /// that it can be closed without knowing its params. An op that was
+    /// approved but not finalized in time is left to be renewed instead,
+    /// unless it already has been.
+    pub fn expire(&mut self, clock: &Clock) -> ProgramResult {
+        let expired = self.state().apply(OpEvent::Expire, clock.unix_timestamp)?;
+        self.set_state(expired);
+        msg!(
+            "OperationDisposition: [{}]",
+            OperationDisposition::EXPIRED.to_u8()
+        );
+        Ok(())
     }
 
-    pub fn approved(
-        &self,
-        expected_params: &MultisigOpParams,
+    pub fn validate_and_record_approval_disposition(
+        &mut self,
+        approver: &AccountInfo,
+        disposition: ApprovalDisposition,
+        denial_reason: DenialReason,
+        reason_hash: Option<Hash>,
+        signer_directory: Option<&(Pubkey, SignerDirectory)>,
         clock: &Clock,
-    ) -> Result<bool, ProgramError> {
-        if expected_params.hash() != self.params_hash {
+    ) -> ProgramResult {
+        validate_approval_disposition(
+            self.denial_reason_required,
+            approver.is_signer,
+            disposition,
+            denial_reason,
+        )?;
+        self.record_approval_disposition(
+            approver.key,
+            disposition,
+            denial_reason,
+            reason_hash,
+            signer_directory,
+            clock,
+        )
+    }
+
+    /// Records a disposition submitted ahead of time in a pending disposition
+    /// account, whose approver signed when it was submitted.
+    pub fn record_pending_disposition(
+        &mut self,
+        pending_disposition: &PendingDisposition,
+        signer_directory: Option<&(Pubkey, SignerDirectory)>,
+        clock: &Clock,
+    ) -> ProgramResult {
+        if pending_disposition.params_hash != self.params_hash {
             return Err(WalletError::InvalidSignature.into());
         }
+        validate_approval_disposition(
+            self.denial_reason_required,
+            true,
+            pending_disposition.disposition,
+            pending_disposition.denial_reason,
+        )?;
+        self.record_approval_disposition(
+            &pending_disposition.approver,
+            pending_disposition.disposition,
+            pending_disposition.denial_reason,
+            pending_disposition.reason_hash,
+            signer_directory,
+            clock,
+        )
+    }
 
-        if self.operation_disposition == OperationDisposition::NONE
-            && clock.unix_timestamp < self.expires_at
-        {
-            return Err(WalletError::TransferDispositionNotFinal.into());
-        }
+    /// The disposition record a signer's dispositions are recorded in, which
+    /// is the delegating approver's for a delegate, or None if the signer is
+    /// not one of the op's approvers.
+    pub fn disposition_record_of(
+        &self,
+        signer: &Pubkey,
+        signer_directory: Option<&(Pubkey, SignerDirectory)>,
+        now: i64,
+    ) -> Result<Option<&ApprovalDispositionRecord>, ProgramError> {
+        let signer_directory = signer_directory_for_op(self.signer_directory, signer_directory)?;
+        let approvers = self
+            .disposition_records
+            .iter()
+            .map(|r| r.approver)
+            .collect::<Vec<_>>();
+        Ok(resolve_approver(
+            signer,
+            &approvers,
+            &self.directory_slots,
+            signer_directory,
+            &self.delegated_approvals,
+            now,
+        )
+        .map(|(index, _)| &self.disposition_records[index]))
+    }
 
-        let mut operation_disposition = self.operation_disposition;
-        if clock.unix_timestamp > self.expires_at {
-            operation_disposition = OperationDisposition::EXPIRED
+    fn record_approval_disposition(
+        &mut self,
+        approver: &Pubkey,
+        disposition: ApprovalDisposition,
+        denial_reason: DenialReason,
+        reason_hash: Option<Hash>,
+        signer_directory: Option<&(Pubkey, SignerDirectory)>,
+        clock: &Clock,
+    ) -> ProgramResult {
+        let signer_directory = signer_directory_for_op(self.signer_directory, signer_directory)?;
+
+        if disposition == ApprovalDisposition::DENY && self.veto_approvers.contains(approver) {
+            let vetoed = self.state().apply(OpEvent::Veto, clock.unix_timestamp)?;
+            self.set_state(vetoed);
+            msg!("Operation vetoed by {}", approver);
+            log_disposition_reason(ApprovalDisposition::DENY, denial_reason, reason_hash);
+            return Ok(());
         }
-        msg!("OperationDisposition: [{}]", operation_disposition.to_u8());
 
-        if operation_disposition == OperationDisposition::APPROVED {
-            return Ok(true);
+        let approvers = self
+            .disposition_records
+            .iter()
+            .map(|r| r.approver)
+            .collect::<Vec<_>>();
+        if let Some((index, delegated_index)) = resolve_approver(
+            approver,
+            &approvers,
+            &self.directory_slots,
+            signer_directory,
+            &self.delegated_approvals,
+            clock.unix_timestamp,
+        ) {
+            let record = &mut self.disposition_records[index];
+            if record.disposition != disposition {
+                if record.disposition != ApprovalDisposition::NONE {
+                    validate_disposition_change(
+                        self.disposition_changes_allowed,
+                        self.operation_disposition,
+                    )?;
+                    record.disposition_changes =
+                        count_disposition_change(record.disposition_changes)?;
+                }
+                record.disposition = disposition;
+                record.denial_reason = denial_reason;
+                record.reason_hash = reason_hash;
+                if let Some(index) = delegated_index {
+                    self.delegated_approvals[index].signed_by_delegate = true;
+                }
+                log_disposition_reason(disposition, denial_reason, reason_hash);
+            }
+        } else {
+            msg!("Approver is not a configured approver");
+            return Err(WalletError::InvalidApprover.into());
         }
+        self.update_operation_disposition(clock);
 
-        Ok(false)
+        Ok(())
     }
-}
 
-impl Sealed for MultisigOp {}
+    pub fn update_operation_disposition(&mut self, clock: &Clock) -> OperationDisposition {
+        let tally = Tally {
+            approvals: self.get_disposition_count(ApprovalDisposition::APPROVE),
+            denials: self.get_disposition_count(ApprovalDisposition::DENY),
+            dispositions_required: self.dispositions_required,
+            organizations: approving_organizations(
+                self.disposition_records
+                    .iter()
+                    .map(|record| record.disposition),
+                &self.approver_organizations,
+            ),
+            organizations_required: self.organizations_required,
+        };
+        // recording dispositions can't fail on an op that is still open
+        if let Ok(state) = self
+            .state()
+            .apply(OpEvent::Disposition(tally), clock.unix_timestamp)
+        {
+            self.set_state(state);
+        }
+        return self.operation_disposition;
+    }
 
-impl IsInitialized for MultisigOp {
-    fn is_initialized(&self) -> bool {
-        self.is_initialized
+    /// The op's place in its lifecycle, as far as the `OpState` machine is
+    /// concerned. Ops are only ever read while open, since finalizing one
+    /// closes its account.
+    pub fn state(&self) -> OpState {
+        OpState::Open {
+            disposition: self.operation_disposition,
+            expires_at: self.expires_at,
+            renewable: self.renewed_by.is_none(),
+        }
     }
-}
 
-impl Pack for MultisigOp {
-    const LEN: usize =
-        1 + ApprovalDispositionRecord::LEN * Wallet::MAX_SIGNERS + 1 + 1 + 32 + 8 + 8 + 1;
+    fn set_state(&mut self, state: OpState) {
+        if let OpState::Open { expires_at, .. } = state {
+            self.expires_at = expires_at;
+        }
+        self.operation_disposition = state.disposition();
+    }
 
-    fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, MultisigOp::LEN];
+    /// Records an approver's disposition directly in the packed op data. Only
+    /// the approver's record and the operation disposition are written, so the
+    /// other disposition records never have to be deserialized. The resulting
+    /// bytes are the same as unpacking the op, calling
+    /// `validate_and_record_approval_disposition` and packing it again.
+    pub fn record_approval_disposition_in_place(
+        data: &mut [u8],
+        params_hash: &Hash,
+        approver: &AccountInfo,
+        disposition: ApprovalDisposition,
+        denial_reason: DenialReason,
+        reason_hash: Option<Hash>,
+        signer_directory: Option<&(Pubkey, SignerDirectory)>,
+        clock: &Clock,
+    ) -> ProgramResult {
+        if data.len() != MultisigOp::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = array_mut_ref![data, 0, MultisigOp::LEN];
         let (
-            is_initialized_dst,
-            disposition_records_count_dst,
-            disposition_records_dst,
-            dispositions_required_dst,
-            hash_dst,
-            started_at_dst,
-            expires_at_dst,
-            operation_disposition_dst,
+            is_initialized,
+            disposition_records_count,
+            disposition_record_bytes,
+            dispositions_required,
+            params_hash_bytes,
+            _started_at,
+            expires_at,
+            operation_disposition,
+            denial_reason_required,
+            _initiator_class,
+            is_renewed,
+            _renewed_by,
+            _renewed_at,
+            _approval_timeout,
+            delegated_approvals_count,
+            delegated_approval_bytes,
+            has_signer_directory,
+            signer_directory_bytes,
+            directory_slot_bytes,
+            _initiator,
+            _op_type,
+            organizations_required,
+            approver_organization_bytes,
+            _has_prerequisite,
+            _prerequisite,
+            _has_held_transfer_value,
+            _held_transfer_value,
+            veto_approvers_count,
+            veto_approver_bytes,
+            _has_config_version,
+            _config_version,
+            _params_hash_version,
+            _has_account_sequence,
+            _account_sequence_guid_hash,
+            _account_sequence,
+            disposition_changes_allowed,
+            _instructions_executed,
+            _wallet_address,
         ) = mut_array_refs![
-            dst,
+            data,
             1,
             1,
             ApprovalDispositionRecord::LEN * Wallet::MAX_SIGNERS,
@@ -359,25 +1122,592 @@ impl Pack for MultisigOp {
             32,
             8,
             8,
-            1
-        ];
-
-        let MultisigOp {
-            is_initialized,
-            disposition_records,
-            dispositions_required,
-            params_hash,
-            started_at,
-            expires_at,
-            operation_disposition,
-        } = self;
-
-        is_initialized_dst[0] = *is_initialized as u8;
-
-        disposition_records_count_dst[0] = disposition_records.len() as u8;
-        disposition_records_dst.fill(0);
-        disposition_records_dst
-            .chunks_exact_mut(ApprovalDispositionRecord::LEN)
+            1,
+            1,
+            1,
+            1,
+            PUBKEY_BYTES,
+            8,
+            8,
+            1,
+            DelegatedApproval::LEN * Wallet::MAX_APPROVER_DELEGATIONS,
+            1,
+            PUBKEY_BYTES,
+            Wallet::MAX_SIGNERS,
+            PUBKEY_BYTES,
+            1,
+            1,
+            2 * Wallet::MAX_SIGNERS,
+            1,
+            HASH_BYTES,
+            1,
+            HeldTransferValue::LEN,
+            1,
+            PUBKEY_BYTES * Wallet::MAX_VETO_APPROVERS,
+            1,
+            8,
+            1,
+            1,
+            32,
+            8,
+            1,
+            2,
+            PUBKEY_BYTES
+        ];
+
+        if !AccountType::MultisigOp.from_discriminator(is_initialized[0])? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        if params_hash.as_ref() != params_hash_bytes {
+            return Err(WalletError::InvalidSignature.into());
+        }
+
+        validate_approval_disposition(
+            denial_reason_required[0] == 1,
+            approver.is_signer,
+            disposition,
+            denial_reason,
+        )?;
+        let signer_directory = signer_directory_for_op(
+            if has_signer_directory[0] == 1 {
+                Some(Pubkey::new_from_array(*signer_directory_bytes))
+            } else {
+                None
+            },
+            signer_directory,
+        )?;
+
+        let state = OpState::Open {
+            disposition: OperationDisposition::from_u8(operation_disposition[0]),
+            expires_at: i64::from_le_bytes(*expires_at),
+            renewable: is_renewed[0] == 0,
+        };
+        let veto_approvers_count =
+            usize::from(veto_approvers_count[0]).min(Wallet::MAX_VETO_APPROVERS);
+        if disposition == ApprovalDisposition::DENY
+            && veto_approver_bytes[..veto_approvers_count * PUBKEY_BYTES]
+                .chunks_exact(PUBKEY_BYTES)
+                .any(|veto_approver| veto_approver == approver.key.as_ref())
+        {
+            operation_disposition[0] = state
+                .apply(OpEvent::Veto, clock.unix_timestamp)?
+                .disposition()
+                .to_u8();
+            msg!("Operation vetoed by {}", approver.key);
+            log_disposition_reason(ApprovalDisposition::DENY, denial_reason, reason_hash);
+            return Ok(());
+        }
+
+        let disposition_records_count =
+            usize::from(disposition_records_count[0]).min(Wallet::MAX_SIGNERS);
+        let disposition_record_bytes = &mut disposition_record_bytes
+            [..disposition_records_count * ApprovalDispositionRecord::LEN];
+
+        let delegated_approvals_count =
+            usize::from(delegated_approvals_count[0]).min(Wallet::MAX_APPROVER_DELEGATIONS);
+        let delegated_approval_bytes =
+            &mut delegated_approval_bytes[..delegated_approvals_count * DelegatedApproval::LEN];
+        let delegated_approvals = delegated_approval_bytes
+            .chunks_exact(DelegatedApproval::LEN)
+            .map(DelegatedApproval::unpack_from_slice)
+            .collect::<Result<Vec<_>, _>>()?;
+        let approvers = disposition_record_bytes
+            .chunks_exact(ApprovalDispositionRecord::LEN)
+            .map(|record| Pubkey::new(&record[..PUBKEY_BYTES]))
+            .collect::<Vec<_>>();
+        let directory_slots = directory_slot_bytes[..disposition_records_count]
+            .iter()
+            .map(|byte| unpack_directory_slot(*byte))
+            .collect::<Vec<_>>();
+
+        if let Some((index, delegated_index)) = resolve_approver(
+            approver.key,
+            &approvers,
+            &directory_slots,
+            signer_directory,
+            &delegated_approvals,
+            clock.unix_timestamp,
+        ) {
+            let record = array_mut_ref![
+                disposition_record_bytes,
+                index * ApprovalDispositionRecord::LEN,
+                ApprovalDispositionRecord::LEN
+            ];
+            let (
+                _approver,
+                disposition_dst,
+                denial_reason_dst,
+                has_reason_hash_dst,
+                reason_hash_dst,
+                disposition_changes_dst,
+            ) = mut_array_refs![record, 32, 1, 1, 1, 32, 2];
+            let current_disposition = ApprovalDisposition::from_u8(disposition_dst[0]);
+            if current_disposition != disposition {
+                if current_disposition != ApprovalDisposition::NONE {
+                    validate_disposition_change(
+                        disposition_changes_allowed[0] == 1,
+                        state.disposition(),
+                    )?;
+                    *disposition_changes_dst =
+                        count_disposition_change(u16::from_le_bytes(*disposition_changes_dst))?
+                            .to_le_bytes();
+                }
+                disposition_dst[0] = disposition.to_u8();
+                denial_reason_dst[0] = denial_reason.to_u8();
+                has_reason_hash_dst[0] = reason_hash.is_some() as u8;
+                reason_hash_dst.copy_from_slice(reason_hash.unwrap_or_default().as_ref());
+                if let Some(index) = delegated_index {
+                    delegated_approval_bytes[(index + 1) * DelegatedApproval::LEN - 1] = 1;
+                }
+                log_disposition_reason(disposition, denial_reason, reason_hash);
+            }
+        } else {
+            msg!("Approver is not a configured approver");
+            return Err(WalletError::InvalidApprover.into());
+        }
+
+        if state.disposition() == OperationDisposition::NONE {
+            let count_dispositions = |target: ApprovalDisposition| {
+                disposition_record_bytes
+                    .chunks_exact(ApprovalDispositionRecord::LEN)
+                    .filter(|record| ApprovalDisposition::from_u8(record[PUBKEY_BYTES]) == target)
+                    .count() as u8
+            };
+            let approver_organizations = approver_organization_bytes
+                .chunks_exact(2)
+                .take(disposition_records_count)
+                .map(unpack_organization)
+                .collect::<Vec<_>>();
+            let tally = Tally {
+                approvals: count_dispositions(ApprovalDisposition::APPROVE),
+                denials: count_dispositions(ApprovalDisposition::DENY),
+                dispositions_required: dispositions_required[0],
+                organizations: approving_organizations(
+                    disposition_record_bytes
+                        .chunks_exact(ApprovalDispositionRecord::LEN)
+                        .map(|record| ApprovalDisposition::from_u8(record[PUBKEY_BYTES])),
+                    &approver_organizations,
+                ),
+                organizations_required: organizations_required[0],
+            };
+            operation_disposition[0] = state
+                .apply(OpEvent::Disposition(tally), clock.unix_timestamp)?
+                .disposition()
+                .to_u8();
+        }
+
+        Ok(())
+    }
+
+    /// The wallet the op in the packed data belongs to, read without
+    /// deserializing the rest of the op.
+    pub fn wallet_address_in_place(data: &[u8]) -> Result<Pubkey, ProgramError> {
+        if data.len() != MultisigOp::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // the wallet address is the last field of the op
+        Ok(Pubkey::new(&data[MultisigOp::LEN - PUBKEY_BYTES..]))
+    }
+
+    /// The params hash and prerequisite of the op in the packed data if the op
+    /// is approved and can still be executed, read without deserializing the
+    /// disposition records.
+    pub fn executable_in_place(data: &[u8], now: i64) -> Option<(Hash, Option<Hash>)> {
+        if data.len() != MultisigOp::LEN {
+            return None;
+        }
+        let data = array_ref![data, 0, MultisigOp::LEN];
+        let (
+            is_initialized,
+            _disposition_records_count,
+            _disposition_record_bytes,
+            _dispositions_required,
+            params_hash,
+            _started_at,
+            expires_at,
+            operation_disposition,
+            _denial_reason_required,
+            _initiator_class,
+            _is_renewed,
+            _renewed_by,
+            _renewed_at,
+            _approval_timeout,
+            _delegated_approvals_count,
+            _delegated_approval_bytes,
+            _has_signer_directory,
+            _signer_directory_bytes,
+            _directory_slot_bytes,
+            _initiator,
+            _op_type,
+            _organizations_required,
+            _approver_organization_bytes,
+            has_prerequisite,
+            prerequisite,
+            _has_held_transfer_value,
+            _held_transfer_value,
+            _veto_approvers_count,
+            _veto_approver_bytes,
+            _has_config_version,
+            _config_version,
+            _params_hash_version,
+            _has_account_sequence,
+            _account_sequence_guid_hash,
+            _account_sequence,
+            _disposition_changes_allowed,
+            _instructions_executed,
+            _wallet_address,
+        ) = array_refs![
+            data,
+            1,
+            1,
+            ApprovalDispositionRecord::LEN * Wallet::MAX_SIGNERS,
+            1,
+            32,
+            8,
+            8,
+            1,
+            1,
+            1,
+            1,
+            PUBKEY_BYTES,
+            8,
+            8,
+            1,
+            DelegatedApproval::LEN * Wallet::MAX_APPROVER_DELEGATIONS,
+            1,
+            PUBKEY_BYTES,
+            Wallet::MAX_SIGNERS,
+            PUBKEY_BYTES,
+            1,
+            1,
+            2 * Wallet::MAX_SIGNERS,
+            1,
+            HASH_BYTES,
+            1,
+            HeldTransferValue::LEN,
+            1,
+            PUBKEY_BYTES * Wallet::MAX_VETO_APPROVERS,
+            1,
+            8,
+            1,
+            1,
+            32,
+            8,
+            1,
+            2,
+            PUBKEY_BYTES
+        ];
+
+        if is_initialized[0] != AccountType::MultisigOp as u8
+            || OperationDisposition::from_u8(operation_disposition[0])
+                != OperationDisposition::APPROVED
+            || now > i64::from_le_bytes(*expires_at)
+        {
+            return None;
+        }
+        Some((
+            Hash::new_from_array(*params_hash),
+            if has_prerequisite[0] == 1 {
+                Some(Hash::new_from_array(*prerequisite))
+            } else {
+                None
+            },
+        ))
+    }
+
+    pub fn approved(
+        &self,
+        expected_params: &MultisigOpParams,
+        clock: &Clock,
+    ) -> Result<bool, ProgramError> {
+        if expected_params.hash_with(self.params_hash_version) != self.params_hash {
+            return Err(WalletError::InvalidSignature.into());
+        }
+
+        Ok(self
+            .state()
+            .apply(OpEvent::Finalize, clock.unix_timestamp)?
+            .executed())
+    }
+}
+
+impl Sealed for MultisigOp {}
+
+impl IsInitialized for MultisigOp {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+fn log_disposition_reason(
+    disposition: ApprovalDisposition,
+    denial_reason: DenialReason,
+    reason_hash: Option<Hash>,
+) {
+    if disposition == ApprovalDisposition::DENY {
+        msg!("DenialReason: [{}]", denial_reason.to_u8());
+    }
+    if let Some(reason_hash) = reason_hash {
+        msg!("ReasonHash: [{}]", reason_hash);
+    }
+}
+
+fn validate_approval_disposition(
+    denial_reason_required: bool,
+    approver_signed: bool,
+    disposition: ApprovalDisposition,
+    denial_reason: DenialReason,
+) -> ProgramResult {
+    if disposition != ApprovalDisposition::APPROVE && disposition != ApprovalDisposition::DENY {
+        msg!("Invalid Disposition provided");
+        return Err(WalletError::InvalidDisposition.into());
+    }
+
+    if disposition == ApprovalDisposition::DENY {
+        if denial_reason_required && denial_reason == DenialReason::None {
+            msg!("A denial reason is required to deny this operation");
+            return Err(WalletError::DenialReasonRequired.into());
+        }
+    } else if denial_reason != DenialReason::None {
+        msg!("A denial reason can only be given with a DENY disposition");
+        return Err(WalletError::InvalidDisposition.into());
+    }
+
+    if !approver_signed {
+        return Err(WalletError::InvalidSignature.into());
+    }
+
+    Ok(())
+}
+
+/// An approver's disposition can only be replaced by a different one if the
+/// op allows it and its outcome is still undecided.
+fn validate_disposition_change(
+    disposition_changes_allowed: bool,
+    operation_disposition: OperationDisposition,
+) -> ProgramResult {
+    if !disposition_changes_allowed {
+        msg!("Approver already registered a different disposition");
+        return Err(WalletError::InvalidDisposition.into());
+    }
+    if operation_disposition != OperationDisposition::NONE {
+        msg!("Dispositions can't be changed once the operation is decided");
+        return Err(WalletError::InvalidDisposition.into());
+    }
+    msg!("Approver changed their disposition");
+    Ok(())
+}
+
+fn count_disposition_change(disposition_changes: u16) -> Result<u16, ProgramError> {
+    disposition_changes.checked_add(1).ok_or_else(|| {
+        msg!("Approver can't change their disposition any further");
+        WalletError::InvalidDisposition.into()
+    })
+}
+
+// directory slots are stored off by one, so that zero means not directory-backed
+fn pack_directory_slot(slot_id: &Option<SlotId<Signer>>) -> u8 {
+    slot_id.map_or(0, |slot_id| slot_id.value as u8 + 1)
+}
+
+fn unpack_directory_slot(byte: u8) -> Option<SlotId<Signer>> {
+    match usize::from(byte) {
+        value if value > 0 && value <= Wallet::MAX_SIGNERS => Some(SlotId::new(value - 1)),
+        _ => None,
+    }
+}
+
+// organizations are stored as a flag byte and a tag byte
+fn pack_organization(organization: &Option<u8>, dst: &mut [u8]) {
+    dst[0] = organization.is_some() as u8;
+    dst[1] = organization.unwrap_or_default();
+}
+
+fn unpack_organization(src: &[u8]) -> Option<u8> {
+    if src[0] == 1 {
+        Some(src[1])
+    } else {
+        None
+    }
+}
+
+/// The number of distinct organizations among the approvers who approved.
+/// Approvers without an organization don't count towards any.
+fn approving_organizations(
+    dispositions: impl Iterator<Item = ApprovalDisposition>,
+    approver_organizations: &[Option<u8>],
+) -> u8 {
+    let mut organizations = dispositions
+        .zip(approver_organizations.iter())
+        .filter(|(disposition, _)| *disposition == ApprovalDisposition::APPROVE)
+        .filter_map(|(_, organization)| *organization)
+        .collect::<Vec<_>>();
+    organizations.sort_unstable();
+    organizations.dedup();
+    organizations.len() as u8
+}
+
+impl Pack for MultisigOp {
+    const LEN: usize = 1
+        + ApprovalDispositionRecord::LEN * Wallet::MAX_SIGNERS
+        + 1
+        + 1
+        + 32
+        + 8
+        + 8
+        + 1
+        + 1
+        + 1
+        + 1
+        + PUBKEY_BYTES
+        + 8
+        + 8
+        + 1
+        + DelegatedApproval::LEN * Wallet::MAX_APPROVER_DELEGATIONS
+        + 1
+        + PUBKEY_BYTES
+        + Wallet::MAX_SIGNERS
+        + PUBKEY_BYTES
+        + 1
+        + 1
+        + 2 * Wallet::MAX_SIGNERS
+        + 1
+        + HASH_BYTES
+        + 1
+        + HeldTransferValue::LEN
+        + 1
+        + PUBKEY_BYTES * Wallet::MAX_VETO_APPROVERS
+        + 1
+        + 8
+        + 1
+        + 1
+        + 32
+        + 8
+        + 1
+        + 2
+        + PUBKEY_BYTES;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, MultisigOp::LEN];
+        let (
+            is_initialized_dst,
+            disposition_records_count_dst,
+            disposition_records_dst,
+            dispositions_required_dst,
+            hash_dst,
+            started_at_dst,
+            expires_at_dst,
+            operation_disposition_dst,
+            denial_reason_required_dst,
+            initiator_class_dst,
+            is_renewed_dst,
+            renewed_by_dst,
+            renewed_at_dst,
+            approval_timeout_dst,
+            delegated_approvals_count_dst,
+            delegated_approvals_dst,
+            has_signer_directory_dst,
+            signer_directory_dst,
+            directory_slots_dst,
+            initiator_dst,
+            op_type_dst,
+            organizations_required_dst,
+            approver_organizations_dst,
+            has_prerequisite_dst,
+            prerequisite_dst,
+            has_held_transfer_value_dst,
+            held_transfer_value_dst,
+            veto_approvers_count_dst,
+            veto_approvers_dst,
+            has_config_version_dst,
+            config_version_dst,
+            params_hash_version_dst,
+            has_account_sequence_dst,
+            account_sequence_guid_hash_dst,
+            account_sequence_dst,
+            disposition_changes_allowed_dst,
+            instructions_executed_dst,
+            wallet_address_dst,
+        ) = mut_array_refs![
+            dst,
+            1,
+            1,
+            ApprovalDispositionRecord::LEN * Wallet::MAX_SIGNERS,
+            1,
+            32,
+            8,
+            8,
+            1,
+            1,
+            1,
+            1,
+            PUBKEY_BYTES,
+            8,
+            8,
+            1,
+            DelegatedApproval::LEN * Wallet::MAX_APPROVER_DELEGATIONS,
+            1,
+            PUBKEY_BYTES,
+            Wallet::MAX_SIGNERS,
+            PUBKEY_BYTES,
+            1,
+            1,
+            2 * Wallet::MAX_SIGNERS,
+            1,
+            HASH_BYTES,
+            1,
+            HeldTransferValue::LEN,
+            1,
+            PUBKEY_BYTES * Wallet::MAX_VETO_APPROVERS,
+            1,
+            8,
+            1,
+            1,
+            32,
+            8,
+            1,
+            2,
+            PUBKEY_BYTES
+        ];
+
+        let MultisigOp {
+            is_initialized,
+            disposition_records,
+            dispositions_required,
+            params_hash,
+            started_at,
+            expires_at,
+            operation_disposition,
+            denial_reason_required,
+            initiator_class,
+            renewed_by,
+            renewed_at,
+            approval_timeout,
+            delegated_approvals,
+            signer_directory,
+            directory_slots,
+            initiator,
+            op_type,
+            organizations_required,
+            approver_organizations,
+            prerequisite,
+            held_transfer_value,
+            veto_approvers,
+            config_version,
+            params_hash_version,
+            account_sequence,
+            disposition_changes_allowed,
+            instructions_executed,
+            wallet_address,
+        } = self;
+
+        is_initialized_dst[0] = AccountType::MultisigOp.to_discriminator(*is_initialized);
+
+        disposition_records_count_dst[0] = disposition_records.len() as u8;
+        disposition_records_dst.fill(0);
+        disposition_records_dst
+            .chunks_exact_mut(ApprovalDispositionRecord::LEN)
             .take(disposition_records.len())
             .enumerate()
             .for_each(|(i, chunk)| disposition_records[i].pack_into_slice(chunk));
@@ -390,6 +1720,71 @@ impl Pack for MultisigOp {
         *expires_at_dst = expires_at.to_le_bytes();
 
         operation_disposition_dst[0] = operation_disposition.to_u8();
+        denial_reason_required_dst[0] = *denial_reason_required as u8;
+        initiator_class_dst[0] = initiator_class.to_u8();
+        is_renewed_dst[0] = renewed_by.is_some() as u8;
+        renewed_by_dst.copy_from_slice(renewed_by.unwrap_or_default().as_ref());
+        *renewed_at_dst = renewed_at.to_le_bytes();
+        *approval_timeout_dst = approval_timeout.as_secs().to_le_bytes();
+
+        delegated_approvals_count_dst[0] = delegated_approvals.len() as u8;
+        delegated_approvals_dst.fill(0);
+        delegated_approvals_dst
+            .chunks_exact_mut(DelegatedApproval::LEN)
+            .zip(delegated_approvals.iter())
+            .for_each(|(chunk, delegated)| delegated.pack_into_slice(chunk));
+
+        has_signer_directory_dst[0] = signer_directory.is_some() as u8;
+        signer_directory_dst.copy_from_slice(signer_directory.unwrap_or_default().as_ref());
+        directory_slots_dst.fill(0);
+        directory_slots_dst
+            .iter_mut()
+            .zip(directory_slots.iter())
+            .for_each(|(byte, slot_id)| *byte = pack_directory_slot(slot_id));
+
+        initiator_dst.copy_from_slice(initiator.as_ref());
+        op_type_dst[0] = *op_type;
+
+        organizations_required_dst[0] = *organizations_required;
+        approver_organizations_dst.fill(0);
+        approver_organizations_dst
+            .chunks_exact_mut(2)
+            .zip(approver_organizations.iter())
+            .for_each(|(chunk, organization)| pack_organization(organization, chunk));
+
+        has_prerequisite_dst[0] = prerequisite.is_some() as u8;
+        prerequisite_dst.copy_from_slice(prerequisite.unwrap_or_default().as_ref());
+
+        has_held_transfer_value_dst[0] = held_transfer_value.is_some() as u8;
+        held_transfer_value_dst.fill(0);
+        if let Some(held_transfer_value) = held_transfer_value {
+            held_transfer_value.pack_into_slice(held_transfer_value_dst);
+        }
+
+        veto_approvers_count_dst[0] = veto_approvers.len() as u8;
+        veto_approvers_dst.fill(0);
+        veto_approvers_dst
+            .chunks_exact_mut(PUBKEY_BYTES)
+            .zip(veto_approvers.iter())
+            .for_each(|(chunk, veto_approver)| chunk.copy_from_slice(veto_approver.as_ref()));
+
+        has_config_version_dst[0] = config_version.is_some() as u8;
+        *config_version_dst = config_version.unwrap_or_default().to_le_bytes();
+        params_hash_version_dst[0] = params_hash_version.to_u8();
+        has_account_sequence_dst[0] = account_sequence.is_some() as u8;
+        match account_sequence {
+            Some((account_guid_hash, op_sequence)) => {
+                account_sequence_guid_hash_dst.copy_from_slice(account_guid_hash.to_bytes());
+                *account_sequence_dst = op_sequence.to_le_bytes();
+            }
+            None => {
+                account_sequence_guid_hash_dst.fill(0);
+                account_sequence_dst.fill(0);
+            }
+        }
+        disposition_changes_allowed_dst[0] = *disposition_changes_allowed as u8;
+        *instructions_executed_dst = instructions_executed.to_le_bytes();
+        wallet_address_dst.copy_from_slice(wallet_address.as_ref());
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
@@ -403,6 +1798,36 @@ impl Pack for MultisigOp {
             started_at,
             expires_at,
             operation_disposition,
+            denial_reason_required,
+            initiator_class,
+            is_renewed,
+            renewed_by,
+            renewed_at,
+            approval_timeout,
+            delegated_approvals_count,
+            delegated_approval_bytes,
+            has_signer_directory,
+            signer_directory,
+            directory_slot_bytes,
+            initiator,
+            op_type,
+            organizations_required,
+            approver_organization_bytes,
+            has_prerequisite,
+            prerequisite,
+            has_held_transfer_value,
+            held_transfer_value,
+            veto_approvers_count,
+            veto_approver_bytes,
+            has_config_version,
+            config_version,
+            params_hash_version,
+            has_account_sequence,
+            account_sequence_guid_hash,
+            account_sequence,
+            disposition_changes_allowed,
+            instructions_executed,
+            wallet_address,
         ) = array_refs![
             src,
             1,
@@ -412,23 +1837,46 @@ impl Pack for MultisigOp {
             32,
             8,
             8,
-            1
+            1,
+            1,
+            1,
+            1,
+            PUBKEY_BYTES,
+            8,
+            8,
+            1,
+            DelegatedApproval::LEN * Wallet::MAX_APPROVER_DELEGATIONS,
+            1,
+            PUBKEY_BYTES,
+            Wallet::MAX_SIGNERS,
+            PUBKEY_BYTES,
+            1,
+            1,
+            2 * Wallet::MAX_SIGNERS,
+            1,
+            HASH_BYTES,
+            1,
+            HeldTransferValue::LEN,
+            1,
+            PUBKEY_BYTES * Wallet::MAX_VETO_APPROVERS,
+            1,
+            8,
+            1,
+            1,
+            32,
+            8,
+            1,
+            2,
+            PUBKEY_BYTES
         ];
-        let is_initialized = match is_initialized {
-            [0] => false,
-            [1] => true,
-            _ => return Err(ProgramError::InvalidAccountData),
-        };
+        let is_initialized = AccountType::MultisigOp.from_discriminator(is_initialized[0])?;
 
         let disposition_records_count = usize::from(disposition_records_count[0]);
-        let mut disposition_records = Vec::with_capacity(Wallet::MAX_SIGNERS);
-        disposition_record_bytes
+        let disposition_records = disposition_record_bytes
             .chunks_exact(ApprovalDispositionRecord::LEN)
             .take(disposition_records_count)
-            .for_each(|chunk| {
-                let record = ApprovalDispositionRecord::unpack_from_slice(chunk).unwrap();
-                disposition_records.push(record);
-            });
+            .map(ApprovalDispositionRecord::unpack_from_slice)
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(MultisigOp {
             is_initialized,
@@ -438,10 +1886,79 @@ impl Pack for MultisigOp {
             started_at: i64::from_le_bytes(*started_at),
             expires_at: i64::from_le_bytes(*expires_at),
             operation_disposition: OperationDisposition::from_u8(operation_disposition[0]),
+            denial_reason_required: denial_reason_required[0] == 1,
+            initiator_class: InitiatorClass::from_u8(initiator_class[0]),
+            renewed_by: if is_renewed[0] == 1 {
+                Some(Pubkey::new_from_array(*renewed_by))
+            } else {
+                None
+            },
+            renewed_at: i64::from_le_bytes(*renewed_at),
+            approval_timeout: Duration::from_secs(u64::from_le_bytes(*approval_timeout)),
+            delegated_approvals: delegated_approval_bytes
+                .chunks_exact(DelegatedApproval::LEN)
+                .take(usize::from(delegated_approvals_count[0]))
+                .map(DelegatedApproval::unpack_from_slice)
+                .collect::<Result<Vec<_>, _>>()?,
+            signer_directory: if has_signer_directory[0] == 1 {
+                Some(Pubkey::new_from_array(*signer_directory))
+            } else {
+                None
+            },
+            directory_slots: directory_slot_bytes
+                .iter()
+                .take(disposition_records_count)
+                .map(|byte| unpack_directory_slot(*byte))
+                .collect(),
+            initiator: Pubkey::new_from_array(*initiator),
+            op_type: op_type[0],
+            organizations_required: organizations_required[0],
+            approver_organizations: approver_organization_bytes
+                .chunks_exact(2)
+                .take(disposition_records_count)
+                .map(unpack_organization)
+                .collect(),
+            prerequisite: if has_prerequisite[0] == 1 {
+                Some(Hash::new_from_array(*prerequisite))
+            } else {
+                None
+            },
+            held_transfer_value: if has_held_transfer_value[0] == 1 {
+                Some(HeldTransferValue::unpack_from_slice(held_transfer_value))
+            } else {
+                None
+            },
+            veto_approvers: veto_approver_bytes
+                .chunks_exact(PUBKEY_BYTES)
+                .take(usize::from(veto_approvers_count[0]))
+                .map(Pubkey::new)
+                .collect(),
+            config_version: if has_config_version[0] == 1 {
+                Some(u64::from_le_bytes(*config_version))
+            } else {
+                None
+            },
+            params_hash_version: ParamsHashVersion::from_u8(params_hash_version[0])?,
+            account_sequence: if has_account_sequence[0] == 1 {
+                Some((
+                    BalanceAccountGuidHash::new(account_sequence_guid_hash),
+                    u64::from_le_bytes(*account_sequence),
+                ))
+            } else {
+                None
+            },
+            disposition_changes_allowed: disposition_changes_allowed[0] == 1,
+            instructions_executed: u16::from_le_bytes(*instructions_executed),
+            wallet_address: Pubkey::new_from_array(*wallet_address),
         })
     }
 }
 
+// the SPL Token program, without depending on spl-token, which only the program needs
+mod spl_token_program {
+    solana_program::declare_id!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+}
+
 // represents multisig operation params that are hashed and signed by the client
 #[derive(Debug, PartialEq, Clone)]
 pub enum MultisigOpParams {
@@ -451,12 +1968,23 @@ pub enum MultisigOpParams {
         destination: Pubkey,
         amount: u64,
         token_mint: Pubkey,
+        memo_hash: Option<Hash>,
+        ata_rent_payer: Option<AtaRentPayer>,
+        /// The program the token mint belongs to, or the system program for SOL transfers.
+        token_program: Pubkey,
+        amount_spec: AmountSpec,
+        /// The hash of the travel rule data collected for the transfer.
+        travel_rule_hash: Option<Hash>,
     },
     Wrap {
         wallet_address: Pubkey,
         account_guid_hash: BalanceAccountGuidHash,
         amount: u64,
         direction: WrapDirection,
+        /// Where an unwrap sends the unwrapped SOL, other than the balance account.
+        destination: Option<Pubkey>,
+        /// The hash of the travel rule data collected for an unwrap to a destination.
+        travel_rule_hash: Option<Hash>,
     },
     UpdateSigner {
         wallet_address: Pubkey,
@@ -473,6 +2001,7 @@ pub enum MultisigOpParams {
         account_guid_hash: BalanceAccountGuidHash,
         dapp: DAppBookEntry,
         instructions: Vec<Instruction>,
+        rent_funding_limit: u64,
     },
     UpdateDAppBook {
         wallet_address: Pubkey,
@@ -502,40 +2031,318 @@ pub enum MultisigOpParams {
         account_guid_hash: BalanceAccountGuidHash,
         whitelist_enabled: Option<BooleanSetting>,
         dapps_enabled: Option<BooleanSetting>,
+        sol_transfers_enabled: Option<BooleanSetting>,
+        spl_transfers_enabled: Option<BooleanSetting>,
+    },
+    VerifyAddressBookEntries {
+        wallet_address: Pubkey,
+        entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    },
+    ActivityCheckpoint {
+        wallet_address: Pubkey,
+        checkpoint_index: u32,
+    },
+    AddressBookPageUpdate {
+        wallet_address: Pubkey,
+        page_index: u8,
+        update: AddressBookUpdate,
+    },
+    RepairWallet {
+        wallet_address: Pubkey,
+    },
+    UpdateApproverDelegations {
+        wallet_address: Pubkey,
+        update: ApproverDelegationUpdate,
+    },
+    UpdateWalletSignerDirectory {
+        wallet_address: Pubkey,
+        update: WalletSignerDirectoryUpdate,
+    },
+    UpdateWalletCanary {
+        wallet_address: Pubkey,
+        canary_key: Option<Pubkey>,
+    },
+    InternalTransfer {
+        wallet_address: Pubkey,
+        source_account_guid_hash: BalanceAccountGuidHash,
+        destination_account_guid_hash: BalanceAccountGuidHash,
+        amount: u64,
+        token_mint: Pubkey,
+    },
+    UpdateGovernedParameters {
+        wallet_address: Pubkey,
+        update: GovernedParameterUpdate,
+    },
+    UpdateSigners {
+        wallet_address: Pubkey,
+        update: SignersUpdate,
+    },
+    UpdateViewers {
+        wallet_address: Pubkey,
+        update: ViewersUpdate,
+    },
+    UpdateSignerOrganizations {
+        wallet_address: Pubkey,
+        update: SignerOrganizationsUpdate,
+    },
+    UpdateAssistant {
+        wallet_address: Pubkey,
+        assistant: Pubkey,
+    },
+    AddressBookUpdateChunked {
+        wallet_address: Pubkey,
+        update_hash: Hash,
+    },
+    ExportWalletState {
+        wallet_address: Pubkey,
+        target_program_id: Pubkey,
+    },
+    CombinedTransfer {
+        wallet_address: Pubkey,
+        account_guid_hash: BalanceAccountGuidHash,
+        destination: Pubkey,
+        transfer: CombinedTransfer,
+    },
+    UpdateWalletMetadata {
+        wallet_address: Pubkey,
+        metadata_hash: Hash,
+    },
+    UpdateDAppTemplates {
+        wallet_address: Pubkey,
+        update: DAppTemplatesUpdate,
+    },
+    CloseWallet {
+        wallet_address: Pubkey,
+        rent_collector: Pubkey,
     },
 }
 
-impl MultisigOpParams {
-    fn hash_wallet_update_op(
+impl MultisigOpParams {
+    /// Prefixes the canonical bytes of every op's params.
+    pub const DIGEST_DOMAIN: &'static [u8] = b"strike-wallet:op-params:v1";
+
+    pub fn wallet_address(&self) -> &Pubkey {
+        match self {
+            MultisigOpParams::Transfer { wallet_address, .. }
+            | MultisigOpParams::Wrap { wallet_address, .. }
+            | MultisigOpParams::UpdateSigner { wallet_address, .. }
+            | MultisigOpParams::UpdateWalletConfigPolicy { wallet_address, .. }
+            | MultisigOpParams::DAppTransaction { wallet_address, .. }
+            | MultisigOpParams::UpdateDAppBook { wallet_address, .. }
+            | MultisigOpParams::AddressBookUpdate { wallet_address, .. }
+            | MultisigOpParams::CreateBalanceAccount { wallet_address, .. }
+            | MultisigOpParams::UpdateBalanceAccountPolicy { wallet_address, .. }
+            | MultisigOpParams::UpdateBalanceAccountName { wallet_address, .. }
+            | MultisigOpParams::UpdateBalanceAccountSettings { wallet_address, .. }
+            | MultisigOpParams::VerifyAddressBookEntries { wallet_address, .. }
+            | MultisigOpParams::ActivityCheckpoint { wallet_address, .. }
+            | MultisigOpParams::AddressBookPageUpdate { wallet_address, .. }
+            | MultisigOpParams::RepairWallet { wallet_address }
+            | MultisigOpParams::UpdateApproverDelegations { wallet_address, .. }
+            | MultisigOpParams::UpdateWalletSignerDirectory { wallet_address, .. }
+            | MultisigOpParams::UpdateWalletCanary { wallet_address, .. }
+            | MultisigOpParams::InternalTransfer { wallet_address, .. }
+            | MultisigOpParams::UpdateGovernedParameters { wallet_address, .. }
+            | MultisigOpParams::UpdateSigners { wallet_address, .. }
+            | MultisigOpParams::UpdateViewers { wallet_address, .. }
+            | MultisigOpParams::UpdateSignerOrganizations { wallet_address, .. }
+            | MultisigOpParams::UpdateAssistant { wallet_address, .. }
+            | MultisigOpParams::AddressBookUpdateChunked { wallet_address, .. }
+            | MultisigOpParams::ExportWalletState { wallet_address, .. }
+            | MultisigOpParams::CombinedTransfer { wallet_address, .. }
+            | MultisigOpParams::UpdateWalletMetadata { wallet_address, .. }
+            | MultisigOpParams::UpdateDAppTemplates { wallet_address, .. }
+            | MultisigOpParams::CloseWallet { wallet_address, .. } => wallet_address,
+        }
+    }
+
+    /// The existing balance account the operation acts on, if any. For
+    /// internal transfers that is the source account.
+    pub fn account_guid_hash(&self) -> Option<BalanceAccountGuidHash> {
+        match self {
+            MultisigOpParams::Transfer {
+                account_guid_hash, ..
+            }
+            | MultisigOpParams::Wrap {
+                account_guid_hash, ..
+            }
+            | MultisigOpParams::DAppTransaction {
+                account_guid_hash, ..
+            }
+            | MultisigOpParams::UpdateBalanceAccountPolicy {
+                account_guid_hash, ..
+            }
+            | MultisigOpParams::UpdateBalanceAccountName {
+                account_guid_hash, ..
+            }
+            | MultisigOpParams::UpdateBalanceAccountSettings {
+                account_guid_hash, ..
+            }
+            | MultisigOpParams::CombinedTransfer {
+                account_guid_hash, ..
+            } => Some(*account_guid_hash),
+            MultisigOpParams::InternalTransfer {
+                source_account_guid_hash,
+                ..
+            } => Some(*source_account_guid_hash),
+            _ => None,
+        }
+    }
+
+    /// The amount moved by the operation, for operations that move funds.
+    pub fn value(&self) -> Option<u64> {
+        match self {
+            MultisigOpParams::Transfer { amount, .. } => Some(*amount),
+            MultisigOpParams::Wrap { amount, .. } => Some(*amount),
+            MultisigOpParams::InternalTransfer { amount, .. } => Some(*amount),
+            MultisigOpParams::CombinedTransfer { transfer, .. } => transfer.total().ok(),
+            _ => None,
+        }
+    }
+
+    /// The outstanding transfer value a balance account holds for the
+    /// operation while it is pending, for transfers out of a balance account.
+    pub fn held_transfer_value(&self) -> Option<HeldTransferValue> {
+        match self {
+            MultisigOpParams::Transfer {
+                wallet_address,
+                account_guid_hash,
+                amount,
+                ..
+            } => Some(HeldTransferValue {
+                wallet_address: *wallet_address,
+                account_guid_hash: *account_guid_hash,
+                amount: *amount,
+            }),
+            MultisigOpParams::InternalTransfer {
+                wallet_address,
+                source_account_guid_hash,
+                amount,
+                ..
+            } => Some(HeldTransferValue {
+                wallet_address: *wallet_address,
+                account_guid_hash: *source_account_guid_hash,
+                amount: *amount,
+            }),
+            MultisigOpParams::CombinedTransfer {
+                wallet_address,
+                account_guid_hash,
+                transfer,
+                ..
+            } => transfer.total().ok().map(|amount| HeldTransferValue {
+                wallet_address: *wallet_address,
+                account_guid_hash: *account_guid_hash,
+                amount,
+            }),
+            _ => None,
+        }
+    }
+
+    /// The code identifying the type of the params, the same one their hash
+    /// starts with.
+    pub fn type_code(&self) -> u8 {
+        match self {
+            MultisigOpParams::CreateBalanceAccount { .. } => 1,
+            MultisigOpParams::Transfer { .. } => 3,
+            MultisigOpParams::Wrap { .. } => 4,
+            MultisigOpParams::UpdateSigner { .. } => 5,
+            MultisigOpParams::UpdateWalletConfigPolicy { .. } => 6,
+            MultisigOpParams::DAppTransaction { .. } => 7,
+            MultisigOpParams::UpdateBalanceAccountSettings { .. } => 8,
+            MultisigOpParams::UpdateDAppBook { .. } => 9,
+            MultisigOpParams::AddressBookUpdate { .. } => 10,
+            MultisigOpParams::UpdateBalanceAccountName { .. } => 11,
+            MultisigOpParams::UpdateBalanceAccountPolicy { .. } => 12,
+            MultisigOpParams::VerifyAddressBookEntries { .. } => 13,
+            MultisigOpParams::ActivityCheckpoint { .. } => 14,
+            MultisigOpParams::AddressBookPageUpdate { .. } => 15,
+            MultisigOpParams::RepairWallet { .. } => 16,
+            MultisigOpParams::UpdateApproverDelegations { .. } => 17,
+            MultisigOpParams::UpdateWalletSignerDirectory { .. } => 18,
+            MultisigOpParams::UpdateWalletCanary { .. } => 19,
+            MultisigOpParams::InternalTransfer { .. } => 20,
+            MultisigOpParams::UpdateGovernedParameters { .. } => 21,
+            MultisigOpParams::UpdateSigners { .. } => 22,
+            MultisigOpParams::UpdateViewers { .. } => 23,
+            MultisigOpParams::UpdateSignerOrganizations { .. } => 24,
+            MultisigOpParams::UpdateAssistant { .. } => 25,
+            MultisigOpParams::AddressBookUpdateChunked { .. } => 26,
+            MultisigOpParams::ExportWalletState { .. } => 27,
+            MultisigOpParams::CombinedTransfer { .. } => 28,
+            MultisigOpParams::UpdateWalletMetadata { .. } => 29,
+            MultisigOpParams::UpdateDAppTemplates { .. } => 30,
+            MultisigOpParams::CloseWallet { .. } => 31,
+        }
+    }
+
+    fn wallet_update_op_parts(
         type_code: u8,
         wallet_address: &Pubkey,
         update_bytes: Vec<u8>,
-    ) -> Hash {
+    ) -> Vec<Vec<u8>> {
         let mut bytes: Vec<u8> = Vec::new();
         bytes.resize(1 + PUBKEY_BYTES + update_bytes.len(), 0);
         bytes[0] = type_code; // type code
         bytes[1..1 + PUBKEY_BYTES].copy_from_slice(&wallet_address.to_bytes());
         bytes[1 + PUBKEY_BYTES..1 + PUBKEY_BYTES + update_bytes.len()]
             .copy_from_slice(&update_bytes);
-        hash(&bytes)
+        vec![bytes]
     }
 
-    fn hash_balance_account_update_op(
+    fn balance_account_update_op_parts(
         type_code: u8,
         wallet_address: &Pubkey,
         account_guid_hash: &BalanceAccountGuidHash,
         update_bytes: Vec<u8>,
-    ) -> Hash {
+    ) -> Vec<Vec<u8>> {
         let mut bytes: Vec<u8> = Vec::new();
         bytes.resize(1 + PUBKEY_BYTES + 32 + update_bytes.len(), 0);
         bytes[0] = type_code; // type code
         bytes[1..33].copy_from_slice(&wallet_address.to_bytes());
         bytes[33..65].copy_from_slice(account_guid_hash.to_bytes());
         bytes[65..65 + update_bytes.len()].copy_from_slice(&update_bytes);
-        hash(&bytes)
+        vec![bytes]
     }
 
     pub fn hash(&self) -> Hash {
+        let parts = self.hash_parts();
+        hashv(&parts.iter().map(|part| part.as_slice()).collect::<Vec<_>>())
+    }
+
+    /// The params hash of the given version.
+    pub fn hash_with(&self, version: ParamsHashVersion) -> Hash {
+        match version {
+            ParamsHashVersion::Legacy => self.hash(),
+            ParamsHashVersion::Compact => self.params_digest(),
+        }
+    }
+
+    /// The params digest signers can check for themselves: the hash of
+    /// `canonical_bytes`.
+    pub fn params_digest(&self) -> Hash {
+        hash(&self.canonical_bytes())
+    }
+
+    /// The params in a form a hardware signer can parse in a single pass: a
+    /// domain separator, the type code, and each of the parts the legacy hash
+    /// is taken over, prefixed with its length. Lengths keep parts from being
+    /// mistaken for one another, and the separator keeps the bytes from being
+    /// mistaken for anything else the signer signs.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let parts = self.hash_parts();
+        let mut bytes: Vec<u8> = Vec::new();
+        bytes.extend_from_slice(MultisigOpParams::DIGEST_DOMAIN);
+        bytes.push(self.type_code());
+        bytes.push(parts.len() as u8);
+        for part in parts.iter() {
+            bytes.put_u32_le(part.len() as u32);
+            bytes.extend_from_slice(part);
+        }
+        bytes
+    }
+
+    /// The byte strings the legacy params hash is taken over, in order.
+    fn hash_parts(&self) -> Vec<Vec<u8>> {
         match self {
             MultisigOpParams::Transfer {
                 wallet_address,
@@ -543,6 +2350,11 @@ impl MultisigOpParams {
                 destination,
                 amount,
                 token_mint,
+                memo_hash,
+                ata_rent_payer,
+                token_program,
+                amount_spec,
+                travel_rule_hash,
             } => {
                 const LEN: usize = 1 + PUBKEY_BYTES * 4 + 8;
                 let mut bytes: [u8; LEN] = [0; LEN];
@@ -569,13 +2381,41 @@ impl MultisigOpParams {
                 destination_ref.copy_from_slice(destination.as_ref());
                 *amount_ref = amount.to_le_bytes();
                 token_mint_ref.copy_from_slice(token_mint.as_ref());
-                hash(&bytes)
+                // transfers without a memo or rent payer hash the same as before those existed
+                let ata_rent_payer_bytes = ata_rent_payer.map(|payer| [payer.to_u8()]);
+                let mut hash_parts: Vec<&[u8]> = vec![&bytes];
+                if let Some(memo_hash) = memo_hash {
+                    hash_parts.push(memo_hash.as_ref());
+                }
+                if let Some(ref ata_rent_payer_bytes) = ata_rent_payer_bytes {
+                    hash_parts.push(ata_rent_payer_bytes);
+                }
+                // SOL and legacy SPL token transfers hash the same as before the token program
+                // was recorded; the label keeps it from being mistaken for a memo hash
+                if *token_program != system_program::id()
+                    && *token_program != spl_token_program::id()
+                {
+                    hash_parts.push(b"token_program");
+                    hash_parts.push(token_program.as_ref());
+                }
+                // so do exact amount transfers
+                if *amount_spec == AmountSpec::Max {
+                    hash_parts.push(b"max_amount");
+                }
+                // and so do transfers without travel rule data
+                if let Some(travel_rule_hash) = travel_rule_hash {
+                    hash_parts.push(b"travel_rule_hash");
+                    hash_parts.push(travel_rule_hash.as_ref());
+                }
+                hash_parts.iter().map(|part| part.to_vec()).collect()
             }
             MultisigOpParams::Wrap {
                 wallet_address,
                 account_guid_hash,
                 amount,
                 direction,
+                destination,
+                travel_rule_hash,
             } => {
                 const LEN: usize = 1 + PUBKEY_BYTES + 32 + 8 + 1;
                 let mut bytes: [u8; LEN] = [0; LEN];
@@ -592,7 +2432,17 @@ impl MultisigOpParams {
                 account_guid_hash_ref.copy_from_slice(account_guid_hash.to_bytes());
                 *amount_ref = amount.to_le_bytes();
                 *direction_ref = direction.to_u8().to_le_bytes();
-                hash(&bytes)
+                // unwraps to the balance account hash as they did before destinations,
+                // and unwraps without travel rule data as they did before it
+                let mut hash_parts = vec![bytes.to_vec()];
+                if let Some(destination) = destination {
+                    hash_parts.push(destination.to_bytes().to_vec());
+                }
+                if let Some(travel_rule_hash) = travel_rule_hash {
+                    hash_parts.push(b"travel_rule_hash".to_vec());
+                    hash_parts.push(travel_rule_hash.to_bytes().to_vec());
+                }
+                hash_parts
             }
             MultisigOpParams::UpdateSigner {
                 wallet_address,
@@ -606,13 +2456,14 @@ impl MultisigOpParams {
                 bytes.push(slot_update_type.to_u8());
                 bytes.push(slot_id.value as u8);
                 bytes.extend_from_slice(signer.key.as_ref());
-                hash(&bytes)
+                vec![bytes]
             }
             MultisigOpParams::DAppTransaction {
                 wallet_address,
                 account_guid_hash,
                 dapp,
                 instructions,
+                rent_funding_limit,
             } => {
                 let mut bytes: Vec<u8> = Vec::new();
                 bytes.push(7);
@@ -621,12 +2472,13 @@ impl MultisigOpParams {
                 let mut buf = vec![0; DAppBookEntry::LEN];
                 dapp.pack_into_slice(buf.as_mut_slice());
                 bytes.extend_from_slice(&buf[..]);
+                bytes.put_u64_le(*rent_funding_limit);
                 bytes.put_u16_le(instructions.len().as_u16());
                 for instruction in instructions.into_iter() {
                     append_instruction_expanded(instruction, &mut bytes);
                 }
 
-                hash(&bytes)
+                vec![bytes]
             }
             MultisigOpParams::UpdateWalletConfigPolicy {
                 wallet_address,
@@ -634,7 +2486,7 @@ impl MultisigOpParams {
             } => {
                 let mut update_bytes: Vec<u8> = Vec::new();
                 update.pack(&mut update_bytes);
-                Self::hash_wallet_update_op(6, wallet_address, update_bytes)
+                Self::wallet_update_op_parts(6, wallet_address, update_bytes)
             }
             MultisigOpParams::UpdateDAppBook {
                 wallet_address,
@@ -642,7 +2494,7 @@ impl MultisigOpParams {
             } => {
                 let mut update_bytes: Vec<u8> = Vec::new();
                 update.pack(&mut update_bytes);
-                Self::hash_wallet_update_op(9, wallet_address, update_bytes)
+                Self::wallet_update_op_parts(9, wallet_address, update_bytes)
             }
             MultisigOpParams::AddressBookUpdate {
                 wallet_address,
@@ -650,7 +2502,7 @@ impl MultisigOpParams {
             } => {
                 let mut update_bytes: Vec<u8> = Vec::new();
                 update.pack(&mut update_bytes);
-                Self::hash_wallet_update_op(10, wallet_address, update_bytes)
+                Self::wallet_update_op_parts(10, wallet_address, update_bytes)
             }
             MultisigOpParams::CreateBalanceAccount {
                 wallet_address,
@@ -659,7 +2511,7 @@ impl MultisigOpParams {
             } => {
                 let mut update_bytes: Vec<u8> = Vec::new();
                 creation_params.pack(&mut update_bytes);
-                Self::hash_balance_account_update_op(
+                Self::balance_account_update_op_parts(
                     1,
                     wallet_address,
                     account_guid_hash,
@@ -676,7 +2528,7 @@ impl MultisigOpParams {
                 bytes.extend_from_slice(&wallet_address.to_bytes());
                 bytes.extend_from_slice(account_guid_hash.to_bytes());
                 bytes.extend_from_slice(account_name_hash.to_bytes());
-                hash(&bytes)
+                vec![bytes]
             }
             MultisigOpParams::UpdateBalanceAccountPolicy {
                 wallet_address,
@@ -685,7 +2537,7 @@ impl MultisigOpParams {
             } => {
                 let mut update_bytes: Vec<u8> = Vec::new();
                 update.pack(&mut update_bytes);
-                Self::hash_balance_account_update_op(
+                Self::balance_account_update_op_parts(
                     12,
                     wallet_address,
                     account_guid_hash,
@@ -697,15 +2549,1284 @@ impl MultisigOpParams {
                 account_guid_hash,
                 whitelist_enabled,
                 dapps_enabled,
+                sol_transfers_enabled,
+                spl_transfers_enabled,
             } => {
-                let mut bytes: Vec<u8> = Vec::with_capacity(1 + PUBKEY_BYTES + 32 + 2 + 2);
+                let mut bytes: Vec<u8> = Vec::with_capacity(1 + PUBKEY_BYTES + 32 + 2 + 2 + 2 + 2);
                 bytes.push(8);
                 bytes.extend_from_slice(&wallet_address.to_bytes());
                 bytes.extend_from_slice(account_guid_hash.to_bytes());
                 pack_option(whitelist_enabled.as_ref(), &mut bytes);
                 pack_option(dapps_enabled.as_ref(), &mut bytes);
-                hash(&bytes)
+                pack_transfer_settings(sol_transfers_enabled, spl_transfers_enabled, &mut bytes);
+                vec![bytes]
+            }
+            MultisigOpParams::VerifyAddressBookEntries {
+                wallet_address,
+                entries,
+            } => {
+                let mut entries_bytes: Vec<u8> = Vec::new();
+                append_address_book_entries(entries, &mut entries_bytes);
+                Self::wallet_update_op_parts(13, wallet_address, entries_bytes)
+            }
+            MultisigOpParams::ActivityCheckpoint {
+                wallet_address,
+                checkpoint_index,
+            } => Self::wallet_update_op_parts(
+                14,
+                wallet_address,
+                checkpoint_index.to_le_bytes().to_vec(),
+            ),
+            MultisigOpParams::AddressBookPageUpdate {
+                wallet_address,
+                page_index,
+                update,
+            } => {
+                let mut update_bytes: Vec<u8> = vec![*page_index];
+                update.pack(&mut update_bytes);
+                Self::wallet_update_op_parts(15, wallet_address, update_bytes)
+            }
+            MultisigOpParams::RepairWallet { wallet_address } => {
+                Self::wallet_update_op_parts(16, wallet_address, Vec::new())
+            }
+            MultisigOpParams::UpdateApproverDelegations {
+                wallet_address,
+                update,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                Self::wallet_update_op_parts(17, wallet_address, update_bytes)
+            }
+            MultisigOpParams::UpdateWalletSignerDirectory {
+                wallet_address,
+                update,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                Self::wallet_update_op_parts(18, wallet_address, update_bytes)
+            }
+            MultisigOpParams::UpdateWalletCanary {
+                wallet_address,
+                canary_key,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                append_optional_pubkey(canary_key, &mut update_bytes);
+                Self::wallet_update_op_parts(19, wallet_address, update_bytes)
+            }
+            MultisigOpParams::InternalTransfer {
+                wallet_address,
+                source_account_guid_hash,
+                destination_account_guid_hash,
+                amount,
+                token_mint,
+            } => {
+                let mut bytes: Vec<u8> = Vec::with_capacity(1 + PUBKEY_BYTES * 4 + 8);
+                bytes.push(20); // type code
+                bytes.extend_from_slice(&wallet_address.to_bytes());
+                bytes.extend_from_slice(source_account_guid_hash.to_bytes());
+                bytes.extend_from_slice(destination_account_guid_hash.to_bytes());
+                bytes.extend_from_slice(&amount.to_le_bytes());
+                bytes.extend_from_slice(&token_mint.to_bytes());
+                vec![bytes]
+            }
+            MultisigOpParams::UpdateGovernedParameters {
+                wallet_address,
+                update,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                Self::wallet_update_op_parts(21, wallet_address, update_bytes)
+            }
+            MultisigOpParams::UpdateSigners {
+                wallet_address,
+                update,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                Self::wallet_update_op_parts(22, wallet_address, update_bytes)
+            }
+            MultisigOpParams::UpdateViewers {
+                wallet_address,
+                update,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                Self::wallet_update_op_parts(23, wallet_address, update_bytes)
+            }
+            MultisigOpParams::UpdateSignerOrganizations {
+                wallet_address,
+                update,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                Self::wallet_update_op_parts(24, wallet_address, update_bytes)
+            }
+            MultisigOpParams::UpdateAssistant {
+                wallet_address,
+                assistant,
+            } => Self::wallet_update_op_parts(25, wallet_address, assistant.to_bytes().to_vec()),
+            MultisigOpParams::AddressBookUpdateChunked {
+                wallet_address,
+                update_hash,
+            } => Self::wallet_update_op_parts(26, wallet_address, update_hash.to_bytes().to_vec()),
+            MultisigOpParams::ExportWalletState {
+                wallet_address,
+                target_program_id,
+            } => Self::wallet_update_op_parts(
+                27,
+                wallet_address,
+                target_program_id.to_bytes().to_vec(),
+            ),
+            MultisigOpParams::CombinedTransfer {
+                wallet_address,
+                account_guid_hash,
+                destination,
+                transfer,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update_bytes.extend_from_slice(account_guid_hash.to_bytes());
+                update_bytes.extend_from_slice(destination.as_ref());
+                transfer.pack(&mut update_bytes);
+                Self::wallet_update_op_parts(28, wallet_address, update_bytes)
+            }
+            MultisigOpParams::UpdateWalletMetadata {
+                wallet_address,
+                metadata_hash,
+            } => {
+                Self::wallet_update_op_parts(29, wallet_address, metadata_hash.to_bytes().to_vec())
+            }
+            MultisigOpParams::UpdateDAppTemplates {
+                wallet_address,
+                update,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                Self::wallet_update_op_parts(30, wallet_address, update_bytes)
+            }
+            MultisigOpParams::CloseWallet {
+                wallet_address,
+                rent_collector,
+            } => {
+                Self::wallet_update_op_parts(31, wallet_address, rent_collector.to_bytes().to_vec())
+            }
+        }
+    }
+}
+
+#[test]
+fn test_record_approval_disposition_in_place_matches_unpacked_path() {
+    let approvers = vec![
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let params = MultisigOpParams::ActivityCheckpoint {
+        wallet_address: Pubkey::new_unique(),
+        checkpoint_index: 0,
+    };
+    let clock = Clock {
+        unix_timestamp: 100,
+        ..Clock::default()
+    };
+
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    multisig_op
+        .init(
+            approvers.clone(),
+            2,
+            0,
+            1000,
+            params.clone(),
+            true,
+            InitiatorClass::Approver,
+            Pubkey::new_unique(),
+        )
+        .unwrap();
+    let mut in_place_data = vec![0; MultisigOp::LEN];
+    MultisigOp::pack(multisig_op, &mut in_place_data).unwrap();
+    let mut unpacked_data = in_place_data.clone();
+
+    let stranger = Pubkey::new_unique();
+    let steps = vec![
+        (
+            approvers[0],
+            true,
+            ApprovalDisposition::APPROVE,
+            DenialReason::None,
+        ),
+        (
+            approvers[0],
+            true,
+            ApprovalDisposition::APPROVE,
+            DenialReason::None,
+        ),
+        (
+            approvers[0],
+            true,
+            ApprovalDisposition::DENY,
+            DenialReason::Other,
+        ),
+        (
+            approvers[1],
+            false,
+            ApprovalDisposition::APPROVE,
+            DenialReason::None,
+        ),
+        (
+            stranger,
+            true,
+            ApprovalDisposition::APPROVE,
+            DenialReason::None,
+        ),
+        (
+            approvers[1],
+            true,
+            ApprovalDisposition::NONE,
+            DenialReason::None,
+        ),
+        (
+            approvers[1],
+            true,
+            ApprovalDisposition::APPROVE,
+            DenialReason::Other,
+        ),
+        (
+            approvers[1],
+            true,
+            ApprovalDisposition::DENY,
+            DenialReason::None,
+        ),
+        (
+            approvers[1],
+            true,
+            ApprovalDisposition::DENY,
+            DenialReason::SuspectedFraud,
+        ),
+        (
+            approvers[2],
+            true,
+            ApprovalDisposition::APPROVE,
+            DenialReason::None,
+        ),
+        (
+            approvers[2],
+            true,
+            ApprovalDisposition::APPROVE,
+            DenialReason::None,
+        ),
+    ];
+
+    for (key, is_signer, disposition, denial_reason) in steps {
+        let reason_hash = Some(hash(key.as_ref()));
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut account_data: [u8; 0] = [0; 0];
+        let approver = AccountInfo::new(
+            &key,
+            is_signer,
+            false,
+            &mut lamports,
+            &mut account_data,
+            &owner,
+            false,
+            0,
+        );
+
+        let in_place_result = MultisigOp::record_approval_disposition_in_place(
+            &mut in_place_data,
+            &params.hash(),
+            &approver,
+            disposition,
+            denial_reason,
+            reason_hash,
+            None,
+            &clock,
+        );
+
+        let mut multisig_op = MultisigOp::unpack(&unpacked_data).unwrap();
+        let unpacked_result = multisig_op.validate_and_record_approval_disposition(
+            &approver,
+            disposition,
+            denial_reason,
+            reason_hash,
+            None,
+            &clock,
+        );
+        if unpacked_result.is_ok() {
+            MultisigOp::pack(multisig_op, &mut unpacked_data).unwrap();
+        }
+
+        assert_eq!(in_place_result, unpacked_result);
+        assert_eq!(in_place_data, unpacked_data);
+    }
+
+    let multisig_op = MultisigOp::unpack(&in_place_data).unwrap();
+    assert_eq!(
+        multisig_op.operation_disposition,
+        OperationDisposition::APPROVED
+    );
+    assert_eq!(
+        MultisigOp::record_approval_disposition_in_place(
+            &mut in_place_data,
+            &Hash::new_unique(),
+            &AccountInfo::new(
+                &approvers[0],
+                true,
+                false,
+                &mut 0,
+                &mut [],
+                &Pubkey::new_unique(),
+                false,
+                0,
+            ),
+            ApprovalDisposition::APPROVE,
+            DenialReason::None,
+            None,
+            None,
+            &clock,
+        ),
+        Err(WalletError::InvalidSignature.into())
+    );
+}
+
+#[test]
+fn test_approvers_change_dispositions_while_undecided() {
+    let approvers = vec![
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let params = MultisigOpParams::ActivityCheckpoint {
+        wallet_address: Pubkey::new_unique(),
+        checkpoint_index: 0,
+    };
+    let clock = Clock {
+        unix_timestamp: 100,
+        ..Clock::default()
+    };
+
+    for disposition_changes_allowed in vec![false, true] {
+        let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+        multisig_op.disposition_changes_allowed = disposition_changes_allowed;
+        multisig_op
+            .init(
+                approvers.clone(),
+                2,
+                0,
+                1000,
+                params.clone(),
+                false,
+                InitiatorClass::Approver,
+                Pubkey::new_unique(),
+            )
+            .unwrap();
+        let mut in_place_data = vec![0; MultisigOp::LEN];
+        MultisigOp::pack(multisig_op, &mut in_place_data).unwrap();
+        let mut unpacked_data = in_place_data.clone();
+
+        let mut record = |key: Pubkey, disposition: ApprovalDisposition| {
+            let owner = Pubkey::new_unique();
+            let mut lamports = 0;
+            let mut account_data: [u8; 0] = [0; 0];
+            let approver = AccountInfo::new(
+                &key,
+                true,
+                false,
+                &mut lamports,
+                &mut account_data,
+                &owner,
+                false,
+                0,
+            );
+            let in_place_result = MultisigOp::record_approval_disposition_in_place(
+                &mut in_place_data,
+                &params.hash(),
+                &approver,
+                disposition,
+                DenialReason::None,
+                None,
+                None,
+                &clock,
+            );
+            let mut multisig_op = MultisigOp::unpack(&unpacked_data).unwrap();
+            let unpacked_result = multisig_op.validate_and_record_approval_disposition(
+                &approver,
+                disposition,
+                DenialReason::None,
+                None,
+                None,
+                &clock,
+            );
+            if unpacked_result.is_ok() {
+                MultisigOp::pack(multisig_op, &mut unpacked_data).unwrap();
             }
+            assert_eq!(in_place_result, unpacked_result);
+            assert_eq!(in_place_data, unpacked_data);
+            in_place_result.map(|_| MultisigOp::unpack(&in_place_data).unwrap())
+        };
+
+        record(approvers[0], ApprovalDisposition::APPROVE).unwrap();
+        if !disposition_changes_allowed {
+            assert_eq!(
+                record(approvers[0], ApprovalDisposition::DENY).unwrap_err(),
+                WalletError::InvalidDisposition.into()
+            );
+            continue;
+        }
+
+        let multisig_op = record(approvers[0], ApprovalDisposition::DENY).unwrap();
+        assert_eq!(multisig_op.disposition_records[0].disposition_changes, 1);
+        assert_eq!(
+            multisig_op.get_disposition_count(ApprovalDisposition::APPROVE),
+            0
+        );
+        assert_eq!(
+            multisig_op.get_disposition_count(ApprovalDisposition::DENY),
+            1
+        );
+        let multisig_op = record(approvers[1], ApprovalDisposition::APPROVE).unwrap();
+        assert_eq!(
+            multisig_op.operation_disposition,
+            OperationDisposition::NONE
+        );
+        let multisig_op = record(approvers[0], ApprovalDisposition::APPROVE).unwrap();
+        assert_eq!(multisig_op.disposition_records[0].disposition_changes, 2);
+        assert_eq!(multisig_op.disposition_records[1].disposition_changes, 0);
+        assert_eq!(
+            multisig_op.operation_disposition,
+            OperationDisposition::APPROVED
+        );
+        assert_eq!(
+            record(approvers[1], ApprovalDisposition::DENY).unwrap_err(),
+            WalletError::InvalidDisposition.into()
+        );
+    }
+}
+
+#[test]
+fn test_veto_denies_approved_op() {
+    let approvers = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+    let veto_approver = Pubkey::new_unique();
+    let params = MultisigOpParams::ActivityCheckpoint {
+        wallet_address: Pubkey::new_unique(),
+        checkpoint_index: 0,
+    };
+    let clock = Clock {
+        unix_timestamp: 100,
+        ..Clock::default()
+    };
+
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    multisig_op
+        .init(
+            approvers.clone(),
+            1,
+            0,
+            1000,
+            params.clone(),
+            false,
+            InitiatorClass::Approver,
+            Pubkey::new_unique(),
+        )
+        .unwrap();
+    multisig_op.veto_approvers = vec![veto_approver];
+    let mut in_place_data = vec![0; MultisigOp::LEN];
+    MultisigOp::pack(multisig_op, &mut in_place_data).unwrap();
+    let mut unpacked_data = in_place_data.clone();
+
+    // a veto approver can't approve, only deny
+    let steps = vec![
+        (approvers[0], ApprovalDisposition::APPROVE),
+        (veto_approver, ApprovalDisposition::APPROVE),
+        (veto_approver, ApprovalDisposition::DENY),
+    ];
+    for (key, disposition) in steps {
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut account_data: [u8; 0] = [0; 0];
+        let approver = AccountInfo::new(
+            &key,
+            true,
+            false,
+            &mut lamports,
+            &mut account_data,
+            &owner,
+            false,
+            0,
+        );
+        let in_place_result = MultisigOp::record_approval_disposition_in_place(
+            &mut in_place_data,
+            &params.hash(),
+            &approver,
+            disposition,
+            DenialReason::None,
+            None,
+            None,
+            &clock,
+        );
+
+        let mut multisig_op = MultisigOp::unpack(&unpacked_data).unwrap();
+        let unpacked_result = multisig_op.validate_and_record_approval_disposition(
+            &approver,
+            disposition,
+            DenialReason::None,
+            None,
+            None,
+            &clock,
+        );
+        if unpacked_result.is_ok() {
+            MultisigOp::pack(multisig_op, &mut unpacked_data).unwrap();
+        }
+
+        assert_eq!(in_place_result, unpacked_result);
+        assert_eq!(in_place_data, unpacked_data);
+        if key == veto_approver && disposition == ApprovalDisposition::APPROVE {
+            assert_eq!(in_place_result, Err(WalletError::InvalidApprover.into()));
+        }
+    }
+
+    let multisig_op = MultisigOp::unpack(&in_place_data).unwrap();
+    assert_eq!(
+        multisig_op.operation_disposition,
+        OperationDisposition::DENIED
+    );
+    assert_eq!(
+        multisig_op.get_disposition_count(ApprovalDisposition::APPROVE),
+        1
+    );
+}
+
+#[test]
+fn test_renew_preserves_dispositions() {
+    let approvers = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+    let params = MultisigOpParams::ActivityCheckpoint {
+        wallet_address: Pubkey::new_unique(),
+        checkpoint_index: 0,
+    };
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    multisig_op
+        .init(
+            approvers.clone(),
+            2,
+            0,
+            1000,
+            params.clone(),
+            false,
+            InitiatorClass::Approver,
+            Pubkey::new_unique(),
+        )
+        .unwrap();
+    multisig_op.disposition_records[0].disposition = ApprovalDisposition::APPROVE;
+
+    let clock = Clock {
+        unix_timestamp: 1500,
+        ..Clock::default()
+    };
+    assert_eq!(
+        multisig_op.update_operation_disposition(&clock),
+        OperationDisposition::EXPIRED
+    );
+
+    assert_eq!(
+        multisig_op.renew(
+            &params.hash(),
+            &Pubkey::new_unique(),
+            Duration::from_secs(1000),
+            &clock
+        ),
+        Err(WalletError::InvalidApprover.into())
+    );
+    assert_eq!(
+        multisig_op.renew(
+            &params.hash(),
+            &approvers[1],
+            Duration::from_secs(1001),
+            &clock
+        ),
+        Err(WalletError::InvalidApprovalTimeout.into())
+    );
+
+    multisig_op
+        .renew(
+            &params.hash(),
+            &approvers[1],
+            Duration::from_secs(1000),
+            &clock,
+        )
+        .unwrap();
+    assert_eq!(multisig_op.expires_at, 2000);
+    assert_eq!(
+        multisig_op.operation_disposition,
+        OperationDisposition::NONE
+    );
+    assert_eq!(
+        multisig_op.get_disposition_count(ApprovalDisposition::APPROVE),
+        1
+    );
+
+    let mut data = vec![0; MultisigOp::LEN];
+    MultisigOp::pack(multisig_op, &mut data).unwrap();
+    let mut multisig_op = MultisigOp::unpack(&data).unwrap();
+    assert_eq!(multisig_op.renewed_by, Some(approvers[1]));
+    assert_eq!(multisig_op.renewed_at, 1500);
+    assert_eq!(multisig_op.approval_timeout, Duration::from_secs(1000));
+
+    assert_eq!(
+        multisig_op.renew(
+            &params.hash(),
+            &approvers[1],
+            Duration::from_secs(1000),
+            &clock
+        ),
+        Err(WalletError::MultisigOpAlreadyRenewed.into())
+    );
+}
+
+#[test]
+fn test_approved_reports_why_an_op_did_not_run() {
+    let approvers = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+    let params = MultisigOpParams::ActivityCheckpoint {
+        wallet_address: Pubkey::new_unique(),
+        checkpoint_index: 0,
+    };
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    multisig_op
+        .init(
+            approvers.clone(),
+            2,
+            0,
+            1000,
+            params.clone(),
+            false,
+            InitiatorClass::Approver,
+            Pubkey::new_unique(),
+        )
+        .unwrap();
+    let at = |unix_timestamp: i64| Clock {
+        unix_timestamp,
+        ..Clock::default()
+    };
+
+    assert_eq!(
+        multisig_op.approved(&params, &at(500)),
+        Err(WalletError::NotApproved.into())
+    );
+    // pending ops that expired are closed without running
+    assert_eq!(multisig_op.approved(&params, &at(1001)), Ok(false));
+
+    for record in multisig_op.disposition_records.iter_mut() {
+        record.disposition = ApprovalDisposition::APPROVE;
+    }
+    multisig_op.update_operation_disposition(&at(500));
+    assert_eq!(multisig_op.approved(&params, &at(500)), Ok(true));
+    assert_eq!(
+        multisig_op.approved(&params, &at(1001)),
+        Err(WalletError::OpExpired.into())
+    );
+
+    // an approved op that lapsed can be renewed and then finalized
+    multisig_op
+        .renew(
+            &params.hash(),
+            &approvers[0],
+            Duration::from_secs(1000),
+            &at(1001),
+        )
+        .unwrap();
+    assert_eq!(multisig_op.approved(&params, &at(1001)), Ok(true));
+
+    // but if it lapses again it is closed without running
+    multisig_op.update_operation_disposition(&at(1001));
+    assert_eq!(
+        multisig_op.operation_disposition,
+        OperationDisposition::APPROVED
+    );
+    assert_eq!(multisig_op.approved(&params, &at(2001)), Ok(false));
+}
+
+#[test]
+fn test_approvals_must_span_organizations() {
+    use crate::model::governed_parameter::GovernedParameter;
+
+    let approvers = vec![
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    for (i, (approver, organization)) in approvers.iter().zip([1, 1, 2].iter()).enumerate() {
+        wallet
+            .signers
+            .insert(SlotId::new(i), Signer::new(*approver));
+        wallet
+            .signer_organizations
+            .set(SlotId::new(i), Some(*organization));
+    }
+    wallet
+        .governed_parameters
+        .set(GovernedParameter::MinDistinctApproverOrganizations, Some(2));
+
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    multisig_op
+        .init(
+            approvers.clone(),
+            2,
+            0,
+            1000,
+            MultisigOpParams::RepairWallet {
+                wallet_address: Pubkey::new_unique(),
+            },
+            false,
+            InitiatorClass::Approver,
+            Pubkey::new_unique(),
+        )
+        .unwrap();
+    multisig_op.require_distinct_organizations(&wallet);
+
+    let mut data = vec![0; MultisigOp::LEN];
+    MultisigOp::pack(multisig_op, &mut data).unwrap();
+    let mut multisig_op = MultisigOp::unpack(&data).unwrap();
+    assert_eq!(multisig_op.organizations_required, 2);
+    assert_eq!(
+        multisig_op.approver_organizations,
+        vec![Some(1), Some(1), Some(2)]
+    );
+
+    let clock = Clock::default();
+    multisig_op.disposition_records[0].disposition = ApprovalDisposition::APPROVE;
+    multisig_op.disposition_records[1].disposition = ApprovalDisposition::APPROVE;
+    assert_eq!(
+        multisig_op.update_operation_disposition(&clock),
+        OperationDisposition::NONE
+    );
+    multisig_op.disposition_records[2].disposition = ApprovalDisposition::APPROVE;
+    assert_eq!(
+        multisig_op.update_operation_disposition(&clock),
+        OperationDisposition::APPROVED
+    );
+}
+
+#[test]
+fn test_init_rejects_initialized_and_foreign_accounts() {
+    let params = MultisigOpParams::RepairWallet {
+        wallet_address: Pubkey::new_unique(),
+    };
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    let init = |multisig_op: &mut MultisigOp| {
+        multisig_op.init(
+            vec![Pubkey::new_unique()],
+            1,
+            0,
+            1000,
+            params.clone(),
+            false,
+            InitiatorClass::Approver,
+            Pubkey::new_unique(),
+        )
+    };
+    init(&mut multisig_op).unwrap();
+
+    let mut data = vec![0; MultisigOp::LEN];
+    MultisigOp::pack(multisig_op, &mut data).unwrap();
+    assert_eq!(data[0], AccountType::MultisigOp as u8);
+    let mut multisig_op = MultisigOp::unpack_unchecked(&data).unwrap();
+    assert_eq!(
+        init(&mut multisig_op),
+        Err(ProgramError::AccountAlreadyInitialized)
+    );
+
+    data[0] = AccountType::Wallet as u8;
+    assert_eq!(
+        MultisigOp::unpack_unchecked(&data),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn test_init_reuses_denied_or_expired_ops() {
+    let params = MultisigOpParams::RepairWallet {
+        wallet_address: Pubkey::new_unique(),
+    };
+    let approver = Pubkey::new_unique();
+    let init = |multisig_op: &mut MultisigOp, started_at: i64| {
+        multisig_op.init(
+            vec![approver],
+            1,
+            started_at,
+            started_at + 1000,
+            params.clone(),
+            false,
+            InitiatorClass::Approver,
+            Pubkey::new_unique(),
+        )
+    };
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    init(&mut multisig_op, 0).unwrap();
+
+    // still pending
+    assert_eq!(
+        init(&mut multisig_op, 1000),
+        Err(ProgramError::AccountAlreadyInitialized)
+    );
+
+    // expired
+    init(&mut multisig_op, 1001).unwrap();
+    assert_eq!(multisig_op.started_at, 1001);
+    assert_eq!(multisig_op.expires_at, 2001);
+
+    // approved, but not yet expired
+    multisig_op.disposition_records[0].disposition = ApprovalDisposition::APPROVE;
+    multisig_op.operation_disposition = OperationDisposition::APPROVED;
+    assert_eq!(
+        init(&mut multisig_op, 1002),
+        Err(ProgramError::AccountAlreadyInitialized)
+    );
+
+    // denied, with the previous dispositions cleared on reuse
+    multisig_op.disposition_records[0].disposition = ApprovalDisposition::DENY;
+    multisig_op.operation_disposition = OperationDisposition::DENIED;
+    init(&mut multisig_op, 1002).unwrap();
+    assert_eq!(
+        multisig_op.operation_disposition,
+        OperationDisposition::NONE
+    );
+    assert_eq!(
+        multisig_op.disposition_records[0].disposition,
+        ApprovalDisposition::NONE
+    );
+}
+
+#[test]
+fn test_init_only_accepts_the_params_an_account_was_created_for() {
+    let params = MultisigOpParams::RepairWallet {
+        wallet_address: Pubkey::new_unique(),
+    };
+    let other_params = MultisigOpParams::RepairWallet {
+        wallet_address: Pubkey::new_unique(),
+    };
+    let init = |multisig_op: &mut MultisigOp, params: &MultisigOpParams| {
+        multisig_op.init(
+            vec![Pubkey::new_unique()],
+            1,
+            0,
+            1000,
+            params.clone(),
+            false,
+            InitiatorClass::Approver,
+            Pubkey::new_unique(),
+        )
+    };
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    multisig_op.params_hash = params.hash();
+
+    assert_eq!(
+        init(&mut multisig_op, &other_params),
+        Err(WalletError::OpParamsMismatch.into())
+    );
+    init(&mut multisig_op, &params).unwrap();
+
+    // accounts created without params take any op
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    init(&mut multisig_op, &other_params).unwrap();
+}
+
+#[test]
+fn test_delegate_records_disposition_for_approver() {
+    let approvers = vec![
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let delegate = Pubkey::new_unique();
+    let expired_delegate = Pubkey::new_unique();
+    let params = MultisigOpParams::RepairWallet {
+        wallet_address: Pubkey::new_unique(),
+    };
+    let clock = Clock {
+        unix_timestamp: 100,
+        ..Clock::default()
+    };
+
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    multisig_op
+        .init(
+            approvers.clone(),
+            2,
+            0,
+            1000,
+            params.clone(),
+            false,
+            InitiatorClass::Approver,
+            Pubkey::new_unique(),
+        )
+        .unwrap();
+    multisig_op.delegate_approvals(&[
+        ApproverDelegation {
+            approver: approvers[0],
+            delegate,
+            expires_at: 500,
+        },
+        ApproverDelegation {
+            approver: approvers[1],
+            delegate: expired_delegate,
+            expires_at: 50,
+        },
+        // delegates who approve the op themselves are skipped
+        ApproverDelegation {
+            approver: approvers[2],
+            delegate: approvers[1],
+            expires_at: 500,
+        },
+    ]);
+    assert_eq!(multisig_op.delegated_approvals.len(), 2);
+
+    let mut in_place_data = vec![0; MultisigOp::LEN];
+    MultisigOp::pack(multisig_op, &mut in_place_data).unwrap();
+    let mut unpacked_data = in_place_data.clone();
+
+    let steps: Vec<(Pubkey, ApprovalDisposition, ProgramResult)> = vec![
+        (delegate, ApprovalDisposition::APPROVE, Ok(())),
+        (
+            expired_delegate,
+            ApprovalDisposition::APPROVE,
+            Err(WalletError::InvalidApprover.into()),
+        ),
+        (approvers[0], ApprovalDisposition::APPROVE, Ok(())),
+        (
+            delegate,
+            ApprovalDisposition::DENY,
+            Err(WalletError::InvalidDisposition.into()),
+        ),
+    ];
+
+    for (key, disposition, expected_result) in steps {
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut account_data: [u8; 0] = [0; 0];
+        let approver = AccountInfo::new(
+            &key,
+            true,
+            false,
+            &mut lamports,
+            &mut account_data,
+            &owner,
+            false,
+            0,
+        );
+
+        let in_place_result = MultisigOp::record_approval_disposition_in_place(
+            &mut in_place_data,
+            &params.hash(),
+            &approver,
+            disposition,
+            DenialReason::None,
+            None,
+            None,
+            &clock,
+        );
+
+        let mut multisig_op = MultisigOp::unpack(&unpacked_data).unwrap();
+        let unpacked_result = multisig_op.validate_and_record_approval_disposition(
+            &approver,
+            disposition,
+            DenialReason::None,
+            None,
+            None,
+            &clock,
+        );
+        if unpacked_result.is_ok() {
+            MultisigOp::pack(multisig_op, &mut unpacked_data).unwrap();
         }
+
+        assert_eq!(in_place_result, expected_result);
+        assert_eq!(unpacked_result, expected_result);
+        assert_eq!(in_place_data, unpacked_data);
     }
+
+    let multisig_op = MultisigOp::unpack(&in_place_data).unwrap();
+    assert_eq!(
+        multisig_op.disposition_records[0].disposition,
+        ApprovalDisposition::APPROVE
+    );
+    assert_eq!(
+        multisig_op.disposition_records[1].disposition,
+        ApprovalDisposition::NONE
+    );
+    assert!(multisig_op.delegated_approvals[0].signed_by_delegate);
+    assert!(!multisig_op.delegated_approvals[1].signed_by_delegate);
+}
+
+#[test]
+fn test_executable_in_place() {
+    let params = MultisigOpParams::RepairWallet {
+        wallet_address: Pubkey::new_unique(),
+    };
+    let prerequisite = Hash::new_unique();
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    multisig_op
+        .init(
+            vec![Pubkey::new_unique()],
+            1,
+            0,
+            1000,
+            params.clone(),
+            false,
+            InitiatorClass::Approver,
+            Pubkey::new_unique(),
+        )
+        .unwrap();
+    multisig_op.prerequisite = Some(prerequisite);
+
+    let mut data = vec![0; MultisigOp::LEN];
+    MultisigOp::pack(multisig_op, &mut data).unwrap();
+    assert_eq!(MultisigOp::executable_in_place(&data, 500), None);
+
+    let mut multisig_op = MultisigOp::unpack(&data).unwrap();
+    multisig_op.operation_disposition = OperationDisposition::APPROVED;
+    MultisigOp::pack(multisig_op, &mut data).unwrap();
+    assert_eq!(
+        MultisigOp::executable_in_place(&data, 500),
+        Some((params.hash(), Some(prerequisite)))
+    );
+    // an approved op that expired can't be executed until it is renewed
+    assert_eq!(MultisigOp::executable_in_place(&data, 1001), None);
+}
+
+#[test]
+fn test_wallet_address_in_place() {
+    let wallet_address = Pubkey::new_unique();
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    multisig_op
+        .init(
+            vec![Pubkey::new_unique()],
+            1,
+            0,
+            1000,
+            MultisigOpParams::RepairWallet { wallet_address },
+            false,
+            InitiatorClass::Approver,
+            Pubkey::new_unique(),
+        )
+        .unwrap();
+    assert_eq!(multisig_op.wallet_address, wallet_address);
+
+    let mut data = vec![0; MultisigOp::LEN];
+    MultisigOp::pack(multisig_op, &mut data).unwrap();
+    assert_eq!(
+        MultisigOp::wallet_address_in_place(&data),
+        Ok(wallet_address)
+    );
+    assert_eq!(
+        MultisigOp::wallet_address_in_place(&data[1..]),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn test_expire_holds_transfer_value_until_expired() {
+    let wallet_address = Pubkey::new_unique();
+    let account_guid_hash = BalanceAccountGuidHash::new(&[7; 32]);
+    let at = |unix_timestamp: i64| Clock {
+        unix_timestamp,
+        ..Clock::default()
+    };
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    multisig_op
+        .init(
+            vec![Pubkey::new_unique()],
+            1,
+            0,
+            1000,
+            MultisigOpParams::InternalTransfer {
+                wallet_address,
+                source_account_guid_hash: account_guid_hash,
+                destination_account_guid_hash: BalanceAccountGuidHash::new(&[8; 32]),
+                amount: 123,
+                token_mint: Pubkey::default(),
+            },
+            false,
+            InitiatorClass::Approver,
+            Pubkey::new_unique(),
+        )
+        .unwrap();
+
+    let mut data = vec![0; MultisigOp::LEN];
+    MultisigOp::pack(multisig_op, &mut data).unwrap();
+    let mut multisig_op = MultisigOp::unpack(&data).unwrap();
+    assert_eq!(
+        multisig_op.held_transfer_value,
+        Some(HeldTransferValue {
+            wallet_address,
+            account_guid_hash,
+            amount: 123,
+        })
+    );
+
+    assert_eq!(
+        multisig_op.expire(&at(500)),
+        Err(WalletError::NotApproved.into())
+    );
+    assert_eq!(multisig_op.expire(&at(1001)), Ok(()));
+    assert_eq!(
+        multisig_op.operation_disposition,
+        OperationDisposition::EXPIRED
+    );
+
+    // an approved op that lapsed is kept for renewal
+    multisig_op.operation_disposition = OperationDisposition::APPROVED;
+    assert_eq!(
+        multisig_op.expire(&at(1001)),
+        Err(WalletError::InvalidDisposition.into())
+    );
+
+    // but expires once it has been renewed
+    multisig_op.renewed_by = Some(Pubkey::new_unique());
+    assert_eq!(multisig_op.expire(&at(1001)), Ok(()));
+    assert_eq!(
+        multisig_op.operation_disposition,
+        OperationDisposition::EXPIRED
+    );
+}
+
+#[test]
+fn test_ops_pinned_to_wallet_config_version() {
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    multisig_op
+        .init(
+            vec![Pubkey::new_unique()],
+            1,
+            0,
+            1000,
+            MultisigOpParams::UpdateWalletMetadata {
+                wallet_address: Pubkey::new_unique(),
+                metadata_hash: Hash::new_unique(),
+            },
+            false,
+            InitiatorClass::Approver,
+            Pubkey::new_unique(),
+        )
+        .unwrap();
+
+    // ops that aren't pinned run whatever the wallet's config version
+    wallet.record_config_change();
+    assert!(multisig_op.started_under(&wallet));
+
+    multisig_op.pin_config_version(&wallet);
+    let mut data = vec![0; MultisigOp::LEN];
+    MultisigOp::pack(multisig_op, &mut data).unwrap();
+    let multisig_op = MultisigOp::unpack(&data).unwrap();
+    assert_eq!(multisig_op.config_version, Some(1));
+    assert!(multisig_op.started_under(&wallet));
+
+    wallet.record_config_change();
+    assert!(!multisig_op.started_under(&wallet));
+}
+
+#[test]
+fn test_ops_pinned_to_balance_account_sequence() {
+    use crate::model::balance_account::BalanceAccount;
+
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    let mut balance_account = BalanceAccount::unpack_from_slice(&[0; BalanceAccount::LEN]).unwrap();
+    balance_account.guid_hash = BalanceAccountGuidHash::new(&[1; 32]);
+    wallet
+        .balance_accounts
+        .insert(SlotId::new(0), balance_account);
+    let account_guid_hash = balance_account.guid_hash;
+
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    multisig_op
+        .init(
+            vec![Pubkey::new_unique()],
+            1,
+            0,
+            1000,
+            MultisigOpParams::UpdateBalanceAccountName {
+                wallet_address: Pubkey::new_unique(),
+                account_guid_hash,
+                account_name_hash: BalanceAccountNameHash::new(&[2; 32]),
+            },
+            false,
+            InitiatorClass::Approver,
+            Pubkey::new_unique(),
+        )
+        .unwrap();
+    multisig_op
+        .pin_account_sequence(&wallet, &account_guid_hash)
+        .unwrap();
+    let mut data = vec![0; MultisigOp::LEN];
+    MultisigOp::pack(multisig_op, &mut data).unwrap();
+    let multisig_op = MultisigOp::unpack(&data).unwrap();
+    assert_eq!(multisig_op.account_sequence, Some((account_guid_hash, 0)));
+    assert!(multisig_op.started_under(&wallet));
+
+    // changes to the wallet's config alone don't stop an op that isn't
+    // pinned to it, but any change to its balance account does
+    wallet.record_config_change();
+    assert!(multisig_op.started_under(&wallet));
+    wallet
+        .record_balance_account_change(&account_guid_hash)
+        .unwrap();
+    assert!(!multisig_op.started_under(&wallet));
+}
+
+#[cfg(feature = "program")]
+#[test]
+fn test_spl_token_program_id() {
+    assert_eq!(spl_token_program::id(), spl_token::id());
+}
+
+#[test]
+fn test_compact_params_hash() {
+    let params = MultisigOpParams::Wrap {
+        wallet_address: Pubkey::new_unique(),
+        account_guid_hash: BalanceAccountGuidHash::new(&[3; 32]),
+        amount: 500,
+        direction: WrapDirection::WRAP,
+        destination: Some(Pubkey::new_unique()),
+        travel_rule_hash: None,
+    };
+    assert_eq!(params.hash_with(ParamsHashVersion::Legacy), params.hash());
+    assert_eq!(
+        params.hash_with(ParamsHashVersion::Compact),
+        hash(&params.canonical_bytes())
+    );
+    assert_ne!(params.params_digest(), params.hash());
+
+    // the domain and type code lead, then each part with its length
+    let bytes = params.canonical_bytes();
+    let domain_len = MultisigOpParams::DIGEST_DOMAIN.len();
+    assert_eq!(&bytes[..domain_len], MultisigOpParams::DIGEST_DOMAIN);
+    assert_eq!(bytes[domain_len], params.type_code());
+    assert_eq!(bytes[domain_len + 1], 2);
+    assert_eq!(
+        bytes[domain_len + 2..domain_len + 6],
+        (1 + PUBKEY_BYTES as u32 + 32 + 8 + 1).to_le_bytes()
+    );
+
+    // an op keeps the version it was started with
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    multisig_op.params_hash_version = ParamsHashVersion::Compact;
+    multisig_op
+        .init(
+            vec![Pubkey::new_unique()],
+            1,
+            0,
+            1000,
+            params.clone(),
+            false,
+            InitiatorClass::Approver,
+            Pubkey::new_unique(),
+        )
+        .unwrap();
+    let mut data = vec![0; MultisigOp::LEN];
+    MultisigOp::pack(multisig_op, &mut data).unwrap();
+    let multisig_op = MultisigOp::unpack(&data).unwrap();
+    assert_eq!(multisig_op.params_hash_version, ParamsHashVersion::Compact);
+    assert_eq!(multisig_op.params_hash, params.params_digest());
+    assert_eq!(
+        multisig_op.approved(&params, &Clock::default()),
+        Err(WalletError::NotApproved.into())
+    );
 }
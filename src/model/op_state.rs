@@ -0,0 +1,503 @@
+use crate::error::WalletError;
+use crate::model::multisig_op::OperationDisposition;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+
+/// The dispositions recorded on an op so far, against the ones it needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tally {
+    pub approvals: u8,
+    pub denials: u8,
+    pub dispositions_required: u8,
+    /// Distinct organizations among the approvers who approved.
+    pub organizations: u8,
+    pub organizations_required: u8,
+}
+
+/// Something that happens to an op, moving it through its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpEvent {
+    /// An approver's disposition was recorded, leaving the given tally.
+    Disposition(Tally),
+    /// A veto approver denied the op.
+    Veto,
+    /// The op is settled as expired, so that its account can be closed.
+    Expire,
+    /// The op is given `extension_secs` more to be approved and finalized.
+    Renew { extension_secs: i64 },
+    /// The op is finalized, running only if it was approved in time.
+    Finalize,
+}
+
+/// The lifecycle of a multisig op. Ops start out open with no disposition,
+/// are approved, denied or expire, and end up finalized:
+///
+/// ```text
+/// NONE ──┬──> APPROVED ──┬──> finalized, executed
+///        ├──> DENIED ────┤
+///        └──> EXPIRED ───┴──> finalized, not executed
+/// ```
+///
+/// An op approved but not finalized in time is kept open while it can still
+/// be renewed, and settles as expired once it can't.
+///
+/// `apply` only depends on the state, the event and the time, so off-chain
+/// services mirroring op accounts reach the same dispositions as the program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpState {
+    Open {
+        disposition: OperationDisposition,
+        expires_at: i64,
+        /// Whether the op can still be renewed, which it can only be once.
+        renewable: bool,
+    },
+    /// The op account was closed with the given disposition; only approved
+    /// ops are executed.
+    Finalized { disposition: OperationDisposition },
+}
+
+impl OpState {
+    pub fn disposition(&self) -> OperationDisposition {
+        match *self {
+            OpState::Open { disposition, .. } | OpState::Finalized { disposition } => disposition,
+        }
+    }
+
+    pub fn executed(&self) -> bool {
+        *self
+            == OpState::Finalized {
+                disposition: OperationDisposition::APPROVED,
+            }
+    }
+
+    /// The state the op is in after the event, at time `now`, or an error if
+    /// the event can't happen in the op's current state.
+    pub fn apply(&self, event: OpEvent, now: i64) -> Result<OpState, ProgramError> {
+        let (disposition, expires_at, renewable) = match *self {
+            OpState::Open {
+                disposition,
+                expires_at,
+                renewable,
+            } => (disposition, expires_at, renewable),
+            OpState::Finalized { .. } => {
+                msg!("Operation has already been finalized");
+                return Err(WalletError::AlreadyFinalized.into());
+            }
+        };
+        let open = |disposition| OpState::Open {
+            disposition,
+            expires_at,
+            renewable,
+        };
+        let expired = now > expires_at;
+
+        match event {
+            // dispositions recorded once the op has one no longer change it
+            OpEvent::Disposition(_) if disposition != OperationDisposition::NONE => Ok(*self),
+            OpEvent::Disposition(_) if expired => Ok(open(OperationDisposition::EXPIRED)),
+            OpEvent::Disposition(tally) => Ok(open(
+                if tally.approvals >= tally.dispositions_required
+                    && tally.organizations >= tally.organizations_required
+                {
+                    OperationDisposition::APPROVED
+                } else if tally.denials == tally.dispositions_required {
+                    OperationDisposition::DENIED
+                } else {
+                    OperationDisposition::NONE
+                },
+            )),
+
+            // a veto denies an op whatever approvals it has, unless it expired
+            OpEvent::Veto
+                if disposition == OperationDisposition::EXPIRED
+                    || (disposition == OperationDisposition::NONE && expired) =>
+            {
+                msg!("Operation has expired");
+                Err(WalletError::InvalidDisposition.into())
+            }
+            OpEvent::Veto => Ok(open(OperationDisposition::DENIED)),
+
+            OpEvent::Expire => match disposition {
+                OperationDisposition::EXPIRED => Ok(*self),
+                OperationDisposition::NONE if expired => Ok(open(OperationDisposition::EXPIRED)),
+                OperationDisposition::APPROVED if expired && !renewable => {
+                    Ok(open(OperationDisposition::EXPIRED))
+                }
+                OperationDisposition::NONE => {
+                    msg!("Operation does not expire until {}", expires_at);
+                    Err(WalletError::NotApproved.into())
+                }
+                OperationDisposition::APPROVED if expired => {
+                    msg!("Operation was approved and can still be renewed");
+                    Err(WalletError::InvalidDisposition.into())
+                }
+                _ => {
+                    msg!("Operation already has a final disposition");
+                    Err(WalletError::InvalidDisposition.into())
+                }
+            },
+
+            OpEvent::Renew { .. } if !renewable => {
+                msg!("Operation has already been renewed");
+                Err(WalletError::MultisigOpAlreadyRenewed.into())
+            }
+            // an op approved but not finalized in time can be renewed too
+            OpEvent::Renew { extension_secs } => match disposition {
+                OperationDisposition::NONE | OperationDisposition::EXPIRED => Ok(OpState::Open {
+                    disposition: OperationDisposition::NONE,
+                    expires_at: expires_at
+                        .checked_add(extension_secs)
+                        .ok_or(WalletError::AmountOverflow)?,
+                    renewable: false,
+                }),
+                OperationDisposition::APPROVED if expired => Ok(OpState::Open {
+                    disposition: OperationDisposition::NONE,
+                    expires_at: expires_at
+                        .checked_add(extension_secs)
+                        .ok_or(WalletError::AmountOverflow)?,
+                    renewable: false,
+                }),
+                _ => {
+                    msg!("Operation already has a final disposition");
+                    Err(WalletError::InvalidDisposition.into())
+                }
+            },
+
+            OpEvent::Finalize if disposition == OperationDisposition::NONE && now < expires_at => {
+                msg!("Operation is still waiting for approvals");
+                Err(WalletError::NotApproved.into())
+            }
+            // an approved op that lapsed is left in place so it can be
+            // renewed, rather than being silently closed without running
+            OpEvent::Finalize
+                if expired && renewable && disposition == OperationDisposition::APPROVED =>
+            {
+                msg!("Operation was approved but expired at {}", expires_at);
+                Err(WalletError::OpExpired.into())
+            }
+            OpEvent::Finalize => {
+                let disposition = if expired {
+                    OperationDisposition::EXPIRED
+                } else {
+                    disposition
+                };
+                msg!("OperationDisposition: [{}]", disposition.to_u8());
+                Ok(OpState::Finalized { disposition })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPIRES_AT: i64 = 1000;
+    const BEFORE: i64 = EXPIRES_AT - 1;
+    const AFTER: i64 = EXPIRES_AT + 1;
+
+    const DISPOSITIONS: [OperationDisposition; 4] = [
+        OperationDisposition::NONE,
+        OperationDisposition::APPROVED,
+        OperationDisposition::DENIED,
+        OperationDisposition::EXPIRED,
+    ];
+
+    fn open(disposition: OperationDisposition) -> OpState {
+        OpState::Open {
+            disposition,
+            expires_at: EXPIRES_AT,
+            renewable: true,
+        }
+    }
+
+    fn renewed(disposition: OperationDisposition) -> OpState {
+        OpState::Open {
+            disposition,
+            expires_at: EXPIRES_AT,
+            renewable: false,
+        }
+    }
+
+    fn tally(approvals: u8, denials: u8, organizations: u8) -> OpEvent {
+        OpEvent::Disposition(Tally {
+            approvals,
+            denials,
+            dispositions_required: 2,
+            organizations,
+            organizations_required: 1,
+        })
+    }
+
+    #[test]
+    fn test_dispositions_settle_open_ops() {
+        let none = open(OperationDisposition::NONE);
+        assert_eq!(none.apply(tally(1, 0, 1), BEFORE), Ok(none));
+        assert_eq!(
+            none.apply(tally(2, 0, 1), BEFORE),
+            Ok(open(OperationDisposition::APPROVED))
+        );
+        // approvals have to span the required organizations
+        assert_eq!(none.apply(tally(2, 0, 0), BEFORE), Ok(none));
+        assert_eq!(
+            none.apply(tally(0, 2, 0), BEFORE),
+            Ok(open(OperationDisposition::DENIED))
+        );
+        assert_eq!(
+            none.apply(tally(0, 2, 0), EXPIRES_AT),
+            Ok(open(OperationDisposition::DENIED))
+        );
+        assert_eq!(
+            none.apply(tally(2, 0, 1), AFTER),
+            Ok(open(OperationDisposition::EXPIRED))
+        );
+
+        for disposition in DISPOSITIONS[1..].iter() {
+            for now in [BEFORE, AFTER].iter() {
+                for event in [tally(2, 0, 1), tally(0, 2, 0), tally(0, 0, 0)].iter() {
+                    assert_eq!(
+                        open(*disposition).apply(*event, *now),
+                        Ok(open(*disposition))
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_veto() {
+        for (disposition, now, expected) in [
+            (
+                OperationDisposition::NONE,
+                BEFORE,
+                Some(OperationDisposition::DENIED),
+            ),
+            (OperationDisposition::NONE, AFTER, None),
+            (
+                OperationDisposition::APPROVED,
+                BEFORE,
+                Some(OperationDisposition::DENIED),
+            ),
+            (
+                OperationDisposition::APPROVED,
+                AFTER,
+                Some(OperationDisposition::DENIED),
+            ),
+            (
+                OperationDisposition::DENIED,
+                BEFORE,
+                Some(OperationDisposition::DENIED),
+            ),
+            (
+                OperationDisposition::DENIED,
+                AFTER,
+                Some(OperationDisposition::DENIED),
+            ),
+            (OperationDisposition::EXPIRED, BEFORE, None),
+            (OperationDisposition::EXPIRED, AFTER, None),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                open(*disposition).apply(OpEvent::Veto, *now),
+                match expected {
+                    Some(expected) => Ok(open(*expected)),
+                    None => Err(WalletError::InvalidDisposition.into()),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_expire() {
+        for (disposition, now, expected) in [
+            (
+                OperationDisposition::NONE,
+                BEFORE,
+                Err(WalletError::NotApproved),
+            ),
+            (
+                OperationDisposition::NONE,
+                AFTER,
+                Ok(OperationDisposition::EXPIRED),
+            ),
+            (
+                OperationDisposition::APPROVED,
+                BEFORE,
+                Err(WalletError::InvalidDisposition),
+            ),
+            (
+                OperationDisposition::APPROVED,
+                AFTER,
+                Err(WalletError::InvalidDisposition),
+            ),
+            (
+                OperationDisposition::DENIED,
+                BEFORE,
+                Err(WalletError::InvalidDisposition),
+            ),
+            (
+                OperationDisposition::DENIED,
+                AFTER,
+                Err(WalletError::InvalidDisposition),
+            ),
+            (
+                OperationDisposition::EXPIRED,
+                BEFORE,
+                Ok(OperationDisposition::EXPIRED),
+            ),
+            (
+                OperationDisposition::EXPIRED,
+                AFTER,
+                Ok(OperationDisposition::EXPIRED),
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                open(*disposition).apply(OpEvent::Expire, *now),
+                expected.clone().map(open).map_err(ProgramError::from)
+            );
+        }
+
+        // an approved op that lapsed expires once it can't be renewed anymore
+        assert_eq!(
+            renewed(OperationDisposition::APPROVED).apply(OpEvent::Expire, AFTER),
+            Ok(renewed(OperationDisposition::EXPIRED))
+        );
+        assert_eq!(
+            renewed(OperationDisposition::APPROVED).apply(OpEvent::Expire, BEFORE),
+            Err(WalletError::InvalidDisposition.into())
+        );
+    }
+
+    #[test]
+    fn test_renew() {
+        let renew = OpEvent::Renew {
+            extension_secs: 100,
+        };
+        let extended = OpState::Open {
+            disposition: OperationDisposition::NONE,
+            expires_at: EXPIRES_AT + 100,
+            renewable: false,
+        };
+        for (disposition, now, renewable) in [
+            (OperationDisposition::NONE, BEFORE, true),
+            (OperationDisposition::NONE, AFTER, true),
+            (OperationDisposition::APPROVED, BEFORE, false),
+            (OperationDisposition::APPROVED, AFTER, true),
+            (OperationDisposition::DENIED, BEFORE, false),
+            (OperationDisposition::DENIED, AFTER, false),
+            (OperationDisposition::EXPIRED, BEFORE, true),
+            (OperationDisposition::EXPIRED, AFTER, true),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                open(*disposition).apply(renew, *now),
+                if *renewable {
+                    Ok(extended)
+                } else {
+                    Err(WalletError::InvalidDisposition.into())
+                }
+            );
+            // only once
+            assert_eq!(
+                renewed(*disposition).apply(renew, *now),
+                Err(WalletError::MultisigOpAlreadyRenewed.into())
+            );
+        }
+    }
+
+    #[test]
+    fn test_finalize() {
+        let finalized = |disposition| -> Result<OpState, ProgramError> {
+            Ok(OpState::Finalized { disposition })
+        };
+        for (disposition, now, expected) in [
+            (
+                OperationDisposition::NONE,
+                BEFORE,
+                Err(WalletError::NotApproved.into()),
+            ),
+            (
+                OperationDisposition::NONE,
+                EXPIRES_AT,
+                finalized(OperationDisposition::NONE),
+            ),
+            (
+                OperationDisposition::NONE,
+                AFTER,
+                finalized(OperationDisposition::EXPIRED),
+            ),
+            (
+                OperationDisposition::APPROVED,
+                BEFORE,
+                finalized(OperationDisposition::APPROVED),
+            ),
+            (
+                OperationDisposition::APPROVED,
+                AFTER,
+                Err(WalletError::OpExpired.into()),
+            ),
+            (
+                OperationDisposition::DENIED,
+                BEFORE,
+                finalized(OperationDisposition::DENIED),
+            ),
+            (
+                OperationDisposition::DENIED,
+                AFTER,
+                finalized(OperationDisposition::EXPIRED),
+            ),
+            (
+                OperationDisposition::EXPIRED,
+                BEFORE,
+                finalized(OperationDisposition::EXPIRED),
+            ),
+            (
+                OperationDisposition::EXPIRED,
+                AFTER,
+                finalized(OperationDisposition::EXPIRED),
+            ),
+        ]
+        .iter()
+        {
+            let result = open(*disposition).apply(OpEvent::Finalize, *now);
+            assert_eq!(result, expected.clone());
+            assert_eq!(
+                result.map(|state| state.executed()).unwrap_or(false),
+                *disposition == OperationDisposition::APPROVED && *now < EXPIRES_AT
+            );
+        }
+
+        // an approved op that lapsed again after its renewal is closed unexecuted
+        let result = renewed(OperationDisposition::APPROVED).apply(OpEvent::Finalize, AFTER);
+        assert_eq!(result, finalized(OperationDisposition::EXPIRED));
+        assert!(!result.unwrap().executed());
+    }
+
+    #[test]
+    fn test_finalized_ops_take_no_more_events() {
+        let events = [
+            tally(2, 0, 1),
+            OpEvent::Veto,
+            OpEvent::Expire,
+            OpEvent::Renew {
+                extension_secs: 100,
+            },
+            OpEvent::Finalize,
+        ];
+        for disposition in DISPOSITIONS.iter() {
+            for event in events.iter() {
+                assert_eq!(
+                    OpState::Finalized {
+                        disposition: *disposition
+                    }
+                    .apply(*event, BEFORE),
+                    Err(WalletError::AlreadyFinalized.into())
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use crate::model::account_type::AccountType;
+use crate::model::multisig_op::{ApprovalDisposition, DenialReason};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::hash::{Hash, HASH_BYTES};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+/// An approver's disposition on an op, submitted to an account of its own
+/// rather than to the op account. As submitting only reads the op account,
+/// approvers submitting at the same time don't contend for its write lock;
+/// `CollectApprovalDispositions` applies any number of them to the op at once.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PendingDisposition {
+    pub is_initialized: bool,
+    pub multisig_op: Pubkey,
+    /// The approver, who paid for the account and gets its rent back.
+    pub approver: Pubkey,
+    pub params_hash: Hash,
+    pub disposition: ApprovalDisposition,
+    pub denial_reason: DenialReason,
+    pub reason_hash: Option<Hash>,
+}
+
+impl PendingDisposition {
+    pub const SEED: &'static [u8] = b"disposition";
+
+    /// Each approver has a single pending disposition account per op.
+    pub fn find_address(
+        multisig_op_address: &Pubkey,
+        approver: &Pubkey,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                multisig_op_address.as_ref(),
+                PendingDisposition::SEED,
+                approver.as_ref(),
+            ],
+            program_id,
+        )
+    }
+}
+
+impl Sealed for PendingDisposition {}
+
+impl IsInitialized for PendingDisposition {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for PendingDisposition {
+    const LEN: usize = 1 + // account type discriminator
+        PUBKEY_BYTES + // multisig_op
+        PUBKEY_BYTES + // approver
+        HASH_BYTES + // params_hash
+        1 + // disposition
+        1 + // denial_reason
+        1 + // has reason_hash
+        HASH_BYTES; // reason_hash
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, PendingDisposition::LEN];
+        let (
+            is_initialized_dst,
+            multisig_op_dst,
+            approver_dst,
+            params_hash_dst,
+            disposition_dst,
+            denial_reason_dst,
+            has_reason_hash_dst,
+            reason_hash_dst,
+        ) = mut_array_refs![
+            dst,
+            1,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            HASH_BYTES,
+            1,
+            1,
+            1,
+            HASH_BYTES
+        ];
+
+        is_initialized_dst[0] =
+            AccountType::PendingDisposition.to_discriminator(self.is_initialized);
+        multisig_op_dst.copy_from_slice(self.multisig_op.as_ref());
+        approver_dst.copy_from_slice(self.approver.as_ref());
+        params_hash_dst.copy_from_slice(self.params_hash.as_ref());
+        disposition_dst[0] = self.disposition.to_u8();
+        denial_reason_dst[0] = self.denial_reason.to_u8();
+        has_reason_hash_dst[0] = self.reason_hash.is_some() as u8;
+        reason_hash_dst.copy_from_slice(self.reason_hash.unwrap_or_default().as_ref());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, PendingDisposition::LEN];
+        let (
+            is_initialized,
+            multisig_op,
+            approver,
+            params_hash,
+            disposition,
+            denial_reason,
+            has_reason_hash,
+            reason_hash,
+        ) = array_refs![
+            src,
+            1,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            HASH_BYTES,
+            1,
+            1,
+            1,
+            HASH_BYTES
+        ];
+
+        Ok(PendingDisposition {
+            is_initialized: AccountType::PendingDisposition
+                .from_discriminator(is_initialized[0])?,
+            multisig_op: Pubkey::new_from_array(*multisig_op),
+            approver: Pubkey::new_from_array(*approver),
+            params_hash: Hash::new_from_array(*params_hash),
+            disposition: ApprovalDisposition::from_u8(disposition[0]),
+            denial_reason: DenialReason::from_u8(denial_reason[0]),
+            reason_hash: if has_reason_hash[0] == 1 {
+                Some(Hash::new_from_array(*reason_hash))
+            } else {
+                None
+            },
+        })
+    }
+}
+
+#[test]
+fn test_pack_unpack_pending_disposition() {
+    let pending_disposition = PendingDisposition {
+        is_initialized: true,
+        multisig_op: Pubkey::new_unique(),
+        approver: Pubkey::new_unique(),
+        params_hash: Hash::new_unique(),
+        disposition: ApprovalDisposition::DENY,
+        denial_reason: DenialReason::None,
+        reason_hash: Some(Hash::new_unique()),
+    };
+
+    let mut packed = vec![0; PendingDisposition::LEN];
+    PendingDisposition::pack(pending_disposition, &mut packed).unwrap();
+    assert_eq!(packed[0], AccountType::PendingDisposition as u8);
+    assert_eq!(
+        PendingDisposition::unpack(&packed).unwrap(),
+        pending_disposition
+    );
+}
@@ -0,0 +1,289 @@
+use crate::error::WalletError;
+use crate::model::account_type::AccountType;
+use crate::utils::{SlotId, Slots};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::{Hash, HASH_BYTES};
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+pub type PendingOpSlots = Slots<PendingOp, { PendingOps::MAX_PENDING_OPS }>;
+
+/// An op started for the wallet that has not been finalized yet.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Ord, PartialOrd)]
+pub struct PendingOp {
+    pub multisig_op: Pubkey,
+    pub params_hash: Hash,
+    pub expires_at: i64,
+}
+
+impl Sealed for PendingOp {}
+
+impl Pack for PendingOp {
+    const LEN: usize = PUBKEY_BYTES + HASH_BYTES + 8;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, PendingOp::LEN];
+        let (multisig_op_dst, params_hash_dst, expires_at_dst) =
+            mut_array_refs![dst, PUBKEY_BYTES, HASH_BYTES, 8];
+        multisig_op_dst.copy_from_slice(self.multisig_op.as_ref());
+        params_hash_dst.copy_from_slice(self.params_hash.as_ref());
+        *expires_at_dst = self.expires_at.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, PendingOp::LEN];
+        let (multisig_op_src, params_hash_src, expires_at_src) =
+            array_refs![src, PUBKEY_BYTES, HASH_BYTES, 8];
+        Ok(PendingOp {
+            multisig_op: Pubkey::new_from_array(*multisig_op_src),
+            params_hash: Hash::new_from_array(*params_hash_src),
+            expires_at: i64::from_le_bytes(*expires_at_src),
+        })
+    }
+}
+
+/// Registry of a wallet's pending ops, stored in a PDA derived from the
+/// wallet. When the registry is passed along, ops started for the wallet are
+/// added to it and removed once their account is closed, so clients can list
+/// a wallet's pending ops by reading one account.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PendingOps {
+    pub is_initialized: bool,
+    pub bump_seed: u8,
+    pub wallet: Pubkey,
+    pub ops: PendingOpSlots,
+    /// Params hashes of the ops most recently executed with the registry
+    /// passed along, oldest first, which ops depending on them check.
+    pub executed_ops: Vec<Hash>,
+}
+
+impl PendingOps {
+    pub const MAX_PENDING_OPS: usize = 64;
+    pub const MAX_EXECUTED_OPS: usize = 16;
+    pub const SEED: &'static [u8] = b"pending-ops";
+
+    pub fn find_address(wallet_address: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[wallet_address.as_ref(), PendingOps::SEED], program_id)
+    }
+
+    pub fn new(wallet_address: &Pubkey, bump_seed: u8) -> PendingOps {
+        PendingOps {
+            is_initialized: true,
+            bump_seed,
+            wallet: *wallet_address,
+            ops: PendingOpSlots::new(),
+            executed_ops: Vec::new(),
+        }
+    }
+
+    /// Returns the first initialized pending ops registry among the given
+    /// accounts and its account. The address is checked against the bump seed
+    /// stored in the account.
+    pub fn from_accounts<'a, 'b>(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<Option<(&'a AccountInfo<'b>, PendingOps)>, ProgramError> {
+        for account_info in accounts {
+            if account_info.owner != program_id || account_info.data_len() != PendingOps::LEN {
+                continue;
+            }
+            let registry = PendingOps::unpack_from_slice(&account_info.data.borrow())?;
+            if !registry.is_initialized {
+                continue;
+            }
+            let expected_address = Pubkey::create_program_address(
+                &[
+                    registry.wallet.as_ref(),
+                    PendingOps::SEED,
+                    &[registry.bump_seed],
+                ],
+                program_id,
+            )?;
+            if expected_address == *account_info.key {
+                return Ok(Some((account_info, registry)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Adds the op, or updates it if its account is already listed, which is
+    /// the case when an op is renewed or its account reused.
+    pub fn record(&mut self, op: PendingOp) -> ProgramResult {
+        if let Some((slot_id, _)) = self
+            .ops
+            .find_by(|pending| pending.multisig_op == op.multisig_op)
+        {
+            self.ops.replace(slot_id, op);
+            return Ok(());
+        }
+        let slot_id = (0..PendingOps::MAX_PENDING_OPS)
+            .map(SlotId::new)
+            .find(|slot_id| self.ops[*slot_id].is_none())
+            .ok_or_else(|| {
+                msg!("Pending ops registry is full; clean up finalized ops first");
+                WalletError::SlotCannotBeInserted
+            })?;
+        self.ops.insert(slot_id, op);
+        Ok(())
+    }
+
+    /// Remembers that the op with the given params hash was executed, dropping
+    /// the oldest executed op once there are too many.
+    pub fn record_executed(&mut self, params_hash: Hash) {
+        if self.executed_ops.len() == PendingOps::MAX_EXECUTED_OPS {
+            self.executed_ops.remove(0);
+        }
+        self.executed_ops.push(params_hash);
+    }
+
+    pub fn was_executed(&self, params_hash: &Hash) -> bool {
+        self.executed_ops.contains(params_hash)
+    }
+
+    /// Removes the op in the given account, returning whether it was listed.
+    pub fn remove(&mut self, multisig_op: &Pubkey) -> bool {
+        match self
+            .ops
+            .find_by(|pending| pending.multisig_op == *multisig_op)
+        {
+            Some((slot_id, pending)) => {
+                self.ops.remove(slot_id, pending);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Sealed for PendingOps {}
+
+impl IsInitialized for PendingOps {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for PendingOps {
+    const LEN: usize = 1 + // is_initialized
+        1 + // bump_seed
+        PUBKEY_BYTES + // wallet
+        PendingOpSlots::LEN +
+        1 + HASH_BYTES * PendingOps::MAX_EXECUTED_OPS; // executed_ops
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, PendingOps::LEN];
+        let (
+            is_initialized_dst,
+            bump_seed_dst,
+            wallet_dst,
+            ops_dst,
+            executed_ops_count_dst,
+            executed_ops_dst,
+        ) = mut_array_refs![
+            dst,
+            1,
+            1,
+            PUBKEY_BYTES,
+            PendingOpSlots::LEN,
+            1,
+            HASH_BYTES * PendingOps::MAX_EXECUTED_OPS
+        ];
+
+        is_initialized_dst[0] = AccountType::PendingOps.to_discriminator(self.is_initialized);
+        bump_seed_dst[0] = self.bump_seed;
+        wallet_dst.copy_from_slice(self.wallet.as_ref());
+        self.ops.pack_into_slice(ops_dst);
+        executed_ops_count_dst[0] = self.executed_ops.len() as u8;
+        executed_ops_dst.fill(0);
+        executed_ops_dst
+            .chunks_exact_mut(HASH_BYTES)
+            .zip(self.executed_ops.iter())
+            .for_each(|(chunk, params_hash)| chunk.copy_from_slice(params_hash.as_ref()));
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, PendingOps::LEN];
+        let (
+            is_initialized_src,
+            bump_seed_src,
+            wallet_src,
+            ops_src,
+            executed_ops_count_src,
+            executed_ops_src,
+        ) = array_refs![
+            src,
+            1,
+            1,
+            PUBKEY_BYTES,
+            PendingOpSlots::LEN,
+            1,
+            HASH_BYTES * PendingOps::MAX_EXECUTED_OPS
+        ];
+
+        Ok(PendingOps {
+            is_initialized: AccountType::PendingOps.from_discriminator(is_initialized_src[0])?,
+            bump_seed: bump_seed_src[0],
+            wallet: Pubkey::new_from_array(*wallet_src),
+            ops: PendingOpSlots::unpack_from_slice(ops_src)?,
+            executed_ops: executed_ops_src
+                .chunks_exact(HASH_BYTES)
+                .take(usize::from(executed_ops_count_src[0]))
+                .map(Hash::new)
+                .collect(),
+        })
+    }
+}
+
+#[test]
+fn test_pending_ops_record_and_remove() {
+    let mut registry = PendingOps::new(&Pubkey::new_unique(), 255);
+    let op = PendingOp {
+        multisig_op: Pubkey::new_unique(),
+        params_hash: Hash::new_unique(),
+        expires_at: 100,
+    };
+
+    registry.record(op).unwrap();
+    // recording the same account again updates its entry
+    let renewed = PendingOp {
+        expires_at: 200,
+        ..op
+    };
+    registry.record(renewed).unwrap();
+    assert_eq!(registry.ops.filled_slots(), vec![(SlotId::new(0), renewed)]);
+
+    for _ in 1..PendingOps::MAX_PENDING_OPS {
+        registry
+            .record(PendingOp {
+                multisig_op: Pubkey::new_unique(),
+                ..op
+            })
+            .unwrap();
+    }
+    assert_eq!(
+        registry.record(PendingOp {
+            multisig_op: Pubkey::new_unique(),
+            ..op
+        }),
+        Err(WalletError::SlotCannotBeInserted.into())
+    );
+
+    assert!(registry.remove(&op.multisig_op));
+    assert!(!registry.remove(&op.multisig_op));
+    assert!(registry.ops[SlotId::new(0)].is_none());
+
+    for _ in 0..=PendingOps::MAX_EXECUTED_OPS {
+        registry.record_executed(Hash::new_unique());
+    }
+    registry.record_executed(op.params_hash);
+    assert!(registry.was_executed(&op.params_hash));
+    assert_eq!(registry.executed_ops.len(), PendingOps::MAX_EXECUTED_OPS);
+
+    let mut packed = vec![0; PendingOps::LEN];
+    registry.pack_into_slice(&mut packed);
+    assert_eq!(PendingOps::unpack_from_slice(&packed).unwrap(), registry);
+}
@@ -0,0 +1,660 @@
+use crate::error::WalletError;
+use crate::model::balance_account::BalanceAccount;
+use crate::model::wallet::Wallet;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+use std::time::Duration;
+
+const SECONDS_PER_DAY: u32 = 60 * 60 * 24;
+const SECONDS_PER_HOUR: u32 = 60 * 60;
+const HOURS_PER_DAY: u8 = 24;
+/// The Unix epoch fell on a Thursday, day 3 of a week starting on Monday.
+const EPOCH_DAY_OF_WEEK: i64 = 3;
+
+/// The kinds of destination an op can move funds to, as used by
+/// `PolicyRule::DestinationClass`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum DestinationClass {
+    Address = 0,
+    BalanceAccount = 1,
+    DApp = 2,
+}
+
+impl DestinationClass {
+    pub fn from_u8(value: u8) -> Result<Self, ProgramError> {
+        match value {
+            0 => Ok(DestinationClass::Address),
+            1 => Ok(DestinationClass::BalanceAccount),
+            2 => Ok(DestinationClass::DApp),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+}
+
+/// A rule of a balance account's policy. Rules only ever tighten the policy:
+/// the approvals an op needs are the most any matching rule asks for, an op
+/// has to be started within one of the time windows, if there are any, and a
+/// transfer has to be finalized within one of the settlement windows, if there
+/// are any.
+///
+/// Tiered approvals are set up with the balance account's own approvals
+/// required for the smallest transfers and an `AmountTier` for each larger
+/// tier.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum PolicyRule {
+    /// Ops moving at least `min_amount` need `approvals_required` approvals.
+    AmountTier {
+        min_amount: u64,
+        approvals_required: u8,
+    },
+    /// Ops can be started from `start_secs` up to `end_secs` into the UTC day;
+    /// a window with `end_secs` before `start_secs` spans midnight.
+    TimeWindow { start_secs: u32, end_secs: u32 },
+    /// Ops to destinations of the class need `approvals_required` approvals.
+    DestinationClass {
+        class: DestinationClass,
+        approvals_required: u8,
+    },
+    /// Transfers can be finalized on the days of `days_mask` (bit 0 for Monday
+    /// through bit 6 for Sunday) from `start_hour` up to `end_hour` UTC, no
+    /// matter when they were approved. An `end_hour` before `start_hour`
+    /// spans midnight, into the next day.
+    SettlementWindow {
+        days_mask: u8,
+        start_hour: u8,
+        end_hour: u8,
+    },
+}
+
+impl PolicyRule {
+    fn validate(&self, approvers_count: usize) -> ProgramResult {
+        let valid = match *self {
+            PolicyRule::AmountTier {
+                approvals_required, ..
+            }
+            | PolicyRule::DestinationClass {
+                approvals_required, ..
+            } => approvals_required > 0 && usize::from(approvals_required) <= approvers_count,
+            PolicyRule::TimeWindow {
+                start_secs,
+                end_secs,
+            } => {
+                start_secs < SECONDS_PER_DAY && end_secs < SECONDS_PER_DAY && start_secs != end_secs
+            }
+            PolicyRule::SettlementWindow {
+                days_mask,
+                start_hour,
+                end_hour,
+            } => {
+                days_mask != 0
+                    && days_mask < 1 << 7
+                    && start_hour < HOURS_PER_DAY
+                    && end_hour <= HOURS_PER_DAY
+                    && start_hour != end_hour
+            }
+        };
+        if !valid {
+            msg!("Invalid policy rule {:?}", self);
+            return Err(WalletError::InvalidPolicyRule.into());
+        }
+        Ok(())
+    }
+
+    fn approvals_required(&self, request: &PolicyRequest) -> u8 {
+        match *self {
+            PolicyRule::AmountTier {
+                min_amount,
+                approvals_required,
+            } if request.amount >= min_amount => approvals_required,
+            PolicyRule::DestinationClass {
+                class,
+                approvals_required,
+            } if class == request.destination.class() => approvals_required,
+            _ => 0,
+        }
+    }
+
+    fn time_window_open(start_secs: u32, end_secs: u32, now: i64) -> bool {
+        let secs = now.rem_euclid(i64::from(SECONDS_PER_DAY)) as u32;
+        if start_secs < end_secs {
+            start_secs <= secs && secs < end_secs
+        } else {
+            secs >= start_secs || secs < end_secs
+        }
+    }
+
+    fn settlement_window_open(days_mask: u8, start_hour: u8, end_hour: u8, now: i64) -> bool {
+        let day_open = |day: i64| days_mask >> (day + EPOCH_DAY_OF_WEEK).rem_euclid(7) & 1 == 1;
+        let day = now.div_euclid(i64::from(SECONDS_PER_DAY));
+        let hour = (now.rem_euclid(i64::from(SECONDS_PER_DAY)) as u32 / SECONDS_PER_HOUR) as u8;
+        if start_hour < end_hour {
+            day_open(day) && start_hour <= hour && hour < end_hour
+        } else {
+            // the hours after midnight belong to the window opened the day before
+            (day_open(day) && hour >= start_hour) || (day_open(day - 1) && hour < end_hour)
+        }
+    }
+}
+
+impl Sealed for PolicyRule {}
+
+impl Pack for PolicyRule {
+    const LEN: usize = 1 + 9;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, PolicyRule::LEN];
+        let (type_dst, data_dst) = mut_array_refs![dst, 1, 9];
+        data_dst.fill(0);
+        match *self {
+            PolicyRule::AmountTier {
+                min_amount,
+                approvals_required,
+            } => {
+                type_dst[0] = 1;
+                data_dst[..8].copy_from_slice(&min_amount.to_le_bytes());
+                data_dst[8] = approvals_required;
+            }
+            PolicyRule::TimeWindow {
+                start_secs,
+                end_secs,
+            } => {
+                type_dst[0] = 2;
+                data_dst[..4].copy_from_slice(&start_secs.to_le_bytes());
+                data_dst[4..8].copy_from_slice(&end_secs.to_le_bytes());
+            }
+            PolicyRule::DestinationClass {
+                class,
+                approvals_required,
+            } => {
+                type_dst[0] = 3;
+                data_dst[0] = class.to_u8();
+                data_dst[1] = approvals_required;
+            }
+            PolicyRule::SettlementWindow {
+                days_mask,
+                start_hour,
+                end_hour,
+            } => {
+                type_dst[0] = 4;
+                data_dst[0] = days_mask;
+                data_dst[1] = start_hour;
+                data_dst[2] = end_hour;
+            }
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, PolicyRule::LEN];
+        let (type_src, data_src) = array_refs![src, 1, 9];
+        match type_src[0] {
+            1 => Ok(PolicyRule::AmountTier {
+                min_amount: u64::from_le_bytes(*array_ref![data_src, 0, 8]),
+                approvals_required: data_src[8],
+            }),
+            2 => Ok(PolicyRule::TimeWindow {
+                start_secs: u32::from_le_bytes(*array_ref![data_src, 0, 4]),
+                end_secs: u32::from_le_bytes(*array_ref![data_src, 4, 4]),
+            }),
+            3 => Ok(PolicyRule::DestinationClass {
+                class: DestinationClass::from_u8(data_src[0])?,
+                approvals_required: data_src[1],
+            }),
+            4 => Ok(PolicyRule::SettlementWindow {
+                days_mask: data_src[0],
+                start_hour: data_src[1],
+                end_hour: data_src[2],
+            }),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+/// The rules stored with a balance account.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub struct PolicyRules {
+    rules: [Option<PolicyRule>; PolicyRules::MAX_RULES],
+}
+
+impl PolicyRules {
+    pub const MAX_RULES: usize = 8;
+    pub const STORAGE_SIZE: usize = PolicyRules::MAX_RULES * (1 + PolicyRule::LEN);
+
+    pub fn new() -> Self {
+        PolicyRules {
+            rules: [None; PolicyRules::MAX_RULES],
+        }
+    }
+
+    pub fn from_vec(rules: &Vec<PolicyRule>) -> Result<Self, ProgramError> {
+        if rules.len() > PolicyRules::MAX_RULES {
+            msg!(
+                "A balance account can have at most {} policy rules",
+                PolicyRules::MAX_RULES
+            );
+            return Err(WalletError::InvalidPolicyRule.into());
+        }
+        let mut policy_rules = PolicyRules::new();
+        for (slot, rule) in policy_rules.rules.iter_mut().zip(rules.iter()) {
+            *slot = Some(*rule);
+        }
+        Ok(policy_rules)
+    }
+
+    pub fn validate(&self, approvers_count: usize) -> ProgramResult {
+        self.iter()
+            .try_for_each(|rule| rule.validate(approvers_count))?;
+
+        // a larger tier can't need fewer approvals than a smaller one
+        let mut amount_tiers = self
+            .iter()
+            .filter_map(|rule| match *rule {
+                PolicyRule::AmountTier {
+                    min_amount,
+                    approvals_required,
+                } => Some((min_amount, approvals_required)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        amount_tiers.sort();
+        if amount_tiers
+            .windows(2)
+            .any(|tiers| tiers[0].0 == tiers[1].0 || tiers[0].1 > tiers[1].1)
+        {
+            msg!("Amount tiers must have distinct amounts and increasing approvals");
+            return Err(WalletError::InvalidPolicyRule.into());
+        }
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PolicyRule> {
+        self.rules.iter().flatten()
+    }
+
+    /// Fails unless a transfer can be finalized now, i.e. one of the
+    /// settlement windows is open, if there are any.
+    pub fn validate_settlement(&self, now: i64) -> ProgramResult {
+        let settlement_windows = self
+            .iter()
+            .filter_map(|rule| match *rule {
+                PolicyRule::SettlementWindow {
+                    days_mask,
+                    start_hour,
+                    end_hour,
+                } => Some((days_mask, start_hour, end_hour)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if !settlement_windows.is_empty()
+            && !settlement_windows
+                .iter()
+                .any(|(days_mask, start_hour, end_hour)| {
+                    PolicyRule::settlement_window_open(*days_mask, *start_hour, *end_hour, now)
+                })
+        {
+            msg!("Transfers can't be finalized outside of the policy's settlement windows");
+            return Err(WalletError::PolicyTimeWindowClosed.into());
+        }
+        Ok(())
+    }
+
+    pub fn to_vec(&self) -> Vec<PolicyRule> {
+        self.iter().cloned().collect()
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst.fill(0);
+        for (rule, chunk) in self
+            .rules
+            .iter()
+            .zip(dst.chunks_exact_mut(1 + PolicyRule::LEN))
+        {
+            if let Some(rule) = rule {
+                chunk[0] = 1;
+                rule.pack_into_slice(&mut chunk[1..]);
+            }
+        }
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let mut policy_rules = PolicyRules::new();
+        for (rule, chunk) in policy_rules
+            .rules
+            .iter_mut()
+            .zip(src.chunks_exact(1 + PolicyRule::LEN))
+        {
+            if chunk[0] != 0 {
+                *rule = Some(PolicyRule::unpack_from_slice(&chunk[1..])?);
+            }
+        }
+        Ok(policy_rules)
+    }
+}
+
+/// Where an op sends funds, as resolved by the handler starting it.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Destination {
+    /// An address, `whitelisted` if the balance account's whitelist lets it
+    /// through (which it always does while whitelisting is disabled).
+    Address { whitelisted: bool },
+    /// Another balance account of the wallet, or the balance account itself.
+    BalanceAccount,
+    /// A dapp, `listed` if it is in the wallet's dapp book.
+    DApp { listed: bool },
+}
+
+impl Destination {
+    pub fn class(&self) -> DestinationClass {
+        match self {
+            Destination::Address { .. } => DestinationClass::Address,
+            Destination::BalanceAccount => DestinationClass::BalanceAccount,
+            Destination::DApp { .. } => DestinationClass::DApp,
+        }
+    }
+}
+
+/// An op a balance account's policy is evaluated for.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct PolicyRequest {
+    pub amount: u64,
+    pub destination: Destination,
+    pub now: i64,
+}
+
+/// Who has to approve an op and how long they have to do it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PolicyDecision {
+    pub approvers: Vec<Pubkey>,
+    pub approvals_required: u8,
+    pub approval_timeout: Duration,
+}
+
+impl Wallet {
+    /// Evaluates the balance account's policy for an op moving funds out of
+    /// it. This is the one place transfer, wrap and dapp ops are checked
+    /// against the whitelist and dapp settings and given their quorum.
+    pub fn evaluate_policy(
+        &self,
+        balance_account: &BalanceAccount,
+        request: &PolicyRequest,
+    ) -> Result<PolicyDecision, ProgramError> {
+        match request.destination {
+            Destination::Address { whitelisted: false } => {
+                msg!("Destination account is not whitelisted");
+                return Err(WalletError::DestinationNotAllowed.into());
+            }
+            Destination::DApp { .. } if balance_account.are_dapps_disabled() => {
+                return Err(WalletError::DAppsDisabled.into());
+            }
+            Destination::DApp { listed: false } if !balance_account.is_whitelist_disabled() => {
+                return Err(WalletError::DAppNotAllowed.into());
+            }
+            _ => {}
+        }
+
+        let time_windows = balance_account
+            .policy_rules
+            .iter()
+            .filter_map(|rule| match *rule {
+                PolicyRule::TimeWindow {
+                    start_secs,
+                    end_secs,
+                } => Some((start_secs, end_secs)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if !time_windows.is_empty()
+            && !time_windows.iter().any(|(start_secs, end_secs)| {
+                PolicyRule::time_window_open(*start_secs, *end_secs, request.now)
+            })
+        {
+            msg!("Ops can't be started outside of the policy's time windows");
+            return Err(WalletError::PolicyTimeWindowClosed.into());
+        }
+
+        // dapp transactions have always needed a single approval unless a rule says otherwise
+        let base_approvals_required = match request.destination {
+            Destination::DApp { .. } => 1,
+            _ => balance_account.approvals_required_for_transfer,
+        };
+        let approvers = self.get_transfer_approvers_keys(balance_account);
+        // a rule can't ask for more approvals than there are approvers left
+        let rule_approvals_required = balance_account
+            .policy_rules
+            .iter()
+            .map(|rule| rule.approvals_required(request))
+            .max()
+            .unwrap_or(0)
+            .min(approvers.len() as u8);
+        let approvals_required = base_approvals_required.max(rule_approvals_required);
+
+        Ok(PolicyDecision {
+            approvers,
+            approvals_required,
+            approval_timeout: balance_account.approval_timeout_for_transfer,
+        })
+    }
+}
+
+#[test]
+fn test_policy_rules_pack_round_trip() {
+    let rules = PolicyRules::from_vec(&vec![
+        PolicyRule::AmountTier {
+            min_amount: 1_000_000,
+            approvals_required: 3,
+        },
+        PolicyRule::TimeWindow {
+            start_secs: 22 * 3600,
+            end_secs: 6 * 3600,
+        },
+        PolicyRule::DestinationClass {
+            class: DestinationClass::DApp,
+            approvals_required: 2,
+        },
+        PolicyRule::SettlementWindow {
+            days_mask: 0b0011111,
+            start_hour: 9,
+            end_hour: 17,
+        },
+    ])
+    .unwrap();
+    let mut data = [0; PolicyRules::STORAGE_SIZE];
+    rules.pack_into_slice(&mut data);
+    assert_eq!(PolicyRules::unpack_from_slice(&data).unwrap(), rules);
+    assert_eq!(rules.to_vec().len(), 4);
+}
+
+#[test]
+fn test_amount_tiers_must_increase() {
+    let tiers = |tiers: Vec<(u64, u8)>| {
+        PolicyRules::from_vec(
+            &tiers
+                .into_iter()
+                .map(|(min_amount, approvals_required)| PolicyRule::AmountTier {
+                    min_amount,
+                    approvals_required,
+                })
+                .collect(),
+        )
+        .unwrap()
+        .validate(3)
+    };
+    assert!(tiers(vec![(1000, 3), (100, 2)]).is_ok());
+    assert!(tiers(vec![(100, 2), (1000, 2)]).is_ok());
+    assert_eq!(
+        tiers(vec![(100, 3), (1000, 2)]).unwrap_err(),
+        WalletError::InvalidPolicyRule.into()
+    );
+    assert_eq!(
+        tiers(vec![(100, 2), (100, 3)]).unwrap_err(),
+        WalletError::InvalidPolicyRule.into()
+    );
+}
+
+#[test]
+fn test_time_window_spanning_midnight() {
+    let (start_secs, end_secs) = (22 * 3600, 6 * 3600);
+    let day = i64::from(SECONDS_PER_DAY);
+    assert!(PolicyRule::time_window_open(
+        start_secs,
+        end_secs,
+        10 * day + 23 * 3600
+    ));
+    assert!(PolicyRule::time_window_open(
+        start_secs,
+        end_secs,
+        10 * day + 3600
+    ));
+    assert!(!PolicyRule::time_window_open(
+        start_secs,
+        end_secs,
+        10 * day + 12 * 3600
+    ));
+    assert!(PolicyRule::time_window_open(
+        9 * 3600,
+        17 * 3600,
+        10 * day + 12 * 3600
+    ));
+    assert!(!PolicyRule::time_window_open(
+        9 * 3600,
+        17 * 3600,
+        10 * day + 17 * 3600
+    ));
+}
+
+#[test]
+fn test_settlement_windows() {
+    let day = i64::from(SECONDS_PER_DAY);
+    let hour = i64::from(SECONDS_PER_HOUR);
+    // 1970-01-05 was a Monday
+    let monday = 4 * day;
+    let weekdays = 0b0011111;
+    let business_hours = PolicyRules::from_vec(&vec![PolicyRule::SettlementWindow {
+        days_mask: weekdays,
+        start_hour: 9,
+        end_hour: 17,
+    }])
+    .unwrap();
+    assert!(business_hours.validate(1).is_ok());
+    assert!(business_hours
+        .validate_settlement(monday + 9 * hour)
+        .is_ok());
+    assert!(business_hours
+        .validate_settlement(monday + 4 * day + 16 * hour)
+        .is_ok());
+    assert_eq!(
+        business_hours.validate_settlement(monday + 17 * hour),
+        Err(WalletError::PolicyTimeWindowClosed.into())
+    );
+    // saturday
+    assert_eq!(
+        business_hours.validate_settlement(monday + 5 * day + 12 * hour),
+        Err(WalletError::PolicyTimeWindowClosed.into())
+    );
+
+    // a friday night window runs into saturday morning
+    assert!(PolicyRule::settlement_window_open(
+        1 << 4,
+        22,
+        6,
+        monday + 5 * day + 3 * hour
+    ));
+    assert!(!PolicyRule::settlement_window_open(
+        1 << 4,
+        22,
+        6,
+        monday + 4 * day + 3 * hour
+    ));
+
+    // no settlement windows, no restriction
+    assert!(PolicyRules::new().validate_settlement(monday).is_ok());
+
+    for &(days_mask, start_hour, end_hour) in [
+        (0, 9, 17),
+        (1 << 7, 9, 17),
+        (1, 24, 17),
+        (1, 9, 25),
+        (1, 9, 9),
+    ]
+    .iter()
+    {
+        assert!(PolicyRule::SettlementWindow {
+            days_mask,
+            start_hour,
+            end_hour,
+        }
+        .validate(1)
+        .is_err());
+    }
+}
+
+#[test]
+fn test_evaluate_policy_takes_the_strictest_matching_rule() {
+    use crate::model::signer::Signer;
+    use crate::utils::SlotId;
+
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    let mut balance_account = BalanceAccount::unpack_from_slice(&[0; BalanceAccount::LEN]).unwrap();
+    for i in 0..3 {
+        wallet
+            .signers
+            .insert(SlotId::new(i), Signer::new(Pubkey::new_unique()));
+        balance_account.transfer_approvers.enable(&SlotId::new(i));
+    }
+    balance_account.approvals_required_for_transfer = 1;
+    balance_account.policy_rules = PolicyRules::from_vec(&vec![
+        PolicyRule::AmountTier {
+            min_amount: 100,
+            approvals_required: 2,
+        },
+        PolicyRule::DestinationClass {
+            class: DestinationClass::Address,
+            approvals_required: 3,
+        },
+    ])
+    .unwrap();
+    let request = |amount, destination| PolicyRequest {
+        amount,
+        destination,
+        now: 0,
+    };
+
+    let approvals_required = |request| {
+        wallet
+            .evaluate_policy(&balance_account, &request)
+            .unwrap()
+            .approvals_required
+    };
+    assert_eq!(
+        approvals_required(request(99, Destination::BalanceAccount)),
+        1
+    );
+    assert_eq!(
+        approvals_required(request(100, Destination::BalanceAccount)),
+        2
+    );
+    assert_eq!(
+        approvals_required(request(1, Destination::Address { whitelisted: true })),
+        3
+    );
+    assert_eq!(
+        wallet
+            .evaluate_policy(
+                &balance_account,
+                &request(1, Destination::Address { whitelisted: false })
+            )
+            .unwrap_err(),
+        WalletError::DestinationNotAllowed.into()
+    );
+}
@@ -0,0 +1,354 @@
+use crate::error::WalletError;
+use crate::model::multisig_op::BooleanSetting;
+use crate::model::wallet::Wallet;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+use std::time::Duration;
+
+/// Policy limits enforced by the handlers. These are the compiled-in limits
+/// until the program config is created, and the program config's after.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PolicyLimits {
+    pub min_approval_timeout: Duration,
+    pub max_approval_timeout: Duration,
+    pub max_signers: usize,
+    pub max_balance_accounts: usize,
+}
+
+impl Default for PolicyLimits {
+    fn default() -> Self {
+        PolicyLimits {
+            min_approval_timeout: Wallet::MIN_APPROVAL_TIMEOUT,
+            max_approval_timeout: Wallet::MAX_APPROVAL_TIMEOUT,
+            max_signers: Wallet::MAX_SIGNERS,
+            max_balance_accounts: Wallet::MAX_BALANCE_ACCOUNTS,
+        }
+    }
+}
+
+impl PolicyLimits {
+    pub fn validate_approval_timeout(&self, timeout: &Duration) -> ProgramResult {
+        // approval timeout seconds must fall within program-defined range.
+        if *timeout < self.min_approval_timeout {
+            msg!(
+                "Approval timeout can't be less than {} seconds",
+                self.min_approval_timeout.as_secs(),
+            );
+            return Err(WalletError::InvalidApprovalTimeout.into());
+        }
+
+        if *timeout > self.max_approval_timeout {
+            msg!(
+                "Approval timeout can't be more than {} seconds",
+                self.max_approval_timeout.as_secs(),
+            );
+            return Err(WalletError::InvalidApprovalTimeout.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Program-wide settings, stored in a singleton PDA that is created by the
+/// program's upgrade authority.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ProgramConfig {
+    pub is_initialized: bool,
+    pub bump_seed: u8,
+    pub authority: Pubkey,
+    pub min_approval_timeout: Duration,
+    pub max_approval_timeout: Duration,
+    pub fee_collector: Pubkey,
+    pub paused: BooleanSetting,
+    /// Can only lower the compiled-in limit, which is the wallet's storage capacity.
+    pub max_signers: u8,
+    /// Can only lower the compiled-in limit, which is the wallet's storage capacity.
+    pub max_balance_accounts: u8,
+    /// Programs wallets can be exported to and imported from, such as a
+    /// redeployment of this program.
+    pub migration_programs: Vec<Pubkey>,
+}
+
+impl ProgramConfig {
+    pub const SEED: &'static [u8] = b"program-config";
+    pub const MAX_MIGRATION_PROGRAMS: usize = 4;
+
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ProgramConfig::SEED], program_id)
+    }
+
+    /// Derives the address from a known bump seed, which is cheaper than
+    /// searching for it with `find_address`.
+    pub fn address(program_id: &Pubkey, bump_seed: u8) -> Option<Pubkey> {
+        Pubkey::create_program_address(&[ProgramConfig::SEED, &[bump_seed]], program_id).ok()
+    }
+
+    pub fn new(authority: &Pubkey, bump_seed: u8) -> ProgramConfig {
+        ProgramConfig {
+            is_initialized: true,
+            bump_seed,
+            authority: *authority,
+            min_approval_timeout: Wallet::MIN_APPROVAL_TIMEOUT,
+            max_approval_timeout: Wallet::MAX_APPROVAL_TIMEOUT,
+            fee_collector: *authority,
+            paused: BooleanSetting::Off,
+            max_signers: Wallet::MAX_SIGNERS as u8,
+            max_balance_accounts: Wallet::MAX_BALANCE_ACCOUNTS as u8,
+            migration_programs: Vec::new(),
+        }
+    }
+
+    /// Returns the program config, or None if it hasn't been created yet. The
+    /// program config PDA has to be among the given accounts either way, so
+    /// that callers can't skip the pause switch and the limits by leaving it out.
+    /// Once created, the config is recognized by the bump seed it stores, so
+    /// that its address doesn't have to be searched for.
+    pub fn from_accounts(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> Result<Option<ProgramConfig>, ProgramError> {
+        // only the program can create the account at its PDA
+        for account_info in accounts.iter().filter(|account_info| {
+            account_info.owner == program_id && account_info.data_len() == ProgramConfig::LEN
+        }) {
+            let program_config = match ProgramConfig::unpack_from_slice(&account_info.data.borrow())
+            {
+                Ok(program_config) if program_config.is_initialized => program_config,
+                _ => continue,
+            };
+            if ProgramConfig::address(program_id, program_config.bump_seed).as_ref()
+                == Some(account_info.key)
+            {
+                return Ok(Some(program_config));
+            }
+        }
+
+        let (address, _) = ProgramConfig::find_address(program_id);
+        if !accounts
+            .iter()
+            .any(|account_info| *account_info.key == address)
+        {
+            msg!("The program config account {} is required", address);
+            return Err(WalletError::ProgramConfigAccountRequired.into());
+        }
+        Ok(None)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused == BooleanSetting::On
+    }
+
+    pub fn validate_migration_program(&self, migration_program: &Pubkey) -> ProgramResult {
+        if !self.migration_programs.contains(migration_program) {
+            msg!("Program {} is not a migration program", migration_program);
+            return Err(WalletError::MigrationProgramNotAllowed.into());
+        }
+        Ok(())
+    }
+
+    pub fn limits(&self) -> PolicyLimits {
+        PolicyLimits {
+            min_approval_timeout: self.min_approval_timeout,
+            max_approval_timeout: self.max_approval_timeout,
+            max_signers: usize::from(self.max_signers).min(Wallet::MAX_SIGNERS),
+            max_balance_accounts: usize::from(self.max_balance_accounts)
+                .min(Wallet::MAX_BALANCE_ACCOUNTS),
+        }
+    }
+}
+
+impl Sealed for ProgramConfig {}
+
+impl IsInitialized for ProgramConfig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for ProgramConfig {
+    const LEN: usize = 1 + // is_initialized
+        1 + // bump_seed
+        PUBKEY_BYTES + // authority
+        8 + // min_approval_timeout
+        8 + // max_approval_timeout
+        PUBKEY_BYTES + // fee_collector
+        1 + // paused
+        1 + // max_signers
+        1 + // max_balance_accounts
+        1 + PUBKEY_BYTES * ProgramConfig::MAX_MIGRATION_PROGRAMS; // migration_programs
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, ProgramConfig::LEN];
+        let (
+            is_initialized_dst,
+            bump_seed_dst,
+            authority_dst,
+            min_approval_timeout_dst,
+            max_approval_timeout_dst,
+            fee_collector_dst,
+            paused_dst,
+            max_signers_dst,
+            max_balance_accounts_dst,
+            migration_programs_count_dst,
+            migration_programs_dst,
+        ) = mut_array_refs![
+            dst,
+            1,
+            1,
+            PUBKEY_BYTES,
+            8,
+            8,
+            PUBKEY_BYTES,
+            1,
+            1,
+            1,
+            1,
+            PUBKEY_BYTES * ProgramConfig::MAX_MIGRATION_PROGRAMS
+        ];
+
+        is_initialized_dst[0] = self.is_initialized as u8;
+        bump_seed_dst[0] = self.bump_seed;
+        authority_dst.copy_from_slice(self.authority.as_ref());
+        *min_approval_timeout_dst = self.min_approval_timeout.as_secs().to_le_bytes();
+        *max_approval_timeout_dst = self.max_approval_timeout.as_secs().to_le_bytes();
+        fee_collector_dst.copy_from_slice(self.fee_collector.as_ref());
+        paused_dst[0] = self.paused.to_u8();
+        max_signers_dst[0] = self.max_signers;
+        max_balance_accounts_dst[0] = self.max_balance_accounts;
+        migration_programs_count_dst[0] = self.migration_programs.len() as u8;
+        migration_programs_dst.fill(0);
+        migration_programs_dst
+            .chunks_exact_mut(PUBKEY_BYTES)
+            .zip(self.migration_programs.iter())
+            .for_each(|(chunk, migration_program)| {
+                chunk.copy_from_slice(migration_program.as_ref())
+            });
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, ProgramConfig::LEN];
+        let (
+            is_initialized_src,
+            bump_seed_src,
+            authority_src,
+            min_approval_timeout_src,
+            max_approval_timeout_src,
+            fee_collector_src,
+            paused_src,
+            max_signers_src,
+            max_balance_accounts_src,
+            migration_programs_count_src,
+            migration_programs_src,
+        ) = array_refs![
+            src,
+            1,
+            1,
+            PUBKEY_BYTES,
+            8,
+            8,
+            PUBKEY_BYTES,
+            1,
+            1,
+            1,
+            1,
+            PUBKEY_BYTES * ProgramConfig::MAX_MIGRATION_PROGRAMS
+        ];
+
+        Ok(ProgramConfig {
+            is_initialized: match is_initialized_src {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump_seed: bump_seed_src[0],
+            authority: Pubkey::new(authority_src),
+            min_approval_timeout: Duration::from_secs(u64::from_le_bytes(
+                *min_approval_timeout_src,
+            )),
+            max_approval_timeout: Duration::from_secs(u64::from_le_bytes(
+                *max_approval_timeout_src,
+            )),
+            fee_collector: Pubkey::new(fee_collector_src),
+            paused: BooleanSetting::from_u8(paused_src[0]),
+            max_signers: max_signers_src[0],
+            max_balance_accounts: max_balance_accounts_src[0],
+            migration_programs: migration_programs_src
+                .chunks_exact(PUBKEY_BYTES)
+                .take(usize::from(migration_programs_count_src[0]))
+                .map(Pubkey::new)
+                .collect(),
+        })
+    }
+}
+
+#[test]
+fn test_program_config_from_accounts() {
+    let program_id = Pubkey::new_unique();
+    let system_program_id = Pubkey::default();
+    let (address, bump_seed) = ProgramConfig::find_address(&program_id);
+    assert_eq!(
+        ProgramConfig::address(&program_id, bump_seed),
+        Some(address)
+    );
+    let program_config = ProgramConfig::new(&Pubkey::new_unique(), bump_seed);
+
+    // a config stored anywhere but at the PDA isn't the program config
+    let other_key = Pubkey::new_unique();
+    let mut other_lamports = 1;
+    let mut other_data = vec![0; ProgramConfig::LEN];
+    ProgramConfig::pack(program_config.clone(), &mut other_data).unwrap();
+    let other = AccountInfo::new(
+        &other_key,
+        false,
+        false,
+        &mut other_lamports,
+        &mut other_data,
+        &program_id,
+        false,
+        0,
+    );
+    assert_eq!(
+        ProgramConfig::from_accounts(&program_id, &[other.clone()]),
+        Err(WalletError::ProgramConfigAccountRequired.into())
+    );
+
+    let mut uncreated_lamports = 0;
+    let mut uncreated_data = vec![];
+    let uncreated = AccountInfo::new(
+        &address,
+        false,
+        false,
+        &mut uncreated_lamports,
+        &mut uncreated_data,
+        &system_program_id,
+        false,
+        0,
+    );
+    assert_eq!(
+        ProgramConfig::from_accounts(&program_id, &[other.clone(), uncreated]),
+        Ok(None)
+    );
+
+    let mut created_lamports = 1;
+    let mut created_data = vec![0; ProgramConfig::LEN];
+    ProgramConfig::pack(program_config.clone(), &mut created_data).unwrap();
+    let created = AccountInfo::new(
+        &address,
+        false,
+        false,
+        &mut created_lamports,
+        &mut created_data,
+        &program_id,
+        false,
+        0,
+    );
+    assert_eq!(
+        ProgramConfig::from_accounts(&program_id, &[other, created]),
+        Ok(Some(program_config))
+    );
+}
@@ -1,7 +1,10 @@
+use crate::model::wallet::Wallet;
+use crate::utils::SlotId;
 use arrayref::array_ref;
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{Pack, Sealed};
 use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+use std::time::Duration;
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
 pub struct Signer {
@@ -30,3 +33,102 @@ impl Pack for Signer {
         })
     }
 }
+
+/// The organization each of the wallet's signer slots is tagged with, if any,
+/// so that approvals can be required to come from several organizations.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub struct SignerOrganizations {
+    organizations: [Option<u8>; Wallet::MAX_SIGNERS],
+}
+
+impl SignerOrganizations {
+    pub fn get(&self, slot_id: SlotId<Signer>) -> Option<u8> {
+        self.organizations[slot_id.value]
+    }
+
+    /// Tags a slot with an organization, or clears its tag if `organization` is `None`.
+    pub fn set(&mut self, slot_id: SlotId<Signer>, organization: Option<u8>) {
+        self.organizations[slot_id.value] = organization;
+    }
+}
+
+impl Sealed for SignerOrganizations {}
+
+impl Pack for SignerOrganizations {
+    const LEN: usize = Wallet::MAX_SIGNERS * 2;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        for (chunk, organization) in dst.chunks_exact_mut(2).zip(self.organizations.iter()) {
+            chunk[0] = organization.is_some() as u8;
+            chunk[1] = organization.unwrap_or_default();
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let mut organizations = SignerOrganizations::default();
+        for (organization, chunk) in organizations
+            .organizations
+            .iter_mut()
+            .zip(src[..SignerOrganizations::LEN].chunks_exact(2))
+        {
+            *organization = match chunk[0] {
+                0 => None,
+                1 => Some(chunk[1]),
+                _ => return Err(ProgramError::InvalidAccountData),
+            };
+        }
+        Ok(organizations)
+    }
+}
+
+/// When each of the wallet's signer slots was last seen giving a disposition.
+/// Slots nothing has been recorded for yet, such as those of wallets created
+/// before activity was tracked, are never considered inactive.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub struct SignerActivity {
+    last_active_at: [i64; Wallet::MAX_SIGNERS],
+}
+
+impl SignerActivity {
+    pub fn get(&self, slot_id: SlotId<Signer>) -> Option<i64> {
+        Some(self.last_active_at[slot_id.value]).filter(|at| *at != 0)
+    }
+
+    pub fn record(&mut self, slot_id: SlotId<Signer>, at: i64) {
+        self.last_active_at[slot_id.value] = at;
+    }
+
+    pub fn clear(&mut self, slot_id: SlotId<Signer>) {
+        self.last_active_at[slot_id.value] = 0;
+    }
+
+    /// Whether the slot was last active more than `period` before `now`.
+    pub fn is_inactive(&self, slot_id: SlotId<Signer>, now: i64, period: Duration) -> bool {
+        self.get(slot_id)
+            .map_or(false, |at| now.saturating_sub(at) > period.as_secs() as i64)
+    }
+}
+
+impl Sealed for SignerActivity {}
+
+impl Pack for SignerActivity {
+    const LEN: usize = Wallet::MAX_SIGNERS * 8;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        for (chunk, at) in dst.chunks_exact_mut(8).zip(self.last_active_at.iter()) {
+            chunk.copy_from_slice(&at.to_le_bytes());
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let mut activity = SignerActivity::default();
+        for (at, chunk) in activity
+            .last_active_at
+            .iter_mut()
+            .zip(src[..SignerActivity::LEN].chunks_exact(8))
+        {
+            *at = i64::from_le_bytes(*array_ref![chunk, 0, 8]);
+        }
+        Ok(activity)
+    }
+}
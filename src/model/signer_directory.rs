@@ -0,0 +1,126 @@
+use crate::error::WalletError;
+use crate::instruction::SignerDirectoryUpdate;
+use crate::model::account_type::AccountType;
+use crate::model::wallet::Signers;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+/// Signer keys shared by the wallets of an organization, stored in a PDA
+/// derived from the directory authority. A wallet signer slot that is marked
+/// as directory-backed resolves to the key in the same slot of the directory,
+/// so rotating a key here rotates it in every wallet that references it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SignerDirectory {
+    pub is_initialized: bool,
+    pub bump_seed: u8,
+    pub authority: Pubkey,
+    pub signers: Signers,
+}
+
+impl SignerDirectory {
+    pub const SEED: &'static [u8] = b"signer-directory";
+
+    pub fn find_address(authority: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[authority.as_ref(), SignerDirectory::SEED], program_id)
+    }
+
+    pub fn new(authority: &Pubkey, bump_seed: u8) -> SignerDirectory {
+        SignerDirectory {
+            is_initialized: true,
+            bump_seed,
+            authority: *authority,
+            signers: Signers::new(),
+        }
+    }
+
+    /// Returns the address and contents of the first initialized signer
+    /// directory among the given accounts. The address is checked against the
+    /// bump seed stored in the account.
+    pub fn from_accounts(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> Result<Option<(Pubkey, SignerDirectory)>, ProgramError> {
+        for account_info in accounts {
+            if account_info.owner != program_id || account_info.data_len() != SignerDirectory::LEN {
+                continue;
+            }
+            let directory = SignerDirectory::unpack_from_slice(&account_info.data.borrow())?;
+            if !directory.is_initialized {
+                continue;
+            }
+            let expected_address = Pubkey::create_program_address(
+                &[
+                    directory.authority.as_ref(),
+                    SignerDirectory::SEED,
+                    &[directory.bump_seed],
+                ],
+                program_id,
+            )?;
+            if expected_address == *account_info.key {
+                return Ok(Some((expected_address, directory)));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn update(&mut self, update: &SignerDirectoryUpdate) -> ProgramResult {
+        if !self.signers.can_be_removed(&update.remove_signers) {
+            msg!("Failed to remove signers: at least one of the provided signers is not present in the directory");
+            return Err(WalletError::SlotCannotBeRemoved.into());
+        }
+        self.signers.remove_many(&update.remove_signers);
+
+        if !self.signers.can_be_inserted(&update.add_signers) {
+            msg!("Failed to add signers: at least one of the provided slots is already taken");
+            return Err(WalletError::SlotCannotBeInserted.into());
+        }
+        self.signers.insert_many(&update.add_signers);
+
+        Ok(())
+    }
+}
+
+impl Sealed for SignerDirectory {}
+
+impl IsInitialized for SignerDirectory {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for SignerDirectory {
+    const LEN: usize = 1 + // is_initialized
+        1 + // bump_seed
+        PUBKEY_BYTES + // authority
+        Signers::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, SignerDirectory::LEN];
+        let (is_initialized_dst, bump_seed_dst, authority_dst, signers_dst) =
+            mut_array_refs![dst, 1, 1, PUBKEY_BYTES, Signers::LEN];
+
+        is_initialized_dst[0] = AccountType::SignerDirectory.to_discriminator(self.is_initialized);
+        bump_seed_dst[0] = self.bump_seed;
+        authority_dst.copy_from_slice(self.authority.as_ref());
+        self.signers.pack_into_slice(signers_dst);
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, SignerDirectory::LEN];
+        let (is_initialized_src, bump_seed_src, authority_src, signers_src) =
+            array_refs![src, 1, 1, PUBKEY_BYTES, Signers::LEN];
+
+        Ok(SignerDirectory {
+            is_initialized: AccountType::SignerDirectory
+                .from_discriminator(is_initialized_src[0])?,
+            bump_seed: bump_seed_src[0],
+            authority: Pubkey::new_from_array(*authority_src),
+            signers: Signers::unpack_from_slice(signers_src)?,
+        })
+    }
+}
@@ -0,0 +1,277 @@
+use crate::model::balance_account::BalanceAccount;
+use crate::model::multisig_op::{InitiatorClass, MultisigOp};
+use crate::model::policy::PolicyRule;
+use crate::model::wallet::Wallet;
+use itertools::Itertools;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+
+/// A flattened view of a wallet for off-chain consumers, with every slot
+/// reference resolved to the key or address it points at.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct WalletSummary {
+    pub signers: Vec<Pubkey>,
+    pub assistant: Pubkey,
+    pub config_approvers: Vec<Pubkey>,
+    pub approvals_required_for_config: u8,
+    pub approvals_required_for_assistant_config: u8,
+    pub approval_timeout_for_config_secs: u64,
+    pub config_policy_update_locked: bool,
+    pub address_book_approvers: Vec<Pubkey>,
+    pub approvals_required_for_address_book: u8,
+    pub default_transfer_approvers: Vec<Pubkey>,
+    pub veto_approvers: Vec<Pubkey>,
+    pub destructive_config_approvers: Vec<Pubkey>,
+    pub approvals_required_for_destructive_config: u8,
+    pub address_book: Vec<Pubkey>,
+    pub pending_address_book_entries: Vec<Pubkey>,
+    pub dapps: Vec<Pubkey>,
+    pub destination_verification_delay_secs: u64,
+    pub denial_reason_threshold: u64,
+    pub balance_accounts: Vec<BalanceAccountSummary>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct BalanceAccountSummary {
+    pub guid_hash: [u8; 32],
+    pub name_hash: [u8; 32],
+    pub transfer_approvers: Vec<Pubkey>,
+    pub approvals_required_for_transfer: u8,
+    pub approval_timeout_for_transfer_secs: u64,
+    pub use_config_approvers: bool,
+    pub policy_update_locked: bool,
+    pub whitelist_enabled: bool,
+    pub allowed_destinations: Vec<Pubkey>,
+    pub dapps_enabled: bool,
+    pub sol_transfers_enabled: bool,
+    pub spl_transfers_enabled: bool,
+    pub token_mint_allowlist_enabled: bool,
+    pub allowed_token_mints: Vec<Pubkey>,
+    pub outstanding_transfer_value: u64,
+    pub max_outstanding_transfer_value: u64,
+    pub max_finalize_fee_reimbursement: u64,
+    pub policy_rules: Vec<PolicyRule>,
+    /// Transfers of at least this amount are subject to the cooldown.
+    pub large_transfer_min_amount: u64,
+    /// Zero when the balance account has no large transfer cooldown.
+    pub large_transfer_cooldown_secs: u64,
+    pub last_large_transfer_at: i64,
+    /// The stored bump seed of the account's PDA, if it has one.
+    pub bump_seed: Option<u8>,
+}
+
+/// The approval policy an op was started with, read from the op account alone
+/// so approval clients don't need to fetch and unpack the wallet. These are
+/// the same fields the op's quorum is computed from, along with who started
+/// the op and what type of op it is.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PolicySnapshot {
+    pub approvers: Vec<Pubkey>,
+    pub approvals_required: u8,
+    pub approval_timeout_secs: u64,
+    pub started_at: i64,
+    pub expires_at: i64,
+    pub initiator: Pubkey,
+    pub op_type: u8,
+    pub veto_approvers: Vec<Pubkey>,
+}
+
+impl MultisigOp {
+    pub fn policy_snapshot(&self) -> PolicySnapshot {
+        PolicySnapshot {
+            approvers: self
+                .disposition_records
+                .iter()
+                .map(|record| record.approver)
+                .collect_vec(),
+            approvals_required: self.dispositions_required,
+            approval_timeout_secs: self.approval_timeout.as_secs(),
+            started_at: self.started_at,
+            expires_at: self.expires_at,
+            initiator: self.initiator,
+            op_type: self.op_type,
+            veto_approvers: self.veto_approvers.clone(),
+        }
+    }
+}
+
+impl Wallet {
+    pub fn summary(&self) -> WalletSummary {
+        WalletSummary {
+            signers: self.get_signers_keys(),
+            assistant: self.assistant.key,
+            config_approvers: self.get_config_approvers_keys(),
+            approvals_required_for_config: self.approvals_required_for_config,
+            approvals_required_for_assistant_config: self.approvals_required_for_assistant_config,
+            approval_timeout_for_config_secs: self.approval_timeout_for_config.as_secs(),
+            config_policy_update_locked: self.config_policy_update_locked,
+            address_book_approvers: self.get_address_book_approvers_keys(),
+            approvals_required_for_address_book: self
+                .approvals_required_for_address_book_op(InitiatorClass::Approver),
+            default_transfer_approvers: self.get_default_transfer_approvers_keys(),
+            veto_approvers: self.get_veto_approvers_keys(),
+            destructive_config_approvers: self.get_destructive_config_approvers_keys(),
+            approvals_required_for_destructive_config: self
+                .approvals_required_for_destructive_config_op(InitiatorClass::Approver),
+            address_book: self
+                .address_book
+                .filled_slots()
+                .iter()
+                .map(|(_, entry)| entry.address)
+                .collect_vec(),
+            pending_address_book_entries: self
+                .pending_address_book_entries
+                .iter_enabled()
+                .filter_map(|slot_id| self.address_book[slot_id].map(|entry| entry.address))
+                .collect_vec(),
+            dapps: self
+                .dapp_book
+                .filled_slots()
+                .iter()
+                .map(|(_, entry)| entry.address)
+                .collect_vec(),
+            destination_verification_delay_secs: self.destination_verification_delay.as_secs(),
+            denial_reason_threshold: self.denial_reason_threshold,
+            balance_accounts: self
+                .balance_accounts
+                .filled_slots()
+                .iter()
+                .map(|(_, balance_account)| balance_account.summary(self))
+                .collect_vec(),
+        }
+    }
+}
+
+impl BalanceAccount {
+    /// Resolves the approver, destination and token mint slots against the
+    /// wallet the balance account belongs to.
+    pub fn summary(&self, wallet: &Wallet) -> BalanceAccountSummary {
+        let mut guid_hash = [0; 32];
+        guid_hash.copy_from_slice(self.guid_hash.to_bytes());
+        BalanceAccountSummary {
+            guid_hash,
+            name_hash: *self.name_hash.to_bytes(),
+            transfer_approvers: wallet.get_transfer_approvers_keys(self),
+            approvals_required_for_transfer: self.approvals_required_for_transfer,
+            approval_timeout_for_transfer_secs: self.approval_timeout_for_transfer.as_secs(),
+            use_config_approvers: self.uses_config_approvers(),
+            policy_update_locked: self.policy_update_locked,
+            whitelist_enabled: !self.is_whitelist_disabled(),
+            allowed_destinations: wallet
+                .get_allowed_destinations(self)
+                .iter()
+                .map(|entry| entry.address)
+                .collect_vec(),
+            dapps_enabled: !self.are_dapps_disabled(),
+            sol_transfers_enabled: !self.are_sol_transfers_disabled(),
+            spl_transfers_enabled: !self.are_spl_transfers_disabled(),
+            token_mint_allowlist_enabled: self.is_token_mint_allowlist_enabled(),
+            allowed_token_mints: self
+                .allowed_token_mints
+                .iter_enabled()
+                .filter_map(|slot_id| wallet.token_mint_book[slot_id].map(|mint| mint.address))
+                .collect_vec(),
+            outstanding_transfer_value: self.outstanding_transfer_value,
+            max_outstanding_transfer_value: self.max_outstanding_transfer_value,
+            max_finalize_fee_reimbursement: self.max_finalize_fee_reimbursement,
+            policy_rules: self.policy_rules.to_vec(),
+            large_transfer_min_amount: self
+                .large_transfer_cooldown
+                .map_or(0, |cooldown| cooldown.min_amount),
+            large_transfer_cooldown_secs: self
+                .large_transfer_cooldown
+                .map_or(0, |cooldown| cooldown.period.as_secs()),
+            last_large_transfer_at: self.last_large_transfer_at,
+            bump_seed: self.bump_seed,
+        }
+    }
+}
+
+#[test]
+fn test_summary_resolves_slots() {
+    use crate::model::signer::Signer;
+    use crate::utils::SlotId;
+    use solana_program::program_pack::Pack;
+
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    let signer = Signer::new(Pubkey::new_unique());
+    wallet.signers.insert(SlotId::new(2), signer);
+    wallet.config_approvers.enable(&SlotId::new(2));
+    // a dangling flag does not show up in the summary
+    wallet.config_approvers.enable(&SlotId::new(3));
+
+    let mut balance_account = BalanceAccount::unpack_from_slice(&[0; BalanceAccount::LEN]).unwrap();
+    balance_account.transfer_approvers.enable(&SlotId::new(2));
+    wallet
+        .balance_accounts
+        .insert(SlotId::new(0), balance_account);
+
+    let summary = wallet.summary();
+    assert_eq!(summary.signers, vec![signer.key]);
+    assert_eq!(summary.config_approvers, vec![signer.key]);
+    assert_eq!(summary.balance_accounts.len(), 1);
+    assert_eq!(
+        summary.balance_accounts[0].transfer_approvers,
+        vec![signer.key]
+    );
+    assert!(!summary.balance_accounts[0].whitelist_enabled);
+}
+
+#[test]
+fn test_policy_snapshot_survives_renewal() {
+    use crate::model::multisig_op::MultisigOpParams;
+    use solana_program::clock::Clock;
+    use solana_program::program_pack::Pack;
+    use std::time::Duration;
+
+    let approvers = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+    let initiator = Pubkey::new_unique();
+    let params = MultisigOpParams::RepairWallet {
+        wallet_address: Pubkey::new_unique(),
+    };
+    let mut multisig_op = MultisigOp::unpack_unchecked(&[0; MultisigOp::LEN]).unwrap();
+    multisig_op
+        .init(
+            approvers.clone(),
+            2,
+            100,
+            400,
+            params.clone(),
+            false,
+            InitiatorClass::Approver,
+            initiator,
+        )
+        .unwrap();
+    let clock = Clock {
+        unix_timestamp: 500,
+        ..Clock::default()
+    };
+    multisig_op
+        .renew(
+            &params.hash(),
+            &approvers[0],
+            Duration::from_secs(300),
+            &clock,
+        )
+        .unwrap();
+
+    let mut data = vec![0; MultisigOp::LEN];
+    MultisigOp::pack(multisig_op, &mut data).unwrap();
+    assert_eq!(
+        MultisigOp::unpack(&data).unwrap().policy_snapshot(),
+        PolicySnapshot {
+            approvers,
+            approvals_required: 2,
+            approval_timeout_secs: 300,
+            started_at: 100,
+            expires_at: 700,
+            initiator,
+            op_type: 16,
+            veto_approvers: vec![],
+        }
+    );
+}
@@ -0,0 +1,29 @@
+use crate::model::wallet::Wallet;
+use crate::utils::{SlotFlags, Slots};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{Pack, Sealed};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+pub type TokenMintBook = Slots<TokenMint, { Wallet::MAX_TOKEN_MINTS }>;
+pub type AllowedTokenMints = SlotFlags<TokenMint, { TokenMintBook::FLAGS_STORAGE_SIZE }>;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
+pub struct TokenMint {
+    pub address: Pubkey,
+}
+
+impl Sealed for TokenMint {}
+
+impl Pack for TokenMint {
+    const LEN: usize = PUBKEY_BYTES;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[..PUBKEY_BYTES].copy_from_slice(self.address.as_ref());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        Ok(TokenMint {
+            address: Pubkey::new(&src[..PUBKEY_BYTES]),
+        })
+    }
+}
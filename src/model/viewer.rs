@@ -0,0 +1,39 @@
+use crate::model::wallet::Wallet;
+use crate::utils::Slots;
+use arrayref::array_ref;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{Pack, Sealed};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+pub type Viewers = Slots<Viewer, { Wallet::MAX_VIEWERS }>;
+
+/// A key registered with a wallet for read-only access, such as an auditor's.
+/// Viewers can't initiate or approve anything; they can only prove that they
+/// are registered.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
+pub struct Viewer {
+    pub key: Pubkey,
+}
+
+impl Viewer {
+    pub fn new(key: Pubkey) -> Self {
+        Viewer { key }
+    }
+}
+
+impl Sealed for Viewer {}
+
+impl Pack for Viewer {
+    const LEN: usize = PUBKEY_BYTES;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst.copy_from_slice(self.key.as_ref());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, Viewer::LEN];
+        Ok(Viewer {
+            key: Pubkey::new_from_array(*src),
+        })
+    }
+}
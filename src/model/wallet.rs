@@ -1,30 +1,53 @@
 use crate::error::WalletError;
 use crate::instruction::{
-    AddressBookUpdate, BalanceAccountCreation, BalanceAccountPolicyUpdate, DAppBookUpdate,
-    InitialWalletConfig, WalletConfigPolicyUpdate,
+    AddressBookUpdate, ApproverDelegationUpdate, BalanceAccountCreation,
+    BalanceAccountPolicyUpdate, DAppBookUpdate, DAppTemplatesUpdate, GovernedParameterUpdate,
+    InitialWalletConfig, SignerOrganizationsUpdate, SignersUpdate, ViewersUpdate,
+    WalletConfigPolicyUpdate, WalletSignerDirectoryUpdate,
 };
+use crate::model::account_type::AccountType;
+use crate::model::activity::WalletActivity;
 use crate::model::address_book::{
-    AddressBook, AddressBookEntry, AddressBookEntryNameHash, DAppBook, DAppBookEntry,
+    AddressBook, AddressBookEntry, AddressBookEntryNameHash, AddressNamespace, DAppBook,
+    DAppBookEntry, DAppInstructionPrefixes, DestinationNamespace, DestinationNamespaces,
+    DestinationTokenMint, DestinationTokenMints, PendingAddressBookEntryTimes,
 };
+use crate::model::approver_delegation::{ApproverDelegation, ApproverDelegations};
 use crate::model::balance_account::{
     AllowedDestinations, BalanceAccount, BalanceAccountGuidHash, BalanceAccountNameHash,
 };
-use crate::model::multisig_op::BooleanSetting;
-use crate::model::signer::Signer;
+use crate::model::dapp_approval_policy::{DAppApprovalPolicies, DAppApprovalPolicy};
+use crate::model::dapp_inner_programs::{DAppInnerProgramSets, DAppInnerPrograms};
+use crate::model::dapp_template::{DAppTemplate, DAppTemplates};
+use crate::model::dapp_value_limit::{DAppValueLimit, DAppValueLimits, DAppValueUsages};
+use crate::model::governed_parameter::GovernedParameters;
+use crate::model::idempotency::{IdempotencyKey, RecentIdempotencyKeys};
+use crate::model::multisig_op::{
+    BooleanSetting, ConfigChangeClass, InitiatorClass, MultisigOpParams, SlotUpdateType,
+};
+use crate::model::policy::PolicyRules;
+use crate::model::program_config::PolicyLimits;
+use crate::model::signer::{Signer, SignerActivity, SignerOrganizations};
+use crate::model::signer_directory::SignerDirectory;
+use crate::model::token_mint::{AllowedTokenMints, TokenMint, TokenMintBook};
+use crate::model::viewer::{Viewer, Viewers};
 use crate::utils::{GetSlotIds, SlotFlags, SlotId, Slots};
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use itertools::Itertools;
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::{Hash, HASH_BYTES};
 use solana_program::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{IsInitialized, Pack, Sealed};
-use solana_program::pubkey::Pubkey;
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
 use std::time::Duration;
 
 pub type Signers = Slots<Signer, { Wallet::MAX_SIGNERS }>;
 pub type Approvers = SlotFlags<Signer, { Signers::FLAGS_STORAGE_SIZE }>;
 pub type BalanceAccounts = Slots<BalanceAccount, { Wallet::MAX_BALANCE_ACCOUNTS }>;
+pub type PendingAddressBookEntries =
+    SlotFlags<AddressBookEntry, { AddressBook::FLAGS_STORAGE_SIZE }>;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Wallet {
@@ -38,6 +61,69 @@ pub struct Wallet {
     pub balance_accounts: BalanceAccounts,
     pub config_policy_update_locked: bool,
     pub dapp_book: DAppBook,
+    pub destination_verification_delay: Duration,
+    pub pending_address_book_entries: PendingAddressBookEntries,
+    pub activity: WalletActivity,
+    pub denial_reason_threshold: u64,
+    pub approvals_required_for_assistant_config: u8,
+    pub token_mint_book: TokenMintBook,
+    /// Approvers of address book and dapp book ops. When empty, the config
+    /// approvers and quorum apply, which is what existing wallets start with.
+    pub address_book_approvers: Approvers,
+    pub approvals_required_for_address_book: u8,
+    /// Approvers of transfers from balance accounts that don't have their own
+    /// transfer approvers. When empty, the config approvers apply.
+    pub default_transfer_approvers: Approvers,
+    pub approver_delegations: ApproverDelegations,
+    /// The signer directory that directory-backed signer slots resolve against.
+    pub signer_directory: Option<Pubkey>,
+    pub directory_signers: Approvers,
+    pub governed_parameters: GovernedParameters,
+    /// Keys registered for read-only access, with no signing power.
+    pub viewers: Viewers,
+    pub signer_organizations: SignerOrganizations,
+    /// Token mints address book entries are bound to.
+    pub destination_token_mints: DestinationTokenMints,
+    /// Per-period caps on the value each dapp's transactions can move out of a
+    /// balance account, in the slot of the dapp they apply to.
+    pub dapp_value_limits: DAppValueLimits,
+    /// A key that is never used legitimately. An instruction signed by it
+    /// freezes the wallet until the canary key is updated.
+    pub canary_key: Option<Pubkey>,
+    pub frozen: bool,
+    /// Hash of the off-chain policy document the wallet's configuration is
+    /// governed by, or the default hash if none has been set.
+    pub metadata_hash: Hash,
+    /// Instruction templates the assistant can send from balance accounts
+    /// without an op of their own.
+    pub dapp_templates: DAppTemplates,
+    /// Signers any one of whom can deny an op outright, regardless of the
+    /// approvals it already has.
+    pub veto_approvers: Approvers,
+    /// Namespaces address book entries are tagged with.
+    pub destination_namespaces: DestinationNamespaces,
+    /// Approvers of destructive config ops. When empty, the config approvers
+    /// and quorum apply to every config op.
+    pub destructive_config_approvers: Approvers,
+    pub approvals_required_for_destructive_config: u8,
+    /// Bumped by every finalized config op, so that ops started under an
+    /// earlier configuration can tell.
+    pub config_version: u64,
+    /// Programs each dapp's instructions may hand it as accounts, in the slot
+    /// of the dapp they apply to.
+    pub dapp_inner_programs: DAppInnerProgramSets,
+    /// When each signer slot last gave a disposition, so dormant keys can be
+    /// spotted.
+    pub signer_activity: SignerActivity,
+    /// Idempotency keys of the last init instructions submitted with one, so
+    /// a retried submission can't start the same op twice.
+    pub recent_idempotency_keys: RecentIdempotencyKeys,
+    /// Approvers and quorum of each dapp's transactions, in the slot of the
+    /// dapp they apply to, overriding those of the balance account.
+    pub dapp_approval_policies: DAppApprovalPolicies,
+    /// When each pending address book entry was added, which its destination
+    /// verification delay runs from.
+    pub pending_address_book_entries_added_at: PendingAddressBookEntryTimes,
 }
 
 impl Sealed for Wallet {}
@@ -55,6 +141,24 @@ impl Wallet {
     pub const MIN_APPROVAL_TIMEOUT: Duration = Duration::from_secs(60);
     pub const MAX_APPROVAL_TIMEOUT: Duration = Duration::from_secs(60 * 60 * 24 * 365);
     pub const MAX_DAPP_BOOK_ENTRIES: usize = 32;
+    pub const MAX_TOKEN_MINTS: usize = 16;
+    pub const MAX_APPROVER_DELEGATIONS: usize = 4;
+    pub const MAX_VIEWERS: usize = 8;
+    pub const MAX_DESTINATION_TOKEN_MINTS: usize = 32;
+    pub const MAX_DAPP_TEMPLATES: usize = 8;
+    pub const MAX_VETO_APPROVERS: usize = 4;
+    pub const MAX_DESTINATION_NAMESPACES: usize = 32;
+    const CANARY_LEN: usize = 1 + PUBKEY_BYTES + 1;
+    pub const FEE_ACCOUNT_SEED: &'static [u8] = b"fee-account";
+
+    /// The system-owned PDA customers can prefund to pay the rent of the
+    /// wallet's op accounts, and that op rent can be returned to.
+    pub fn find_fee_account_address(wallet_address: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[wallet_address.as_ref(), Wallet::FEE_ACCOUNT_SEED],
+            program_id,
+        )
+    }
 
     pub fn get_signers_keys(&self) -> Vec<Pubkey> {
         return self
@@ -69,8 +173,60 @@ impl Wallet {
         self.get_approvers_keys(&self.config_approvers)
     }
 
+    pub fn get_address_book_approvers_keys(&self) -> Vec<Pubkey> {
+        self.get_approvers_keys(self.address_book_approvers())
+    }
+
+    pub fn get_default_transfer_approvers_keys(&self) -> Vec<Pubkey> {
+        self.get_approvers_keys(self.default_transfer_approvers())
+    }
+
+    pub fn get_destructive_config_approvers_keys(&self) -> Vec<Pubkey> {
+        self.get_approvers_keys(self.destructive_config_approvers())
+    }
+
+    pub fn get_veto_approvers_keys(&self) -> Vec<Pubkey> {
+        self.get_approvers_keys(&self.veto_approvers)
+    }
+
     pub fn get_transfer_approvers_keys(&self, balance_account: &BalanceAccount) -> Vec<Pubkey> {
-        self.get_approvers_keys(&balance_account.transfer_approvers)
+        if balance_account.uses_config_approvers() {
+            self.get_default_transfer_approvers_keys()
+        } else {
+            self.get_approvers_keys(&balance_account.transfer_approvers)
+        }
+    }
+
+    pub fn get_transfer_approvers_count(&self, balance_account: &BalanceAccount) -> usize {
+        if balance_account.uses_config_approvers() {
+            self.default_transfer_approvers().count_enabled()
+        } else {
+            balance_account.transfer_approvers.count_enabled()
+        }
+    }
+
+    fn address_book_approvers(&self) -> &Approvers {
+        if self.address_book_approvers.count_enabled() > 0 {
+            &self.address_book_approvers
+        } else {
+            &self.config_approvers
+        }
+    }
+
+    fn destructive_config_approvers(&self) -> &Approvers {
+        if self.destructive_config_approvers.count_enabled() > 0 {
+            &self.destructive_config_approvers
+        } else {
+            &self.config_approvers
+        }
+    }
+
+    fn default_transfer_approvers(&self) -> &Approvers {
+        if self.default_transfer_approvers.count_enabled() > 0 {
+            &self.default_transfer_approvers
+        } else {
+            &self.config_approvers
+        }
     }
 
     fn get_approvers_keys(&self, approvers: &Approvers) -> Vec<Pubkey> {
@@ -116,35 +272,165 @@ impl Wallet {
         return self.validate_initiator(initiator, || self.get_signers_keys());
     }
 
-    /// Validates the state of a wallet.
-    pub fn validate_approval_timeout(timeout: &Duration) -> ProgramResult {
-        // approval timeout seconds must fall within program-defined range.
-        if *timeout < Wallet::MIN_APPROVAL_TIMEOUT {
-            msg!(
-                "Approval timeout for config can't be less than {}",
-                Wallet::MIN_APPROVAL_TIMEOUT.as_secs(),
-            );
-            return Err(WalletError::InvalidApprovalTimeout.into());
+    pub fn validate_approvals_required(approvals_required: u8) -> ProgramResult {
+        if approvals_required == 0 {
+            msg!("Approvals required can't be 0");
+            return Err(WalletError::InvalidApproverCount.into());
         }
 
-        if *timeout > Wallet::MAX_APPROVAL_TIMEOUT {
+        Ok(())
+    }
+
+    /// Fails if the address book update makes more changes than can be
+    /// applied within the compute budget of one instruction.
+    pub fn validate_address_book_update_size(update: &AddressBookUpdate) -> ProgramResult {
+        if update.change_count() > AddressBookUpdate::MAX_CHANGES {
             msg!(
-                "Approval timeout for config can't be more than {} seconds",
-                Wallet::MAX_APPROVAL_TIMEOUT.as_secs(),
+                "Address book update makes {} changes, at most {} are allowed per update",
+                update.change_count(),
+                AddressBookUpdate::MAX_CHANGES
             );
-            return Err(WalletError::InvalidApprovalTimeout.into());
+            return Err(WalletError::AddressBookUpdateTooLarge.into());
         }
 
         Ok(())
     }
 
-    pub fn validate_approvals_required(approvals_required: u8) -> ProgramResult {
-        if approvals_required == 0 {
-            msg!("Approvals required can't be 0");
-            return Err(WalletError::InvalidApproverCount.into());
+    pub fn initiator_class(&self, initiator: &AccountInfo) -> InitiatorClass {
+        if initiator.key == &self.assistant.key {
+            InitiatorClass::Assistant
+        } else {
+            InitiatorClass::Approver
         }
+    }
 
-        Ok(())
+    /// Approvals required for a config op started by the given class of
+    /// initiator. Zero for assistant-initiated ops means the regular config
+    /// quorum applies.
+    pub fn approvals_required_for_config_op(&self, initiator_class: InitiatorClass) -> u8 {
+        match initiator_class {
+            InitiatorClass::Assistant if self.approvals_required_for_assistant_config > 0 => {
+                self.approvals_required_for_assistant_config
+            }
+            _ => self.approvals_required_for_config,
+        }
+    }
+
+    /// Approvals required for an address book or dapp book op. Falls back to
+    /// the config quorum while no dedicated address book approvers are set.
+    pub fn approvals_required_for_address_book_op(&self, initiator_class: InitiatorClass) -> u8 {
+        if self.address_book_approvers.count_enabled() > 0 {
+            self.approvals_required_for_address_book
+        } else {
+            self.approvals_required_for_config_op(initiator_class)
+        }
+    }
+
+    /// Approvals required for a destructive config op. Falls back to the
+    /// regular config quorum while no destructive config approvers are set.
+    pub fn approvals_required_for_destructive_config_op(
+        &self,
+        initiator_class: InitiatorClass,
+    ) -> u8 {
+        if self.destructive_config_approvers.count_enabled() > 0 {
+            self.approvals_required_for_destructive_config
+        } else {
+            self.approvals_required_for_config_op(initiator_class)
+        }
+    }
+
+    /// The approvers and quorum of a config op with the given params, which
+    /// depend on whether the op would weaken the wallet's configuration.
+    /// Closing the wallet can't be undone, so it needs every config approver.
+    pub fn config_op_approvers(
+        &self,
+        params: &MultisigOpParams,
+        initiator_class: InitiatorClass,
+    ) -> (Vec<Pubkey>, u8) {
+        if let MultisigOpParams::CloseWallet { .. } = params {
+            let approvers = self.get_config_approvers_keys();
+            let approvals_required = approvers.len() as u8;
+            return (approvers, approvals_required);
+        }
+        match self.config_change_class(params) {
+            ConfigChangeClass::Additive => (
+                self.get_config_approvers_keys(),
+                self.approvals_required_for_config_op(initiator_class),
+            ),
+            ConfigChangeClass::Destructive => (
+                self.get_destructive_config_approvers_keys(),
+                self.approvals_required_for_destructive_config_op(initiator_class),
+            ),
+        }
+    }
+
+    /// Classifies a config op against the wallet's current configuration.
+    /// Removing signers or approvers, lowering a quorum, turning off a
+    /// balance account's whitelist and closing the wallet are destructive;
+    /// everything else is additive.
+    pub fn config_change_class(&self, params: &MultisigOpParams) -> ConfigChangeClass {
+        let lowered = |update: Option<u8>, current: u8| update.map_or(false, |u| u < current);
+        let destructive = match params {
+            MultisigOpParams::UpdateSigner {
+                slot_update_type, ..
+            } => *slot_update_type == SlotUpdateType::Clear,
+            MultisigOpParams::UpdateSigners { update, .. } => !update.remove_signers.is_empty(),
+            MultisigOpParams::UpdateWalletConfigPolicy { update, .. } => {
+                update.replace_config_approvers.is_some()
+                    || !update.remove_config_approvers.is_empty()
+                    || !update.remove_address_book_approvers.is_empty()
+                    || !update.remove_default_transfer_approvers.is_empty()
+                    || !update.remove_veto_approvers.is_empty()
+                    || !update.remove_destructive_config_approvers.is_empty()
+                    || lowered(
+                        update.approvals_required_for_config,
+                        self.approvals_required_for_config,
+                    )
+                    || lowered(
+                        update.approvals_required_for_assistant_config,
+                        self.approvals_required_for_assistant_config,
+                    )
+                    || lowered(
+                        update.approvals_required_for_address_book,
+                        self.approvals_required_for_address_book,
+                    )
+                    || lowered(
+                        update.approvals_required_for_destructive_config,
+                        self.approvals_required_for_destructive_config,
+                    )
+            }
+            MultisigOpParams::UpdateBalanceAccountPolicy {
+                account_guid_hash,
+                update,
+                ..
+            } => {
+                update
+                    .remove_transfer_approvers
+                    .as_ref()
+                    .map_or(false, |approvers| !approvers.is_empty())
+                    || self
+                        .balance_accounts
+                        .filled_slots()
+                        .iter()
+                        .find(|(_, account)| account.guid_hash == *account_guid_hash)
+                        .map_or(false, |(_, account)| {
+                            lowered(
+                                update.approvals_required_for_transfer,
+                                account.approvals_required_for_transfer,
+                            )
+                        })
+            }
+            MultisigOpParams::UpdateBalanceAccountSettings {
+                whitelist_enabled, ..
+            } => *whitelist_enabled == Some(BooleanSetting::Off),
+            MultisigOpParams::CloseWallet { .. } => true,
+            _ => false,
+        };
+        if destructive {
+            ConfigChangeClass::Destructive
+        } else {
+            ConfigChangeClass::Additive
+        }
     }
 
     fn validate_initiator<F: FnOnce() -> Vec<Pubkey>>(
@@ -174,11 +460,149 @@ impl Wallet {
                 address: *address,
                 name_hash: *name_hash,
             }) {
-                Some(entry_ref) => balance_account.allowed_destinations.is_enabled(&entry_ref),
+                Some(entry_ref) => {
+                    balance_account.allowed_destinations.is_enabled(&entry_ref)
+                        && !self.pending_address_book_entries.is_enabled(&entry_ref)
+                }
                 None => false,
             })
     }
 
+    /// Returns an error if every address book entry for the address is bound
+    /// to token mints other than the given one. Addresses without an unbound
+    /// entry only receive the mints their entries are bound to.
+    pub fn validate_destination_token_mint(
+        &self,
+        address: &Pubkey,
+        token_mint: &Pubkey,
+    ) -> ProgramResult {
+        let bindings = self.destination_token_mints.filled_slots();
+        let entry_ids = self
+            .address_book
+            .filled_slots()
+            .into_iter()
+            .filter(|(_, entry)| entry.address == *address)
+            .map(|(entry_id, _)| entry_id)
+            .collect_vec();
+        let allowed = entry_ids.is_empty()
+            || entry_ids.iter().any(|entry_id| {
+                let mut entry_bindings = bindings
+                    .iter()
+                    .filter(|(_, binding)| binding.address_book_entry == *entry_id)
+                    .peekable();
+                entry_bindings.peek().is_none()
+                    || entry_bindings.any(|(_, binding)| binding.token_mint == *token_mint)
+            });
+        if !allowed {
+            msg!(
+                "Destination {} does not accept tokens of mint {}",
+                address,
+                token_mint
+            );
+            return Err(WalletError::TokenMintNotAllowed.into());
+        }
+        Ok(())
+    }
+
+    /// Returns an error unless an address book entry for the address is
+    /// untagged, or tagged with a namespace the transfer of the mint (the
+    /// default pubkey for SOL) with the given memo fits. Transfers to an
+    /// address with an entry tagged as an exchange also have to carry a
+    /// travel rule data hash.
+    pub fn validate_destination_namespace(
+        &self,
+        address: &Pubkey,
+        token_mint: &Pubkey,
+        memo_hash: Option<&Hash>,
+        travel_rule_hash: Option<&Hash>,
+    ) -> ProgramResult {
+        let namespaces = self.destination_namespaces_of(address);
+        if travel_rule_hash.is_none() && namespaces.contains(&Some(AddressNamespace::Exchange)) {
+            msg!(
+                "Transfers to exchange destination {} require a travel rule data hash",
+                address
+            );
+            return Err(WalletError::TravelRuleDataRequired.into());
+        }
+        // exchange tags don't restrict what the address receives, so an address
+        // that is also a deposit address still needs its memo
+        let namespaces = namespaces
+            .into_iter()
+            .filter(|namespace| *namespace != Some(AddressNamespace::Exchange))
+            .collect_vec();
+        let allowed = namespaces.is_empty()
+            || namespaces.iter().any(|namespace| match namespace {
+                Some(namespace) => namespace.accepts(token_mint, memo_hash),
+                None => true,
+            });
+        if !allowed {
+            msg!(
+                "Transfer of mint {} does not fit the namespaces of destination {}",
+                token_mint,
+                address
+            );
+            return Err(WalletError::DestinationNamespaceMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// Whether the address is in the address book as a token account, which
+    /// SPL transfers go to directly rather than to its associated token account.
+    pub fn is_token_account_destination(&self, address: &Pubkey) -> bool {
+        self.destination_namespaces_of(address)
+            .contains(&Some(AddressNamespace::TokenAccount))
+    }
+
+    /// The namespace of each address book entry for the address.
+    fn destination_namespaces_of(&self, address: &Pubkey) -> Vec<Option<AddressNamespace>> {
+        let tags = self.destination_namespaces.filled_slots();
+        self.address_book
+            .filled_slots()
+            .into_iter()
+            .filter(|(_, entry)| entry.address == *address)
+            .map(|(entry_id, _)| {
+                tags.iter()
+                    .find(|(_, tag)| tag.address_book_entry == entry_id)
+                    .map(|(_, tag)| tag.namespace)
+            })
+            .collect_vec()
+    }
+
+    /// Returns whether SPL tokens of the given mint can be transferred out of
+    /// the balance account.
+    pub fn token_mint_allowed(&self, balance_account: &BalanceAccount, mint: &Pubkey) -> bool {
+        !balance_account.is_token_mint_allowlist_enabled()
+            || match self.token_mint_book.find_id(&TokenMint { address: *mint }) {
+                Some(slot_id) => balance_account.allowed_token_mints.is_enabled(&slot_id),
+                None => false,
+            }
+    }
+
+    /// The SPL token mints the wallet knows of: those allowed for a balance
+    /// account, bound to a destination or transferred this activity period.
+    pub fn tracked_token_mints(&self) -> Vec<Pubkey> {
+        self.token_mint_book
+            .filled_slots()
+            .into_iter()
+            .map(|(_, token_mint)| token_mint.address)
+            .chain(
+                self.destination_token_mints
+                    .filled_slots()
+                    .into_iter()
+                    .map(|(_, binding)| binding.token_mint),
+            )
+            .chain(
+                self.activity
+                    .transfer_totals
+                    .iter()
+                    .map(|total| total.token_mint),
+            )
+            .filter(|token_mint| *token_mint != Pubkey::default())
+            .sorted()
+            .dedup()
+            .collect_vec()
+    }
+
     pub fn validate_remove_signer(
         &self,
         signer_to_remove: (SlotId<Signer>, Signer),
@@ -187,20 +611,67 @@ impl Wallet {
         self_clone.remove_signers(&vec![signer_to_remove])
     }
 
-    pub fn validate_add_signer(&self, signer_to_add: (SlotId<Signer>, Signer)) -> ProgramResult {
+    pub fn validate_add_signer(
+        &self,
+        signer_to_add: (SlotId<Signer>, Signer),
+        limits: &PolicyLimits,
+    ) -> ProgramResult {
         let mut self_clone = self.clone();
-        self_clone.add_signers(&vec![signer_to_add])
+        self_clone.add_signers(&vec![signer_to_add], limits)
     }
 
     pub fn remove_signer(&mut self, signer_to_remove: (SlotId<Signer>, Signer)) -> ProgramResult {
         self.remove_signers(&vec![signer_to_remove])
     }
 
-    pub fn add_signer(&mut self, signer_to_add: (SlotId<Signer>, Signer)) -> ProgramResult {
-        self.add_signers(&vec![signer_to_add])
+    pub fn add_signer(
+        &mut self,
+        signer_to_add: (SlotId<Signer>, Signer),
+        limits: &PolicyLimits,
+    ) -> ProgramResult {
+        self.add_signers(&vec![signer_to_add], limits)
+    }
+
+    pub fn validate_signers_update(
+        &self,
+        update: &SignersUpdate,
+        limits: &PolicyLimits,
+    ) -> ProgramResult {
+        let mut self_clone = self.clone();
+        self_clone.update_signers(update, limits)
+    }
+
+    /// Removes and then adds the given signers, so a slot can be emptied and
+    /// refilled in the same update. Fails as a whole if any of them can't be
+    /// applied or the wallet's quorums can't be met afterwards.
+    pub fn update_signers(
+        &mut self,
+        update: &SignersUpdate,
+        limits: &PolicyLimits,
+    ) -> ProgramResult {
+        let add_count = update.add_signers.len();
+        if update.add_signers.slot_ids().iter().unique().count() != add_count
+            || update
+                .add_signers
+                .iter()
+                .map(|(_, signer)| signer.key)
+                .unique()
+                .count()
+                != add_count
+        {
+            msg!("Failed to add signers: a slot or key is given more than once");
+            return Err(WalletError::SlotCannotBeInserted.into());
+        }
+        self.remove_signers(&update.remove_signers)?;
+        self.add_signers(&update.add_signers, limits)?;
+        self.validate_quorums()
     }
 
-    pub fn initialize(&mut self, initial_config: &InitialWalletConfig) -> ProgramResult {
+    pub fn initialize(
+        &mut self,
+        initial_config: &InitialWalletConfig,
+        limits: &PolicyLimits,
+    ) -> ProgramResult {
         self.approvals_required_for_config = initial_config.approvals_required_for_config;
 
         // NOTE: A timeout of 0 means that the existing value should not be updated.
@@ -209,7 +680,7 @@ impl Wallet {
             self.approval_timeout_for_config = initial_config.approval_timeout_for_config;
         }
 
-        self.add_signers(&initial_config.signers)?;
+        self.add_signers(&initial_config.signers, limits)?;
         self.enable_config_approvers(&initial_config.config_approvers)?;
 
         let approvers_count_after_update = self.config_approvers.count_enabled();
@@ -223,7 +694,7 @@ impl Wallet {
             return Err(WalletError::InvalidApproverCount.into());
         }
 
-        Wallet::validate_approval_timeout(&self.approval_timeout_for_config)?;
+        limits.validate_approval_timeout(&self.approval_timeout_for_config)?;
 
         if self.approvals_required_for_config == 0 {
             msg!("Approvals required for config can't be 0");
@@ -239,12 +710,35 @@ impl Wallet {
     }
 
     pub fn validate_address_book_update(&self, update: &AddressBookUpdate) -> ProgramResult {
+        Wallet::validate_address_book_update_size(update)?;
         let mut self_clone = self.clone();
-        self_clone.update_address_book(update)
+        // when the entries are added doesn't decide whether the update applies
+        self_clone.update_address_book(update, 0)
     }
 
-    pub fn update_address_book(&mut self, update: &AddressBookUpdate) -> ProgramResult {
-        self.add_address_book_entries(&update.add_address_book_entries)?;
+    /// Applies the update without checking its size, which only limits updates
+    /// sent whole in an instruction, so that chunked updates can be larger.
+    pub fn update_address_book(&mut self, update: &AddressBookUpdate, now: i64) -> ProgramResult {
+        let new_entries = self.new_address_book_entries(&update.add_address_book_entries)?;
+        self.add_address_book_entries(&new_entries)?;
+        if self.destination_verification_delay.as_secs() > 0 {
+            // new entries can't be used as destinations until verified
+            let new_entry_ids = new_entries.slot_ids();
+            self.pending_address_book_entries
+                .enable_many(&new_entry_ids);
+            for slot_id in new_entry_ids {
+                self.pending_address_book_entries_added_at
+                    .record(*slot_id, now);
+            }
+        }
+        self.update_destination_token_mints(
+            &update.add_destination_token_mints,
+            &update.remove_destination_token_mints,
+        )?;
+        self.update_destination_namespaces(
+            &update.add_destination_namespaces,
+            &update.remove_destination_namespaces,
+        )?;
         for balance_account_whitelist_update in update.balance_account_whitelist_updates.clone() {
             let (slot_id, mut balance_account) =
                 self.get_balance_account_with_slot_id(&balance_account_whitelist_update.guid_hash)?;
@@ -262,12 +756,65 @@ impl Wallet {
         Ok(())
     }
 
+    pub fn validate_address_book_verification(
+        &self,
+        entries: &Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    ) -> ProgramResult {
+        let mut self_clone = self.clone();
+        self_clone.verify_address_book_entries(entries)
+    }
+
+    pub fn verify_address_book_entries(
+        &mut self,
+        entries: &Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    ) -> ProgramResult {
+        if !self.address_book.contains(entries) {
+            msg!("Failed to verify address book entries: address book does not contain one of the given entries");
+            return Err(WalletError::InvalidSlot.into());
+        }
+        for (id, _) in entries {
+            if !self.pending_address_book_entries.is_enabled(id) {
+                msg!("Failed to verify address book entries: entry is not pending verification");
+                return Err(WalletError::InvalidSlot.into());
+            }
+            self.pending_address_book_entries.disable(id);
+            self.pending_address_book_entries_added_at.clear(*id);
+        }
+        Ok(())
+    }
+
+    /// Returns an error unless the destination verification delay has elapsed
+    /// since each of the entries was added.
+    pub fn validate_destination_verification_delay(
+        &self,
+        entries: &Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+        now: i64,
+    ) -> ProgramResult {
+        let delay = self.destination_verification_delay.as_secs() as i64;
+        for (id, _) in entries {
+            let verifiable_at = self
+                .pending_address_book_entries_added_at
+                .get(*id)
+                .saturating_add(delay);
+            if now < verifiable_at {
+                msg!(
+                    "Address book entry in slot {} can't be verified before {}",
+                    id.value,
+                    verifiable_at
+                );
+                return Err(WalletError::DestinationVerificationDelayNotElapsed.into());
+            }
+        }
+        Ok(())
+    }
+
     pub fn validate_config_policy_update(
         &self,
         update: &WalletConfigPolicyUpdate,
+        limits: &PolicyLimits,
     ) -> ProgramResult {
         let mut self_clone = self.clone();
-        self_clone.update_config_policy(update)
+        self_clone.update_config_policy(update, limits)
     }
 
     pub fn lock_config_policy_updates(&mut self) -> ProgramResult {
@@ -283,23 +830,169 @@ impl Wallet {
         self.config_policy_update_locked = false;
     }
 
-    pub fn update_config_policy(&mut self, update: &WalletConfigPolicyUpdate) -> ProgramResult {
+    /// Counts a finalized config op, both towards the current activity period
+    /// and the wallet's config version.
+    pub fn record_config_change(&mut self) {
+        self.activity.record_config_change();
+        self.config_version = self.config_version.wrapping_add(1);
+    }
+
+    /// Counts a finalized op that changed the given balance account towards
+    /// the account's op sequence.
+    pub fn record_balance_account_change(
+        &mut self,
+        account_guid_hash: &BalanceAccountGuidHash,
+    ) -> ProgramResult {
+        let (slot_id, mut balance_account) =
+            self.get_balance_account_with_slot_id(account_guid_hash)?;
+        balance_account.op_sequence = balance_account.op_sequence.wrapping_add(1);
+        self.balance_accounts.replace(slot_id, balance_account);
+        Ok(())
+    }
+
+    /// Applies a transfer of the given amount out of the balance account,
+    /// finalized at `now`, to the account's large transfer cooldown.
+    pub fn record_large_transfer(
+        &mut self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        amount: u64,
+        now: i64,
+    ) -> ProgramResult {
+        let (slot_id, mut balance_account) =
+            self.get_balance_account_with_slot_id(account_guid_hash)?;
+        balance_account.record_large_transfer(amount, now)?;
+        self.balance_accounts.replace(slot_id, balance_account);
+        Ok(())
+    }
+
+    pub fn update_config_policy(
+        &mut self,
+        update: &WalletConfigPolicyUpdate,
+        limits: &PolicyLimits,
+    ) -> ProgramResult {
         if let Some(approval_timeout_for_config) = update.approval_timeout_for_config {
-            Wallet::validate_approval_timeout(&approval_timeout_for_config)?;
+            limits.validate_approval_timeout(&approval_timeout_for_config)?;
             self.approval_timeout_for_config = approval_timeout_for_config;
         }
         if let Some(approvals_required_for_config) = update.approvals_required_for_config {
             self.approvals_required_for_config = approvals_required_for_config;
         }
+        if let Some(destination_verification_delay) = update.destination_verification_delay {
+            if destination_verification_delay > limits.max_approval_timeout {
+                msg!(
+                    "Destination verification delay can't be more than {} seconds",
+                    limits.max_approval_timeout.as_secs(),
+                );
+                return Err(WalletError::InvalidApprovalTimeout.into());
+            }
+            self.destination_verification_delay = destination_verification_delay;
+        }
+        if let Some(denial_reason_threshold) = update.denial_reason_threshold {
+            self.denial_reason_threshold = denial_reason_threshold;
+        }
+        if let Some(approvals_required_for_assistant_config) =
+            update.approvals_required_for_assistant_config
+        {
+            self.approvals_required_for_assistant_config = approvals_required_for_assistant_config;
+        }
+
+        if let Some(approvals_required_for_address_book) =
+            update.approvals_required_for_address_book
+        {
+            self.approvals_required_for_address_book = approvals_required_for_address_book;
+        }
+
+        if let Some(approvals_required_for_destructive_config) =
+            update.approvals_required_for_destructive_config
+        {
+            self.approvals_required_for_destructive_config =
+                approvals_required_for_destructive_config;
+        }
 
-        self.disable_config_approvers(&update.remove_config_approvers)?;
-        self.enable_config_approvers(&update.add_config_approvers)?;
+        if let Some(config_approvers) = &update.replace_config_approvers {
+            if !update.add_config_approvers.is_empty() || !update.remove_config_approvers.is_empty()
+            {
+                msg!("Replacing config approvers can't be combined with adding or removing them");
+                return Err(WalletError::ConflictingApproverUpdates.into());
+            }
+            if update.approvals_required_for_config.is_none() {
+                msg!("Replacing config approvers requires approvals required for config");
+                return Err(WalletError::InvalidApproverCount.into());
+            }
+            self.config_approvers = Approvers::zero();
+            self.enable_config_approvers(config_approvers)?;
+        } else {
+            self.disable_config_approvers(&update.remove_config_approvers)?;
+            self.enable_config_approvers(&update.add_config_approvers)?;
+        }
 
+        self.address_book_approvers = self.updated_approvers(
+            self.address_book_approvers,
+            &update.add_address_book_approvers,
+            &update.remove_address_book_approvers,
+        )?;
+        self.default_transfer_approvers = self.updated_approvers(
+            self.default_transfer_approvers,
+            &update.add_default_transfer_approvers,
+            &update.remove_default_transfer_approvers,
+        )?;
+        self.veto_approvers = self.updated_approvers(
+            self.veto_approvers,
+            &update.add_veto_approvers,
+            &update.remove_veto_approvers,
+        )?;
+        self.destructive_config_approvers = self.updated_approvers(
+            self.destructive_config_approvers,
+            &update.add_destructive_config_approvers,
+            &update.remove_destructive_config_approvers,
+        )?;
+        if self.veto_approvers.count_enabled() > Wallet::MAX_VETO_APPROVERS {
+            msg!(
+                "Wallet can't have more than {} veto approvers",
+                Wallet::MAX_VETO_APPROVERS
+            );
+            return Err(WalletError::InvalidApproverCount.into());
+        }
+
+        self.validate_quorums()
+    }
+
+    /// Checks that each of the wallet's approval quorums can be met by the
+    /// approvers it is drawn from.
+    fn validate_quorums(&self) -> ProgramResult {
         if self.approvals_required_for_config == 0 {
             msg!("Approvals required for config can't be 0");
             return Err(WalletError::InvalidApproverCount.into());
         }
 
+        let address_book_approvers_count = self.address_book_approvers.count_enabled();
+        if address_book_approvers_count > 0
+            && (self.approvals_required_for_address_book == 0
+                || usize::from(self.approvals_required_for_address_book)
+                    > address_book_approvers_count)
+        {
+            msg!(
+                "Approvals required for address book {} must be between 1 and the address book approvers count {}",
+                self.approvals_required_for_address_book,
+                address_book_approvers_count
+            );
+            return Err(WalletError::InvalidApproverCount.into());
+        }
+
+        let destructive_config_approvers_count = self.destructive_config_approvers.count_enabled();
+        if destructive_config_approvers_count > 0
+            && (self.approvals_required_for_destructive_config == 0
+                || usize::from(self.approvals_required_for_destructive_config)
+                    > destructive_config_approvers_count)
+        {
+            msg!(
+                "Approvals required for destructive config {} must be between 1 and the destructive config approvers count {}",
+                self.approvals_required_for_destructive_config,
+                destructive_config_approvers_count
+            );
+            return Err(WalletError::InvalidApproverCount.into());
+        }
+
         let approvers_count = self.config_approvers.count_enabled();
         if usize::from(self.approvals_required_for_config) > approvers_count {
             msg!(
@@ -310,6 +1003,63 @@ impl Wallet {
             return Err(WalletError::InvalidApproverCount.into());
         }
 
+        if self.approvals_required_for_assistant_config > 0 {
+            if self.approvals_required_for_assistant_config < self.approvals_required_for_config {
+                msg!(
+                    "Approvals required for assistant-initiated config {} can't be less than approvals required for config {}",
+                    self.approvals_required_for_assistant_config,
+                    self.approvals_required_for_config
+                );
+                return Err(WalletError::InvalidApproverCount.into());
+            }
+            if usize::from(self.approvals_required_for_assistant_config) > approvers_count {
+                msg!(
+                    "Approvals required for assistant-initiated config {} can't exceed configured approvers count {}",
+                    self.approvals_required_for_assistant_config,
+                    approvers_count
+                );
+                return Err(WalletError::InvalidApproverCount.into());
+            }
+        }
+
+        let default_transfer_approvers_count = self.default_transfer_approvers().count_enabled();
+        for (_, balance_account) in &self.balance_accounts.filled_slots() {
+            if balance_account.uses_config_approvers()
+                && usize::from(balance_account.approvals_required_for_transfer)
+                    > default_transfer_approvers_count
+            {
+                msg!(
+                    "Approvals required for transfer {} can't exceed default transfer approvers count {}",
+                    balance_account.approvals_required_for_transfer,
+                    default_transfer_approvers_count
+                );
+                return Err(WalletError::InvalidApproverCount.into());
+            }
+        }
+
+        self.validate_organization_quorum()
+    }
+
+    /// Checks that the config approvers span enough organizations to approve
+    /// config ops, so the wallet can't be locked out of its own config.
+    fn validate_organization_quorum(&self) -> ProgramResult {
+        let organizations_required = self
+            .governed_parameters
+            .min_distinct_approver_organizations();
+        let organizations_count = self
+            .config_approvers
+            .iter_enabled()
+            .filter_map(|slot_id| self.signer_organizations.get(slot_id))
+            .unique()
+            .count();
+        if usize::from(organizations_required) > organizations_count {
+            msg!(
+                "Approvals required from {} organizations, but the config approvers only belong to {}",
+                organizations_required,
+                organizations_count
+            );
+            return Err(WalletError::InvalidApproverCount.into());
+        }
         Ok(())
     }
 
@@ -320,23 +1070,617 @@ impl Wallet {
 
     pub fn update_dapp_book(&mut self, update: &DAppBookUpdate) -> ProgramResult {
         self.add_dapp_book_entries(&update.add_dapps)?;
+        self.remove_dapp_value_limits(&update.remove_value_limits)?;
+        self.remove_dapp_inner_programs(&update.remove_inner_programs)?;
+        self.remove_dapp_approval_policies(&update.remove_approval_policies)?;
         self.remove_dapp_book_entries(&update.remove_dapps)?;
+        self.add_dapp_value_limits(&update.add_value_limits)?;
+        self.add_dapp_inner_programs(&update.add_inner_programs)?;
+        self.add_dapp_approval_policies(&update.add_approval_policies)?;
 
         Ok(())
     }
 
+    /// The value limit of the given dapp and the dapp book slot it is kept in.
+    /// A dapp that is not in the dapp book is still held to the limit of an
+    /// entry with the same address, so the limit can't be dodged by passing
+    /// the dapp unlisted.
+    pub fn dapp_value_limit(
+        &self,
+        dapp: &DAppBookEntry,
+    ) -> Option<(SlotId<DAppBookEntry>, DAppValueLimit)> {
+        let limit_of = |slot_id: SlotId<DAppBookEntry>| {
+            self.dapp_value_limits[DAppValueLimit::slot_id(slot_id)].map(|limit| (slot_id, limit))
+        };
+        match self.dapp_book.find_id(dapp) {
+            Some(slot_id) => limit_of(slot_id),
+            None => self
+                .dapp_book
+                .filled_slots()
+                .into_iter()
+                .filter(|(_, entry)| entry.address == dapp.address)
+                .find_map(|(slot_id, _)| limit_of(slot_id)),
+        }
+    }
+
+    /// The approval policy of the given dapp's transactions, if it has one.
+    /// Unlike value limits, only the dapp's own entry counts, so that a dapp
+    /// passed unlisted can't take on the policy of another.
+    pub fn dapp_approval_policy(&self, dapp: &DAppBookEntry) -> Option<DAppApprovalPolicy> {
+        self.dapp_book
+            .find_id(dapp)
+            .and_then(|slot_id| self.dapp_approval_policies[DAppApprovalPolicy::slot_id(slot_id)])
+    }
+
+    /// The approvers of the given dapp approval policy's signer slots.
+    pub fn get_dapp_approvers_keys(&self, policy: &DAppApprovalPolicy) -> Vec<Pubkey> {
+        self.get_approvers_keys(&policy.approvers)
+    }
+
+    /// The programs the given dapp's instructions may hand it as accounts, or
+    /// None if it may be handed any. Like value limits, an unlisted dapp is
+    /// held to those of an entry with the same address.
+    pub fn dapp_inner_programs(&self, dapp: &DAppBookEntry) -> Option<DAppInnerPrograms> {
+        let inner_programs_of = |slot_id: SlotId<DAppBookEntry>| {
+            self.dapp_inner_programs[DAppInnerPrograms::slot_id(slot_id)]
+        };
+        match self.dapp_book.find_id(dapp) {
+            Some(slot_id) => inner_programs_of(slot_id),
+            None => self
+                .dapp_book
+                .filled_slots()
+                .into_iter()
+                .filter(|(_, entry)| entry.address == dapp.address)
+                .find_map(|(slot_id, _)| inner_programs_of(slot_id)),
+        }
+    }
+
+    /// Adds `value` moved out of the balance account by a transaction of the
+    /// given dapp to the balance account's usage of the dapp's value limit.
+    /// Returns whether the dapp has a limit, and so whether the wallet changed.
+    pub fn record_dapp_value(
+        &mut self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        dapp: &DAppBookEntry,
+        value: u64,
+        now: i64,
+    ) -> Result<bool, ProgramError> {
+        let (dapp_slot_id, limit) = match self.dapp_value_limit(dapp) {
+            Some(dapp_value_limit) => dapp_value_limit,
+            None => return Ok(false),
+        };
+        let (slot_id, mut balance_account) =
+            self.get_balance_account_with_slot_id(account_guid_hash)?;
+        balance_account
+            .dapp_value_usage
+            .get_mut(dapp_slot_id)
+            .record(&limit, value, now)?;
+        self.balance_accounts.replace(slot_id, balance_account);
+        Ok(true)
+    }
+
+    pub fn validate_approver_delegation_update(
+        &self,
+        update: &ApproverDelegationUpdate,
+        now: i64,
+    ) -> ProgramResult {
+        let mut self_clone = self.clone();
+        self_clone.update_approver_delegations(update, now)
+    }
+
+    pub fn update_approver_delegations(
+        &mut self,
+        update: &ApproverDelegationUpdate,
+        now: i64,
+    ) -> ProgramResult {
+        if !self
+            .approver_delegations
+            .can_be_removed(&update.remove_delegations)
+        {
+            msg!("Failed to remove approver delegations: at least one of the provided delegations is not present in the config");
+            return Err(WalletError::SlotCannotBeRemoved.into());
+        }
+        self.approver_delegations
+            .remove_many(&update.remove_delegations);
+
+        if !self
+            .approver_delegations
+            .can_be_inserted(&update.add_delegations)
+        {
+            msg!("Failed to add approver delegations: at least one slot cannot be inserted");
+            return Err(WalletError::SlotCannotBeInserted.into());
+        }
+        for (_, delegation) in update.add_delegations.iter() {
+            self.validate_approver_delegation(delegation, now)?;
+        }
+        self.approver_delegations
+            .insert_many(&update.add_delegations);
+
+        // a signer can act for at most one approver, and not while they have
+        // delegated their own approvals
+        let active_delegations = self.active_approver_delegations(now);
+        for delegation in active_delegations.iter() {
+            let overlapping = active_delegations
+                .iter()
+                .filter(|other| {
+                    other.approver == delegation.approver || other.delegate == delegation.delegate
+                })
+                .count();
+            if overlapping > 1
+                || active_delegations
+                    .iter()
+                    .any(|other| other.approver == delegation.delegate)
+            {
+                msg!("An approver can have one delegate, who can't be delegating themselves or acting for another approver");
+                return Err(WalletError::InvalidApproverDelegation.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_approver_delegation(
+        &self,
+        delegation: &ApproverDelegation,
+        now: i64,
+    ) -> ProgramResult {
+        if self
+            .signers
+            .find_id(&Signer::new(delegation.approver))
+            .is_none()
+            || self
+                .signers
+                .find_id(&Signer::new(delegation.delegate))
+                .is_none()
+        {
+            msg!("Approver delegations can only be between signers");
+            return Err(WalletError::UnknownSigner.into());
+        }
+        if delegation.approver == delegation.delegate {
+            msg!("An approver can't delegate to themselves");
+            return Err(WalletError::InvalidApproverDelegation.into());
+        }
+        if !delegation.is_active(now)
+            || delegation.expires_at - now
+                > self
+                    .governed_parameters
+                    .max_approver_delegation_period()
+                    .as_secs() as i64
+        {
+            msg!(
+                "Approver delegations must expire within {} seconds",
+                self.governed_parameters
+                    .max_approver_delegation_period()
+                    .as_secs()
+            );
+            return Err(WalletError::InvalidApproverDelegation.into());
+        }
+        Ok(())
+    }
+
+    pub fn active_approver_delegations(&self, now: i64) -> Vec<ApproverDelegation> {
+        self.approver_delegations
+            .filled_slots()
+            .into_iter()
+            .map(|(_, delegation)| delegation)
+            .filter(|delegation| delegation.is_active(now))
+            .collect_vec()
+    }
+
+    /// Records that the signer gave a disposition at `now`, returning whether
+    /// the key is one of the wallet's signers.
+    pub fn record_signer_activity(&mut self, key: &Pubkey, now: i64) -> bool {
+        match self.signers.find_id(&Signer::new(*key)) {
+            Some(slot_id) => {
+                self.signer_activity.record(slot_id, now);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Fails if an init instruction was recently submitted with the key.
+    pub fn validate_idempotency_key(&self, key: &IdempotencyKey) -> ProgramResult {
+        if self.recent_idempotency_keys.contains(key) {
+            msg!("An instruction with this idempotency key was already submitted");
+            return Err(WalletError::DuplicateIdempotencyKey.into());
+        }
+        Ok(())
+    }
+
+    pub fn record_idempotency_key(&mut self, key: IdempotencyKey) {
+        self.recent_idempotency_keys.record(key);
+    }
+
+    /// Starts tracking the activity of signers nothing has been recorded for
+    /// yet, as of `now`, so that a signer that never gives a disposition
+    /// eventually counts as inactive too.
+    pub fn start_tracking_signer_activity(&mut self, now: i64) {
+        for (slot_id, _) in self.signers.filled_slots() {
+            if self.signer_activity.get(slot_id).is_none() {
+                self.signer_activity.record(slot_id, now);
+            }
+        }
+    }
+
+    /// The signers that have been inactive for longer than the wallet's
+    /// signer inactivity period, if one is set.
+    pub fn inactive_signers(&self, now: i64) -> Vec<Pubkey> {
+        let period = match self.governed_parameters.signer_inactivity_period() {
+            Some(period) => period,
+            None => return Vec::new(),
+        };
+        self.signers
+            .filled_slots()
+            .into_iter()
+            .filter(|(slot_id, _)| self.signer_activity.is_inactive(*slot_id, now, period))
+            .map(|(_, signer)| signer.key)
+            .collect_vec()
+    }
+
+    /// Leaves inactive signers out of an op's approvers, unless that would
+    /// leave too few approvers to reach the quorum.
+    pub fn exclude_inactive_approvers(
+        &self,
+        approvers: Vec<Pubkey>,
+        approvals_required: u8,
+        now: i64,
+    ) -> Vec<Pubkey> {
+        let inactive = self.inactive_signers(now);
+        if inactive.is_empty() {
+            return approvers;
+        }
+        let active = approvers
+            .iter()
+            .filter(|approver| !inactive.contains(approver))
+            .cloned()
+            .collect_vec();
+        if active.len() < usize::from(approvals_required) {
+            msg!("Keeping inactive approvers, as the quorum can't be reached without them");
+            return approvers;
+        }
+        for approver in approvers.iter().filter(|a| inactive.contains(a)) {
+            msg!("Leaving out inactive approver {}", approver);
+        }
+        active
+    }
+
+    /// Replaces the keys of directory-backed signer slots with the keys the
+    /// signer directory currently holds in the same slots. A slot the directory
+    /// no longer holds a key for is emptied.
+    pub fn resolve_directory_signers(&mut self, directory: &SignerDirectory) {
+        for slot_id in self.directory_signers.iter_enabled() {
+            match (self.signers[slot_id], directory.signers[slot_id]) {
+                (_, Some(directory_signer)) => self.signers.replace(slot_id, directory_signer),
+                (Some(signer), None) => self.signers.remove(slot_id, signer),
+                (None, None) => {}
+            }
+        }
+    }
+
+    /// The slot of the given signer, if it is backed by the signer directory.
+    pub fn directory_slot(&self, key: &Pubkey) -> Option<SlotId<Signer>> {
+        self.signers
+            .find_id(&Signer::new(*key))
+            .filter(|slot_id| self.directory_signers.is_enabled(slot_id))
+    }
+
+    pub fn validate_signer_directory_update(
+        &self,
+        update: &WalletSignerDirectoryUpdate,
+        directory: Option<&(Pubkey, SignerDirectory)>,
+    ) -> ProgramResult {
+        let mut self_clone = self.clone();
+        self_clone.update_signer_directory(update, directory)
+    }
+
+    pub fn update_signer_directory(
+        &mut self,
+        update: &WalletSignerDirectoryUpdate,
+        directory: Option<&(Pubkey, SignerDirectory)>,
+    ) -> ProgramResult {
+        let mut directory_signers = Approvers::zero();
+        match update.signer_directory {
+            Some(directory_address) => {
+                let directory = match directory {
+                    Some((address, directory)) if *address == directory_address => directory,
+                    _ => {
+                        msg!("The signer directory being linked must be passed along");
+                        return Err(WalletError::SignerDirectoryRequired.into());
+                    }
+                };
+                for (slot_id, signer) in update.directory_signers.iter() {
+                    if slot_id.value >= Wallet::MAX_SIGNERS
+                        || self.signers[*slot_id] != Some(*signer)
+                        || directory.signers[*slot_id] != Some(*signer)
+                    {
+                        msg!("Directory-backed signer slots must hold the same key in the wallet and the directory");
+                        return Err(WalletError::InvalidSignerDirectory.into());
+                    }
+                    directory_signers.enable(slot_id);
+                }
+            }
+            None => {
+                if !update.directory_signers.is_empty() {
+                    msg!("Signer slots can't be directory-backed without a signer directory");
+                    return Err(WalletError::InvalidSignerDirectory.into());
+                }
+            }
+        }
+        self.signer_directory = update.signer_directory;
+        self.directory_signers = directory_signers;
+        Ok(())
+    }
+
+    /// The canary key must not otherwise be known to the wallet, so that using
+    /// the wallet legitimately never involves a signature from it.
+    pub fn validate_canary_key(&self, canary_key: &Option<Pubkey>) -> ProgramResult {
+        if let Some(canary_key) = canary_key {
+            if *canary_key == self.assistant.key
+                || self.signers.find_id(&Signer::new(*canary_key)).is_some()
+            {
+                msg!("The canary key can't be a signer or the assistant of the wallet");
+                return Err(WalletError::InvalidCanaryKey.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets or rotates the canary key. As this takes an approved config op,
+    /// it is also how a frozen wallet is unfrozen.
+    pub fn update_canary_key(&mut self, canary_key: Option<Pubkey>) -> ProgramResult {
+        self.validate_canary_key(&canary_key)?;
+        self.canary_key = canary_key;
+        self.frozen = false;
+        Ok(())
+    }
+
+    /// Logs the metadata hash, if one is set, so that an op's logs tie it to
+    /// the policy document in force when it was started.
+    pub fn log_metadata_hash(&self) {
+        if self.metadata_hash != Hash::default() {
+            msg!("WalletMetadataHash: [{}]", self.metadata_hash);
+        }
+    }
+
+    pub fn validate_assistant_update(&self, assistant: &Pubkey) -> ProgramResult {
+        let mut self_clone = self.clone();
+        self_clone.update_assistant(*assistant)
+    }
+
+    /// Rotates the assistant key. Like any other key known to the wallet, the
+    /// new assistant can't be the canary key.
+    pub fn update_assistant(&mut self, assistant: Pubkey) -> ProgramResult {
+        if self.canary_key == Some(assistant) {
+            msg!("The assistant can't be the canary key of the wallet");
+            return Err(WalletError::InvalidAssistant.into());
+        }
+        self.assistant = Signer::new(assistant);
+        self.validate_assistant_separation()
+    }
+
+    /// Unless the governed parameters allow it, the assistant must not also
+    /// be one of the wallet's signers.
+    fn validate_assistant_separation(&self) -> ProgramResult {
+        if !self.governed_parameters.assistant_may_be_signer()
+            && self.signers.find_id(&self.assistant).is_some()
+        {
+            msg!("The assistant can't also be a signer of the wallet");
+            return Err(WalletError::InvalidAssistant.into());
+        }
+        Ok(())
+    }
+
+    pub fn validate_governed_parameter_update(
+        &self,
+        update: &GovernedParameterUpdate,
+    ) -> ProgramResult {
+        let mut self_clone = self.clone();
+        self_clone.update_governed_parameters(update)
+    }
+
+    pub fn update_governed_parameters(
+        &mut self,
+        update: &GovernedParameterUpdate,
+    ) -> ProgramResult {
+        for (i, (key, value)) in update.values.iter().enumerate() {
+            if update.values[..i].iter().any(|(other, _)| other == key) {
+                msg!("{:?} can only be updated once", key);
+                return Err(WalletError::InvalidGovernedParameter.into());
+            }
+            if let Some(value) = value {
+                key.validate(*value)?;
+            }
+            self.governed_parameters.set(*key, *value);
+        }
+        self.validate_organization_quorum()?;
+        self.validate_assistant_separation()
+    }
+
+    pub fn validate_dapp_templates_update(&self, update: &DAppTemplatesUpdate) -> ProgramResult {
+        let mut self_clone = self.clone();
+        self_clone.update_dapp_templates(update)
+    }
+
+    /// Removes and then adds the given templates. Templates can only be added
+    /// for existing balance accounts.
+    pub fn update_dapp_templates(&mut self, update: &DAppTemplatesUpdate) -> ProgramResult {
+        if !self.dapp_templates.can_be_removed(&update.remove_templates) {
+            msg!("Failed to remove dapp templates: at least one of the provided templates is not present in the config");
+            return Err(WalletError::SlotCannotBeRemoved.into());
+        }
+        self.dapp_templates.remove_many(&update.remove_templates);
+
+        for (slot_id, template) in update.add_templates.iter() {
+            self.get_balance_account(&template.account_guid_hash)?;
+            // checked one at a time, so the same slot can't be added twice
+            if !self
+                .dapp_templates
+                .can_be_inserted(&vec![(*slot_id, *template)])
+            {
+                msg!(
+                    "Failed to add dapp templates: slot {} is already taken",
+                    slot_id.value
+                );
+                return Err(WalletError::SlotCannotBeInserted.into());
+            }
+            self.dapp_templates.insert(*slot_id, *template);
+        }
+        Ok(())
+    }
+
+    pub fn get_dapp_template(
+        &self,
+        slot_id: SlotId<DAppTemplate>,
+    ) -> Result<DAppTemplate, ProgramError> {
+        if slot_id.value >= Wallet::MAX_DAPP_TEMPLATES {
+            return Err(WalletError::InvalidSlot.into());
+        }
+        self.dapp_templates[slot_id].ok_or_else(|| {
+            msg!("No dapp template in slot {}", slot_id.value);
+            WalletError::InvalidSlot.into()
+        })
+    }
+
+    pub fn validate_viewers_update(&self, update: &ViewersUpdate) -> ProgramResult {
+        let mut self_clone = self.clone();
+        self_clone.update_viewers(update)
+    }
+
+    /// Removes and then adds the given viewers, logging each one so that
+    /// access grants and revocations can be followed in the program logs.
+    pub fn update_viewers(&mut self, update: &ViewersUpdate) -> ProgramResult {
+        if !self.viewers.can_be_removed(&update.remove_viewers) {
+            msg!("Failed to remove viewers: at least one of the provided viewers is not present in the config");
+            return Err(WalletError::SlotCannotBeRemoved.into());
+        }
+        self.viewers.remove_many(&update.remove_viewers);
+        for (_, viewer) in update.remove_viewers.iter() {
+            msg!("Viewer removed: {}", viewer.key);
+        }
+
+        for (slot_id, viewer) in update.add_viewers.iter() {
+            // checked one at a time, so the same slot or key can't be added twice
+            if !self.viewers.can_be_inserted(&vec![(*slot_id, *viewer)])
+                || self
+                    .viewers
+                    .find_id(viewer)
+                    .map_or(false, |existing| existing != *slot_id)
+            {
+                msg!("Failed to add viewers: {} can't be added", viewer.key);
+                return Err(WalletError::SlotCannotBeInserted.into());
+            }
+            self.viewers.insert(*slot_id, *viewer);
+            msg!("Viewer added: {}", viewer.key);
+        }
+        Ok(())
+    }
+
+    pub fn is_viewer(&self, key: &Pubkey) -> bool {
+        self.viewers.find_id(&Viewer::new(*key)).is_some()
+    }
+
+    pub fn validate_signer_organizations_update(
+        &self,
+        update: &SignerOrganizationsUpdate,
+    ) -> ProgramResult {
+        let mut self_clone = self.clone();
+        self_clone.update_signer_organizations(update)
+    }
+
+    pub fn update_signer_organizations(
+        &mut self,
+        update: &SignerOrganizationsUpdate,
+    ) -> ProgramResult {
+        for (i, (slot_id, organization)) in update.organizations.iter().enumerate() {
+            if slot_id.value >= Wallet::MAX_SIGNERS || self.signers[*slot_id].is_none() {
+                msg!("Only signer slots holding a signer can be tagged");
+                return Err(WalletError::InvalidSlot.into());
+            }
+            if update.organizations[..i]
+                .iter()
+                .any(|(other, _)| other == slot_id)
+            {
+                msg!("Signer slot {} can only be tagged once", slot_id.value);
+                return Err(WalletError::InvalidSlot.into());
+            }
+            self.signer_organizations.set(*slot_id, *organization);
+        }
+        self.validate_organization_quorum()
+    }
+
+    /// The organization the given signer is tagged with, if any.
+    pub fn signer_organization(&self, key: &Pubkey) -> Option<u8> {
+        self.signers
+            .find_id(&Signer::new(*key))
+            .and_then(|slot_id| self.signer_organizations.get(slot_id))
+    }
+
+    /// Reads the canary key and frozen flag of an initialized wallet account
+    /// without unpacking the rest of it, since this is checked on every
+    /// instruction. Returns `None` if the data isn't an initialized wallet.
+    pub fn unpack_canary(data: &[u8]) -> Option<(Option<Pubkey>, bool)> {
+        if data.len() != Wallet::LEN || data[0] != AccountType::Wallet as u8 {
+            return None;
+        }
+        let src = array_ref![data, Wallet::LEN - Wallet::CANARY_LEN, Wallet::CANARY_LEN];
+        let (has_canary_key, canary_key, frozen) = array_refs![src, 1, PUBKEY_BYTES, 1];
+        Some((
+            if has_canary_key[0] == 1 {
+                Some(Pubkey::new_from_array(*canary_key))
+            } else {
+                None
+            },
+            frozen[0] == 1,
+        ))
+    }
+
+    pub fn freeze_in_place(data: &mut [u8]) {
+        data[Wallet::LEN - 1] = 1;
+    }
+
+    pub fn denial_reason_required(&self, value: Option<u64>) -> bool {
+        match value {
+            Some(value) => {
+                self.denial_reason_threshold > 0 && value >= self.denial_reason_threshold
+            }
+            None => false,
+        }
+    }
+
     pub fn dapp_allowed(&self, dapp: DAppBookEntry) -> bool {
         self.dapp_book.find_id(&dapp).is_some()
     }
 
+    /// The instruction prefixes the given dapp's instructions are restricted to.
+    /// A dapp that is not in the dapp book is still held to the prefixes of a
+    /// restricted entry with the same address, so the restriction can't be
+    /// dropped by passing the dapp unlisted.
+    pub fn dapp_instruction_prefixes(&self, dapp: &DAppBookEntry) -> DAppInstructionPrefixes {
+        if self.dapp_allowed(*dapp) {
+            return dapp.instruction_prefixes;
+        }
+        self.dapp_book
+            .filled_slots()
+            .iter()
+            .map(|(_, entry)| entry)
+            .find(|entry| {
+                entry.address == dapp.address && entry.instruction_prefixes.is_restricted()
+            })
+            .map_or(dapp.instruction_prefixes, |entry| {
+                entry.instruction_prefixes
+            })
+    }
+
     pub fn validate_balance_account_creation(
         &self,
         account_guid_hash: &BalanceAccountGuidHash,
         creation_params: &BalanceAccountCreation,
         program_id: &Pubkey,
+        limits: &PolicyLimits,
     ) -> ProgramResult {
         let mut self_clone = self.clone();
-        self_clone.create_balance_account(account_guid_hash, creation_params, program_id)
+        self_clone.create_balance_account(account_guid_hash, creation_params, program_id, limits)
     }
 
     pub fn create_balance_account(
@@ -344,9 +1688,17 @@ impl Wallet {
         account_guid_hash: &BalanceAccountGuidHash,
         creation_params: &BalanceAccountCreation,
         program_id: &Pubkey,
+        limits: &PolicyLimits,
     ) -> ProgramResult {
         Wallet::validate_approvals_required(creation_params.approvals_required_for_transfer)?;
-        Wallet::validate_approval_timeout(&creation_params.approval_timeout_for_transfer)?;
+        limits.validate_approval_timeout(&creation_params.approval_timeout_for_transfer)?;
+        if self.balance_accounts.filled_slots().len() >= limits.max_balance_accounts {
+            msg!(
+                "Wallet can't have more than {} balance accounts",
+                limits.max_balance_accounts
+            );
+            return Err(WalletError::SlotCannotBeInserted.into());
+        }
         if creation_params.approvals_required_for_transfer
             > creation_params.transfer_approvers.len() as u8
         {
@@ -367,16 +1719,29 @@ impl Wallet {
             allowed_destinations: AllowedDestinations::zero(),
             whitelist_enabled: creation_params.whitelist_enabled,
             dapps_enabled: creation_params.dapps_enabled,
+            sol_transfers_enabled: BooleanSetting::On,
+            spl_transfers_enabled: BooleanSetting::On,
+            use_config_approvers: BooleanSetting::Off,
             policy_update_locked: false,
+            allowed_token_mints: AllowedTokenMints::zero(),
+            token_mint_allowlist_enabled: BooleanSetting::Off,
+            outstanding_transfer_value: 0,
+            max_outstanding_transfer_value: 0,
+            max_finalize_fee_reimbursement: 0,
+            policy_rules: PolicyRules::new(),
+            dapp_value_usage: DAppValueUsages::new(),
+            op_sequence: 0,
+            large_transfer_cooldown: None,
+            last_large_transfer_at: 0,
+            bump_seed: None,
         };
+        let (source_account_pda, bump_seed) = balance_account.find_address(program_id)?;
+        balance_account.bump_seed = Some(bump_seed);
         self.enable_transfer_approvers(&mut balance_account, &creation_params.transfer_approvers)?;
 
         self.balance_accounts
             .insert(creation_params.slot_id, balance_account);
 
-        let (source_account_pda, _) =
-            Pubkey::find_program_address(&[&account_guid_hash.to_bytes()], program_id);
-
         self.add_address_book_entries(&vec![(
             creation_params.address_book_slot_id,
             AddressBookEntry {
@@ -391,9 +1756,10 @@ impl Wallet {
         &self,
         account_guid_hash: &BalanceAccountGuidHash,
         update: &BalanceAccountPolicyUpdate,
+        limits: &PolicyLimits,
     ) -> ProgramResult {
         let mut self_clone = self.clone();
-        self_clone.update_balance_account_policy(account_guid_hash, update)
+        self_clone.update_balance_account_policy(account_guid_hash, update, limits)
     }
 
     pub fn validate_whitelist_enabled_update(
@@ -437,6 +1803,30 @@ impl Wallet {
         Ok(())
     }
 
+    pub fn update_sol_transfers_enabled(
+        &mut self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        enabled: BooleanSetting,
+    ) -> ProgramResult {
+        let (slot_id, mut balance_account) =
+            self.get_balance_account_with_slot_id(account_guid_hash)?;
+        balance_account.sol_transfers_enabled = enabled;
+        self.balance_accounts.replace(slot_id, balance_account);
+        Ok(())
+    }
+
+    pub fn update_spl_transfers_enabled(
+        &mut self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        enabled: BooleanSetting,
+    ) -> ProgramResult {
+        let (slot_id, mut balance_account) =
+            self.get_balance_account_with_slot_id(account_guid_hash)?;
+        balance_account.spl_transfers_enabled = enabled;
+        self.balance_accounts.replace(slot_id, balance_account);
+        Ok(())
+    }
+
     pub fn update_balance_account_name_hash(
         &mut self,
         account_guid_hash: &BalanceAccountGuidHash,
@@ -476,26 +1866,96 @@ impl Wallet {
         Ok(())
     }
 
+    /// Adds a newly initiated transfer to the balance account's outstanding
+    /// transfer value, failing if that would exceed the account's cap.
+    pub fn reserve_outstanding_transfer_value(
+        &mut self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        amount: u64,
+    ) -> ProgramResult {
+        let (slot_id, mut balance_account) =
+            self.get_balance_account_with_slot_id(account_guid_hash)?;
+
+        balance_account.outstanding_transfer_value =
+            balance_account.outstanding_transfer_value_after(amount)?;
+        self.balance_accounts.replace(slot_id, balance_account);
+        Ok(())
+    }
+
+    /// Removes a finalized transfer from the balance account's outstanding
+    /// transfer value, whatever its disposition.
+    pub fn release_outstanding_transfer_value(
+        &mut self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        amount: u64,
+    ) -> ProgramResult {
+        let (slot_id, mut balance_account) =
+            self.get_balance_account_with_slot_id(account_guid_hash)?;
+        // transfers initiated before the value was tracked were never reserved
+        balance_account.outstanding_transfer_value = balance_account
+            .outstanding_transfer_value
+            .saturating_sub(amount);
+        self.balance_accounts.replace(slot_id, balance_account);
+        Ok(())
+    }
+
     pub fn update_balance_account_policy(
         &mut self,
         account_guid_hash: &BalanceAccountGuidHash,
         update: &BalanceAccountPolicyUpdate,
+        limits: &PolicyLimits,
     ) -> ProgramResult {
         let (slot_id, mut balance_account) =
             self.get_balance_account_with_slot_id(account_guid_hash)?;
 
-        self.disable_transfer_approvers(&mut balance_account, &update.remove_transfer_approvers)?;
-        self.enable_transfer_approvers(&mut balance_account, &update.add_transfer_approvers)?;
+        if let Some(ref remove_transfer_approvers) = update.remove_transfer_approvers {
+            self.disable_transfer_approvers(&mut balance_account, remove_transfer_approvers)?;
+        }
+        if let Some(ref add_transfer_approvers) = update.add_transfer_approvers {
+            self.enable_transfer_approvers(&mut balance_account, add_transfer_approvers)?;
+        }
 
         if let Some(approval_timeout_for_transfer) = update.approval_timeout_for_transfer {
-            Wallet::validate_approval_timeout(&approval_timeout_for_transfer)?;
+            limits.validate_approval_timeout(&approval_timeout_for_transfer)?;
             balance_account.approval_timeout_for_transfer = approval_timeout_for_transfer;
         }
         if let Some(approvals_required_for_transfer) = update.approvals_required_for_transfer {
             balance_account.approvals_required_for_transfer = approvals_required_for_transfer;
         }
+        if let Some(use_config_approvers) = update.use_config_approvers {
+            balance_account.use_config_approvers = use_config_approvers;
+        }
+        if let Some(ref remove_allowed_token_mints) = update.remove_allowed_token_mints {
+            self.disable_token_mints(&mut balance_account, remove_allowed_token_mints)?;
+        }
+        if let Some(ref add_allowed_token_mints) = update.add_allowed_token_mints {
+            self.enable_token_mints(&mut balance_account, add_allowed_token_mints)?;
+        }
+        if let Some(token_mint_allowlist_enabled) = update.token_mint_allowlist_enabled {
+            balance_account.token_mint_allowlist_enabled = token_mint_allowlist_enabled;
+        }
+        if let Some(max_outstanding_transfer_value) = update.max_outstanding_transfer_value {
+            balance_account.max_outstanding_transfer_value = max_outstanding_transfer_value;
+        }
+        if let Some(max_finalize_fee_reimbursement) = update.max_finalize_fee_reimbursement {
+            balance_account.max_finalize_fee_reimbursement = max_finalize_fee_reimbursement;
+        }
+        if let Some(ref policy_rules) = update.policy_rules {
+            balance_account.policy_rules = PolicyRules::from_vec(policy_rules)?;
+        }
+        if let Some(large_transfer_cooldown) = update.large_transfer_cooldown {
+            balance_account.large_transfer_cooldown =
+                if large_transfer_cooldown.period.as_secs() == 0 {
+                    None
+                } else {
+                    Some(large_transfer_cooldown)
+                };
+        }
 
-        let approvers_count_after_update = balance_account.transfer_approvers.count_enabled();
+        let approvers_count_after_update = self.get_transfer_approvers_count(&balance_account);
+        balance_account
+            .policy_rules
+            .validate(approvers_count_after_update)?;
         if usize::from(balance_account.approvals_required_for_transfer)
             > approvers_count_after_update
         {
@@ -512,22 +1972,144 @@ impl Wallet {
             return Err(WalletError::InvalidApproverCount.into());
         }
 
-        if balance_account.transfer_approvers.count_enabled() == 0 {
+        if approvers_count_after_update == 0 {
             msg!("At least one transfer approver has to be configured");
             return Err(WalletError::NoApproversEnabled.into());
         }
 
         self.balance_accounts.replace(slot_id, balance_account);
+        self.remove_unused_token_mints();
+        Ok(())
+    }
+
+    fn enable_token_mints(
+        &mut self,
+        balance_account: &mut BalanceAccount,
+        token_mints: &Vec<(SlotId<TokenMint>, TokenMint)>,
+    ) -> ProgramResult {
+        if !self.token_mint_book.can_be_inserted(token_mints) {
+            msg!("Failed to allow token mints: one of the given slots is already taken");
+            return Err(WalletError::SlotCannotBeInserted.into());
+        }
+        self.token_mint_book.insert_many(token_mints);
+        balance_account
+            .allowed_token_mints
+            .enable_many(&token_mints.slot_ids());
+        Ok(())
+    }
+
+    fn disable_token_mints(
+        &mut self,
+        balance_account: &mut BalanceAccount,
+        token_mints: &Vec<(SlotId<TokenMint>, TokenMint)>,
+    ) -> ProgramResult {
+        if !self.token_mint_book.can_be_removed(token_mints) {
+            msg!("Failed to disallow token mints: unexpected slot value");
+            return Err(WalletError::InvalidSlot.into());
+        }
+        for (slot_id, _) in token_mints {
+            balance_account.allowed_token_mints.disable(slot_id);
+        }
         Ok(())
     }
 
-    fn add_signers(&mut self, signers_to_add: &Vec<(SlotId<Signer>, Signer)>) -> ProgramResult {
+    /// Frees the token mint slots no balance account allows anymore.
+    fn remove_unused_token_mints(&mut self) {
+        for (slot_id, token_mint) in self.token_mint_book.filled_slots() {
+            if !self
+                .balance_accounts
+                .filled_slots()
+                .iter()
+                .any(|(_, balance_account)| {
+                    balance_account.allowed_token_mints.is_enabled(&slot_id)
+                })
+            {
+                self.token_mint_book.remove(slot_id, token_mint);
+            }
+        }
+    }
+
+    /// Clears approver, whitelist, token mint and pending verification flags
+    /// that point at empty signer, address book or token mint slots, logging
+    /// every flag cleared. Returns the number of flags cleared.
+    pub fn repair_dangling_references(&mut self) -> usize {
+        let mut repaired =
+            clear_dangling_flags(&mut self.config_approvers, &self.signers, "config approver");
+        repaired += clear_dangling_flags(
+            &mut self.address_book_approvers,
+            &self.signers,
+            "address book approver",
+        );
+        repaired += clear_dangling_flags(
+            &mut self.default_transfer_approvers,
+            &self.signers,
+            "default transfer approver",
+        );
+        repaired += clear_dangling_flags(&mut self.veto_approvers, &self.signers, "veto approver");
+        repaired += clear_dangling_flags(
+            &mut self.destructive_config_approvers,
+            &self.signers,
+            "destructive config approver",
+        );
+        repaired += clear_dangling_flags(
+            &mut self.pending_address_book_entries,
+            &self.address_book,
+            "pending address book entry",
+        );
+        for (slot_id, mut balance_account) in self.balance_accounts.filled_slots() {
+            repaired += clear_dangling_flags(
+                &mut balance_account.transfer_approvers,
+                &self.signers,
+                &format!("balance account {} transfer approver", slot_id.value),
+            );
+            repaired += clear_dangling_flags(
+                &mut balance_account.allowed_destinations,
+                &self.address_book,
+                &format!("balance account {} allowed destination", slot_id.value),
+            );
+            repaired += clear_dangling_flags(
+                &mut balance_account.allowed_token_mints,
+                &self.token_mint_book,
+                &format!("balance account {} allowed token mint", slot_id.value),
+            );
+            self.balance_accounts.replace(slot_id, balance_account);
+        }
+        for (slot_id, delegation) in self.approver_delegations.filled_slots() {
+            if self
+                .signers
+                .find_id(&Signer::new(delegation.approver))
+                .is_none()
+                || self
+                    .signers
+                    .find_id(&Signer::new(delegation.delegate))
+                    .is_none()
+            {
+                msg!("Dangling approver delegation in slot {}", slot_id.value);
+                self.approver_delegations.remove(slot_id, delegation);
+                repaired += 1;
+            }
+        }
+        repaired
+    }
+
+    fn add_signers(
+        &mut self,
+        signers_to_add: &Vec<(SlotId<Signer>, Signer)>,
+        limits: &PolicyLimits,
+    ) -> ProgramResult {
         if !self.signers.can_be_inserted(signers_to_add) {
             msg!("Failed to add signers: at least one slot cannot be inserted");
             return Err(WalletError::SlotCannotBeInserted.into());
         }
         self.signers.insert_many(signers_to_add);
-        Ok(())
+        if self.signers.filled_slots().len() > limits.max_signers {
+            msg!(
+                "Failed to add signers: wallet can't have more than {} signers",
+                limits.max_signers
+            );
+            return Err(WalletError::SlotCannotBeInserted.into());
+        }
+        self.validate_assistant_separation()
     }
 
     fn remove_signers(
@@ -540,8 +2122,13 @@ impl Wallet {
         }
         let slot_ids = signers_to_remove.slot_ids();
 
-        if self.config_approvers.any_enabled(&slot_ids) {
-            msg!("Failed to remove signers: not allowed to remove a config approving signer");
+        if self.config_approvers.any_enabled(&slot_ids)
+            || self.address_book_approvers.any_enabled(&slot_ids)
+            || self.default_transfer_approvers.any_enabled(&slot_ids)
+            || self.veto_approvers.any_enabled(&slot_ids)
+            || self.destructive_config_approvers.any_enabled(&slot_ids)
+        {
+            msg!("Failed to remove signers: not allowed to remove a config, address book, default transfer, veto or destructive config approving signer");
             return Err(WalletError::SignerIsConfigApprover.into());
         };
         for (_, balance_account) in &self.balance_accounts.filled_slots() {
@@ -550,10 +2137,60 @@ impl Wallet {
                 return Err(WalletError::SignerIsTransferApprover.into());
             }
         }
+        for (_, policy) in &self.dapp_approval_policies.filled_slots() {
+            if policy.approvers.any_enabled(&slot_ids) {
+                msg!("Failed to remove signers: not allowed to remove a dapp approving signer");
+                return Err(WalletError::SignerIsTransferApprover.into());
+            }
+        }
         self.signers.remove_many(signers_to_remove);
+        for slot_id in slot_ids.iter() {
+            self.directory_signers.disable(slot_id);
+            self.signer_organizations.set(**slot_id, None);
+            self.signer_activity.clear(**slot_id);
+        }
+        for (slot_id, delegation) in self.approver_delegations.filled_slots() {
+            if signers_to_remove.iter().any(|(_, signer)| {
+                signer.key == delegation.approver || signer.key == delegation.delegate
+            }) {
+                self.approver_delegations.remove(slot_id, delegation);
+            }
+        }
         Ok(())
     }
 
+    /// The entries to add that the address book doesn't already hold, so
+    /// that an import of the whole address book can repeat the entries that
+    /// are already there, without setting them pending again. Fails if the
+    /// entries put different values in the same slot.
+    fn new_address_book_entries(
+        &self,
+        entries_to_add: &Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    ) -> Result<Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>, ProgramError> {
+        let mut new_entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)> = Vec::new();
+        let mut skipped = 0;
+        for (slot_id, entry) in entries_to_add.iter() {
+            let repeated = new_entries.iter().find(|(id, _)| id == slot_id);
+            if let Some((_, other_entry)) = repeated {
+                if other_entry != entry {
+                    msg!("Failed to add address book entries: a slot is given different entries");
+                    return Err(WalletError::SlotCannotBeInserted.into());
+                }
+                skipped += 1;
+            } else if slot_id.value < Wallet::MAX_ADDRESS_BOOK_ENTRIES
+                && self.address_book[*slot_id] == Some(*entry)
+            {
+                skipped += 1;
+            } else {
+                new_entries.push((*slot_id, *entry));
+            }
+        }
+        if skipped > 0 {
+            msg!("Skipped {} address book entries already present", skipped);
+        }
+        Ok(new_entries)
+    }
+
     fn add_address_book_entries(
         &mut self,
         entries_to_add: &Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
@@ -582,6 +2219,93 @@ impl Wallet {
             }
         }
         self.address_book.remove_many(entries_to_remove);
+        for id in slot_ids {
+            self.pending_address_book_entries.disable(id);
+            self.pending_address_book_entries_added_at.clear(*id);
+        }
+        // token mint bindings and namespaces go with the entries they bind
+        let entries = self.address_book.filled_slots();
+        let entry_ids = entries.slot_ids();
+        for (binding_id, binding) in self.destination_token_mints.filled_slots() {
+            if !entry_ids.contains(&&binding.address_book_entry) {
+                self.destination_token_mints.remove(binding_id, binding);
+            }
+        }
+        for (tag_id, tag) in self.destination_namespaces.filled_slots() {
+            if !entry_ids.contains(&&tag.address_book_entry) {
+                self.destination_namespaces.remove(tag_id, tag);
+            }
+        }
+        Ok(())
+    }
+
+    fn update_destination_token_mints(
+        &mut self,
+        bindings_to_add: &Vec<(SlotId<DestinationTokenMint>, DestinationTokenMint)>,
+        bindings_to_remove: &Vec<(SlotId<DestinationTokenMint>, DestinationTokenMint)>,
+    ) -> ProgramResult {
+        if !self
+            .destination_token_mints
+            .can_be_removed(bindings_to_remove)
+        {
+            msg!("Failed to unbind token mints: at least one of the provided bindings is not present in the config");
+            return Err(WalletError::SlotCannotBeRemoved.into());
+        }
+        self.destination_token_mints.remove_many(bindings_to_remove);
+        if !self
+            .destination_token_mints
+            .can_be_inserted(bindings_to_add)
+        {
+            msg!("Failed to bind token mints: at least one slot cannot be inserted");
+            return Err(WalletError::SlotCannotBeInserted.into());
+        }
+        for (_, binding) in bindings_to_add {
+            if binding.address_book_entry.value >= Wallet::MAX_ADDRESS_BOOK_ENTRIES
+                || self.address_book[binding.address_book_entry].is_none()
+            {
+                msg!("Failed to bind token mints: address book entry slot is empty");
+                return Err(WalletError::InvalidSlot.into());
+            }
+        }
+        self.destination_token_mints.insert_many(bindings_to_add);
+        Ok(())
+    }
+
+    fn update_destination_namespaces(
+        &mut self,
+        tags_to_add: &Vec<(SlotId<DestinationNamespace>, DestinationNamespace)>,
+        tags_to_remove: &Vec<(SlotId<DestinationNamespace>, DestinationNamespace)>,
+    ) -> ProgramResult {
+        if !self.destination_namespaces.can_be_removed(tags_to_remove) {
+            msg!("Failed to untag address book entries: at least one of the provided tags is not present in the config");
+            return Err(WalletError::SlotCannotBeRemoved.into());
+        }
+        self.destination_namespaces.remove_many(tags_to_remove);
+        if !self.destination_namespaces.can_be_inserted(tags_to_add) {
+            msg!("Failed to tag address book entries: at least one slot cannot be inserted");
+            return Err(WalletError::SlotCannotBeInserted.into());
+        }
+        for (_, tag) in tags_to_add {
+            if tag.address_book_entry.value >= Wallet::MAX_ADDRESS_BOOK_ENTRIES
+                || self.address_book[tag.address_book_entry].is_none()
+            {
+                msg!("Failed to tag address book entries: address book entry slot is empty");
+                return Err(WalletError::InvalidSlot.into());
+            }
+        }
+        self.destination_namespaces.insert_many(tags_to_add);
+        // an entry is in a single namespace
+        let tags = self.destination_namespaces.filled_slots();
+        if tags
+            .iter()
+            .map(|(_, tag)| tag.address_book_entry)
+            .unique()
+            .count()
+            != tags.len()
+        {
+            msg!("Failed to tag address book entries: an entry can only have one namespace");
+            return Err(WalletError::InvalidSlot.into());
+        }
         Ok(())
     }
 
@@ -606,9 +2330,158 @@ impl Wallet {
             return Err(WalletError::SlotCannotBeRemoved.into());
         }
         self.dapp_book.remove_many(entries_to_remove);
+        for (slot_id, _) in entries_to_remove.iter() {
+            let limit_slot_id = DAppValueLimit::slot_id(*slot_id);
+            if let Some(limit) = self.dapp_value_limits[limit_slot_id] {
+                self.dapp_value_limits.remove(limit_slot_id, limit);
+            }
+            let inner_programs_slot_id = DAppInnerPrograms::slot_id(*slot_id);
+            if let Some(inner_programs) = self.dapp_inner_programs[inner_programs_slot_id] {
+                self.dapp_inner_programs
+                    .remove(inner_programs_slot_id, inner_programs);
+            }
+            let policy_slot_id = DAppApprovalPolicy::slot_id(*slot_id);
+            if let Some(policy) = self.dapp_approval_policies[policy_slot_id] {
+                self.dapp_approval_policies.remove(policy_slot_id, policy);
+            }
+            self.reset_dapp_value_usage(*slot_id);
+        }
+        Ok(())
+    }
+
+    fn add_dapp_value_limits(
+        &mut self,
+        limits_to_add: &Vec<(SlotId<DAppValueLimit>, DAppValueLimit)>,
+    ) -> ProgramResult {
+        if !self.dapp_value_limits.can_be_inserted(limits_to_add) {
+            msg!("Failed to add dapp value limits: at least one slot cannot be inserted");
+            return Err(WalletError::SlotCannotBeInserted.into());
+        }
+        for (slot_id, limit) in limits_to_add.iter() {
+            if self.dapp_book[SlotId::new(slot_id.value)].is_none() {
+                msg!("Failed to add dapp value limits: dapp book slot is empty");
+                return Err(WalletError::InvalidSlot.into());
+            }
+            if limit.period.as_secs() == 0 {
+                msg!("Dapp value limit period can't be zero");
+                return Err(WalletError::InvalidPolicyRule.into());
+            }
+        }
+        self.dapp_value_limits.insert_many(limits_to_add);
+        for (slot_id, _) in limits_to_add.iter() {
+            self.reset_dapp_value_usage(SlotId::new(slot_id.value));
+        }
+        Ok(())
+    }
+
+    fn remove_dapp_value_limits(
+        &mut self,
+        limits_to_remove: &Vec<(SlotId<DAppValueLimit>, DAppValueLimit)>,
+    ) -> ProgramResult {
+        if !self.dapp_value_limits.can_be_removed(limits_to_remove) {
+            msg!("Failed to remove dapp value limits: at least one of the provided limits is not present in the config");
+            return Err(WalletError::SlotCannotBeRemoved.into());
+        }
+        self.dapp_value_limits.remove_many(limits_to_remove);
+        Ok(())
+    }
+
+    fn add_dapp_inner_programs(
+        &mut self,
+        inner_programs_to_add: &Vec<(SlotId<DAppInnerPrograms>, DAppInnerPrograms)>,
+    ) -> ProgramResult {
+        if !self
+            .dapp_inner_programs
+            .can_be_inserted(inner_programs_to_add)
+        {
+            msg!("Failed to add dapp inner programs: at least one slot cannot be inserted");
+            return Err(WalletError::SlotCannotBeInserted.into());
+        }
+        for (slot_id, _) in inner_programs_to_add.iter() {
+            if self.dapp_book[SlotId::new(slot_id.value)].is_none() {
+                msg!("Failed to add dapp inner programs: dapp book slot is empty");
+                return Err(WalletError::InvalidSlot.into());
+            }
+        }
+        self.dapp_inner_programs.insert_many(inner_programs_to_add);
+        Ok(())
+    }
+
+    fn remove_dapp_inner_programs(
+        &mut self,
+        inner_programs_to_remove: &Vec<(SlotId<DAppInnerPrograms>, DAppInnerPrograms)>,
+    ) -> ProgramResult {
+        if !self
+            .dapp_inner_programs
+            .can_be_removed(inner_programs_to_remove)
+        {
+            msg!("Failed to remove dapp inner programs: at least one of the provided entries is not present in the config");
+            return Err(WalletError::SlotCannotBeRemoved.into());
+        }
+        self.dapp_inner_programs
+            .remove_many(inner_programs_to_remove);
+        Ok(())
+    }
+
+    fn add_dapp_approval_policies(
+        &mut self,
+        policies_to_add: &Vec<(SlotId<DAppApprovalPolicy>, DAppApprovalPolicy)>,
+    ) -> ProgramResult {
+        if !self.dapp_approval_policies.can_be_inserted(policies_to_add) {
+            msg!("Failed to add dapp approval policies: at least one slot cannot be inserted");
+            return Err(WalletError::SlotCannotBeInserted.into());
+        }
+        for (slot_id, policy) in policies_to_add.iter() {
+            if self.dapp_book[SlotId::new(slot_id.value)].is_none() {
+                msg!("Failed to add dapp approval policies: dapp book slot is empty");
+                return Err(WalletError::InvalidSlot.into());
+            }
+            if policy
+                .approvers
+                .iter_enabled()
+                .any(|signer_slot_id| self.signers[signer_slot_id].is_none())
+            {
+                msg!("Failed to add dapp approval policies: approver slot is empty");
+                return Err(WalletError::InvalidSlot.into());
+            }
+            Wallet::validate_approvals_required(policy.approvals_required)?;
+            if usize::from(policy.approvals_required) > policy.approvers.count_enabled() {
+                msg!(
+                    "Approvals required for dapp {} can't exceed its approvers count {}",
+                    policy.approvals_required,
+                    policy.approvers.count_enabled()
+                );
+                return Err(WalletError::InvalidApproverCount.into());
+            }
+        }
+        self.dapp_approval_policies.insert_many(policies_to_add);
+        Ok(())
+    }
+
+    fn remove_dapp_approval_policies(
+        &mut self,
+        policies_to_remove: &Vec<(SlotId<DAppApprovalPolicy>, DAppApprovalPolicy)>,
+    ) -> ProgramResult {
+        if !self
+            .dapp_approval_policies
+            .can_be_removed(policies_to_remove)
+        {
+            msg!("Failed to remove dapp approval policies: at least one of the provided policies is not present in the config");
+            return Err(WalletError::SlotCannotBeRemoved.into());
+        }
+        self.dapp_approval_policies.remove_many(policies_to_remove);
         Ok(())
     }
 
+    /// Clears every balance account's usage of the limit in the given dapp
+    /// book slot, so a new limit or dapp in the slot starts from nothing.
+    fn reset_dapp_value_usage(&mut self, dapp_slot_id: SlotId<DAppBookEntry>) {
+        for (slot_id, mut balance_account) in self.balance_accounts.filled_slots() {
+            balance_account.dapp_value_usage.reset(dapp_slot_id);
+            self.balance_accounts.replace(slot_id, balance_account);
+        }
+    }
+
     fn enable_config_approvers(
         &mut self,
         approvers: &Vec<(SlotId<Signer>, Signer)>,
@@ -636,6 +2509,30 @@ impl Wallet {
         Ok(())
     }
 
+    /// Returns the given approvers with the removals and additions applied,
+    /// validating them against the signers the same way config approvers are.
+    fn updated_approvers(
+        &self,
+        mut approvers: Approvers,
+        add: &Vec<(SlotId<Signer>, Signer)>,
+        remove: &Vec<(SlotId<Signer>, Signer)>,
+    ) -> Result<Approvers, ProgramError> {
+        for (id, signer) in remove {
+            if self.signers[*id] == Some(*signer) || self.signers[*id] == None {
+                approvers.disable(id);
+            } else {
+                msg!("Failed to disable approvers: unexpected slot value");
+                return Err(WalletError::InvalidSlot.into());
+            }
+        }
+        if !self.signers.contains(add) {
+            msg!("Failed to enable approvers: one of the given approvers is not configured as signer");
+            return Err(WalletError::UnknownSigner.into());
+        }
+        approvers.enable_many(&add.slot_ids());
+        Ok(approvers)
+    }
+
     fn enable_transfer_approvers(
         &mut self,
         balance_account: &mut BalanceAccount,
@@ -704,8 +2601,28 @@ impl Wallet {
     }
 }
 
+fn clear_dangling_flags<A: Copy + PartialEq + Ord, const SIZE: usize, const STORAGE_SIZE: usize>(
+    flags: &mut SlotFlags<A, STORAGE_SIZE>,
+    slots: &Slots<A, SIZE>,
+    description: &str,
+) -> usize {
+    let dangling = flags
+        .iter_enabled()
+        .filter(|slot_id| slots[*slot_id].is_none())
+        .collect_vec();
+    for slot_id in dangling.iter() {
+        msg!(
+            "Dangling {} flag for empty slot {}",
+            description,
+            slot_id.value
+        );
+        flags.disable(slot_id);
+    }
+    dangling.len()
+}
+
 impl Pack for Wallet {
-    const LEN: usize = 1 + // is_initialized
+    const LEN: usize = 1 + // account type discriminator
         Signers::LEN +
         Signer::LEN + // assistant
         AddressBook::LEN +
@@ -714,7 +2631,38 @@ impl Pack for Wallet {
         Approvers::STORAGE_SIZE + // config approvers
         1 + // config_policy_update_locked
         DAppBook::LEN +
-        BalanceAccounts::LEN;
+        BalanceAccounts::LEN +
+        8 + // destination_verification_delay
+        PendingAddressBookEntries::STORAGE_SIZE +
+        WalletActivity::LEN +
+        8 + // denial_reason_threshold
+        1 + // approvals_required_for_assistant_config
+        TokenMintBook::LEN +
+        Approvers::STORAGE_SIZE + // address book approvers
+        1 + // approvals_required_for_address_book
+        Approvers::STORAGE_SIZE + // default transfer approvers
+        ApproverDelegations::LEN +
+        1 + // has signer directory
+        PUBKEY_BYTES + // signer directory
+        Approvers::STORAGE_SIZE + // directory signers
+        GovernedParameters::LEN +
+        Viewers::LEN +
+        SignerOrganizations::LEN +
+        DestinationTokenMints::LEN +
+        DAppValueLimits::LEN +
+        HASH_BYTES + // metadata_hash
+        DAppTemplates::LEN +
+        Approvers::STORAGE_SIZE + // veto approvers
+        DestinationNamespaces::LEN +
+        Approvers::STORAGE_SIZE + // destructive config approvers
+        1 + // approvals_required_for_destructive_config
+        8 + // config_version
+        DAppInnerProgramSets::LEN +
+        SignerActivity::LEN +
+        RecentIdempotencyKeys::LEN +
+        DAppApprovalPolicies::LEN +
+        PendingAddressBookEntryTimes::LEN +
+        Wallet::CANARY_LEN; // canary key and frozen flag, which must stay last
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let dst = array_mut_ref![dst, 0, Wallet::LEN];
@@ -729,6 +2677,39 @@ impl Pack for Wallet {
             config_policy_update_locked_dst,
             dapp_book_dst,
             balance_accounts_dst,
+            destination_verification_delay_dst,
+            pending_address_book_entries_dst,
+            activity_dst,
+            denial_reason_threshold_dst,
+            approvals_required_for_assistant_config_dst,
+            token_mint_book_dst,
+            address_book_approvers_dst,
+            approvals_required_for_address_book_dst,
+            default_transfer_approvers_dst,
+            approver_delegations_dst,
+            has_signer_directory_dst,
+            signer_directory_dst,
+            directory_signers_dst,
+            governed_parameters_dst,
+            viewers_dst,
+            signer_organizations_dst,
+            destination_token_mints_dst,
+            dapp_value_limits_dst,
+            metadata_hash_dst,
+            dapp_templates_dst,
+            veto_approvers_dst,
+            destination_namespaces_dst,
+            destructive_config_approvers_dst,
+            approvals_required_for_destructive_config_dst,
+            config_version_dst,
+            dapp_inner_programs_dst,
+            signer_activity_dst,
+            recent_idempotency_keys_dst,
+            dapp_approval_policies_dst,
+            pending_address_book_entries_added_at_dst,
+            has_canary_key_dst,
+            canary_key_dst,
+            frozen_dst,
         ) = mut_array_refs![
             dst,
             1,
@@ -740,10 +2721,43 @@ impl Pack for Wallet {
             Approvers::STORAGE_SIZE,
             1,
             DAppBook::LEN,
-            BalanceAccounts::LEN
+            BalanceAccounts::LEN,
+            8,
+            PendingAddressBookEntries::STORAGE_SIZE,
+            WalletActivity::LEN,
+            8,
+            1,
+            TokenMintBook::LEN,
+            Approvers::STORAGE_SIZE,
+            1,
+            Approvers::STORAGE_SIZE,
+            ApproverDelegations::LEN,
+            1,
+            PUBKEY_BYTES,
+            Approvers::STORAGE_SIZE,
+            GovernedParameters::LEN,
+            Viewers::LEN,
+            SignerOrganizations::LEN,
+            DestinationTokenMints::LEN,
+            DAppValueLimits::LEN,
+            HASH_BYTES,
+            DAppTemplates::LEN,
+            Approvers::STORAGE_SIZE,
+            DestinationNamespaces::LEN,
+            Approvers::STORAGE_SIZE,
+            1,
+            8,
+            DAppInnerProgramSets::LEN,
+            SignerActivity::LEN,
+            RecentIdempotencyKeys::LEN,
+            DAppApprovalPolicies::LEN,
+            PendingAddressBookEntryTimes::LEN,
+            1,
+            PUBKEY_BYTES,
+            1
         ];
 
-        is_initialized_dst[0] = self.is_initialized as u8;
+        is_initialized_dst[0] = AccountType::Wallet.to_discriminator(self.is_initialized);
         self.signers.pack_into_slice(signers_dst);
         self.assistant.pack_into_slice(assistant_account_dst);
         self.address_book.pack_into_slice(address_book_dst);
@@ -753,6 +2767,54 @@ impl Pack for Wallet {
         config_policy_update_locked_dst[0] = self.config_policy_update_locked as u8;
         self.dapp_book.pack_into_slice(dapp_book_dst);
         self.balance_accounts.pack_into_slice(balance_accounts_dst);
+        *destination_verification_delay_dst =
+            self.destination_verification_delay.as_secs().to_le_bytes();
+        pending_address_book_entries_dst
+            .copy_from_slice(self.pending_address_book_entries.as_bytes());
+        self.activity.pack_into_slice(activity_dst);
+        *denial_reason_threshold_dst = self.denial_reason_threshold.to_le_bytes();
+        approvals_required_for_assistant_config_dst[0] =
+            self.approvals_required_for_assistant_config;
+        self.token_mint_book.pack_into_slice(token_mint_book_dst);
+        address_book_approvers_dst.copy_from_slice(self.address_book_approvers.as_bytes());
+        approvals_required_for_address_book_dst[0] = self.approvals_required_for_address_book;
+        default_transfer_approvers_dst.copy_from_slice(self.default_transfer_approvers.as_bytes());
+        self.approver_delegations
+            .pack_into_slice(approver_delegations_dst);
+        has_signer_directory_dst[0] = self.signer_directory.is_some() as u8;
+        signer_directory_dst.copy_from_slice(self.signer_directory.unwrap_or_default().as_ref());
+        directory_signers_dst.copy_from_slice(self.directory_signers.as_bytes());
+        self.governed_parameters
+            .pack_into_slice(governed_parameters_dst);
+        self.viewers.pack_into_slice(viewers_dst);
+        self.signer_organizations
+            .pack_into_slice(signer_organizations_dst);
+        self.destination_token_mints
+            .pack_into_slice(destination_token_mints_dst);
+        self.dapp_value_limits
+            .pack_into_slice(dapp_value_limits_dst);
+        metadata_hash_dst.copy_from_slice(self.metadata_hash.as_ref());
+        self.dapp_templates.pack_into_slice(dapp_templates_dst);
+        veto_approvers_dst.copy_from_slice(self.veto_approvers.as_bytes());
+        self.destination_namespaces
+            .pack_into_slice(destination_namespaces_dst);
+        destructive_config_approvers_dst
+            .copy_from_slice(self.destructive_config_approvers.as_bytes());
+        approvals_required_for_destructive_config_dst[0] =
+            self.approvals_required_for_destructive_config;
+        *config_version_dst = self.config_version.to_le_bytes();
+        self.dapp_inner_programs
+            .pack_into_slice(dapp_inner_programs_dst);
+        self.signer_activity.pack_into_slice(signer_activity_dst);
+        self.recent_idempotency_keys
+            .pack_into_slice(recent_idempotency_keys_dst);
+        self.dapp_approval_policies
+            .pack_into_slice(dapp_approval_policies_dst);
+        self.pending_address_book_entries_added_at
+            .pack_into_slice(pending_address_book_entries_added_at_dst);
+        has_canary_key_dst[0] = self.canary_key.is_some() as u8;
+        canary_key_dst.copy_from_slice(self.canary_key.unwrap_or_default().as_ref());
+        frozen_dst[0] = self.frozen as u8;
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
@@ -768,6 +2830,39 @@ impl Pack for Wallet {
             config_policy_update_locked_src,
             dapp_book_src,
             balance_accounts_src,
+            destination_verification_delay_src,
+            pending_address_book_entries_src,
+            activity_src,
+            denial_reason_threshold_src,
+            approvals_required_for_assistant_config_src,
+            token_mint_book_src,
+            address_book_approvers_src,
+            approvals_required_for_address_book_src,
+            default_transfer_approvers_src,
+            approver_delegations_src,
+            has_signer_directory_src,
+            signer_directory_src,
+            directory_signers_src,
+            governed_parameters_src,
+            viewers_src,
+            signer_organizations_src,
+            destination_token_mints_src,
+            dapp_value_limits_src,
+            metadata_hash_src,
+            dapp_templates_src,
+            veto_approvers_src,
+            destination_namespaces_src,
+            destructive_config_approvers_src,
+            approvals_required_for_destructive_config_src,
+            config_version_src,
+            dapp_inner_programs_src,
+            signer_activity_src,
+            recent_idempotency_keys_src,
+            dapp_approval_policies_src,
+            pending_address_book_entries_added_at_src,
+            has_canary_key_src,
+            canary_key_src,
+            frozen_src,
         ) = array_refs![
             src,
             1,
@@ -779,15 +2874,44 @@ impl Pack for Wallet {
             Approvers::STORAGE_SIZE,
             1,
             DAppBook::LEN,
-            BalanceAccounts::LEN
+            BalanceAccounts::LEN,
+            8,
+            PendingAddressBookEntries::STORAGE_SIZE,
+            WalletActivity::LEN,
+            8,
+            1,
+            TokenMintBook::LEN,
+            Approvers::STORAGE_SIZE,
+            1,
+            Approvers::STORAGE_SIZE,
+            ApproverDelegations::LEN,
+            1,
+            PUBKEY_BYTES,
+            Approvers::STORAGE_SIZE,
+            GovernedParameters::LEN,
+            Viewers::LEN,
+            SignerOrganizations::LEN,
+            DestinationTokenMints::LEN,
+            DAppValueLimits::LEN,
+            HASH_BYTES,
+            DAppTemplates::LEN,
+            Approvers::STORAGE_SIZE,
+            DestinationNamespaces::LEN,
+            Approvers::STORAGE_SIZE,
+            1,
+            8,
+            DAppInnerProgramSets::LEN,
+            SignerActivity::LEN,
+            RecentIdempotencyKeys::LEN,
+            DAppApprovalPolicies::LEN,
+            PendingAddressBookEntryTimes::LEN,
+            1,
+            PUBKEY_BYTES,
+            1
         ];
 
         Ok(Wallet {
-            is_initialized: match is_initialized {
-                [0] => false,
-                [1] => true,
-                _ => return Err(ProgramError::InvalidAccountData),
-            },
+            is_initialized: AccountType::Wallet.from_discriminator(is_initialized[0])?,
             signers: Signers::unpack_from_slice(signers_src)?,
             assistant: Signer::unpack_from_slice(assistant)?,
             address_book: AddressBook::unpack_from_slice(address_book_src)?,
@@ -803,6 +2927,891 @@ impl Pack for Wallet {
                 _ => return Err(ProgramError::InvalidAccountData),
             },
             dapp_book: DAppBook::unpack_from_slice(dapp_book_src)?,
+            destination_verification_delay: Duration::from_secs(u64::from_le_bytes(
+                *destination_verification_delay_src,
+            )),
+            pending_address_book_entries: PendingAddressBookEntries::new(
+                *pending_address_book_entries_src,
+            ),
+            activity: WalletActivity::unpack_from_slice(activity_src)?,
+            denial_reason_threshold: u64::from_le_bytes(*denial_reason_threshold_src),
+            approvals_required_for_assistant_config: approvals_required_for_assistant_config_src[0],
+            token_mint_book: TokenMintBook::unpack_from_slice(token_mint_book_src)?,
+            address_book_approvers: Approvers::new(*address_book_approvers_src),
+            approvals_required_for_address_book: approvals_required_for_address_book_src[0],
+            default_transfer_approvers: Approvers::new(*default_transfer_approvers_src),
+            approver_delegations: ApproverDelegations::unpack_from_slice(approver_delegations_src)?,
+            signer_directory: if has_signer_directory_src[0] == 1 {
+                Some(Pubkey::new_from_array(*signer_directory_src))
+            } else {
+                None
+            },
+            directory_signers: Approvers::new(*directory_signers_src),
+            governed_parameters: GovernedParameters::unpack_from_slice(governed_parameters_src)?,
+            viewers: Viewers::unpack_from_slice(viewers_src)?,
+            signer_organizations: SignerOrganizations::unpack_from_slice(signer_organizations_src)?,
+            destination_token_mints: DestinationTokenMints::unpack_from_slice(
+                destination_token_mints_src,
+            )?,
+            dapp_value_limits: DAppValueLimits::unpack_from_slice(dapp_value_limits_src)?,
+            canary_key: if has_canary_key_src[0] == 1 {
+                Some(Pubkey::new_from_array(*canary_key_src))
+            } else {
+                None
+            },
+            frozen: match frozen_src {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            metadata_hash: Hash::new_from_array(*metadata_hash_src),
+            dapp_templates: DAppTemplates::unpack_from_slice(dapp_templates_src)?,
+            veto_approvers: Approvers::new(*veto_approvers_src),
+            destination_namespaces: DestinationNamespaces::unpack_from_slice(
+                destination_namespaces_src,
+            )?,
+            destructive_config_approvers: Approvers::new(*destructive_config_approvers_src),
+            approvals_required_for_destructive_config:
+                approvals_required_for_destructive_config_src[0],
+            config_version: u64::from_le_bytes(*config_version_src),
+            dapp_inner_programs: DAppInnerProgramSets::unpack_from_slice(dapp_inner_programs_src)?,
+            signer_activity: SignerActivity::unpack_from_slice(signer_activity_src)?,
+            recent_idempotency_keys: RecentIdempotencyKeys::unpack_from_slice(
+                recent_idempotency_keys_src,
+            )?,
+            dapp_approval_policies: DAppApprovalPolicies::unpack_from_slice(
+                dapp_approval_policies_src,
+            )?,
+            pending_address_book_entries_added_at: PendingAddressBookEntryTimes::unpack_from_slice(
+                pending_address_book_entries_added_at_src,
+            )?,
         })
     }
 }
+
+#[test]
+fn test_repair_dangling_references() {
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    let signer = Signer::new(Pubkey::new_unique());
+    let entry = AddressBookEntry {
+        address: Pubkey::new_unique(),
+        name_hash: AddressBookEntryNameHash::zero(),
+    };
+    wallet.signers.insert(SlotId::new(0), signer);
+    wallet.address_book.insert(SlotId::new(0), entry);
+    wallet.config_approvers.enable(&SlotId::new(0));
+    wallet.config_approvers.enable(&SlotId::new(1));
+    wallet.pending_address_book_entries.enable(&SlotId::new(2));
+
+    let mut balance_account = BalanceAccount::unpack_from_slice(&[0; BalanceAccount::LEN]).unwrap();
+    balance_account.transfer_approvers.enable(&SlotId::new(0));
+    balance_account.transfer_approvers.enable(&SlotId::new(3));
+    balance_account.allowed_destinations.enable(&SlotId::new(0));
+    balance_account.allowed_destinations.enable(&SlotId::new(4));
+    wallet
+        .balance_accounts
+        .insert(SlotId::new(0), balance_account);
+
+    assert_eq!(wallet.repair_dangling_references(), 4);
+    assert_eq!(wallet.repair_dangling_references(), 0);
+
+    assert_eq!(
+        wallet.config_approvers.iter_enabled().collect_vec(),
+        vec![SlotId::new(0)]
+    );
+    assert_eq!(wallet.pending_address_book_entries.count_enabled(), 0);
+    let repaired_balance_account = wallet.balance_accounts[SlotId::new(0)].unwrap();
+    assert_eq!(
+        repaired_balance_account
+            .transfer_approvers
+            .iter_enabled()
+            .collect_vec(),
+        vec![SlotId::new(0)]
+    );
+    assert_eq!(
+        repaired_balance_account
+            .allowed_destinations
+            .iter_enabled()
+            .collect_vec(),
+        vec![SlotId::new(0)]
+    );
+}
+
+#[test]
+fn test_dapp_instruction_prefixes_follow_dapp_book() {
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    let restricted = DAppBookEntry {
+        address: Pubkey::new_unique(),
+        name_hash: AddressBookEntryNameHash::zero(),
+        instruction_prefixes: DAppInstructionPrefixes::new(&[[1; 8]]).unwrap(),
+    };
+    wallet.dapp_book.insert(SlotId::new(0), restricted);
+
+    assert_eq!(
+        wallet.dapp_instruction_prefixes(&restricted),
+        restricted.instruction_prefixes
+    );
+    // passing the same dapp without its restriction doesn't lift it
+    assert_eq!(
+        wallet.dapp_instruction_prefixes(&DAppBookEntry {
+            instruction_prefixes: DAppInstructionPrefixes::default(),
+            ..restricted
+        }),
+        restricted.instruction_prefixes
+    );
+    let other = DAppBookEntry {
+        address: Pubkey::new_unique(),
+        ..restricted
+    };
+    assert_eq!(
+        wallet.dapp_instruction_prefixes(&DAppBookEntry {
+            instruction_prefixes: DAppInstructionPrefixes::default(),
+            ..other
+        }),
+        DAppInstructionPrefixes::default()
+    );
+}
+
+#[test]
+fn test_destination_token_mints() {
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    let entry = AddressBookEntry {
+        address: Pubkey::new_unique(),
+        name_hash: AddressBookEntryNameHash::zero(),
+    };
+    let usdc = Pubkey::new_unique();
+    let other_mint = Pubkey::new_unique();
+    let update = |add_entries, add_bindings, remove_entries| AddressBookUpdate {
+        add_address_book_entries: add_entries,
+        remove_address_book_entries: remove_entries,
+        balance_account_whitelist_updates: vec![],
+        add_destination_token_mints: add_bindings,
+        remove_destination_token_mints: vec![],
+        add_destination_namespaces: vec![],
+        remove_destination_namespaces: vec![],
+    };
+    let binding = DestinationTokenMint {
+        address_book_entry: SlotId::new(0),
+        token_mint: usdc,
+    };
+
+    // the entry has to exist to be bound
+    assert_eq!(
+        wallet.update_address_book(&update(vec![], vec![(SlotId::new(0), binding)], vec![]), 0),
+        Err(WalletError::InvalidSlot.into())
+    );
+    wallet
+        .update_address_book(
+            &update(
+                vec![(SlotId::new(0), entry)],
+                vec![(SlotId::new(0), binding)],
+                vec![],
+            ),
+            0,
+        )
+        .unwrap();
+
+    assert!(wallet
+        .validate_destination_token_mint(&entry.address, &usdc)
+        .is_ok());
+    assert_eq!(
+        wallet.validate_destination_token_mint(&entry.address, &other_mint),
+        Err(WalletError::TokenMintNotAllowed.into())
+    );
+    // nor SOL, which needs a binding of its own
+    assert!(wallet
+        .validate_destination_token_mint(&entry.address, &Pubkey::default())
+        .is_err());
+    // addresses without bound entries are unaffected
+    assert!(wallet
+        .validate_destination_token_mint(&Pubkey::new_unique(), &other_mint)
+        .is_ok());
+
+    // removing the entry removes its bindings
+    wallet
+        .update_address_book(&update(vec![], vec![], vec![(SlotId::new(0), entry)]), 0)
+        .unwrap();
+    assert!(wallet.destination_token_mints.filled_slots().is_empty());
+}
+
+#[test]
+fn test_address_book_import_skips_present_entries() {
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    wallet.destination_verification_delay = Duration::from_secs(60);
+    let entries = (0..3)
+        .map(|index| {
+            (
+                SlotId::new(index),
+                AddressBookEntry {
+                    address: Pubkey::new_unique(),
+                    name_hash: AddressBookEntryNameHash::zero(),
+                },
+            )
+        })
+        .collect_vec();
+    let update = |add_entries| AddressBookUpdate {
+        add_address_book_entries: add_entries,
+        remove_address_book_entries: vec![],
+        balance_account_whitelist_updates: vec![],
+        add_destination_token_mints: vec![],
+        remove_destination_token_mints: vec![],
+        add_destination_namespaces: vec![],
+        remove_destination_namespaces: vec![],
+    };
+
+    wallet
+        .update_address_book(&update(entries[..2].to_vec()), 0)
+        .unwrap();
+    // the first entry has since been verified
+    wallet.pending_address_book_entries.disable(&entries[0].0);
+
+    // importing the whole book again, with an entry repeated, only adds and
+    // sets pending what's new
+    let mut import = entries.clone();
+    import.push(entries[2]);
+    wallet.update_address_book(&update(import), 0).unwrap();
+    assert_eq!(wallet.address_book.filled_slots(), entries);
+    assert!(!wallet
+        .pending_address_book_entries
+        .is_enabled(&entries[0].0));
+    assert!(wallet
+        .pending_address_book_entries
+        .is_enabled(&entries[1].0));
+    assert!(wallet
+        .pending_address_book_entries
+        .is_enabled(&entries[2].0));
+
+    // but a slot can't be given a different entry
+    let conflicting = (
+        SlotId::new(3),
+        AddressBookEntry {
+            address: Pubkey::new_unique(),
+            name_hash: AddressBookEntryNameHash::zero(),
+        },
+    );
+    let mut other = conflicting;
+    other.1.address = Pubkey::new_unique();
+    assert_eq!(
+        wallet.update_address_book(&update(vec![conflicting, other]), 0),
+        Err(WalletError::SlotCannotBeInserted.into())
+    );
+    let mut replacement = entries[0];
+    replacement.1.address = Pubkey::new_unique();
+    assert_eq!(
+        wallet.update_address_book(&update(vec![replacement]), 0),
+        Err(WalletError::SlotCannotBeInserted.into())
+    );
+}
+
+#[test]
+fn test_destination_namespaces() {
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    let token_account = AddressBookEntry {
+        address: Pubkey::new_unique(),
+        name_hash: AddressBookEntryNameHash::zero(),
+    };
+    let exchange = AddressBookEntry {
+        address: Pubkey::new_unique(),
+        name_hash: AddressBookEntryNameHash::zero(),
+    };
+    let usdc = Pubkey::new_unique();
+    let deposit_memo_hash = Hash::new_unique();
+    let update = |add_entries, add_tags, remove_entries| AddressBookUpdate {
+        add_address_book_entries: add_entries,
+        remove_address_book_entries: remove_entries,
+        balance_account_whitelist_updates: vec![],
+        add_destination_token_mints: vec![],
+        remove_destination_token_mints: vec![],
+        add_destination_namespaces: add_tags,
+        remove_destination_namespaces: vec![],
+    };
+    let token_account_tag = DestinationNamespace {
+        address_book_entry: SlotId::new(0),
+        namespace: AddressNamespace::TokenAccount,
+    };
+    let exchange_tag = DestinationNamespace {
+        address_book_entry: SlotId::new(1),
+        namespace: AddressNamespace::DepositMemo(deposit_memo_hash),
+    };
+
+    // the entry has to exist to be tagged
+    assert_eq!(
+        wallet.update_address_book(
+            &update(vec![], vec![(SlotId::new(0), token_account_tag)], vec![]),
+            0
+        ),
+        Err(WalletError::InvalidSlot.into())
+    );
+    wallet
+        .update_address_book(
+            &update(
+                vec![(SlotId::new(0), token_account), (SlotId::new(1), exchange)],
+                vec![
+                    (SlotId::new(0), token_account_tag),
+                    (SlotId::new(1), exchange_tag),
+                ],
+                vec![],
+            ),
+            0,
+        )
+        .unwrap();
+    // and can only be in one namespace
+    assert_eq!(
+        wallet.validate_address_book_update(&update(
+            vec![],
+            vec![(
+                SlotId::new(2),
+                DestinationNamespace {
+                    address_book_entry: SlotId::new(0),
+                    namespace: AddressNamespace::DepositMemo(deposit_memo_hash),
+                }
+            )],
+            vec![]
+        )),
+        Err(WalletError::InvalidSlot.into())
+    );
+
+    assert!(wallet.is_token_account_destination(&token_account.address));
+    assert!(!wallet.is_token_account_destination(&exchange.address));
+    assert!(wallet
+        .validate_destination_namespace(&token_account.address, &usdc, None, None)
+        .is_ok());
+    assert_eq!(
+        wallet.validate_destination_namespace(
+            &token_account.address,
+            &Pubkey::default(),
+            None,
+            None
+        ),
+        Err(WalletError::DestinationNamespaceMismatch.into())
+    );
+    assert!(wallet
+        .validate_destination_namespace(
+            &exchange.address,
+            &Pubkey::default(),
+            Some(&deposit_memo_hash),
+            None
+        )
+        .is_ok());
+    assert_eq!(
+        wallet.validate_destination_namespace(&exchange.address, &usdc, None, None),
+        Err(WalletError::DestinationNamespaceMismatch.into())
+    );
+    assert_eq!(
+        wallet.validate_destination_namespace(
+            &exchange.address,
+            &usdc,
+            Some(&Hash::new_unique()),
+            None
+        ),
+        Err(WalletError::DestinationNamespaceMismatch.into())
+    );
+    // addresses without tagged entries are unaffected
+    assert!(wallet
+        .validate_destination_namespace(&Pubkey::new_unique(), &Pubkey::default(), None, None)
+        .is_ok());
+
+    // tagging another entry for the deposit address as an exchange requires
+    // travel rule data on top of the memo
+    wallet
+        .update_address_book(
+            &update(
+                vec![(
+                    SlotId::new(2),
+                    AddressBookEntry {
+                        name_hash: AddressBookEntryNameHash::new(&[2; 32]),
+                        ..exchange
+                    },
+                )],
+                vec![(
+                    SlotId::new(2),
+                    DestinationNamespace {
+                        address_book_entry: SlotId::new(2),
+                        namespace: AddressNamespace::Exchange,
+                    },
+                )],
+                vec![],
+            ),
+            0,
+        )
+        .unwrap();
+    let travel_rule_hash = Hash::new_unique();
+    assert_eq!(
+        wallet.validate_destination_namespace(
+            &exchange.address,
+            &Pubkey::default(),
+            Some(&deposit_memo_hash),
+            None
+        ),
+        Err(WalletError::TravelRuleDataRequired.into())
+    );
+    assert_eq!(
+        wallet.validate_destination_namespace(
+            &exchange.address,
+            &Pubkey::default(),
+            None,
+            Some(&travel_rule_hash)
+        ),
+        Err(WalletError::DestinationNamespaceMismatch.into())
+    );
+    assert!(wallet
+        .validate_destination_namespace(
+            &exchange.address,
+            &Pubkey::default(),
+            Some(&deposit_memo_hash),
+            Some(&travel_rule_hash)
+        )
+        .is_ok());
+    wallet
+        .update_address_book(
+            &update(
+                vec![],
+                vec![],
+                vec![(
+                    SlotId::new(2),
+                    AddressBookEntry {
+                        name_hash: AddressBookEntryNameHash::new(&[2; 32]),
+                        ..exchange
+                    },
+                )],
+            ),
+            0,
+        )
+        .unwrap();
+
+    // removing an entry removes its tag
+    wallet
+        .update_address_book(
+            &update(vec![], vec![], vec![(SlotId::new(0), token_account)]),
+            0,
+        )
+        .unwrap();
+    assert_eq!(
+        wallet.destination_namespaces.filled_slots(),
+        vec![(SlotId::new(1), exchange_tag)]
+    );
+}
+
+#[test]
+fn test_address_book_update_size() {
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    let entries = (0..AddressBookUpdate::MAX_CHANGES + 1)
+        .map(|i| {
+            (
+                SlotId::new(i),
+                AddressBookEntry {
+                    address: Pubkey::new_unique(),
+                    name_hash: AddressBookEntryNameHash::zero(),
+                },
+            )
+        })
+        .collect_vec();
+    let update = |add_entries| AddressBookUpdate {
+        add_address_book_entries: add_entries,
+        remove_address_book_entries: vec![],
+        balance_account_whitelist_updates: vec![],
+        add_destination_token_mints: vec![],
+        remove_destination_token_mints: vec![],
+        add_destination_namespaces: vec![],
+        remove_destination_namespaces: vec![],
+    };
+
+    assert_eq!(
+        wallet.validate_address_book_update(&update(entries.clone())),
+        Err(WalletError::AddressBookUpdateTooLarge.into())
+    );
+    wallet
+        .validate_address_book_update(&update(entries[..AddressBookUpdate::MAX_CHANGES].to_vec()))
+        .unwrap();
+    // chunked updates are applied from a buffer and aren't held to the limit
+    wallet
+        .update_address_book(&update(entries.clone()), 0)
+        .unwrap();
+    assert_eq!(
+        wallet.address_book.filled_slots().len(),
+        AddressBookUpdate::MAX_CHANGES + 1
+    );
+}
+
+#[test]
+fn test_dapp_value_limits() {
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    let mut balance_account = BalanceAccount::unpack_from_slice(&[0; BalanceAccount::LEN]).unwrap();
+    balance_account.guid_hash = BalanceAccountGuidHash::new(&[1; 32]);
+    wallet
+        .balance_accounts
+        .insert(SlotId::new(0), balance_account);
+    let guid_hash = balance_account.guid_hash;
+
+    let dapp = DAppBookEntry {
+        address: Pubkey::new_unique(),
+        name_hash: AddressBookEntryNameHash::zero(),
+        instruction_prefixes: DAppInstructionPrefixes::default(),
+    };
+    let limit = DAppValueLimit {
+        max_value: 1000,
+        period: Duration::from_secs(3600),
+    };
+    let update = |add_dapps, remove_dapps, add_value_limits| DAppBookUpdate {
+        add_dapps,
+        remove_dapps,
+        add_value_limits,
+        remove_value_limits: vec![],
+        add_inner_programs: vec![],
+        remove_inner_programs: vec![],
+        add_approval_policies: vec![],
+        remove_approval_policies: vec![],
+    };
+
+    // limits need a dapp in their slot
+    assert_eq!(
+        wallet.update_dapp_book(&update(vec![], vec![], vec![(SlotId::new(0), limit)])),
+        Err(WalletError::InvalidSlot.into())
+    );
+    wallet
+        .update_dapp_book(&update(
+            vec![(SlotId::new(0), dapp)],
+            vec![],
+            vec![(SlotId::new(0), limit)],
+        ))
+        .unwrap();
+
+    assert!(wallet
+        .record_dapp_value(&guid_hash, &dapp, 600, 100)
+        .unwrap());
+    // an unlisted dapp at the same address is held to the same limit
+    assert_eq!(
+        wallet.record_dapp_value(
+            &guid_hash,
+            &DAppBookEntry {
+                name_hash: AddressBookEntryNameHash::new(&[2; 32]),
+                ..dapp
+            },
+            600,
+            200
+        ),
+        Err(WalletError::DAppValueLimitExceeded.into())
+    );
+    assert!(!wallet
+        .record_dapp_value(
+            &guid_hash,
+            &DAppBookEntry {
+                address: Pubkey::new_unique(),
+                ..dapp
+            },
+            600,
+            200
+        )
+        .unwrap());
+
+    // removing the dapp removes its limit and the usage of it
+    wallet
+        .update_dapp_book(&update(vec![], vec![(SlotId::new(0), dapp)], vec![]))
+        .unwrap();
+    assert!(wallet.dapp_value_limits.filled_slots().is_empty());
+    assert_eq!(
+        wallet
+            .get_balance_account(&guid_hash)
+            .unwrap()
+            .dapp_value_usage,
+        DAppValueUsages::new()
+    );
+}
+
+#[test]
+fn test_dapp_approval_policies() {
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    let signers = (0..3)
+        .map(|i| (SlotId::new(i), Signer::new(Pubkey::new_unique())))
+        .collect_vec();
+    wallet.signers.insert_many(&signers);
+    let dapp = DAppBookEntry {
+        address: Pubkey::new_unique(),
+        name_hash: AddressBookEntryNameHash::zero(),
+        instruction_prefixes: DAppInstructionPrefixes::default(),
+    };
+    let policy = DAppApprovalPolicy {
+        approvers: Approvers::from_enabled_vec(vec![SlotId::new(0), SlotId::new(1)]),
+        approvals_required: 2,
+    };
+    let update = |add_dapps, remove_dapps, add_approval_policies| DAppBookUpdate {
+        add_dapps,
+        remove_dapps,
+        add_value_limits: vec![],
+        remove_value_limits: vec![],
+        add_inner_programs: vec![],
+        remove_inner_programs: vec![],
+        add_approval_policies,
+        remove_approval_policies: vec![],
+    };
+
+    // policies need a dapp in their slot
+    assert_eq!(
+        wallet.update_dapp_book(&update(vec![], vec![], vec![(SlotId::new(0), policy)])),
+        Err(WalletError::InvalidSlot.into())
+    );
+    // and can't require more approvals than they have approvers
+    assert_eq!(
+        wallet.update_dapp_book(&update(
+            vec![(SlotId::new(0), dapp)],
+            vec![],
+            vec![(
+                SlotId::new(0),
+                DAppApprovalPolicy {
+                    approvals_required: 3,
+                    ..policy
+                }
+            )],
+        )),
+        Err(WalletError::InvalidApproverCount.into())
+    );
+    wallet
+        .update_dapp_book(&update(
+            vec![(SlotId::new(0), dapp)],
+            vec![],
+            vec![(SlotId::new(0), policy)],
+        ))
+        .unwrap();
+
+    assert_eq!(wallet.dapp_approval_policy(&dapp), Some(policy));
+    assert_eq!(
+        wallet.get_dapp_approvers_keys(&policy),
+        vec![signers[0].1.key, signers[1].1.key]
+    );
+    // an unlisted dapp at the same address doesn't get the listed one's policy
+    assert_eq!(
+        wallet.dapp_approval_policy(&DAppBookEntry {
+            name_hash: AddressBookEntryNameHash::new(&[2; 32]),
+            ..dapp
+        }),
+        None
+    );
+
+    // the dapp's approvers can't be removed while the policy is in place
+    assert_eq!(
+        wallet.remove_signers(&vec![signers[1]]),
+        Err(WalletError::SignerIsTransferApprover.into())
+    );
+
+    // removing the dapp removes its policy
+    wallet
+        .update_dapp_book(&update(vec![], vec![(SlotId::new(0), dapp)], vec![]))
+        .unwrap();
+    assert!(wallet.dapp_approval_policies.filled_slots().is_empty());
+    wallet.remove_signers(&vec![signers[1]]).unwrap();
+}
+
+#[test]
+fn test_destructive_config_approvers() {
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    let signers = (0..4)
+        .map(|i| (SlotId::new(i), Signer::new(Pubkey::new_unique())))
+        .collect_vec();
+    wallet.signers.insert_many(&signers);
+    wallet
+        .enable_config_approvers(&signers[..3].to_vec())
+        .unwrap();
+    wallet.approvals_required_for_config = 2;
+    let update = |approvals_required_for_config,
+                  remove_config_approvers,
+                  approvals_required_for_destructive_config,
+                  add_destructive_config_approvers| WalletConfigPolicyUpdate {
+        approvals_required_for_config,
+        approval_timeout_for_config: None,
+        add_config_approvers: vec![],
+        remove_config_approvers,
+        replace_config_approvers: None,
+        destination_verification_delay: None,
+        denial_reason_threshold: None,
+        approvals_required_for_assistant_config: None,
+        approvals_required_for_address_book: None,
+        add_address_book_approvers: vec![],
+        remove_address_book_approvers: vec![],
+        add_default_transfer_approvers: vec![],
+        remove_default_transfer_approvers: vec![],
+        add_veto_approvers: vec![],
+        remove_veto_approvers: vec![],
+        approvals_required_for_destructive_config,
+        add_destructive_config_approvers,
+        remove_destructive_config_approvers: vec![],
+    };
+    let params = |update| MultisigOpParams::UpdateWalletConfigPolicy {
+        wallet_address: Pubkey::new_unique(),
+        update,
+    };
+    let raise_quorum = params(update(Some(3), vec![], None, vec![]));
+    let lower_quorum = params(update(Some(1), vec![], None, vec![]));
+    let remove_approver = params(update(None, vec![signers[2]], None, vec![]));
+
+    assert_eq!(
+        wallet.config_change_class(&raise_quorum),
+        ConfigChangeClass::Additive
+    );
+    assert_eq!(
+        wallet.config_change_class(&lower_quorum),
+        ConfigChangeClass::Destructive
+    );
+    assert_eq!(
+        wallet.config_change_class(&remove_approver),
+        ConfigChangeClass::Destructive
+    );
+
+    // without destructive config approvers, the config approvers and quorum
+    // apply to every config op
+    let config_approvers = (wallet.get_config_approvers_keys(), 2);
+    assert_eq!(
+        wallet.config_op_approvers(&lower_quorum, InitiatorClass::Approver),
+        config_approvers
+    );
+
+    // the quorum has to be within the destructive config approvers
+    assert_eq!(
+        wallet.validate_config_policy_update(
+            &update(None, vec![], Some(2), vec![signers[3]]),
+            &PolicyLimits::default()
+        ),
+        Err(WalletError::InvalidApproverCount.into())
+    );
+    wallet
+        .update_config_policy(
+            &update(None, vec![], Some(1), vec![signers[3]]),
+            &PolicyLimits::default(),
+        )
+        .unwrap();
+    assert_eq!(
+        wallet.config_op_approvers(&raise_quorum, InitiatorClass::Approver),
+        config_approvers
+    );
+    assert_eq!(
+        wallet.config_op_approvers(&lower_quorum, InitiatorClass::Approver),
+        (vec![signers[3].1.key], 1)
+    );
+    assert_eq!(
+        wallet.config_op_approvers(
+            &MultisigOpParams::UpdateSigners {
+                wallet_address: Pubkey::new_unique(),
+                update: SignersUpdate {
+                    add_signers: vec![],
+                    remove_signers: vec![signers[2]],
+                },
+            },
+            InitiatorClass::Assistant
+        ),
+        (vec![signers[3].1.key], 1)
+    );
+
+    // closing the wallet takes every config approver, whatever the quorums
+    let close_wallet = MultisigOpParams::CloseWallet {
+        wallet_address: Pubkey::new_unique(),
+        rent_collector: Pubkey::new_unique(),
+    };
+    assert_eq!(
+        wallet.config_change_class(&close_wallet),
+        ConfigChangeClass::Destructive
+    );
+    assert_eq!(
+        wallet.config_op_approvers(&close_wallet, InitiatorClass::Approver),
+        (wallet.get_config_approvers_keys(), 3)
+    );
+}
+
+#[test]
+fn test_inactive_approvers_are_left_out() {
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    let signers = (0..3)
+        .map(|i| (SlotId::new(i), Signer::new(Pubkey::new_unique())))
+        .collect_vec();
+    wallet.signers.insert_many(&signers);
+    let keys = signers.iter().map(|(_, signer)| signer.key).collect_vec();
+    let day = 24 * 60 * 60;
+    wallet.start_tracking_signer_activity(100);
+    assert!(wallet.record_signer_activity(&keys[0], 100 + 9 * day));
+    assert!(!wallet.record_signer_activity(&Pubkey::new_unique(), 100 + 9 * day));
+
+    // nothing is left out until an inactivity period is set
+    let now = 100 + 10 * day;
+    assert_eq!(wallet.inactive_signers(now), vec![]);
+    assert_eq!(
+        wallet.exclude_inactive_approvers(keys.clone(), 2, now),
+        keys
+    );
+
+    wallet.governed_parameters.set(
+        crate::model::governed_parameter::GovernedParameter::SignerInactivityDays,
+        Some(5),
+    );
+    assert_eq!(wallet.inactive_signers(now), keys[1..].to_vec());
+    assert_eq!(
+        wallet.exclude_inactive_approvers(keys.clone(), 1, now),
+        vec![keys[0]]
+    );
+    // inactive approvers are kept when the quorum needs them
+    assert_eq!(
+        wallet.exclude_inactive_approvers(keys.clone(), 2, now),
+        keys
+    );
+
+    // a removed signer's activity goes with it
+    wallet.remove_signers(&vec![signers[0]]).unwrap();
+    assert_eq!(wallet.signer_activity.get(SlotId::new(0)), None);
+
+    let mut buf = vec![0; Wallet::LEN];
+    Wallet::pack(wallet.clone(), &mut buf).unwrap();
+    assert_eq!(
+        Wallet::unpack_unchecked(&buf).unwrap().signer_activity,
+        wallet.signer_activity
+    );
+}
+
+#[test]
+fn test_destination_verification_delay_runs_from_when_entries_are_added() {
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    wallet.destination_verification_delay = Duration::from_secs(3600);
+    let entry = (
+        SlotId::new(0),
+        AddressBookEntry {
+            address: Pubkey::new_unique(),
+            name_hash: AddressBookEntryNameHash::zero(),
+        },
+    );
+    let added_at = 1_000;
+    wallet
+        .update_address_book(
+            &AddressBookUpdate {
+                add_address_book_entries: vec![entry],
+                remove_address_book_entries: vec![],
+                balance_account_whitelist_updates: vec![],
+                add_destination_token_mints: vec![],
+                remove_destination_token_mints: vec![],
+                add_destination_namespaces: vec![],
+                remove_destination_namespaces: vec![],
+            },
+            added_at,
+        )
+        .unwrap();
+    assert!(wallet.pending_address_book_entries.is_enabled(&entry.0));
+    assert_eq!(
+        wallet.pending_address_book_entries_added_at.get(entry.0),
+        added_at
+    );
+
+    let mut buf = vec![0; Wallet::LEN];
+    wallet.pack_into_slice(&mut buf);
+    assert_eq!(Wallet::unpack_unchecked(&buf).unwrap(), wallet);
+
+    assert_eq!(
+        wallet.validate_destination_verification_delay(&vec![entry], added_at + 3599),
+        Err(WalletError::DestinationVerificationDelayNotElapsed.into())
+    );
+
+    // an op started well after the entry was added doesn't have to wait out
+    // the delay again
+    let op_started_at = added_at + 3 * 3600;
+    assert_eq!(
+        wallet.validate_destination_verification_delay(&vec![entry], op_started_at + 60),
+        Ok(())
+    );
+    wallet.verify_address_book_entries(&vec![entry]).unwrap();
+    assert!(!wallet.pending_address_book_entries.is_enabled(&entry.0));
+    assert_eq!(wallet.pending_address_book_entries_added_at.get(entry.0), 0);
+}
@@ -0,0 +1,88 @@
+use crate::model::account_type::AccountType;
+use crate::model::wallet::Wallet;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+/// A snapshot of a wallet's state, written by `FinalizeWalletStateExport` to
+/// an account of the exporting program, from which the target program's
+/// `ImportWalletState` recreates the wallet. Balance accounts are derived
+/// from the wallet address and program id, so funds are not part of it and
+/// have to be moved separately.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WalletExport {
+    pub is_initialized: bool,
+    /// The exported wallet's address in the exporting program.
+    pub wallet_address: Pubkey,
+    /// The only program the snapshot can be imported into.
+    pub target_program_id: Pubkey,
+    pub exported_at: i64,
+    pub wallet: Wallet,
+}
+
+impl Sealed for WalletExport {}
+
+impl IsInitialized for WalletExport {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for WalletExport {
+    const LEN: usize = 1 + // account type discriminator
+        PUBKEY_BYTES + // wallet_address
+        PUBKEY_BYTES + // target_program_id
+        8 + // exported_at
+        Wallet::LEN; // wallet
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, WalletExport::LEN];
+        let (
+            is_initialized_dst,
+            wallet_address_dst,
+            target_program_id_dst,
+            exported_at_dst,
+            wallet_dst,
+        ) = mut_array_refs![dst, 1, PUBKEY_BYTES, PUBKEY_BYTES, 8, Wallet::LEN];
+
+        is_initialized_dst[0] = AccountType::WalletExport.to_discriminator(self.is_initialized);
+        wallet_address_dst.copy_from_slice(self.wallet_address.as_ref());
+        target_program_id_dst.copy_from_slice(self.target_program_id.as_ref());
+        *exported_at_dst = self.exported_at.to_le_bytes();
+        self.wallet.pack_into_slice(wallet_dst);
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, WalletExport::LEN];
+        let (is_initialized, wallet_address, target_program_id, exported_at, wallet) =
+            array_refs![src, 1, PUBKEY_BYTES, PUBKEY_BYTES, 8, Wallet::LEN];
+
+        Ok(WalletExport {
+            is_initialized: AccountType::WalletExport.from_discriminator(is_initialized[0])?,
+            wallet_address: Pubkey::new_from_array(*wallet_address),
+            target_program_id: Pubkey::new_from_array(*target_program_id),
+            exported_at: i64::from_le_bytes(*exported_at),
+            wallet: Wallet::unpack_from_slice(wallet)?,
+        })
+    }
+}
+
+#[test]
+fn test_pack_unpack_wallet_export() {
+    let mut wallet = Wallet::unpack_unchecked(&[0; Wallet::LEN]).unwrap();
+    wallet.is_initialized = true;
+    wallet.approvals_required_for_config = 2;
+    let export = WalletExport {
+        is_initialized: true,
+        wallet_address: Pubkey::new_unique(),
+        target_program_id: Pubkey::new_unique(),
+        exported_at: 1234,
+        wallet,
+    };
+
+    let mut packed = vec![0; WalletExport::LEN];
+    WalletExport::pack(export.clone(), &mut packed).unwrap();
+    assert_eq!(packed[0], AccountType::WalletExport as u8);
+    assert_eq!(WalletExport::unpack(&packed).unwrap(), export);
+}
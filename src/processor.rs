@@ -1,15 +1,103 @@
+use crate::error::WalletError;
+use crate::handlers::utils::next_program_account_info;
 use crate::handlers::{
-    address_book_update_handler, approval_disposition_handler, balance_account_creation_handler,
+    activity_checkpoint_handler, address_book_page_update_handler,
+    address_book_update_chunked_handler, address_book_update_handler,
+    address_book_verification_handler, approval_disposition_handler,
+    approver_delegation_update_handler, assistant_update_handler, balance_account_creation_handler,
     balance_account_name_update_handler, balance_account_policy_update_handler,
-    balance_account_settings_update_handler, dapp_book_update_handler, dapp_transaction_handler,
-    init_wallet_handler, transfer_handler, update_signer_handler,
-    wallet_config_policy_update_handler, wrap_unwrap_handler,
+    balance_account_settings_update_handler, capacity_report_handler, co_signature_handler,
+    combined_transfer_handler, dapp_book_update_handler, dapp_template_update_handler,
+    dapp_transaction_handler, deposit_consolidation_handler, governed_parameter_update_handler,
+    init_wallet_handler, internal_transfer_handler, multisig_op_expiry_handler,
+    multisig_op_renewal_handler, name_verification_handler, op_account_creation_handler,
+    pending_disposition_handler, pending_ops_handler, policy_manifest_handler,
+    program_config_handler, signer_activity_report_handler, signer_directory_handler,
+    signer_organizations_update_handler, transfer_handler, update_signer_handler, validation,
+    viewer_handler, wallet_canary_handler, wallet_close_handler,
+    wallet_config_policy_update_handler, wallet_export_handler, wallet_metadata_update_handler,
+    wallet_repair_handler, wallet_signer_directory_update_handler, wrap_unwrap_handler,
 };
 use crate::instruction::ProgramInstruction;
-use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+use crate::model::idempotency::IdempotencyKey;
+use crate::model::multisig_op::MultisigOp;
+use crate::model::program_config::ProgramConfig;
+use crate::model::wallet::Wallet;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, hash::Hash, msg,
+    program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+};
 
 pub struct Processor;
 
+/// Every instruction but the program config's own and the read-only reports
+/// has to carry the program config account, whether or not the config has been
+/// created. It is looked up once per instruction and passed down to the
+/// handlers. Once it has been created, the program-wide pause switch is
+/// enforced before the instruction is handled, and handlers apply the
+/// configured policy limits.
+fn load_program_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction: &ProgramInstruction,
+) -> Result<Option<ProgramConfig>, ProgramError> {
+    match instruction {
+        ProgramInstruction::InitProgramConfig { .. }
+        | ProgramInstruction::UpdateProgramConfig { .. }
+        | ProgramInstruction::AssertPolicyManifest { .. }
+        | ProgramInstruction::ReportCapacity { .. }
+        | ProgramInstruction::ReportSignerActivity { .. }
+        | ProgramInstruction::VerifyAccess
+        | ProgramInstruction::VerifyNameHash { .. } => return Ok(None),
+        _ => {}
+    }
+
+    let program_config = ProgramConfig::from_accounts(program_id, accounts)?;
+    // a preflight reports the pause as one of its checks instead
+    let paused = program_config
+        .as_ref()
+        .map_or(false, |program_config| program_config.is_paused());
+    if paused && !matches!(instruction, ProgramInstruction::PreflightTransfer { .. }) {
+        msg!("Program is paused");
+        return Err(WalletError::ProgramPaused.into());
+    }
+    Ok(program_config)
+}
+
+fn supports_validate_only(instruction: &ProgramInstruction) -> bool {
+    matches!(
+        instruction,
+        ProgramInstruction::InitTransfer { .. }
+            | ProgramInstruction::InitWrapUnwrap { .. }
+            | ProgramInstruction::InitWalletConfigPolicyUpdate { .. }
+            | ProgramInstruction::InitBalanceAccountCreation { .. }
+            | ProgramInstruction::InitBalanceAccountNameUpdate { .. }
+            | ProgramInstruction::InitBalanceAccountPolicyUpdate { .. }
+            | ProgramInstruction::InitUpdateSigner { .. }
+            | ProgramInstruction::InitAccountSettingsUpdate { .. }
+            | ProgramInstruction::InitDAppBookUpdate { .. }
+            | ProgramInstruction::InitAddressBookUpdate { .. }
+            | ProgramInstruction::InitAddressBookVerification { .. }
+            | ProgramInstruction::InitActivityCheckpoint { .. }
+            | ProgramInstruction::InitAddressBookPageUpdate { .. }
+            | ProgramInstruction::InitWalletRepair
+            | ProgramInstruction::InitApproverDelegationUpdate { .. }
+            | ProgramInstruction::InitWalletSignerDirectoryUpdate { .. }
+            | ProgramInstruction::InitWalletCanaryUpdate { .. }
+            | ProgramInstruction::InitInternalTransfer { .. }
+            | ProgramInstruction::InitGovernedParameterUpdate { .. }
+            | ProgramInstruction::InitUpdateSigners { .. }
+            | ProgramInstruction::InitViewersUpdate { .. }
+            | ProgramInstruction::InitSignerOrganizationsUpdate { .. }
+            | ProgramInstruction::InitAssistantUpdate { .. }
+            | ProgramInstruction::InitWalletStateExport { .. }
+            | ProgramInstruction::InitCombinedTransfer { .. }
+            | ProgramInstruction::InitWalletMetadataUpdate { .. }
+            | ProgramInstruction::InitDAppTemplatesUpdate { .. }
+            | ProgramInstruction::InitWalletClose { .. }
+    )
+}
+
 impl Processor {
     pub fn process(
         program_id: &Pubkey,
@@ -18,17 +106,52 @@ impl Processor {
     ) -> ProgramResult {
         let instruction = ProgramInstruction::unpack(instruction_data)?;
 
+        let program_config = load_program_config(program_id, accounts, &instruction)?;
+
+        if wallet_canary_handler::freeze_on_canary_signature(program_id, accounts)? {
+            return Ok(());
+        }
+        match instruction {
+            ProgramInstruction::InitWalletCanaryUpdate { .. }
+            | ProgramInstruction::FinalizeWalletCanaryUpdate { .. }
+            | ProgramInstruction::AssertPolicyManifest { .. }
+            | ProgramInstruction::ReportCapacity { .. }
+            | ProgramInstruction::ReportSignerActivity { .. }
+            | ProgramInstruction::VerifyAccess
+            | ProgramInstruction::VerifyNameHash { .. } => {}
+            _ => wallet_canary_handler::validate_wallets_not_frozen(program_id, accounts)?,
+        }
+
+        let starts_op = pending_ops_handler::starts_op(&instruction);
+        let executable = pending_ops_handler::executable_op(program_id, accounts)?;
+        Self::dispatch(program_id, accounts, instruction, program_config.as_ref())?;
+        pending_ops_handler::record(program_id, accounts, starts_op, executable)
+    }
+
+    fn dispatch(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction: ProgramInstruction,
+        program_config: Option<&ProgramConfig>,
+    ) -> ProgramResult {
+        validation::validate_accounts(program_id, accounts, &instruction)?;
+        let limits = program_config
+            .map(ProgramConfig::limits)
+            .unwrap_or_default();
+
         match instruction {
             ProgramInstruction::InitWallet {
                 initial_config: update,
-            } => init_wallet_handler::handle(program_id, accounts, &update),
+            } => init_wallet_handler::handle(program_id, accounts, &update, &limits),
 
             ProgramInstruction::InitWalletConfigPolicyUpdate { update } => {
-                wallet_config_policy_update_handler::init(program_id, accounts, &update)
+                wallet_config_policy_update_handler::init(program_id, accounts, &update, &limits)
             }
 
             ProgramInstruction::FinalizeWalletConfigPolicyUpdate { update } => {
-                wallet_config_policy_update_handler::finalize(program_id, accounts, &update)
+                wallet_config_policy_update_handler::finalize(
+                    program_id, accounts, &update, &limits,
+                )
             }
 
             ProgramInstruction::InitBalanceAccountCreation {
@@ -39,6 +162,7 @@ impl Processor {
                 accounts,
                 &account_guid_hash,
                 &creation_params,
+                &limits,
             ),
 
             ProgramInstruction::FinalizeBalanceAccountCreation {
@@ -49,6 +173,7 @@ impl Processor {
                 accounts,
                 &account_guid_hash,
                 &creation_params,
+                &limits,
             ),
 
             ProgramInstruction::InitBalanceAccountNameUpdate {
@@ -79,6 +204,7 @@ impl Processor {
                 accounts,
                 &account_guid_hash,
                 &update,
+                &limits,
             ),
 
             ProgramInstruction::FinalizeBalanceAccountPolicyUpdate {
@@ -89,64 +215,119 @@ impl Processor {
                 accounts,
                 &account_guid_hash,
                 &update,
+                &limits,
             ),
 
             ProgramInstruction::InitTransfer {
                 account_guid_hash,
                 amount,
                 destination_name_hash,
+                approval_timeout,
+                memo_hash,
+                ata_rent_payer,
+                amount_spec,
+                travel_rule_hash,
             } => transfer_handler::init(
                 program_id,
                 &accounts,
                 &account_guid_hash,
                 amount,
                 &destination_name_hash,
+                approval_timeout,
+                memo_hash,
+                ata_rent_payer,
+                amount_spec,
+                travel_rule_hash,
+                &limits,
             ),
 
             ProgramInstruction::FinalizeTransfer {
                 account_guid_hash,
                 amount,
                 token_mint,
+                memo_hash,
+                fee_reimbursement,
+                ata_rent_payer,
+                token_program,
+                amount_spec,
+                travel_rule_hash,
             } => transfer_handler::finalize(
                 program_id,
                 &accounts,
                 &account_guid_hash,
                 amount,
                 token_mint,
+                memo_hash,
+                fee_reimbursement,
+                ata_rent_payer,
+                token_program,
+                amount_spec,
+                travel_rule_hash,
             ),
 
             ProgramInstruction::SetApprovalDisposition {
                 disposition,
                 params_hash,
+                denial_reason,
+                reason_hash,
             } => approval_disposition_handler::handle(
                 program_id,
                 &accounts,
                 disposition,
                 params_hash,
+                denial_reason,
+                reason_hash,
             ),
 
+            ProgramInstruction::SetApprovalDispositions { dispositions } => {
+                approval_disposition_handler::handle_many(program_id, &accounts, &dispositions)
+            }
+
+            ProgramInstruction::InitWalletRepair => {
+                wallet_repair_handler::init(program_id, accounts)
+            }
+
+            ProgramInstruction::FinalizeWalletRepair => {
+                wallet_repair_handler::finalize(program_id, accounts)
+            }
+
+            ProgramInstruction::CheckWalletIntegrity => {
+                wallet_repair_handler::check(program_id, accounts)
+            }
+
             ProgramInstruction::InitWrapUnwrap {
                 account_guid_hash,
                 amount,
                 direction,
+                approval_timeout,
+                destination,
+                travel_rule_hash,
             } => wrap_unwrap_handler::init(
                 program_id,
                 &accounts,
                 &account_guid_hash,
                 amount,
                 direction,
+                approval_timeout,
+                destination,
+                travel_rule_hash,
+                &limits,
             ),
 
             ProgramInstruction::FinalizeWrapUnwrap {
                 account_guid_hash,
                 amount,
                 direction,
+                destination,
+                travel_rule_hash,
             } => wrap_unwrap_handler::finalize(
                 program_id,
                 &accounts,
                 &account_guid_hash,
                 amount,
                 direction,
+                destination,
+                travel_rule_hash,
             ),
 
             ProgramInstruction::InitUpdateSigner {
@@ -159,6 +340,7 @@ impl Processor {
                 slot_update_type,
                 slot_id,
                 signer,
+                &limits,
             ),
 
             ProgramInstruction::FinalizeUpdateSigner {
@@ -171,54 +353,91 @@ impl Processor {
                 slot_update_type,
                 slot_id,
                 signer,
+                &limits,
             ),
 
+            ProgramInstruction::InitUpdateSigners { update } => {
+                update_signer_handler::init_many(program_id, &accounts, &update, &limits)
+            }
+
+            ProgramInstruction::FinalizeUpdateSigners { update } => {
+                update_signer_handler::finalize_many(program_id, &accounts, &update, &limits)
+            }
+
             ProgramInstruction::InitDAppTransaction {
                 ref account_guid_hash,
                 dapp,
                 instructions,
+                rent_funding_limit,
             } => dapp_transaction_handler::init(
                 program_id,
                 accounts,
                 account_guid_hash,
                 dapp,
                 instructions,
+                rent_funding_limit,
             ),
 
             ProgramInstruction::FinalizeDAppTransaction {
                 ref account_guid_hash,
                 dapp,
                 ref instructions,
+                rent_funding_limit,
             } => dapp_transaction_handler::finalize(
                 program_id,
                 accounts,
                 account_guid_hash,
                 dapp,
                 instructions,
+                rent_funding_limit,
+            ),
+
+            ProgramInstruction::ExecuteDAppChunk {
+                ref account_guid_hash,
+                dapp,
+                ref instructions,
+                rent_funding_limit,
+                chunk_len,
+            } => dapp_transaction_handler::execute_chunk(
+                program_id,
+                accounts,
+                account_guid_hash,
+                dapp,
+                instructions,
+                rent_funding_limit,
+                chunk_len,
             ),
 
             ProgramInstruction::InitAccountSettingsUpdate {
                 account_guid_hash,
                 whitelist_enabled,
                 dapps_enabled,
+                sol_transfers_enabled,
+                spl_transfers_enabled,
             } => balance_account_settings_update_handler::init(
                 program_id,
                 &accounts,
                 &account_guid_hash,
                 whitelist_enabled,
                 dapps_enabled,
+                sol_transfers_enabled,
+                spl_transfers_enabled,
             ),
 
             ProgramInstruction::FinalizeAccountSettingsUpdate {
                 account_guid_hash,
                 whitelist_enabled,
                 dapps_enabled,
+                sol_transfers_enabled,
+                spl_transfers_enabled,
             } => balance_account_settings_update_handler::finalize(
                 program_id,
                 &accounts,
                 &account_guid_hash,
                 whitelist_enabled,
                 dapps_enabled,
+                sol_transfers_enabled,
+                spl_transfers_enabled,
             ),
 
             ProgramInstruction::InitDAppBookUpdate { update } => {
@@ -236,6 +455,417 @@ impl Processor {
             ProgramInstruction::FinalizeAddressBookUpdate { update } => {
                 address_book_update_handler::finalize(program_id, accounts, &update)
             }
+
+            ProgramInstruction::InitAddressBookVerification { entries } => {
+                address_book_verification_handler::init(program_id, accounts, &entries)
+            }
+
+            ProgramInstruction::FinalizeAddressBookVerification { entries } => {
+                address_book_verification_handler::finalize(program_id, accounts, &entries)
+            }
+
+            ProgramInstruction::InitActivityCheckpoint { checkpoint_index } => {
+                activity_checkpoint_handler::init(program_id, accounts, checkpoint_index)
+            }
+
+            ProgramInstruction::FinalizeActivityCheckpoint { checkpoint_index } => {
+                activity_checkpoint_handler::finalize(program_id, accounts, checkpoint_index)
+            }
+
+            ProgramInstruction::InitProgramConfig { update } => {
+                program_config_handler::init(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::UpdateProgramConfig { update } => {
+                program_config_handler::update(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::InitAddressBookPageUpdate { page_index, update } => {
+                address_book_page_update_handler::init(program_id, accounts, page_index, &update)
+            }
+
+            ProgramInstruction::FinalizeAddressBookPageUpdate { page_index, update } => {
+                address_book_page_update_handler::finalize(
+                    program_id, accounts, page_index, &update,
+                )
+            }
+
+            ProgramInstruction::PreflightTransfer {
+                account_guid_hash,
+                amount,
+                token_mint,
+                destination_name_hash,
+            } => transfer_handler::preflight(
+                program_id,
+                &accounts,
+                &account_guid_hash,
+                amount,
+                token_mint,
+                &destination_name_hash,
+                program_config,
+            ),
+
+            ProgramInstruction::RenewMultisigOp {
+                params_hash,
+                extension,
+            } => multisig_op_renewal_handler::handle(program_id, accounts, &params_hash, extension),
+
+            ProgramInstruction::CreateOpAccount => {
+                op_account_creation_handler::handle(program_id, accounts)
+            }
+
+            ProgramInstruction::CreateOpAccountForParams { params_hash, nonce } => {
+                op_account_creation_handler::handle_for_params(
+                    program_id,
+                    accounts,
+                    &params_hash,
+                    nonce,
+                )
+            }
+
+            ProgramInstruction::InitApproverDelegationUpdate { update } => {
+                approver_delegation_update_handler::init(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::FinalizeApproverDelegationUpdate { update } => {
+                approver_delegation_update_handler::finalize(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::InitSignerDirectory => {
+                signer_directory_handler::init(program_id, accounts)
+            }
+
+            ProgramInstruction::UpdateSignerDirectory { update } => {
+                signer_directory_handler::update(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::InitWalletSignerDirectoryUpdate { update } => {
+                wallet_signer_directory_update_handler::init(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::FinalizeWalletSignerDirectoryUpdate { update } => {
+                wallet_signer_directory_update_handler::finalize(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::InitWalletCanaryUpdate { canary_key } => {
+                wallet_canary_handler::init(program_id, accounts, &canary_key)
+            }
+
+            ProgramInstruction::FinalizeWalletCanaryUpdate { canary_key } => {
+                wallet_canary_handler::finalize(program_id, accounts, &canary_key)
+            }
+
+            ProgramInstruction::InitInternalTransfer {
+                source_account_guid_hash,
+                destination_account_guid_hash,
+                amount,
+                approval_timeout,
+            } => internal_transfer_handler::init(
+                program_id,
+                accounts,
+                &source_account_guid_hash,
+                &destination_account_guid_hash,
+                amount,
+                approval_timeout,
+                &limits,
+            ),
+
+            ProgramInstruction::FinalizeInternalTransfer {
+                source_account_guid_hash,
+                destination_account_guid_hash,
+                amount,
+                token_mint,
+            } => internal_transfer_handler::finalize(
+                program_id,
+                accounts,
+                &source_account_guid_hash,
+                &destination_account_guid_hash,
+                amount,
+                token_mint,
+            ),
+
+            ProgramInstruction::AssertPolicyManifest { manifest_hash } => {
+                policy_manifest_handler::handle(program_id, accounts, &manifest_hash)
+            }
+
+            ProgramInstruction::ReportCapacity {
+                warning_threshold_percent,
+            } => capacity_report_handler::handle(program_id, accounts, warning_threshold_percent),
+
+            ProgramInstruction::ReportSignerActivity {
+                inactive_after_days,
+            } => signer_activity_report_handler::handle(program_id, accounts, inactive_after_days),
+
+            ProgramInstruction::InitGovernedParameterUpdate { update } => {
+                governed_parameter_update_handler::init(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::FinalizeGovernedParameterUpdate { update } => {
+                governed_parameter_update_handler::finalize(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::InitViewersUpdate { update } => {
+                viewer_handler::init(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::FinalizeViewersUpdate { update } => {
+                viewer_handler::finalize(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::VerifyAccess => viewer_handler::verify_access(program_id, accounts),
+
+            ProgramInstruction::InitSignerOrganizationsUpdate { update } => {
+                signer_organizations_update_handler::init(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::FinalizeSignerOrganizationsUpdate { update } => {
+                signer_organizations_update_handler::finalize(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::InitAssistantUpdate { assistant } => {
+                assistant_update_handler::init(program_id, accounts, &assistant)
+            }
+
+            ProgramInstruction::FinalizeAssistantUpdate { assistant } => {
+                assistant_update_handler::finalize(program_id, accounts, &assistant)
+            }
+
+            ProgramInstruction::InitAddressBookUpdateChunked {
+                update_hash,
+                update_len,
+            } => address_book_update_chunked_handler::init(
+                program_id,
+                accounts,
+                &update_hash,
+                update_len,
+            ),
+
+            ProgramInstruction::SupplyAddressBookChunk { offset, chunk } => {
+                address_book_update_chunked_handler::supply(program_id, accounts, offset, &chunk)
+            }
+
+            ProgramInstruction::FinalizeAddressBookUpdateChunked { update_hash } => {
+                address_book_update_chunked_handler::finalize(program_id, accounts, &update_hash)
+            }
+
+            ProgramInstruction::ConsolidateDeposits {
+                account_guid_hash,
+                deposit_indexes,
+            } => deposit_consolidation_handler::handle(
+                program_id,
+                accounts,
+                &account_guid_hash,
+                &deposit_indexes,
+            ),
+
+            ProgramInstruction::InitPendingOps => pending_ops_handler::init(program_id, accounts),
+
+            ProgramInstruction::CleanupPendingOps => {
+                pending_ops_handler::cleanup(program_id, accounts)
+            }
+
+            ProgramInstruction::ValidateOnly { instruction } => {
+                Self::validate_only(program_id, accounts, *instruction, program_config)
+            }
+
+            ProgramInstruction::InitWithPrerequisite {
+                prerequisite,
+                instruction,
+            } => Self::init_with_prerequisite(
+                program_id,
+                accounts,
+                &prerequisite,
+                *instruction,
+                program_config,
+            ),
+
+            ProgramInstruction::InitWithIdempotencyKey {
+                idempotency_key,
+                instruction,
+            } => Self::init_with_idempotency_key(
+                program_id,
+                accounts,
+                idempotency_key,
+                *instruction,
+                program_config,
+            ),
+
+            ProgramInstruction::InitWalletStateExport { target_program_id } => {
+                wallet_export_handler::init(
+                    program_id,
+                    accounts,
+                    &target_program_id,
+                    program_config,
+                )
+            }
+
+            ProgramInstruction::FinalizeWalletStateExport { target_program_id } => {
+                wallet_export_handler::finalize(
+                    program_id,
+                    accounts,
+                    &target_program_id,
+                    program_config,
+                )
+            }
+
+            ProgramInstruction::ImportWalletState => {
+                wallet_export_handler::import(program_id, accounts, program_config)
+            }
+
+            ProgramInstruction::VerifyNameHash { entry, name } => {
+                name_verification_handler::handle(program_id, accounts, &entry, &name)
+            }
+
+            ProgramInstruction::ExpireOp => {
+                multisig_op_expiry_handler::handle(program_id, accounts)
+            }
+
+            ProgramInstruction::InitCombinedTransfer {
+                account_guid_hash,
+                destination_name_hash,
+                transfer,
+            } => combined_transfer_handler::init(
+                program_id,
+                accounts,
+                &account_guid_hash,
+                &destination_name_hash,
+                &transfer,
+            ),
+
+            ProgramInstruction::FinalizeCombinedTransfer {
+                account_guid_hash,
+                transfer,
+            } => combined_transfer_handler::finalize(
+                program_id,
+                accounts,
+                &account_guid_hash,
+                &transfer,
+            ),
+
+            ProgramInstruction::InitWalletMetadataUpdate { metadata_hash } => {
+                wallet_metadata_update_handler::init(program_id, accounts, &metadata_hash)
+            }
+
+            ProgramInstruction::FinalizeWalletMetadataUpdate { metadata_hash } => {
+                wallet_metadata_update_handler::finalize(program_id, accounts, &metadata_hash)
+            }
+
+            ProgramInstruction::SubmitApprovalDisposition {
+                disposition,
+                params_hash,
+                denial_reason,
+                reason_hash,
+            } => pending_disposition_handler::submit(
+                program_id,
+                accounts,
+                disposition,
+                params_hash,
+                denial_reason,
+                reason_hash,
+            ),
+
+            ProgramInstruction::VerifyCoSignature {
+                disposition,
+                params_hash,
+                denial_reason,
+                reason_hash,
+            } => co_signature_handler::handle(
+                program_id,
+                accounts,
+                disposition,
+                params_hash,
+                denial_reason,
+                reason_hash,
+            ),
+
+            ProgramInstruction::CollectApprovalDispositions { dispositions } => {
+                pending_disposition_handler::collect(program_id, accounts, dispositions)
+            }
+
+            ProgramInstruction::InitDAppTemplatesUpdate { update } => {
+                dapp_template_update_handler::init(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::FinalizeDAppTemplatesUpdate { update } => {
+                dapp_template_update_handler::finalize(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::ExecuteDAppTemplate {
+                template_slot,
+                instruction,
+            } => dapp_transaction_handler::execute_template(
+                program_id,
+                accounts,
+                template_slot,
+                &instruction,
+            ),
+
+            ProgramInstruction::InitWalletClose { rent_collector } => {
+                wallet_close_handler::init(program_id, accounts, &rent_collector)
+            }
+
+            ProgramInstruction::FinalizeWalletClose { rent_collector } => {
+                wallet_close_handler::finalize(program_id, accounts, &rent_collector)
+            }
         }
     }
+
+    fn validate_only(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction: ProgramInstruction,
+        program_config: Option<&ProgramConfig>,
+    ) -> ProgramResult {
+        if !supports_validate_only(&instruction) {
+            msg!("Instruction can't be run in validate-only mode");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Self::dispatch(program_id, accounts, instruction, program_config)?;
+
+        // fail on purpose so that nothing the instruction wrote is kept
+        msg!("Validation succeeded");
+        Err(WalletError::SimulationFinished.into())
+    }
+
+    fn init_with_prerequisite(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        prerequisite: &Hash,
+        instruction: ProgramInstruction,
+        program_config: Option<&ProgramConfig>,
+    ) -> ProgramResult {
+        if MultisigOp::space_required(&instruction).is_none() {
+            msg!("Only instructions that start an op can have a prerequisite");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Self::dispatch(program_id, accounts, instruction, program_config)?;
+        pending_ops_handler::set_prerequisite(program_id, accounts, prerequisite)
+    }
+
+    fn init_with_idempotency_key(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        idempotency_key: IdempotencyKey,
+        instruction: ProgramInstruction,
+        program_config: Option<&ProgramConfig>,
+    ) -> ProgramResult {
+        if MultisigOp::space_required(&instruction).is_none() {
+            msg!("Only instructions that start an op can have an idempotency key");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        // the wallet account follows the op account in every init instruction
+        let accounts_iter = &mut accounts.iter().skip(1);
+        let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+        Wallet::unpack(&wallet_account_info.data.borrow())?
+            .validate_idempotency_key(&idempotency_key)?;
+
+        Self::dispatch(program_id, accounts, instruction, program_config)?;
+
+        // unpacked again, as the wrapped instruction may have written the wallet
+        let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+        wallet.record_idempotency_key(idempotency_key);
+        Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())
+    }
 }
@@ -72,6 +72,30 @@ pub fn append_optional_u8(maybe_u8: &Option<u8>, dst: &mut Vec<u8>) {
     }
 }
 
+pub fn read_optional_u64(iter: &mut Iter<u8>) -> Result<Option<u64>, ProgramError> {
+    if let Some(has_value) = iter.next() {
+        let value_data =
+            read_fixed_size_array::<8>(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        Ok(if *has_value == 0 {
+            None
+        } else {
+            Some(u64::from_le_bytes(*value_data))
+        })
+    } else {
+        Err(ProgramError::InvalidInstructionData)
+    }
+}
+
+pub fn append_optional_u64(maybe_u64: &Option<u64>, dst: &mut Vec<u8>) {
+    if let Some(value) = maybe_u64 {
+        dst.push(1);
+        dst.extend_from_slice(&value.to_le_bytes()[..]);
+    } else {
+        dst.push(0);
+        dst.extend_from_slice(&[0; 8]);
+    }
+}
+
 pub fn read_u8<'a>(iter: &'a mut Iter<u8>) -> Option<&'a u8> {
     iter.next()
 }
@@ -86,8 +110,16 @@ pub fn read_fixed_size_array<'a, const SIZE: usize>(
     read_slice(iter, SIZE).and_then(|slice| slice.try_into().ok())
 }
 
+/// Upper bound on any duration carried by an instruction. Policy limits are
+/// enforced by the handlers; this only keeps values that no policy could
+/// accept (and that would overflow timestamp arithmetic) from being decoded.
+pub const MAX_DURATION: Duration = Duration::from_secs(60 * 60 * 24 * 365 * 10);
+
+/// Returns None if the data is too short or the duration exceeds `MAX_DURATION`.
 pub fn read_duration(iter: &mut Iter<u8>) -> Option<Duration> {
-    read_fixed_size_array::<8>(iter).map(|slice| Duration::from_secs(u64::from_le_bytes(*slice)))
+    read_fixed_size_array::<8>(iter)
+        .map(|slice| Duration::from_secs(u64::from_le_bytes(*slice)))
+        .filter(|duration| *duration <= MAX_DURATION)
 }
 
 pub fn append_duration(duration: &Duration, dst: &mut Vec<u8>) {
@@ -96,14 +128,16 @@ pub fn append_duration(duration: &Duration, dst: &mut Vec<u8>) {
 
 pub fn read_optional_duration(iter: &mut Iter<u8>) -> Result<Option<Duration>, ProgramError> {
     if let Some(has_value) = iter.next() {
-        let value_data = read_fixed_size_array::<8>(iter)
-            .ok_or(ProgramError::InvalidInstructionData)
-            .unwrap();
-        Ok(if *has_value == 0 {
-            None
-        } else {
-            Some(Duration::from_secs(u64::from_le_bytes(*value_data)))
-        })
+        let value_data =
+            read_fixed_size_array::<8>(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        if *has_value == 0 {
+            return Ok(None);
+        }
+        let duration = Duration::from_secs(u64::from_le_bytes(*value_data));
+        if duration > MAX_DURATION {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(Some(duration))
     } else {
         Err(ProgramError::InvalidInstructionData)
     }
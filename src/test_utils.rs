@@ -0,0 +1,10 @@
+//! A banks-client harness for integration tests, behind the `test-utils`
+//! feature: `TestContext` setups that start the program in a `ProgramTest`
+//! with a wallet, balance accounts and approvers ready to go. Programs and
+//! services integrating with the wallet can drive the same scenarios as the
+//! wallet's own tests. The instruction builders live in
+//! [`crate::instruction::builders`] and are re-exported here as
+//! `instructions`.
+
+pub use crate::instruction::builders as instructions;
+pub mod utils;
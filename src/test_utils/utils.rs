@@ -1,14 +1,40 @@
-use crate::common::instructions;
-use crate::common::instructions::{
+use crate::client::{
+    create_address_book_update_buffer_account, create_op_account, DEFAULT_LAMPORTS_PER_SIGNATURE,
+};
+use crate::instruction::{
+    AddressBookUpdate, ApproverDelegationUpdate, BalanceAccountCreation,
+    BalanceAccountPolicyUpdate, BalanceAccountWhitelistUpdate, DAppBookUpdate,
+    GovernedParameterUpdate, InitialWalletConfig, ProgramInstruction, SignerOrganizationsUpdate,
+    SignersUpdate, ViewersUpdate, WalletConfigPolicyUpdate,
+};
+use crate::model::activity::ActivityCheckpoint;
+use crate::model::address_book::{
+    AddressBookEntry, AddressBookEntryNameHash, DAppBookEntry, DAppBookEntryNameHash,
+    DAppInstructionPrefixes,
+};
+use crate::model::address_book_page::AddressBookPage;
+use crate::model::address_book_update_buffer::AddressBookUpdateBuffer;
+use crate::model::balance_account::{BalanceAccountGuidHash, BalanceAccountNameHash};
+use crate::model::multisig_op::{
+    ApprovalDisposition, ApprovalDispositionRecord, BooleanSetting, DenialReason, MultisigOp,
+    MultisigOpParams, OperationDisposition, SlotUpdateType, WrapDirection,
+};
+use crate::model::signer::Signer;
+use crate::model::wallet::Signers;
+use crate::test_utils::instructions;
+use crate::test_utils::instructions::{
     finalize_account_settings_update, finalize_balance_account_name_update, finalize_update_signer,
     finalize_wallet_config_policy_update_instruction, init_account_settings_update,
     init_balance_account_creation, init_balance_account_name_update, init_transfer,
     init_update_signer, init_wallet_config_policy_update_instruction, set_approval_disposition,
 };
-use crate::{
-    finalize_address_book_update, finalize_balance_account_policy_update_instruction,
-    init_address_book_update, init_balance_account_policy_update_instruction,
+use crate::test_utils::instructions::{
+    finalize_activity_checkpoint, finalize_address_book_page_update, finalize_address_book_update,
+    finalize_address_book_verification, finalize_balance_account_policy_update_instruction,
+    init_activity_checkpoint, init_address_book_page_update, init_address_book_update,
+    init_address_book_verification, init_balance_account_policy_update_instruction,
 };
+use crate::utils::SlotId;
 use arrayref::array_ref;
 use itertools::Itertools;
 use sha2::{Digest, Sha256};
@@ -23,23 +49,9 @@ use std::borrow::BorrowMut;
 use std::collections::HashSet;
 use std::fmt::Debug;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use strike_wallet::instruction::{
-    AddressBookUpdate, BalanceAccountCreation, BalanceAccountPolicyUpdate,
-    BalanceAccountWhitelistUpdate, DAppBookUpdate, InitialWalletConfig, WalletConfigPolicyUpdate,
-};
-use strike_wallet::model::address_book::{
-    AddressBookEntry, AddressBookEntryNameHash, DAppBookEntry, DAppBookEntryNameHash,
-};
-use strike_wallet::model::balance_account::{BalanceAccountGuidHash, BalanceAccountNameHash};
-use strike_wallet::model::multisig_op::{
-    ApprovalDisposition, ApprovalDispositionRecord, BooleanSetting, MultisigOp, MultisigOpParams,
-    OperationDisposition, SlotUpdateType, WrapDirection,
-};
-use strike_wallet::model::signer::Signer;
-use strike_wallet::model::wallet::Signers;
-use strike_wallet::utils::SlotId;
 use uuid::Uuid;
 use {
+    crate::{model::wallet::Wallet, processor::Processor},
     solana_program::{program_pack::Pack, pubkey::Pubkey},
     solana_program_test::BanksClient,
     solana_sdk::{
@@ -49,7 +61,6 @@ use {
         transaction::Transaction,
         transport::TransportError,
     },
-    strike_wallet::{model::wallet::Wallet, processor::Processor},
 };
 
 pub trait SignerKey {
@@ -132,11 +143,14 @@ pub async fn init_multisig_op(
         .banks_client
         .process_transaction(Transaction::new_signed_with_payer(
             &[
-                create_program_owned_account_instruction(
-                    &test_context,
+                create_op_account(
+                    &test_context.program_id,
+                    &test_context.payer.pubkey(),
                     &multisig_op_account.pubkey(),
-                    MultisigOp::LEN,
-                ),
+                    &test_context.rent,
+                    &ProgramInstruction::unpack(&instruction.data).unwrap(),
+                )
+                .unwrap(),
                 instruction,
             ],
             Some(&test_context.payer.pubkey()),
@@ -182,7 +196,7 @@ pub async fn finalize_multisig_op(
         .unwrap()
         .is_none());
 
-    // and that the remaining balance went to the rent collector (less the 5000 in fees for the finalize)
+    // and that the remaining balance went to the rent collector (less the signature fee for the finalize)
     let ending_rent_collector_balance = test_context
         .banks_client
         .get_balance(test_context.payer.pubkey())
@@ -190,7 +204,7 @@ pub async fn finalize_multisig_op(
         .unwrap();
 
     assert_eq!(
-        starting_rent_collector_balance + op_account_balance - 5000,
+        starting_rent_collector_balance + op_account_balance - DEFAULT_LAMPORTS_PER_SIGNATURE,
         ending_rent_collector_balance
     );
 }
@@ -451,10 +465,16 @@ pub async fn update_signer(
             ApprovalDispositionRecord {
                 approver: context.approvers[0].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                denial_reason: DenialReason::None,
+                reason_hash: None,
+                disposition_changes: 0,
             },
             ApprovalDispositionRecord {
                 approver: context.approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                denial_reason: DenialReason::None,
+                reason_hash: None,
+                disposition_changes: 0,
             },
         ])
     );
@@ -545,14 +565,14 @@ pub async fn update_signer(
         .await
         .unwrap()
         .is_none());
-    // and that the remaining balance went to the rent collector (less the 5000 in fees for the finalize)
+    // and that the remaining balance went to the rent collector (less the signature fee for the finalize)
     let ending_rent_collector_balance = context
         .banks_client
         .get_balance(context.payer.pubkey())
         .await
         .unwrap();
     assert_eq!(
-        starting_rent_collector_balance + op_account_balance - 5000,
+        starting_rent_collector_balance + op_account_balance - DEFAULT_LAMPORTS_PER_SIGNATURE,
         ending_rent_collector_balance
     );
 }
@@ -562,6 +582,25 @@ pub async fn account_settings_update(
     whitelist_status: Option<BooleanSetting>,
     dapps_enabled: Option<BooleanSetting>,
     expected_error: Option<InstructionError>,
+) {
+    update_account_settings(
+        context,
+        whitelist_status,
+        dapps_enabled,
+        None,
+        None,
+        expected_error,
+    )
+    .await
+}
+
+pub async fn update_account_settings(
+    context: &mut BalanceAccountTestContext,
+    whitelist_status: Option<BooleanSetting>,
+    dapps_enabled: Option<BooleanSetting>,
+    sol_transfers_enabled: Option<BooleanSetting>,
+    spl_transfers_enabled: Option<BooleanSetting>,
+    expected_error: Option<InstructionError>,
 ) {
     let rent = context.banks_client.get_rent().await.unwrap();
     let multisig_op_rent = rent.minimum_balance(MultisigOp::LEN);
@@ -583,6 +622,8 @@ pub async fn account_settings_update(
                 context.balance_account_guid_hash,
                 whitelist_status,
                 dapps_enabled,
+                sol_transfers_enabled,
+                spl_transfers_enabled,
             ),
         ],
         Some(&context.payer.pubkey()),
@@ -631,10 +672,16 @@ pub async fn account_settings_update(
             ApprovalDispositionRecord {
                 approver: context.approvers[0].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                denial_reason: DenialReason::None,
+                reason_hash: None,
+                disposition_changes: 0,
             },
             ApprovalDispositionRecord {
                 approver: context.approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                denial_reason: DenialReason::None,
+                reason_hash: None,
+                disposition_changes: 0,
             },
         ])
     );
@@ -651,6 +698,8 @@ pub async fn account_settings_update(
             account_guid_hash: context.balance_account_guid_hash,
             whitelist_enabled: whitelist_status,
             dapps_enabled,
+            sol_transfers_enabled,
+            spl_transfers_enabled,
         }
         .hash()
     );
@@ -693,6 +742,8 @@ pub async fn account_settings_update(
             context.balance_account_guid_hash,
             whitelist_status,
             dapps_enabled,
+            sol_transfers_enabled,
+            spl_transfers_enabled,
         )],
         Some(&context.payer.pubkey()),
         &[&context.payer],
@@ -722,14 +773,14 @@ pub async fn account_settings_update(
         .await
         .unwrap()
         .is_none());
-    // and that the remaining balance went to the rent collector (less the 5000 in fees for the finalize)
+    // and that the remaining balance went to the rent collector (less the signature fee for the finalize)
     let ending_rent_collector_balance = context
         .banks_client
         .get_balance(context.payer.pubkey())
         .await
         .unwrap();
     assert_eq!(
-        starting_rent_collector_balance + op_account_balance - 5000,
+        starting_rent_collector_balance + op_account_balance - DEFAULT_LAMPORTS_PER_SIGNATURE,
         ending_rent_collector_balance
     );
 }
@@ -756,21 +807,436 @@ pub async fn init_dapp_book_update(
         .map(|_| multisig_op_pubkey)
 }
 
-pub async fn finalize_dapp_book_update(
+pub async fn finalize_dapp_book_update(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    multisig_op_account: Pubkey,
+    update: DAppBookUpdate,
+) {
+    finalize_multisig_op(
+        test_context,
+        multisig_op_account,
+        instructions::finalize_dapp_book_update(
+            &test_context.program_id,
+            &wallet_account,
+            &multisig_op_account,
+            &test_context.payer.pubkey(),
+            update,
+        ),
+    )
+    .await;
+}
+
+pub async fn init_approver_delegation_update(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    initiator: &Keypair,
+    update: ApproverDelegationUpdate,
+) -> Result<Pubkey, TransportError> {
+    let multisig_op_keypair = Keypair::new();
+    let multisig_op_pubkey = multisig_op_keypair.pubkey();
+
+    let instruction = instructions::init_approver_delegation_update(
+        &test_context.program_id,
+        &wallet_account,
+        &multisig_op_pubkey,
+        &initiator.pubkey(),
+        update,
+    );
+
+    init_multisig_op(test_context, multisig_op_keypair, instruction, initiator)
+        .await
+        .map(|_| multisig_op_pubkey)
+}
+
+pub async fn finalize_approver_delegation_update(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    multisig_op_account: Pubkey,
+    update: ApproverDelegationUpdate,
+) {
+    finalize_multisig_op(
+        test_context,
+        multisig_op_account,
+        instructions::finalize_approver_delegation_update(
+            &test_context.program_id,
+            &wallet_account,
+            &multisig_op_account,
+            &test_context.payer.pubkey(),
+            update,
+        ),
+    )
+    .await;
+}
+
+pub async fn init_update_signers(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    initiator: &Keypair,
+    update: SignersUpdate,
+) -> Result<Pubkey, TransportError> {
+    let multisig_op_keypair = Keypair::new();
+    let multisig_op_pubkey = multisig_op_keypair.pubkey();
+
+    let instruction = instructions::init_update_signers(
+        &test_context.program_id,
+        &wallet_account,
+        &multisig_op_pubkey,
+        &initiator.pubkey(),
+        update,
+    );
+
+    init_multisig_op(test_context, multisig_op_keypair, instruction, initiator)
+        .await
+        .map(|_| multisig_op_pubkey)
+}
+
+pub async fn finalize_update_signers(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    multisig_op_account: Pubkey,
+    update: SignersUpdate,
+) {
+    finalize_multisig_op(
+        test_context,
+        multisig_op_account,
+        instructions::finalize_update_signers(
+            &test_context.program_id,
+            &wallet_account,
+            &multisig_op_account,
+            &test_context.payer.pubkey(),
+            update,
+        ),
+    )
+    .await;
+}
+
+pub async fn init_viewers_update(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    initiator: &Keypair,
+    update: ViewersUpdate,
+) -> Result<Pubkey, TransportError> {
+    let multisig_op_keypair = Keypair::new();
+    let multisig_op_pubkey = multisig_op_keypair.pubkey();
+
+    let instruction = instructions::init_viewers_update(
+        &test_context.program_id,
+        &wallet_account,
+        &multisig_op_pubkey,
+        &initiator.pubkey(),
+        update,
+    );
+
+    init_multisig_op(test_context, multisig_op_keypair, instruction, initiator)
+        .await
+        .map(|_| multisig_op_pubkey)
+}
+
+pub async fn finalize_viewers_update(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    multisig_op_account: Pubkey,
+    update: ViewersUpdate,
+) {
+    finalize_multisig_op(
+        test_context,
+        multisig_op_account,
+        instructions::finalize_viewers_update(
+            &test_context.program_id,
+            &wallet_account,
+            &multisig_op_account,
+            &test_context.payer.pubkey(),
+            update,
+        ),
+    )
+    .await;
+}
+
+pub async fn init_signer_organizations_update(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    initiator: &Keypair,
+    update: SignerOrganizationsUpdate,
+) -> Result<Pubkey, TransportError> {
+    let multisig_op_keypair = Keypair::new();
+    let multisig_op_pubkey = multisig_op_keypair.pubkey();
+
+    let instruction = instructions::init_signer_organizations_update(
+        &test_context.program_id,
+        &wallet_account,
+        &multisig_op_pubkey,
+        &initiator.pubkey(),
+        update,
+    );
+
+    init_multisig_op(test_context, multisig_op_keypair, instruction, initiator)
+        .await
+        .map(|_| multisig_op_pubkey)
+}
+
+pub async fn finalize_signer_organizations_update(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    multisig_op_account: Pubkey,
+    update: SignerOrganizationsUpdate,
+) {
+    finalize_multisig_op(
+        test_context,
+        multisig_op_account,
+        instructions::finalize_signer_organizations_update(
+            &test_context.program_id,
+            &wallet_account,
+            &multisig_op_account,
+            &test_context.payer.pubkey(),
+            update,
+        ),
+    )
+    .await;
+}
+
+pub async fn init_assistant_update(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    initiator: &Keypair,
+    assistant: Pubkey,
+) -> Result<Pubkey, TransportError> {
+    let multisig_op_keypair = Keypair::new();
+    let multisig_op_pubkey = multisig_op_keypair.pubkey();
+
+    let instruction = instructions::init_assistant_update(
+        &test_context.program_id,
+        &wallet_account,
+        &multisig_op_pubkey,
+        &initiator.pubkey(),
+        assistant,
+    );
+
+    init_multisig_op(test_context, multisig_op_keypair, instruction, initiator)
+        .await
+        .map(|_| multisig_op_pubkey)
+}
+
+pub async fn finalize_assistant_update(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    multisig_op_account: Pubkey,
+    assistant: Pubkey,
+) {
+    finalize_multisig_op(
+        test_context,
+        multisig_op_account,
+        instructions::finalize_assistant_update(
+            &test_context.program_id,
+            &wallet_account,
+            &multisig_op_account,
+            &test_context.payer.pubkey(),
+            assistant,
+        ),
+    )
+    .await;
+}
+
+/// Starts a chunked address book update and supplies it in chunks of
+/// `chunk_len` bytes, one transaction per chunk. Returns the op and buffer
+/// accounts.
+pub async fn init_address_book_update_chunked(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    initiator: &Keypair,
+    update: &AddressBookUpdate,
+    chunk_len: usize,
+) -> Result<(Pubkey, Pubkey), TransportError> {
+    let multisig_op_keypair = Keypair::new();
+    let multisig_op_pubkey = multisig_op_keypair.pubkey();
+    let buffer_keypair = Keypair::new();
+    let buffer_pubkey = buffer_keypair.pubkey();
+
+    let mut update_bytes = Vec::new();
+    update.pack(&mut update_bytes);
+    let update_hash = AddressBookUpdateBuffer::hash_update(&update_bytes);
+    let update_len = update_bytes.len() as u32;
+
+    let instruction = instructions::init_address_book_update_chunked(
+        &test_context.program_id,
+        &wallet_account,
+        &multisig_op_pubkey,
+        &initiator.pubkey(),
+        &buffer_pubkey,
+        update_hash,
+        update_len,
+    );
+    test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                create_op_account(
+                    &test_context.program_id,
+                    &test_context.payer.pubkey(),
+                    &multisig_op_pubkey,
+                    &test_context.rent,
+                    &ProgramInstruction::unpack(&instruction.data).unwrap(),
+                )
+                .unwrap(),
+                create_address_book_update_buffer_account(
+                    &test_context.program_id,
+                    &test_context.payer.pubkey(),
+                    &buffer_pubkey,
+                    &test_context.rent,
+                    update_len,
+                ),
+                instruction,
+            ],
+            Some(&test_context.payer.pubkey()),
+            &[
+                &test_context.payer,
+                &multisig_op_keypair,
+                &buffer_keypair,
+                initiator,
+            ],
+            test_context.recent_blockhash,
+        ))
+        .await?;
+
+    for (index, chunk) in update_bytes.chunks(chunk_len).enumerate() {
+        test_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[instructions::supply_address_book_chunk(
+                    &test_context.program_id,
+                    &buffer_pubkey,
+                    &initiator.pubkey(),
+                    (index * chunk_len) as u32,
+                    chunk.to_vec(),
+                )],
+                Some(&test_context.payer.pubkey()),
+                &[&test_context.payer, initiator],
+                test_context.recent_blockhash,
+            ))
+            .await?;
+    }
+
+    Ok((multisig_op_pubkey, buffer_pubkey))
+}
+
+pub async fn finalize_address_book_update_chunked(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    multisig_op_account: Pubkey,
+    buffer_account: Pubkey,
+    update: &AddressBookUpdate,
+) {
+    let mut update_bytes = Vec::new();
+    update.pack(&mut update_bytes);
+
+    test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[instructions::finalize_address_book_update_chunked(
+                &test_context.program_id,
+                &wallet_account,
+                &multisig_op_account,
+                &test_context.payer.pubkey(),
+                &buffer_account,
+                AddressBookUpdateBuffer::hash_update(&update_bytes),
+            )],
+            Some(&test_context.payer.pubkey()),
+            &[&test_context.payer],
+            test_context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // both the op and the buffer account are closed
+    for account in vec![multisig_op_account, buffer_account] {
+        assert!(test_context
+            .banks_client
+            .get_account(account)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
+
+pub async fn init_governed_parameter_update(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    initiator: &Keypair,
+    update: GovernedParameterUpdate,
+) -> Result<Pubkey, TransportError> {
+    let multisig_op_keypair = Keypair::new();
+    let multisig_op_pubkey = multisig_op_keypair.pubkey();
+
+    let instruction = instructions::init_governed_parameter_update(
+        &test_context.program_id,
+        &wallet_account,
+        &multisig_op_pubkey,
+        &initiator.pubkey(),
+        update,
+    );
+
+    init_multisig_op(test_context, multisig_op_keypair, instruction, initiator)
+        .await
+        .map(|_| multisig_op_pubkey)
+}
+
+pub async fn finalize_governed_parameter_update(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    multisig_op_account: Pubkey,
+    update: GovernedParameterUpdate,
+) {
+    finalize_multisig_op(
+        test_context,
+        multisig_op_account,
+        instructions::finalize_governed_parameter_update(
+            &test_context.program_id,
+            &wallet_account,
+            &multisig_op_account,
+            &test_context.payer.pubkey(),
+            update,
+        ),
+    )
+    .await;
+}
+
+pub async fn init_wallet_canary_update(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    initiator: &Keypair,
+    canary_key: Option<Pubkey>,
+) -> Result<Pubkey, TransportError> {
+    let multisig_op_keypair = Keypair::new();
+    let multisig_op_pubkey = multisig_op_keypair.pubkey();
+
+    let instruction = instructions::init_wallet_canary_update(
+        &test_context.program_id,
+        &wallet_account,
+        &multisig_op_pubkey,
+        &initiator.pubkey(),
+        canary_key,
+    );
+
+    init_multisig_op(test_context, multisig_op_keypair, instruction, initiator)
+        .await
+        .map(|_| multisig_op_pubkey)
+}
+
+pub async fn finalize_wallet_canary_update(
     test_context: &mut TestContext,
     wallet_account: Pubkey,
     multisig_op_account: Pubkey,
-    update: DAppBookUpdate,
+    canary_key: Option<Pubkey>,
 ) {
     finalize_multisig_op(
         test_context,
         multisig_op_account,
-        instructions::finalize_dapp_book_update(
+        instructions::finalize_wallet_canary_update(
             &test_context.program_id,
             &wallet_account,
             &multisig_op_account,
             &test_context.payer.pubkey(),
-            update,
+            canary_key,
         ),
     )
     .await;
@@ -827,13 +1293,15 @@ pub async fn approve_or_deny_n_of_n_multisig_op(
     disposition: ApprovalDisposition,
     expected_operation_disposition: OperationDisposition,
 ) {
-    let params_hash = get_operation_hash(banks_client.borrow_mut(), *multisig_op_account).await;
+    let multisig_op = get_multisig_op_data(banks_client.borrow_mut(), *multisig_op_account).await;
+    let params_hash = multisig_op.params_hash;
 
     // approve the config change
     for approver in approvers.iter() {
         let approve_transaction = Transaction::new_signed_with_payer(
             &[set_approval_disposition(
                 program_id,
+                &multisig_op.wallet_address,
                 multisig_op_account,
                 &approver.pubkey(),
                 disposition,
@@ -866,6 +1334,9 @@ pub async fn approve_or_deny_n_of_n_multisig_op(
             .map(|approver| ApprovalDispositionRecord {
                 approver: approver.pubkey(),
                 disposition,
+                denial_reason: DenialReason::None,
+                reason_hash: None,
+                disposition_changes: 0,
             })
             .collect_vec()
             .to_set()
@@ -922,12 +1393,14 @@ pub async fn approve_or_deny_1_of_2_multisig_op(
     recent_blockhash: Hash,
     disposition: ApprovalDisposition,
 ) {
-    let params_hash = get_operation_hash(banks_client.borrow_mut(), *multisig_op_account).await;
+    let multisig_op = get_multisig_op_data(banks_client.borrow_mut(), *multisig_op_account).await;
+    let params_hash = multisig_op.params_hash;
 
     // approve the config change
     let approve_transaction = Transaction::new_signed_with_payer(
         &[set_approval_disposition(
             program_id,
+            &multisig_op.wallet_address,
             multisig_op_account,
             &approver.pubkey(),
             disposition,
@@ -958,10 +1431,16 @@ pub async fn approve_or_deny_1_of_2_multisig_op(
             ApprovalDispositionRecord {
                 approver: approver.pubkey(),
                 disposition,
+                denial_reason: DenialReason::None,
+                reason_hash: None,
+                disposition_changes: 0,
             },
             ApprovalDispositionRecord {
                 approver: *other_approver,
                 disposition: ApprovalDisposition::NONE,
+                denial_reason: DenialReason::None,
+                reason_hash: None,
+                disposition_changes: 0,
             },
         ])
     );
@@ -997,7 +1476,7 @@ pub struct BalanceAccountTestContext {
 }
 
 impl BalanceAccountTestContext {
-    fn to_test_context(&self) -> TestContext {
+    pub fn to_test_context(&self) -> TestContext {
         let new_payer = Keypair::from_bytes(&self.payer.to_bytes()[..]).unwrap();
         TestContext {
             program_id: self.program_id,
@@ -1031,6 +1510,7 @@ pub async fn setup_balance_account_tests(
     let allowed_dapp = DAppBookEntry {
         address: Keypair::new().pubkey(),
         name_hash: DAppBookEntryNameHash::new(&hash_of(b"DApp Name")),
+        instruction_prefixes: DAppInstructionPrefixes::default(),
     };
 
     // first initialize the wallet
@@ -1129,10 +1609,16 @@ pub async fn setup_balance_account_tests(
             ApprovalDispositionRecord {
                 approver: approvers[0].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                denial_reason: DenialReason::None,
+                reason_hash: None,
+                disposition_changes: 0,
             },
             ApprovalDispositionRecord {
                 approver: approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                denial_reason: DenialReason::None,
+                reason_hash: None,
+                disposition_changes: 0,
             },
         ])
     );
@@ -1367,6 +1853,12 @@ pub async fn setup_balance_account_tests_and_finalize(
     let update = DAppBookUpdate {
         add_dapps: vec![(SlotId::new(0), context.allowed_dapp)],
         remove_dapps: vec![],
+        add_value_limits: vec![],
+        remove_value_limits: vec![],
+        add_inner_programs: vec![],
+        remove_inner_programs: vec![],
+        add_approval_policies: vec![],
+        remove_approval_policies: vec![],
     };
 
     let multisig_op_account = init_dapp_book_update(
@@ -1430,6 +1922,7 @@ pub async fn setup_transfer_test(
                     context.destination_name_hash,
                     token_mint.unwrap_or(&system_program::id()),
                     &context.payer.pubkey(),
+                    None,
                 ),
             ],
             Some(&context.payer.pubkey()),
@@ -1554,6 +2047,10 @@ pub async fn modify_address_book_and_whitelist(
             add_allowed_destinations: whitelist_destinations_to_add.clone(),
             remove_allowed_destinations: whitelist_destinations_to_remove.clone(),
         }],
+        add_destination_token_mints: vec![],
+        remove_destination_token_mints: vec![],
+        add_destination_namespaces: vec![],
+        remove_destination_namespaces: vec![],
     };
 
     // finalize the config update
@@ -1576,6 +2073,377 @@ pub async fn modify_address_book_and_whitelist(
         .unwrap();
 }
 
+pub async fn update_destination_verification_delay(
+    context: &mut BalanceAccountTestContext,
+    destination_verification_delay: Duration,
+) {
+    update_wallet_config_policy_with_test_approvers(
+        context,
+        &WalletConfigPolicyUpdate {
+            approvals_required_for_config: None,
+            approval_timeout_for_config: None,
+            add_config_approvers: vec![],
+            remove_config_approvers: vec![],
+            replace_config_approvers: None,
+            destination_verification_delay: Some(destination_verification_delay),
+            denial_reason_threshold: None,
+            approvals_required_for_assistant_config: None,
+            approvals_required_for_address_book: None,
+            add_address_book_approvers: vec![],
+            remove_address_book_approvers: vec![],
+            add_default_transfer_approvers: vec![],
+            remove_default_transfer_approvers: vec![],
+            add_veto_approvers: vec![],
+            remove_veto_approvers: vec![],
+            approvals_required_for_destructive_config: None,
+            add_destructive_config_approvers: vec![],
+            remove_destructive_config_approvers: vec![],
+        },
+    )
+    .await;
+}
+
+pub async fn update_denial_reason_threshold(
+    context: &mut BalanceAccountTestContext,
+    denial_reason_threshold: u64,
+) {
+    update_wallet_config_policy_with_test_approvers(
+        context,
+        &WalletConfigPolicyUpdate {
+            approvals_required_for_config: None,
+            approval_timeout_for_config: None,
+            add_config_approvers: vec![],
+            remove_config_approvers: vec![],
+            replace_config_approvers: None,
+            destination_verification_delay: None,
+            denial_reason_threshold: Some(denial_reason_threshold),
+            approvals_required_for_assistant_config: None,
+            approvals_required_for_address_book: None,
+            add_address_book_approvers: vec![],
+            remove_address_book_approvers: vec![],
+            add_default_transfer_approvers: vec![],
+            remove_default_transfer_approvers: vec![],
+            add_veto_approvers: vec![],
+            remove_veto_approvers: vec![],
+            approvals_required_for_destructive_config: None,
+            add_destructive_config_approvers: vec![],
+            remove_destructive_config_approvers: vec![],
+        },
+    )
+    .await;
+}
+
+async fn update_wallet_config_policy_with_test_approvers(
+    context: &mut BalanceAccountTestContext,
+    update: &WalletConfigPolicyUpdate,
+) {
+    let mut test_context = context.to_test_context();
+    update_wallet_config_policy(
+        &mut test_context,
+        context.wallet_account.pubkey(),
+        &context.assistant_account,
+        update,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+}
+
+pub async fn verify_pending_address_book_entries(
+    context: &mut BalanceAccountTestContext,
+    entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    expected_error: Option<InstructionError>,
+) {
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let multisig_op_rent = rent.minimum_balance(MultisigOp::LEN);
+    let multisig_op_account = Keypair::new();
+
+    let init_verification_transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &multisig_op_account.pubkey(),
+                multisig_op_rent,
+                MultisigOp::LEN as u64,
+                &context.program_id,
+            ),
+            init_address_book_verification(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op_account.pubkey(),
+                &context.initiator_account.pubkey(),
+                entries.clone(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[
+            &context.payer,
+            &multisig_op_account,
+            &context.initiator_account,
+        ],
+        context.recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(init_verification_transaction)
+        .await
+        .unwrap();
+
+    approve_or_deny_1_of_2_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        &context.approvers[0],
+        &context.payer,
+        &context.approvers[1].pubkey(),
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+    )
+    .await;
+
+    let finalize_verification_transaction = Transaction::new_signed_with_payer(
+        &[finalize_address_book_verification(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &multisig_op_account.pubkey(),
+            &context.payer.pubkey(),
+            entries,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.recent_blockhash,
+    );
+    match expected_error {
+        None => context
+            .banks_client
+            .process_transaction(finalize_verification_transaction)
+            .await
+            .unwrap(),
+        Some(error) => assert_eq!(
+            context
+                .banks_client
+                .process_transaction(finalize_verification_transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, error),
+        ),
+    }
+}
+
+pub async fn emit_activity_checkpoint(
+    context: &mut BalanceAccountTestContext,
+    checkpoint_index: u32,
+    expected_error: Option<InstructionError>,
+) -> Option<Pubkey> {
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let multisig_op_rent = rent.minimum_balance(MultisigOp::LEN);
+    let multisig_op_account = Keypair::new();
+
+    let init_checkpoint_transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &multisig_op_account.pubkey(),
+                multisig_op_rent,
+                MultisigOp::LEN as u64,
+                &context.program_id,
+            ),
+            init_activity_checkpoint(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op_account.pubkey(),
+                &context.initiator_account.pubkey(),
+                checkpoint_index,
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[
+            &context.payer,
+            &multisig_op_account,
+            &context.initiator_account,
+        ],
+        context.recent_blockhash,
+    );
+    if let Some(error) = expected_error {
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(init_checkpoint_transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(1, error),
+        );
+        return None;
+    }
+    context
+        .banks_client
+        .process_transaction(init_checkpoint_transaction)
+        .await
+        .unwrap();
+
+    approve_or_deny_1_of_2_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        &context.approvers[0],
+        &context.payer,
+        &context.approvers[1].pubkey(),
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+    )
+    .await;
+
+    let (checkpoint_account, _) = ActivityCheckpoint::find_address(
+        &context.wallet_account.pubkey(),
+        checkpoint_index,
+        &context.program_id,
+    );
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_activity_checkpoint(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op_account.pubkey(),
+                &checkpoint_account,
+                &context.payer.pubkey(),
+                checkpoint_index,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    Some(checkpoint_account)
+}
+
+pub async fn get_activity_checkpoint(
+    banks_client: &mut BanksClient,
+    account: &Pubkey,
+) -> ActivityCheckpoint {
+    ActivityCheckpoint::unpack_from_slice(
+        banks_client
+            .get_account(*account)
+            .await
+            .unwrap()
+            .unwrap()
+            .data(),
+    )
+    .unwrap()
+}
+
+pub async fn update_address_book_page(
+    context: &mut BalanceAccountTestContext,
+    page_index: u8,
+    update: AddressBookUpdate,
+    expected_error: Option<InstructionError>,
+) -> Option<Pubkey> {
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let multisig_op_rent = rent.minimum_balance(MultisigOp::LEN);
+    let multisig_op_account = Keypair::new();
+    let (page_account, _) = AddressBookPage::find_address(
+        &context.wallet_account.pubkey(),
+        page_index,
+        &context.program_id,
+    );
+
+    let init_update_tx = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &multisig_op_account.pubkey(),
+                multisig_op_rent,
+                MultisigOp::LEN as u64,
+                &context.program_id,
+            ),
+            init_address_book_page_update(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op_account.pubkey(),
+                &page_account,
+                &context.initiator_account.pubkey(),
+                page_index,
+                update.clone(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[
+            &context.payer,
+            &multisig_op_account,
+            &context.initiator_account,
+        ],
+        context.recent_blockhash,
+    );
+    if let Some(error) = expected_error {
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(init_update_tx)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(1, error),
+        );
+        return None;
+    }
+    context
+        .banks_client
+        .process_transaction(init_update_tx)
+        .await
+        .unwrap();
+
+    approve_or_deny_1_of_2_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        &context.approvers[0],
+        &context.payer,
+        &context.approvers[1].pubkey(),
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+    )
+    .await;
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_address_book_page_update(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op_account.pubkey(),
+                &page_account,
+                &context.payer.pubkey(),
+                page_index,
+                update,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    Some(page_account)
+}
+
+pub async fn get_address_book_page(
+    banks_client: &mut BanksClient,
+    account: &Pubkey,
+) -> AddressBookPage {
+    AddressBookPage::unpack_from_slice(
+        banks_client
+            .get_account(*account)
+            .await
+            .unwrap()
+            .unwrap()
+            .data(),
+    )
+    .unwrap()
+}
+
 pub async fn update_balance_account_name_hash(
     context: &mut BalanceAccountTestContext,
     account_name_hash: BalanceAccountNameHash,
@@ -1995,6 +2863,9 @@ pub async fn process_wrap(
                     &context.balance_account_guid_hash,
                     amount,
                     WrapDirection::WRAP,
+                    None,
+                    None,
+                    None,
                 ),
             ],
             Some(&context.payer.pubkey()),
@@ -2049,6 +2920,8 @@ pub async fn process_wrap(
                 &context.balance_account_guid_hash,
                 amount,
                 WrapDirection::WRAP,
+                None,
+                None,
             )],
             Some(&context.payer.pubkey()),
             &[&context.payer],
@@ -2062,6 +2935,7 @@ pub async fn process_unwrapping(
     multisig_account_rent: u64,
     balance_account: Pubkey,
     unwrap_amount: u64,
+    destination: Option<AddressBookEntry>,
 ) -> transport::Result<()> {
     let unwrap_multisig_op_account = Keypair::new();
 
@@ -2085,6 +2959,9 @@ pub async fn process_unwrapping(
                     &context.balance_account_guid_hash,
                     unwrap_amount,
                     WrapDirection::UNWRAP,
+                    None,
+                    destination,
+                    None,
                 ),
             ],
             Some(&context.payer.pubkey()),
@@ -2122,6 +2999,8 @@ pub async fn process_unwrapping(
                 &context.balance_account_guid_hash,
                 unwrap_amount,
                 WrapDirection::UNWRAP,
+                destination.map(|destination| destination.address),
+                None,
             )],
             Some(&context.payer.pubkey()),
             &[&context.payer],
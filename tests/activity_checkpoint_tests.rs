@@ -0,0 +1,104 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::borrow::BorrowMut;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::system_program;
+use strike_wallet::error::WalletError;
+use strike_wallet::model::activity::TransferTotal;
+use strike_wallet::model::multisig_op::{ApprovalDisposition, OperationDisposition};
+use {
+    solana_program::system_instruction,
+    solana_program_test::tokio,
+    solana_sdk::{signature::Signer as SdkSigner, transaction::Transaction},
+};
+
+#[tokio::test]
+async fn test_activity_checkpoint() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let (multisig_op_account, result) =
+        setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    result.unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.payer,
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::transfer(&context.payer.pubkey(), &balance_account, 1000),
+                finalize_transfer(
+                    &context.program_id,
+                    &multisig_op_account.pubkey(),
+                    &context.wallet_account.pubkey(),
+                    &balance_account,
+                    &context.destination.pubkey(),
+                    &context.payer.pubkey(),
+                    context.balance_account_guid_hash,
+                    123,
+                    &system_program::id(),
+                    None,
+                ),
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    let expected_transfer_totals = vec![TransferTotal {
+        token_mint: system_program::id(),
+        count: 1,
+        amount: 123,
+    }];
+    assert_eq!(wallet.activity.checkpoint_count, 0);
+    assert_eq!(wallet.activity.transfer_totals, expected_transfer_totals);
+    assert!(wallet.activity.config_change_count > 0);
+
+    let checkpoint_account = emit_activity_checkpoint(&mut context, 0, None)
+        .await
+        .unwrap();
+
+    let checkpoint = get_activity_checkpoint(&mut context.banks_client, &checkpoint_account).await;
+    assert!(checkpoint.is_initialized);
+    assert_eq!(checkpoint.wallet_address, context.wallet_account.pubkey());
+    assert_eq!(checkpoint.checkpoint_index, 0);
+    assert_eq!(checkpoint.period_start, wallet.activity.period_start);
+    assert!(checkpoint.period_end >= checkpoint.period_start);
+    assert_eq!(
+        checkpoint.config_change_count,
+        wallet.activity.config_change_count
+    );
+    assert_eq!(checkpoint.transfer_totals, expected_transfer_totals);
+
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    assert_eq!(wallet.activity.checkpoint_count, 1);
+    assert_eq!(wallet.activity.period_start, checkpoint.period_end);
+    assert_eq!(wallet.activity.config_change_count, 0);
+    assert_eq!(wallet.activity.transfer_totals, vec![]);
+
+    // a checkpoint can only be emitted once per period
+    emit_activity_checkpoint(
+        &mut context,
+        0,
+        Some(Custom(WalletError::InvalidCheckpointIndex as u32)),
+    )
+    .await;
+}
@@ -0,0 +1,196 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+pub use common::utils;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use std::time::Duration;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{AddressBookUpdate, InitialWalletConfig};
+use strike_wallet::model::address_book::{AddressBookEntry, AddressBookEntryNameHash};
+use strike_wallet::utils::SlotId;
+
+#[tokio::test]
+async fn test_chunked_address_book_update() {
+    let mut context = setup_test(60_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let approvers = vec![Keypair::new(), Keypair::new()];
+    let signers = vec![
+        (SlotId::new(0), approvers[0].pubkey_as_signer()),
+        (SlotId::new(1), approvers[1].pubkey_as_signer()),
+    ];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: signers.clone(),
+            config_approvers: signers,
+        },
+    )
+    .await
+    .unwrap();
+
+    let entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)> = (0..8)
+        .map(|index| {
+            (
+                SlotId::new(index),
+                AddressBookEntry {
+                    address: Keypair::new().pubkey(),
+                    name_hash: AddressBookEntryNameHash::new(&hash_of(
+                        format!("Destination {}", index).as_bytes(),
+                    )),
+                },
+            )
+        })
+        .collect();
+    let update = AddressBookUpdate {
+        add_address_book_entries: entries.clone(),
+        remove_address_book_entries: vec![],
+        balance_account_whitelist_updates: vec![],
+        add_destination_token_mints: vec![],
+        remove_destination_token_mints: vec![],
+        add_destination_namespaces: vec![],
+        remove_destination_namespaces: vec![],
+    };
+
+    let (multisig_op_account, buffer_account) = utils::init_address_book_update_chunked(
+        &mut context,
+        wallet_account.pubkey(),
+        &approvers[0],
+        &update,
+        200,
+    )
+    .await
+    .unwrap();
+
+    // the buffer is complete, so nothing more can be supplied
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[supply_address_book_chunk(
+                    &context.program_id,
+                    &buffer_account,
+                    &approvers[0].pubkey(),
+                    0,
+                    vec![0; 8],
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, &approvers[0]],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidUpdateChunk as u32)),
+    );
+
+    approve_n_of_n_multisig_op(
+        &mut context,
+        &multisig_op_account,
+        vec![&approvers[0], &approvers[1]],
+    )
+    .await;
+    utils::finalize_address_book_update_chunked(
+        &mut context,
+        wallet_account.pubkey(),
+        multisig_op_account,
+        buffer_account,
+        &update,
+    )
+    .await;
+
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &wallet_account.pubkey())
+            .await
+            .address_book
+            .filled_slots(),
+        entries
+    );
+}
+
+#[tokio::test]
+async fn test_chunks_only_come_from_the_initiator() {
+    let mut context = setup_test(60_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let approvers = vec![Keypair::new(), Keypair::new()];
+    let signers = vec![
+        (SlotId::new(0), approvers[0].pubkey_as_signer()),
+        (SlotId::new(1), approvers[1].pubkey_as_signer()),
+    ];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: signers.clone(),
+            config_approvers: signers,
+        },
+    )
+    .await
+    .unwrap();
+
+    let update = AddressBookUpdate {
+        add_address_book_entries: vec![],
+        remove_address_book_entries: vec![],
+        balance_account_whitelist_updates: vec![],
+        add_destination_token_mints: vec![],
+        remove_destination_token_mints: vec![],
+        add_destination_namespaces: vec![],
+        remove_destination_namespaces: vec![],
+    };
+    let (_, buffer_account) = utils::init_address_book_update_chunked(
+        &mut context,
+        wallet_account.pubkey(),
+        &approvers[0],
+        &update,
+        200,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[supply_address_book_chunk(
+                    &context.program_id,
+                    &buffer_account,
+                    &approvers[1].pubkey(),
+                    0,
+                    vec![0; 3],
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, &approvers[1]],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidSignature as u32)),
+    );
+}
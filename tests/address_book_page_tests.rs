@@ -0,0 +1,151 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::borrow::BorrowMut;
+
+use solana_program::instruction::AccountMeta;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::system_program;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{AddressBookUpdate, BalanceAccountWhitelistUpdate};
+use strike_wallet::model::multisig_op::{BooleanSetting, MultisigOp};
+use strike_wallet::utils::SlotId;
+use {solana_program::program_pack::Pack, solana_sdk::system_instruction};
+
+#[tokio::test]
+async fn test_transfer_to_address_book_page_destination() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(64000)).await;
+    account_settings_update(&mut context, Some(BooleanSetting::On), None, None).await;
+
+    let entry = (SlotId::new(0), context.allowed_destination);
+    let page_account = update_address_book_page(
+        &mut context,
+        0,
+        AddressBookUpdate {
+            add_address_book_entries: vec![entry],
+            remove_address_book_entries: vec![],
+            balance_account_whitelist_updates: vec![BalanceAccountWhitelistUpdate {
+                guid_hash: context.balance_account_guid_hash,
+                add_allowed_destinations: vec![entry],
+                remove_allowed_destinations: vec![],
+            }],
+            add_destination_token_mints: vec![],
+            remove_destination_token_mints: vec![],
+            add_destination_namespaces: vec![],
+            remove_destination_namespaces: vec![],
+        },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let page = get_address_book_page(&mut context.banks_client, &page_account).await;
+    assert!(page.is_initialized);
+    assert_eq!(page.wallet_address, context.wallet_account.pubkey());
+    assert_eq!(page.page_index, 0);
+    assert_eq!(page.entries.filled_slots(), vec![entry]);
+
+    // the destination is not whitelisted in the wallet itself
+    let (_, result) = setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::DestinationNotAllowed as u32)),
+    );
+
+    // but it is allowed once the page is passed along
+    let multisig_op_account = Keypair::new();
+    let mut init_transfer_instruction = init_transfer(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &multisig_op_account.pubkey(),
+        &context.initiator_account.pubkey(),
+        &balance_account,
+        &context.destination.pubkey(),
+        context.balance_account_guid_hash,
+        123,
+        context.destination_name_hash,
+        &system_program::id(),
+        &context.payer.pubkey(),
+        None,
+    );
+    init_transfer_instruction
+        .accounts
+        .push(AccountMeta::new_readonly(page_account, false));
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                init_transfer_instruction,
+            ],
+            Some(&context.payer.pubkey()),
+            &[
+                &context.payer,
+                &multisig_op_account,
+                &context.initiator_account,
+            ],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_address_book_page_update_failures() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(Some(64000)).await;
+
+    let entry = (SlotId::new(0), context.allowed_destination);
+
+    // can't remove an entry the page does not hold
+    update_address_book_page(
+        &mut context,
+        0,
+        AddressBookUpdate {
+            add_address_book_entries: vec![],
+            remove_address_book_entries: vec![entry],
+            balance_account_whitelist_updates: vec![],
+            add_destination_token_mints: vec![],
+            remove_destination_token_mints: vec![],
+            add_destination_namespaces: vec![],
+            remove_destination_namespaces: vec![],
+        },
+        Some(Custom(WalletError::SlotCannotBeRemoved as u32)),
+    )
+    .await;
+
+    // can't whitelist while whitelisting is off
+    update_address_book_page(
+        &mut context,
+        0,
+        AddressBookUpdate {
+            add_address_book_entries: vec![entry],
+            remove_address_book_entries: vec![],
+            balance_account_whitelist_updates: vec![BalanceAccountWhitelistUpdate {
+                guid_hash: context.balance_account_guid_hash,
+                add_allowed_destinations: vec![entry],
+                remove_allowed_destinations: vec![],
+            }],
+            add_destination_token_mints: vec![],
+            remove_destination_token_mints: vec![],
+            add_destination_namespaces: vec![],
+            remove_destination_namespaces: vec![],
+        },
+        Some(Custom(WalletError::WhitelistDisabled as u32)),
+    )
+    .await;
+}
@@ -6,10 +6,18 @@ pub use common::instructions::*;
 pub use common::utils::*;
 
 use solana_program::instruction::InstructionError::Custom;
+use solana_program::program_pack::Pack;
+use solana_program::system_instruction;
 use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
 use solana_sdk::signer::Signer;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use std::time::Duration;
 use strike_wallet::error::WalletError;
-use strike_wallet::model::multisig_op::BooleanSetting;
+use strike_wallet::instruction::BalanceAccountWhitelistUpdate;
+use strike_wallet::model::address_book::{AddressBookEntry, AddressBookEntryNameHash};
+use strike_wallet::model::multisig_op::{BooleanSetting, MultisigOp};
+use strike_wallet::utils::SlotId;
 
 #[tokio::test]
 async fn test_address_book_update() {
@@ -181,3 +189,159 @@ async fn test_address_book_failures() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn test_address_book_destination_verification() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(Some(64000)).await;
+
+    update_destination_verification_delay(&mut context, Duration::from_secs(3600)).await;
+    account_settings_update(&mut context, Some(BooleanSetting::On), None, None).await;
+
+    // a newly added entry is pending verification and can't be used as a destination
+    let new_entry = (
+        SlotId::new(3),
+        AddressBookEntry {
+            address: Keypair::new().pubkey(),
+            name_hash: AddressBookEntryNameHash::new(&hash_of(b"New Destination")),
+        },
+    );
+    modify_address_book_and_whitelist(
+        &mut context,
+        vec![new_entry],
+        vec![],
+        vec![new_entry],
+        vec![],
+        None,
+    )
+    .await;
+
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    let balance_account = wallet
+        .get_balance_account(&context.balance_account_guid_hash)
+        .unwrap();
+    assert!(wallet.pending_address_book_entries.is_enabled(&new_entry.0));
+    assert!(!wallet
+        .destination_allowed(
+            &balance_account,
+            &new_entry.1.address,
+            &new_entry.1.name_hash
+        )
+        .unwrap());
+
+    // entries that are not pending can't be verified
+    let existing_entry = wallet.address_book.filled_slots()[0];
+    verify_pending_address_book_entries(
+        &mut context,
+        vec![existing_entry],
+        Some(Custom(WalletError::InvalidSlot as u32)),
+    )
+    .await;
+
+    // verification can't be finalized before the delay has elapsed
+    verify_pending_address_book_entries(
+        &mut context,
+        vec![new_entry],
+        Some(Custom(
+            WalletError::DestinationVerificationDelayNotElapsed as u32,
+        )),
+    )
+    .await;
+
+    update_destination_verification_delay(&mut context, Duration::from_secs(0)).await;
+    verify_pending_address_book_entries(&mut context, vec![new_entry], None).await;
+
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    let balance_account = wallet
+        .get_balance_account(&context.balance_account_guid_hash)
+        .unwrap();
+    assert!(!wallet.pending_address_book_entries.is_enabled(&new_entry.0));
+    assert!(wallet
+        .destination_allowed(
+            &balance_account,
+            &new_entry.1.address,
+            &new_entry.1.name_hash
+        )
+        .unwrap());
+}
+
+#[tokio::test]
+async fn test_address_book_update_validate_only() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(Some(64000)).await;
+
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    let entries = wallet.address_book.filled_slots();
+
+    let multisig_op_account = Keypair::new();
+    let validate_transaction = |whitelist_destinations_to_add| {
+        Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                validate_only(init_address_book_update(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    &context.initiator_account.pubkey(),
+                    vec![],
+                    entries.clone(),
+                    vec![BalanceAccountWhitelistUpdate {
+                        guid_hash: context.balance_account_guid_hash,
+                        add_allowed_destinations: whitelist_destinations_to_add,
+                        remove_allowed_destinations: vec![],
+                    }],
+                )),
+            ],
+            Some(&context.payer.pubkey()),
+            &[
+                &context.payer,
+                &multisig_op_account,
+                &context.initiator_account,
+            ],
+            context.recent_blockhash,
+        )
+    };
+
+    // whitelisting is off, so this update would be rejected at init
+    let invalid_transaction = validate_transaction(vec![entries[0]]);
+    let valid_transaction = validate_transaction(vec![]);
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(invalid_transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::WhitelistDisabled as u32)),
+    );
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(valid_transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::SimulationFinished as u32)),
+    );
+
+    // neither the op nor the wallet were written
+    assert!(context
+        .banks_client
+        .get_account(multisig_op_account.pubkey())
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &context.wallet_account.pubkey())
+            .await
+            .address_book
+            .filled_slots(),
+        entries
+    );
+}
@@ -0,0 +1,194 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+pub use common::utils;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{ApproverDelegationUpdate, InitialWalletConfig};
+use strike_wallet::model::approver_delegation::ApproverDelegation;
+use strike_wallet::model::multisig_op::{ApprovalDisposition, OperationDisposition};
+use strike_wallet::utils::{SlotId, Slots};
+
+#[tokio::test]
+async fn test_delegate_approves_in_place_of_approver() {
+    let mut context = setup_test(40_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+
+    let approvers = vec![Keypair::new(), Keypair::new()];
+    let delegate = Keypair::new();
+    let signers = vec![
+        (SlotId::new(0), approvers[0].pubkey_as_signer()),
+        (SlotId::new(1), approvers[1].pubkey_as_signer()),
+        (SlotId::new(2), delegate.pubkey_as_signer()),
+    ];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: signers.clone(),
+            config_approvers: signers[0..2].to_vec(),
+        },
+    )
+    .await
+    .unwrap();
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let delegation = (
+        SlotId::new(0),
+        ApproverDelegation {
+            approver: approvers[0].pubkey(),
+            delegate: delegate.pubkey(),
+            expires_at: now + 86400,
+        },
+    );
+
+    // a delegation must expire within the maximum period
+    assert_eq!(
+        utils::init_approver_delegation_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &approvers[0],
+            ApproverDelegationUpdate {
+                add_delegations: vec![(
+                    SlotId::new(0),
+                    ApproverDelegation {
+                        expires_at: now + 86400 * 60,
+                        ..delegation.1
+                    },
+                )],
+                remove_delegations: vec![],
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            Custom(WalletError::InvalidApproverDelegation as u32)
+        ),
+    );
+
+    let add_delegation = ApproverDelegationUpdate {
+        add_delegations: vec![delegation],
+        remove_delegations: vec![],
+    };
+    let multisig_op_account = utils::init_approver_delegation_update(
+        &mut context,
+        wallet_account.pubkey(),
+        &approvers[0],
+        add_delegation.clone(),
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut context,
+        &multisig_op_account,
+        vec![&approvers[0], &approvers[1]],
+    )
+    .await;
+    utils::finalize_approver_delegation_update(
+        &mut context,
+        wallet_account.pubkey(),
+        multisig_op_account,
+        add_delegation,
+    )
+    .await;
+
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &wallet_account.pubkey())
+            .await
+            .approver_delegations,
+        Slots::from_vec(vec![delegation])
+    );
+
+    // ops started from now on can be approved by the delegate
+    let remove_delegation = ApproverDelegationUpdate {
+        add_delegations: vec![],
+        remove_delegations: vec![delegation],
+    };
+    let multisig_op_account = utils::init_approver_delegation_update(
+        &mut context,
+        wallet_account.pubkey(),
+        &approvers[1],
+        remove_delegation.clone(),
+    )
+    .await
+    .unwrap();
+
+    let params_hash = get_operation_hash(&mut context.banks_client, multisig_op_account).await;
+    for approver in vec![&delegate, &approvers[1]] {
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[set_approval_disposition(
+                    &context.program_id,
+                    &wallet_account.pubkey(),
+                    &multisig_op_account,
+                    &approver.pubkey(),
+                    ApprovalDisposition::APPROVE,
+                    params_hash,
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, approver],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap();
+    }
+
+    let multisig_op = get_multisig_op_data(&mut context.banks_client, multisig_op_account).await;
+    assert_eq!(
+        multisig_op.operation_disposition,
+        OperationDisposition::APPROVED
+    );
+    assert_eq!(
+        multisig_op.disposition_records[0].approver,
+        approvers[0].pubkey()
+    );
+    assert_eq!(
+        multisig_op.disposition_records[0].disposition,
+        ApprovalDisposition::APPROVE
+    );
+    assert_eq!(multisig_op.delegated_approvals.len(), 1);
+    assert_eq!(
+        multisig_op.delegated_approvals[0].delegate,
+        delegate.pubkey()
+    );
+    assert!(multisig_op.delegated_approvals[0].signed_by_delegate);
+
+    utils::finalize_approver_delegation_update(
+        &mut context,
+        wallet_account.pubkey(),
+        multisig_op_account,
+        remove_delegation,
+    )
+    .await;
+
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &wallet_account.pubkey())
+            .await
+            .approver_delegations,
+        Slots::new()
+    );
+}
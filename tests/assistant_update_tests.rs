@@ -0,0 +1,142 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+pub use common::utils;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::TransactionError;
+use std::time::Duration;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{GovernedParameterUpdate, InitialWalletConfig};
+use strike_wallet::model::governed_parameter::GovernedParameter;
+use strike_wallet::utils::SlotId;
+
+#[tokio::test]
+async fn test_assistant_update() {
+    let mut context = setup_test(60_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+
+    let approvers = vec![Keypair::new(), Keypair::new()];
+    let signers = vec![
+        (SlotId::new(0), approvers[0].pubkey_as_signer()),
+        (SlotId::new(1), approvers[1].pubkey_as_signer()),
+    ];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: signers.clone(),
+            config_approvers: signers,
+        },
+    )
+    .await
+    .unwrap();
+
+    let new_assistant = Keypair::new();
+    let multisig_op_account = utils::init_assistant_update(
+        &mut context,
+        wallet_account.pubkey(),
+        &assistant_account,
+        new_assistant.pubkey(),
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut context,
+        &multisig_op_account,
+        vec![&approvers[0], &approvers[1]],
+    )
+    .await;
+    utils::finalize_assistant_update(
+        &mut context,
+        wallet_account.pubkey(),
+        multisig_op_account,
+        new_assistant.pubkey(),
+    )
+    .await;
+
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &wallet_account.pubkey())
+            .await
+            .assistant
+            .key,
+        new_assistant.pubkey()
+    );
+
+    // the old assistant can no longer initiate ops, the new one can
+    assert_eq!(
+        utils::init_assistant_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &assistant_account,
+            assistant_account.pubkey(),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidApprover as u32)),
+    );
+
+    // signers can be made the assistant until the wallet forbids it
+    utils::init_assistant_update(
+        &mut context,
+        wallet_account.pubkey(),
+        &new_assistant,
+        approvers[1].pubkey(),
+    )
+    .await
+    .unwrap();
+
+    let update = GovernedParameterUpdate {
+        values: vec![(GovernedParameter::AssistantMayBeSigner, Some(0))],
+    };
+    let multisig_op_account = utils::init_governed_parameter_update(
+        &mut context,
+        wallet_account.pubkey(),
+        &approvers[0],
+        update.clone(),
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut context,
+        &multisig_op_account,
+        vec![&approvers[0], &approvers[1]],
+    )
+    .await;
+    utils::finalize_governed_parameter_update(
+        &mut context,
+        wallet_account.pubkey(),
+        multisig_op_account,
+        update,
+    )
+    .await;
+
+    assert_eq!(
+        utils::init_assistant_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &new_assistant,
+            approvers[1].pubkey(),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidAssistant as u32)),
+    );
+}
@@ -14,6 +14,7 @@ use solana_sdk::transaction::TransactionError;
 use crate::common::utils;
 use common::instructions::finalize_balance_account_creation;
 use std::collections::HashSet;
+use strike_wallet::client::DEFAULT_LAMPORTS_PER_SIGNATURE;
 use strike_wallet::error::WalletError;
 use strike_wallet::model::balance_account::BalanceAccountGuidHash;
 use strike_wallet::model::multisig_op::{ApprovalDisposition, BooleanSetting};
@@ -93,14 +94,14 @@ async fn test_balance_account_creation() {
         .await
         .unwrap()
         .is_none());
-    // and that the remaining balance went to the rent collector (less the 5000 in signature fees for the finalize)
+    // and that the remaining balance went to the rent collector (less the signature fee for the finalize)
     let ending_rent_collector_balance = context
         .banks_client
         .get_balance(context.payer.pubkey())
         .await
         .unwrap();
     assert_eq!(
-        starting_rent_collector_balance + op_account_balance - 5000,
+        starting_rent_collector_balance + op_account_balance - DEFAULT_LAMPORTS_PER_SIGNATURE,
         ending_rent_collector_balance
     );
 }
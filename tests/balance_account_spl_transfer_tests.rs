@@ -7,12 +7,25 @@ pub use common::utils::*;
 
 use std::borrow::BorrowMut;
 
+use solana_program::instruction::AccountMeta;
 use solana_program::instruction::InstructionError::Custom;
+use solana_program::pubkey::Pubkey;
+use solana_program::{system_program, sysvar};
+use solana_sdk::signature::Keypair;
 use solana_sdk::transaction::TransactionError;
 
 use common::instructions::finalize_transfer;
 use strike_wallet::error::WalletError;
-use strike_wallet::model::multisig_op::{ApprovalDisposition, OperationDisposition};
+use strike_wallet::instruction::{
+    BalanceAccountPolicyUpdate, CombinedTransfer, GovernedParameterUpdate, ProgramInstruction,
+};
+use strike_wallet::model::activity::TransferTotal;
+use strike_wallet::model::governed_parameter::GovernedParameter;
+use strike_wallet::model::multisig_op::{
+    AmountSpec, ApprovalDisposition, AtaRentPayer, BooleanSetting, OperationDisposition,
+};
+use strike_wallet::model::token_mint::TokenMint;
+use strike_wallet::utils::SlotId;
 use {
     solana_program::system_instruction,
     solana_program_test::tokio,
@@ -149,6 +162,7 @@ async fn test_wrap_unwrap() {
         multisig_account_rent,
         balance_account,
         amount * 2,
+        None,
     )
     .await;
     assert_eq!(
@@ -162,6 +176,7 @@ async fn test_wrap_unwrap() {
         multisig_account_rent,
         balance_account,
         unwrap_amount,
+        None,
     )
     .await
     .unwrap();
@@ -188,6 +203,120 @@ async fn test_wrap_unwrap() {
             .unwrap(),
         unwrap_amount
     );
+
+    // unwrapping everything that is left closes the wrapped SOL account and
+    // returns its rent to the balance account
+    process_unwrapping(
+        &mut context,
+        multisig_account_rent,
+        balance_account,
+        amount - unwrap_amount,
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert!(context
+        .banks_client
+        .get_account(wrapped_sol_account)
+        .await
+        .unwrap()
+        .is_none());
+
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(balance_account)
+            .await
+            .unwrap(),
+        amount + token_account_rent
+    );
+}
+
+#[tokio::test]
+async fn test_unwrap_to_destination() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(60_000)).await;
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let token_account_rent = rent.minimum_balance(spl_token::state::Account::LEN);
+    let multisig_account_rent = rent.minimum_balance(MultisigOp::LEN);
+    let wrapped_sol_account = spl_associated_token_account::get_associated_token_address(
+        &balance_account,
+        &spl_token::native_mint::id(),
+    );
+    let destination = context.allowed_destination;
+    // the destination has to be rent exempt after receiving the unwrapped SOL
+    let amount = rent.minimum_balance(0) * 2;
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &context.payer.pubkey(),
+                &balance_account,
+                token_account_rent + amount,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+    process_wrap(
+        &mut context,
+        multisig_account_rent,
+        balance_account,
+        amount,
+        token_account_rent,
+        wrapped_sol_account,
+    )
+    .await
+    .unwrap();
+
+    let unwrap_amount = amount / 2;
+    process_unwrapping(
+        &mut context,
+        multisig_account_rent,
+        balance_account,
+        unwrap_amount,
+        Some(destination),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context, &wrapped_sol_account).await,
+        amount - unwrap_amount
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(destination.address)
+            .await
+            .unwrap(),
+        unwrap_amount
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(balance_account)
+            .await
+            .unwrap(),
+        0
+    );
+
+    // only the unwrap paid to the destination leaves the wallet
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &context.wallet_account.pubkey())
+            .await
+            .activity
+            .transfer_totals,
+        vec![TransferTotal {
+            token_mint: system_program::id(),
+            count: 1,
+            amount: unwrap_amount,
+        }]
+    );
 }
 
 #[tokio::test]
@@ -371,3 +500,545 @@ async fn test_transfer_spl_insufficient_balance() {
         0
     );
 }
+
+#[tokio::test]
+async fn test_transfer_spl_token_program_mismatch() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(60_000)).await;
+    let spl_context = setup_spl_transfer_test(&mut context, &balance_account, true).await;
+
+    let (multisig_op_account, result) = setup_transfer_test(
+        context.borrow_mut(),
+        &balance_account,
+        Some(&spl_context.mint.pubkey()),
+        Some(123),
+    )
+    .await;
+    result.unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.payer,
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    let finalize_instruction = finalize_transfer(
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        &context.wallet_account.pubkey(),
+        &balance_account,
+        &context.allowed_destination.address,
+        &context.payer.pubkey(),
+        context.balance_account_guid_hash,
+        123,
+        &spl_context.mint.pubkey(),
+        Some(&spl_context.mint_authority.pubkey()),
+    );
+    let other_token_program = Keypair::new().pubkey();
+
+    // the transfer was approved for the legacy token program, so it can't be
+    // finalized against another one
+    let mut other_program_instruction = finalize_instruction.clone();
+    other_program_instruction.accounts[9] = AccountMeta::new_readonly(other_token_program, false);
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[other_program_instruction.clone()],
+                Some(&context.payer.pubkey()),
+                &[&context.payer],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::TokenProgramMismatch as u32)),
+    );
+
+    // nor by claiming it was approved for the other one
+    other_program_instruction.data = ProgramInstruction::FinalizeTransfer {
+        account_guid_hash: context.balance_account_guid_hash,
+        amount: 123,
+        token_mint: spl_context.mint.pubkey(),
+        memo_hash: None,
+        fee_reimbursement: 0,
+        ata_rent_payer: None,
+        token_program: Some(other_token_program),
+        amount_spec: AmountSpec::Exact,
+        travel_rule_hash: None,
+    }
+    .pack();
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[other_program_instruction],
+                Some(&context.payer.pubkey()),
+                &[&context.payer],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidSignature as u32)),
+    );
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_instruction],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(
+        get_token_balance(&mut context, &spl_context.destination_token_address).await,
+        123
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_spl_token_mint_allowlist() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(60_000)).await;
+    let spl_context = setup_spl_transfer_test(&mut context, &balance_account, true).await;
+
+    let other_mint = TokenMint {
+        address: Pubkey::new_unique(),
+    };
+    update_balance_account_policy(
+        &mut context,
+        BalanceAccountPolicyUpdate {
+            approvals_required_for_transfer: None,
+            approval_timeout_for_transfer: None,
+            add_transfer_approvers: None,
+            remove_transfer_approvers: None,
+            use_config_approvers: None,
+            token_mint_allowlist_enabled: Some(BooleanSetting::On),
+            add_allowed_token_mints: Some(vec![(SlotId::new(0), other_mint)]),
+            remove_allowed_token_mints: None,
+            max_outstanding_transfer_value: None,
+            max_finalize_fee_reimbursement: None,
+            policy_rules: None,
+            large_transfer_cooldown: None,
+        },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let (_, result) = setup_transfer_test(
+        context.borrow_mut(),
+        &balance_account,
+        Some(&spl_context.mint.pubkey()),
+        None,
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::TokenMintNotAllowed as u32)),
+    );
+
+    let allowed_mint = TokenMint {
+        address: spl_context.mint.pubkey(),
+    };
+    update_balance_account_policy(
+        &mut context,
+        BalanceAccountPolicyUpdate {
+            approvals_required_for_transfer: None,
+            approval_timeout_for_transfer: None,
+            add_transfer_approvers: None,
+            remove_transfer_approvers: None,
+            use_config_approvers: None,
+            token_mint_allowlist_enabled: None,
+            add_allowed_token_mints: Some(vec![(SlotId::new(1), allowed_mint)]),
+            remove_allowed_token_mints: Some(vec![(SlotId::new(0), other_mint)]),
+            max_outstanding_transfer_value: None,
+            max_finalize_fee_reimbursement: None,
+            policy_rules: None,
+            large_transfer_cooldown: None,
+        },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    assert_eq!(
+        wallet.token_mint_book.filled_slots(),
+        vec![(SlotId::new(1), allowed_mint)]
+    );
+
+    let (_, result) = setup_transfer_test(
+        context.borrow_mut(),
+        &balance_account,
+        Some(&spl_context.mint.pubkey()),
+        None,
+    )
+    .await;
+    result.unwrap();
+}
+
+#[tokio::test]
+async fn test_transfer_spl_with_ata_rent_paid_by_finalizer() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(60_000)).await;
+    let spl_context = setup_spl_transfer_test(&mut context, &balance_account, false).await;
+
+    let multisig_op_account = Keypair::new();
+    let mut init_transfer_instruction = init_transfer(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &multisig_op_account.pubkey(),
+        &context.initiator_account.pubkey(),
+        &balance_account,
+        &context.destination.pubkey(),
+        context.balance_account_guid_hash,
+        123,
+        context.destination_name_hash,
+        &spl_context.mint.pubkey(),
+        &context.payer.pubkey(),
+        None,
+    );
+    init_transfer_instruction.data = ProgramInstruction::InitTransfer {
+        account_guid_hash: context.balance_account_guid_hash,
+        amount: 123,
+        destination_name_hash: context.destination_name_hash,
+        approval_timeout: None,
+        memo_hash: None,
+        ata_rent_payer: Some(AtaRentPayer::Payer),
+        amount_spec: AmountSpec::Exact,
+        travel_rule_hash: None,
+    }
+    .pack();
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                init_transfer_instruction,
+            ],
+            Some(&context.payer.pubkey()),
+            &[
+                &context.payer,
+                &multisig_op_account,
+                &context.initiator_account,
+            ],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // the destination token account is not created until the transfer is finalized
+    assert!(context
+        .banks_client
+        .get_account(spl_context.destination_token_address)
+        .await
+        .unwrap()
+        .is_none());
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.payer,
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    let mut finalize_transfer_instruction = finalize_transfer(
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        &context.wallet_account.pubkey(),
+        &balance_account,
+        &context.destination.pubkey(),
+        &context.payer.pubkey(),
+        context.balance_account_guid_hash,
+        123,
+        &spl_context.mint.pubkey(),
+        Some(&spl_context.mint_authority.pubkey()),
+    );
+    finalize_transfer_instruction.data = ProgramInstruction::FinalizeTransfer {
+        account_guid_hash: context.balance_account_guid_hash,
+        amount: 123,
+        token_mint: spl_context.mint.pubkey(),
+        memo_hash: None,
+        fee_reimbursement: 0,
+        ata_rent_payer: Some(AtaRentPayer::Payer),
+        token_program: None,
+        amount_spec: AmountSpec::Exact,
+        travel_rule_hash: None,
+    }
+    .pack();
+    finalize_transfer_instruction.accounts.extend_from_slice(&[
+        AccountMeta::new_readonly(spl_context.mint.pubkey(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ]);
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_transfer_instruction],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context, &spl_context.destination_token_address).await,
+        123
+    );
+    // none of the rent came out of the balance account
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(balance_account)
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_spl_with_ata_rent_paid_by_source_requires_governed_parameter() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(60_000)).await;
+    let spl_context = setup_spl_transfer_test(&mut context, &balance_account, false).await;
+
+    let update = GovernedParameterUpdate {
+        values: vec![(
+            GovernedParameter::BalanceAccountMayFundTokenAccounts,
+            Some(0),
+        )],
+    };
+    let mut test_context = context.to_test_context();
+    let multisig_op_account = init_governed_parameter_update(
+        &mut test_context,
+        context.wallet_account.pubkey(),
+        &context.assistant_account,
+        update.clone(),
+    )
+    .await
+    .unwrap();
+    approve_or_deny_1_of_2_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account,
+        &context.approvers[0],
+        &context.payer,
+        &context.approvers[1].pubkey(),
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+    )
+    .await;
+    finalize_governed_parameter_update(
+        &mut test_context,
+        context.wallet_account.pubkey(),
+        multisig_op_account,
+        update,
+    )
+    .await;
+
+    let multisig_op_account = Keypair::new();
+    let mut init_transfer_instruction = init_transfer(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &multisig_op_account.pubkey(),
+        &context.initiator_account.pubkey(),
+        &balance_account,
+        &context.destination.pubkey(),
+        context.balance_account_guid_hash,
+        123,
+        context.destination_name_hash,
+        &spl_context.mint.pubkey(),
+        &context.payer.pubkey(),
+        None,
+    );
+    init_transfer_instruction.data = ProgramInstruction::InitTransfer {
+        account_guid_hash: context.balance_account_guid_hash,
+        amount: 123,
+        destination_name_hash: context.destination_name_hash,
+        approval_timeout: None,
+        memo_hash: None,
+        ata_rent_payer: Some(AtaRentPayer::Source),
+        amount_spec: AmountSpec::Exact,
+        travel_rule_hash: None,
+    }
+    .pack();
+    assert_instruction_error(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[
+                    system_instruction::create_account(
+                        &context.payer.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        context.rent.minimum_balance(MultisigOp::LEN),
+                        MultisigOp::LEN as u64,
+                        &context.program_id,
+                    ),
+                    init_transfer_instruction,
+                ],
+                Some(&context.payer.pubkey()),
+                &[
+                    &context.payer,
+                    &multisig_op_account,
+                    &context.initiator_account,
+                ],
+                context.recent_blockhash,
+            ))
+            .await,
+        1,
+        Custom(WalletError::RentFundingLimitExceeded as u32),
+    );
+}
+
+#[tokio::test]
+async fn test_combined_sol_and_spl_transfer() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(100_000)).await;
+    let spl_context = setup_spl_transfer_test(&mut context, &balance_account, false).await;
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                spl_associated_token_account::create_associated_token_account(
+                    &context.payer.pubkey(),
+                    &context.destination.pubkey(),
+                    &spl_context.mint.pubkey(),
+                ),
+                system_instruction::transfer(&context.payer.pubkey(), &balance_account, 1000),
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let transfer = CombinedTransfer {
+        lamports: 100,
+        token_amounts: vec![(spl_context.mint.pubkey(), 123)],
+        travel_rule_hash: None,
+    };
+    let multisig_op_account = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                init_combined_transfer(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    &context.initiator_account.pubkey(),
+                    &balance_account,
+                    &context.destination.pubkey(),
+                    context.balance_account_guid_hash,
+                    context.destination_name_hash,
+                    transfer.clone(),
+                ),
+            ],
+            Some(&context.payer.pubkey()),
+            &[
+                &context.payer,
+                &multisig_op_account,
+                &context.initiator_account,
+            ],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // the lamports and tokens are held and approved together
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    assert_eq!(
+        wallet
+            .get_balance_account(&context.balance_account_guid_hash)
+            .unwrap()
+            .outstanding_transfer_value,
+        223
+    );
+    approve_or_deny_n_of_n_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.payer,
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_combined_transfer(
+                &context.program_id,
+                &multisig_op_account.pubkey(),
+                &context.wallet_account.pubkey(),
+                &balance_account,
+                &context.destination.pubkey(),
+                &context.payer.pubkey(),
+                context.balance_account_guid_hash,
+                transfer,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(context.destination.pubkey())
+            .await
+            .unwrap(),
+        100
+    );
+    assert_eq!(
+        get_token_balance(&mut context, &spl_context.source_token_address).await,
+        1000 - 123
+    );
+    assert_eq!(
+        get_token_balance(&mut context, &spl_context.destination_token_address).await,
+        123
+    );
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    assert_eq!(
+        wallet
+            .get_balance_account(&context.balance_account_guid_hash)
+            .unwrap()
+            .outstanding_transfer_value,
+        0
+    );
+}
@@ -6,20 +6,32 @@ pub use common::instructions::*;
 pub use common::utils::*;
 
 use std::borrow::BorrowMut;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
 
-use solana_program::hash::Hash;
+use solana_program::hash::{hash, Hash};
 use solana_program::instruction::InstructionError::Custom;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::pubkey::Pubkey;
 use solana_program::system_program;
+use solana_program::sysvar;
+use solana_sdk::signature::Keypair;
 use solana_sdk::transaction::TransactionError;
+use solana_sdk::transport;
 
 use common::instructions::finalize_transfer;
 use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{
+    ApprovalDispositionUpdate, BalanceAccountPolicyUpdate, ProgramInstruction,
+};
 use strike_wallet::model::address_book::AddressBookEntryNameHash;
 use strike_wallet::model::multisig_op::{
-    ApprovalDisposition, BooleanSetting, OperationDisposition,
+    AmountSpec, ApprovalDisposition, BooleanSetting, DenialReason, MultisigOp, OperationDisposition,
 };
+use strike_wallet::model::policy::PolicyRule;
 use strike_wallet::utils::SlotId;
 use {
+    solana_program::program_pack::Pack,
     solana_program::system_instruction,
     solana_program_test::tokio,
     solana_sdk::{signature::Signer as SdkSigner, transaction::Transaction},
@@ -118,11 +130,97 @@ async fn test_transfer_sol() {
 }
 
 #[tokio::test]
-async fn test_transfer_sol_denied() {
+async fn test_sol_transfers_can_be_disabled() {
     let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
-    let (multisig_op_account, result) =
-        setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    update_account_settings(
+        &mut context,
+        None,
+        None,
+        Some(BooleanSetting::Off),
+        None,
+        None,
+    )
+    .await;
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    let account = wallet
+        .get_balance_account(&context.balance_account_guid_hash)
+        .unwrap();
+    assert!(account.are_sol_transfers_disabled());
+    assert!(!account.are_spl_transfers_disabled());
+
+    let (_, result) = setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::TransfersDisabled as u32)),
+    );
+
+    update_account_settings(
+        &mut context,
+        None,
+        None,
+        Some(BooleanSetting::On),
+        None,
+        None,
+    )
+    .await;
+    let (_, result) = setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
     result.unwrap();
+}
+
+#[tokio::test]
+async fn test_transfer_sol_with_memo() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let memo_hash = Hash::new_from_array([7; 32]);
+
+    let multisig_op_account = Keypair::new();
+    let mut init_transfer_instruction = init_transfer(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &multisig_op_account.pubkey(),
+        &context.initiator_account.pubkey(),
+        &balance_account,
+        &context.destination.pubkey(),
+        context.balance_account_guid_hash,
+        123,
+        context.destination_name_hash,
+        &system_program::id(),
+        &context.payer.pubkey(),
+        None,
+    );
+    init_transfer_instruction.data = ProgramInstruction::InitTransfer {
+        account_guid_hash: context.balance_account_guid_hash,
+        amount: 123,
+        destination_name_hash: context.destination_name_hash,
+        approval_timeout: None,
+        memo_hash: Some(memo_hash),
+        ata_rent_payer: None,
+        amount_spec: AmountSpec::Exact,
+        travel_rule_hash: None,
+    }
+    .pack();
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                init_transfer_instruction,
+            ],
+            Some(&context.payer.pubkey()),
+            &[
+                &context.payer,
+                &multisig_op_account,
+                &context.initiator_account,
+            ],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
 
     approve_or_deny_n_of_n_multisig_op(
         context.banks_client.borrow_mut(),
@@ -131,12 +229,11 @@ async fn test_transfer_sol_denied() {
         vec![&context.approvers[0], &context.approvers[1]],
         &context.payer,
         context.recent_blockhash,
-        ApprovalDisposition::DENY,
-        OperationDisposition::DENIED,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
     )
     .await;
 
-    // transfer enough balance from fee payer to source account
     context
         .banks_client
         .process_transaction(Transaction::new_signed_with_payer(
@@ -152,38 +249,57 @@ async fn test_transfer_sol_denied() {
         .await
         .unwrap();
 
-    assert_eq!(
-        context
-            .banks_client
-            .get_balance(balance_account)
-            .await
-            .unwrap(),
-        1000
+    let mut finalize_transfer_instruction = finalize_transfer(
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        &context.wallet_account.pubkey(),
+        &balance_account,
+        &context.destination.pubkey(),
+        &context.payer.pubkey(),
+        context.balance_account_guid_hash,
+        123,
+        &system_program::id(),
+        None,
     );
+    finalize_transfer_instruction.data = ProgramInstruction::FinalizeTransfer {
+        account_guid_hash: context.balance_account_guid_hash,
+        amount: 123,
+        token_mint: system_program::id(),
+        memo_hash: Some(memo_hash),
+        fee_reimbursement: 0,
+        ata_rent_payer: None,
+        token_program: None,
+        amount_spec: AmountSpec::Exact,
+        travel_rule_hash: None,
+    }
+    .pack();
+
+    // the memo program has to be passed along to write the memo
     assert_eq!(
         context
             .banks_client
-            .get_balance(context.destination.pubkey())
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[finalize_transfer_instruction.clone()],
+                Some(&context.payer.pubkey()),
+                &[&context.payer],
+                context.recent_blockhash,
+            ))
             .await
+            .unwrap_err()
             .unwrap(),
-        0
+        TransactionError::InstructionError(0, Custom(WalletError::AccountNotRecognized as u32)),
     );
 
+    finalize_transfer_instruction
+        .accounts
+        .push(AccountMeta::new_readonly(
+            Pubkey::from_str("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr").unwrap(),
+            false,
+        ));
     context
         .banks_client
         .process_transaction(Transaction::new_signed_with_payer(
-            &[finalize_transfer(
-                &context.program_id,
-                &multisig_op_account.pubkey(),
-                &context.wallet_account.pubkey(),
-                &balance_account,
-                &context.destination.pubkey(),
-                &context.payer.pubkey(),
-                context.balance_account_guid_hash,
-                123,
-                &system_program::id(),
-                None,
-            )],
+            &[finalize_transfer_instruction],
             Some(&context.payer.pubkey()),
             &[&context.payer],
             context.recent_blockhash,
@@ -191,83 +307,114 @@ async fn test_transfer_sol_denied() {
         .await
         .unwrap();
 
-    // balances should all be the same
-    assert_eq!(
-        context
-            .banks_client
-            .get_balance(balance_account)
-            .await
-            .unwrap(),
-        1000
-    );
     assert_eq!(
         context
             .banks_client
             .get_balance(context.destination.pubkey())
             .await
             .unwrap(),
-        0
+        123
     );
 }
 
 #[tokio::test]
-async fn test_transfer_wrong_destination_name_hash() {
+async fn test_transfer_sol_max_amount() {
     let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let cap = 1_000_000;
 
-    account_settings_update(&mut context, Some(BooleanSetting::On), None, None).await;
-    let destination_to_add = context.allowed_destination;
-    modify_whitelist(
-        &mut context,
-        vec![(SlotId::new(0), destination_to_add)],
-        vec![],
+    let multisig_op_account = Keypair::new();
+    let mut init_transfer_instruction = init_transfer(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &multisig_op_account.pubkey(),
+        &context.initiator_account.pubkey(),
+        &balance_account,
+        &context.destination.pubkey(),
+        context.balance_account_guid_hash,
+        cap,
+        context.destination_name_hash,
+        &system_program::id(),
+        &context.payer.pubkey(),
         None,
-    )
-    .await;
-
-    context.destination_name_hash = AddressBookEntryNameHash::zero();
-
-    let (_, result) = setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
-    assert_eq!(
-        result.unwrap_err().unwrap(),
-        TransactionError::InstructionError(1, Custom(WalletError::DestinationNotAllowed as u32)),
-    )
-}
-
-#[tokio::test]
-async fn test_transfer_requires_multisig() {
-    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
-    let (multisig_op_account, result) =
-        setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
-    result.unwrap();
+    );
+    init_transfer_instruction.data = ProgramInstruction::InitTransfer {
+        account_guid_hash: context.balance_account_guid_hash,
+        amount: cap,
+        destination_name_hash: context.destination_name_hash,
+        approval_timeout: None,
+        memo_hash: None,
+        ata_rent_payer: None,
+        amount_spec: AmountSpec::Max,
+        travel_rule_hash: None,
+    }
+    .pack();
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                init_transfer_instruction,
+            ],
+            Some(&context.payer.pubkey()),
+            &[
+                &context.payer,
+                &multisig_op_account,
+                &context.initiator_account,
+            ],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
 
-    approve_or_deny_1_of_2_multisig_op(
+    approve_or_deny_n_of_n_multisig_op(
         context.banks_client.borrow_mut(),
         &context.program_id,
         &multisig_op_account.pubkey(),
-        &context.approvers[0],
+        vec![&context.approvers[0], &context.approvers[1]],
         &context.payer,
-        &context.approvers[1].pubkey(),
         context.recent_blockhash,
         ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
     )
     .await;
 
+    let mut finalize_transfer_instruction = finalize_transfer(
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        &context.wallet_account.pubkey(),
+        &balance_account,
+        &context.destination.pubkey(),
+        &context.payer.pubkey(),
+        context.balance_account_guid_hash,
+        cap,
+        &system_program::id(),
+        None,
+    );
+    finalize_transfer_instruction.data = ProgramInstruction::FinalizeTransfer {
+        account_guid_hash: context.balance_account_guid_hash,
+        amount: cap,
+        token_mint: system_program::id(),
+        memo_hash: None,
+        fee_reimbursement: 0,
+        ata_rent_payer: None,
+        token_program: None,
+        amount_spec: AmountSpec::Max,
+        travel_rule_hash: None,
+    }
+    .pack();
+
+    // there is nothing to transfer yet
     assert_eq!(
         context
             .banks_client
             .process_transaction(Transaction::new_signed_with_payer(
-                &[finalize_transfer(
-                    &context.program_id,
-                    &multisig_op_account.pubkey(),
-                    &context.wallet_account.pubkey(),
-                    &balance_account,
-                    &context.destination.pubkey(),
-                    &context.payer.pubkey(),
-                    context.balance_account_guid_hash,
-                    123,
-                    &system_program::id(),
-                    None,
-                )],
+                &[finalize_transfer_instruction.clone()],
                 Some(&context.payer.pubkey()),
                 &[&context.payer],
                 context.recent_blockhash,
@@ -275,96 +422,1116 @@ async fn test_transfer_requires_multisig() {
             .await
             .unwrap_err()
             .unwrap(),
-        TransactionError::InstructionError(
-            0,
-            Custom(WalletError::TransferDispositionNotFinal as u32)
-        ),
+        TransactionError::InstructionError(0, Custom(WalletError::InsufficientBalance as u32)),
     );
-}
 
-#[tokio::test]
-async fn test_approval_fails_if_incorrect_params_hash() {
-    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
-    let (multisig_op_account, result) =
-        setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
-    result.unwrap();
+    let rent_exempt_minimum = context.rent.minimum_balance(0);
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &context.payer.pubkey(),
+                &balance_account,
+                rent_exempt_minimum + 4321,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_transfer_instruction],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
 
+    // everything above the rent exempt minimum is sent, and nothing is left outstanding
     assert_eq!(
         context
             .banks_client
-            .process_transaction(Transaction::new_signed_with_payer(
-                &[set_approval_disposition(
-                    &context.program_id,
-                    &multisig_op_account.pubkey(),
-                    &context.approvers[1].pubkey(),
-                    ApprovalDisposition::APPROVE,
-                    Hash::new_from_array([0; 32])
-                )],
-                Some(&context.payer.pubkey()),
-                &[&context.payer, &context.approvers[1]],
-                context.recent_blockhash,
-            ))
+            .get_balance(balance_account)
             .await
-            .unwrap_err()
             .unwrap(),
-        TransactionError::InstructionError(0, Custom(WalletError::InvalidSignature as u32)),
+        rent_exempt_minimum
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(context.destination.pubkey())
+            .await
+            .unwrap(),
+        4321
+    );
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    assert_eq!(
+        wallet
+            .get_balance_account(&context.balance_account_guid_hash)
+            .unwrap()
+            .outstanding_transfer_value,
+        0
     );
 }
 
+/// Drops the clock sysvar account, as clients that rely on the clock syscall do.
+fn without_clock_account(mut instruction: Instruction) -> Instruction {
+    instruction
+        .accounts
+        .retain(|account| account.pubkey != sysvar::clock::id());
+    instruction
+}
+
 #[tokio::test]
-async fn test_transfer_insufficient_balance() {
+async fn test_transfer_sol_without_clock_account() {
     let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
-    let (multisig_op_account, result) =
-        setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
-    result.unwrap();
-
-    approve_or_deny_n_of_n_multisig_op(
-        context.banks_client.borrow_mut(),
-        &context.program_id,
-        &multisig_op_account.pubkey(),
-        vec![&context.approvers[0], &context.approvers[1]],
-        &context.payer,
-        context.recent_blockhash,
-        ApprovalDisposition::APPROVE,
-        OperationDisposition::APPROVED,
-    )
-    .await;
 
-    assert_eq!(
-        context
-            .banks_client
-            .process_transaction(Transaction::new_signed_with_payer(
-                &[finalize_transfer(
-                    &context.program_id,
+    let multisig_op_account = Keypair::new();
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.payer.pubkey(),
                     &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                without_clock_account(init_transfer(
+                    &context.program_id,
                     &context.wallet_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    &context.initiator_account.pubkey(),
                     &balance_account,
                     &context.destination.pubkey(),
-                    &context.payer.pubkey(),
                     context.balance_account_guid_hash,
                     123,
+                    context.destination_name_hash,
                     &system_program::id(),
+                    &context.payer.pubkey(),
                     None,
-                )],
-                Some(&context.payer.pubkey()),
-                &[&context.payer],
-                context.recent_blockhash,
-            ))
-            .await
-            .unwrap_err()
-            .unwrap(),
-        TransactionError::InstructionError(0, Custom(WalletError::InsufficientBalance as u32)),
+                )),
+            ],
+            Some(&context.payer.pubkey()),
+            &[
+                &context.payer,
+                &multisig_op_account,
+                &context.initiator_account,
+            ],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // approvals with and without the clock account count alike
+    let params_hash =
+        get_operation_hash(&mut context.banks_client, multisig_op_account.pubkey()).await;
+    for (approver, with_clock) in vec![
+        (&context.approvers[0], false),
+        (&context.approvers[1], true),
+    ] {
+        let instruction = set_approval_disposition(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &multisig_op_account.pubkey(),
+            &approver.pubkey(),
+            ApprovalDisposition::APPROVE,
+            params_hash,
+        );
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[if with_clock {
+                    instruction
+                } else {
+                    without_clock_account(instruction)
+                }],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, approver],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        get_multisig_op_data(&mut context.banks_client, multisig_op_account.pubkey())
+            .await
+            .operation_disposition,
+        OperationDisposition::APPROVED
+    );
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::transfer(&context.payer.pubkey(), &balance_account, 1000),
+                without_clock_account(finalize_transfer(
+                    &context.program_id,
+                    &multisig_op_account.pubkey(),
+                    &context.wallet_account.pubkey(),
+                    &balance_account,
+                    &context.destination.pubkey(),
+                    &context.payer.pubkey(),
+                    context.balance_account_guid_hash,
+                    123,
+                    &system_program::id(),
+                    None,
+                )),
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(context.destination.pubkey())
+            .await
+            .unwrap(),
+        123
     );
 }
 
 #[tokio::test]
-async fn test_transfer_unwhitelisted_address() {
+async fn test_transfer_sol_denied() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let (multisig_op_account, result) =
+        setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    result.unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.payer,
+        context.recent_blockhash,
+        ApprovalDisposition::DENY,
+        OperationDisposition::DENIED,
+    )
+    .await;
+
+    // transfer enough balance from fee payer to source account
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &context.payer.pubkey(),
+                &balance_account,
+                1000,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(balance_account)
+            .await
+            .unwrap(),
+        1000
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(context.destination.pubkey())
+            .await
+            .unwrap(),
+        0
+    );
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_transfer(
+                &context.program_id,
+                &multisig_op_account.pubkey(),
+                &context.wallet_account.pubkey(),
+                &balance_account,
+                &context.destination.pubkey(),
+                &context.payer.pubkey(),
+                context.balance_account_guid_hash,
+                123,
+                &system_program::id(),
+                None,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // balances should all be the same
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(balance_account)
+            .await
+            .unwrap(),
+        1000
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(context.destination.pubkey())
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_denial_reason_required_above_threshold() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    update_denial_reason_threshold(&mut context, 100).await;
+
+    // transfers below the threshold can be denied without a reason
+    let (multisig_op_account, result) =
+        setup_transfer_test(context.borrow_mut(), &balance_account, None, Some(99)).await;
+    result.unwrap();
+    assert!(
+        !get_multisig_op_data(&mut context.banks_client, multisig_op_account.pubkey())
+            .await
+            .denial_reason_required
+    );
+
+    let (multisig_op_account, result) =
+        setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    result.unwrap();
+    assert!(
+        get_multisig_op_data(&mut context.banks_client, multisig_op_account.pubkey())
+            .await
+            .denial_reason_required
+    );
+
+    let params_hash = get_operation_hash(
+        context.banks_client.borrow_mut(),
+        multisig_op_account.pubkey(),
+    )
+    .await;
+    let reason_hash = hash(b"destination flagged by compliance");
+    let deny_transactions = [DenialReason::None, DenialReason::SuspectedFraud]
+        .iter()
+        .map(|denial_reason| {
+            Transaction::new_signed_with_payer(
+                &[set_approval_disposition_with_reason(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    &context.approvers[0].pubkey(),
+                    ApprovalDisposition::DENY,
+                    params_hash,
+                    *denial_reason,
+                    Some(reason_hash),
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, &context.approvers[0]],
+                context.recent_blockhash,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(deny_transactions[0].clone())
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::DenialReasonRequired as u32)),
+    );
+
+    context
+        .banks_client
+        .process_transaction(deny_transactions[1].clone())
+        .await
+        .unwrap();
+
+    let multisig_op =
+        get_multisig_op_data(&mut context.banks_client, multisig_op_account.pubkey()).await;
+    let record = multisig_op
+        .disposition_records
+        .iter()
+        .find(|record| record.approver == context.approvers[0].pubkey())
+        .unwrap();
+    assert_eq!(record.disposition, ApprovalDisposition::DENY);
+    assert_eq!(record.denial_reason, DenialReason::SuspectedFraud);
+    assert_eq!(record.reason_hash, Some(reason_hash));
+}
+
+#[tokio::test]
+async fn test_transfer_wrong_destination_name_hash() {
     let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+
     account_settings_update(&mut context, Some(BooleanSetting::On), None, None).await;
+    let destination_to_add = context.allowed_destination;
+    modify_whitelist(
+        &mut context,
+        vec![(SlotId::new(0), destination_to_add)],
+        vec![],
+        None,
+    )
+    .await;
+
+    context.destination_name_hash = AddressBookEntryNameHash::zero();
 
     let (_, result) = setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
     assert_eq!(
         result.unwrap_err().unwrap(),
         TransactionError::InstructionError(1, Custom(WalletError::DestinationNotAllowed as u32)),
+    )
+}
+
+#[tokio::test]
+async fn test_transfer_requires_multisig() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let (multisig_op_account, result) =
+        setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    result.unwrap();
+
+    approve_or_deny_1_of_2_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        &context.approvers[0],
+        &context.payer,
+        &context.approvers[1].pubkey(),
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+    )
+    .await;
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[finalize_transfer(
+                    &context.program_id,
+                    &multisig_op_account.pubkey(),
+                    &context.wallet_account.pubkey(),
+                    &balance_account,
+                    &context.destination.pubkey(),
+                    &context.payer.pubkey(),
+                    context.balance_account_guid_hash,
+                    123,
+                    &system_program::id(),
+                    None,
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::NotApproved as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_approve_multiple_transfers_at_once() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let mut multisig_op_accounts = Vec::new();
+    for _ in 0..2 {
+        let (multisig_op_account, result) =
+            setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+        result.unwrap();
+        multisig_op_accounts.push(multisig_op_account.pubkey());
+    }
+
+    let mut dispositions = Vec::new();
+    for multisig_op_account in multisig_op_accounts.iter() {
+        dispositions.push((
+            *multisig_op_account,
+            ApprovalDispositionUpdate {
+                disposition: ApprovalDisposition::APPROVE,
+                params_hash: get_operation_hash(
+                    context.banks_client.borrow_mut(),
+                    *multisig_op_account,
+                )
+                .await,
+                denial_reason: DenialReason::None,
+                reason_hash: None,
+            },
+        ));
+    }
+
+    for approver in context.approvers[0..2].iter() {
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[set_approval_dispositions(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &approver.pubkey(),
+                    dispositions.clone(),
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, approver],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap();
+    }
+
+    for multisig_op_account in multisig_op_accounts {
+        assert_eq!(
+            get_multisig_op_data(&mut context.banks_client, multisig_op_account)
+                .await
+                .operation_disposition,
+            OperationDisposition::APPROVED
+        );
+    }
+
+    // a mismatched params hash fails the batch
+    dispositions[0].1.params_hash = Hash::new_from_array([0; 32]);
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[set_approval_dispositions(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &context.approvers[0].pubkey(),
+                    dispositions,
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, &context.approvers[0]],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidSignature as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_approval_requires_the_ops_wallet() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let (multisig_op_account, result) =
+        setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    result.unwrap();
+    let params_hash =
+        get_operation_hash(&mut context.banks_client, multisig_op_account.pubkey()).await;
+
+    // leaving the wallet out, or passing another one, would skip recording
+    // the approver's activity
+    for wallet_account in vec![None, Some(Pubkey::new_unique())] {
+        let mut instruction = set_approval_disposition(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &multisig_op_account.pubkey(),
+            &context.approvers[0].pubkey(),
+            ApprovalDisposition::APPROVE,
+            params_hash,
+        );
+        instruction
+            .accounts
+            .retain(|account| account.pubkey != context.wallet_account.pubkey());
+        if let Some(wallet_account) = wallet_account {
+            instruction
+                .accounts
+                .push(AccountMeta::new(wallet_account, false));
+        }
+        assert_instruction_error(
+            context
+                .banks_client
+                .process_transaction(Transaction::new_signed_with_payer(
+                    &[instruction],
+                    Some(&context.payer.pubkey()),
+                    &[&context.payer, &context.approvers[0]],
+                    context.recent_blockhash,
+                ))
+                .await,
+            0,
+            Custom(WalletError::AccountNotRecognized as u32),
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_approval_fails_if_incorrect_params_hash() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let (multisig_op_account, result) =
+        setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    result.unwrap();
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[set_approval_disposition(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    &context.approvers[1].pubkey(),
+                    ApprovalDisposition::APPROVE,
+                    Hash::new_from_array([0; 32])
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, &context.approvers[1]],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidSignature as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_insufficient_balance() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let (multisig_op_account, result) =
+        setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    result.unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.payer,
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[finalize_transfer(
+                    &context.program_id,
+                    &multisig_op_account.pubkey(),
+                    &context.wallet_account.pubkey(),
+                    &balance_account,
+                    &context.destination.pubkey(),
+                    &context.payer.pubkey(),
+                    context.balance_account_guid_hash,
+                    123,
+                    &system_program::id(),
+                    None,
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InsufficientBalance as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_unwhitelisted_address() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    account_settings_update(&mut context, Some(BooleanSetting::On), None, None).await;
+
+    let (_, result) = setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::DestinationNotAllowed as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_approval_timeout_override() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+
+    async fn init_transfer_with_timeout(
+        context: &mut BalanceAccountTestContext,
+        balance_account: &Pubkey,
+        approval_timeout: Duration,
+    ) -> (Keypair, transport::Result<()>) {
+        let multisig_op_account = Keypair::new();
+        let result = context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[
+                    system_instruction::create_account(
+                        &context.payer.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        context.rent.minimum_balance(MultisigOp::LEN),
+                        MultisigOp::LEN as u64,
+                        &context.program_id,
+                    ),
+                    init_transfer(
+                        &context.program_id,
+                        &context.wallet_account.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        &context.initiator_account.pubkey(),
+                        balance_account,
+                        &context.destination.pubkey(),
+                        context.balance_account_guid_hash,
+                        123,
+                        context.destination_name_hash,
+                        &system_program::id(),
+                        &context.payer.pubkey(),
+                        Some(approval_timeout),
+                    ),
+                ],
+                Some(&context.payer.pubkey()),
+                &[
+                    &context.payer,
+                    &multisig_op_account,
+                    &context.initiator_account,
+                ],
+                context.recent_blockhash,
+            ))
+            .await;
+        (multisig_op_account, result)
+    }
+
+    // a shorter timeout than the balance account's is recorded on the op
+    let initialized_at = SystemTime::now();
+    let (multisig_op_account, result) =
+        init_transfer_with_timeout(&mut context, &balance_account, Duration::from_secs(90)).await;
+    result.unwrap();
+    assert_multisig_op_timestamps(
+        &get_multisig_op_data(&mut context.banks_client, multisig_op_account.pubkey()).await,
+        initialized_at,
+        Duration::from_secs(90),
+    );
+
+    // below the program minimum
+    let (_, result) =
+        init_transfer_with_timeout(&mut context, &balance_account, Duration::from_secs(30)).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidApprovalTimeout as u32)),
+    );
+
+    // longer than the balance account's timeout
+    let (_, result) =
+        init_transfer_with_timeout(&mut context, &balance_account, Duration::from_secs(300)).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidApprovalTimeout as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_preflight_transfer() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &context.payer.pubkey(),
+                &balance_account,
+                1_000_000,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    async fn preflight(
+        context: &mut BalanceAccountTestContext,
+        balance_account: &Pubkey,
+        amount: u64,
+    ) -> transport::Result<()> {
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[preflight_transfer(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    balance_account,
+                    &context.destination.pubkey(),
+                    context.balance_account_guid_hash,
+                    amount,
+                    &system_program::id(),
+                    context.destination_name_hash,
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer],
+                context.recent_blockhash,
+            ))
+            .await
+    }
+
+    // the destination is empty and would not be rent exempt after a small transfer
+    assert_eq!(
+        preflight(&mut context, &balance_account, 123)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::DestinationNotRentExempt as u32)),
+    );
+
+    let rent_exempt_amount = context.rent.minimum_balance(0);
+    preflight(&mut context, &balance_account, rent_exempt_amount)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        preflight(&mut context, &balance_account, 2_000_000)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InsufficientBalance as u32)),
+    );
+
+    // nothing was written
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(balance_account)
+            .await
+            .unwrap(),
+        1_000_000
+    );
+
+    account_settings_update(&mut context, Some(BooleanSetting::On), None, None).await;
+    assert_eq!(
+        preflight(&mut context, &balance_account, rent_exempt_amount + 1)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::DestinationNotAllowed as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_outstanding_value_cap() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    update_balance_account_policy(
+        &mut context,
+        BalanceAccountPolicyUpdate {
+            approvals_required_for_transfer: None,
+            approval_timeout_for_transfer: None,
+            add_transfer_approvers: None,
+            remove_transfer_approvers: None,
+            use_config_approvers: None,
+            token_mint_allowlist_enabled: None,
+            add_allowed_token_mints: None,
+            remove_allowed_token_mints: None,
+            max_outstanding_transfer_value: Some(200),
+            max_finalize_fee_reimbursement: None,
+            policy_rules: None,
+            large_transfer_cooldown: None,
+        },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let (multisig_op_account, result) =
+        setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    result.unwrap();
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    assert_eq!(
+        wallet
+            .get_balance_account(&context.balance_account_guid_hash)
+            .unwrap()
+            .outstanding_transfer_value,
+        123
+    );
+
+    // a second open transfer would exceed the cap
+    let (_, result) = setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            1,
+            Custom(WalletError::OutstandingTransferValueExceeded as u32)
+        ),
+    );
+
+    // finalizing the first one releases its value, even if it was denied
+    approve_or_deny_n_of_n_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.payer,
+        context.recent_blockhash,
+        ApprovalDisposition::DENY,
+        OperationDisposition::DENIED,
+    )
+    .await;
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_transfer(
+                &context.program_id,
+                &multisig_op_account.pubkey(),
+                &context.wallet_account.pubkey(),
+                &balance_account,
+                &context.destination.pubkey(),
+                &context.payer.pubkey(),
+                context.balance_account_guid_hash,
+                123,
+                &system_program::id(),
+                None,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    assert_eq!(
+        wallet
+            .get_balance_account(&context.balance_account_guid_hash)
+            .unwrap()
+            .outstanding_transfer_value,
+        0
+    );
+
+    let (_, result) = setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    result.unwrap();
+}
+
+#[tokio::test]
+async fn test_expire_op_requires_an_expired_op() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let (multisig_op_account, result) =
+        setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    result.unwrap();
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[expire_op(
+                    &context.program_id,
+                    &multisig_op_account.pubkey(),
+                    &context.wallet_account.pubkey(),
+                    &context.payer.pubkey(),
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::NotApproved as u32)),
+    );
+
+    // the pending transfer keeps holding its value
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    assert_eq!(
+        wallet
+            .get_balance_account(&context.balance_account_guid_hash)
+            .unwrap()
+            .outstanding_transfer_value,
+        123
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_sol_with_fee_reimbursement() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    update_balance_account_policy(
+        &mut context,
+        BalanceAccountPolicyUpdate {
+            approvals_required_for_transfer: None,
+            approval_timeout_for_transfer: None,
+            add_transfer_approvers: None,
+            remove_transfer_approvers: None,
+            use_config_approvers: None,
+            token_mint_allowlist_enabled: None,
+            add_allowed_token_mints: None,
+            remove_allowed_token_mints: None,
+            max_outstanding_transfer_value: None,
+            max_finalize_fee_reimbursement: Some(5000),
+            policy_rules: None,
+            large_transfer_cooldown: None,
+        },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let (multisig_op_account, result) =
+        setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    result.unwrap();
+    approve_or_deny_n_of_n_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.payer,
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    // a third-party relayer pays for and collects the rent of the finalize
+    let relayer = Keypair::new();
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::transfer(&context.payer.pubkey(), &balance_account, 10000),
+                system_instruction::transfer(&context.payer.pubkey(), &relayer.pubkey(), 1_000_000),
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let finalize_with_reimbursement = |fee_reimbursement: u64| {
+        let mut instruction = finalize_transfer(
+            &context.program_id,
+            &multisig_op_account.pubkey(),
+            &context.wallet_account.pubkey(),
+            &balance_account,
+            &context.destination.pubkey(),
+            &relayer.pubkey(),
+            context.balance_account_guid_hash,
+            123,
+            &system_program::id(),
+            None,
+        );
+        instruction.data = ProgramInstruction::FinalizeTransfer {
+            account_guid_hash: context.balance_account_guid_hash,
+            amount: 123,
+            token_mint: system_program::id(),
+            memo_hash: None,
+            fee_reimbursement,
+            ata_rent_payer: None,
+            token_program: None,
+            amount_spec: AmountSpec::Exact,
+            travel_rule_hash: None,
+        }
+        .pack();
+        Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&relayer.pubkey()),
+            &[&relayer],
+            context.recent_blockhash,
+        )
+    };
+    let too_much = finalize_with_reimbursement(5001);
+    let within_limit = finalize_with_reimbursement(5000);
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(too_much)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::FeeReimbursementExceeded as u32)),
+    );
+
+    context
+        .banks_client
+        .process_transaction(within_limit)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(balance_account)
+            .await
+            .unwrap(),
+        10000 - 123 - 5000
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(context.destination.pubkey())
+            .await
+            .unwrap(),
+        123
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_sol_with_amount_tier_policy_rule() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let policy_update =
+        |approvals_required_for_transfer, policy_rules| BalanceAccountPolicyUpdate {
+            approvals_required_for_transfer,
+            approval_timeout_for_transfer: None,
+            add_transfer_approvers: None,
+            remove_transfer_approvers: None,
+            use_config_approvers: None,
+            token_mint_allowlist_enabled: None,
+            add_allowed_token_mints: None,
+            remove_allowed_token_mints: None,
+            max_outstanding_transfer_value: None,
+            max_finalize_fee_reimbursement: None,
+            policy_rules: Some(policy_rules),
+            large_transfer_cooldown: None,
+        };
+
+    // a rule can't ask for more approvals than the balance account has approvers
+    update_balance_account_policy(
+        &mut context,
+        policy_update(
+            None,
+            vec![PolicyRule::AmountTier {
+                min_amount: 100,
+                approvals_required: 3,
+            }],
+        ),
+        Some(Custom(WalletError::InvalidPolicyRule as u32)),
+    )
+    .await;
+
+    update_balance_account_policy(
+        &mut context,
+        policy_update(
+            Some(1),
+            vec![PolicyRule::AmountTier {
+                min_amount: 100,
+                approvals_required: 2,
+            }],
+        ),
+        None,
+    )
+    .await
+    .unwrap();
+
+    let (multisig_op_account, result) =
+        setup_transfer_test(context.borrow_mut(), &balance_account, None, Some(99)).await;
+    result.unwrap();
+    assert_eq!(
+        get_multisig_op_data(&mut context.banks_client, multisig_op_account.pubkey())
+            .await
+            .dispositions_required,
+        1
+    );
+
+    let (multisig_op_account, result) =
+        setup_transfer_test(context.borrow_mut(), &balance_account, None, Some(100)).await;
+    result.unwrap();
+    assert_eq!(
+        get_multisig_op_data(&mut context.banks_client, multisig_op_account.pubkey())
+            .await
+            .dispositions_required,
+        2
     );
 }
@@ -15,10 +15,13 @@ use common::instructions::{
     init_balance_account_policy_update_instruction,
 };
 use std::collections::HashSet;
+use strike_wallet::client::DEFAULT_LAMPORTS_PER_SIGNATURE;
 use strike_wallet::error::WalletError;
 use strike_wallet::instruction::BalanceAccountPolicyUpdate;
 use strike_wallet::model::balance_account::{BalanceAccountGuidHash, BalanceAccountNameHash};
-use strike_wallet::model::multisig_op::{ApprovalDisposition, OperationDisposition};
+use strike_wallet::model::multisig_op::{
+    ApprovalDisposition, BooleanSetting, OperationDisposition,
+};
 use strike_wallet::utils::SlotId;
 use {
     solana_program::system_instruction,
@@ -43,8 +46,22 @@ async fn test_balance_account_policy_update() {
     let update = BalanceAccountPolicyUpdate {
         approvals_required_for_transfer: Some(1),
         approval_timeout_for_transfer: Some(Duration::from_secs(7200)),
-        add_transfer_approvers: vec![(SlotId::new(2), context.approvers[2].pubkey_as_signer())],
-        remove_transfer_approvers: vec![(SlotId::new(0), context.approvers[0].pubkey_as_signer())],
+        add_transfer_approvers: Some(vec![(
+            SlotId::new(2),
+            context.approvers[2].pubkey_as_signer(),
+        )]),
+        remove_transfer_approvers: Some(vec![(
+            SlotId::new(0),
+            context.approvers[0].pubkey_as_signer(),
+        )]),
+        use_config_approvers: None,
+        token_mint_allowlist_enabled: None,
+        add_allowed_token_mints: None,
+        remove_allowed_token_mints: None,
+        max_outstanding_transfer_value: None,
+        max_finalize_fee_reimbursement: None,
+        policy_rules: None,
+        large_transfer_cooldown: None,
     };
     let multisig_op_account = update_balance_account_policy(&mut context, update, None)
         .await
@@ -102,8 +119,16 @@ async fn test_balance_account_policy_update() {
         BalanceAccountPolicyUpdate {
             approvals_required_for_transfer: None,
             approval_timeout_for_transfer: Some(Duration::from_secs(6200)),
-            add_transfer_approvers: vec![],
-            remove_transfer_approvers: vec![],
+            add_transfer_approvers: None,
+            remove_transfer_approvers: None,
+            use_config_approvers: None,
+            token_mint_allowlist_enabled: None,
+            add_allowed_token_mints: None,
+            remove_allowed_token_mints: None,
+            max_outstanding_transfer_value: None,
+            max_finalize_fee_reimbursement: None,
+            policy_rules: None,
+            large_transfer_cooldown: None,
         },
         None,
     )
@@ -123,8 +148,51 @@ async fn test_balance_account_policy_update() {
         BalanceAccountPolicyUpdate {
             approvals_required_for_transfer: Some(2),
             approval_timeout_for_transfer: None,
-            add_transfer_approvers: vec![],
-            remove_transfer_approvers: vec![],
+            add_transfer_approvers: None,
+            remove_transfer_approvers: None,
+            use_config_approvers: None,
+            token_mint_allowlist_enabled: None,
+            add_allowed_token_mints: None,
+            remove_allowed_token_mints: None,
+            max_outstanding_transfer_value: None,
+            max_finalize_fee_reimbursement: None,
+            policy_rules: None,
+            large_transfer_cooldown: None,
+        },
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        expected_balance_account,
+        get_wallet(&mut context.banks_client, &context.wallet_account.pubkey())
+            .await
+            .get_balance_account(&context.balance_account_guid_hash)
+            .unwrap()
+    );
+
+    // adding an approver without a removal list leaves the other approvers in place
+    expected_balance_account
+        .transfer_approvers
+        .enable(&SlotId::new(0));
+    update_balance_account_policy(
+        &mut context,
+        BalanceAccountPolicyUpdate {
+            approvals_required_for_transfer: None,
+            approval_timeout_for_transfer: None,
+            add_transfer_approvers: Some(vec![(
+                SlotId::new(0),
+                context.approvers[0].pubkey_as_signer(),
+            )]),
+            remove_transfer_approvers: None,
+            use_config_approvers: None,
+            token_mint_allowlist_enabled: None,
+            add_allowed_token_mints: None,
+            remove_allowed_token_mints: None,
+            max_outstanding_transfer_value: None,
+            max_finalize_fee_reimbursement: None,
+            policy_rules: None,
+            large_transfer_cooldown: None,
         },
         None,
     )
@@ -150,15 +218,37 @@ async fn test_only_one_pending_balance_account_policy_update_allowed_at_time() {
     let update1 = BalanceAccountPolicyUpdate {
         approvals_required_for_transfer: Some(1),
         approval_timeout_for_transfer: Some(Duration::from_secs(7200)),
-        add_transfer_approvers: vec![(SlotId::new(2), context.approvers[2].pubkey_as_signer())],
-        remove_transfer_approvers: vec![(SlotId::new(0), context.approvers[0].pubkey_as_signer())],
+        add_transfer_approvers: Some(vec![(
+            SlotId::new(2),
+            context.approvers[2].pubkey_as_signer(),
+        )]),
+        remove_transfer_approvers: Some(vec![(
+            SlotId::new(0),
+            context.approvers[0].pubkey_as_signer(),
+        )]),
+        use_config_approvers: None,
+        token_mint_allowlist_enabled: None,
+        add_allowed_token_mints: None,
+        remove_allowed_token_mints: None,
+        max_outstanding_transfer_value: None,
+        max_finalize_fee_reimbursement: None,
+        policy_rules: None,
+        large_transfer_cooldown: None,
     };
 
     let update2 = BalanceAccountPolicyUpdate {
         approvals_required_for_transfer: None,
         approval_timeout_for_transfer: Some(Duration::from_secs(6200)),
-        add_transfer_approvers: vec![],
-        remove_transfer_approvers: vec![],
+        add_transfer_approvers: None,
+        remove_transfer_approvers: None,
+        use_config_approvers: None,
+        token_mint_allowlist_enabled: None,
+        add_allowed_token_mints: None,
+        remove_allowed_token_mints: None,
+        max_outstanding_transfer_value: None,
+        max_finalize_fee_reimbursement: None,
+        policy_rules: None,
+        large_transfer_cooldown: None,
     };
 
     context
@@ -291,8 +381,22 @@ async fn test_balance_account_policy_update_is_denied() {
     let update = BalanceAccountPolicyUpdate {
         approvals_required_for_transfer: Some(1),
         approval_timeout_for_transfer: Some(Duration::from_secs(7200)),
-        add_transfer_approvers: vec![(SlotId::new(2), context.approvers[2].pubkey_as_signer())],
-        remove_transfer_approvers: vec![(SlotId::new(0), context.approvers[0].pubkey_as_signer())],
+        add_transfer_approvers: Some(vec![(
+            SlotId::new(2),
+            context.approvers[2].pubkey_as_signer(),
+        )]),
+        remove_transfer_approvers: Some(vec![(
+            SlotId::new(0),
+            context.approvers[0].pubkey_as_signer(),
+        )]),
+        use_config_approvers: None,
+        token_mint_allowlist_enabled: None,
+        add_allowed_token_mints: None,
+        remove_allowed_token_mints: None,
+        max_outstanding_transfer_value: None,
+        max_finalize_fee_reimbursement: None,
+        policy_rules: None,
+        large_transfer_cooldown: None,
     };
 
     let balance_account_update_transaction = Transaction::new_signed_with_payer(
@@ -415,14 +519,14 @@ async fn test_balance_account_policy_update_is_denied() {
         .unwrap()
         .is_none());
 
-    // and that the remaining balance went to the rent collector (less the 5000 in signature fees for the finalize)
+    // and that the remaining balance went to the rent collector (less the signature fee for the finalize)
     let ending_rent_collector_balance = context
         .banks_client
         .get_balance(context.payer.pubkey())
         .await
         .unwrap();
     assert_eq!(
-        starting_rent_collector_balance + op_account_balance - 5000,
+        starting_rent_collector_balance + op_account_balance - DEFAULT_LAMPORTS_PER_SIGNATURE,
         ending_rent_collector_balance
     );
 }
@@ -450,8 +554,16 @@ async fn invalid_balance_account_policy_updates() {
                 BalanceAccountPolicyUpdate {
                     approvals_required_for_transfer: Some(2),
                     approval_timeout_for_transfer: Some(Duration::from_secs(7200)),
-                    add_transfer_approvers: vec![],
-                    remove_transfer_approvers: vec![],
+                    add_transfer_approvers: None,
+                    remove_transfer_approvers: None,
+                    use_config_approvers: None,
+                    token_mint_allowlist_enabled: None,
+                    add_allowed_token_mints: None,
+                    remove_allowed_token_mints: None,
+                    max_outstanding_transfer_value: None,
+                    max_finalize_fee_reimbursement: None,
+                    policy_rules: None,
+                    large_transfer_cooldown: None,
                 },
             ),
             Custom(WalletError::BalanceAccountNotFound as u32),
@@ -476,8 +588,16 @@ async fn invalid_balance_account_policy_updates() {
                 BalanceAccountPolicyUpdate {
                     approvals_required_for_transfer: Some(3),
                     approval_timeout_for_transfer: Some(Duration::from_secs(7200)),
-                    add_transfer_approvers: vec![],
-                    remove_transfer_approvers: vec![],
+                    add_transfer_approvers: None,
+                    remove_transfer_approvers: None,
+                    use_config_approvers: None,
+                    token_mint_allowlist_enabled: None,
+                    add_allowed_token_mints: None,
+                    remove_allowed_token_mints: None,
+                    max_outstanding_transfer_value: None,
+                    max_finalize_fee_reimbursement: None,
+                    policy_rules: None,
+                    large_transfer_cooldown: None,
                 },
             ),
             Custom(WalletError::InvalidApproverCount as u32),
@@ -502,11 +622,19 @@ async fn invalid_balance_account_policy_updates() {
                 BalanceAccountPolicyUpdate {
                     approvals_required_for_transfer: Some(2),
                     approval_timeout_for_transfer: Some(Duration::from_secs(7200)),
-                    add_transfer_approvers: vec![(
+                    add_transfer_approvers: Some(vec![(
                         SlotId::new(2),
                         Keypair::new().pubkey_as_signer(),
-                    )],
-                    remove_transfer_approvers: vec![],
+                    )]),
+                    remove_transfer_approvers: None,
+                    use_config_approvers: None,
+                    token_mint_allowlist_enabled: None,
+                    add_allowed_token_mints: None,
+                    remove_allowed_token_mints: None,
+                    max_outstanding_transfer_value: None,
+                    max_finalize_fee_reimbursement: None,
+                    policy_rules: None,
+                    large_transfer_cooldown: None,
                 },
             ),
             Custom(WalletError::UnknownSigner as u32),
@@ -531,11 +659,19 @@ async fn invalid_balance_account_policy_updates() {
                 BalanceAccountPolicyUpdate {
                     approvals_required_for_transfer: Some(2),
                     approval_timeout_for_transfer: Some(Duration::from_secs(7200)),
-                    add_transfer_approvers: vec![],
-                    remove_transfer_approvers: vec![(
+                    add_transfer_approvers: None,
+                    remove_transfer_approvers: Some(vec![(
                         SlotId::new(0),
                         context.approvers[1].pubkey_as_signer(),
-                    )],
+                    )]),
+                    use_config_approvers: None,
+                    token_mint_allowlist_enabled: None,
+                    add_allowed_token_mints: None,
+                    remove_allowed_token_mints: None,
+                    max_outstanding_transfer_value: None,
+                    max_finalize_fee_reimbursement: None,
+                    policy_rules: None,
+                    large_transfer_cooldown: None,
                 },
             ),
             Custom(WalletError::InvalidSlot as u32),
@@ -544,6 +680,70 @@ async fn invalid_balance_account_policy_updates() {
     }
 }
 
+#[tokio::test]
+async fn test_balance_account_policy_update_use_config_approvers() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(Some(200000)).await;
+
+    update_balance_account_policy(
+        &mut context,
+        BalanceAccountPolicyUpdate {
+            approvals_required_for_transfer: Some(1),
+            approval_timeout_for_transfer: None,
+            add_transfer_approvers: None,
+            remove_transfer_approvers: Some(vec![
+                (SlotId::new(0), context.approvers[0].pubkey_as_signer()),
+                (SlotId::new(1), context.approvers[1].pubkey_as_signer()),
+            ]),
+            use_config_approvers: Some(BooleanSetting::On),
+            token_mint_allowlist_enabled: None,
+            add_allowed_token_mints: None,
+            remove_allowed_token_mints: None,
+            max_outstanding_transfer_value: None,
+            max_finalize_fee_reimbursement: None,
+            policy_rules: None,
+            large_transfer_cooldown: None,
+        },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    let balance_account = wallet
+        .get_balance_account(&context.balance_account_guid_hash)
+        .unwrap();
+
+    assert!(balance_account.uses_config_approvers());
+    assert_eq!(balance_account.transfer_approvers.count_enabled(), 0);
+    assert_eq!(
+        wallet
+            .get_transfer_approvers_keys(&balance_account)
+            .to_set(),
+        wallet.get_config_approvers_keys().to_set()
+    );
+
+    // turning inheritance off without own transfer approvers is not allowed
+    update_balance_account_policy(
+        &mut context,
+        BalanceAccountPolicyUpdate {
+            approvals_required_for_transfer: None,
+            approval_timeout_for_transfer: None,
+            add_transfer_approvers: None,
+            remove_transfer_approvers: None,
+            use_config_approvers: Some(BooleanSetting::Off),
+            token_mint_allowlist_enabled: None,
+            add_allowed_token_mints: None,
+            remove_allowed_token_mints: None,
+            max_outstanding_transfer_value: None,
+            max_finalize_fee_reimbursement: None,
+            policy_rules: None,
+            large_transfer_cooldown: None,
+        },
+        Some(Custom(WalletError::InvalidApproverCount as u32)),
+    )
+    .await;
+}
+
 #[tokio::test]
 async fn test_update_balance_account_name_happy_path() {
     let mut context = setup_balance_account_tests_and_finalize(None).await.0;
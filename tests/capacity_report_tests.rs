@@ -0,0 +1,74 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::instruction::InstructionError;
+use solana_program_test::tokio;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use strike_wallet::model::wallet::Wallet;
+
+#[tokio::test]
+async fn test_report_capacity() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    let usage = wallet.capacity_usage();
+    let usage_of = |collection: &str| {
+        usage
+            .iter()
+            .find(|usage| usage.collection == collection)
+            .unwrap()
+            .clone()
+    };
+    assert_eq!(usage_of("signers").used, 3);
+    assert_eq!(usage_of("balance accounts").used, 1);
+    assert_eq!(
+        usage_of("balance accounts").free(),
+        Wallet::MAX_BALANCE_ACCOUNTS - 1
+    );
+    // one whitelist per balance account
+    assert_eq!(
+        usage
+            .iter()
+            .filter(|usage| usage.collection.starts_with("whitelist"))
+            .count(),
+        1
+    );
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[report_capacity(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                10,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // thresholds are percentages
+    let mut instruction =
+        report_capacity(&context.program_id, &context.wallet_account.pubkey(), 100);
+    *instruction.data.last_mut().unwrap() = 101;
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&context.payer.pubkey()),
+                &[&context.payer],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidInstructionData),
+    );
+}
@@ -0,0 +1,326 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+pub use common::utils;
+use solana_program::instruction::Instruction;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::Transaction;
+use solana_sdk::transport;
+use std::convert::TryInto;
+use strike_wallet::co_signing::{CoSignature, CoSigningPayload};
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{GovernedParameterUpdate, ProgramInstruction};
+use strike_wallet::model::governed_parameter::GovernedParameter;
+use strike_wallet::model::multisig_op::{
+    ApprovalDisposition, ApprovalDispositionRecord, DenialReason, MultisigOp,
+};
+
+fn co_sign(
+    multisig_op_address: &Pubkey,
+    multisig_op: &MultisigOp,
+    approver: &Keypair,
+    disposition_changes: u16,
+    disposition: ApprovalDisposition,
+) -> CoSignature {
+    let denial_reason = match disposition {
+        ApprovalDisposition::DENY => DenialReason::Other,
+        _ => DenialReason::None,
+    };
+    let payload = CoSigningPayload::new(
+        multisig_op_address,
+        multisig_op,
+        &approver.pubkey(),
+        disposition_changes,
+        disposition,
+        denial_reason,
+        None,
+    );
+    CoSignature {
+        payload,
+        signature: approver
+            .sign_message(&payload.message())
+            .as_ref()
+            .try_into()
+            .unwrap(),
+    }
+}
+
+async fn submit(
+    context: &mut BalanceAccountTestContext,
+    instructions: &[Instruction],
+) -> transport::Result<()> {
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            instructions,
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+}
+
+async fn disposition_record(
+    context: &mut BalanceAccountTestContext,
+    multisig_op: Pubkey,
+    approver: &Keypair,
+) -> ApprovalDispositionRecord {
+    get_multisig_op_data(&mut context.banks_client, multisig_op)
+        .await
+        .disposition_records
+        .into_iter()
+        .find(|record| record.approver == approver.pubkey())
+        .unwrap()
+}
+
+async fn allow_disposition_changes(context: &mut BalanceAccountTestContext) {
+    let update = GovernedParameterUpdate {
+        values: vec![(GovernedParameter::DispositionChangesAllowed, Some(1))],
+    };
+    let mut test_context = context.to_test_context();
+    let multisig_op = utils::init_governed_parameter_update(
+        &mut test_context,
+        context.wallet_account.pubkey(),
+        &context.approvers[0],
+        update.clone(),
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    utils::finalize_governed_parameter_update(
+        &mut test_context,
+        context.wallet_account.pubkey(),
+        multisig_op,
+        update,
+    )
+    .await;
+}
+
+async fn setup_co_signature_test(
+    disposition_changes_allowed: bool,
+) -> (BalanceAccountTestContext, Pubkey) {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    if disposition_changes_allowed {
+        allow_disposition_changes(&mut context).await;
+    }
+    let (multisig_op_account, result) =
+        setup_transfer_test(&mut context, &balance_account, None, None).await;
+    result.unwrap();
+    (context, multisig_op_account.pubkey())
+}
+
+#[tokio::test]
+async fn test_co_signed_disposition_is_recorded() {
+    let (mut context, multisig_op) = setup_co_signature_test(false).await;
+    let multisig_op_data = get_multisig_op_data(&mut context.banks_client, multisig_op).await;
+    let approver = Keypair::from_bytes(&context.approvers[0].to_bytes()).unwrap();
+
+    // the co-signature is carried as text, and submitted by the payer alone
+    let co_signature = CoSignature::from_base64(
+        &co_sign(
+            &multisig_op,
+            &multisig_op_data,
+            &approver,
+            0,
+            ApprovalDisposition::APPROVE,
+        )
+        .to_base64(),
+    )
+    .unwrap();
+    submit(
+        &mut context,
+        &co_signature.instructions(&context.program_id),
+    )
+    .await
+    .unwrap();
+
+    let record = disposition_record(&mut context, multisig_op, &approver).await;
+    assert_eq!(record.disposition, ApprovalDisposition::APPROVE);
+    assert_eq!(record.disposition_changes, 0);
+}
+
+#[tokio::test]
+async fn test_co_signature_requires_the_preceding_ed25519_instruction() {
+    let (mut context, multisig_op) = setup_co_signature_test(false).await;
+    let multisig_op_data = get_multisig_op_data(&mut context.banks_client, multisig_op).await;
+    let approver = Keypair::from_bytes(&context.approvers[0].to_bytes()).unwrap();
+    let instructions = co_sign(
+        &multisig_op,
+        &multisig_op_data,
+        &approver,
+        0,
+        ApprovalDisposition::APPROVE,
+    )
+    .instructions(&context.program_id);
+
+    assert_instruction_error(
+        submit(&mut context, &instructions[1..]).await,
+        0,
+        Custom(WalletError::InvalidSignature as u32),
+    );
+
+    // the ed25519 instruction is in the transaction, but not right before
+    let transfer =
+        system_instruction::transfer(&context.payer.pubkey(), &context.wallet_account.pubkey(), 1);
+    assert_instruction_error(
+        submit(
+            &mut context,
+            &[instructions[0].clone(), transfer, instructions[1].clone()],
+        )
+        .await,
+        2,
+        Custom(WalletError::InvalidSignature as u32),
+    );
+
+    let record = disposition_record(&mut context, multisig_op, &approver).await;
+    assert_eq!(record.disposition, ApprovalDisposition::NONE);
+}
+
+#[tokio::test]
+async fn test_co_signature_must_be_the_approvers_over_the_disposition() {
+    let (mut context, multisig_op) = setup_co_signature_test(false).await;
+    let multisig_op_data = get_multisig_op_data(&mut context.banks_client, multisig_op).await;
+    let approver = Keypair::from_bytes(&context.approvers[0].to_bytes()).unwrap();
+    let other_approver = Keypair::from_bytes(&context.approvers[1].to_bytes()).unwrap();
+
+    // another approver's signature doesn't stand in for the approver's
+    let mut instructions = co_sign(
+        &multisig_op,
+        &multisig_op_data,
+        &other_approver,
+        0,
+        ApprovalDisposition::APPROVE,
+    )
+    .instructions(&context.program_id);
+    instructions[1].accounts[1].pubkey = approver.pubkey();
+    assert_instruction_error(
+        submit(&mut context, &instructions).await,
+        1,
+        Custom(WalletError::InvalidSignature as u32),
+    );
+
+    // and a signature over a denial doesn't record an approval
+    let mut instructions = co_sign(
+        &multisig_op,
+        &multisig_op_data,
+        &approver,
+        0,
+        ApprovalDisposition::DENY,
+    )
+    .instructions(&context.program_id);
+    instructions[1].data = ProgramInstruction::VerifyCoSignature {
+        disposition: ApprovalDisposition::APPROVE,
+        params_hash: multisig_op_data.params_hash,
+        denial_reason: DenialReason::None,
+        reason_hash: None,
+    }
+    .pack();
+    assert_instruction_error(
+        submit(&mut context, &instructions).await,
+        1,
+        Custom(WalletError::InvalidSignature as u32),
+    );
+
+    for approver in [&approver, &other_approver] {
+        let record = disposition_record(&mut context, multisig_op, approver).await;
+        assert_eq!(record.disposition, ApprovalDisposition::NONE);
+    }
+}
+
+#[tokio::test]
+async fn test_co_signature_cannot_be_replayed() {
+    let (mut context, multisig_op) = setup_co_signature_test(true).await;
+    let multisig_op_data = get_multisig_op_data(&mut context.banks_client, multisig_op).await;
+    let approver = Keypair::from_bytes(&context.approvers[0].to_bytes()).unwrap();
+    let instructions = co_sign(
+        &multisig_op,
+        &multisig_op_data,
+        &approver,
+        0,
+        ApprovalDisposition::APPROVE,
+    )
+    .instructions(&context.program_id);
+    submit(&mut context, &instructions).await.unwrap();
+
+    // each replay carries a different transfer, so that it isn't dropped as
+    // a duplicate of the transaction that recorded the approval
+    let replay = |lamports: u64, context: &BalanceAccountTestContext| {
+        let mut replay = instructions.clone();
+        replay.push(system_instruction::transfer(
+            &context.payer.pubkey(),
+            &context.wallet_account.pubkey(),
+            lamports,
+        ));
+        replay
+    };
+
+    let instructions = replay(1, &context);
+    assert_instruction_error(
+        submit(&mut context, &instructions).await,
+        1,
+        Custom(WalletError::InvalidSignature as u32),
+    );
+
+    // once the approver has changed their mind, the approval can't be
+    // brought back without them
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[set_approval_disposition_with_reason(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op,
+                &approver.pubkey(),
+                ApprovalDisposition::DENY,
+                multisig_op_data.params_hash,
+                DenialReason::Other,
+                None,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &approver],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+    let record = disposition_record(&mut context, multisig_op, &approver).await;
+    assert_eq!(record.disposition, ApprovalDisposition::DENY);
+    assert_eq!(record.disposition_changes, 1);
+
+    let instructions = replay(2, &context);
+    assert_instruction_error(
+        submit(&mut context, &instructions).await,
+        1,
+        Custom(WalletError::InvalidSignature as u32),
+    );
+    let record = disposition_record(&mut context, multisig_op, &approver).await;
+    assert_eq!(record.disposition, ApprovalDisposition::DENY);
+
+    // a co-signature given after the change is good for one more change
+    let multisig_op_data = get_multisig_op_data(&mut context.banks_client, multisig_op).await;
+    let instructions = co_sign(
+        &multisig_op,
+        &multisig_op_data,
+        &approver,
+        1,
+        ApprovalDisposition::APPROVE,
+    )
+    .instructions(&context.program_id);
+    submit(&mut context, &instructions).await.unwrap();
+    let record = disposition_record(&mut context, multisig_op, &approver).await;
+    assert_eq!(record.disposition, ApprovalDisposition::APPROVE);
+    assert_eq!(record.disposition_changes, 2);
+}
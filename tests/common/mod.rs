@@ -1,2 +1 @@
-pub mod instructions;
-pub mod utils;
+pub use strike_wallet::test_utils::{instructions, utils};
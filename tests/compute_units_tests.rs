@@ -0,0 +1,61 @@
+//! Compute unit benchmark for recording approval dispositions. With the
+//! `log-compute-units` feature the program logs the units left before and
+//! after recording each disposition by unpacking the op, on a copy of it, and
+//! before and after recording it in place. Run it with
+//! `RUST_LOG=solana_runtime::message_processor=debug cargo test-bpf --features
+//! log-compute-units --test compute_units_tests` and compare the differences
+//! between consecutive logs.
+#![cfg(all(feature = "test-bpf", feature = "log-compute-units"))]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::borrow::BorrowMut;
+
+use solana_program_test::tokio;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::Transaction;
+use strike_wallet::model::multisig_op::{ApprovalDisposition, OperationDisposition};
+
+#[tokio::test]
+async fn test_approval_disposition_compute_units() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let (multisig_op_account, result) =
+        setup_transfer_test(context.borrow_mut(), &balance_account, None, None).await;
+    result.unwrap();
+    let multisig_op = multisig_op_account.pubkey();
+    let params_hash = get_operation_hash(&mut context.banks_client, multisig_op).await;
+
+    for approver in context.approvers[0..2].iter() {
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[set_approval_disposition(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &multisig_op,
+                    &approver.pubkey(),
+                    ApprovalDisposition::APPROVE,
+                    params_hash,
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, approver],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap();
+    }
+
+    // both paths ran, and only the in-place one was written
+    let multisig_op = get_multisig_op_data(&mut context.banks_client, multisig_op).await;
+    assert_eq!(
+        multisig_op.get_disposition_count(ApprovalDisposition::APPROVE),
+        2
+    );
+    assert_eq!(
+        multisig_op.operation_disposition,
+        OperationDisposition::APPROVED
+    );
+}
@@ -4,14 +4,19 @@ pub use common::instructions::*;
 pub use common::utils::*;
 
 pub use common::utils;
+use solana_program::instruction::InstructionError;
 use solana_program_test::tokio;
 use solana_sdk::signature::Keypair;
 use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::Transaction;
 use std::time::{Duration, SystemTime};
 use strike_wallet::instruction::{DAppBookUpdate, InitialWalletConfig};
-use strike_wallet::model::address_book::{DAppBookEntry, DAppBookEntryNameHash};
+use strike_wallet::model::address_book::{
+    DAppBookEntry, DAppBookEntryNameHash, DAppInstructionPrefixes,
+};
 use strike_wallet::model::multisig_op::{
-    ApprovalDisposition, ApprovalDispositionRecord, MultisigOpParams, OperationDisposition,
+    ApprovalDisposition, ApprovalDispositionRecord, DenialReason, MultisigOpParams,
+    OperationDisposition,
 };
 use strike_wallet::utils::{SlotId, Slots};
 
@@ -50,12 +55,19 @@ async fn test_dapp_book_update() {
         DAppBookEntry {
             address: dapp_program_id,
             name_hash: DAppBookEntryNameHash::new(&hash_of(b"DApp Name")),
+            instruction_prefixes: DAppInstructionPrefixes::default(),
         },
     );
 
     let add_dapp = DAppBookUpdate {
         add_dapps: vec![dapp_slot],
         remove_dapps: vec![],
+        add_value_limits: vec![],
+        remove_value_limits: vec![],
+        add_inner_programs: vec![],
+        remove_inner_programs: vec![],
+        add_approval_policies: vec![],
+        remove_approval_policies: vec![],
     };
 
     let multisig_op_account = utils::init_dapp_book_update(
@@ -75,6 +87,9 @@ async fn test_dapp_book_update() {
         &vec![ApprovalDispositionRecord {
             approver: approvers[0].pubkey(),
             disposition: ApprovalDisposition::NONE,
+            denial_reason: DenialReason::None,
+            reason_hash: None,
+            disposition_changes: 0,
         }],
         OperationDisposition::NONE,
         &MultisigOpParams::UpdateDAppBook {
@@ -108,6 +123,12 @@ async fn test_dapp_book_update() {
     let remove_dapp = DAppBookUpdate {
         add_dapps: vec![],
         remove_dapps: vec![dapp_slot],
+        add_value_limits: vec![],
+        remove_value_limits: vec![],
+        add_inner_programs: vec![],
+        remove_inner_programs: vec![],
+        add_approval_policies: vec![],
+        remove_approval_policies: vec![],
     };
 
     let remove_multisig_op_account = utils::init_dapp_book_update(
@@ -127,6 +148,9 @@ async fn test_dapp_book_update() {
         &vec![ApprovalDispositionRecord {
             approver: approvers[0].pubkey(),
             disposition: ApprovalDisposition::NONE,
+            denial_reason: DenialReason::None,
+            reason_hash: None,
+            disposition_changes: 0,
         }],
         OperationDisposition::NONE,
         &MultisigOpParams::UpdateDAppBook {
@@ -157,3 +181,214 @@ async fn test_dapp_book_update() {
             .dapp_book
     );
 }
+
+#[tokio::test]
+async fn test_multisig_op_cannot_be_reinitialized() {
+    let mut context = setup_test(30_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let approver = Keypair::new();
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 1,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![(SlotId::new(0), approver.pubkey_as_signer())],
+            config_approvers: vec![(SlotId::new(0), approver.pubkey_as_signer())],
+        },
+    )
+    .await
+    .unwrap();
+
+    let dapp_update = |name: &[u8]| DAppBookUpdate {
+        add_dapps: vec![(
+            SlotId::new(0),
+            DAppBookEntry {
+                address: Keypair::new().pubkey(),
+                name_hash: DAppBookEntryNameHash::new(&hash_of(name)),
+                instruction_prefixes: DAppInstructionPrefixes::default(),
+            },
+        )],
+        remove_dapps: vec![],
+        add_value_limits: vec![],
+        remove_value_limits: vec![],
+        add_inner_programs: vec![],
+        remove_inner_programs: vec![],
+        add_approval_policies: vec![],
+        remove_approval_policies: vec![],
+    };
+
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    init_multisig_op(
+        &mut context,
+        multisig_op_account,
+        init_dapp_book_update(
+            &context.program_id,
+            &wallet_account.pubkey(),
+            &multisig_op_pubkey,
+            &assistant_account.pubkey(),
+            dapp_update(b"DApp Name"),
+        ),
+        &assistant_account,
+    )
+    .await
+    .unwrap();
+    let multisig_op = get_multisig_op_data(&mut context.banks_client, multisig_op_pubkey).await;
+
+    // a second init on the same op account must not overwrite the pending op
+    assert_instruction_error(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[init_dapp_book_update(
+                    &context.program_id,
+                    &wallet_account.pubkey(),
+                    &multisig_op_pubkey,
+                    &assistant_account.pubkey(),
+                    dapp_update(b"Other DApp Name"),
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, &assistant_account],
+                context.recent_blockhash,
+            ))
+            .await,
+        0,
+        InstructionError::AccountAlreadyInitialized,
+    );
+    assert_eq!(
+        multisig_op.params_hash,
+        get_multisig_op_data(&mut context.banks_client, multisig_op_pubkey)
+            .await
+            .params_hash
+    );
+}
+
+#[tokio::test]
+async fn test_denied_multisig_op_account_can_be_reused() {
+    let mut context = setup_test(30_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let approver = Keypair::new();
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 1,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![(SlotId::new(0), approver.pubkey_as_signer())],
+            config_approvers: vec![(SlotId::new(0), approver.pubkey_as_signer())],
+        },
+    )
+    .await
+    .unwrap();
+
+    let dapp_update = |name: &[u8]| DAppBookUpdate {
+        add_dapps: vec![(
+            SlotId::new(0),
+            DAppBookEntry {
+                address: Keypair::new().pubkey(),
+                name_hash: DAppBookEntryNameHash::new(&hash_of(name)),
+                instruction_prefixes: DAppInstructionPrefixes::default(),
+            },
+        )],
+        remove_dapps: vec![],
+        add_value_limits: vec![],
+        remove_value_limits: vec![],
+        add_inner_programs: vec![],
+        remove_inner_programs: vec![],
+        add_approval_policies: vec![],
+        remove_approval_policies: vec![],
+    };
+
+    let multisig_op_account = Keypair::new();
+    let multisig_op_signer = Keypair::from_bytes(&multisig_op_account.to_bytes()).unwrap();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    init_multisig_op(
+        &mut context,
+        multisig_op_account,
+        init_dapp_book_update(
+            &context.program_id,
+            &wallet_account.pubkey(),
+            &multisig_op_pubkey,
+            &assistant_account.pubkey(),
+            dapp_update(b"DApp Name"),
+        ),
+        &assistant_account,
+    )
+    .await
+    .unwrap();
+    approve_or_deny_n_of_n_multisig_op(
+        &mut context.banks_client,
+        &context.program_id,
+        &multisig_op_pubkey,
+        vec![&approver],
+        &context.payer,
+        context.recent_blockhash,
+        ApprovalDisposition::DENY,
+        OperationDisposition::DENIED,
+    )
+    .await;
+    let denied_op = get_multisig_op_data(&mut context.banks_client, multisig_op_pubkey).await;
+
+    // reusing the account requires the op account's signature
+    assert_instruction_error(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[init_dapp_book_update(
+                    &context.program_id,
+                    &wallet_account.pubkey(),
+                    &multisig_op_pubkey,
+                    &assistant_account.pubkey(),
+                    dapp_update(b"Other DApp Name"),
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, &assistant_account],
+                context.recent_blockhash,
+            ))
+            .await,
+        0,
+        InstructionError::MissingRequiredSignature,
+    );
+
+    let mut reuse_instruction = init_dapp_book_update(
+        &context.program_id,
+        &wallet_account.pubkey(),
+        &multisig_op_pubkey,
+        &assistant_account.pubkey(),
+        dapp_update(b"Other DApp Name"),
+    );
+    reuse_instruction.accounts[0].is_signer = true;
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[reuse_instruction],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &multisig_op_signer, &assistant_account],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let reused_op = get_multisig_op_data(&mut context.banks_client, multisig_op_pubkey).await;
+    assert_ne!(reused_op.params_hash, denied_op.params_hash);
+    assert_eq!(reused_op.operation_disposition, OperationDisposition::NONE);
+    assert_eq!(
+        reused_op.disposition_records[0].disposition,
+        ApprovalDisposition::NONE
+    );
+}
@@ -0,0 +1,304 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+pub use common::utils;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::pubkey::Pubkey;
+use solana_program::{system_instruction, system_program};
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+use std::borrow::BorrowMut;
+use std::time::Duration;
+use strike_wallet::client::execute_dapp_template;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{DAppBookUpdate, DAppTemplatesUpdate};
+use strike_wallet::model::address_book::{
+    DAppBookEntry, DAppBookEntryNameHash, DAppInstructionPrefixes,
+};
+use strike_wallet::model::dapp_template::{DAppTemplate, DAppTemplateField};
+use strike_wallet::model::dapp_value_limit::DAppValueLimit;
+use strike_wallet::model::multisig_op::ApprovalDisposition;
+use strike_wallet::utils::SlotId;
+
+async fn fund_balance_account(context: &mut BalanceAccountTestContext, balance_account: &Pubkey) {
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &context.payer.pubkey(),
+                balance_account,
+                5_000_000_000,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+}
+
+/// Approves the template into slot 0.
+async fn add_template(context: &mut BalanceAccountTestContext, template: DAppTemplate) {
+    let update = DAppTemplatesUpdate {
+        add_templates: vec![(SlotId::new(0), template)],
+        remove_templates: vec![],
+    };
+
+    let mut test_context = context.to_test_context();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    utils::init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init_dapp_templates_update(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &multisig_op_pubkey,
+            &context.assistant_account.pubkey(),
+            update.clone(),
+        ),
+        &context.assistant_account,
+    )
+    .await
+    .unwrap();
+    approve_or_deny_1_of_2_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_pubkey,
+        &context.approvers[0],
+        &context.payer,
+        &context.approvers[1].pubkey(),
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+    )
+    .await;
+    utils::finalize_multisig_op(
+        &mut test_context,
+        multisig_op_pubkey,
+        finalize_dapp_templates_update(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &multisig_op_pubkey,
+            &context.payer.pubkey(),
+            update,
+        ),
+    )
+    .await;
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &context.wallet_account.pubkey())
+            .await
+            .dapp_templates[SlotId::new(0)],
+        Some(template)
+    );
+}
+
+#[tokio::test]
+async fn test_dapp_template_instances_run_without_op() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(100_000)).await;
+    let destination = Keypair::new().pubkey();
+
+    fund_balance_account(&mut context, &balance_account).await;
+
+    // transfers to the destination of up to 2 SOL, the amount following the
+    // 4-byte system instruction tag
+    let template = DAppTemplate::new(
+        context.balance_account_guid_hash,
+        &system_instruction::transfer(&balance_account, &destination, 0),
+        &[DAppTemplateField {
+            offset: 4,
+            max_value: 2_000_000_000,
+        }],
+    )
+    .unwrap();
+    add_template(&mut context, template).await;
+    let mut test_context = context.to_test_context();
+
+    let execute = |amount: u64, destination: &Pubkey, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[execute_dapp_template(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &balance_account,
+                &signer.pubkey(),
+                SlotId::new(0),
+                system_instruction::transfer(&balance_account, destination, amount),
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, signer],
+            context.recent_blockhash,
+        )
+    };
+
+    // only the assistant can execute templates
+    assert_instruction_error(
+        test_context
+            .banks_client
+            .process_transaction(execute(1_000_000_000, &destination, &context.approvers[0]))
+            .await,
+        0,
+        Custom(WalletError::InvalidSignature as u32),
+    );
+    assert_instruction_error(
+        test_context
+            .banks_client
+            .process_transaction(execute(
+                1_000_000_000,
+                &Keypair::new().pubkey(),
+                &context.assistant_account,
+            ))
+            .await,
+        0,
+        Custom(WalletError::DAppTemplateMismatch as u32),
+    );
+    assert_instruction_error(
+        test_context
+            .banks_client
+            .process_transaction(execute(
+                3_000_000_000,
+                &destination,
+                &context.assistant_account,
+            ))
+            .await,
+        0,
+        Custom(WalletError::DAppTemplateCapExceeded as u32),
+    );
+
+    test_context
+        .banks_client
+        .process_transaction(execute(
+            1_500_000_000,
+            &destination,
+            &context.assistant_account,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(
+        test_context
+            .banks_client
+            .get_balance(destination)
+            .await
+            .unwrap(),
+        1_500_000_000
+    );
+}
+
+#[tokio::test]
+async fn test_dapp_template_instances_count_against_dapp_value_limit() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(100_000)).await;
+    let destination = Keypair::new().pubkey();
+
+    fund_balance_account(&mut context, &balance_account).await;
+
+    // the system program can move 2 SOL out of the balance account per hour
+    let mut test_context = context.to_test_context();
+    let update = DAppBookUpdate {
+        add_dapps: vec![(
+            SlotId::new(1),
+            DAppBookEntry {
+                address: system_program::id(),
+                name_hash: DAppBookEntryNameHash::zero(),
+                instruction_prefixes: DAppInstructionPrefixes::default(),
+            },
+        )],
+        remove_dapps: vec![],
+        add_value_limits: vec![(
+            SlotId::new(1),
+            DAppValueLimit {
+                max_value: 2_000_000_000,
+                period: Duration::from_secs(3600),
+            },
+        )],
+        remove_value_limits: vec![],
+        add_inner_programs: vec![],
+        remove_inner_programs: vec![],
+        add_approval_policies: vec![],
+        remove_approval_policies: vec![],
+    };
+    let multisig_op_account = init_dapp_book_update(
+        &mut test_context,
+        context.wallet_account.pubkey(),
+        &context.assistant_account,
+        update.clone(),
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_account,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    finalize_dapp_book_update(
+        &mut test_context,
+        context.wallet_account.pubkey(),
+        multisig_op_account,
+        update,
+    )
+    .await;
+
+    // each instance is within the template's cap of 1.5 SOL
+    add_template(
+        &mut context,
+        DAppTemplate::new(
+            context.balance_account_guid_hash,
+            &system_instruction::transfer(&balance_account, &destination, 0),
+            &[DAppTemplateField {
+                offset: 4,
+                max_value: 1_500_000_000,
+            }],
+        )
+        .unwrap(),
+    )
+    .await;
+
+    let execute = |amount: u64| {
+        Transaction::new_signed_with_payer(
+            &[execute_dapp_template(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &balance_account,
+                &context.assistant_account.pubkey(),
+                SlotId::new(0),
+                system_instruction::transfer(&balance_account, &destination, amount),
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &context.assistant_account],
+            context.recent_blockhash,
+        )
+    };
+
+    test_context
+        .banks_client
+        .process_transaction(execute(1_500_000_000))
+        .await
+        .unwrap();
+    assert_instruction_error(
+        test_context
+            .banks_client
+            .process_transaction(execute(1_000_000_000))
+            .await,
+        0,
+        Custom(WalletError::DAppValueLimitExceeded as u32),
+    );
+    test_context
+        .banks_client
+        .process_transaction(execute(500_000_000))
+        .await
+        .unwrap();
+    assert_eq!(
+        test_context
+            .banks_client
+            .get_balance(destination)
+            .await
+            .unwrap(),
+        2_000_000_000
+    );
+}
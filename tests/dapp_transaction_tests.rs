@@ -6,9 +6,11 @@ pub use common::instructions::*;
 pub use common::utils::*;
 
 use std::borrow::BorrowMut;
+use std::convert::TryInto;
+use std::time::Duration;
 
-use solana_program::instruction::Instruction;
 use solana_program::instruction::InstructionError::Custom;
+use solana_program::instruction::{Instruction, InstructionError};
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 use solana_program::{system_instruction, system_program};
@@ -17,14 +19,21 @@ use solana_sdk::account::ReadableAccount;
 use solana_sdk::signature::Keypair;
 use solana_sdk::signer::Signer as SdkSigner;
 use solana_sdk::transaction::{Transaction, TransactionError};
+use solana_sdk::transport;
 
 use common::instructions::{
     finalize_dapp_transaction, init_dapp_transaction, init_transfer, set_approval_disposition,
 };
 use strike_wallet::error::WalletError;
-use strike_wallet::model::address_book::{DAppBookEntry, DAppBookEntryNameHash};
+use strike_wallet::instruction::DAppBookUpdate;
+use strike_wallet::model::activity::TransferTotal;
+use strike_wallet::model::address_book::{
+    DAppBookEntry, DAppBookEntryNameHash, DAppInstructionPrefixes,
+};
 use strike_wallet::model::balance_account::BalanceAccountGuidHash;
+use strike_wallet::model::dapp_value_limit::DAppValueLimit;
 use strike_wallet::model::multisig_op::{ApprovalDisposition, BooleanSetting, MultisigOp};
+use strike_wallet::utils::SlotId;
 
 use crate::common::utils;
 use crate::utils::BalanceAccountTestContext;
@@ -58,6 +67,7 @@ async fn setup_dapp_test() -> DAppTest {
     let dapp = DAppBookEntry {
         address: context.program_id.clone(),
         name_hash: DAppBookEntryNameHash::new(&hash_of(b"Strike Wallet")),
+        instruction_prefixes: DAppInstructionPrefixes::default(),
     };
 
     let inner_instructions = vec![
@@ -80,6 +90,7 @@ async fn setup_dapp_test() -> DAppTest {
             context.destination_name_hash,
             &system_program::id(),
             &context.payer.pubkey(),
+            None,
         ),
     ];
 
@@ -102,6 +113,7 @@ async fn setup_dapp_test() -> DAppTest {
                     &context.balance_account_guid_hash,
                     dapp,
                     inner_instructions.clone(),
+                    0,
                 ),
             ],
             Some(&context.payer.pubkey()),
@@ -145,6 +157,7 @@ async fn test_dapp_transaction_simulation() {
                     &context.balance_account_guid_hash,
                     dapp_test.dapp,
                     &dapp_test.inner_instructions,
+                    0,
                 )],
                 Some(&context.payer.pubkey()),
                 &[
@@ -181,6 +194,7 @@ async fn test_dapp_transaction_bad_signature() {
                     &BalanceAccountGuidHash::zero(),
                     dapp_test.dapp,
                     &dapp_test.inner_instructions,
+                    0,
                 )],
                 Some(&context.payer.pubkey()),
                 &[
@@ -212,6 +226,7 @@ async fn test_dapp_transaction() {
     let approve_transaction = Transaction::new_signed_with_payer(
         &[set_approval_disposition(
             &context.program_id,
+            &context.wallet_account.pubkey(),
             &dapp_test.multisig_op_account.pubkey(),
             &approver.pubkey(),
             ApprovalDisposition::APPROVE,
@@ -239,6 +254,7 @@ async fn test_dapp_transaction() {
                 &context.balance_account_guid_hash,
                 dapp_test.dapp,
                 &dapp_test.inner_instructions,
+                0,
             )],
             Some(&context.payer.pubkey()),
             &[
@@ -279,6 +295,7 @@ async fn test_dapp_transaction_denied() {
     let approve_transaction = Transaction::new_signed_with_payer(
         &[set_approval_disposition(
             &context.program_id,
+            &context.wallet_account.pubkey(),
             &dapp_test.multisig_op_account.pubkey(),
             &approver.pubkey(),
             ApprovalDisposition::DENY,
@@ -306,6 +323,7 @@ async fn test_dapp_transaction_denied() {
                 &context.balance_account_guid_hash,
                 dapp_test.dapp,
                 &dapp_test.inner_instructions,
+                0,
             )],
             Some(&context.payer.pubkey()),
             &[
@@ -395,6 +413,7 @@ async fn test_dapp_transaction_with_spl_transfers() {
     let dapp = DAppBookEntry {
         address: context.program_id.clone(),
         name_hash: DAppBookEntryNameHash::new(&hash_of(b"Strike Wallet")),
+        instruction_prefixes: DAppInstructionPrefixes::default(),
     };
 
     context
@@ -416,6 +435,7 @@ async fn test_dapp_transaction_with_spl_transfers() {
                     &context.balance_account_guid_hash,
                     dapp,
                     inner_instructions.clone(),
+                    0,
                 ),
             ],
             Some(&context.payer.pubkey()),
@@ -443,6 +463,7 @@ async fn test_dapp_transaction_with_spl_transfers() {
                     &context.balance_account_guid_hash,
                     dapp,
                     &inner_instructions,
+                    0,
                 )],
                 Some(&context.payer.pubkey()),
                 &[&context.payer, &mint, &mint_authority],
@@ -466,6 +487,7 @@ async fn test_dapp_transaction_without_dapps_enabled() {
     let dapp = DAppBookEntry {
         address: context.program_id.clone(),
         name_hash: DAppBookEntryNameHash::new(&hash_of(b"Strike Wallet")),
+        instruction_prefixes: DAppInstructionPrefixes::default(),
     };
     assert_eq!(
         context
@@ -487,6 +509,7 @@ async fn test_dapp_transaction_without_dapps_enabled() {
                         &context.balance_account_guid_hash,
                         dapp,
                         vec![],
+                        0,
                     ),
                 ],
                 Some(&context.payer.pubkey()),
@@ -523,6 +546,7 @@ async fn test_dapp_transaction_unwhitelisted() {
     let dapp = DAppBookEntry {
         address: context.program_id.clone(),
         name_hash: DAppBookEntryNameHash::new(&hash_of(b"Strike Wallet")),
+        instruction_prefixes: DAppInstructionPrefixes::default(),
     };
     assert_eq!(
         context
@@ -544,6 +568,7 @@ async fn test_dapp_transaction_unwhitelisted() {
                         &context.balance_account_guid_hash,
                         dapp,
                         vec![],
+                        0,
                     ),
                 ],
                 Some(&context.payer.pubkey()),
@@ -595,6 +620,7 @@ async fn test_dapp_transaction_whitelisted() {
                     &context.balance_account_guid_hash,
                     context.allowed_dapp,
                     vec![],
+                    0,
                 ),
             ],
             Some(&context.payer.pubkey()),
@@ -608,3 +634,580 @@ async fn test_dapp_transaction_whitelisted() {
         .await
         .unwrap();
 }
+
+#[tokio::test]
+async fn test_dapp_transaction_rent_funding_limit() {
+    let (mut context, balance_account) =
+        utils::setup_balance_account_tests_and_finalize(None).await;
+
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::Off),
+        Some(BooleanSetting::On),
+        None,
+    )
+    .await;
+
+    let seed = "dapp-account";
+    let new_account =
+        Pubkey::create_with_seed(&balance_account, seed, &system_program::id()).unwrap();
+    let inner_instructions = vec![system_instruction::create_account_with_seed(
+        &balance_account,
+        &new_account,
+        &balance_account,
+        seed,
+        1_000_000,
+        0,
+        &system_program::id(),
+    )];
+
+    let multisig_account_rent = context.rent.minimum_balance(MultisigOp::LEN);
+    let dapp = DAppBookEntry {
+        address: system_program::id(),
+        name_hash: DAppBookEntryNameHash::new(&hash_of(b"System Program")),
+        instruction_prefixes: DAppInstructionPrefixes::default(),
+    };
+
+    for (rent_funding_limit, expected_error) in [
+        (
+            999_999,
+            Some(Custom(WalletError::RentFundingLimitExceeded as u32)),
+        ),
+        (1_000_000, None),
+    ] {
+        let multisig_op_account = Keypair::new();
+        let result = context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[
+                    system_instruction::create_account(
+                        &context.payer.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        multisig_account_rent,
+                        MultisigOp::LEN as u64,
+                        &context.program_id,
+                    ),
+                    init_dapp_transaction(
+                        &context.program_id,
+                        &context.wallet_account.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        &context.initiator_account.pubkey(),
+                        &context.balance_account_guid_hash,
+                        dapp,
+                        inner_instructions.clone(),
+                        rent_funding_limit,
+                    ),
+                ],
+                Some(&context.payer.pubkey()),
+                &[
+                    &context.payer,
+                    &multisig_op_account,
+                    &context.initiator_account,
+                ],
+                context.recent_blockhash,
+            ))
+            .await;
+        match expected_error {
+            Some(error) => assert_eq!(
+                result.unwrap_err().unwrap(),
+                TransactionError::InstructionError(1, error),
+            ),
+            None => result.unwrap(),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_dapp_transaction_instruction_prefixes() {
+    let (mut context, balance_account) =
+        utils::setup_balance_account_tests_and_finalize(None).await;
+
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::Off),
+        Some(BooleanSetting::On),
+        None,
+    )
+    .await;
+
+    let transfer = system_instruction::transfer(&balance_account, &Pubkey::new_unique(), 1000);
+    let create_account = system_instruction::create_account(
+        &balance_account,
+        &Pubkey::new_unique(),
+        1000,
+        0,
+        &system_program::id(),
+    );
+    // the dapp is restricted to transfers of 1000 lamports
+    let dapp = DAppBookEntry {
+        address: system_program::id(),
+        name_hash: DAppBookEntryNameHash::new(&hash_of(b"System Program")),
+        instruction_prefixes: DAppInstructionPrefixes::new(&[transfer.data[..8]
+            .try_into()
+            .unwrap()])
+        .unwrap(),
+    };
+
+    let multisig_account_rent = context.rent.minimum_balance(MultisigOp::LEN);
+    for (inner_instructions, expected_error) in [
+        (
+            vec![transfer.clone(), create_account],
+            Some(Custom(WalletError::DAppInstructionNotAllowed as u32)),
+        ),
+        (vec![transfer], None),
+    ] {
+        let multisig_op_account = Keypair::new();
+        let result = context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[
+                    system_instruction::create_account(
+                        &context.payer.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        multisig_account_rent,
+                        MultisigOp::LEN as u64,
+                        &context.program_id,
+                    ),
+                    init_dapp_transaction(
+                        &context.program_id,
+                        &context.wallet_account.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        &context.initiator_account.pubkey(),
+                        &context.balance_account_guid_hash,
+                        dapp,
+                        inner_instructions,
+                        u64::MAX,
+                    ),
+                ],
+                Some(&context.payer.pubkey()),
+                &[
+                    &context.payer,
+                    &multisig_op_account,
+                    &context.initiator_account,
+                ],
+                context.recent_blockhash,
+            ))
+            .await;
+        match expected_error {
+            Some(error) => assert_eq!(
+                result.unwrap_err().unwrap(),
+                TransactionError::InstructionError(1, error),
+            ),
+            None => result.unwrap(),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_dapp_transaction_value_limit() {
+    let (mut context, balance_account) =
+        utils::setup_balance_account_tests_and_finalize(None).await;
+
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::Off),
+        Some(BooleanSetting::On),
+        None,
+    )
+    .await;
+
+    // the dapp can move 1.5M lamports out of the balance account per hour
+    let mut test_context = context.to_test_context();
+    let update = DAppBookUpdate {
+        add_dapps: vec![],
+        remove_dapps: vec![],
+        add_value_limits: vec![(
+            SlotId::new(0),
+            DAppValueLimit {
+                max_value: 1_500_000,
+                period: Duration::from_secs(3600),
+            },
+        )],
+        remove_value_limits: vec![],
+        add_inner_programs: vec![],
+        remove_inner_programs: vec![],
+        add_approval_policies: vec![],
+        remove_approval_policies: vec![],
+    };
+    let multisig_op_account = init_dapp_book_update(
+        &mut test_context,
+        context.wallet_account.pubkey(),
+        &context.assistant_account,
+        update.clone(),
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_account,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    finalize_dapp_book_update(
+        &mut test_context,
+        context.wallet_account.pubkey(),
+        multisig_op_account,
+        update,
+    )
+    .await;
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &context.payer.pubkey(),
+                &balance_account,
+                10_000_000,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let dapp = context.allowed_dapp;
+    let multisig_account_rent = context.rent.minimum_balance(MultisigOp::LEN);
+    for expected_error in [
+        None,
+        Some(Custom(WalletError::DAppValueLimitExceeded as u32)),
+    ] {
+        let inner_instructions = vec![system_instruction::transfer(
+            &balance_account,
+            &Pubkey::new_unique(),
+            1_000_000,
+        )];
+        let multisig_op_account = Keypair::new();
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[
+                    system_instruction::create_account(
+                        &context.payer.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        multisig_account_rent,
+                        MultisigOp::LEN as u64,
+                        &context.program_id,
+                    ),
+                    init_dapp_transaction(
+                        &context.program_id,
+                        &context.wallet_account.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        &context.initiator_account.pubkey(),
+                        &context.balance_account_guid_hash,
+                        dapp,
+                        inner_instructions.clone(),
+                        0,
+                    ),
+                ],
+                Some(&context.payer.pubkey()),
+                &[
+                    &context.payer,
+                    &multisig_op_account,
+                    &context.initiator_account,
+                ],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap();
+
+        let params_hash = utils::get_operation_hash(
+            context.banks_client.borrow_mut(),
+            multisig_op_account.pubkey(),
+        )
+        .await;
+        let approver = &context.approvers[0];
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[set_approval_disposition(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    &approver.pubkey(),
+                    ApprovalDisposition::APPROVE,
+                    params_hash,
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, approver],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap();
+
+        let result = context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[finalize_dapp_transaction(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    &balance_account,
+                    &context.payer.pubkey(),
+                    &context.balance_account_guid_hash,
+                    dapp,
+                    &inner_instructions,
+                    0,
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer],
+                context.recent_blockhash,
+            ))
+            .await;
+        match expected_error {
+            Some(error) => assert_eq!(
+                result.unwrap_err().unwrap(),
+                TransactionError::InstructionError(0, error),
+            ),
+            None => result.unwrap(),
+        }
+    }
+
+    // what the first transaction moved out of the balance account is
+    // recorded as the wallet's activity
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &context.wallet_account.pubkey())
+            .await
+            .activity
+            .transfer_totals,
+        vec![TransferTotal {
+            token_mint: system_program::id(),
+            count: 1,
+            amount: 1_000_000,
+        }]
+    );
+}
+
+async fn approve_dapp_transaction(context: &mut BalanceAccountTestContext, multisig_op: &Pubkey) {
+    let params_hash =
+        utils::get_operation_hash(context.banks_client.borrow_mut(), *multisig_op).await;
+    let approver = &context.approvers[0];
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[set_approval_disposition(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                multisig_op,
+                &approver.pubkey(),
+                ApprovalDisposition::APPROVE,
+                params_hash,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, approver],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+}
+
+/// Runs the one instruction after the first `instructions_executed`, which
+/// `signer` has to sign along with the payer.
+async fn execute_chunk(
+    context: &mut BalanceAccountTestContext,
+    multisig_op: &Pubkey,
+    balance_account: &Pubkey,
+    dapp: DAppBookEntry,
+    instructions: &Vec<Instruction>,
+    instructions_executed: u16,
+    signer: &Keypair,
+) -> transport::Result<()> {
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[execute_dapp_chunk(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                multisig_op,
+                balance_account,
+                &context.payer.pubkey(),
+                &context.balance_account_guid_hash,
+                dapp,
+                instructions,
+                0,
+                instructions_executed,
+                1,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, signer],
+            context.recent_blockhash,
+        ))
+        .await
+}
+
+#[tokio::test]
+async fn test_dapp_transaction_in_chunks() {
+    let DAppTest {
+        mut context,
+        balance_account,
+        multisig_op_account,
+        dapp,
+        inner_instructions,
+        inner_multisig_op_account,
+    } = setup_dapp_test().await;
+    let multisig_op = multisig_op_account.pubkey();
+    let inner_multisig_op = inner_multisig_op_account.pubkey();
+    let initiator = Keypair::from_bytes(&context.initiator_account.to_bytes()).unwrap();
+    approve_dapp_transaction(&mut context, &multisig_op).await;
+
+    // the first chunk only creates the inner op's account
+    execute_chunk(
+        &mut context,
+        &multisig_op,
+        &balance_account,
+        dapp,
+        &inner_instructions,
+        0,
+        &inner_multisig_op_account,
+    )
+    .await
+    .unwrap();
+    let inner_account = context
+        .banks_client
+        .get_account(inner_multisig_op)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(inner_account.owner, context.program_id);
+    assert!(inner_account.data().iter().all(|byte| *byte == 0));
+    assert_eq!(
+        get_multisig_op_data(&mut context.banks_client, multisig_op)
+            .await
+            .instructions_executed,
+        1
+    );
+
+    // the second one starts the inner op, and closes the op as it was the last
+    execute_chunk(
+        &mut context,
+        &multisig_op,
+        &balance_account,
+        dapp,
+        &inner_instructions,
+        1,
+        &initiator,
+    )
+    .await
+    .unwrap();
+    assert!(
+        get_multisig_op_data(&mut context.banks_client, inner_multisig_op)
+            .await
+            .is_initialized
+    );
+    assert!(context
+        .banks_client
+        .get_account(multisig_op)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_dapp_transaction_chunks_must_keep_the_approved_instructions() {
+    let DAppTest {
+        mut context,
+        balance_account,
+        multisig_op_account,
+        dapp,
+        inner_instructions,
+        inner_multisig_op_account,
+    } = setup_dapp_test().await;
+    let multisig_op = multisig_op_account.pubkey();
+    approve_dapp_transaction(&mut context, &multisig_op).await;
+    execute_chunk(
+        &mut context,
+        &multisig_op,
+        &balance_account,
+        dapp,
+        &inner_instructions,
+        0,
+        &inner_multisig_op_account,
+    )
+    .await
+    .unwrap();
+
+    // changing an instruction that already ran still changes the transaction
+    let initiator = Keypair::from_bytes(&context.initiator_account.to_bytes()).unwrap();
+    let mut changed_instructions = inner_instructions.clone();
+    changed_instructions[0] = system_instruction::create_account(
+        &context.payer.pubkey(),
+        &inner_multisig_op_account.pubkey(),
+        context.rent.minimum_balance(MultisigOp::LEN) + 1,
+        MultisigOp::LEN as u64,
+        &context.program_id,
+    );
+    assert_instruction_error(
+        execute_chunk(
+            &mut context,
+            &multisig_op,
+            &balance_account,
+            dapp,
+            &changed_instructions,
+            1,
+            &initiator,
+        )
+        .await,
+        0,
+        Custom(WalletError::InvalidSignature as u32),
+    );
+    assert_eq!(
+        get_multisig_op_data(&mut context.banks_client, multisig_op)
+            .await
+            .instructions_executed,
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_dapp_transaction_in_chunks_cannot_be_finalized() {
+    let DAppTest {
+        mut context,
+        balance_account,
+        multisig_op_account,
+        dapp,
+        inner_instructions,
+        inner_multisig_op_account,
+    } = setup_dapp_test().await;
+    let multisig_op = multisig_op_account.pubkey();
+    approve_dapp_transaction(&mut context, &multisig_op).await;
+    execute_chunk(
+        &mut context,
+        &multisig_op,
+        &balance_account,
+        dapp,
+        &inner_instructions,
+        0,
+        &inner_multisig_op_account,
+    )
+    .await
+    .unwrap();
+
+    // running the whole transaction again would repeat the first chunk
+    assert_instruction_error(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[finalize_dapp_transaction(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &multisig_op,
+                    &balance_account,
+                    &context.payer.pubkey(),
+                    &context.balance_account_guid_hash,
+                    dapp,
+                    &inner_instructions,
+                    0,
+                )],
+                Some(&context.payer.pubkey()),
+                &[
+                    &context.payer,
+                    &context.initiator_account,
+                    &inner_multisig_op_account,
+                ],
+                context.recent_blockhash,
+            ))
+            .await,
+        0,
+        InstructionError::InvalidArgument,
+    );
+}
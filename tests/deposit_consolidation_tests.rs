@@ -0,0 +1,117 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::system_instruction;
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use strike_wallet::error::WalletError;
+
+#[tokio::test]
+async fn test_consolidate_sol_deposits() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+
+    let deposit_addresses: Vec<_> = vec![0, 7]
+        .into_iter()
+        .map(|index| {
+            context
+                .balance_account_guid_hash
+                .find_deposit_address(index, &context.program_id)
+                .0
+        })
+        .collect();
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::transfer(
+                    &context.payer.pubkey(),
+                    &deposit_addresses[0],
+                    1_000_000_000,
+                ),
+                system_instruction::transfer(
+                    &context.payer.pubkey(),
+                    &deposit_addresses[1],
+                    2_000_000_000,
+                ),
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let starting_balance = context
+        .banks_client
+        .get_balance(balance_account)
+        .await
+        .unwrap();
+
+    // only the wallet's signers and assistant can sweep deposits
+    let outsider = Keypair::new();
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[consolidate_sol_deposits(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &balance_account,
+                    &outsider.pubkey(),
+                    context.balance_account_guid_hash,
+                    vec![0, 7],
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, &outsider],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidApprover as u32)),
+    );
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[consolidate_sol_deposits(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &balance_account,
+                &context.assistant_account.pubkey(),
+                context.balance_account_guid_hash,
+                vec![0, 7],
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &context.assistant_account],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(balance_account)
+            .await
+            .unwrap(),
+        starting_balance + 3_000_000_000
+    );
+    for deposit_address in deposit_addresses {
+        assert_eq!(
+            context
+                .banks_client
+                .get_balance(deposit_address)
+                .await
+                .unwrap(),
+            0
+        );
+    }
+}
@@ -0,0 +1,136 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+pub use common::utils;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::TransactionError;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{
+    ApproverDelegationUpdate, GovernedParameterUpdate, InitialWalletConfig,
+};
+use strike_wallet::model::approver_delegation::ApproverDelegation;
+use strike_wallet::model::governed_parameter::GovernedParameter;
+use strike_wallet::utils::SlotId;
+
+#[tokio::test]
+async fn test_governed_parameter_update() {
+    let mut context = setup_test(40_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+
+    let approvers = vec![Keypair::new(), Keypair::new()];
+    let signers = vec![
+        (SlotId::new(0), approvers[0].pubkey_as_signer()),
+        (SlotId::new(1), approvers[1].pubkey_as_signer()),
+    ];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: signers.clone(),
+            config_approvers: signers.clone(),
+        },
+    )
+    .await
+    .unwrap();
+
+    // a delegation period can only be shortened
+    assert_eq!(
+        utils::init_governed_parameter_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &approvers[0],
+            GovernedParameterUpdate {
+                values: vec![(
+                    GovernedParameter::MaxApproverDelegationPeriodSecs,
+                    Some(ApproverDelegation::MAX_PERIOD.as_secs() + 1),
+                )],
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidGovernedParameter as u32)),
+    );
+
+    let update = GovernedParameterUpdate {
+        values: vec![(
+            GovernedParameter::MaxApproverDelegationPeriodSecs,
+            Some(86400),
+        )],
+    };
+    let multisig_op_account = utils::init_governed_parameter_update(
+        &mut context,
+        wallet_account.pubkey(),
+        &approvers[0],
+        update.clone(),
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut context,
+        &multisig_op_account,
+        vec![&approvers[0], &approvers[1]],
+    )
+    .await;
+    utils::finalize_governed_parameter_update(
+        &mut context,
+        wallet_account.pubkey(),
+        multisig_op_account,
+        update,
+    )
+    .await;
+
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(
+        wallet.governed_parameters.max_approver_delegation_period(),
+        Duration::from_secs(86400)
+    );
+
+    // delegations are now held to the shorter period
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    assert_eq!(
+        utils::init_approver_delegation_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &approvers[0],
+            ApproverDelegationUpdate {
+                add_delegations: vec![(
+                    SlotId::new(0),
+                    ApproverDelegation {
+                        approver: approvers[0].pubkey(),
+                        delegate: approvers[1].pubkey(),
+                        expires_at: now + 86400 * 2,
+                    },
+                )],
+                remove_delegations: vec![],
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            Custom(WalletError::InvalidApproverDelegation as u32)
+        ),
+    );
+}
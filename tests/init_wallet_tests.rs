@@ -7,16 +7,22 @@ pub use common::utils::*;
 
 use std::time::Duration;
 
+use solana_program::instruction::InstructionError;
 use solana_program::instruction::InstructionError::Custom;
-use solana_sdk::transaction::TransactionError;
+use solana_sdk::transaction::{Transaction, TransactionError};
 
 use crate::common::utils;
 use itertools::Itertools;
 use strike_wallet::error::WalletError;
 use strike_wallet::instruction::InitialWalletConfig;
+use strike_wallet::model::activity::WalletActivity;
 use strike_wallet::model::address_book::{AddressBook, DAppBook};
+use strike_wallet::model::approver_delegation::ApproverDelegations;
 use strike_wallet::model::signer::Signer;
-use strike_wallet::model::wallet::{Approvers, BalanceAccounts, Signers, Wallet};
+use strike_wallet::model::token_mint::TokenMintBook;
+use strike_wallet::model::wallet::{
+    Approvers, BalanceAccounts, PendingAddressBookEntries, Signers, Wallet,
+};
 use strike_wallet::utils::SlotId;
 use {
     solana_program_test::{processor, tokio, ProgramTest},
@@ -62,8 +68,9 @@ async fn init_wallet() {
     .await
     .unwrap();
 
+    let wallet = get_wallet(&mut banks_client, &wallet_account.pubkey()).await;
     assert_eq!(
-        get_wallet(&mut banks_client, &wallet_account.pubkey()).await,
+        wallet,
         Wallet {
             is_initialized: true,
             signers: Signers::from_vec(signers),
@@ -80,6 +87,25 @@ async fn init_wallet() {
             balance_accounts: BalanceAccounts::new(),
             config_policy_update_locked: false,
             dapp_book: DAppBook::from_vec(vec![]),
+            destination_verification_delay: Duration::from_secs(0),
+            pending_address_book_entries: PendingAddressBookEntries::zero(),
+            activity: WalletActivity {
+                period_start: wallet.activity.period_start,
+                checkpoint_count: 0,
+                config_change_count: 0,
+                transfer_totals: vec![],
+            },
+            denial_reason_threshold: 0,
+            approvals_required_for_assistant_config: 0,
+            token_mint_book: TokenMintBook::new(),
+            address_book_approvers: Approvers::zero(),
+            approvals_required_for_address_book: 0,
+            default_transfer_approvers: Approvers::zero(),
+            approver_delegations: ApproverDelegations::new(),
+            signer_directory: None,
+            directory_signers: Approvers::zero(),
+            canary_key: None,
+            frozen: false,
         }
     );
 }
@@ -144,3 +170,56 @@ async fn invalid_wallet_initialization() {
         TransactionError::InstructionError(1, Custom(WalletError::UnknownSigner as u32)),
     );
 }
+
+#[tokio::test]
+async fn wallet_cannot_be_reinitialized() {
+    let program_id = Keypair::new().pubkey();
+    let mut pt = ProgramTest::new("strike_wallet", program_id, processor!(Processor::process));
+    pt.set_bpf_compute_max_units(30_000);
+    let (mut banks_client, payer, recent_blockhash) = pt.start().await;
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+
+    let approver = Keypair::new();
+    let initial_config = InitialWalletConfig {
+        approvals_required_for_config: 1,
+        approval_timeout_for_config: Duration::from_secs(3600),
+        signers: vec![(SlotId::new(0), approver.pubkey_as_signer())],
+        config_approvers: vec![(SlotId::new(0), approver.pubkey_as_signer())],
+    };
+    utils::init_wallet(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &program_id,
+        &wallet_account,
+        &assistant_account,
+        initial_config.clone(),
+    )
+    .await
+    .unwrap();
+    let wallet = get_wallet(&mut banks_client, &wallet_account.pubkey()).await;
+
+    let other_assistant_account = Keypair::new();
+    assert_instruction_error(
+        banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[common::instructions::init_wallet(
+                    &program_id,
+                    &wallet_account.pubkey(),
+                    &other_assistant_account.pubkey(),
+                    initial_config,
+                )],
+                Some(&payer.pubkey()),
+                &[&payer, &other_assistant_account],
+                recent_blockhash,
+            ))
+            .await,
+        0,
+        InstructionError::AccountAlreadyInitialized,
+    );
+    assert_eq!(
+        wallet,
+        get_wallet(&mut banks_client, &wallet_account.pubkey()).await
+    );
+}
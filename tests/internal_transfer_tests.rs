@@ -0,0 +1,293 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::borrow::BorrowMut;
+use std::time::Duration;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use solana_sdk::signature::Keypair;
+use solana_sdk::transaction::TransactionError;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::BalanceAccountCreation;
+use strike_wallet::model::activity::TransferTotal;
+use strike_wallet::model::balance_account::{BalanceAccountGuidHash, BalanceAccountNameHash};
+use strike_wallet::model::multisig_op::{
+    ApprovalDisposition, BooleanSetting, MultisigOp, OperationDisposition,
+};
+use strike_wallet::utils::SlotId;
+use {
+    solana_program::program_pack::Pack,
+    solana_program::system_instruction,
+    solana_program_test::tokio,
+    solana_sdk::{signature::Signer as SdkSigner, transaction::Transaction},
+};
+
+/// Creates a second balance account in the wallet, returning its guid hash
+/// and address.
+async fn create_other_balance_account(
+    context: &mut BalanceAccountTestContext,
+) -> (BalanceAccountGuidHash, Pubkey) {
+    let account_guid_hash = BalanceAccountGuidHash::new(&hash_of(b"Other Account"));
+    let creation_params = BalanceAccountCreation {
+        slot_id: SlotId::new(1),
+        name_hash: BalanceAccountNameHash::new(&hash_of(b"Other Account Name")),
+        approvals_required_for_transfer: 2,
+        approval_timeout_for_transfer: Duration::from_secs(120),
+        transfer_approvers: vec![
+            (SlotId::new(0), context.approvers[0].pubkey_as_signer()),
+            (SlotId::new(1), context.approvers[1].pubkey_as_signer()),
+        ],
+        whitelist_enabled: BooleanSetting::On,
+        dapps_enabled: BooleanSetting::Off,
+        address_book_slot_id: SlotId::new(33),
+    };
+
+    let multisig_op_account = Keypair::new();
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                init_balance_account_creation(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    &context.initiator_account.pubkey(),
+                    creation_params.slot_id,
+                    account_guid_hash,
+                    creation_params.name_hash,
+                    creation_params.approvals_required_for_transfer,
+                    creation_params.approval_timeout_for_transfer,
+                    creation_params.transfer_approvers.clone(),
+                    creation_params.whitelist_enabled,
+                    creation_params.dapps_enabled,
+                    creation_params.address_book_slot_id,
+                ),
+            ],
+            Some(&context.payer.pubkey()),
+            &[
+                &context.payer,
+                &multisig_op_account,
+                &context.initiator_account,
+            ],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    approve_or_deny_1_of_2_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        &context.approvers[0],
+        &context.payer,
+        &context.approvers[1].pubkey(),
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+    )
+    .await;
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[common::instructions::finalize_balance_account_creation(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op_account.pubkey(),
+                &context.payer.pubkey(),
+                account_guid_hash,
+                creation_params,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let (address, _) =
+        Pubkey::find_program_address(&[&account_guid_hash.to_bytes()], &context.program_id);
+    (account_guid_hash, address)
+}
+
+#[tokio::test]
+async fn test_internal_transfer_sol() {
+    let (mut context, source_account) = setup_balance_account_tests_and_finalize(Some(64000)).await;
+    // neither balance account is in the other's whitelist
+    account_settings_update(&mut context, Some(BooleanSetting::On), None, None).await;
+    let (destination_account_guid_hash, destination_account) =
+        create_other_balance_account(&mut context).await;
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &context.payer.pubkey(),
+                &source_account,
+                1000,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let init = |multisig_op_account: &Keypair,
+                destination_account: &Pubkey,
+                destination_account_guid_hash: BalanceAccountGuidHash| {
+        Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                init_internal_transfer(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    &context.initiator_account.pubkey(),
+                    &source_account,
+                    destination_account,
+                    context.balance_account_guid_hash,
+                    destination_account_guid_hash,
+                    123,
+                    &system_program::id(),
+                ),
+            ],
+            Some(&context.payer.pubkey()),
+            &[
+                &context.payer,
+                multisig_op_account,
+                &context.initiator_account,
+            ],
+            context.recent_blockhash,
+        )
+    };
+
+    // a balance account can't transfer to itself
+    let to_self = init(
+        &Keypair::new(),
+        &source_account,
+        context.balance_account_guid_hash,
+    );
+    // nor to an address that is not the destination balance account
+    let to_other_address = init(
+        &Keypair::new(),
+        &context.destination.pubkey(),
+        destination_account_guid_hash,
+    );
+    let multisig_op_account = Keypair::new();
+    let to_destination = init(
+        &multisig_op_account,
+        &destination_account,
+        destination_account_guid_hash,
+    );
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(to_self)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidInternalTransfer as u32)),
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(to_other_address)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::AccountNotRecognized as u32)),
+    );
+    context
+        .banks_client
+        .process_transaction(to_destination)
+        .await
+        .unwrap();
+
+    // the transfer is approved by the source balance account's approvers
+    approve_or_deny_n_of_n_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.payer,
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_internal_transfer(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op_account.pubkey(),
+                &context.payer.pubkey(),
+                &source_account,
+                &destination_account,
+                context.balance_account_guid_hash,
+                destination_account_guid_hash,
+                123,
+                &system_program::id(),
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(source_account)
+            .await
+            .unwrap(),
+        1000 - 123
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(destination_account)
+            .await
+            .unwrap(),
+        123
+    );
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    assert_eq!(
+        wallet
+            .get_balance_account(&context.balance_account_guid_hash)
+            .unwrap()
+            .outstanding_transfer_value,
+        0
+    );
+    assert_eq!(
+        wallet.activity.transfer_totals,
+        vec![TransferTotal {
+            token_mint: system_program::id(),
+            count: 1,
+            amount: 123,
+        }]
+    );
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program_test::tokio;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use strike_wallet::client::verify_name_hash;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::NamedEntry;
+
+#[tokio::test]
+async fn test_verify_name_hash() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let balance_account_address = context.balance_account_address_book_entry.1.address;
+
+    let verify = |context: &BalanceAccountTestContext, entry: NamedEntry, name: &str| {
+        Transaction::new_signed_with_payer(
+            &[verify_name_hash(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                entry,
+                name,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        )
+    };
+
+    for entry in vec![
+        NamedEntry::BalanceAccount(context.balance_account_guid_hash),
+        NamedEntry::AddressBookEntry(balance_account_address),
+    ] {
+        context
+            .banks_client
+            .process_transaction(verify(&context, entry, "Account Name"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(verify(&context, entry, "Another Name"))
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, Custom(WalletError::NameHashMismatch as u32)),
+        );
+    }
+
+    // an address that's not in the address book has no name to verify
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(verify(
+                &context,
+                NamedEntry::AddressBookEntry(context.destination.pubkey()),
+                "Account Name"
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::NameHashMismatch as u32)),
+    );
+}
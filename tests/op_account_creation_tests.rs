@@ -0,0 +1,304 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils;
+pub use common::utils::*;
+
+use solana_program::instruction::{AccountMeta, InstructionError, InstructionError::Custom};
+use solana_program::program_pack::Pack;
+use solana_program::system_instruction;
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::Transaction;
+use std::borrow::BorrowMut;
+use std::time::Duration;
+use strike_wallet::client::create_op_account_for_params;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{InitialWalletConfig, WalletConfigPolicyUpdate};
+use strike_wallet::model::multisig_op::{
+    ApprovalDisposition, MultisigOp, MultisigOpParams, OperationDisposition, ParamsHashVersion,
+};
+use strike_wallet::model::wallet::Wallet;
+use strike_wallet::utils::SlotId;
+
+#[tokio::test]
+async fn test_op_account_paid_by_fee_account() {
+    let mut context = setup_test(40_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let approver = Keypair::new();
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 1,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![(SlotId::new(0), approver.pubkey_as_signer())],
+            config_approvers: vec![(SlotId::new(0), approver.pubkey_as_signer())],
+        },
+    )
+    .await
+    .unwrap();
+
+    let (fee_account, _) =
+        Wallet::find_fee_account_address(&wallet_account.pubkey(), &context.program_id);
+    let prefunded_amount = 10_000_000;
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &context.payer.pubkey(),
+                &fee_account,
+                prefunded_amount,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // only the wallet's signers and assistant can spend the fee account
+    let stranger = Keypair::new();
+    let multisig_op_account = Keypair::new();
+    assert_instruction_error(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[create_op_account(
+                    &context.program_id,
+                    &multisig_op_account.pubkey(),
+                    &wallet_account.pubkey(),
+                    &fee_account,
+                    &stranger.pubkey(),
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, &multisig_op_account, &stranger],
+                context.recent_blockhash,
+            ))
+            .await,
+        0,
+        Custom(WalletError::InvalidApprover as u32),
+    );
+
+    let update = WalletConfigPolicyUpdate {
+        approvals_required_for_config: None,
+        approval_timeout_for_config: Some(Duration::from_secs(7200)),
+        add_config_approvers: vec![],
+        remove_config_approvers: vec![],
+        replace_config_approvers: None,
+        destination_verification_delay: None,
+        denial_reason_threshold: None,
+        approvals_required_for_assistant_config: None,
+        approvals_required_for_address_book: None,
+        add_address_book_approvers: vec![],
+        remove_address_book_approvers: vec![],
+        add_default_transfer_approvers: vec![],
+        remove_default_transfer_approvers: vec![],
+        add_veto_approvers: vec![],
+        remove_veto_approvers: vec![],
+        approvals_required_for_destructive_config: None,
+        add_destructive_config_approvers: vec![],
+        remove_destructive_config_approvers: vec![],
+    };
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                create_op_account(
+                    &context.program_id,
+                    &multisig_op_account.pubkey(),
+                    &wallet_account.pubkey(),
+                    &fee_account,
+                    &assistant_account.pubkey(),
+                ),
+                init_wallet_config_policy_update_instruction(
+                    context.program_id,
+                    wallet_account.pubkey(),
+                    multisig_op_account.pubkey(),
+                    assistant_account.pubkey(),
+                    &update,
+                ),
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &multisig_op_account, &assistant_account],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let op_rent = context.rent.minimum_balance(MultisigOp::LEN);
+    assert_eq!(
+        context.banks_client.get_balance(fee_account).await.unwrap(),
+        prefunded_amount - op_rent
+    );
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&approver],
+        &context.payer,
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    // the fee account collects the rent back without having to sign
+    let mut finalize_instruction = finalize_wallet_config_policy_update_instruction(
+        context.program_id,
+        wallet_account.pubkey(),
+        multisig_op_account.pubkey(),
+        fee_account,
+        &update,
+    );
+    finalize_instruction.accounts[2] = AccountMeta::new(fee_account, false);
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_instruction],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context.banks_client.get_balance(fee_account).await.unwrap(),
+        prefunded_amount
+    );
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &wallet_account.pubkey())
+            .await
+            .approval_timeout_for_config,
+        Duration::from_secs(7200)
+    );
+}
+
+#[tokio::test]
+async fn test_op_account_derived_from_params() {
+    let mut context = setup_test(40_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let approver = Keypair::new();
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 1,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![(SlotId::new(0), approver.pubkey_as_signer())],
+            config_approvers: vec![(SlotId::new(0), approver.pubkey_as_signer())],
+        },
+    )
+    .await
+    .unwrap();
+
+    let update = WalletConfigPolicyUpdate {
+        approvals_required_for_config: None,
+        approval_timeout_for_config: Some(Duration::from_secs(7200)),
+        add_config_approvers: vec![],
+        remove_config_approvers: vec![],
+        replace_config_approvers: None,
+        destination_verification_delay: None,
+        denial_reason_threshold: None,
+        approvals_required_for_assistant_config: None,
+        approvals_required_for_address_book: None,
+        add_address_book_approvers: vec![],
+        remove_address_book_approvers: vec![],
+        add_default_transfer_approvers: vec![],
+        remove_default_transfer_approvers: vec![],
+        add_veto_approvers: vec![],
+        remove_veto_approvers: vec![],
+        approvals_required_for_destructive_config: None,
+        add_destructive_config_approvers: vec![],
+        remove_destructive_config_approvers: vec![],
+    };
+    let params = MultisigOpParams::UpdateWalletConfigPolicy {
+        wallet_address: wallet_account.pubkey(),
+        update: update.clone(),
+    };
+    let start_op = |nonce: u64, initiator: &Keypair, update: &WalletConfigPolicyUpdate| {
+        let (multisig_op_account, create_instruction) = create_op_account_for_params(
+            &context.program_id,
+            &context.payer.pubkey(),
+            &params,
+            ParamsHashVersion::Legacy,
+            nonce,
+        );
+        Transaction::new_signed_with_payer(
+            &[
+                create_instruction,
+                init_wallet_config_policy_update_instruction(
+                    context.program_id,
+                    wallet_account.pubkey(),
+                    multisig_op_account,
+                    initiator.pubkey(),
+                    update,
+                ),
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, initiator],
+            context.recent_blockhash,
+        )
+    };
+
+    let first_op = start_op(0, &assistant_account, &update);
+    let second_op = start_op(0, &approver, &update);
+    let other_op = start_op(
+        1,
+        &assistant_account,
+        &WalletConfigPolicyUpdate {
+            approval_timeout_for_config: Some(Duration::from_secs(60)),
+            ..update.clone()
+        },
+    );
+
+    context
+        .banks_client
+        .process_transaction(first_op)
+        .await
+        .unwrap();
+
+    // the op can be found from its params alone
+    let (multisig_op_account, _) = MultisigOp::find_address(
+        &wallet_account.pubkey(),
+        &params.hash(),
+        0,
+        &context.program_id,
+    );
+    let multisig_op = get_multisig_op_data(&mut context.banks_client, multisig_op_account).await;
+    assert!(multisig_op.is_initialized);
+    assert_eq!(multisig_op.params_hash, params.hash());
+
+    // submitting the same op again fails while it is pending
+    assert_instruction_error(
+        context.banks_client.process_transaction(second_op).await,
+        0,
+        InstructionError::AccountAlreadyInitialized,
+    );
+
+    // and an account created for one op can't be used for another
+    assert_instruction_error(
+        context.banks_client.process_transaction(other_op).await,
+        1,
+        Custom(WalletError::OpParamsMismatch as u32),
+    );
+}
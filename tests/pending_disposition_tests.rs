@@ -0,0 +1,190 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+pub use common::utils;
+use solana_program::hash::hash;
+use solana_program::instruction::InstructionError;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::Transaction;
+use std::time::Duration;
+use strike_wallet::client::{collect_approval_dispositions, submit_approval_disposition};
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::InitialWalletConfig;
+use strike_wallet::model::multisig_op::{
+    ApprovalDisposition, DenialReason, MultisigOpParams, OperationDisposition,
+};
+use strike_wallet::utils::SlotId;
+
+#[tokio::test]
+async fn test_submitted_dispositions_apply_once_collected() {
+    let mut context = setup_test(60_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let approvers = vec![Keypair::new(), Keypair::new()];
+    let signers = vec![
+        (SlotId::new(0), approvers[0].pubkey_as_signer()),
+        (SlotId::new(1), approvers[1].pubkey_as_signer()),
+    ];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: signers.clone(),
+            config_approvers: signers,
+        },
+    )
+    .await
+    .unwrap();
+
+    let metadata_hash = hash(b"policy document v1");
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    utils::init_multisig_op(
+        &mut context,
+        multisig_op_account,
+        init_wallet_metadata_update(
+            &context.program_id,
+            &wallet_account.pubkey(),
+            &multisig_op_pubkey,
+            &assistant_account.pubkey(),
+            metadata_hash,
+        ),
+        &assistant_account,
+    )
+    .await
+    .unwrap();
+    let params_hash = MultisigOpParams::UpdateWalletMetadata {
+        wallet_address: wallet_account.pubkey(),
+        metadata_hash,
+    }
+    .hash();
+
+    // a disposition that couldn't be applied is rejected when it is submitted
+    assert_instruction_error(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[submit_approval_disposition(
+                    &context.program_id,
+                    &multisig_op_pubkey,
+                    &assistant_account.pubkey(),
+                    ApprovalDisposition::APPROVE,
+                    params_hash,
+                    DenialReason::None,
+                    None,
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, &assistant_account],
+                context.recent_blockhash,
+            ))
+            .await,
+        0,
+        Custom(WalletError::InvalidApprover as u32),
+    );
+
+    for approver in approvers.iter() {
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[submit_approval_disposition(
+                    &context.program_id,
+                    &multisig_op_pubkey,
+                    &approver.pubkey(),
+                    ApprovalDisposition::APPROVE,
+                    params_hash,
+                    DenialReason::None,
+                    None,
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, approver],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        get_multisig_op_data(&mut context.banks_client, multisig_op_pubkey)
+            .await
+            .operation_disposition,
+        OperationDisposition::NONE
+    );
+
+    let collect = collect_approval_dispositions(
+        &context.program_id,
+        &wallet_account.pubkey(),
+        &multisig_op_pubkey,
+        &approvers
+            .iter()
+            .map(|approver| approver.pubkey())
+            .collect::<Vec<_>>(),
+    );
+
+    // the instruction says how many pairs follow, so a missing one isn't
+    // mistaken for the accounts after them
+    let mut missing_pair = collect.clone();
+    missing_pair.accounts.drain(4..6);
+    assert_instruction_error(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[missing_pair],
+                Some(&context.payer.pubkey()),
+                &[&context.payer],
+                context.recent_blockhash,
+            ))
+            .await,
+        0,
+        InstructionError::NotEnoughAccountKeys,
+    );
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[collect],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(
+        get_multisig_op_data(&mut context.banks_client, multisig_op_pubkey)
+            .await
+            .operation_disposition,
+        OperationDisposition::APPROVED
+    );
+
+    utils::finalize_multisig_op(
+        &mut context,
+        multisig_op_pubkey,
+        finalize_wallet_metadata_update(
+            &context.program_id,
+            &wallet_account.pubkey(),
+            &multisig_op_pubkey,
+            &context.payer.pubkey(),
+            metadata_hash,
+        ),
+    )
+    .await;
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &wallet_account.pubkey())
+            .await
+            .metadata_hash,
+        metadata_hash
+    );
+}
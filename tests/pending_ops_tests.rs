@@ -0,0 +1,413 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+pub use common::{instructions, utils};
+use solana_program::instruction::{AccountMeta, InstructionError};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::Transaction;
+use std::time::Duration;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{DAppBookUpdate, InitialWalletConfig};
+use strike_wallet::model::address_book::{
+    DAppBookEntry, DAppBookEntryNameHash, DAppInstructionPrefixes,
+};
+use strike_wallet::model::pending_ops::PendingOps;
+use strike_wallet::utils::SlotId;
+
+async fn get_pending_ops(context: &mut TestContext, registry: &Pubkey) -> PendingOps {
+    PendingOps::unpack_from_slice(
+        context
+            .banks_client
+            .get_account(*registry)
+            .await
+            .unwrap()
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_pending_ops_registry() {
+    let mut context = setup_test(40_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let approver = Keypair::new();
+    let signer = approver.pubkey_as_signer();
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 1,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![(SlotId::new(0), signer)],
+            config_approvers: vec![(SlotId::new(0), signer)],
+        },
+    )
+    .await
+    .unwrap();
+
+    let (registry, _) = PendingOps::find_address(&wallet_account.pubkey(), &context.program_id);
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[init_pending_ops(
+                &context.program_id,
+                &registry,
+                &wallet_account.pubkey(),
+                &context.payer.pubkey(),
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+    assert!(get_pending_ops(&mut context, &registry)
+        .await
+        .ops
+        .filled_slots()
+        .is_empty());
+
+    let update = DAppBookUpdate {
+        add_dapps: vec![(
+            SlotId::new(0),
+            DAppBookEntry {
+                address: Keypair::new().pubkey(),
+                name_hash: DAppBookEntryNameHash::new(&hash_of(b"DApp Name")),
+                instruction_prefixes: DAppInstructionPrefixes::default(),
+            },
+        )],
+        remove_dapps: vec![],
+        add_value_limits: vec![],
+        remove_value_limits: vec![],
+        add_inner_programs: vec![],
+        remove_inner_programs: vec![],
+        add_approval_policies: vec![],
+        remove_approval_policies: vec![],
+    };
+
+    // starting an op with the registry passed along records it
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let mut instruction = instructions::init_dapp_book_update(
+        &context.program_id,
+        &wallet_account.pubkey(),
+        &multisig_op_pubkey,
+        &approver.pubkey(),
+        update.clone(),
+    );
+    instruction.accounts.push(AccountMeta::new(registry, false));
+    utils::init_multisig_op(&mut context, multisig_op_account, instruction, &approver)
+        .await
+        .unwrap();
+
+    let multisig_op = get_multisig_op_data(&mut context.banks_client, multisig_op_pubkey).await;
+    let pending_ops = get_pending_ops(&mut context, &registry)
+        .await
+        .ops
+        .filled_slots();
+    assert_eq!(pending_ops.len(), 1);
+    assert_eq!(pending_ops[0].1.multisig_op, multisig_op_pubkey);
+    assert_eq!(pending_ops[0].1.params_hash, multisig_op.params_hash);
+    assert_eq!(pending_ops[0].1.expires_at, multisig_op.expires_at);
+
+    // and finalizing it removes it
+    approve_n_of_n_multisig_op(&mut context, &multisig_op_pubkey, vec![&approver]).await;
+    let mut instruction = instructions::finalize_dapp_book_update(
+        &context.program_id,
+        &wallet_account.pubkey(),
+        &multisig_op_pubkey,
+        &context.payer.pubkey(),
+        update.clone(),
+    );
+    instruction.accounts.push(AccountMeta::new(registry, false));
+    utils::finalize_multisig_op(&mut context, multisig_op_pubkey, instruction).await;
+
+    assert!(get_pending_ops(&mut context, &registry)
+        .await
+        .ops
+        .filled_slots()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_cleanup_pending_ops() {
+    let mut context = setup_test(40_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let approver = Keypair::new();
+    let signer = approver.pubkey_as_signer();
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 1,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![(SlotId::new(0), signer)],
+            config_approvers: vec![(SlotId::new(0), signer)],
+        },
+    )
+    .await
+    .unwrap();
+
+    let (registry, _) = PendingOps::find_address(&wallet_account.pubkey(), &context.program_id);
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[init_pending_ops(
+                &context.program_id,
+                &registry,
+                &wallet_account.pubkey(),
+                &context.payer.pubkey(),
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let update = DAppBookUpdate {
+        add_dapps: vec![],
+        remove_dapps: vec![],
+        add_value_limits: vec![],
+        remove_value_limits: vec![],
+        add_inner_programs: vec![],
+        remove_inner_programs: vec![],
+        add_approval_policies: vec![],
+        remove_approval_policies: vec![],
+    };
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let mut instruction = instructions::init_dapp_book_update(
+        &context.program_id,
+        &wallet_account.pubkey(),
+        &multisig_op_pubkey,
+        &approver.pubkey(),
+        update.clone(),
+    );
+    instruction.accounts.push(AccountMeta::new(registry, false));
+    utils::init_multisig_op(&mut context, multisig_op_account, instruction, &approver)
+        .await
+        .unwrap();
+
+    // finalized without the registry, so it still lists the op
+    approve_n_of_n_multisig_op(&mut context, &multisig_op_pubkey, vec![&approver]).await;
+    utils::finalize_dapp_book_update(
+        &mut context,
+        wallet_account.pubkey(),
+        multisig_op_pubkey,
+        update,
+    )
+    .await;
+    assert_eq!(
+        get_pending_ops(&mut context, &registry)
+            .await
+            .ops
+            .filled_slots()
+            .len(),
+        1
+    );
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[cleanup_pending_ops(
+                &context.program_id,
+                &registry,
+                &[multisig_op_pubkey],
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+    assert!(get_pending_ops(&mut context, &registry)
+        .await
+        .ops
+        .filled_slots()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_op_prerequisite() {
+    let mut context = setup_test(80_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let approver = Keypair::new();
+    let signer = approver.pubkey_as_signer();
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 1,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![(SlotId::new(0), signer)],
+            config_approvers: vec![(SlotId::new(0), signer)],
+        },
+    )
+    .await
+    .unwrap();
+
+    let (registry, _) = PendingOps::find_address(&wallet_account.pubkey(), &context.program_id);
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[init_pending_ops(
+                &context.program_id,
+                &registry,
+                &wallet_account.pubkey(),
+                &context.payer.pubkey(),
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let dapp_update = |slot: usize| DAppBookUpdate {
+        add_dapps: vec![(
+            SlotId::new(slot),
+            DAppBookEntry {
+                address: Keypair::new().pubkey(),
+                name_hash: DAppBookEntryNameHash::new(&hash_of(b"DApp Name")),
+                instruction_prefixes: DAppInstructionPrefixes::default(),
+            },
+        )],
+        remove_dapps: vec![],
+        add_value_limits: vec![],
+        remove_value_limits: vec![],
+        add_inner_programs: vec![],
+        remove_inner_programs: vec![],
+        add_approval_policies: vec![],
+        remove_approval_policies: vec![],
+    };
+    let first_update = dapp_update(0);
+    let second_update = dapp_update(1);
+
+    let first_op_account = Keypair::new();
+    let first_op = first_op_account.pubkey();
+    utils::init_multisig_op(
+        &mut context,
+        first_op_account,
+        instructions::init_dapp_book_update(
+            &context.program_id,
+            &wallet_account.pubkey(),
+            &first_op,
+            &approver.pubkey(),
+            first_update.clone(),
+        ),
+        &approver,
+    )
+    .await
+    .unwrap();
+    let first_params_hash = get_multisig_op_data(&mut context.banks_client, first_op)
+        .await
+        .params_hash;
+
+    // the second op can only be executed once the first one has been
+    let second_op_account = Keypair::new();
+    let second_op = second_op_account.pubkey();
+    utils::init_multisig_op(
+        &mut context,
+        second_op_account,
+        with_prerequisite(
+            instructions::init_dapp_book_update(
+                &context.program_id,
+                &wallet_account.pubkey(),
+                &second_op,
+                &approver.pubkey(),
+                second_update.clone(),
+            ),
+            first_params_hash,
+        ),
+        &approver,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        get_multisig_op_data(&mut context.banks_client, second_op)
+            .await
+            .prerequisite,
+        Some(first_params_hash)
+    );
+
+    approve_n_of_n_multisig_op(&mut context, &first_op, vec![&approver]).await;
+    approve_n_of_n_multisig_op(&mut context, &second_op, vec![&approver]).await;
+
+    let program_id = context.program_id;
+    let wallet_address = wallet_account.pubkey();
+    let rent_collector = context.payer.pubkey();
+    let finalize_instruction = |op: &Pubkey, update: DAppBookUpdate| {
+        let mut instruction = instructions::finalize_dapp_book_update(
+            &program_id,
+            &wallet_address,
+            op,
+            &rent_collector,
+            update,
+        );
+        instruction.accounts.push(AccountMeta::new(registry, false));
+        instruction
+    };
+
+    assert_instruction_error(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[finalize_instruction(&second_op, second_update.clone())],
+                Some(&context.payer.pubkey()),
+                &[&context.payer],
+                context.recent_blockhash,
+            ))
+            .await,
+        0,
+        InstructionError::Custom(WalletError::PrerequisiteNotExecuted as u32),
+    );
+
+    let instruction = finalize_instruction(&first_op, first_update);
+    utils::finalize_multisig_op(&mut context, first_op, instruction).await;
+    assert!(get_pending_ops(&mut context, &registry)
+        .await
+        .was_executed(&first_params_hash));
+
+    let instruction = finalize_instruction(&second_op, second_update);
+    utils::finalize_multisig_op(&mut context, second_op, instruction).await;
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &wallet_account.pubkey())
+            .await
+            .dapp_book
+            .filled_slots()
+            .len(),
+        2
+    );
+}
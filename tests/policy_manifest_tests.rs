@@ -0,0 +1,53 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program_test::tokio;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use strike_wallet::error::WalletError;
+use strike_wallet::model::multisig_op::BooleanSetting;
+
+#[tokio::test]
+async fn test_assert_policy_manifest() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    let manifest_hash = wallet.policy_manifest_hash();
+    assert!(wallet
+        .policy_manifest()
+        .contains(&context.approvers[0].pubkey().to_string()));
+
+    let assert_manifest = |context: &BalanceAccountTestContext| {
+        Transaction::new_signed_with_payer(
+            &[assert_policy_manifest(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                manifest_hash,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        )
+    };
+    context
+        .banks_client
+        .process_transaction(assert_manifest(&context))
+        .await
+        .unwrap();
+
+    // any change to the configuration makes the signed-off manifest stale
+    account_settings_update(&mut context, None, Some(BooleanSetting::On), None).await;
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(assert_manifest(&context))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::PolicyManifestMismatch as u32)),
+    );
+}
@@ -0,0 +1,463 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::time::Duration;
+
+use solana_program::bpf_loader_upgradeable;
+use solana_program::hash::Hash;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::program_pack::Pack;
+use solana_program::system_instruction;
+use solana_program_test::BanksClient;
+use solana_sdk::account::Account;
+use solana_sdk::transaction::TransactionError;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{InitialWalletConfig, ProgramConfigUpdate};
+use strike_wallet::model::multisig_op::BooleanSetting;
+use strike_wallet::model::program_config::ProgramConfig;
+use strike_wallet::model::signer::Signer;
+use strike_wallet::model::wallet::Wallet;
+use strike_wallet::utils::SlotId;
+use {
+    solana_program_test::{processor, tokio, ProgramTest},
+    solana_sdk::{
+        pubkey::Pubkey,
+        signature::{Keypair, Signer as SdkSigner},
+        transaction::Transaction,
+        transport::TransportError,
+    },
+    strike_wallet::processor::Processor,
+};
+
+struct ProgramConfigTestContext {
+    program_id: Pubkey,
+    banks_client: BanksClient,
+    payer: Keypair,
+    recent_blockhash: Hash,
+    program_data_account: Pubkey,
+    program_config_account: Pubkey,
+}
+
+async fn setup_program_config_test() -> (ProgramConfigTestContext, Keypair) {
+    let program_id = Keypair::new().pubkey();
+    let upgrade_authority = Keypair::new();
+    let (program_data_account, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+
+    let mut pt = ProgramTest::new("strike_wallet", program_id, processor!(Processor::process));
+    pt.set_bpf_compute_max_units(25_000);
+
+    // bincode-encoded UpgradeableLoaderState::ProgramData header
+    let mut program_data = vec![3, 0, 0, 0];
+    program_data.extend_from_slice(&0u64.to_le_bytes());
+    program_data.push(1);
+    program_data.extend_from_slice(upgrade_authority.pubkey().as_ref());
+    pt.add_account(
+        program_data_account,
+        Account {
+            lamports: 1_000_000_000,
+            data: program_data,
+            owner: bpf_loader_upgradeable::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    pt.add_account(
+        upgrade_authority.pubkey(),
+        Account {
+            lamports: 1_000_000_000,
+            data: vec![],
+            owner: solana_program::system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let (banks_client, payer, recent_blockhash) = pt.start().await;
+
+    (
+        ProgramConfigTestContext {
+            program_id,
+            banks_client,
+            payer,
+            recent_blockhash,
+            program_data_account,
+            program_config_account: ProgramConfig::find_address(&program_id).0,
+        },
+        upgrade_authority,
+    )
+}
+
+async fn init_program_config_with_authority(
+    context: &mut ProgramConfigTestContext,
+    authority: &Keypair,
+    update: ProgramConfigUpdate,
+) -> Result<(), TransportError> {
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[init_program_config(
+                &context.program_id,
+                &context.program_config_account,
+                &context.program_data_account,
+                &authority.pubkey(),
+                update,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, authority],
+            context.recent_blockhash,
+        ))
+        .await
+}
+
+async fn update_program_config_with_authority(
+    context: &mut ProgramConfigTestContext,
+    authority: &Keypair,
+    update: ProgramConfigUpdate,
+) -> Result<(), TransportError> {
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[update_program_config(
+                &context.program_id,
+                &context.program_config_account,
+                &authority.pubkey(),
+                update,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, authority],
+            context.recent_blockhash,
+        ))
+        .await
+}
+
+async fn init_wallet_with_program_config(
+    context: &mut ProgramConfigTestContext,
+    approval_timeout_for_config: Duration,
+) -> Result<(), TransportError> {
+    init_wallet_with_signers_and_program_config(context, approval_timeout_for_config, 1).await
+}
+
+async fn init_wallet_with_signers_and_program_config(
+    context: &mut ProgramConfigTestContext,
+    approval_timeout_for_config: Duration,
+    signer_count: usize,
+) -> Result<(), TransportError> {
+    init_wallet_for_test(context, approval_timeout_for_config, signer_count, true).await
+}
+
+async fn init_wallet_without_program_config(
+    context: &mut ProgramConfigTestContext,
+    signer_count: usize,
+) -> Result<(), TransportError> {
+    init_wallet_for_test(context, Duration::from_secs(3600), signer_count, false).await
+}
+
+async fn init_wallet_for_test(
+    context: &mut ProgramConfigTestContext,
+    approval_timeout_for_config: Duration,
+    signer_count: usize,
+    include_program_config: bool,
+) -> Result<(), TransportError> {
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let signers = (0..signer_count)
+        .map(|i| (SlotId::new(i), Signer::new(Pubkey::new_unique())))
+        .collect::<Vec<_>>();
+
+    let mut init_wallet_instruction = init_wallet(
+        &context.program_id,
+        &wallet_account.pubkey(),
+        &assistant_account.pubkey(),
+        InitialWalletConfig {
+            approvals_required_for_config: 1,
+            approval_timeout_for_config,
+            signers: signers.clone(),
+            config_approvers: signers[..1].to_vec(),
+        },
+    );
+    if !include_program_config {
+        let program_config_account = context.program_config_account;
+        init_wallet_instruction
+            .accounts
+            .retain(|account| account.pubkey != program_config_account);
+    }
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.payer.pubkey(),
+                    &wallet_account.pubkey(),
+                    rent.minimum_balance(Wallet::LEN),
+                    Wallet::LEN as u64,
+                    &context.program_id,
+                ),
+                init_wallet_instruction,
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &wallet_account, &assistant_account],
+            context.recent_blockhash,
+        ))
+        .await
+}
+
+async fn get_program_config(context: &mut ProgramConfigTestContext) -> ProgramConfig {
+    ProgramConfig::unpack_from_slice(
+        context
+            .banks_client
+            .get_account(context.program_config_account)
+            .await
+            .unwrap()
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap()
+}
+
+fn empty_update() -> ProgramConfigUpdate {
+    ProgramConfigUpdate {
+        min_approval_timeout: None,
+        max_approval_timeout: None,
+        fee_collector: None,
+        paused: None,
+        max_signers: None,
+        max_balance_accounts: None,
+        add_migration_programs: vec![],
+        remove_migration_programs: vec![],
+    }
+}
+
+#[tokio::test]
+async fn test_program_config_pause() {
+    let (mut context, upgrade_authority) = setup_program_config_test().await;
+    let fee_collector = Pubkey::new_unique();
+
+    init_program_config_with_authority(
+        &mut context,
+        &upgrade_authority,
+        ProgramConfigUpdate {
+            fee_collector: Some(fee_collector),
+            ..empty_update()
+        },
+    )
+    .await
+    .unwrap();
+
+    let program_config = get_program_config(&mut context).await;
+    assert_eq!(program_config.authority, upgrade_authority.pubkey());
+    assert_eq!(program_config.fee_collector, fee_collector);
+    assert_eq!(
+        program_config.min_approval_timeout,
+        Wallet::MIN_APPROVAL_TIMEOUT
+    );
+    assert_eq!(
+        program_config.max_approval_timeout,
+        Wallet::MAX_APPROVAL_TIMEOUT
+    );
+    assert!(!program_config.is_paused());
+
+    update_program_config_with_authority(
+        &mut context,
+        &upgrade_authority,
+        ProgramConfigUpdate {
+            paused: Some(BooleanSetting::On),
+            ..empty_update()
+        },
+    )
+    .await
+    .unwrap();
+    assert!(get_program_config(&mut context).await.is_paused());
+
+    assert_eq!(
+        init_wallet_with_program_config(&mut context, Duration::from_secs(3600))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::ProgramPaused as u32)),
+    );
+    // leaving the program config account out doesn't get around the pause
+    assert_eq!(
+        init_wallet_without_program_config(&mut context, 1)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            Custom(WalletError::ProgramConfigAccountRequired as u32)
+        ),
+    );
+
+    update_program_config_with_authority(
+        &mut context,
+        &upgrade_authority,
+        ProgramConfigUpdate {
+            paused: Some(BooleanSetting::Off),
+            ..empty_update()
+        },
+    )
+    .await
+    .unwrap();
+
+    init_wallet_with_program_config(&mut context, Duration::from_secs(3600))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_program_config_approval_timeout_limits() {
+    let (mut context, upgrade_authority) = setup_program_config_test().await;
+
+    init_program_config_with_authority(
+        &mut context,
+        &upgrade_authority,
+        ProgramConfigUpdate {
+            min_approval_timeout: Some(Duration::from_secs(600)),
+            max_approval_timeout: Some(Duration::from_secs(7200)),
+            ..empty_update()
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        init_wallet_with_program_config(&mut context, Duration::from_secs(300))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidApprovalTimeout as u32)),
+    );
+    assert_eq!(
+        init_wallet_with_program_config(&mut context, Duration::from_secs(7201))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidApprovalTimeout as u32)),
+    );
+    init_wallet_with_program_config(&mut context, Duration::from_secs(3600))
+        .await
+        .unwrap();
+
+    // the minimum can't exceed the maximum
+    assert_eq!(
+        update_program_config_with_authority(
+            &mut context,
+            &upgrade_authority,
+            ProgramConfigUpdate {
+                min_approval_timeout: Some(Duration::from_secs(9000)),
+                ..empty_update()
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidApprovalTimeout as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_program_config_requires_authority() {
+    let (mut context, upgrade_authority) = setup_program_config_test().await;
+    let other_authority = Keypair::new();
+
+    assert_eq!(
+        init_program_config_with_authority(&mut context, &other_authority, empty_update())
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::InvalidProgramConfigAuthority as u32)
+        ),
+    );
+
+    init_program_config_with_authority(&mut context, &upgrade_authority, empty_update())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        update_program_config_with_authority(
+            &mut context,
+            &other_authority,
+            ProgramConfigUpdate {
+                paused: Some(BooleanSetting::On),
+                ..empty_update()
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::InvalidProgramConfigAuthority as u32)
+        ),
+    );
+    assert!(!get_program_config(&mut context).await.is_paused());
+}
+
+#[tokio::test]
+async fn test_program_config_overrides_compiled_limits() {
+    let (mut context, upgrade_authority) = setup_program_config_test().await;
+
+    init_program_config_with_authority(
+        &mut context,
+        &upgrade_authority,
+        ProgramConfigUpdate {
+            min_approval_timeout: Some(Duration::from_secs(10)),
+            max_signers: Some(2),
+            ..empty_update()
+        },
+    )
+    .await
+    .unwrap();
+
+    // looser than the compiled-in minimum approval timeout
+    init_wallet_with_program_config(&mut context, Duration::from_secs(30))
+        .await
+        .unwrap();
+
+    init_wallet_with_signers_and_program_config(&mut context, Duration::from_secs(3600), 2)
+        .await
+        .unwrap();
+    assert_eq!(
+        init_wallet_with_signers_and_program_config(&mut context, Duration::from_secs(3600), 3)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::SlotCannotBeInserted as u32)),
+    );
+    // nor can the lowered limit be traded for the compiled-in one by leaving
+    // the program config account out
+    assert_eq!(
+        init_wallet_without_program_config(&mut context, 3)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            Custom(WalletError::ProgramConfigAccountRequired as u32)
+        ),
+    );
+
+    // storage capacity can't be exceeded
+    assert_eq!(
+        update_program_config_with_authority(
+            &mut context,
+            &upgrade_authority,
+            ProgramConfigUpdate {
+                max_signers: Some(Wallet::MAX_SIGNERS as u8 + 1),
+                ..empty_update()
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidPolicyLimit as u32)),
+    );
+}
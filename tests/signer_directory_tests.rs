@@ -0,0 +1,302 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+pub use common::utils;
+use solana_program::instruction::AccountMeta;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use std::time::Duration;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{
+    InitialWalletConfig, SignerDirectoryUpdate, WalletSignerDirectoryUpdate,
+};
+use strike_wallet::model::multisig_op::{ApprovalDisposition, OperationDisposition};
+use strike_wallet::model::signer_directory::SignerDirectory;
+use strike_wallet::utils::SlotId;
+use {solana_program::program_pack::Pack, solana_sdk::account::ReadableAccount};
+
+#[tokio::test]
+async fn test_rotating_a_directory_signer_applies_to_linked_wallets() {
+    let mut context = setup_test(40_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+
+    let approvers = vec![Keypair::new(), Keypair::new()];
+    let rotated_approver = Keypair::new();
+    let signers = vec![
+        (SlotId::new(0), approvers[0].pubkey_as_signer()),
+        (SlotId::new(1), approvers[1].pubkey_as_signer()),
+    ];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: signers.clone(),
+            config_approvers: signers.clone(),
+        },
+    )
+    .await
+    .unwrap();
+
+    // the organization keeps slot 0 in its signer directory
+    let (directory_address, _) =
+        SignerDirectory::find_address(&context.payer.pubkey(), &context.program_id);
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                init_signer_directory(
+                    &context.program_id,
+                    &directory_address,
+                    &context.payer.pubkey(),
+                ),
+                update_signer_directory(
+                    &context.program_id,
+                    &directory_address,
+                    &context.payer.pubkey(),
+                    SignerDirectoryUpdate {
+                        add_signers: vec![signers[0]],
+                        remove_signers: vec![],
+                    },
+                ),
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // link the wallet's slot 0 to the directory
+    let link = WalletSignerDirectoryUpdate {
+        signer_directory: Some(directory_address),
+        directory_signers: vec![signers[0]],
+    };
+    let multisig_op_account = Keypair::new();
+    let multisig_op_address = multisig_op_account.pubkey();
+    utils::init_multisig_op(
+        &mut context,
+        multisig_op_account,
+        init_wallet_signer_directory_update(
+            &context.program_id,
+            &wallet_account.pubkey(),
+            &multisig_op_address,
+            &approvers[0].pubkey(),
+            &directory_address,
+            link.clone(),
+        ),
+        &approvers[0],
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut context,
+        &multisig_op_address,
+        vec![&approvers[0], &approvers[1]],
+    )
+    .await;
+    utils::finalize_multisig_op(
+        &mut context,
+        multisig_op_address,
+        finalize_wallet_signer_directory_update(
+            &context.program_id,
+            &wallet_account.pubkey(),
+            &multisig_op_address,
+            &context.payer.pubkey(),
+            &directory_address,
+            link,
+        ),
+    )
+    .await;
+
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(wallet.signer_directory, Some(directory_address));
+    assert!(wallet.directory_signers.is_enabled(&SlotId::new(0)));
+
+    // rotate the key in the directory only
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[update_signer_directory(
+                &context.program_id,
+                &directory_address,
+                &context.payer.pubkey(),
+                SignerDirectoryUpdate {
+                    add_signers: vec![(SlotId::new(0), rotated_approver.pubkey_as_signer())],
+                    remove_signers: vec![signers[0]],
+                },
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let directory = SignerDirectory::unpack(
+        context
+            .banks_client
+            .get_account(directory_address)
+            .await
+            .unwrap()
+            .unwrap()
+            .data(),
+    )
+    .unwrap();
+    assert_eq!(
+        directory.signers.filled_slots(),
+        vec![(SlotId::new(0), rotated_approver.pubkey_as_signer())]
+    );
+
+    // the wallet can't be used without its directory
+    let unlink = WalletSignerDirectoryUpdate {
+        signer_directory: None,
+        directory_signers: vec![],
+    };
+    let multisig_op_account = Keypair::new();
+    let multisig_op_address = multisig_op_account.pubkey();
+    let mut instruction = init_wallet_signer_directory_update(
+        &context.program_id,
+        &wallet_account.pubkey(),
+        &multisig_op_address,
+        &rotated_approver.pubkey(),
+        &directory_address,
+        unlink.clone(),
+    );
+    instruction
+        .accounts
+        .retain(|account| account.pubkey != directory_address);
+    assert_eq!(
+        utils::init_multisig_op(
+            &mut context,
+            multisig_op_account,
+            instruction,
+            &rotated_approver
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::SignerDirectoryRequired as u32)),
+    );
+
+    // the old key no longer initiates for the wallet
+    let multisig_op_account = Keypair::new();
+    let multisig_op_address = multisig_op_account.pubkey();
+    assert_eq!(
+        utils::init_multisig_op(
+            &mut context,
+            multisig_op_account,
+            init_wallet_signer_directory_update(
+                &context.program_id,
+                &wallet_account.pubkey(),
+                &multisig_op_address,
+                &approvers[0].pubkey(),
+                &directory_address,
+                unlink.clone(),
+            ),
+            &approvers[0],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidApprover as u32)),
+    );
+
+    // but the rotated one does
+    let multisig_op_account = Keypair::new();
+    let multisig_op_address = multisig_op_account.pubkey();
+    utils::init_multisig_op(
+        &mut context,
+        multisig_op_account,
+        init_wallet_signer_directory_update(
+            &context.program_id,
+            &wallet_account.pubkey(),
+            &multisig_op_address,
+            &rotated_approver.pubkey(),
+            &directory_address,
+            unlink.clone(),
+        ),
+        &rotated_approver,
+    )
+    .await
+    .unwrap();
+
+    let params_hash = get_operation_hash(&mut context.banks_client, multisig_op_address).await;
+    let approve = |approver: &Keypair| {
+        let mut instruction = set_approval_disposition(
+            &context.program_id,
+            &wallet_account.pubkey(),
+            &multisig_op_address,
+            &approver.pubkey(),
+            ApprovalDisposition::APPROVE,
+            params_hash,
+        );
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(directory_address, false));
+        Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, approver],
+            context.recent_blockhash,
+        )
+    };
+
+    let old_key_approval = approve(&approvers[0]);
+    let approvals = vec![approve(&rotated_approver), approve(&approvers[1])];
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(old_key_approval)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidApprover as u32)),
+    );
+    for approval in approvals {
+        context
+            .banks_client
+            .process_transaction(approval)
+            .await
+            .unwrap();
+    }
+
+    let multisig_op = get_multisig_op_data(&mut context.banks_client, multisig_op_address).await;
+    assert_eq!(
+        multisig_op.operation_disposition,
+        OperationDisposition::APPROVED
+    );
+
+    utils::finalize_multisig_op(
+        &mut context,
+        multisig_op_address,
+        finalize_wallet_signer_directory_update(
+            &context.program_id,
+            &wallet_account.pubkey(),
+            &multisig_op_address,
+            &context.payer.pubkey(),
+            &directory_address,
+            unlink,
+        ),
+    )
+    .await;
+
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(wallet.signer_directory, None);
+    assert_eq!(wallet.directory_signers.count_enabled(), 0);
+}
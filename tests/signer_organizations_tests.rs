@@ -0,0 +1,169 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+pub use common::utils;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use std::time::Duration;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{
+    GovernedParameterUpdate, InitialWalletConfig, SignerOrganizationsUpdate,
+};
+use strike_wallet::model::governed_parameter::GovernedParameter;
+use strike_wallet::model::multisig_op::{ApprovalDisposition, OperationDisposition};
+use strike_wallet::utils::SlotId;
+
+#[tokio::test]
+async fn test_approvals_from_distinct_organizations() {
+    let mut context = setup_test(60_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+
+    let approvers = vec![Keypair::new(), Keypair::new(), Keypair::new()];
+    let signers = vec![
+        (SlotId::new(0), approvers[0].pubkey_as_signer()),
+        (SlotId::new(1), approvers[1].pubkey_as_signer()),
+        (SlotId::new(2), approvers[2].pubkey_as_signer()),
+    ];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: signers.clone(),
+            config_approvers: signers.clone(),
+        },
+    )
+    .await
+    .unwrap();
+
+    // the first two approvers work for one firm, the third for another
+    let update = SignerOrganizationsUpdate {
+        organizations: vec![
+            (SlotId::new(0), Some(1)),
+            (SlotId::new(1), Some(1)),
+            (SlotId::new(2), Some(2)),
+        ],
+    };
+    let multisig_op_account = utils::init_signer_organizations_update(
+        &mut context,
+        wallet_account.pubkey(),
+        &approvers[0],
+        update.clone(),
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut context,
+        &multisig_op_account,
+        vec![&approvers[0], &approvers[1], &approvers[2]],
+    )
+    .await;
+    utils::finalize_signer_organizations_update(
+        &mut context,
+        wallet_account.pubkey(),
+        multisig_op_account,
+        update,
+    )
+    .await;
+
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(wallet.signer_organization(&approvers[1].pubkey()), Some(1));
+    assert_eq!(wallet.signer_organization(&approvers[2].pubkey()), Some(2));
+
+    // the config approvers only span two organizations
+    let require_organizations = |count: u64| GovernedParameterUpdate {
+        values: vec![(
+            GovernedParameter::MinDistinctApproverOrganizations,
+            Some(count),
+        )],
+    };
+    assert_eq!(
+        utils::init_governed_parameter_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &approvers[0],
+            require_organizations(3),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidApproverCount as u32)),
+    );
+
+    let multisig_op_account = utils::init_governed_parameter_update(
+        &mut context,
+        wallet_account.pubkey(),
+        &approvers[0],
+        require_organizations(2),
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut context,
+        &multisig_op_account,
+        vec![&approvers[0], &approvers[1], &approvers[2]],
+    )
+    .await;
+    utils::finalize_governed_parameter_update(
+        &mut context,
+        wallet_account.pubkey(),
+        multisig_op_account,
+        require_organizations(2),
+    )
+    .await;
+
+    // two approvals from the same firm are no longer enough
+    let multisig_op_account = utils::init_governed_parameter_update(
+        &mut context,
+        wallet_account.pubkey(),
+        &approvers[0],
+        require_organizations(1),
+    )
+    .await
+    .unwrap();
+    let params_hash = get_operation_hash(&mut context.banks_client, multisig_op_account).await;
+    for (approver, expected_disposition) in vec![
+        (&approvers[0], OperationDisposition::NONE),
+        (&approvers[1], OperationDisposition::NONE),
+        (&approvers[2], OperationDisposition::APPROVED),
+    ] {
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[set_approval_disposition(
+                    &context.program_id,
+                    &wallet_account.pubkey(),
+                    &multisig_op_account,
+                    &approver.pubkey(),
+                    ApprovalDisposition::APPROVE,
+                    params_hash,
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, approver],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            get_multisig_op_data(&mut context.banks_client, multisig_op_account)
+                .await
+                .operation_disposition,
+            expected_disposition
+        );
+    }
+}
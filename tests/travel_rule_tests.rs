@@ -0,0 +1,160 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+pub use common::utils;
+use solana_program::hash::Hash;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer as SdkSigner;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{AddressBookUpdate, CombinedTransfer, ProgramInstruction};
+use strike_wallet::model::address_book::{AddressNamespace, DestinationNamespace};
+use strike_wallet::model::multisig_op::WrapDirection;
+use strike_wallet::utils::SlotId;
+
+/// Tags the test destination, which is in address book slot 0, as an exchange.
+async fn tag_destination_as_exchange(context: &mut BalanceAccountTestContext) {
+    let update = AddressBookUpdate {
+        add_address_book_entries: vec![],
+        remove_address_book_entries: vec![],
+        balance_account_whitelist_updates: vec![],
+        add_destination_token_mints: vec![],
+        remove_destination_token_mints: vec![],
+        add_destination_namespaces: vec![(
+            SlotId::new(0),
+            DestinationNamespace {
+                address_book_entry: SlotId::new(0),
+                namespace: AddressNamespace::Exchange,
+            },
+        )],
+        remove_destination_namespaces: vec![],
+    };
+    let mut test_context = context.to_test_context();
+    let multisig_op_account = Keypair::new();
+    let multisig_op = multisig_op_account.pubkey();
+    let mut init = init_address_book_update(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &multisig_op,
+        &context.assistant_account.pubkey(),
+        vec![],
+        vec![],
+        vec![],
+    );
+    init.data = ProgramInstruction::InitAddressBookUpdate {
+        update: update.clone(),
+    }
+    .pack();
+    utils::init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init,
+        &context.assistant_account,
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    utils::finalize_multisig_op(
+        &mut test_context,
+        multisig_op,
+        finalize_address_book_update(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &multisig_op,
+            &context.payer.pubkey(),
+            update,
+        ),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_combined_transfer_to_exchange_requires_travel_rule_data() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(100_000)).await;
+    let spl_context = setup_spl_transfer_test(&mut context, &balance_account, false).await;
+    tag_destination_as_exchange(&mut context).await;
+
+    let mut test_context = context.to_test_context();
+    for (travel_rule_hash, expected_error) in [
+        (None, Some(WalletError::TravelRuleDataRequired)),
+        (Some(Hash::new_from_array([7; 32])), None),
+    ] {
+        let multisig_op_account = Keypair::new();
+        let multisig_op = multisig_op_account.pubkey();
+        let result = utils::init_multisig_op(
+            &mut test_context,
+            multisig_op_account,
+            init_combined_transfer(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op,
+                &context.initiator_account.pubkey(),
+                &balance_account,
+                &context.destination.pubkey(),
+                context.balance_account_guid_hash,
+                context.destination_name_hash,
+                CombinedTransfer {
+                    lamports: 0,
+                    token_amounts: vec![(spl_context.mint.pubkey(), 123)],
+                    travel_rule_hash,
+                },
+            ),
+            &context.initiator_account,
+        )
+        .await;
+        match expected_error {
+            Some(error) => assert_instruction_error(result, 1, Custom(error as u32)),
+            None => result.unwrap(),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_unwrap_to_exchange_requires_travel_rule_data() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(100_000)).await;
+    tag_destination_as_exchange(&mut context).await;
+
+    let mut test_context = context.to_test_context();
+    for (travel_rule_hash, expected_error) in [
+        (None, Some(WalletError::TravelRuleDataRequired)),
+        (Some(Hash::new_from_array([7; 32])), None),
+    ] {
+        let multisig_op_account = Keypair::new();
+        let multisig_op = multisig_op_account.pubkey();
+        let result = utils::init_multisig_op(
+            &mut test_context,
+            multisig_op_account,
+            init_wrap_unwrap(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op,
+                &context.assistant_account.pubkey(),
+                &balance_account,
+                &context.balance_account_guid_hash,
+                123,
+                WrapDirection::UNWRAP,
+                None,
+                Some(context.allowed_destination),
+                travel_rule_hash,
+            ),
+            &context.assistant_account,
+        )
+        .await;
+        match expected_error {
+            Some(error) => assert_instruction_error(result, 1, Custom(error as u32)),
+            None => result.unwrap(),
+        }
+    }
+}
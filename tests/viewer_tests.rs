@@ -0,0 +1,138 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::time::Duration;
+
+use solana_program::instruction::InstructionError::Custom;
+
+use crate::common::utils;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{InitialWalletConfig, ViewersUpdate};
+use strike_wallet::model::viewer::{Viewer, Viewers};
+use strike_wallet::utils::SlotId;
+use {
+    solana_program_test::tokio,
+    solana_sdk::signature::{Keypair, Signer as SdkSigner},
+    solana_sdk::transaction::{Transaction, TransactionError},
+};
+
+#[tokio::test]
+async fn test_viewers() {
+    let mut context = setup_test(60_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let approvers = vec![Keypair::new(), Keypair::new()];
+    let config_approvers = vec![
+        (SlotId::new(0), approvers[0].pubkey_as_signer()),
+        (SlotId::new(1), approvers[1].pubkey_as_signer()),
+    ];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: config_approvers.clone(),
+            config_approvers,
+        },
+    )
+    .await
+    .unwrap();
+
+    let viewer = Keypair::new();
+    let update = ViewersUpdate {
+        add_viewers: vec![(SlotId::new(0), Viewer::new(viewer.pubkey()))],
+        remove_viewers: vec![],
+    };
+    let multisig_op_account = utils::init_viewers_update(
+        &mut context,
+        wallet_account.pubkey(),
+        &approvers[0],
+        update.clone(),
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut context,
+        &multisig_op_account,
+        vec![&approvers[0], &approvers[1]],
+    )
+    .await;
+    utils::finalize_viewers_update(
+        &mut context,
+        wallet_account.pubkey(),
+        multisig_op_account,
+        update,
+    )
+    .await;
+
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &wallet_account.pubkey())
+            .await
+            .viewers,
+        Viewers::from_vec(vec![(SlotId::new(0), Viewer::new(viewer.pubkey()))])
+    );
+
+    // a viewer can prove their access
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[verify_access(
+                &context.program_id,
+                &wallet_account.pubkey(),
+                &viewer.pubkey(),
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &viewer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // but signers are not viewers
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[verify_access(
+                    &context.program_id,
+                    &wallet_account.pubkey(),
+                    &approvers[0].pubkey(),
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, &approvers[0]],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::UnknownViewer as u32)),
+    );
+
+    // and viewers can't initiate anything
+    assert_eq!(
+        utils::init_viewers_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &viewer,
+            ViewersUpdate {
+                add_viewers: vec![],
+                remove_viewers: vec![(SlotId::new(0), Viewer::new(viewer.pubkey()))],
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidApprover as u32)),
+    );
+}
@@ -0,0 +1,165 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+pub use common::utils;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use std::borrow::Borrow;
+use std::time::Duration;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{
+    ApproverDelegationUpdate, InitialWalletConfig, ProgramInstruction,
+};
+use strike_wallet::utils::SlotId;
+
+#[tokio::test]
+async fn test_canary_signature_freezes_wallet() {
+    let mut context = setup_test(40_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+
+    let approvers = vec![Keypair::new(), Keypair::new()];
+    let canary = Keypair::new();
+    let signers = vec![
+        (SlotId::new(0), approvers[0].pubkey_as_signer()),
+        (SlotId::new(1), approvers[1].pubkey_as_signer()),
+    ];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: signers.clone(),
+            config_approvers: signers.clone(),
+        },
+    )
+    .await
+    .unwrap();
+
+    // the canary key can't be a key the wallet uses
+    assert_eq!(
+        utils::init_wallet_canary_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &approvers[0],
+            Some(approvers[1].pubkey()),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidCanaryKey as u32)),
+    );
+
+    let multisig_op_account = utils::init_wallet_canary_update(
+        &mut context,
+        wallet_account.pubkey(),
+        &approvers[0],
+        Some(canary.pubkey()),
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut context,
+        &multisig_op_account,
+        vec![&approvers[0], &approvers[1]],
+    )
+    .await;
+    utils::finalize_wallet_canary_update(
+        &mut context,
+        wallet_account.pubkey(),
+        multisig_op_account,
+        Some(canary.pubkey()),
+    )
+    .await;
+
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(wallet.canary_key, Some(canary.pubkey()));
+    assert!(!wallet.frozen);
+
+    // any instruction signed by the canary key freezes the wallet
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[Instruction {
+                program_id: context.program_id,
+                accounts: with_program_config(
+                    &context.program_id,
+                    vec![
+                        AccountMeta::new(wallet_account.pubkey(), false),
+                        AccountMeta::new_readonly(canary.pubkey(), true),
+                    ],
+                ),
+                data: ProgramInstruction::CheckWalletIntegrity.borrow().pack(),
+            }],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &canary],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    assert!(
+        get_wallet(&mut context.banks_client, &wallet_account.pubkey())
+            .await
+            .frozen
+    );
+
+    // nothing but a canary update goes through while the wallet is frozen
+    assert_eq!(
+        utils::init_approver_delegation_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &approvers[0],
+            ApproverDelegationUpdate {
+                add_delegations: vec![],
+                remove_delegations: vec![],
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::WalletFrozen as u32)),
+    );
+
+    let rotated_canary = Keypair::new();
+    let multisig_op_account = utils::init_wallet_canary_update(
+        &mut context,
+        wallet_account.pubkey(),
+        &approvers[1],
+        Some(rotated_canary.pubkey()),
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut context,
+        &multisig_op_account,
+        vec![&approvers[0], &approvers[1]],
+    )
+    .await;
+    utils::finalize_wallet_canary_update(
+        &mut context,
+        wallet_account.pubkey(),
+        multisig_op_account,
+        Some(rotated_canary.pubkey()),
+    )
+    .await;
+
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(wallet.canary_key, Some(rotated_canary.pubkey()));
+    assert!(!wallet.frozen);
+}
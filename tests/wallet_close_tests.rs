@@ -0,0 +1,155 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+pub use common::utils;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::Transaction;
+use solana_sdk::transport;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::BalanceAccountPolicyUpdate;
+use strike_wallet::model::token_mint::TokenMint;
+use strike_wallet::utils::SlotId;
+
+async fn allow_token_mint(context: &mut BalanceAccountTestContext, mint: &Pubkey) {
+    update_balance_account_policy(
+        context,
+        BalanceAccountPolicyUpdate {
+            approvals_required_for_transfer: None,
+            approval_timeout_for_transfer: None,
+            add_transfer_approvers: None,
+            remove_transfer_approvers: None,
+            use_config_approvers: None,
+            token_mint_allowlist_enabled: None,
+            add_allowed_token_mints: Some(vec![(SlotId::new(0), TokenMint { address: *mint })]),
+            remove_allowed_token_mints: None,
+            max_outstanding_transfer_value: None,
+            max_finalize_fee_reimbursement: None,
+            policy_rules: None,
+            large_transfer_cooldown: None,
+        },
+        None,
+    )
+    .await
+    .unwrap();
+}
+
+async fn approve_wallet_close(context: &mut BalanceAccountTestContext) -> Pubkey {
+    let mut test_context = context.to_test_context();
+    let multisig_op_account = Keypair::new();
+    let multisig_op = multisig_op_account.pubkey();
+    utils::init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init_wallet_close(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &multisig_op,
+            &context.assistant_account.pubkey(),
+            &context.payer.pubkey(),
+        ),
+        &context.assistant_account,
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    multisig_op
+}
+
+async fn finalize_close(
+    context: &mut BalanceAccountTestContext,
+    multisig_op: &Pubkey,
+    closed_accounts: &[Pubkey],
+) -> transport::Result<()> {
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_wallet_close(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                multisig_op,
+                &context.payer.pubkey(),
+                &context.payer.pubkey(),
+                closed_accounts,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+}
+
+#[tokio::test]
+async fn test_wallet_close_requires_token_accounts_of_tracked_mints() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(200_000)).await;
+    let spl_context = setup_spl_transfer_test(&mut context, &balance_account, false).await;
+    allow_token_mint(&mut context, &spl_context.mint.pubkey()).await;
+    let multisig_op = approve_wallet_close(&mut context).await;
+
+    // leaving out the funded token account would strand its tokens
+    assert_instruction_error(
+        finalize_close(&mut context, &multisig_op, &[balance_account]).await,
+        0,
+        Custom(WalletError::AccountNotRecognized as u32),
+    );
+
+    assert_instruction_error(
+        finalize_close(
+            &mut context,
+            &multisig_op,
+            &[balance_account, spl_context.source_token_address],
+        )
+        .await,
+        0,
+        Custom(WalletError::BalanceAccountNotEmpty as u32),
+    );
+    get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+}
+
+#[tokio::test]
+async fn test_wallet_close_accepts_uncreated_token_accounts() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(200_000)).await;
+    // the tokens go to another owner, so the balance account's token account
+    // is never created
+    let spl_context = setup_spl_transfer_test(&mut context, &Pubkey::new_unique(), false).await;
+    allow_token_mint(&mut context, &spl_context.mint.pubkey()).await;
+    let multisig_op = approve_wallet_close(&mut context).await;
+    let token_account = spl_associated_token_account::get_associated_token_address(
+        &balance_account,
+        &spl_context.mint.pubkey(),
+    );
+
+    assert_instruction_error(
+        finalize_close(&mut context, &multisig_op, &[balance_account]).await,
+        0,
+        Custom(WalletError::AccountNotRecognized as u32),
+    );
+
+    finalize_close(
+        &mut context,
+        &multisig_op,
+        &[balance_account, token_account],
+    )
+    .await
+    .unwrap();
+    assert!(context
+        .banks_client
+        .get_account(context.wallet_account.pubkey())
+        .await
+        .unwrap()
+        .is_none());
+}
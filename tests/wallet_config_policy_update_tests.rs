@@ -12,9 +12,13 @@ use solana_sdk::signature::Keypair;
 use solana_sdk::signer::Signer as SdkSigner;
 use std::time::{Duration, SystemTime};
 use strike_wallet::error::WalletError;
-use strike_wallet::instruction::{InitialWalletConfig, WalletConfigPolicyUpdate};
+use strike_wallet::instruction::{DAppBookUpdate, InitialWalletConfig, WalletConfigPolicyUpdate};
+use strike_wallet::model::address_book::{
+    DAppBookEntry, DAppBookEntryNameHash, DAppInstructionPrefixes,
+};
 use strike_wallet::model::multisig_op::{
-    ApprovalDisposition, ApprovalDispositionRecord, MultisigOpParams, OperationDisposition,
+    ApprovalDisposition, ApprovalDispositionRecord, DenialReason, InitiatorClass, MultisigOpParams,
+    OperationDisposition,
 };
 use strike_wallet::model::wallet::Approvers;
 use strike_wallet::utils::SlotId;
@@ -63,6 +67,20 @@ async fn wallet_config_policy_update() {
         approval_timeout_for_config: Some(Duration::from_secs(7200)),
         add_config_approvers: vec![(SlotId::new(2), signers[2])],
         remove_config_approvers: vec![(SlotId::new(0), signers[0])],
+        replace_config_approvers: None,
+        destination_verification_delay: None,
+        denial_reason_threshold: None,
+        approvals_required_for_assistant_config: None,
+        approvals_required_for_address_book: None,
+        add_address_book_approvers: vec![],
+        remove_address_book_approvers: vec![],
+        add_default_transfer_approvers: vec![],
+        remove_default_transfer_approvers: vec![],
+        add_veto_approvers: vec![],
+        remove_veto_approvers: vec![],
+        approvals_required_for_destructive_config: None,
+        add_destructive_config_approvers: vec![],
+        remove_destructive_config_approvers: vec![],
     };
 
     let multisig_op_account = utils::init_wallet_config_policy_update(
@@ -83,10 +101,16 @@ async fn wallet_config_policy_update() {
             ApprovalDispositionRecord {
                 approver: approvers[0].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                denial_reason: DenialReason::None,
+                reason_hash: None,
+                disposition_changes: 0,
             },
             ApprovalDispositionRecord {
                 approver: approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                denial_reason: DenialReason::None,
+                reason_hash: None,
+                disposition_changes: 0,
             },
         ],
         OperationDisposition::NONE,
@@ -140,6 +164,20 @@ async fn wallet_config_policy_update() {
             approval_timeout_for_config: None,
             add_config_approvers: vec![],
             remove_config_approvers: vec![],
+            replace_config_approvers: None,
+            destination_verification_delay: None,
+            denial_reason_threshold: None,
+            approvals_required_for_assistant_config: None,
+            approvals_required_for_address_book: None,
+            add_address_book_approvers: vec![],
+            remove_address_book_approvers: vec![],
+            add_default_transfer_approvers: vec![],
+            remove_default_transfer_approvers: vec![],
+            add_veto_approvers: vec![],
+            remove_veto_approvers: vec![],
+            approvals_required_for_destructive_config: None,
+            add_destructive_config_approvers: vec![],
+            remove_destructive_config_approvers: vec![],
         },
         vec![&approvers[1], &approvers[2]],
     )
@@ -161,6 +199,20 @@ async fn wallet_config_policy_update() {
             approval_timeout_for_config: Some(Duration::from_secs(3600)),
             add_config_approvers: vec![],
             remove_config_approvers: vec![],
+            replace_config_approvers: None,
+            destination_verification_delay: None,
+            denial_reason_threshold: None,
+            approvals_required_for_assistant_config: None,
+            approvals_required_for_address_book: None,
+            add_address_book_approvers: vec![],
+            remove_address_book_approvers: vec![],
+            add_default_transfer_approvers: vec![],
+            remove_default_transfer_approvers: vec![],
+            add_veto_approvers: vec![],
+            remove_veto_approvers: vec![],
+            approvals_required_for_destructive_config: None,
+            add_destructive_config_approvers: vec![],
+            remove_destructive_config_approvers: vec![],
         },
         vec![&approvers[1], &approvers[2]],
     )
@@ -212,6 +264,20 @@ async fn only_one_pending_wallet_config_policy_update_allowed_at_time() {
         approval_timeout_for_config: Some(Duration::from_secs(7200)),
         add_config_approvers: vec![(SlotId::new(2), signers[2])],
         remove_config_approvers: vec![(SlotId::new(0), signers[0])],
+        replace_config_approvers: None,
+        destination_verification_delay: None,
+        denial_reason_threshold: None,
+        approvals_required_for_assistant_config: None,
+        approvals_required_for_address_book: None,
+        add_address_book_approvers: vec![],
+        remove_address_book_approvers: vec![],
+        add_default_transfer_approvers: vec![],
+        remove_default_transfer_approvers: vec![],
+        add_veto_approvers: vec![],
+        remove_veto_approvers: vec![],
+        approvals_required_for_destructive_config: None,
+        add_destructive_config_approvers: vec![],
+        remove_destructive_config_approvers: vec![],
     };
 
     let second_update = WalletConfigPolicyUpdate {
@@ -219,6 +285,20 @@ async fn only_one_pending_wallet_config_policy_update_allowed_at_time() {
         approval_timeout_for_config: Some(Duration::from_secs(7200)),
         add_config_approvers: vec![(SlotId::new(0), signers[0])],
         remove_config_approvers: vec![],
+        replace_config_approvers: None,
+        destination_verification_delay: None,
+        denial_reason_threshold: None,
+        approvals_required_for_assistant_config: None,
+        approvals_required_for_address_book: None,
+        add_address_book_approvers: vec![],
+        remove_address_book_approvers: vec![],
+        add_default_transfer_approvers: vec![],
+        remove_default_transfer_approvers: vec![],
+        add_veto_approvers: vec![],
+        remove_veto_approvers: vec![],
+        approvals_required_for_destructive_config: None,
+        add_destructive_config_approvers: vec![],
+        remove_destructive_config_approvers: vec![],
     };
 
     let multisig_op_account = utils::init_wallet_config_policy_update(
@@ -349,6 +429,20 @@ async fn invalid_wallet_config_policy_updates() {
                 approval_timeout_for_config: Some(Duration::from_secs(3200)),
                 add_config_approvers: vec![],
                 remove_config_approvers: vec![],
+                replace_config_approvers: None,
+                destination_verification_delay: None,
+                denial_reason_threshold: None,
+                approvals_required_for_assistant_config: None,
+                approvals_required_for_address_book: None,
+                add_address_book_approvers: vec![],
+                remove_address_book_approvers: vec![],
+                add_default_transfer_approvers: vec![],
+                remove_default_transfer_approvers: vec![],
+                add_veto_approvers: vec![],
+                remove_veto_approvers: vec![],
+                approvals_required_for_destructive_config: None,
+                add_destructive_config_approvers: vec![],
+                remove_destructive_config_approvers: vec![],
             },
         )
         .await,
@@ -367,6 +461,20 @@ async fn invalid_wallet_config_policy_updates() {
                 approval_timeout_for_config: Some(Duration::from_secs(3200)),
                 add_config_approvers: vec![(SlotId::new(2), signers[2])],
                 remove_config_approvers: vec![],
+                replace_config_approvers: None,
+                destination_verification_delay: None,
+                denial_reason_threshold: None,
+                approvals_required_for_assistant_config: None,
+                approvals_required_for_address_book: None,
+                add_address_book_approvers: vec![],
+                remove_address_book_approvers: vec![],
+                add_default_transfer_approvers: vec![],
+                remove_default_transfer_approvers: vec![],
+                add_veto_approvers: vec![],
+                remove_veto_approvers: vec![],
+                approvals_required_for_destructive_config: None,
+                add_destructive_config_approvers: vec![],
+                remove_destructive_config_approvers: vec![],
             },
         )
         .await,
@@ -385,6 +493,20 @@ async fn invalid_wallet_config_policy_updates() {
                 approval_timeout_for_config: Some(Duration::from_secs(3200)),
                 add_config_approvers: vec![(SlotId::new(0), signers[2])],
                 remove_config_approvers: vec![],
+                replace_config_approvers: None,
+                destination_verification_delay: None,
+                denial_reason_threshold: None,
+                approvals_required_for_assistant_config: None,
+                approvals_required_for_address_book: None,
+                add_address_book_approvers: vec![],
+                remove_address_book_approvers: vec![],
+                add_default_transfer_approvers: vec![],
+                remove_default_transfer_approvers: vec![],
+                add_veto_approvers: vec![],
+                remove_veto_approvers: vec![],
+                approvals_required_for_destructive_config: None,
+                add_destructive_config_approvers: vec![],
+                remove_destructive_config_approvers: vec![],
             },
         )
         .await,
@@ -402,6 +524,20 @@ async fn invalid_wallet_config_policy_updates() {
                 approval_timeout_for_config: Some(Duration::from_secs(3200)),
                 add_config_approvers: vec![],
                 remove_config_approvers: vec![(SlotId::new(0), signers[2])],
+                replace_config_approvers: None,
+                destination_verification_delay: None,
+                denial_reason_threshold: None,
+                approvals_required_for_assistant_config: None,
+                approvals_required_for_address_book: None,
+                add_address_book_approvers: vec![],
+                remove_address_book_approvers: vec![],
+                add_default_transfer_approvers: vec![],
+                remove_default_transfer_approvers: vec![],
+                add_veto_approvers: vec![],
+                remove_veto_approvers: vec![],
+                approvals_required_for_destructive_config: None,
+                add_destructive_config_approvers: vec![],
+                remove_destructive_config_approvers: vec![],
             },
         )
         .await,
@@ -409,3 +545,398 @@ async fn invalid_wallet_config_policy_updates() {
         Custom(WalletError::InvalidSlot as u32),
     );
 }
+
+#[tokio::test]
+async fn wallet_config_policy_update_replace_config_approvers() {
+    let mut context = setup_test(30_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+
+    let approvers = vec![Keypair::new(), Keypair::new(), Keypair::new()];
+    let signers = vec![
+        approvers[0].pubkey_as_signer(),
+        approvers[1].pubkey_as_signer(),
+        approvers[2].pubkey_as_signer(),
+    ];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![
+                (SlotId::new(0), signers[0]),
+                (SlotId::new(1), signers[1]),
+                (SlotId::new(2), signers[2]),
+            ],
+            config_approvers: vec![(SlotId::new(0), signers[0]), (SlotId::new(1), signers[1])],
+        },
+    )
+    .await
+    .unwrap();
+
+    // verify replacing can't be combined with incremental changes
+    assert_instruction_error(
+        utils::init_wallet_config_policy_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &assistant_account,
+            &WalletConfigPolicyUpdate {
+                approvals_required_for_config: Some(1),
+                approval_timeout_for_config: None,
+                add_config_approvers: vec![(SlotId::new(2), signers[2])],
+                remove_config_approvers: vec![],
+                replace_config_approvers: Some(vec![(SlotId::new(2), signers[2])]),
+                destination_verification_delay: None,
+                denial_reason_threshold: None,
+                approvals_required_for_assistant_config: None,
+                approvals_required_for_address_book: None,
+                add_address_book_approvers: vec![],
+                remove_address_book_approvers: vec![],
+                add_default_transfer_approvers: vec![],
+                remove_default_transfer_approvers: vec![],
+                add_veto_approvers: vec![],
+                remove_veto_approvers: vec![],
+                approvals_required_for_destructive_config: None,
+                add_destructive_config_approvers: vec![],
+                remove_destructive_config_approvers: vec![],
+            },
+        )
+        .await,
+        1,
+        Custom(WalletError::ConflictingApproverUpdates as u32),
+    );
+
+    // verify replacing requires the new threshold
+    assert_instruction_error(
+        utils::init_wallet_config_policy_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &assistant_account,
+            &WalletConfigPolicyUpdate {
+                approvals_required_for_config: None,
+                approval_timeout_for_config: None,
+                add_config_approvers: vec![],
+                remove_config_approvers: vec![],
+                replace_config_approvers: Some(vec![(SlotId::new(2), signers[2])]),
+                destination_verification_delay: None,
+                denial_reason_threshold: None,
+                approvals_required_for_assistant_config: None,
+                approvals_required_for_address_book: None,
+                add_address_book_approvers: vec![],
+                remove_address_book_approvers: vec![],
+                add_default_transfer_approvers: vec![],
+                remove_default_transfer_approvers: vec![],
+                add_veto_approvers: vec![],
+                remove_veto_approvers: vec![],
+                approvals_required_for_destructive_config: None,
+                add_destructive_config_approvers: vec![],
+                remove_destructive_config_approvers: vec![],
+            },
+        )
+        .await,
+        1,
+        Custom(WalletError::InvalidApproverCount as u32),
+    );
+
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+
+    utils::update_wallet_config_policy(
+        &mut context,
+        wallet_account.pubkey(),
+        &assistant_account,
+        &WalletConfigPolicyUpdate {
+            approvals_required_for_config: Some(1),
+            approval_timeout_for_config: None,
+            add_config_approvers: vec![],
+            remove_config_approvers: vec![],
+            replace_config_approvers: Some(vec![(SlotId::new(2), signers[2])]),
+            destination_verification_delay: None,
+            denial_reason_threshold: None,
+            approvals_required_for_assistant_config: None,
+            approvals_required_for_address_book: None,
+            add_address_book_approvers: vec![],
+            remove_address_book_approvers: vec![],
+            add_default_transfer_approvers: vec![],
+            remove_default_transfer_approvers: vec![],
+            add_veto_approvers: vec![],
+            remove_veto_approvers: vec![],
+            approvals_required_for_destructive_config: None,
+            add_destructive_config_approvers: vec![],
+            remove_destructive_config_approvers: vec![],
+        },
+        vec![&approvers[0], &approvers[1]],
+    )
+    .await;
+
+    let mut expected_wallet = wallet.clone();
+    expected_wallet.approvals_required_for_config = 1;
+    expected_wallet.config_approvers = Approvers::from_enabled_vec(vec![SlotId::new(2)]);
+
+    assert_eq!(
+        expected_wallet,
+        get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await
+    );
+}
+
+#[tokio::test]
+async fn assistant_initiated_config_ops_use_assistant_quorum() {
+    let mut context = setup_test(30_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+
+    let approvers = vec![Keypair::new(), Keypair::new(), Keypair::new()];
+    let signers = vec![
+        approvers[0].pubkey_as_signer(),
+        approvers[1].pubkey_as_signer(),
+        approvers[2].pubkey_as_signer(),
+    ];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 1,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![
+                (SlotId::new(0), signers[0]),
+                (SlotId::new(1), signers[1]),
+                (SlotId::new(2), signers[2]),
+            ],
+            config_approvers: vec![
+                (SlotId::new(0), signers[0]),
+                (SlotId::new(1), signers[1]),
+                (SlotId::new(2), signers[2]),
+            ],
+        },
+    )
+    .await
+    .unwrap();
+
+    let assistant_quorum_update =
+        |approvals_required_for_assistant_config| WalletConfigPolicyUpdate {
+            approvals_required_for_config: None,
+            approval_timeout_for_config: None,
+            add_config_approvers: vec![],
+            remove_config_approvers: vec![],
+            replace_config_approvers: None,
+            destination_verification_delay: None,
+            denial_reason_threshold: None,
+            approvals_required_for_assistant_config: Some(approvals_required_for_assistant_config),
+            approvals_required_for_address_book: None,
+            add_address_book_approvers: vec![],
+            remove_address_book_approvers: vec![],
+            add_default_transfer_approvers: vec![],
+            remove_default_transfer_approvers: vec![],
+            add_veto_approvers: vec![],
+            remove_veto_approvers: vec![],
+            approvals_required_for_destructive_config: None,
+            add_destructive_config_approvers: vec![],
+            remove_destructive_config_approvers: vec![],
+        };
+
+    // the assistant quorum can't exceed the number of config approvers
+    assert_instruction_error(
+        utils::init_wallet_config_policy_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &approvers[0],
+            &assistant_quorum_update(4),
+        )
+        .await,
+        1,
+        Custom(WalletError::InvalidApproverCount as u32),
+    );
+
+    utils::update_wallet_config_policy(
+        &mut context,
+        wallet_account.pubkey(),
+        &approvers[0],
+        &assistant_quorum_update(2),
+        vec![&approvers[0]],
+    )
+    .await;
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &wallet_account.pubkey())
+            .await
+            .approvals_required_for_assistant_config,
+        2
+    );
+
+    let multisig_op_account = utils::init_wallet_config_policy_update(
+        &mut context,
+        wallet_account.pubkey(),
+        &assistant_account,
+        &assistant_quorum_update(3),
+    )
+    .await
+    .unwrap();
+    let multisig_op = get_multisig_op_data(&mut context.banks_client, multisig_op_account).await;
+    assert_eq!(multisig_op.dispositions_required, 2);
+    assert_eq!(multisig_op.initiator_class, InitiatorClass::Assistant);
+    assert_eq!(multisig_op.initiator, assistant_account.pubkey());
+    assert_eq!(multisig_op.op_type, 6);
+}
+
+#[tokio::test]
+async fn address_book_ops_use_address_book_approvers() {
+    let mut context = setup_test(30_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+
+    let approvers = vec![Keypair::new(), Keypair::new(), Keypair::new()];
+    let signers = vec![
+        approvers[0].pubkey_as_signer(),
+        approvers[1].pubkey_as_signer(),
+        approvers[2].pubkey_as_signer(),
+    ];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![
+                (SlotId::new(0), signers[0]),
+                (SlotId::new(1), signers[1]),
+                (SlotId::new(2), signers[2]),
+            ],
+            config_approvers: vec![(SlotId::new(0), signers[0]), (SlotId::new(1), signers[1])],
+        },
+    )
+    .await
+    .unwrap();
+
+    let approvers_update = |approvals_required_for_address_book| WalletConfigPolicyUpdate {
+        approvals_required_for_config: None,
+        approval_timeout_for_config: None,
+        add_config_approvers: vec![],
+        remove_config_approvers: vec![],
+        replace_config_approvers: None,
+        destination_verification_delay: None,
+        denial_reason_threshold: None,
+        approvals_required_for_assistant_config: None,
+        approvals_required_for_address_book,
+        add_address_book_approvers: vec![(SlotId::new(2), signers[2])],
+        remove_address_book_approvers: vec![],
+        add_default_transfer_approvers: vec![(SlotId::new(1), signers[1])],
+        remove_default_transfer_approvers: vec![],
+        add_veto_approvers: vec![],
+        remove_veto_approvers: vec![],
+        approvals_required_for_destructive_config: None,
+        add_destructive_config_approvers: vec![],
+        remove_destructive_config_approvers: vec![],
+    };
+
+    // dedicated address book approvers need their own quorum
+    assert_instruction_error(
+        utils::init_wallet_config_policy_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &approvers[0],
+            &approvers_update(None),
+        )
+        .await,
+        1,
+        Custom(WalletError::InvalidApproverCount as u32),
+    );
+
+    utils::update_wallet_config_policy(
+        &mut context,
+        wallet_account.pubkey(),
+        &approvers[0],
+        &approvers_update(Some(1)),
+        vec![&approvers[0], &approvers[1]],
+    )
+    .await;
+
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(
+        wallet.address_book_approvers,
+        Approvers::from_enabled_vec(vec![SlotId::new(2)])
+    );
+    assert_eq!(wallet.approvals_required_for_address_book, 1);
+    assert_eq!(
+        wallet.default_transfer_approvers,
+        Approvers::from_enabled_vec(vec![SlotId::new(1)])
+    );
+
+    // dapp book updates are seeded with the address book approvers
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    init_multisig_op(
+        &mut context,
+        multisig_op_account,
+        init_dapp_book_update(
+            &context.program_id,
+            &wallet_account.pubkey(),
+            &multisig_op_pubkey,
+            &approvers[0].pubkey(),
+            DAppBookUpdate {
+                add_dapps: vec![(
+                    SlotId::new(0),
+                    DAppBookEntry {
+                        address: Keypair::new().pubkey(),
+                        name_hash: DAppBookEntryNameHash::zero(),
+                        instruction_prefixes: DAppInstructionPrefixes::default(),
+                    },
+                )],
+                remove_dapps: vec![],
+                add_value_limits: vec![],
+                remove_value_limits: vec![],
+                add_inner_programs: vec![],
+                remove_inner_programs: vec![],
+                add_approval_policies: vec![],
+                remove_approval_policies: vec![],
+            },
+        ),
+        &approvers[0],
+    )
+    .await
+    .unwrap();
+    let multisig_op = get_multisig_op_data(&mut context.banks_client, multisig_op_pubkey).await;
+    assert_eq!(multisig_op.dispositions_required, 1);
+    assert_eq!(
+        multisig_op
+            .disposition_records
+            .iter()
+            .map(|record| record.approver)
+            .collect::<Vec<_>>(),
+        vec![approvers[2].pubkey()]
+    );
+
+    // while config ops keep using the config approvers
+    let multisig_op_account = utils::init_wallet_config_policy_update(
+        &mut context,
+        wallet_account.pubkey(),
+        &approvers[0],
+        &approvers_update(Some(1)),
+    )
+    .await
+    .unwrap();
+    let multisig_op = get_multisig_op_data(&mut context.banks_client, multisig_op_account).await;
+    assert_eq!(multisig_op.dispositions_required, 2);
+
+    // address book approvers can't be removed as signers
+    assert_eq!(
+        wallet.validate_remove_signer((SlotId::new(2), signers[2])),
+        Err(WalletError::SignerIsConfigApprover.into())
+    );
+}
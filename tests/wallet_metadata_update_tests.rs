@@ -0,0 +1,117 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+pub use common::utils;
+use solana_program::hash::{hash, Hash};
+use solana_program::instruction::InstructionError::Custom;
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::Transaction;
+use std::time::Duration;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::InitialWalletConfig;
+use strike_wallet::utils::SlotId;
+
+#[tokio::test]
+async fn test_wallet_metadata_update() {
+    let mut context = setup_test(60_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+
+    let approvers = vec![Keypair::new(), Keypair::new()];
+    let signers = vec![
+        (SlotId::new(0), approvers[0].pubkey_as_signer()),
+        (SlotId::new(1), approvers[1].pubkey_as_signer()),
+    ];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: signers.clone(),
+            config_approvers: signers,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &wallet_account.pubkey())
+            .await
+            .metadata_hash,
+        Hash::default()
+    );
+
+    let metadata_hash = hash(b"policy document v1");
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    utils::init_multisig_op(
+        &mut context,
+        multisig_op_account,
+        init_wallet_metadata_update(
+            &context.program_id,
+            &wallet_account.pubkey(),
+            &multisig_op_pubkey,
+            &assistant_account.pubkey(),
+            metadata_hash,
+        ),
+        &assistant_account,
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut context,
+        &multisig_op_pubkey,
+        vec![&approvers[0], &approvers[1]],
+    )
+    .await;
+
+    // finalizing with a different hash than the one approved fails
+    let instruction = finalize_wallet_metadata_update(
+        &context.program_id,
+        &wallet_account.pubkey(),
+        &multisig_op_pubkey,
+        &context.payer.pubkey(),
+        hash(b"policy document v2"),
+    );
+    assert_instruction_error(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&context.payer.pubkey()),
+                &[&context.payer],
+                context.recent_blockhash,
+            ))
+            .await,
+        0,
+        Custom(WalletError::InvalidSignature as u32),
+    );
+
+    let instruction = finalize_wallet_metadata_update(
+        &context.program_id,
+        &wallet_account.pubkey(),
+        &multisig_op_pubkey,
+        &context.payer.pubkey(),
+        metadata_hash,
+    );
+    utils::finalize_multisig_op(&mut context, multisig_op_pubkey, instruction).await;
+
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &wallet_account.pubkey())
+            .await
+            .metadata_hash,
+        metadata_hash
+    );
+}
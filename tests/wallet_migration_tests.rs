@@ -0,0 +1,249 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+pub use common::{instructions, utils};
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::program_pack::Pack;
+use solana_program::system_instruction;
+use solana_program_test::{processor, tokio, ProgramTest};
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::Transaction;
+use std::time::Duration;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::InitialWalletConfig;
+use strike_wallet::model::program_config::ProgramConfig;
+use strike_wallet::model::wallet::Wallet;
+use strike_wallet::model::wallet_export::WalletExport;
+use strike_wallet::processor::Processor;
+use strike_wallet::utils::SlotId;
+
+/// Deploys the program twice, each with a program config that allows
+/// migrating to and from the other. Returns a context for the first one
+/// along with the second one's program id.
+async fn setup_migration_test() -> (TestContext, Pubkey) {
+    let source_program_id = Keypair::new().pubkey();
+    let target_program_id = Keypair::new().pubkey();
+    let mut pt = ProgramTest::new(
+        "strike_wallet",
+        source_program_id,
+        processor!(Processor::process),
+    );
+    pt.add_program(
+        "strike_wallet",
+        target_program_id,
+        processor!(Processor::process),
+    );
+    for (program_id, migration_program) in vec![
+        (source_program_id, target_program_id),
+        (target_program_id, source_program_id),
+    ] {
+        let (program_config_address, bump_seed) = ProgramConfig::find_address(&program_id);
+        let mut data = vec![0; ProgramConfig::LEN];
+        ProgramConfig::pack(
+            ProgramConfig {
+                migration_programs: vec![migration_program],
+                ..ProgramConfig::new(&Pubkey::new_unique(), bump_seed)
+            },
+            &mut data,
+        )
+        .unwrap();
+        pt.add_account(
+            program_config_address,
+            Account {
+                lamports: 1_000_000_000,
+                data,
+                owner: program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+    pt.set_bpf_compute_max_units(200_000);
+    let (mut banks_client, payer, recent_blockhash) = pt.start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+
+    (
+        TestContext {
+            program_id: source_program_id,
+            banks_client,
+            rent,
+            payer,
+            recent_blockhash,
+        },
+        target_program_id,
+    )
+}
+
+#[tokio::test]
+async fn test_wallet_migration() {
+    let (mut context, target_program_id) = setup_migration_test().await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let approvers = vec![Keypair::new(), Keypair::new()];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 1,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![
+                (SlotId::new(0), approvers[0].pubkey_as_signer()),
+                (SlotId::new(1), approvers[1].pubkey_as_signer()),
+            ],
+            config_approvers: vec![
+                (SlotId::new(0), approvers[0].pubkey_as_signer()),
+                (SlotId::new(1), approvers[1].pubkey_as_signer()),
+            ],
+        },
+    )
+    .await
+    .unwrap();
+
+    // only allowlisted programs can be exported to
+    assert_instruction_error(
+        utils::init_multisig_op(
+            &mut context,
+            Keypair::new(),
+            init_wallet_state_export(
+                &context.program_id,
+                &wallet_account.pubkey(),
+                &Keypair::new().pubkey(),
+                &approvers[0].pubkey(),
+                &Pubkey::new_unique(),
+            ),
+            &approvers[0],
+        )
+        .await,
+        1,
+        Custom(WalletError::MigrationProgramNotAllowed as u32),
+    );
+
+    // exporting takes every config approver, not just the config quorum
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    utils::init_multisig_op(
+        &mut context,
+        multisig_op_account,
+        init_wallet_state_export(
+            &context.program_id,
+            &wallet_account.pubkey(),
+            &multisig_op_pubkey,
+            &approvers[0].pubkey(),
+            &target_program_id,
+        ),
+        &approvers[0],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        get_multisig_op_data(&mut context.banks_client, multisig_op_pubkey)
+            .await
+            .dispositions_required,
+        2
+    );
+    approve_n_of_n_multisig_op(
+        &mut context,
+        &multisig_op_pubkey,
+        vec![&approvers[0], &approvers[1]],
+    )
+    .await;
+
+    let export_account = Keypair::new();
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[create_program_owned_account_instruction(
+                &context,
+                &export_account.pubkey(),
+                WalletExport::LEN,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &export_account],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+    let instruction = finalize_wallet_state_export(
+        &context.program_id,
+        &wallet_account.pubkey(),
+        &multisig_op_pubkey,
+        &context.payer.pubkey(),
+        &export_account.pubkey(),
+        &target_program_id,
+    );
+    utils::finalize_multisig_op(&mut context, multisig_op_pubkey, instruction).await;
+
+    let imported_wallet_account = Keypair::new();
+    let imported_wallet_address = imported_wallet_account.pubkey();
+    let payer_address = context.payer.pubkey();
+    let wallet_rent = context.rent.minimum_balance(Wallet::LEN);
+    let create_imported_wallet_account = |program_id: &Pubkey| {
+        system_instruction::create_account(
+            &payer_address,
+            &imported_wallet_address,
+            wallet_rent,
+            Wallet::LEN as u64,
+            program_id,
+        )
+    };
+
+    // the export can't be imported anywhere but into the target program
+    assert_instruction_error(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[
+                    create_imported_wallet_account(&context.program_id),
+                    import_wallet_state(
+                        &context.program_id,
+                        &imported_wallet_account.pubkey(),
+                        &export_account.pubkey(),
+                        &assistant_account.pubkey(),
+                    ),
+                ],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, &imported_wallet_account, &assistant_account],
+                context.recent_blockhash,
+            ))
+            .await,
+        1,
+        Custom(WalletError::MigrationProgramNotAllowed as u32),
+    );
+
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                create_imported_wallet_account(&target_program_id),
+                import_wallet_state(
+                    &target_program_id,
+                    &imported_wallet_account.pubkey(),
+                    &export_account.pubkey(),
+                    &assistant_account.pubkey(),
+                ),
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &imported_wallet_account, &assistant_account],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &imported_wallet_account.pubkey()).await,
+        get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await
+    );
+}
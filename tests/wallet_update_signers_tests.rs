@@ -13,13 +13,14 @@ use solana_program::instruction::InstructionError::Custom;
 use crate::common::utils;
 use common::instructions::init_update_signer;
 use strike_wallet::error::WalletError;
-use strike_wallet::instruction::InitialWalletConfig;
+use strike_wallet::instruction::{InitialWalletConfig, SignersUpdate};
 use strike_wallet::model::multisig_op::{ApprovalDisposition, SlotUpdateType};
-use strike_wallet::model::wallet::Signers;
+use strike_wallet::model::wallet::{Signers, Wallet};
 use strike_wallet::utils::SlotId;
 use {
     solana_program_test::tokio,
     solana_sdk::signature::{Keypair, Signer as SdkSigner},
+    solana_sdk::transaction::{Transaction, TransactionError},
 };
 
 #[tokio::test]
@@ -167,3 +168,131 @@ async fn test_remove_signer_fails_for_a_transfer_approver() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn test_update_signers() {
+    let mut context = setup_test(100_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+
+    let approvers = vec![Keypair::new(), Keypair::new()];
+    let config_approvers = vec![
+        (SlotId::new(0), approvers[0].pubkey_as_signer()),
+        (SlotId::new(1), approvers[1].pubkey_as_signer()),
+    ];
+    let old_signer = (SlotId::new(2), Keypair::new().pubkey_as_signer());
+    let mut signers = config_approvers.clone();
+    signers.push(old_signer);
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers,
+            config_approvers: config_approvers.clone(),
+        },
+    )
+    .await
+    .unwrap();
+
+    // the whole update fails if any part of it does
+    assert_eq!(
+        utils::init_update_signers(
+            &mut context,
+            wallet_account.pubkey(),
+            &approvers[0],
+            SignersUpdate {
+                add_signers: vec![(SlotId::new(3), Keypair::new().pubkey_as_signer())],
+                remove_signers: vec![config_approvers[1]],
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::SignerIsConfigApprover as u32)),
+    );
+    let new_signer = Keypair::new().pubkey_as_signer();
+    assert_eq!(
+        utils::init_update_signers(
+            &mut context,
+            wallet_account.pubkey(),
+            &approvers[0],
+            SignersUpdate {
+                add_signers: vec![(SlotId::new(3), new_signer), (SlotId::new(4), new_signer)],
+                remove_signers: vec![],
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::SlotCannotBeInserted as u32)),
+    );
+
+    // a slot can be emptied and refilled in the same update
+    let new_signers = (0..Wallet::MAX_SIGNERS / 2)
+        .map(|i| (SlotId::new(i + 2), Keypair::new().pubkey_as_signer()))
+        .collect::<Vec<_>>();
+    let update = SignersUpdate {
+        add_signers: new_signers.clone(),
+        remove_signers: vec![old_signer],
+    };
+    let multisig_op_account = utils::init_update_signers(
+        &mut context,
+        wallet_account.pubkey(),
+        &approvers[0],
+        update.clone(),
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut context,
+        &multisig_op_account,
+        vec![&approvers[0], &approvers[1]],
+    )
+    .await;
+    utils::finalize_update_signers(
+        &mut context,
+        wallet_account.pubkey(),
+        multisig_op_account,
+        update.clone(),
+    )
+    .await;
+
+    // a retried finalize can tell that the first one went through
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[common::instructions::finalize_update_signers(
+                    &context.program_id,
+                    &wallet_account.pubkey(),
+                    &multisig_op_account,
+                    &approvers[0].pubkey(),
+                    update,
+                )],
+                Some(&context.payer.pubkey()),
+                &[&context.payer, &approvers[0]],
+                context.recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::AlreadyFinalized as u32)),
+    );
+
+    let mut expected_signers = config_approvers.clone();
+    expected_signers.extend(new_signers);
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &wallet_account.pubkey())
+            .await
+            .signers,
+        Signers::from_vec(expected_signers)
+    );
+}